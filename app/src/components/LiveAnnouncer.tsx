@@ -0,0 +1,41 @@
+import { listen } from '@tauri-apps/api/event';
+import { useEffect, useState } from 'react';
+import { type Announcement, isTauri } from '@/lib/tauri';
+
+/**
+ * Visually hidden live regions that read out the app's announcements: server and
+ * recording state and finished jobs
+ */
+export function LiveAnnouncer() {
+  const [polite, setPolite] = useState<Announcement | null>(null);
+  const [assertive, setAssertive] = useState<Announcement | null>(null);
+
+  useEffect(() => {
+    if (!isTauri()) {
+      return;
+    }
+    const unlisten = listen<Announcement>('announcement', (event) => {
+      const announcement = event.payload;
+      if (announcement.politeness === 'assertive') {
+        setAssertive(announcement);
+      } else {
+        setPolite(announcement);
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  // Keyed by sequence so the same message announced twice is read twice
+  return (
+    <>
+      <div className="sr-only" role="status" aria-live="polite" aria-atomic="true">
+        {polite && <span key={polite.sequence}>{polite.message}</span>}
+      </div>
+      <div className="sr-only" role="alert" aria-live="assertive" aria-atomic="true">
+        {assertive && <span key={assertive.sequence}>{assertive.message}</span>}
+      </div>
+    </>
+  );
+}
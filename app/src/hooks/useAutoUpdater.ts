@@ -1,6 +1,7 @@
 import { relaunch } from '@tauri-apps/plugin-process';
 import { check, type Update } from '@tauri-apps/plugin-updater';
 import { useCallback, useEffect, useState } from 'react';
+import { notify } from '@/lib/tauri';
 
 export interface UpdateStatus {
   checking: boolean;
@@ -55,6 +56,11 @@ export function useAutoUpdater(checkOnMount = false) {
           installing: false,
           readyToInstall: false,
         });
+        notify(
+          'update_available',
+          'Update available',
+          `Voicebox ${foundUpdate.version} is ready to download.`,
+        ).catch((error) => console.error('Failed to show update notification:', error));
       } else {
         setStatus({
           checking: false,
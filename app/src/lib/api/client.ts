@@ -114,6 +114,17 @@ class ApiClient {
     return response.json();
   }
 
+  async linkProfileSample(
+    profileId: string,
+    sampleId: string,
+    referenceText?: string,
+  ): Promise<ProfileSampleResponse> {
+    return this.request<ProfileSampleResponse>(`/profiles/${profileId}/samples/link`, {
+      method: 'POST',
+      body: JSON.stringify({ sample_id: sampleId, reference_text: referenceText }),
+    });
+  }
+
   async listProfileSamples(profileId: string): Promise<ProfileSampleResponse[]> {
     return this.request<ProfileSampleResponse[]>(`/profiles/${profileId}/samples`);
   }
@@ -26,6 +26,7 @@ export interface ProfileSampleResponse {
   profile_id: string;
   audio_path: string;
   reference_text: string;
+  source_sha256?: string | null;
 }
 
 export interface GenerationRequest {
@@ -84,6 +85,8 @@ export interface HealthResponse {
   model_size?: string;
   gpu_available: boolean;
   vram_used_mb?: number;
+  vram_free_mb?: number;
+  vram_total_mb?: number;
 }
 
 export interface PromptEnhanceRequest {
@@ -135,6 +138,7 @@ export interface ActiveDownloadTask {
   model_name: string;
   status: string;
   started_at: string;
+  progress?: number | null;
 }
 
 export interface ActiveGenerationTask {
@@ -1,6 +1,6 @@
 import { useCallback, useEffect, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { isTauri } from '@/lib/tauri';
+import { announceStateChange, isTauri } from '@/lib/tauri';
 import { convertToWav } from '@/lib/utils/audio';
 
 // ============================================================================
@@ -91,6 +91,7 @@ function useMicrophoneRecording({
       };
 
       mediaRecorder.onstop = async () => {
+        announceStateChange({ event: 'recording_stopped' }).catch(() => {});
         const webmBlob = new Blob(chunksRef.current, { type: 'audio/webm' });
 
         try {
@@ -120,6 +121,7 @@ function useMicrophoneRecording({
 
       mediaRecorder.start(100);
       setIsRecording(true);
+      announceStateChange({ event: 'recording_started' }).catch(() => {});
       startTimeRef.current = Date.now();
 
       timerRef.current = window.setInterval(() => {
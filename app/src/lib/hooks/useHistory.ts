@@ -2,7 +2,7 @@ import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
 import { apiClient } from '@/lib/api/client';
 import { queryKeys } from '@/lib/queryKeys';
 import type { HistoryQuery } from '@/lib/api/types';
-import { isTauri } from '@/lib/tauri';
+import { isTauri, trashLibraryItem } from '@/lib/tauri';
 
 export function useHistory(query?: HistoryQuery) {
   return useQuery({
@@ -23,7 +23,8 @@ export function useDeleteGeneration() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: (generationId: string) => apiClient.deleteGeneration(generationId),
+    mutationFn: (generationId: string) =>
+      isTauri() ? trashLibraryItem('generation', generationId) : apiClient.deleteGeneration(generationId),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: queryKeys.history.list() });
     },
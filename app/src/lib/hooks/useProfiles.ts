@@ -2,7 +2,7 @@ import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
 import { apiClient } from '@/lib/api/client';
 import { queryKeys } from '@/lib/queryKeys';
 import type { VoiceProfileCreate } from '@/lib/api/types';
-import { isTauri } from '@/lib/tauri';
+import { findDuplicateSamples, isTauri, trashLibraryItem } from '@/lib/tauri';
 
 export function useProfiles() {
   return useQuery({
@@ -68,7 +68,7 @@ export function useAddSample() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: ({
+    mutationFn: async ({
       profileId,
       file,
       referenceText,
@@ -76,7 +76,27 @@ export function useAddSample() {
       profileId: string;
       file: File;
       referenceText: string;
-    }) => apiClient.addProfileSample(profileId, file, referenceText),
+    }) => {
+      if (isTauri()) {
+        // Offer to reuse audio that is already in the library instead of storing it twice
+        const duplicates = await findDuplicateSamples(file).catch(() => []);
+        const sameProfile = duplicates.find((d) => d.profile_id === profileId);
+        const elsewhere = duplicates[0];
+        if (sameProfile) {
+          if (!confirm('This recording is already a sample of this voice. Add it again anyway?')) {
+            throw new Error('This recording is already a sample of this voice');
+          }
+        } else if (
+          elsewhere &&
+          confirm(
+            `This recording is already a sample of "${elsewhere.profile_name}". Link that sample instead of storing a copy?`,
+          )
+        ) {
+          return apiClient.linkProfileSample(profileId, elsewhere.sample_id, referenceText);
+        }
+      }
+      return apiClient.addProfileSample(profileId, file, referenceText);
+    },
     onSuccess: (_, variables) => {
       queryClient.invalidateQueries({
         queryKey: queryKeys.profiles.samplesList(variables.profileId),
@@ -92,7 +112,8 @@ export function useDeleteSample() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: (sampleId: string) => apiClient.deleteProfileSample(sampleId),
+    mutationFn: (sampleId: string) =>
+      isTauri() ? trashLibraryItem('sample', sampleId) : apiClient.deleteProfileSample(sampleId),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: queryKeys.profiles.list() });
     },
@@ -70,6 +70,184 @@ export async function setKeepServerRunning(keepRunning: boolean): Promise<void>
   }
 }
 
+export type TrashKind = 'generation' | 'sample';
+
+/**
+ * Delete a generation or voice sample through the app's trash, so it can be
+ * restored until it is purged (Tauri only)
+ */
+export async function trashLibraryItem(kind: TrashKind, id: string): Promise<void> {
+  await invoke('trash_library_item', { kind, id, serverUrl: useServerStore.getState().serverUrl });
+}
+
+export interface DuplicateSample {
+  sample_id: string;
+  profile_id: string;
+  profile_name: string;
+  exact: boolean;
+  similarity: number | null;
+}
+
+/**
+ * Library samples that an audio file is already a copy of, best match first (Tauri only)
+ */
+export async function findDuplicateSamples(file: Blob): Promise<DuplicateSample[]> {
+  const audio = Array.from(new Uint8Array(await file.arrayBuffer()));
+  return invoke<DuplicateSample[]>('find_duplicate_samples', {
+    audio,
+    serverUrl: useServerStore.getState().serverUrl,
+  });
+}
+
+export interface GameBankClip {
+  name: string;
+  file: string;
+  generation_id: string;
+  voice: string;
+  text: string;
+  language: string | null;
+  duration_secs: number;
+  lip_sync: string[];
+}
+
+export interface GameBankManifest {
+  bank: string;
+  engine: 'unity' | 'unreal';
+  sample_rate: number;
+  channels: number;
+  bits_per_sample: number;
+  exported_at: string;
+  clips: GameBankClip[];
+}
+
+/**
+ * Export generations as an audio bank for a game engine using a built-in (`unity`,
+ * `unreal`) or configured export profile (Tauri only)
+ */
+export async function exportGameBank(
+  generationIds: string[],
+  profile: string,
+  bank: string,
+  dir: string,
+): Promise<GameBankManifest> {
+  return invoke<GameBankManifest>('export_game_bank', {
+    generationIds,
+    profile,
+    bank,
+    dir,
+    serverUrl: useServerStore.getState().serverUrl,
+  });
+}
+
+export interface ProjectInfo {
+  name: string;
+  root: string;
+  dir: string;
+  created_at: string;
+  last_opened_at: string | null;
+  /** Opened for review only; the server refuses changes */
+  locked: boolean;
+}
+
+/**
+ * Recently opened projects, most recent first (Tauri only)
+ */
+export async function listRecentProjects(): Promise<ProjectInfo[]> {
+  return invoke<ProjectInfo[]>('list_recent_projects');
+}
+
+/**
+ * Start an empty project with its own database and audio (Tauri only)
+ */
+export async function createProject(name: string, root?: string): Promise<ProjectInfo> {
+  return invoke<ProjectInfo>('create_project', { name, root });
+}
+
+/**
+ * Restart the server on another project, or on the default data when `name` is
+ * null (Tauri only). Returns the new server URL.
+ */
+export async function switchProject(name: string | null, remote = false): Promise<string> {
+  const url = await invoke<string>('switch_project', { name, remote });
+  useServerStore.getState().setServerUrl(url);
+  return url;
+}
+
+/**
+ * Lock a finished project for review or unlock it; an open project's server is
+ * restarted so the change applies right away (Tauri only)
+ */
+export async function setProjectLocked(name: string, locked: boolean, remote = false): Promise<ProjectInfo> {
+  return invoke<ProjectInfo>('set_project_locked', { name, locked, remote });
+}
+
+export type NotificationCategory =
+  | 'update_available'
+  | 'backup_finished'
+  | 'job_finished'
+  | 'backend_crashed'
+  | 'out_of_disk';
+
+/** `respect_focus` holds a notification back while do-not-disturb or focus assist is on */
+export type NotificationPolicy = 'always' | 'respect_focus' | 'never';
+
+export interface NotificationSettings {
+  /** Categories not listed use their default: `always` for crashes and a full disk */
+  policies: Partial<Record<NotificationCategory, NotificationPolicy>>;
+}
+
+/**
+ * Show a system notification, or defer or drop it per the category's policy (Tauri only)
+ */
+export async function notify(category: NotificationCategory, title: string, body: string): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+  await invoke('notify', { category, title, body });
+}
+
+export async function getNotificationSettings(): Promise<NotificationSettings> {
+  return invoke<NotificationSettings>('get_notification_settings');
+}
+
+export async function setNotificationPolicy(
+  category: NotificationCategory,
+  policy: NotificationPolicy,
+): Promise<void> {
+  await invoke('set_notification_policy', { category, policy });
+}
+
+/** State changes the app announces to screen readers; mirrors the Rust `StateChange` */
+export type StateChange =
+  | { event: 'server_starting' }
+  | { event: 'server_ready' }
+  | { event: 'server_start_failed' }
+  | { event: 'server_stopped' }
+  | { event: 'server_crashed' }
+  | { event: 'recording_started' }
+  | { event: 'recording_stopped' }
+  | { event: 'job_finished'; label: string }
+  | { event: 'job_failed'; label: string };
+
+export interface Announcement {
+  /** Stable message ID such as `server.ready` */
+  id: string;
+  message: string;
+  politeness: 'polite' | 'assertive';
+  sequence: number;
+}
+
+/**
+ * Announce a state change noticed in the UI through the app's announcement
+ * channel (Tauri only)
+ */
+export async function announceStateChange(change: StateChange): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+  await invoke('announce_state_change', { change });
+}
+
 /**
  * Setup window close handler to check setting and stop server if needed
  */
@@ -1,10 +1,12 @@
 import ReactDOM from 'react-dom/client';
 import App from './App';
+import { LiveAnnouncer } from '@/components/LiveAnnouncer';
 import './index.css';
 import { AppProviders } from '@/lib/appInit.tsx';
 
 ReactDOM.createRoot(document.getElementById('root')!).render(
   <AppProviders>
     <App />
+    <LiveAnnouncer />
   </AppProviders>,
 );
@@ -1,3 +1,7 @@
 # Backend package
 
 __version__ = "0.2.0"
+
+# Bumped on breaking changes to the HTTP API; the launcher refuses backends whose
+# API_VERSION it doesn't support
+API_VERSION = 1
@@ -4,6 +4,7 @@ Configuration module for voicebox backend.
 Handles data directory configuration for production bundling.
 """
 
+import os
 from pathlib import Path
 
 # Default data directory (used in development)
@@ -30,8 +31,37 @@ def get_data_dir() -> Path:
     """
     return _data_dir
 
+def is_read_only() -> bool:
+    """Whether the launcher opened a locked project, which must not be changed."""
+    return os.environ.get("VOICEBOX_READ_ONLY") == "1"
+
+LOG_LEVELS = ("debug", "info", "warning", "error", "critical")
+
+def get_log_level() -> str:
+    """Log level the launcher asked for with VOICEBOX_LOG_LEVEL, or "info"."""
+    level = os.environ.get("VOICEBOX_LOG_LEVEL", "").strip().lower()
+    return level if level in LOG_LEVELS else "info"
+
+def get_forced_device(kind: str) -> str:
+    """
+    Device the launcher asked for, or "" to pick the best available one.
+
+    VOICEBOX_TTS_DEVICE and VOICEBOX_STT_DEVICE choose it for synthesis and
+    transcription ("tts" and "stt" as kind) and take precedence over VOICEBOX_DEVICE.
+    """
+    device = os.environ.get(f"VOICEBOX_{kind.upper()}_DEVICE") or os.environ.get("VOICEBOX_DEVICE", "")
+    device = device.strip().lower()
+    return "" if device == "auto" else device
+
 def get_db_path() -> Path:
-    """Get database file path."""
+    """
+    Get database file path.
+
+    The launcher sets VOICEBOX_DB_PATH to the database of the open project.
+    """
+    override = os.environ.get("VOICEBOX_DB_PATH")
+    if override:
+        return Path(override)
     return _data_dir / "voicebox.db"
 
 def get_profiles_dir() -> Path:
@@ -0,0 +1,54 @@
+"""
+Consent and license metadata for cloned voices.
+
+Each profile's record is stored as consent.json in its profile directory and is
+carried inside profile exports.
+"""
+
+import os
+from pathlib import Path
+from typing import Optional
+
+from .models import VoiceConsent
+from . import config
+
+CONSENT_FILE_NAME = "consent.json"
+
+
+def consent_required() -> bool:
+    """Whether reference audio may only be added to profiles with a consent record."""
+    return os.environ.get("VOICEBOX_REQUIRE_CONSENT") == "1"
+
+
+def get_consent_path(profile_id: str) -> Path:
+    """Get the consent file path of a profile."""
+    return config.get_profiles_dir() / profile_id / CONSENT_FILE_NAME
+
+
+def load_consent(profile_id: str) -> Optional[VoiceConsent]:
+    """
+    Load the consent record of a profile.
+
+    Args:
+        profile_id: Profile ID
+
+    Returns:
+        Consent record, or None if the profile has none
+    """
+    path = get_consent_path(profile_id)
+    if not path.exists():
+        return None
+    return VoiceConsent.model_validate_json(path.read_text(encoding="utf-8"))
+
+
+def save_consent(profile_id: str, consent: VoiceConsent) -> None:
+    """
+    Store the consent record of a profile, replacing any previous one.
+
+    Args:
+        profile_id: Profile ID
+        consent: Consent record
+    """
+    path = get_consent_path(profile_id)
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(consent.model_dump_json(indent=2), encoding="utf-8")
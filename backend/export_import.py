@@ -15,8 +15,8 @@ from sqlalchemy.orm import Session
 from .models import VoiceProfileResponse
 from .database import VoiceProfile as DBVoiceProfile, ProfileSample as DBProfileSample, Generation as DBGeneration
 from .profiles import create_profile, add_profile_sample
-from .models import VoiceProfileCreate
-from . import config
+from .models import VoiceProfileCreate, VoiceConsent
+from . import config, consent
 
 
 def _get_profiles_dir() -> Path:
@@ -67,7 +67,7 @@ def export_profile_to_zip(profile_id: str, db: Session) -> bytes:
         raise ValueError(f"Profile {profile_id} not found")
     
     # Get all samples
-    samples = db.query(DBProfileSample).filter_by(profile_id=profile_id).all()
+    samples = db.query(DBProfileSample).filter_by(profile_id=profile_id, deleted_at=None).all()
     if not samples:
         raise ValueError(f"Profile {profile_id} has no samples")
     
@@ -107,6 +107,11 @@ def export_profile_to_zip(profile_id: str, db: Session) -> bytes:
             samples_data[filename] = sample.reference_text
         
         zip_file.writestr("samples.json", json.dumps(samples_data, indent=2))
+
+        # Consent travels with the voice
+        consent_path = consent.get_consent_path(profile_id)
+        if consent_path.exists():
+            zip_file.write(consent_path, consent.CONSENT_FILE_NAME)
     
     zip_buffer.seek(0)
     return zip_buffer.read()
@@ -155,6 +160,16 @@ async def import_profile_from_zip(file_bytes: bytes, db: Session) -> VoiceProfil
             
             if not isinstance(samples_data, dict):
                 raise ValueError("Invalid samples.json: must be a dictionary")
+
+            # Validate consent before anything is created
+            consent_record = None
+            if consent.CONSENT_FILE_NAME in namelist:
+                try:
+                    consent_record = VoiceConsent.model_validate_json(zip_file.read(consent.CONSENT_FILE_NAME))
+                except Exception as e:
+                    raise ValueError(f"Invalid consent.json: {e}")
+            elif consent.consent_required():
+                raise ValueError("ZIP archive has no consent record for this voice")
             
             # Get unique profile name
             original_name = profile_data.get("name", "Imported Profile")
@@ -168,6 +183,8 @@ async def import_profile_from_zip(file_bytes: bytes, db: Session) -> VoiceProfil
             )
             
             profile = await create_profile(profile_create, db)
+            if consent_record:
+                consent.save_consent(profile.id, consent_record)
             
             # Extract and add samples
             profile_dir = _get_profiles_dir() / profile.id
@@ -29,6 +29,7 @@ async def create_generation(
     seed: Optional[int],
     db: Session,
     instruct: Optional[str] = None,
+    model_size: Optional[str] = None,
 ) -> GenerationResponse:
     """
     Create a new generation history entry.
@@ -42,6 +43,7 @@ async def create_generation(
         seed: Random seed used (if any)
         db: Database session
         instruct: Natural language instruction used (if any)
+        model_size: Model size the audio was generated with
 
     Returns:
         Created generation entry
@@ -55,6 +57,7 @@ async def create_generation(
         duration=duration,
         seed=seed,
         instruct=instruct,
+        model_size=model_size,
         created_at=datetime.utcnow(),
     )
 
@@ -77,9 +80,9 @@ async def get_generation(
         db: Database session
         
     Returns:
-        Generation or None if not found
+        Generation or None if not found (or in the trash)
     """
-    generation = db.query(DBGeneration).filter_by(id=generation_id).first()
+    generation = db.query(DBGeneration).filter_by(id=generation_id, deleted_at=None).first()
     if not generation:
         return None
     
@@ -107,7 +110,7 @@ async def list_generations(
     ).join(
         DBVoiceProfile,
         DBGeneration.profile_id == DBVoiceProfile.id
-    )
+    ).filter(DBGeneration.deleted_at.is_(None))
     
     # Apply profile filter
     if query.profile_id:
@@ -147,6 +150,7 @@ async def list_generations(
             duration=generation.duration,
             seed=generation.seed,
             instruct=generation.instruct,
+            model_size=generation.model_size,
             is_favorite=generation.is_favorite or False,
             created_at=generation.created_at,
         ))
@@ -212,6 +216,65 @@ async def delete_generation(
     return True
 
 
+async def trash_generation(
+    generation_id: str,
+    db: Session,
+) -> Optional[GenerationResponse]:
+    """
+    Move a generation to the trash.
+    
+    The row is kept with a deleted_at tombstone so it can be restored; the
+    caller is responsible for moving the audio file aside.
+    
+    Args:
+        generation_id: Generation ID
+        db: Database session
+        
+    Returns:
+        Trashed generation or None if not found (or already trashed)
+    """
+    generation = db.query(DBGeneration).filter_by(id=generation_id, deleted_at=None).first()
+    if not generation:
+        return None
+    
+    generation.deleted_at = datetime.utcnow()
+    db.commit()
+    db.refresh(generation)
+    
+    return GenerationResponse.model_validate(generation)
+
+
+async def restore_generation(
+    generation_id: str,
+    audio_path: str,
+    db: Session,
+) -> Optional[GenerationResponse]:
+    """
+    Restore a generation from the trash.
+    
+    Args:
+        generation_id: Generation ID
+        audio_path: Where the audio file was put back
+        db: Database session
+        
+    Returns:
+        Restored generation or None if it isn't in the trash
+    """
+    generation = db.query(DBGeneration).filter(
+        DBGeneration.id == generation_id,
+        DBGeneration.deleted_at.isnot(None),
+    ).first()
+    if not generation:
+        return None
+    
+    generation.deleted_at = None
+    generation.audio_path = audio_path
+    db.commit()
+    db.refresh(generation)
+    
+    return GenerationResponse.model_validate(generation)
+
+
 async def delete_generations_by_profile(
     profile_id: str,
     db: Session,
@@ -256,15 +319,16 @@ async def get_generation_stats(db: Session) -> dict:
     """
     from sqlalchemy import func
     
-    total = db.query(func.count(DBGeneration.id)).scalar()
+    live = DBGeneration.deleted_at.is_(None)
+    total = db.query(func.count(DBGeneration.id)).filter(live).scalar()
     
-    total_duration = db.query(func.sum(DBGeneration.duration)).scalar() or 0
+    total_duration = db.query(func.sum(DBGeneration.duration)).filter(live).scalar() or 0
     
     # Get generations by profile
     by_profile = db.query(
         DBGeneration.profile_id,
         func.count(DBGeneration.id).label('count')
-    ).group_by(DBGeneration.profile_id).all()
+    ).filter(live).group_by(DBGeneration.profile_id).all()
     
     return {
         "total_generations": total,
@@ -2,10 +2,16 @@
 Pydantic models for request/response validation.
 """
 
-from pydantic import BaseModel, Field
+from pydantic import BaseModel, Field, model_validator
 from typing import Optional, List
 from datetime import datetime
 
+# Limits shared by request validation and the /capabilities endpoint
+SUPPORTED_LANGUAGES = ["zh", "en", "ja", "ko", "de", "fr", "ru", "pt", "es", "it"]
+MODEL_SIZES = ["1.7B", "0.6B"]
+MAX_TEXT_LENGTH = 5000
+MAX_INSTRUCT_LENGTH = 500
+
 
 class VoiceProfileCreate(BaseModel):
     """Request model for creating a voice profile."""
@@ -32,25 +38,50 @@ class ProfileSampleCreate(BaseModel):
     reference_text: str = Field(..., min_length=1, max_length=1000)
 
 
+class VoiceConsent(BaseModel):
+    """Consent and license metadata for a cloned voice."""
+    basis: str = Field(..., pattern="^(own_voice|speaker_consent|licensed|public_domain)$")
+    speaker: Optional[str] = Field(None, max_length=200)  # Required for speaker_consent
+    license: Optional[str] = Field(None, max_length=500)  # Required for licensed
+    source: Optional[str] = Field(None, max_length=500)
+    terms_version: int = Field(..., ge=1)
+    acknowledged_at: datetime
+
+    @model_validator(mode="after")
+    def check_basis_details(self):
+        if self.basis == "speaker_consent" and not (self.speaker or "").strip():
+            raise ValueError("speaker is required when the speaker gave consent")
+        if self.basis == "licensed" and not (self.license or "").strip():
+            raise ValueError("license is required for licensed audio")
+        return self
+
+
 class ProfileSampleResponse(BaseModel):
     """Response model for profile sample."""
     id: str
     profile_id: str
     audio_path: str
     reference_text: str
+    source_sha256: Optional[str] = None
 
     class Config:
         from_attributes = True
 
 
+class ProfileSampleLink(BaseModel):
+    """Request model for adding an existing sample's audio to a profile."""
+    sample_id: str
+    reference_text: Optional[str] = None  # Defaults to the linked sample's transcript
+
+
 class GenerationRequest(BaseModel):
     """Request model for voice generation."""
     profile_id: str
-    text: str = Field(..., min_length=1, max_length=5000)
+    text: str = Field(..., min_length=1, max_length=MAX_TEXT_LENGTH)
     language: str = Field(default="en", pattern="^(zh|en|ja|ko|de|fr|ru|pt|es|it)$")
     seed: Optional[int] = Field(None, ge=0)
     model_size: Optional[str] = Field(default="1.7B", pattern="^(1\\.7B|0\\.6B)$")
-    instruct: Optional[str] = Field(None, max_length=500)
+    instruct: Optional[str] = Field(None, max_length=MAX_INSTRUCT_LENGTH)
 
 
 class GenerationResponse(BaseModel):
@@ -63,13 +94,20 @@ class GenerationResponse(BaseModel):
     duration: float
     seed: Optional[int]
     instruct: Optional[str]
+    model_size: Optional[str] = None
     is_favorite: bool = False
     created_at: datetime
+    peak_vram_mb: Optional[float] = None  # GPU memory the generation needed on top of what was allocated before
 
     class Config:
         from_attributes = True
 
 
+class TrashRestoreRequest(BaseModel):
+    """Request model for restoring a trashed generation or sample."""
+    audio_path: str = Field(..., min_length=1)  # Where the audio file was put back
+
+
 class HistoryQuery(BaseModel):
     """Query model for generation history."""
     profile_id: Optional[str] = None
@@ -90,6 +128,7 @@ class HistoryResponse(BaseModel):
     duration: float
     seed: Optional[int]
     instruct: Optional[str]
+    model_size: Optional[str] = None
     is_favorite: bool = False
     created_at: datetime
 
@@ -120,6 +159,21 @@ class TranscriptionResponse(BaseModel):
     duration: float
 
 
+class WordTiming(BaseModel):
+    """When a word is spoken, in seconds from the start of the audio."""
+    word: str
+    start: float
+    end: float
+
+
+class GenerationTimingResponse(BaseModel):
+    """Word timings of a generation's audio, as lip-sync exports are built from."""
+    generation_id: str
+    text: str
+    duration: float
+    words: List[WordTiming]
+
+
 class PromptEnhanceRequest(BaseModel):
     """Request model for prompt enhancement."""
     text: str = Field(..., min_length=1, max_length=5000)
@@ -141,8 +195,26 @@ class HealthResponse(BaseModel):
     model_downloaded: Optional[bool] = None  # Whether model is cached/downloaded
     model_size: Optional[str] = None  # Current model size if loaded
     gpu_available: bool
+    consent_required: bool  # Reference audio needs a consent record first
     gpu_type: Optional[str] = None  # GPU type (CUDA, MPS, or None)
     vram_used_mb: Optional[float] = None
+    vram_free_mb: Optional[float] = None  # Free memory on the whole device
+    vram_total_mb: Optional[float] = None
+
+
+class CapabilitiesResponse(BaseModel):
+    """Response model for feature discovery by the app."""
+    version: str
+    api_version: int
+    languages: List[str]
+    model_sizes: List[str]
+    max_text_length: int
+    max_instruct_length: int
+    voice_cloning: bool
+    streaming_tts: bool  # Audio delivered incrementally while generating
+    transcription: bool
+    prompt_enhancer: bool
+    gpu_available: bool
 
 
 class ModelStatus(BaseModel):
@@ -169,6 +241,7 @@ class ActiveDownloadTask(BaseModel):
     model_name: str
     status: str
     started_at: datetime
+    progress: Optional[float] = None  # Percent downloaded, when the size is known
 
 
 class ActiveGenerationTask(BaseModel):
@@ -319,3 +392,8 @@ class StoryItemTrim(BaseModel):
 class StoryItemSplit(BaseModel):
     """Request model for splitting a story item."""
     split_time_ms: int = Field(..., ge=0)  # Time within the clip to split at (relative to clip start)
+
+
+class LogLevelUpdate(BaseModel):
+    """Request model for changing the server's log level."""
+    level: str = Field(..., pattern="^(debug|info|warning|error|critical)$")
@@ -4,12 +4,12 @@ Whisper ASR module for transcription.
 
 from typing import Optional, List, Dict, Tuple
 import asyncio
-import os
 import numpy as np
 from pathlib import Path
 from .utils.progress import get_progress_manager
 from .utils.hf_progress import HFProgressTracker, create_hf_progress_callback
 from .utils.tasks import get_task_manager
+from . import config
 
 # Lazy import torch - allow module to load even without torch
 try:
@@ -31,13 +31,14 @@ class WhisperModel:
     
     def _get_device(self) -> str:
         """Get the best available device."""
-        forced_device = os.environ.get("VOICEBOX_DEVICE", "").strip().lower()
-        if forced_device in {"auto", ""}:
-            forced_device = ""
+        forced_device = config.get_forced_device("stt")
+        if forced_device in {"rocm", "hip", "gpu", "amd"}:
+            forced_device = "cuda"
+        if forced_device.startswith("cuda") and not torch.cuda.is_available():
+            print(f"Transcription was set to run on {forced_device}, but no GPU is available; using the CPU")
+            return "cpu"
         if forced_device in {"cpu", "cuda", "mps"} or forced_device.startswith("cuda:"):
             return forced_device
-        if forced_device in {"rocm", "hip", "gpu", "amd"}:
-            return "cuda"
 
         if torch.cuda.is_available():
             return "cuda"
@@ -420,32 +421,25 @@ class WhisperModel:
     ) -> List[Dict[str, any]]:
         """
         Transcribe audio with word-level timestamps.
-        
+
+        Whisper aligns each token to the audio through its cross-attention heads;
+        audio longer than Whisper's 30 second window is transcribed window by window.
+
         Args:
             audio_path: Path to audio file
             language: Optional language hint
-            
+
         Returns:
-            List of word segments with timestamps
+            List of words as {"word": ..., "start": seconds, "end": seconds}
         """
         await self.load_model_async()
-        
+
         from .utils.audio import load_audio
-        
+
         def _transcribe_timestamps_sync():
             """Run synchronous transcription with timestamps in thread pool."""
-            # Load audio
             audio, sr = load_audio(audio_path, sample_rate=16000)
-            
-            # Process audio
-            inputs = self.processor(
-                audio,
-                sampling_rate=16000,
-                return_tensors="pt",
-            )
-            inputs = inputs.to(self.device)
-            
-            # Set language if provided
+
             forced_decoder_ids = None
             if language:
                 lang_code = self._normalize_language(language)
@@ -466,31 +460,65 @@ class WhisperModel:
                         language=lang_code,
                         task="transcribe",
                     )
-            
-            # Generate with timestamps
-            with torch.inference_mode():
-                predicted_ids = self.model.generate(
-                    inputs["input_features"],
-                    forced_decoder_ids=forced_decoder_ids,
-                    return_timestamps=True,
+
+            tokenizer = self.processor.tokenizer
+            special_ids = set(tokenizer.all_special_ids)
+            window = 30 * sr
+            words: List[Dict[str, any]] = []
+
+            for offset in range(0, len(audio), window):
+                piece = audio[offset:offset + window]
+                piece_start = offset / sr
+                piece_end = piece_start + len(piece) / sr
+
+                inputs = self.processor(
+                    piece,
+                    sampling_rate=16000,
+                    return_tensors="pt",
                 )
-            
-            # Parse timestamps (simplified - would need more robust parsing)
-            # For now, return basic transcription
-            # TODO: Implement proper timestamp parsing
-            transcription = self.processor.batch_decode(
-                predicted_ids,
-                skip_special_tokens=True,
-            )[0]
-            
-            return [
-                {
-                    "text": transcription,
-                    "start": 0.0,
-                    "end": len(audio) / sr,
-                }
-            ]
-        
+                inputs = inputs.to(self.device)
+
+                with torch.inference_mode():
+                    output = self.model.generate(
+                        inputs["input_features"],
+                        forced_decoder_ids=forced_decoder_ids,
+                        return_token_timestamps=True,
+                        return_dict_in_generate=True,
+                    )
+
+                token_ids = [int(t) for t in output.sequences[0]]
+                # Time at which each token starts; a token ends where the next one starts
+                starts = [float(t) for t in output.token_timestamps[0]]
+
+                current = None
+                for i, token_id in enumerate(token_ids):
+                    if token_id in special_ids:
+                        continue
+                    token_text = tokenizer.decode([token_id], skip_special_tokens=True)
+                    if not token_text:
+                        continue
+
+                    token_start = piece_start + starts[i]
+                    token_end = piece_start + starts[i + 1] if i + 1 < len(starts) else piece_end
+                    token_end = min(max(token_end, token_start), piece_end)
+
+                    if token_text[:1].isspace() or current is None:
+                        if current and current["word"].strip():
+                            words.append(current)
+                        current = {"word": token_text, "start": token_start, "end": token_end}
+                    else:
+                        current["word"] += token_text
+                        current["end"] = token_end
+
+                if current and current["word"].strip():
+                    words.append(current)
+
+            for word in words:
+                word["word"] = word["word"].strip()
+                word["start"] = round(word["start"], 3)
+                word["end"] = round(word["end"], 3)
+            return words
+
         # Run blocking transcription in thread pool
         return await asyncio.to_thread(_transcribe_timestamps_sync)
 
@@ -34,6 +34,8 @@ class ProfileSample(Base):
     profile_id = Column(String, ForeignKey("profiles.id"), nullable=False)
     audio_path = Column(String, nullable=False)
     reference_text = Column(Text, nullable=False)
+    source_sha256 = Column(String)  # Hash of the file as it was uploaded, for duplicate detection
+    deleted_at = Column(DateTime)  # Set while the sample is in the trash
 
 
 class Generation(Base):
@@ -48,8 +50,10 @@ class Generation(Base):
     duration = Column(Float, nullable=False)
     seed = Column(Integer)
     instruct = Column(Text)
+    model_size = Column(String)
     is_favorite = Column(Boolean, default=False)
     created_at = Column(DateTime, default=datetime.utcnow)
+    deleted_at = Column(DateTime)  # Set while the generation is in the trash
 
 
 class Story(Base):
@@ -126,6 +130,17 @@ def init_db():
     global engine, SessionLocal, _db_path
 
     _db_path = config.get_db_path()
+
+    if config.is_read_only():
+        # A locked project is opened as it is: no migrations, no default rows, and
+        # SQLite refuses any write that gets past the API
+        engine = create_engine(
+            f"sqlite:///file:{_db_path.as_posix()}?mode=ro&uri=true",
+            connect_args={"check_same_thread": False},
+        )
+        SessionLocal = sessionmaker(autocommit=False, autoflush=False, bind=engine)
+        return
+
     _db_path.parent.mkdir(parents=True, exist_ok=True)
 
     engine = create_engine(
@@ -287,6 +302,34 @@ def _run_migrations(engine):
                 conn.execute(text("ALTER TABLE generations ADD COLUMN is_favorite BOOLEAN DEFAULT 0"))
                 conn.commit()
                 print("Added is_favorite column to generations")
+        if 'model_size' not in gen_columns:
+            print("Migrating generations: adding model_size column")
+            with engine.connect() as conn:
+                conn.execute(text("ALTER TABLE generations ADD COLUMN model_size VARCHAR"))
+                conn.commit()
+                print("Added model_size column to generations")
+        if 'deleted_at' not in gen_columns:
+            print("Migrating generations: adding deleted_at column")
+            with engine.connect() as conn:
+                conn.execute(text("ALTER TABLE generations ADD COLUMN deleted_at DATETIME"))
+                conn.commit()
+                print("Added deleted_at column to generations")
+
+    # Migration: Add deleted_at column to profile_samples table if it doesn't exist
+    if 'profile_samples' in inspector.get_table_names():
+        sample_columns = {col['name'] for col in inspector.get_columns('profile_samples')}
+        if 'deleted_at' not in sample_columns:
+            print("Migrating profile_samples: adding deleted_at column")
+            with engine.connect() as conn:
+                conn.execute(text("ALTER TABLE profile_samples ADD COLUMN deleted_at DATETIME"))
+                conn.commit()
+                print("Added deleted_at column to profile_samples")
+        if 'source_sha256' not in sample_columns:
+            print("Migrating profile_samples: adding source_sha256 column")
+            with engine.connect() as conn:
+                conn.execute(text("ALTER TABLE profile_samples ADD COLUMN source_sha256 VARCHAR"))
+                conn.commit()
+                print("Added source_sha256 column to profile_samples")
 
 
 def get_db():
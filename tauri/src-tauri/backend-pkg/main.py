@@ -4,9 +4,9 @@ FastAPI application for voicebox backend.
 Handles voice cloning, generation history, and server mode.
 """
 
-from fastapi import FastAPI, Depends, UploadFile, File, Form, HTTPException
+from fastapi import FastAPI, Depends, UploadFile, File, Form, HTTPException, Request
 from fastapi.middleware.cors import CORSMiddleware
-from fastapi.responses import FileResponse, StreamingResponse
+from fastapi.responses import FileResponse, JSONResponse, StreamingResponse
 from fastapi.staticfiles import StaticFiles
 from sqlalchemy.orm import Session
 from typing import List, Optional
@@ -22,14 +22,16 @@ except ImportError:
     TORCH_AVAILABLE = False
     torch = None
 import tempfile
+import hashlib
 import io
 from pathlib import Path
 import uuid
 import asyncio
 import signal
 import os
+import logging
 
-from . import database, models, profiles, history, tts, transcribe, config, export_import, channels, stories, __version__
+from . import database, models, profiles, history, tts, transcribe, config, export_import, channels, stories, consent, __version__, API_VERSION
 # prompt_enhancer is imported lazily in endpoints that need it (not available in binary mode)
 from .database import get_db, Generation as DBGeneration, VoiceProfile as DBVoiceProfile
 from .utils.progress import get_progress_manager
@@ -50,6 +52,31 @@ app.add_middleware(
     allow_headers=["*"],
 )
 
+# Requests that don't change the project, still served while it is locked
+READ_ONLY_ALLOWED = (
+    "/shutdown",
+    "/admin/log-level",
+    "/transcribe",
+    "/prompt-enhancer/",
+    "/models/load",
+    "/models/unload",
+)
+
+
+@app.middleware("http")
+async def reject_writes_when_locked(request: Request, call_next):
+    """Refuse requests that would change a locked project."""
+    if (
+        config.is_read_only()
+        and request.method not in ("GET", "HEAD", "OPTIONS")
+        and not request.url.path.startswith(READ_ONLY_ALLOWED)
+    ):
+        return JSONResponse(
+            status_code=423,
+            content={"detail": "This project is locked for review; unlock it to make changes"},
+        )
+    return await call_next(request)
+
 
 # ============================================
 # ROOT & HEALTH ENDPOINTS
@@ -58,7 +85,7 @@ app.add_middleware(
 @app.get("/")
 async def root():
     """Root endpoint."""
-    return {"message": "voicebox API", "version": __version__}
+    return {"message": "voicebox API", "version": __version__, "api_version": API_VERSION}
 
 
 @app.post("/shutdown")
@@ -72,6 +99,29 @@ async def shutdown():
     return {"message": "Shutting down..."}
 
 
+# Loggers whose level follows the server's; uvicorn's don't propagate to the root
+SERVER_LOGGERS = ("", "uvicorn", "uvicorn.error", "uvicorn.access")
+
+
+def apply_log_level(level: str):
+    """Set the level of the root and uvicorn loggers."""
+    for name in SERVER_LOGGERS:
+        logging.getLogger(name).setLevel(level.upper())
+
+
+@app.get("/admin/log-level")
+async def get_log_level():
+    """Current log level of the server."""
+    return {"level": logging.getLevelName(logging.getLogger().level).lower()}
+
+
+@app.put("/admin/log-level")
+async def set_log_level(update: models.LogLevelUpdate):
+    """Change the log level without a restart, e.g. to capture debug logs for a bug report."""
+    apply_log_level(update.level)
+    return {"level": update.level}
+
+
 @app.get("/health", response_model=models.HealthResponse)
 async def health():
     """Health check endpoint."""
@@ -105,8 +155,16 @@ async def health():
         gpu_type = "MPS (Apple Silicon)"
 
     vram_used = None
+    vram_free = None
+    vram_total = None
     if has_cuda and torch is not None:
         vram_used = torch.cuda.memory_allocated() / 1024 / 1024  # MB
+        try:
+            free, total = torch.cuda.mem_get_info()
+            vram_free = free / 1024 / 1024
+            vram_total = total / 1024 / 1024
+        except Exception:
+            pass
     
     # Check if model is loaded - use the same logic as model status endpoint
     model_loaded = False
@@ -166,6 +224,39 @@ async def health():
         gpu_available=gpu_available,
         gpu_type=gpu_type,
         vram_used_mb=vram_used,
+        vram_free_mb=vram_free,
+        vram_total_mb=vram_total,
+    )
+
+
+@app.get("/capabilities", response_model=models.CapabilitiesResponse)
+async def capabilities():
+    """Features and limits of this backend, so clients can adapt instead of assuming."""
+    try:
+        from . import prompt_enhancer  # noqa: F401
+        has_prompt_enhancer = True
+    except (ImportError, ModuleNotFoundError):
+        has_prompt_enhancer = False
+
+    gpu_available = False
+    if TORCH_AVAILABLE and torch is not None:
+        gpu_available = torch.cuda.is_available() or (
+            hasattr(torch.backends, 'mps') and torch.backends.mps.is_available()
+        )
+
+    return models.CapabilitiesResponse(
+        version=__version__,
+        api_version=API_VERSION,
+        languages=models.SUPPORTED_LANGUAGES,
+        model_sizes=models.MODEL_SIZES,
+        max_text_length=models.MAX_TEXT_LENGTH,
+        max_instruct_length=models.MAX_INSTRUCT_LENGTH,
+        voice_cloning=True,
+        streaming_tts=False,
+        transcription=True,
+        prompt_enhancer=has_prompt_enhancer,
+        gpu_available=gpu_available,
+        consent_required=consent.consent_required(),
     )
 
 
@@ -382,6 +473,12 @@ async def add_profile_sample(
     db: Session = Depends(get_db),
 ):
     """Add a sample to a voice profile."""
+    if consent.consent_required() and consent.load_consent(profile_id) is None:
+        raise HTTPException(
+            status_code=428,
+            detail="Record consent for this voice before adding reference audio",
+        )
+
     # Save uploaded file to temporary location
     with tempfile.NamedTemporaryFile(suffix=".wav", delete=False) as tmp:
         content = await file.read()
@@ -394,6 +491,7 @@ async def add_profile_sample(
             tmp_path,
             reference_text,
             db,
+            source_sha256=hashlib.sha256(content).hexdigest(),
         )
         return sample
     except ValueError as e:
@@ -403,6 +501,54 @@ async def add_profile_sample(
         Path(tmp_path).unlink(missing_ok=True)
 
 
+@app.post("/profiles/{profile_id}/samples/link", response_model=models.ProfileSampleResponse)
+async def link_profile_sample(
+    profile_id: str,
+    data: models.ProfileSampleLink,
+    db: Session = Depends(get_db),
+):
+    """Add another profile's sample to a voice profile, reusing its audio file."""
+    if consent.consent_required() and consent.load_consent(profile_id) is None:
+        raise HTTPException(
+            status_code=428,
+            detail="Record consent for this voice before adding reference audio",
+        )
+
+    try:
+        return await profiles.link_profile_sample(profile_id, data.sample_id, data.reference_text, db)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@app.get("/profiles/{profile_id}/consent", response_model=models.VoiceConsent)
+async def get_profile_consent(
+    profile_id: str,
+    db: Session = Depends(get_db),
+):
+    """Get the consent record of a voice profile."""
+    profile = await profiles.get_profile(profile_id, db)
+    if not profile:
+        raise HTTPException(status_code=404, detail="Profile not found")
+    record = consent.load_consent(profile_id)
+    if not record:
+        raise HTTPException(status_code=404, detail="Profile has no consent record")
+    return record
+
+
+@app.put("/profiles/{profile_id}/consent", response_model=models.VoiceConsent)
+async def set_profile_consent(
+    profile_id: str,
+    data: models.VoiceConsent,
+    db: Session = Depends(get_db),
+):
+    """Record consent for cloning a voice profile."""
+    profile = await profiles.get_profile(profile_id, db)
+    if not profile:
+        raise HTTPException(status_code=404, detail="Profile not found")
+    consent.save_consent(profile_id, data)
+    return data
+
+
 @app.get("/profiles/{profile_id}/samples", response_model=List[models.ProfileSampleResponse])
 async def get_profile_samples(
     profile_id: str,
@@ -417,13 +563,38 @@ async def delete_profile_sample(
     sample_id: str,
     db: Session = Depends(get_db),
 ):
-    """Delete a profile sample."""
+    """Delete a profile sample permanently."""
     success = await profiles.delete_profile_sample(sample_id, db)
     if not success:
         raise HTTPException(status_code=404, detail="Sample not found")
     return {"message": "Sample deleted successfully"}
 
 
+@app.post("/profiles/samples/{sample_id}/trash", response_model=models.ProfileSampleResponse)
+async def trash_profile_sample(
+    sample_id: str,
+    db: Session = Depends(get_db),
+):
+    """Hide a profile sample until it is restored or purged. The app moves its audio aside."""
+    sample = await profiles.trash_profile_sample(sample_id, db)
+    if not sample:
+        raise HTTPException(status_code=404, detail="Sample not found")
+    return sample
+
+
+@app.post("/profiles/samples/{sample_id}/restore", response_model=models.ProfileSampleResponse)
+async def restore_profile_sample(
+    sample_id: str,
+    data: models.TrashRestoreRequest,
+    db: Session = Depends(get_db),
+):
+    """Bring a trashed profile sample back."""
+    sample = await profiles.restore_profile_sample(sample_id, data.audio_path, db)
+    if not sample:
+        raise HTTPException(status_code=404, detail="Sample not in trash")
+    return sample
+
+
 @app.get("/profiles/{profile_id}/export")
 async def export_profile(
     profile_id: str,
@@ -727,6 +898,12 @@ async def generate_speech(
         import time
         generation_start_time = time.time()
 
+        # Measured so batch renders can schedule lines by the memory they need
+        track_vram = TORCH_AVAILABLE and torch is not None and torch.cuda.is_available()
+        if track_vram:
+            vram_before = torch.cuda.memory_allocated()
+            torch.cuda.reset_peak_memory_stats()
+
         audio, sample_rate = await tts_model.generate(
             data.text,
             voice_prompt,
@@ -736,6 +913,9 @@ async def generate_speech(
         )
 
         generation_duration = time.time() - generation_start_time
+        peak_vram_mb = None
+        if track_vram:
+            peak_vram_mb = max(torch.cuda.max_memory_allocated() - vram_before, 0) / 1024 / 1024
 
         # Calculate duration
         duration = len(audio) / sample_rate
@@ -757,11 +937,13 @@ async def generate_speech(
             seed=data.seed,
             db=db,
             instruct=data.instruct,
+            model_size=model_size,
         )
         
         # Mark generation as complete
         task_manager.complete_generation(generation_id, actual_duration_seconds=generation_duration)
         
+        generation.peak_vram_mb = peak_vram_mb
         return generation
         
     except ValueError as e:
@@ -861,6 +1043,7 @@ async def get_generation(
         duration=gen.duration,
         seed=gen.seed,
         instruct=gen.instruct,
+        model_size=gen.model_size,
         created_at=gen.created_at,
     )
 
@@ -870,13 +1053,38 @@ async def delete_generation(
     generation_id: str,
     db: Session = Depends(get_db),
 ):
-    """Delete a generation."""
+    """Delete a generation permanently."""
     success = await history.delete_generation(generation_id, db)
     if not success:
         raise HTTPException(status_code=404, detail="Generation not found")
     return {"message": "Generation deleted successfully"}
 
 
+@app.post("/history/{generation_id}/trash", response_model=models.GenerationResponse)
+async def trash_generation(
+    generation_id: str,
+    db: Session = Depends(get_db),
+):
+    """Hide a generation until it is restored or purged. The app moves its audio aside."""
+    generation = await history.trash_generation(generation_id, db)
+    if not generation:
+        raise HTTPException(status_code=404, detail="Generation not found")
+    return generation
+
+
+@app.post("/history/{generation_id}/restore", response_model=models.GenerationResponse)
+async def restore_generation(
+    generation_id: str,
+    data: models.TrashRestoreRequest,
+    db: Session = Depends(get_db),
+):
+    """Bring a trashed generation back."""
+    generation = await history.restore_generation(generation_id, data.audio_path, db)
+    if not generation:
+        raise HTTPException(status_code=404, detail="Generation not in trash")
+    return generation
+
+
 @app.post("/history/{generation_id}/favorite")
 async def toggle_generation_favorite(
     generation_id: str,
@@ -992,6 +1200,33 @@ async def export_generation_audio(
 # TRANSCRIPTION ENDPOINTS
 # ============================================
 
+@app.get("/history/{generation_id}/timing", response_model=models.GenerationTimingResponse)
+async def get_generation_timing(generation_id: str, db: Session = Depends(get_db)):
+    """Word timings of a generation's audio, aligned by Whisper."""
+    generation = db.query(DBGeneration).filter_by(id=generation_id).first()
+    if not generation:
+        raise HTTPException(status_code=404, detail="Generation not found")
+
+    audio_path = Path(str(generation.audio_path))
+    if not audio_path.exists():
+        raise HTTPException(status_code=404, detail="Audio file not found")
+
+    try:
+        from .utils.audio import load_audio
+        audio, sr = load_audio(str(audio_path))
+        whisper_model = transcribe.get_whisper_model()
+        words = await whisper_model.transcribe_with_timestamps(str(audio_path), generation.language)
+    except Exception as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+    return models.GenerationTimingResponse(
+        generation_id=generation_id,
+        text=str(generation.text),
+        duration=len(audio) / sr,
+        words=[models.WordTiming(**word) for word in words],
+    )
+
+
 @app.post("/transcribe", response_model=models.TranscriptionResponse)
 async def transcribe_audio(
     file: UploadFile = File(...),
@@ -1791,11 +2026,14 @@ async def get_active_tasks():
         task = download_map.get(model_name)
         progress = progress_map.get(model_name)
         
+        percent = progress.get("progress") if progress and progress.get("total") else None
+
         if task:
             active_downloads.append(models.ActiveDownloadTask(
                 model_name=model_name,
                 status=task.status,
                 started_at=task.started_at,
+                progress=percent,
             ))
         elif progress:
             # Progress exists but no task - create from progress data
@@ -1812,6 +2050,7 @@ async def get_active_tasks():
                 model_name=model_name,
                 status=progress.get("status", "downloading"),
                 started_at=started_at,
+                progress=percent,
             ))
     
     # Get active generations
@@ -1913,6 +2152,10 @@ if __name__ == "__main__":
     if args.data_dir:
         config.set_data_dir(args.data_dir)
 
+    log_level = config.get_log_level()
+    logging.basicConfig(level=log_level.upper())
+    apply_log_level(log_level)
+
     # Initialize database after data directory is set
     database.init_db()
 
@@ -1921,4 +2164,5 @@ if __name__ == "__main__":
         host=args.host,
         port=args.port,
         reload=False,  # Disable reload in production
+        log_level=log_level,
     )
@@ -4,6 +4,7 @@ Voice profile management module.
 
 from typing import List, Optional
 from datetime import datetime
+import os
 import uuid
 import shutil
 from pathlib import Path
@@ -75,6 +76,7 @@ async def add_profile_sample(
     audio_path: str,
     reference_text: str,
     db: Session,
+    source_sha256: Optional[str] = None,
 ) -> ProfileSampleResponse:
     """
     Add a sample to a voice profile.
@@ -84,6 +86,7 @@ async def add_profile_sample(
         audio_path: Path to temporary audio file
         reference_text: Transcript of audio
         db: Database session
+        source_sha256: SHA-256 of the uploaded file
         
     Returns:
         Created sample
@@ -117,6 +120,7 @@ async def add_profile_sample(
         profile_id=profile_id,
         audio_path=str(dest_path),
         reference_text=reference_text,
+        source_sha256=source_sha256,
     )
     
     db.add(db_sample)
@@ -130,6 +134,62 @@ async def add_profile_sample(
     return ProfileSampleResponse.model_validate(db_sample)
 
 
+async def link_profile_sample(
+    profile_id: str,
+    sample_id: str,
+    reference_text: Optional[str],
+    db: Session,
+) -> ProfileSampleResponse:
+    """
+    Add an existing sample's audio to a voice profile without storing it twice.
+    
+    The audio is hard-linked where the filesystem allows and copied otherwise,
+    so either sample can be deleted without affecting the other.
+    
+    Args:
+        profile_id: Profile ID
+        sample_id: Sample whose audio to reuse
+        reference_text: Transcript, defaulting to the linked sample's
+        db: Database session
+        
+    Returns:
+        Created sample
+    """
+    profile = db.query(DBVoiceProfile).filter_by(id=profile_id).first()
+    if not profile:
+        raise ValueError(f"Profile {profile_id} not found")
+    
+    source = db.query(DBProfileSample).filter_by(id=sample_id, deleted_at=None).first()
+    if not source:
+        raise ValueError(f"Sample {sample_id} not found")
+    if source.profile_id == profile_id:
+        raise ValueError("Sample is already in this profile")
+    
+    new_id = str(uuid.uuid4())
+    profile_dir = _get_profiles_dir() / profile_id
+    profile_dir.mkdir(parents=True, exist_ok=True)
+    dest_path = profile_dir / f"{new_id}.wav"
+    try:
+        os.link(source.audio_path, dest_path)
+    except OSError:
+        shutil.copy2(source.audio_path, dest_path)
+    
+    db_sample = DBProfileSample(
+        id=new_id,
+        profile_id=profile_id,
+        audio_path=str(dest_path),
+        reference_text=reference_text or source.reference_text,
+        source_sha256=source.source_sha256,
+    )
+    
+    db.add(db_sample)
+    profile.updated_at = datetime.utcnow()
+    db.commit()
+    db.refresh(db_sample)
+    
+    return ProfileSampleResponse.model_validate(db_sample)
+
+
 async def get_profile(
     profile_id: str,
     db: Session,
@@ -165,7 +225,7 @@ async def get_profile_samples(
     Returns:
         List of samples
     """
-    samples = db.query(DBProfileSample).filter_by(profile_id=profile_id).all()
+    samples = db.query(DBProfileSample).filter_by(profile_id=profile_id, deleted_at=None).all()
     return [ProfileSampleResponse.model_validate(s) for s in samples]
 
 
@@ -281,6 +341,65 @@ async def delete_profile_sample(
     return True
 
 
+async def trash_profile_sample(
+    sample_id: str,
+    db: Session,
+) -> Optional[ProfileSampleResponse]:
+    """
+    Move a profile sample to the trash.
+    
+    The row is kept with a deleted_at tombstone so it can be restored; the
+    caller is responsible for moving the audio file aside.
+    
+    Args:
+        sample_id: Sample ID
+        db: Database session
+        
+    Returns:
+        Trashed sample or None if not found (or already trashed)
+    """
+    sample = db.query(DBProfileSample).filter_by(id=sample_id, deleted_at=None).first()
+    if not sample:
+        return None
+    
+    sample.deleted_at = datetime.utcnow()
+    db.commit()
+    db.refresh(sample)
+    
+    return ProfileSampleResponse.model_validate(sample)
+
+
+async def restore_profile_sample(
+    sample_id: str,
+    audio_path: str,
+    db: Session,
+) -> Optional[ProfileSampleResponse]:
+    """
+    Restore a profile sample from the trash.
+    
+    Args:
+        sample_id: Sample ID
+        audio_path: Where the audio file was put back
+        db: Database session
+        
+    Returns:
+        Restored sample or None if it isn't in the trash
+    """
+    sample = db.query(DBProfileSample).filter(
+        DBProfileSample.id == sample_id,
+        DBProfileSample.deleted_at.isnot(None),
+    ).first()
+    if not sample:
+        return None
+    
+    sample.deleted_at = None
+    sample.audio_path = audio_path
+    db.commit()
+    db.refresh(sample)
+    
+    return ProfileSampleResponse.model_validate(sample)
+
+
 async def create_voice_prompt_for_profile(
     profile_id: str,
     db: Session,
@@ -300,7 +419,7 @@ async def create_voice_prompt_for_profile(
         Voice prompt dictionary
     """
     # Get all samples for profile
-    samples = db.query(DBProfileSample).filter_by(profile_id=profile_id).all()
+    samples = db.query(DBProfileSample).filter_by(profile_id=profile_id, deleted_at=None).all()
     
     if not samples:
         raise ValueError(f"No samples found for profile {profile_id}")
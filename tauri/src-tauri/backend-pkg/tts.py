@@ -4,7 +4,6 @@ TTS inference module using Qwen3-TTS.
 
 from typing import Optional, List, Tuple, Any
 import asyncio
-import os
 import numpy as np
 import io
 import soundfile as sf
@@ -60,13 +59,14 @@ class TTSModel:
     
     def _get_device(self) -> str:
         """Get the best available device."""
-        forced_device = os.environ.get("VOICEBOX_DEVICE", "").strip().lower()
-        if forced_device in {"auto", ""}:
-            forced_device = ""
+        forced_device = config.get_forced_device("tts")
+        if forced_device in {"rocm", "hip", "gpu", "amd"}:
+            forced_device = "cuda"
+        if forced_device.startswith("cuda") and not (torch is not None and torch.cuda.is_available()):
+            print(f"Synthesis was set to run on {forced_device}, but no GPU is available; using the CPU")
+            return "cpu"
         if forced_device in {"cpu", "cuda", "mps"} or forced_device.startswith("cuda:"):
             return forced_device
-        if forced_device in {"rocm", "hip", "gpu", "amd"}:
-            return "cuda"
 
         if torch is not None and torch.cuda.is_available():
             return "cuda"
@@ -84,5 +84,97 @@ fn main() {
         }
     }
 
+    // Embedded Python builds must not be bundled without the runtime they depend on
+    if std::env::var_os("CARGO_FEATURE_EMBEDDED_PYTHON").is_some() {
+        let python_dir = format!("{}/resources/python", env!("CARGO_MANIFEST_DIR"));
+        println!("cargo:rerun-if-changed={}", python_dir);
+        if !std::path::Path::new(&python_dir).is_dir() {
+            panic!(
+                "The embedded-python feature needs a CPython distribution in {} \
+                 (the Windows embeddable package, or python-build-standalone on macOS and Linux)",
+                python_dir
+            );
+        }
+    }
+
+    write_backend_manifest();
+
     tauri_build::build()
 }
+
+/// Hash every file of the bundled backend into `gen/backend-manifest.json`, which is
+/// installed as `backend/manifest.json` for the launcher to verify the install against,
+/// and embed the files themselves so the launcher can restore damaged ones
+fn write_backend_manifest() {
+    use sha2::{Digest, Sha256};
+
+    let project_root = env!("CARGO_MANIFEST_DIR");
+    let backend_dir = std::path::Path::new(project_root).join("backend-pkg");
+    println!("cargo:rerun-if-changed={}", backend_dir.display());
+
+    let mut files = Vec::new();
+    collect_files(&backend_dir, &backend_dir, &mut files);
+    files.sort();
+    warn_about_drift(&backend_dir, &files);
+
+    let mut entries = Vec::new();
+    let mut embedded = Vec::new();
+    for relative in &files {
+        let path = backend_dir.join(relative);
+        let contents = std::fs::read(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        entries.push(format!(
+            "    {:?}: {{ \"size\": {}, \"sha256\": \"{:x}\" }}",
+            relative,
+            contents.len(),
+            Sha256::digest(&contents)
+        ));
+        embedded.push(format!("    ({:?}, include_bytes!({:?})),", relative, path.display().to_string()));
+    }
+
+    let gen_dir = format!("{}/gen", project_root);
+    std::fs::create_dir_all(&gen_dir).expect("Failed to create gen directory");
+    let manifest = format!("{{\n  \"files\": {{\n{}\n  }}\n}}\n", entries.join(",\n"));
+    std::fs::write(format!("{}/backend-manifest.json", gen_dir), manifest).expect("Failed to write backend manifest");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set for build scripts");
+    let source = format!("&[\n{}\n]\n", embedded.join("\n"));
+    std::fs::write(format!("{}/backend_files.rs", out_dir), source).expect("Failed to write embedded backend files");
+}
+
+/// Warn about bundled files that differ from their source in the repository's
+/// `backend`, which is where backend changes are made: the manifest and the embedded
+/// copies are only as current as `backend-pkg`
+fn warn_about_drift(backend_dir: &std::path::Path, files: &[String]) {
+    let source_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../backend");
+    if !source_dir.is_dir() {
+        return;
+    }
+    println!("cargo:rerun-if-changed={}", source_dir.display());
+    for relative in files {
+        let source = std::fs::read(source_dir.join(relative));
+        let bundled = std::fs::read(backend_dir.join(relative));
+        if let (Ok(source), Ok(bundled)) = (source, bundled) {
+            if source != bundled {
+                println!("cargo:warning=backend-pkg/{} differs from backend/{}; sync it before bundling", relative, relative);
+            }
+        }
+    }
+}
+
+/// Files under `dir`, relative to `root` with `/` separators, without bytecode caches
+fn collect_files(root: &std::path::Path, dir: &std::path::Path, files: &mut Vec<String>) {
+    let entries = std::fs::read_dir(dir).unwrap_or_else(|e| panic!("Failed to read {}: {}", dir.display(), e));
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        if path.is_dir() {
+            if name != "__pycache__" {
+                collect_files(root, &path, files);
+            }
+        } else if path.extension().is_none_or(|ext| ext != "pyc") {
+            let relative = path.strip_prefix(root).expect("collected files are under the root");
+            let parts: Vec<_> = relative.components().map(|part| part.as_os_str().to_string_lossy()).collect();
+            files.push(parts.join("/"));
+        }
+    }
+}
@@ -1,7 +1,9 @@
+use crate::ducking::Ducker;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Host, SampleFormat, StreamConfig};
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct AudioOutputDevice {
@@ -13,6 +15,7 @@ pub struct AudioOutputDevice {
 pub struct AudioOutputState {
     host: Host,
     stop_flag: Arc<AtomicBool>,
+    ducker: Ducker,
 }
 
 impl AudioOutputState {
@@ -20,6 +23,7 @@ impl AudioOutputState {
         Self {
             host: cpal::default_host(),
             stop_flag: Arc::new(AtomicBool::new(false)),
+            ducker: Ducker::default(),
         }
     }
 
@@ -27,6 +31,7 @@ impl AudioOutputState {
         eprintln!("stop_all_playback: Setting stop flag");
         self.stop_flag.store(true, Ordering::Relaxed);
         eprintln!("stop_all_playback: Stop flag set - active streams will output silence");
+        self.ducker.release();
         Ok(())
     }
 
@@ -66,6 +71,7 @@ impl AudioOutputState {
         &self,
         audio_data: Vec<u8>,
         device_ids: Vec<String>,
+        duck: Option<f32>,
     ) -> Result<(), String> {
         eprintln!("play_audio_to_devices called with {} bytes, {} device IDs", audio_data.len(), device_ids.len());
         eprintln!("Requested device IDs: {:?}", device_ids);
@@ -101,8 +107,17 @@ impl AudioOutputState {
 
         eprintln!("Playing to {} device(s)", devices.len());
         
-        // Stop any existing playback first
-        self.stop_all_playback().ok();
+        // Stop any existing playback first. Ducking stays in place and is extended below,
+        // so other applications don't swell up between two clips.
+        self.stop_flag.store(true, Ordering::Relaxed);
+
+        if let Some(level) = duck {
+            let frames = samples.len() as f64 / channels.max(1) as f64;
+            let duration = Duration::from_secs_f64(frames / sample_rate.max(1) as f64);
+            if let Err(e) = self.ducker.duck(level, duration) {
+                eprintln!("Ducking other applications failed: {}", e);
+            }
+        }
         
         // Reset stop flag for new playback
         self.stop_flag.store(false, Ordering::Relaxed);
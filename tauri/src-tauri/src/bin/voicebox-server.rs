@@ -1,25 +1,1619 @@
 use std::env;
-use std::fs::OpenOptions;
-use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use chrono::Local;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use clap::{CommandFactory, Parser};
+use voicebox::launcher::backend_log::{BackendLogFilter, BackendRecord, LOG_LEVEL_ENV};
+use voicebox::launcher::checkpoint::{self, request_sha256, RenderCheckpoint, RenderedFile};
+use voicebox::launcher::cli::{Cli, Commands};
+use voicebox::launcher::config::{default_data_dir, LauncherConfig};
+use voicebox::launcher::console::Console;
+use voicebox::launcher::debug_env::{log_section, probe_python, ConfigLayers, EnvDump};
+use voicebox::launcher::deps::{
+    check_dependencies, read_requirements, requirements_hash, write_filtered_requirements, DependencyCheckCache,
+};
+use voicebox::launcher::dialogs;
+use voicebox::launcher::dry_run::{port_available, DryRunReport, ListenReport};
+use voicebox::launcher::mcp;
+use voicebox::launcher::log::{log, log_backend, log_backend_record, log_path, set_level, set_log_path, set_structured, LOG_FILE_NAME};
+use voicebox::launcher::discovery::{backend_candidates, find_backend_dir};
+use voicebox::launcher::error::{LaunchPhase, LauncherError};
+use voicebox::launcher::fine_tune::{self, FineTuneJob, FineTuneStatus, FineTuneStore, TRAINER_LOG_FILE_NAME};
+use voicebox::launcher::consent::REQUIRE_CONSENT_ENV;
+use voicebox::launcher::jobs::{self, BatchJob, JobStore, Segment, SegmentStatus};
+use voicebox::launcher::operations::{self, Operations, SpeakParams, TranscribeParams};
+use voicebox::launcher::hooks::{run_exit_hook, ExitReport};
+use voicebox::launcher::conda;
+use voicebox::launcher::gpu::{torch_to_install, GpuInfo};
+use voicebox::launcher::integrity::{self, BackendManifest, BUNDLED_FILES};
+use voicebox::launcher::installer::{
+    index_reachable, install_requirements, InstallPlan, Installer, PackageIndexConfig, PackageSource,
+};
+use voicebox::launcher::instance::{self, IfRunning, InstanceLock, TAKEOVER_TIMEOUT};
+use voicebox::launcher::interpreter::{
+    check_min_version, find_python, python_candidates, python_version, PythonCandidate, PythonChoice,
+};
+use voicebox::launcher::pairing::{lan_address, qr_text, Pairing};
+use voicebox::launcher::picker::{viable_interpreters, InterpreterInfo, InterpreterPick};
+use voicebox::launcher::paths::{
+    is_dir_writable, long_path, python_path_with, shared_models_env, LauncherPaths, MAX_PATH,
+};
+use voicebox::launcher::process::{CommandSpec, ProcessRunner, SystemRunner, TimeoutRunner, DEFAULT_PREFLIGHT_TIMEOUT_SECS};
+use voicebox::launcher::projects::{ProjectInfo, Projects, DB_FILE_NAME, DB_PATH_ENV, READ_ONLY_ENV};
+use voicebox::launcher::progress::{ProgressKind, TaskReporter};
+use voicebox::launcher::proxy;
+use voicebox::launcher::retry::with_io_retry;
+use voicebox::launcher::rpc;
+use voicebox::launcher::sapi::{self, SapiVoice};
+use voicebox::launcher::script::{self, LineResult, RenderReport};
+use voicebox::launcher::setup::{
+    download_command, migration_command, migration_modules, Setup, SetupActions, SetupStep, DEFAULT_SETUP_MODELS,
+};
+use voicebox::launcher::state::{self, runtime_state, RuntimeState};
+use voicebox::launcher::status;
+use voicebox::launcher::storage::check_data_dir;
+use voicebox::launcher::supervisor::{Decision, Supervisor};
+use voicebox::launcher::voice_map::VoiceMap;
+use voicebox::launcher::venv::{ManagedVenv, VenvHealth};
+use voicebox::launcher::version::{check_compatible, read_backend_version};
+use voicebox::launcher::wsl::{check_interpreter, host_path, running_in_wsl};
+use voicebox::launcher::zombie::{self, LeftBackend};
+use voicebox::launcher::speechd::{self, SpeechdVoice};
+use voicebox::launcher::shutdown::{self, SHUTDOWN_GRACE};
+use voicebox::launcher::signing::{fingerprint, load_or_create_signing_key};
+use voicebox::launcher::{exit_code, DEFAULT_PORT};
+use voicebox::library::dataset::METADATA_FILE_NAME;
+use voicebox::library::mirror::Mirror;
+use voicebox::postprocess::pcm::Pcm;
+use voicebox::postprocess::snapshot::{self, GenerationSnapshot};
+use voicebox::postprocess::{compare, watermark};
+use voicebox::text::filter::ContentFilter;
 
-fn get_log_path() -> PathBuf {
-    let mut temp = env::temp_dir();
-    temp.push("voicebox-launch.log");
-    temp
+fn load_config(cli: &Cli) -> Result<LauncherConfig, LauncherError> {
+    let data_dir = match cli.data_dir.clone().or_else(default_data_dir) {
+        Some(dir) => dir,
+        None => return Ok(LauncherConfig::default()),
+    };
+    LauncherConfig::load_from_data_dir(&data_dir).map_err(LauncherError::InvalidConfig)
 }
 
-fn log(msg: &str) {
-    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(get_log_path()) {
-        let _ = writeln!(file, "[{}] {}", Local::now().format("%Y-%m-%d %H:%M:%S"), msg);
+fn list_presets(cli: &Cli) -> Result<(), LauncherError> {
+    let config = load_config(cli)?;
+    if config.presets.is_empty() {
+        println!("No presets defined");
+        return Ok(());
     }
+    for name in config.preset_names() {
+        println!("{}", name);
+    }
+    Ok(())
+}
+
+fn speak(cli: &Cli, text: &str, preset: &str) -> Result<(), LauncherError> {
+    let config = load_config(cli)?;
+    let resolved = config.resolve_preset(preset).map_err(LauncherError::InvalidConfig)?;
+
+    let generation = post_generate(cli, &resolved.generate_request(text))?;
+    println!("{}", serde_json::to_string_pretty(&generation).unwrap_or_default());
+    Ok(())
+}
+
+/// POST a request body to the running backend's /generate
+fn post_generate(
+    cli: &Cli,
+    request: &impl serde::Serialize,
+) -> Result<serde_json::Value, LauncherError> {
+    let url = format!("http://127.0.0.1:{}/generate", cli.port.unwrap_or(DEFAULT_PORT));
+    reqwest::blocking::Client::new()
+        .post(&url)
+        .json(request)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.json::<serde_json::Value>())
+        .map_err(|e| LauncherError::Request { url, message: e.to_string() })
+}
+
+/// Download the WAV file of a generation from the running backend
+fn fetch_audio(cli: &Cli, generation_id: &str) -> Result<Vec<u8>, LauncherError> {
+    let url = format!("http://127.0.0.1:{}/audio/{}", cli.port.unwrap_or(DEFAULT_PORT), generation_id);
+    reqwest::blocking::get(&url)
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| LauncherError::Request { url, message: e.to_string() })
+}
+
+fn rerender(cli: &Cli, file: &Path, output: Option<&Path>) -> Result<(), LauncherError> {
+    let invalid = |e: String| LauncherError::InvalidInput(format!("{}: {}", file.display(), e));
+    let wav = std::fs::read(file).map_err(LauncherError::Output)?;
+    let previous = snapshot::read(&wav)
+        .map_err(invalid)?
+        .ok_or_else(|| invalid("no generation settings stored in the file".to_string()))?;
+    let request = previous.rerender_request().map_err(invalid)?;
+    if !previous.is_reproducible() {
+        eprintln!("No seed was recorded for this file; the new render will differ from it");
+    }
+
+    let generation = post_generate(cli, &request)?;
+    let snapshot = GenerationSnapshot {
+        preset: previous.preset,
+        speed: previous.speed,
+        ..GenerationSnapshot::capture(&request, &generation)
+    };
+    match (output, &snapshot.generation_id) {
+        (Some(output), Some(generation_id)) => {
+            let audio = snapshot::embed(&fetch_audio(cli, generation_id)?, &snapshot)
+                .map_err(|e| LauncherError::InvalidInput(format!("Audio of generation {}: {}", generation_id, e)))?;
+            std::fs::write(output, audio).map_err(LauncherError::Output)?;
+            println!("Rendered {} as {}", file.display(), output.display());
+        }
+        _ => println!("{}", serde_json::to_string_pretty(&generation).unwrap_or_default()),
+    }
+    Ok(())
+}
+
+/// Settings of the two renderings of `compare`
+struct Variant<'a> {
+    preset: &'a str,
+    voice: Option<&'a str>,
+}
+
+fn compare_render(cli: &Cli, text: &str, variants: [Variant; 2], output_dir: Option<&Path>) -> Result<(), LauncherError> {
+    let config = load_config(cli)?;
+    let mut requests = Vec::new();
+    let mut resolved = Vec::new();
+    for variant in &variants {
+        let preset = config.resolve_preset(variant.preset).map_err(LauncherError::InvalidConfig)?;
+        let mut request = preset.generate_request(text);
+        if let Some(voice) = variant.voice {
+            request["profile_id"] = serde_json::json!(voice);
+        }
+        requests.push(request.as_object().cloned().unwrap_or_default());
+        resolved.push(preset);
+    }
+
+    let store = job_store(cli);
+    let job = BatchJob::new(&format!("compare {} / {}", variants[0].preset, variants[1].preset), requests);
+    store.save(&job).map_err(LauncherError::State)?;
+    let job = jobs::resume_concurrent(&store, &job.id, |segment| {
+        let generation = post_generate(cli, &segment.request).map_err(|e| e.to_string())?;
+        Ok(GenerationSnapshot::capture(&segment.request, &generation))
+    })
+    .map_err(LauncherError::State)?;
+
+    let mut renders = Vec::new();
+    for (segment, preset) in job.segments.iter().zip(&resolved) {
+        match &segment.status {
+            SegmentStatus::Done { generation_id, snapshot } => {
+                let snapshot = snapshot.clone().map(|s| GenerationSnapshot {
+                    preset: Some(preset.name.clone()),
+                    speed: preset.speed,
+                    ..s
+                });
+                renders.push((fetch_audio(cli, generation_id)?, snapshot));
+            }
+            SegmentStatus::Failed { error } => {
+                return Err(LauncherError::Request {
+                    url: format!("http://127.0.0.1:{}/generate", cli.port.unwrap_or(DEFAULT_PORT)),
+                    message: format!("Rendering {} failed: {}; run resume {} to retry", preset.name, error, job.id),
+                })
+            }
+            SegmentStatus::Pending => {
+                return Err(LauncherError::State(format!(
+                    "Job {} has a segment that was not rendered; the job file may have been changed, run resume {} to finish it",
+                    job.id, job.id
+                )))
+            }
+        }
+    }
+
+    let invalid = |e: String| LauncherError::InvalidInput(format!("Backend audio: {}", e));
+    let report = compare::compare(&renders[0].0, &renders[1].0).map_err(invalid)?;
+    let (a, b) = compare::align(&renders[0].0, &renders[1].0).map_err(invalid)?;
+    let output_dir = output_dir.unwrap_or(Path::new("."));
+    for ((audio, snapshot), label) in [(a, &renders[0].1), (b, &renders[1].1)].into_iter().zip(["a", "b"]) {
+        let audio = match snapshot {
+            Some(snapshot) => snapshot::embed(&audio, snapshot).map_err(invalid)?,
+            None => audio,
+        };
+        let path = output_dir.join(format!("{}-{}.wav", job.id, label));
+        std::fs::write(&path, audio).map_err(LauncherError::Output)?;
+        eprintln!("{}: {}", label.to_uppercase(), path.display());
+    }
+    println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+    Ok(())
+}
+
+/// Options of `render`
+struct RenderOptions<'a> {
+    output_dir: Option<&'a Path>,
+    report: Option<&'a Path>,
+    voices: Option<&'a Path>,
+    languages: &'a [String],
+    /// Render lines again even if their file is up to date
+    force: bool,
+    /// Lines rendered at the same time
+    workers: usize,
+}
+
+/// Render every line of a dialogue script as one batch job and report what was written
+/// where. With a voice table every line is rendered once per language. Lines rendered by
+/// an earlier run whose file is unchanged are skipped. Exit code 0 only if every line
+/// was rendered.
+fn render_script(cli: &Cli, script: &Path, options: RenderOptions) -> Result<i32, LauncherError> {
+    let RenderOptions { output_dir, report, voices, languages, force, workers } = options;
+    let config = load_config(cli)?;
+    let invalid = |e: String| LauncherError::InvalidInput(format!("{}: {}", script.display(), e));
+    let content = std::fs::read_to_string(script).map_err(LauncherError::Output)?;
+    let script_lines = script::parse(script, &content).map_err(invalid)?;
+
+    // Every language is checked before anything is rendered
+    let mut lines = Vec::new();
+    let mut errors = Vec::new();
+    match voices {
+        Some(voices) => {
+            let table = VoiceMap::load(voices).map_err(LauncherError::InvalidInput)?;
+            let languages = match languages.is_empty() {
+                true => table.languages().into_iter().collect(),
+                false => languages.to_vec(),
+            };
+            if languages.is_empty() {
+                return Err(LauncherError::InvalidInput(format!("{}: no languages in the voice table", voices.display())));
+            }
+            for language in &languages {
+                match table.localize(&script_lines, language) {
+                    Ok(localized) => {
+                        let found = script::validate(&localized, &config);
+                        errors.extend(found.into_iter().map(|e| (Some(language.clone()), e)));
+                        lines.extend(localized.into_iter().map(|line| (Some(language.clone()), line)));
+                    }
+                    Err(found) => errors.extend(found.into_iter().map(|e| (Some(language.clone()), e))),
+                }
+            }
+        }
+        None => {
+            errors.extend(script::validate(&script_lines, &config).into_iter().map(|e| (None, e)));
+            lines.extend(script_lines.into_iter().map(|line| (None, line)));
+        }
+    }
+    if !errors.is_empty() {
+        for (language, error) in &errors {
+            let language = language.as_ref().map(|l| format!(" [{}]", l)).unwrap_or_default();
+            eprintln!("{}:{}:{} {}", script.display(), error.line, language, error.message);
+        }
+        return Err(invalid(format!("{} problems found; nothing was rendered", errors.len())));
+    }
+    let requests = lines
+        .iter()
+        .map(|(_, line)| script::generate_request(line, &config))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid)?;
+    let output_dir = output_dir.map(Path::to_path_buf).unwrap_or_else(|| {
+        script.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf()
+    });
+
+    // Lines whose file is still what an identical request rendered last time are kept
+    let checkpoint = RenderCheckpoint::load(&output_dir);
+    let mut results: Vec<Option<LineResult>> = vec![None; lines.len()];
+    let mut pending = Vec::new();
+    for (index, ((language, line), request)) in lines.iter().zip(&requests).enumerate() {
+        match checkpoint.unchanged(&line.filename, request).filter(|_| !force) {
+            Some(file) => {
+                results[index] = Some(LineResult {
+                    generation_id: Some(file.generation_id.clone()),
+                    duration_secs: Some(file.duration_secs),
+                    unchanged: true,
+                    ..LineResult::new(line, language.clone())
+                })
+            }
+            None => pending.push(index),
+        }
+    }
+    if pending.len() < lines.len() {
+        eprintln!("{} lines are unchanged since the last render and are skipped", lines.len() - pending.len());
+    }
+
+    let name = format!("render {}", script.file_name().unwrap_or_default().to_string_lossy());
+    let store = job_store(cli);
+    let job = BatchJob::new(&name, pending.iter().map(|&index| requests[index].clone()).collect());
+    store.save(&job).map_err(LauncherError::State)?;
+    eprintln!("Job {}: {} lines", job.id, pending.len());
+    let state_dir = LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir;
+    let progress = TaskReporter::start(&state_dir, &format!("job-{}", job.id), ProgressKind::BatchJob, &name, Some(0.0));
+    // Each file is saved and checkpointed as soon as it is generated, so an interrupted
+    // run keeps everything finished so far
+    let checkpoint = std::sync::Mutex::new(checkpoint);
+    let saved = std::sync::Mutex::new(std::collections::HashMap::new());
+    let started = std::sync::atomic::AtomicUsize::new(0);
+    let render_line = |segment: &Segment| -> Result<(GenerationSnapshot, Option<f64>), String> {
+        let (_, line) = &lines[pending[segment.index]];
+        eprintln!("Line {}/{}...", segment.index + 1, pending.len());
+        let count = started.fetch_add(1, Ordering::Relaxed).min(pending.len());
+        progress.set(Some(count as f32 / pending.len() as f32));
+        let generation = post_generate(cli, &segment.request).map_err(|e| e.to_string())?;
+        let peak_vram_mb = generation.get("peak_vram_mb").and_then(serde_json::Value::as_f64);
+        let captured = GenerationSnapshot::capture(&segment.request, &generation);
+        if let Some(generation_id) = &captured.generation_id {
+            let speed = line.preset.as_deref().and_then(|p| config.resolve_preset(p).ok()).and_then(|p| p.speed);
+            let snapshot = GenerationSnapshot { preset: line.preset.clone(), speed, ..captured.clone() };
+            let result = save_render(cli, &output_dir.join(&line.filename), generation_id, Some(&snapshot)).map(|(duration, sha256)| {
+                let file = RenderedFile {
+                    filename: line.filename.clone(),
+                    request_sha256: request_sha256(&segment.request),
+                    sha256,
+                    generation_id: generation_id.clone(),
+                    duration_secs: duration,
+                };
+                if let Err(e) = checkpoint.lock().unwrap().record(file) {
+                    eprintln!("{}", e);
+                }
+                duration
+            });
+            saved.lock().unwrap().insert(segment.index, result.map_err(|e| e.to_string()));
+        }
+        Ok((captured, peak_vram_mb))
+    };
+    let job = match workers {
+        0 | 1 => jobs::resume(&store, &job.id, |segment| render_line(segment).map(|(snapshot, _)| snapshot)),
+        workers => {
+            let budget_mb = free_vram_mb(cli);
+            match budget_mb {
+                Some(budget) => eprintln!("Rendering on {} workers within {:.0} MB of free GPU memory", workers, budget),
+                None => eprintln!("Rendering on {} workers; the backend reports no GPU memory", workers),
+            }
+            jobs::resume_scheduled(&store, &job.id, workers, budget_mb, render_line)
+        }
+    }
+    .map_err(LauncherError::State)?;
+    let mut saved = saved.into_inner().unwrap();
+
+    for (segment, &index) in job.segments.iter().zip(&pending) {
+        let (language, line) = &lines[index];
+        let mut result = LineResult::new(line, language.clone());
+        match &segment.status {
+            SegmentStatus::Done { generation_id, .. } => {
+                result.generation_id = Some(generation_id.clone());
+                match saved.remove(&segment.index) {
+                    Some(Ok(duration)) => result.duration_secs = Some(duration),
+                    Some(Err(e)) => result.error = Some(e),
+                    None => result.error = Some("The audio was not saved".to_string()),
+                }
+            }
+            SegmentStatus::Failed { error } => result.error = Some(error.clone()),
+            SegmentStatus::Pending => {
+                result.error = Some("Not rendered; the job file may have been changed".to_string())
+            }
+        }
+        if let Some(error) = &result.error {
+            let language = language.as_ref().map(|l| format!(" [{}]", l)).unwrap_or_default();
+            eprintln!("{}:{}:{} {}", script.display(), line.line, language, error);
+        }
+        results[index] = Some(result);
+    }
+    let results = results.into_iter().flatten().collect();
+
+    let report_data = RenderReport::new(script, &job.id, &output_dir, results);
+    if report_data.failed > 0 {
+        progress.fail();
+    }
+    let report_path = report.map(Path::to_path_buf).unwrap_or_else(|| output_dir.join("render-report.json"));
+    let json = serde_json::to_string_pretty(&report_data).unwrap_or_default();
+    std::fs::write(&report_path, json).map_err(LauncherError::Output)?;
+    println!(
+        "{}: {}/{} lines rendered to {}, report in {}",
+        job.id,
+        report_data.rendered,
+        report_data.lines.len(),
+        output_dir.display(),
+        report_path.display()
+    );
+    Ok(if report_data.failed == 0 { 0 } else { exit_code::FAILURE })
+}
+
+/// Free GPU memory of the running backend, if it has a GPU and says
+fn free_vram_mb(cli: &Cli) -> Option<f64> {
+    let url = format!("http://127.0.0.1:{}/health", cli.port.unwrap_or(DEFAULT_PORT));
+    let health = reqwest::blocking::get(&url).and_then(|r| r.json::<serde_json::Value>()).ok()?;
+    health.get("vram_free_mb").and_then(serde_json::Value::as_f64)
+}
+
+/// Download a generation into `path` with its settings embedded, returning its duration
+/// and the checksum of the file
+fn save_render(cli: &Cli, path: &Path, generation_id: &str, snapshot: Option<&GenerationSnapshot>) -> Result<(f64, String), LauncherError> {
+    let invalid = |e: String| LauncherError::InvalidInput(format!("Audio of generation {}: {}", generation_id, e));
+    let audio = fetch_audio(cli, generation_id)?;
+    let duration = Pcm::read(&audio).map(|pcm| pcm.secs(pcm.frames())).map_err(invalid)?;
+    let audio = match snapshot {
+        Some(snapshot) => snapshot::embed(&audio, snapshot).map_err(invalid)?,
+        None => audio,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(LauncherError::Output)?;
+    }
+    std::fs::write(path, &audio).map_err(LauncherError::Output)?;
+    Ok((duration, checkpoint::sha256(&audio)))
+}
+
+/// Operations of integrations, carried out on the running backend
+struct BackendOperations<'a> {
+    cli: &'a Cli,
+    config: LauncherConfig,
+}
+
+impl Operations for BackendOperations<'_> {
+    fn speak(&self, params: &SpeakParams) -> Result<serde_json::Value, String> {
+        let request = operations::speak_request(&self.config, params)?;
+        let mut generation = post_generate(self.cli, &request).map_err(|e| e.to_string())?;
+        if let Some(output) = &params.output {
+            let generation_id = generation.get("id").and_then(serde_json::Value::as_str).ok_or("Backend response has no generation id")?;
+            save_render(self.cli, output, generation_id, None).map_err(|e| e.to_string())?;
+            generation["path"] = serde_json::json!(output);
+        }
+        Ok(generation)
+    }
+
+    fn transcribe(&self, params: &TranscribeParams) -> Result<serde_json::Value, String> {
+        let url = format!("http://127.0.0.1:{}/transcribe", self.cli.port.unwrap_or(DEFAULT_PORT));
+        let form = reqwest::blocking::multipart::Form::new()
+            .file("file", &params.path)
+            .map_err(|e| format!("{}: {}", params.path.display(), e))?;
+        let form = match &params.language {
+            Some(language) => form.text("language", language.clone()),
+            None => form,
+        };
+        reqwest::blocking::Client::new()
+            .post(&url)
+            .multipart(form)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json::<serde_json::Value>())
+            .map_err(|e| LauncherError::Request { url, message: e.to_string() }.to_string())
+    }
+
+    fn list_voices(&self) -> Result<serde_json::Value, String> {
+        let url = format!("http://127.0.0.1:{}/profiles", self.cli.port.unwrap_or(DEFAULT_PORT));
+        reqwest::blocking::get(&url)
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json::<serde_json::Value>())
+            .map_err(|e| LauncherError::Request { url, message: e.to_string() }.to_string())
+    }
+
+    fn job_status(&self, id: &str) -> Result<serde_json::Value, String> {
+        let job = job_store(self.cli).load(id)?.ok_or_else(|| format!("No job {}", id))?;
+        Ok(operations::job_summary(&job))
+    }
+}
+
+/// Answer JSON-RPC requests on stdin until it is closed
+fn serve_rpc(cli: &Cli) -> Result<(), LauncherError> {
+    let ops = BackendOperations { cli, config: load_config(cli)? };
+    let handler = |method: &str, params| rpc::dispatch(&ops, method, params);
+    rpc::serve(&handler, std::io::stdin().lock(), std::io::stdout().lock()).map_err(LauncherError::Output)
+}
+
+/// Serve MCP tools on stdin until it is closed
+fn serve_mcp(cli: &Cli) -> Result<(), LauncherError> {
+    let ops = BackendOperations { cli, config: load_config(cli)? };
+    let handler = |method: &str, params| mcp::dispatch(&ops, method, params);
+    rpc::serve(&handler, std::io::stdin().lock(), std::io::stdout().lock()).map_err(LauncherError::Output)
+}
+
+/// Register the backend's voice profiles with SAPI, replacing earlier registrations
+fn sapi_voices(cli: &Cli, unregister: bool) -> Result<(), LauncherError> {
+    if !cfg!(windows) {
+        return Err(LauncherError::InvalidInput("SAPI voices are only available on Windows".to_string()));
+    }
+    if unregister {
+        let removed = sapi::unregister(&SystemRunner).map_err(LauncherError::InvalidInput)?;
+        println!("Removed {} Voicebox voices from SAPI", removed);
+        return Ok(());
+    }
+    let ops = BackendOperations { cli, config: load_config(cli)? };
+    let profiles = ops.list_voices().map_err(LauncherError::InvalidInput)?;
+    let voices: Vec<SapiVoice> = profiles
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|profile| {
+            Some(SapiVoice {
+                profile_id: profile["id"].as_str()?.to_string(),
+                name: profile["name"].as_str()?.to_string(),
+                language: profile["language"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect();
+    let url = format!("http://127.0.0.1:{}", cli.port.unwrap_or(DEFAULT_PORT));
+    sapi::register(&SystemRunner, &voices, &url).map_err(LauncherError::InvalidInput)?;
+    for voice in &voices {
+        println!("Registered Voicebox {} ({})", voice.name, voice.profile_id);
+    }
+    Ok(())
+}
+
+/// Print or install the Speech Dispatcher module for the backend's voice profiles
+fn speechd_module(cli: &Cli, install: bool) -> Result<(), LauncherError> {
+    let ops = BackendOperations { cli, config: load_config(cli)? };
+    let profiles = ops.list_voices().map_err(LauncherError::InvalidInput)?;
+    let voices: Vec<SpeechdVoice> = profiles
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|profile| {
+            Some(SpeechdVoice {
+                profile_id: profile["id"].as_str()?.to_string(),
+                language: profile["language"].as_str().unwrap_or("en").to_string(),
+            })
+        })
+        .collect();
+    let exe = std::env::current_exe().map_err(LauncherError::Output)?;
+    let output = std::env::temp_dir().join("voicebox-speechd.wav");
+    let module = speechd::module_config(&exe, &voices, &output);
+    if !install {
+        print!("{}", module);
+        return Ok(());
+    }
+    let config_dir = speechd::config_dir()
+        .ok_or_else(|| LauncherError::InvalidInput("No configuration directory for Speech Dispatcher".to_string()))?;
+    for path in speechd::install(&config_dir, &module).map_err(LauncherError::InvalidInput)? {
+        println!("Wrote {}", path.display());
+    }
+    println!("Restart Speech Dispatcher (`killall speech-dispatcher`) and select the \"{}\" module", speechd::MODULE_NAME);
+    Ok(())
+}
+
+/// Speak stdin with `voice` into `output`, for the Speech Dispatcher module
+fn speechd_say(cli: &Cli, voice: &str, language: Option<&str>, output: &Path) -> Result<(), LauncherError> {
+    let mut text = String::new();
+    std::io::Read::read_to_string(&mut std::io::stdin(), &mut text).map_err(LauncherError::Output)?;
+    let mut request = serde_json::json!({ "text": text, "profile_id": voice });
+    if let Some(language) = language.filter(|l| !l.is_empty()) {
+        request["language"] = serde_json::json!(language);
+    }
+    let url = format!("http://127.0.0.1:{}/tts", cli.port.unwrap_or(DEFAULT_PORT));
+    let wav = reqwest::blocking::Client::new()
+        .post(&url)
+        .json(&request)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .map_err(|e| LauncherError::Request { url, message: e.to_string() })?;
+    std::fs::write(output, &wav).map_err(LauncherError::Output)
+}
+
+/// Show a pairing QR code, or list or revoke paired devices
+fn pair_device(cli: &Cli, list: bool, revoke: Option<&str>) -> Result<(), LauncherError> {
+    let pairing = Pairing::new(&LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir);
+    if let Some(id) = revoke {
+        if !pairing.revoke(id).map_err(LauncherError::State)? {
+            return Err(LauncherError::InvalidInput(format!("No paired device {}", id)));
+        }
+        println!("Unpaired {}", id);
+        return Ok(());
+    }
+    if list {
+        let devices = pairing.devices().map_err(LauncherError::State)?;
+        if devices.is_empty() {
+            println!("No paired devices");
+        }
+        for device in devices {
+            println!("{}  {}  paired {}", device.id, device.name, device.paired_at);
+        }
+        return Ok(());
+    }
+
+    let host = lan_address()
+        .ok_or_else(|| LauncherError::InvalidInput("No network connection to pair over".to_string()))?;
+    let endpoint = format!("{}:{}", host, cli.port.unwrap_or(DEFAULT_PORT));
+    let offer = pairing.offer(&endpoint).map_err(LauncherError::State)?;
+    println!("{}", qr_text(&offer.uri).map_err(LauncherError::InvalidInput)?);
+    println!("Scan with the Voicebox companion app, or enter {}", offer.uri);
+    println!("The code is valid until {}", offer.expires_at);
+    Ok(())
+}
+
+fn job_store(cli: &Cli) -> JobStore {
+    JobStore::new(&LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir)
+}
+
+/// Conda environments the backend could run in; the one it would use is marked with `*`
+fn list_conda_envs(cli: &Cli) -> Result<(), LauncherError> {
+    let config = load_config(cli)?;
+    let envs = conda::list_envs(&SystemRunner);
+    if envs.is_empty() {
+        println!("No conda environments found; is conda or mamba installed?");
+    }
+    let wanted = PythonChoice::from_env(cli.conda_env.clone().or(config.conda_env)).conda_env;
+    for env in envs {
+        let marker = match &wanted {
+            Some(wanted) if env.name == *wanted || env.prefix == Path::new(wanted) => "*",
+            _ => " ",
+        };
+        let python = if env.python().is_some() { "" } else { "  (no Python)" };
+        println!("{} {}  {}{}", marker, env.name, env.prefix.display(), python);
+    }
+    Ok(())
+}
+
+fn list_jobs(cli: &Cli) -> Result<(), LauncherError> {
+    let jobs = job_store(cli).incomplete().map_err(LauncherError::State)?;
+    if jobs.is_empty() {
+        println!("No interrupted jobs");
+    }
+    for job in jobs {
+        println!("{}  {}  {}/{} segments done", job.id, job.name, job.completed(), job.segments.len());
+    }
+    Ok(())
+}
+
+fn watermark_file(
+    cli: &Cli,
+    input: &Path,
+    output: Option<&Path>,
+    generation_id: Option<&str>,
+) -> Result<(), LauncherError> {
+    let state_dir = LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir;
+    let key = load_or_create_signing_key(&state_dir).map_err(LauncherError::InvalidConfig)?;
+    let wav = std::fs::read(input).map_err(LauncherError::Output)?;
+    let marked = watermark::embed(&wav, &key, generation_id)
+        .map_err(|e| LauncherError::InvalidInput(format!("{}: {}", input.display(), e)))?;
+    let output = output.unwrap_or(input);
+    std::fs::write(output, marked).map_err(LauncherError::Output)?;
+    println!("Watermarked {} (signer {})", output.display(), fingerprint(&key.verifying_key()));
+    Ok(())
+}
+
+/// Exit code 0 only if the file carries a watermark
+fn verify_file(file: &Path) -> Result<i32, LauncherError> {
+    let wav = std::fs::read(file).map_err(LauncherError::Output)?;
+    let verification =
+        watermark::verify(&wav).map_err(|e| LauncherError::InvalidInput(format!("{}: {}", file.display(), e)))?;
+    match (&verification.provenance, verification.signature_valid) {
+        (Some(provenance), true) => println!(
+            "Signed: generated by {} {} on {} (signer {})",
+            provenance.generator,
+            provenance.app_version,
+            provenance.created_at,
+            verification.signer.as_deref().unwrap_or_default()
+        ),
+        (Some(_), false) => println!("Signature: invalid, the audio was changed after it was signed"),
+        (None, _) => println!("Signature: none"),
+    }
+    match &verification.sample_mark {
+        Some(signer) => println!("Sample mark: present (signer {})", signer),
+        None => println!("Sample mark: none"),
+    }
+    Ok(if verification.is_synthetic() { 0 } else { exit_code::FAILURE })
+}
+
+fn resume_job(cli: &Cli, id: &str) -> Result<(), LauncherError> {
+    let store = job_store(cli);
+    let state_dir = LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir;
+    let total = store.load(id).ok().flatten().map_or(0, |job| job.segments.len()).max(1);
+    let progress = TaskReporter::start(&state_dir, &format!("job-{}", id), ProgressKind::BatchJob, id, None);
+    let job = jobs::resume(&store, id, |segment| {
+        eprintln!("Segment {}...", segment.index + 1);
+        progress.set(Some(segment.index as f32 / total as f32));
+        let generation = post_generate(cli, &segment.request).map_err(|e| e.to_string())?;
+        Ok(GenerationSnapshot::capture(&segment.request, &generation))
+    })
+    .map_err(LauncherError::State)?;
+    if !job.is_complete() {
+        progress.fail();
+    }
+
+    for segment in &job.segments {
+        if let SegmentStatus::Failed { error } = &segment.status {
+            eprintln!("Segment {} failed: {}", segment.index + 1, error);
+        }
+    }
+    println!("{}: {}/{} segments done", job.id, job.completed(), job.segments.len());
+    if job.is_complete() {
+        Ok(())
+    } else {
+        Err(LauncherError::Request {
+            url: format!("http://127.0.0.1:{}/generate", cli.port.unwrap_or(DEFAULT_PORT)),
+            message: format!("{} segments still unfinished; run resume again", job.segments.len() - job.completed()),
+        })
+    }
+}
+
+/// Exit code 0 only if a backend runs and answers
+fn backend_status(cli: &Cli, json: bool) -> Result<i32, LauncherError> {
+    let state_dir = LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir;
+    let preflight = TimeoutRunner::new(&SystemRunner, Duration::from_secs(DEFAULT_PREFLIGHT_TIMEOUT_SECS));
+    let status = status::check(&preflight, &state_dir).map_err(LauncherError::InvalidConfig)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status).unwrap_or_default());
+    } else {
+        println!("{}", status.summary());
+        if let Some(state) = status.state.as_ref().filter(|_| status.backend_running) {
+            if let Some(interpreter) = &state.interpreter {
+                println!("Interpreter: {}", interpreter.display());
+            }
+            println!("Log: {}", state.log_path.display());
+            if state.restarts > 0 {
+                println!("Restarted {} times after crashing", state.restarts);
+            }
+        }
+    }
+    Ok(if status.is_healthy() { 0 } else { exit_code::FAILURE })
+}
+
+fn list_fine_tunes(cli: &Cli) -> Result<(), LauncherError> {
+    let runs = FineTuneStore::new(&LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir).list();
+    if runs.is_empty() {
+        println!("No fine-tuning runs");
+    }
+    for run in runs {
+        let done = run.fraction.map(|f| format!("{:.0}% ", f * 100.0)).unwrap_or_default();
+        let status = match &run.status {
+            FineTuneStatus::New => "not started".to_string(),
+            FineTuneStatus::Running { pid } => format!("running as PID {}", pid),
+            FineTuneStatus::Finished => "finished".to_string(),
+            FineTuneStatus::Failed { error } => format!("failed: {}", error),
+            FineTuneStatus::Stopped { reason, .. } => format!("stopped: {}", reason),
+        };
+        println!("{}  {}  {}{}", run.id, run.output.display(), done, status);
+    }
+    Ok(())
+}
+
+/// Run a fine-tune in the foreground until it ends or Ctrl+C stops it. Exit code 0 only
+/// if the trainer finished.
+fn fine_tune(cli: &Cli, dataset: Option<&Path>, output: Option<&Path>, resume: Option<&str>) -> Result<i32, LauncherError> {
+    let config = load_config(cli)?.fine_tune;
+    let state_dir = LauncherPaths::resolve(cli.data_dir.as_deref()).state_dir;
+    let store = FineTuneStore::new(&state_dir);
+    let mut job = match (resume, dataset) {
+        (Some(id), _) => store.load(id).map_err(LauncherError::InvalidInput)?,
+        (None, Some(dataset)) => {
+            if !dataset.join(METADATA_FILE_NAME).is_file() {
+                return Err(LauncherError::InvalidInput(format!(
+                    "{} is not a dataset: it has no {}",
+                    dataset.display(),
+                    METADATA_FILE_NAME
+                )));
+            }
+            FineTuneJob::new(dataset, &output.map_or_else(|| dataset.join("training"), Path::to_path_buf))
+        }
+        (None, None) => return Err(LauncherError::InvalidInput("Name a dataset or a run to resume".to_string())),
+    };
+    if let FineTuneStatus::Running { pid } = job.status {
+        if zombie::process_name(&SystemRunner, pid).is_some() {
+            return Err(LauncherError::InvalidInput(format!("Fine-tune {} is already running as PID {}", job.id, pid)));
+        }
+    }
+
+    shutdown::install_handler().map_err(LauncherError::InvalidConfig)?;
+    let progress = TaskReporter::start(&state_dir, &format!("fine-tune-{}", job.id), ProgressKind::FineTune, &job.id, job.fraction);
+    eprintln!("Fine-tune {}; trainer output goes to {}", job.id, job.output.join(TRAINER_LOG_FILE_NAME).display());
+    let mut shown = None;
+    fine_tune::run(&SystemRunner, &config, &store, &mut job, shutdown::requested, |job| {
+        progress.set(job.fraction);
+        let percent = job.fraction.map(|f| (f * 100.0) as u32);
+        if percent != shown {
+            shown = percent;
+            eprintln!("{}% done", percent.unwrap_or(0));
+        }
+    })
+    .map_err(LauncherError::InvalidConfig)?;
+
+    if let Some(checkpoint) = &job.latest_checkpoint {
+        println!("Latest checkpoint: {}", checkpoint.display());
+    }
+    match &job.status {
+        FineTuneStatus::Finished => {
+            println!("Fine-tune {} finished", job.id);
+            Ok(0)
+        }
+        FineTuneStatus::Stopped { reason, .. } => {
+            progress.fail();
+            println!("{}; continue with `voicebox-server fine-tune --resume {}`", reason, job.id);
+            Ok(exit_code::FAILURE)
+        }
+        FineTuneStatus::Failed { error } => {
+            progress.fail();
+            eprintln!("{}", error);
+            Ok(exit_code::FAILURE)
+        }
+        FineTuneStatus::New | FineTuneStatus::Running { .. } => Ok(exit_code::FAILURE),
+    }
+}
+
+/// Exit code 0 only if every file was copied
+fn run_mirrors(cli: &Cli, name: Option<&str>, dry_run: bool) -> Result<i32, LauncherError> {
+    let config = load_config(cli)?;
+    let paths = LauncherPaths::resolve(cli.data_dir.as_deref());
+    let selected: Vec<_> = config.mirrors.iter().filter(|m| name.is_none_or(|name| m.name == name)).collect();
+    if selected.is_empty() {
+        return Err(LauncherError::InvalidConfig(match name {
+            Some(name) => format!("No mirror named {:?}", name),
+            None => "No mirrors defined".to_string(),
+        }));
+    }
+    let mut reports = Vec::new();
+    for config in selected {
+        let mirror = Mirror::new(config, &paths.data_dir, &paths.state_dir).map_err(LauncherError::InvalidConfig)?;
+        let destination = config.destination.open().map_err(LauncherError::InvalidConfig)?;
+        let report = mirror.run(destination.as_ref(), dry_run).map_err(LauncherError::InvalidConfig)?;
+        for (path, error) in &report.failed {
+            eprintln!("{}: {}: {}", report.name, path, error);
+        }
+        reports.push(report);
+    }
+    println!("{}", serde_json::to_string_pretty(&reports).unwrap_or_default());
+    Ok(if reports.iter().all(|report| report.failed.is_empty()) { 0 } else { exit_code::FAILURE })
+}
+
+/// Project the backend runs on: `--project`, which becomes the open project, or else
+/// the project opened last. `None` runs it on the data dir itself.
+fn open_project(cli: &Cli, config: &LauncherConfig, paths: &LauncherPaths, console: &Console) -> Result<Option<ProjectInfo>, LauncherError> {
+    let projects = Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir);
+    let project = match &cli.project {
+        Some(name) => projects.open(Some(name)),
+        None => projects.active(),
+    };
+    let Some(project) = project.map_err(LauncherError::InvalidConfig)? else {
+        return Ok(None);
+    };
+    log(&format!("Launcher: Project {} in root {} at {:?}", project.name, project.root, project.dir));
+    if project.locked {
+        log("Launcher: Project is locked; the backend opens it read-only");
+        console.warn(&format!("Project {} is locked for review; changes will be refused", project.name));
+    }
+    if let Some(warning) = check_data_dir(&project.dir) {
+        log(&format!("Launcher: WARNING: {}", warning.message));
+        console.warn(&warning.message);
+    }
+    Ok(Some(project))
+}
+
+/// Lock a project for review or unlock it again
+fn lock_project(cli: &Cli, name: &str, locked: bool) -> Result<(), LauncherError> {
+    let config = load_config(cli)?;
+    let paths = LauncherPaths::resolve(cli.data_dir.as_deref());
+    let projects = Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir);
+    let project = projects.set_locked(name, locked).map_err(LauncherError::InvalidConfig)?;
+    let state = if project.locked { "locked" } else { "unlocked" };
+    println!("Project {} {}; a running backend picks this up when it is restarted", project.name, state);
+    Ok(())
+}
+
+fn list_projects(cli: &Cli) -> Result<(), LauncherError> {
+    let config = load_config(cli)?;
+    let paths = LauncherPaths::resolve(cli.data_dir.as_deref());
+    let projects = Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir);
+    let active = projects.active().map_err(LauncherError::InvalidConfig)?.map(|p| p.name);
+    let recent = projects.recent().map_err(LauncherError::InvalidConfig)?;
+    if recent.is_empty() {
+        println!("No projects yet; start one with --project <NAME>");
+    }
+    for project in recent {
+        let marker = if active.as_deref() == Some(project.name.as_str()) { "*" } else { " " };
+        let lock = if project.locked { "  (locked)" } else { "" };
+        println!("{} {}  {}  {}{}", marker, project.name, project.root, project.dir.display(), lock);
+    }
+    Ok(())
+}
+
+/// Discover everything a launch would use and print it as JSON instead of launching.
+/// Exits with `FAILURE` when the launch would run into a problem.
+fn dry_run(cli: &Cli) -> Result<i32, LauncherError> {
+    let paths = LauncherPaths::resolve(cli.data_dir.as_deref());
+    let config_file = LauncherConfig::path(&paths.data_dir);
+    let (config, config_error) = match LauncherConfig::load(&config_file) {
+        Ok(config) => (config, None),
+        Err(e) => (LauncherConfig::default(), Some(e)),
+    };
+    let mut report = DryRunReport {
+        launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+        data_dir: paths.data_dir.clone(),
+        config_file,
+        config_error,
+        ..DryRunReport::default()
+    };
+
+    let proxy_enabled = config.proxy.enabled || cli.proxy;
+    let proxy_backend_port = proxy_enabled.then_some(config.proxy.backend_port);
+    let host = cli.host.clone().unwrap_or_else(|| "127.0.0.1".to_string());
+    let port = cli.port.unwrap_or(DEFAULT_PORT);
+    let available = format!("{}:{}", host, port).parse().is_ok_and(port_available);
+    report.listen = ListenReport { host, port, available, proxy: proxy_enabled, backend_port: proxy_backend_port };
+
+    let exe_path = env::current_exe().unwrap_or_else(|_| PathBuf::from("."));
+    let exe_dir = exe_path.parent().unwrap_or(Path::new("."));
+    let Some(backend_dir) = find_backend_dir(exe_dir) else {
+        report.backend.error = Some(format!("No backend found; searched {:?}", backend_candidates(exe_dir)));
+        return print_dry_run(&report);
+    };
+    report.backend.dir = Some(backend_dir.clone());
+    match read_backend_version(&backend_dir) {
+        Ok(version) => {
+            report.backend.version = Some(version.version.to_string());
+            report.backend.api_version = version.api_version;
+            report.backend.error = check_compatible(&version).err().filter(|_| !cli.skip_version_check);
+        }
+        Err(e) => report.backend.error = Some(e),
+    }
+    if report.backend.error.is_none() {
+        if let Ok(Some(manifest)) = BackendManifest::load(&backend_dir) {
+            let integrity = integrity::verify(&backend_dir, &manifest);
+            report.backend.error = (!integrity.is_intact()).then(|| integrity.summary());
+        }
+    }
+
+    let system = SystemRunner;
+    let runner = TimeoutRunner::new(&system, preflight_timeout(cli, &config));
+    let choice = PythonChoice::from_env(cli.conda_env.clone().or(config.conda_env.clone()));
+    let python = match find_python(&runner, &backend_dir, &choice) {
+        Ok(python) => python,
+        Err(e) => {
+            report.python.error = Some(e);
+            return print_dry_run(&report);
+        }
+    };
+    // Only an earlier pick counts; the dry run never asks
+    let python = match choice.uses_as_is(&python) {
+        true => python,
+        false => {
+            let candidates = python_candidates(&runner, &backend_dir, &choice);
+            InterpreterPick::new(&paths.state_dir).find(&candidates).cloned().unwrap_or(python)
+        }
+    };
+    let venv = ManagedVenv::new(&paths.venv_dir, &paths.state_dir);
+    let python = match use_managed_venv(cli, &config, &choice, &python, &venv) {
+        true => match venv.check(&runner) {
+            VenvHealth::Ready(ready) => {
+                report.python.managed_venv = Some("ready".to_string());
+                venv.candidate(ready)
+            }
+            health => {
+                // The launch would create or complete it from this interpreter first
+                report.python.managed_venv = Some(format!("would be prepared ({:?})", health));
+                python
+            }
+        },
+        false => python,
+    };
+    report.python.path = Some(python.python.clone());
+    report.python.source = Some(python.describe());
+    let python_spec = python.command();
+    match python_version(&runner, &python_spec) {
+        Ok(version) => {
+            report.python.version = Some(version.to_string());
+            report.python.error = check_min_version(&python.python, version).err();
+        }
+        Err(e) => report.python.error = Some(e.to_string()),
+    }
+
+    let hash = requirements_hash(&backend_dir, &python_spec);
+    if DependencyCheckCache::new(&paths.state_dir).passed(&hash).is_none() || cli.recheck_deps {
+        report.dependencies.checked = true;
+        match check_dependencies(&runner, &python_spec, &read_requirements(&backend_dir)) {
+            Ok(deps) => {
+                report.dependencies.missing = deps.missing.iter().map(|r| r.to_string()).collect();
+                report.dependencies.outdated = deps.outdated.iter().map(|(r, installed)| format!("{} (installed {})", r, installed)).collect();
+                report.dependencies.would_install = !deps.is_ok();
+            }
+            Err(e) => report.dependencies.error = Some(e.to_string()),
+        }
+    }
+
+    let args = cli.backend_args(&config.workspace.to_backend(&paths.data_dir), proxy_backend_port);
+    let backend = python_spec.args(["-m", "backend.main"]).args(&args);
+    report.command = std::iter::once(&backend.program).chain(&backend.args).map(|a| a.to_string_lossy().into_owned()).collect();
+    print_dry_run(&report)
+}
+
+fn print_dry_run(report: &DryRunReport) -> Result<i32, LauncherError> {
+    println!("{}", serde_json::to_string_pretty(report).unwrap_or_default());
+    Ok(if report.problems().is_empty() { 0 } else { exit_code::FAILURE })
+}
+
+/// Check the backend folder against the manifest installed with it, and restore
+/// damaged files from the copy built into the launcher when `--repair` is given or the
+/// user agrees. Checkouts have no manifest and aren't checked.
+fn verify_backend(backend_dir: &Path, cli: &Cli, console: &Console) -> Result<(), LauncherError> {
+    let manifest = match BackendManifest::load(backend_dir) {
+        Ok(Some(manifest)) => manifest,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            log(&format!("Launcher: {}; skipping the integrity check", e));
+            return Ok(());
+        }
+    };
+    let phase = console.phase("Verifying backend files");
+    let report = integrity::verify(backend_dir, &manifest);
+    if report.is_intact() {
+        phase.finish(&format!("{} files intact", report.checked));
+        return Ok(());
+    }
+    phase.fail(&report.summary());
+    log(&format!("Launcher: {}", report.summary()));
+
+    let repair = cli.repair
+        || (!cli.headless
+            && dialogs::confirm(
+                "Damaged Installation",
+                &format!(
+                    "{}.\n\nThis happens when an installation was cut short or an antivirus program quarantined files.\n\nDo you want to restore them now?",
+                    report.summary()
+                ),
+            ));
+    if !repair {
+        return Err(LauncherError::DamagedBackend(report.summary()));
+    }
+    let phase = console.phase("Restoring backend files");
+    let unrepaired = integrity::repair(backend_dir, &manifest, &report, BUNDLED_FILES);
+    if !unrepaired.is_empty() {
+        phase.fail(&format!("{} could not be restored", unrepaired.len()));
+        return Err(LauncherError::DamagedBackend(format!(
+            "{}; could not restore {}",
+            report.summary(),
+            unrepaired.join(", ")
+        )));
+    }
+    phase.finish(&format!("{} restored", report.damaged.len()));
+    Ok(())
+}
+
+/// Time limit of pre-flight checks: `--preflight-timeout`, then `preflight_timeout_secs`
+/// from the config
+fn preflight_timeout(cli: &Cli, config: &LauncherConfig) -> Duration {
+    Duration::from_secs(cli.preflight_timeout.or(config.preflight_timeout_secs).unwrap_or(DEFAULT_PREFLIGHT_TIMEOUT_SECS))
+}
+
+fn hung_python_message(python: &Path, e: &std::io::Error) -> String {
+    format!(
+        "{} stopped responding ({}). It may be a broken Microsoft Store alias or held up by a virus scanner; \
+         set VOICEBOX_PYTHON to another interpreter, or raise --preflight-timeout if it is just slow.",
+        python.display(),
+        e
+    )
+}
+
+/// The interpreter picked on an earlier launch if it is still found, or, when several
+/// can run the backend and dialogs may be shown, the one the user picks now. Closing
+/// the picker keeps `found` and remembers it too, so the question isn't repeated.
+fn picked_python(
+    runner: &dyn ProcessRunner,
+    backend_dir: &Path,
+    choice: &PythonChoice,
+    state_dir: &Path,
+    cli: &Cli,
+    found: PythonCandidate,
+) -> PythonCandidate {
+    let pick = InterpreterPick::new(state_dir);
+    if cli.pick_python {
+        if let Err(e) = pick.clear() {
+            log(&format!("Launcher: Failed to forget the picked interpreter: {}", e));
+        }
+    }
+    let candidates = python_candidates(runner, backend_dir, choice);
+    if let Some(picked) = pick.find(&candidates) {
+        log(&format!("Launcher: Using the interpreter picked before, {}", picked.describe()));
+        return picked.clone();
+    }
+    if cli.headless {
+        return found;
+    }
+    let viable = viable_interpreters(runner, &candidates);
+    if viable.len() < 2 {
+        return found;
+    }
+    let labels: Vec<String> = viable.iter().map(InterpreterInfo::label).collect();
+    let chosen = dialogs::choose(
+        "Choose Python",
+        "Voicebox found several Python installations it can run on. Which one should it use?\n\n\
+         Run voicebox-server --pick-python to choose again later.",
+        &labels,
+    )
+    .map_or(found, |index| viable[index].candidate.clone());
+    if let Err(e) = pick.save(&chosen.python) {
+        log(&format!("Launcher: Failed to remember the picked interpreter: {}", e));
+    }
+    chosen
+}
+
+/// Whether the backend runs from the managed virtual environment: asked for on the
+/// command line or in the config, or made by an earlier launch, unless `python` is
+/// used as is
+fn use_managed_venv(cli: &Cli, config: &LauncherConfig, choice: &PythonChoice, python: &PythonCandidate, venv: &ManagedVenv) -> bool {
+    !choice.uses_as_is(python) && (cli.managed_venv || config.managed_venv || venv.is_recorded())
+}
+
+/// The managed virtual environment's interpreter, creating the environment from `base`
+/// (again, if it broke) and installing the backend's requirements into it when needed.
+/// `install_plan` is only called when something has to be installed.
+fn managed_python(
+    runner: &dyn ProcessRunner,
+    venv: &ManagedVenv,
+    base: &PythonCandidate,
+    backend_dir: &Path,
+    state_dir: &Path,
+    console: &Console,
+    install_plan: impl FnOnce(&CommandSpec) -> InstallPlan,
+) -> Result<PythonCandidate, LauncherError> {
+    let phase = console.phase("Preparing virtual environment");
+    let python = match venv.check(runner) {
+        VenvHealth::Ready(python) => {
+            phase.finish(&venv.dir().display().to_string());
+            return Ok(venv.candidate(python));
+        }
+        VenvHealth::Incomplete(python) => {
+            log("Launcher: Managed virtual environment never finished installing; installing again");
+            phase.skip("packages incomplete");
+            python
+        }
+        health => {
+            if let VenvHealth::Broken(reason) = &health {
+                log(&format!("Launcher: Managed virtual environment is broken ({}); recreating it", reason));
+                console.warn("The virtual environment is broken and is created again");
+            }
+            log(&format!("Launcher: Creating managed virtual environment at {:?} from {}", venv.dir(), base.describe()));
+            match venv.create(runner, base) {
+                Ok(python) => {
+                    phase.finish(&venv.dir().display().to_string());
+                    python
+                }
+                Err(e) => {
+                    phase.fail("could not create it");
+                    return Err(LauncherError::PythonUnusable(e));
+                }
+            }
+        }
+    };
+
+    let requirements = backend_dir.join("requirements.txt");
+    if !requirements.exists() {
+        return Err(LauncherError::MissingDependencies { requirements: None });
+    }
+    log(&format!("Launcher: Installing {:?} into the managed virtual environment", requirements));
+    let progress = TaskReporter::start(state_dir, "install", ProgressKind::Install, "Installing Python packages", None);
+    let plan = install_plan(&CommandSpec::new(&python));
+    let on_progress = |fraction: f32| progress.set(Some(fraction));
+    if let Err(e) = venv.install(runner, &plan, &python, &requirements, &on_progress) {
+        progress.fail();
+        log(&format!("Launcher: {}", e));
+        return Err(LauncherError::MissingDependencies { requirements: Some(requirements) });
+    }
+    Ok(venv.candidate(python))
+}
+
+/// The first-run setup steps as the launcher does them
+struct LauncherSetup<'a> {
+    runner: &'a dyn ProcessRunner,
+    /// Runs the quick checks under the pre-flight time limit
+    preflight: &'a dyn ProcessRunner,
+    /// Found by discovery or picked by the user
+    python: &'a PythonCandidate,
+    /// The managed environment, when the backend runs in one
+    venv: Option<&'a ManagedVenv>,
+    backend_dir: &'a Path,
+    paths: &'a LauncherPaths,
+    cli: &'a Cli,
+    config: &'a LauncherConfig,
+    console: &'a Console,
+    install_plan: &'a dyn Fn(&CommandSpec) -> InstallPlan,
+}
+
+impl LauncherSetup<'_> {
+    /// Command running `python`, which is the found interpreter or the environment's
+    fn command(&self, python: &Path) -> CommandSpec {
+        match python == self.python.python {
+            true => self.python.command(),
+            false => CommandSpec::new(python),
+        }
+    }
+
+    /// Run `spec` to the end, with the last lines of its stderr as the error
+    fn run_to_end(&self, spec: &CommandSpec, what: &str) -> Result<(), String> {
+        let output = self.runner.output(spec).map_err(|e| format!("Failed to start {}: {}", what, e))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lines: Vec<&str> = stderr.trim().lines().collect();
+        Err(format!("{} failed: {}", what, lines[lines.len().saturating_sub(3)..].join(" ")))
+    }
+}
+
+impl SetupActions for LauncherSetup<'_> {
+    fn find_python(&mut self) -> Result<PathBuf, String> {
+        // Discovery and the picker have run by now
+        Ok(self.python.python.clone())
+    }
+
+    fn create_venv(&mut self, _python: &Path) -> Result<Option<PathBuf>, String> {
+        let Some(venv) = self.venv else {
+            return Ok(None);
+        };
+        let phase = self.console.phase("Creating virtual environment");
+        if let VenvHealth::Ready(python) | VenvHealth::Incomplete(python) = venv.check(self.runner) {
+            phase.finish("already there");
+            return Ok(Some(python));
+        }
+        match venv.create(self.runner, self.python) {
+            Ok(python) => {
+                phase.finish(&venv.dir().display().to_string());
+                Ok(Some(python))
+            }
+            Err(e) => {
+                phase.fail("could not create it");
+                Err(e)
+            }
+        }
+    }
+
+    fn install_dependencies(&mut self, python: &Path) -> Result<Option<String>, String> {
+        let requirements = self.backend_dir.join("requirements.txt");
+        if !requirements.exists() {
+            return Ok(Some("no requirements.txt".to_string()));
+        }
+        let spec = self.command(python);
+        let report = check_dependencies(self.preflight, &spec, &read_requirements(self.backend_dir))
+            .map_err(|e| format!("Failed to check the installed packages: {}", e))?;
+        if report.is_ok() {
+            return Ok(Some("already installed".to_string()));
+        }
+
+        // Packages go into an interpreter the user has only with their consent
+        let venv = self.venv.filter(|_| python != self.python.python);
+        if venv.is_none() {
+            if self.cli.headless {
+                return Err(report.summary());
+            }
+            let install = dialogs::confirm(
+                "Install Python Packages",
+                &format!(
+                    "Voicebox needs Python packages that are missing or outdated in {}:\n\n{}\n\nDo you want to install them now?\n(This will try to protect your existing PyTorch installation)",
+                    self.python.describe(),
+                    report.details().join("\n")
+                ),
+            );
+            if !install {
+                return Err("Installing the packages was declined".to_string());
+            }
+        }
+
+        let phase = self.console.phase("Installing Python packages");
+        let progress = TaskReporter::start(&self.paths.state_dir, "install", ProgressKind::Install, "Installing Python packages", Some(0.0));
+        let on_progress = |fraction: f32| progress.set(Some(fraction));
+        let plan = (self.install_plan)(&spec);
+        let installed = match venv {
+            Some(venv) => venv.install(self.runner, &plan, python, &requirements, &on_progress),
+            // Filter out torch lines to prevent overwrites
+            None => write_filtered_requirements(&requirements, &self.paths.work_dir).and_then(|filtered| {
+                let installed = install_requirements(self.runner, &plan, python, &long_path(&filtered), &on_progress);
+                let _ = std::fs::remove_file(filtered);
+                installed
+            }),
+        };
+        match installed {
+            Ok(()) => {
+                phase.finish("done");
+                Ok(Some(report.summary()))
+            }
+            Err(e) => {
+                phase.fail("see the log for the installer's output");
+                progress.fail();
+                Err(e)
+            }
+        }
+    }
+
+    fn download_models(&mut self, python: &Path) -> Result<Option<String>, String> {
+        // A shared cache is read-only and has the models already
+        if self.cli.shared_models.is_some() {
+            return Ok(Some("using the shared model cache".to_string()));
+        }
+        let models = self
+            .config
+            .setup_models
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SETUP_MODELS.iter().map(|model| model.to_string()).collect());
+        if models.is_empty() {
+            return Ok(Some("no models to download".to_string()));
+        }
+        let phase = self.console.phase("Downloading models");
+        let progress = TaskReporter::start(&self.paths.state_dir, "setup-models", ProgressKind::ModelDownload, "Downloading models", None);
+        match self.run_to_end(&download_command(&self.command(python), &models), "Downloading models") {
+            Ok(()) => {
+                phase.finish(&models.join(", "));
+                Ok(Some(models.join(", ")))
+            }
+            Err(e) => {
+                phase.fail("see the log");
+                progress.fail();
+                Err(e)
+            }
+        }
+    }
+
+    fn run_migrations(&mut self, python: &Path) -> Result<Option<String>, String> {
+        let modules = migration_modules(self.backend_dir);
+        if modules.is_empty() {
+            return Ok(None);
+        }
+        let phase = self.console.phase("Updating the database");
+        for module in &modules {
+            let spec = migration_command(&self.command(python), self.backend_dir, module, &self.paths.data_dir);
+            if let Err(e) = self.run_to_end(&spec, module) {
+                phase.fail(module);
+                return Err(e);
+            }
+            log(&format!("Launcher: Ran migration {}", module));
+        }
+        phase.finish(&format!("{} migrations", modules.len()));
+        Ok(Some(modules.join(", ")))
+    }
+}
+
+/// Log a line of backend output, by level and module when it is a JSON log record.
+/// Returns whether it is echoed too, which records below the configured level aren't.
+fn pass_through(stream: &str, line: &str, filter: &BackendLogFilter) -> bool {
+    match BackendRecord::parse(line) {
+        Some(record) if !filter.allows(&record) => false,
+        Some(record) => {
+            log_backend_record(stream, &record);
+            true
+        }
+        None => {
+            log_backend(stream, line);
+            true
+        }
+    }
+}
+
+static READY_ANNOUNCED: AtomicBool = AtomicBool::new(false);
+
+/// Tell an interactive user once the backend prints one of the startup markers the
+/// Tauri app waits for
+fn announce_if_ready(console: &Console, port: u16, line: &str) {
+    let ready = line.contains("Uvicorn running") || line.contains("Application startup complete");
+    if ready && !READY_ANNOUNCED.swap(true, Ordering::Relaxed) {
+        console.success(&format!("Backend ready at http://127.0.0.1:{}", port));
+    }
+}
+
+/// Deal with a launcher that holds the data directory as `if_running` says. Returns the
+/// instance lock once it is ours, or `None` when the running launcher stays in charge.
+fn claim_running_instance(state_dir: &Path, if_running: IfRunning, console: &Console) -> Result<Option<InstanceLock>, LauncherError> {
+    let running = instance::running(state_dir);
+    let describe = match &running {
+        Some(running) => format!("Voicebox is already running at http://{}:{} (launcher PID {})", running.host, running.port, running.pid),
+        None => "Another Voicebox launcher is starting on this data directory".to_string(),
+    };
+    match if_running {
+        IfRunning::Fail => Err(LauncherError::AlreadyRunning(describe)),
+        IfRunning::Reuse => {
+            log(&format!("Launcher: {}; leaving it in charge", describe));
+            console.success(&format!("{}; using it", describe));
+            Ok(None)
+        }
+        IfRunning::TakeOver => {
+            let phase = console.phase("Stopping the running launcher");
+            let Some(running) = running else {
+                phase.fail("it hasn't started its backend yet");
+                return Err(LauncherError::AlreadyRunning(format!("{}; it can be taken over once its backend runs", describe)));
+            };
+            if !instance::request_stop(&running) {
+                phase.fail("it could not be reached");
+                return Err(LauncherError::AlreadyRunning(format!("{}, but it could not be asked to stop", describe)));
+            }
+            log(&format!("Launcher: Asked the launcher with PID {} to stop so this one takes over", running.pid));
+            match InstanceLock::acquire_within(state_dir, TAKEOVER_TIMEOUT).map_err(LauncherError::AlreadyRunning)? {
+                Some(lock) => {
+                    phase.finish(&format!("PID {}", running.pid));
+                    Ok(Some(lock))
+                }
+                None => {
+                    phase.fail("still running");
+                    Err(LauncherError::AlreadyRunning(format!(
+                        "{} and did not stop within {}s",
+                        describe,
+                        TAKEOVER_TIMEOUT.as_secs()
+                    )))
+                }
+            }
+        }
+    }
+}
+
+/// Deal with a backend an earlier launch left running as `--if-running` says, or ask.
+/// Returns whether it is gone so a new one can start; `false` keeps it in use.
+fn replace_left_backend(cli: &Cli, console: &Console, runner: &dyn ProcessRunner, left: &LeftBackend) -> Result<bool, LauncherError> {
+    let describe = left.describe();
+    log(&format!("Launcher: {}", describe));
+    let action = match cli.if_running {
+        Some(action) => action,
+        None if cli.headless => IfRunning::Reuse,
+        None => match dialogs::confirm(
+            "Voicebox Backend Still Running",
+            &format!("{}.\n\nStop it and start a new one? Choose No to keep using it.", describe),
+        ) {
+            true => IfRunning::TakeOver,
+            false => IfRunning::Reuse,
+        },
+    };
+    match action {
+        IfRunning::Fail => Err(LauncherError::AlreadyRunning(describe)),
+        IfRunning::Reuse => {
+            log("Launcher: Leaving the left-behind backend running");
+            console.success(&format!("{}; using it", describe));
+            Ok(false)
+        }
+        IfRunning::TakeOver => {
+            let phase = console.phase("Stopping the left-behind backend");
+            match zombie::terminate(runner, left, SHUTDOWN_GRACE) {
+                Ok(()) => {
+                    phase.finish("stopped");
+                    Ok(true)
+                }
+                Err(e) => {
+                    phase.fail("still running");
+                    Err(LauncherError::AlreadyRunning(e))
+                }
+            }
+        }
+    }
+}
+
+/// Log the error and tell the user about it, returning the process exit code
+fn report_error(e: &LauncherError, headless: bool) -> i32 {
+    // One-shot commands report on stderr only and leave the launch log alone
+    if e.phase() != LaunchPhase::Command {
+        log(&format!("Launcher: {} failed: {}", e.phase(), e));
+        if let Some(hint) = e.hint() {
+            log(&format!("Launcher: {}", hint));
+        }
+    }
+    // Headless callers get the JSON log lines only
+    if !headless || e.phase() == LaunchPhase::Command {
+        let console = Console::detect(headless);
+        console.error(&e.to_string());
+        if let Some(hint) = e.hint() {
+            console.hint(&hint);
+        }
+    }
+    e.exit_code()
 }
 
 fn main() {
-    let _ = std::fs::remove_file(get_log_path()); // Start fresh on new run
+    let mut cli = Cli::parse();
+    // A Windows caller may hand a WSL launcher C:\ style paths
+    cli.data_dir = cli.data_dir.take().map(host_path);
+    cli.shared_models = cli.shared_models.take().map(host_path);
+
+    let headless = cli.headless;
+    let code = match run(cli) {
+        Ok(code) => code,
+        Err(e) => report_error(&e, headless),
+    };
+    std::process::exit(code);
+}
+
+/// Run a subcommand, or launch the backend and wait for it. Returns the exit code to
+/// finish with.
+fn run(cli: Cli) -> Result<i32, LauncherError> {
+    if cli.dry_run && cli.command.is_none() {
+        return dry_run(&cli);
+    }
+    match &cli.command {
+        Some(Commands::Speak { text, preset }) => return speak(&cli, text, preset).map(|_| 0),
+        Some(Commands::Presets) => return list_presets(&cli).map(|_| 0),
+        Some(Commands::Projects) => return list_projects(&cli).map(|_| 0),
+        Some(Commands::Lock { project }) => return lock_project(&cli, project, true).map(|_| 0),
+        Some(Commands::Unlock { project }) => return lock_project(&cli, project, false).map(|_| 0),
+        Some(Commands::Jobs) => return list_jobs(&cli).map(|_| 0),
+        Some(Commands::CondaEnvs) => return list_conda_envs(&cli).map(|_| 0),
+        Some(Commands::Resume { id }) => return resume_job(&cli, id).map(|_| 0),
+        Some(Commands::FineTune { dataset, output, resume }) => {
+            return fine_tune(&cli, dataset.as_deref(), output.as_deref(), resume.as_deref())
+        }
+        Some(Commands::FineTunes) => return list_fine_tunes(&cli).map(|_| 0),
+        Some(Commands::Status { json }) => return backend_status(&cli, *json),
+        Some(Commands::Watermark { input, output, generation_id }) => {
+            return watermark_file(&cli, input, output.as_deref(), generation_id.as_deref()).map(|_| 0)
+        }
+        Some(Commands::Verify { file }) => return verify_file(file),
+        Some(Commands::Rerender { file, output }) => return rerender(&cli, file, output.as_deref()).map(|_| 0),
+        Some(Commands::Compare { text, a, b, voice_a, voice_b, output_dir }) => {
+            let variants = [
+                Variant { preset: a, voice: voice_a.as_deref() },
+                Variant { preset: b, voice: voice_b.as_deref() },
+            ];
+            return compare_render(&cli, text, variants, output_dir.as_deref()).map(|_| 0);
+        }
+        Some(Commands::Render { script, voices, languages, output_dir, report, force, workers }) => {
+            let options = RenderOptions {
+                output_dir: output_dir.as_deref(),
+                report: report.as_deref(),
+                voices: voices.as_deref(),
+                languages,
+                force: *force,
+                workers: *workers,
+            };
+            return render_script(&cli, script, options);
+        }
+        Some(Commands::Rpc) => return serve_rpc(&cli).map(|_| 0),
+        Some(Commands::Mcp) => return serve_mcp(&cli).map(|_| 0),
+        Some(Commands::Sapi { unregister }) => return sapi_voices(&cli, *unregister).map(|_| 0),
+        Some(Commands::Speechd { install }) => return speechd_module(&cli, *install).map(|_| 0),
+        Some(Commands::SpeechdSay { voice, language, output }) => {
+            return speechd_say(&cli, voice, language.as_deref(), output).map(|_| 0)
+        }
+        Some(Commands::Pair { list, revoke }) => return pair_device(&cli, *list, revoke.as_deref()).map(|_| 0),
+        Some(Commands::Mirror { name, dry_run }) => return run_mirrors(&cli, name.as_deref(), *dry_run),
+        Some(Commands::Completions { shell }) => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(*shell, &mut command, name, &mut std::io::stdout());
+            return Ok(0);
+        }
+        // Runs as part of the launch sequence, which stops after it
+        Some(Commands::Setup { .. }) => {}
+        Some(Commands::Man) => {
+            clap_mangen::Man::new(Cli::command())
+                .render(&mut std::io::stdout())
+                .map_err(LauncherError::Output)?;
+            return Ok(0);
+        }
+        None => {}
+    }
+
+    // Everything we generate goes to per-user writable locations, never the install dir
+    let paths = LauncherPaths::resolve(cli.data_dir.as_deref());
+    let dirs_result = paths.create_dirs();
+    if dirs_result.is_ok() {
+        set_log_path(paths.log_dir.join(LOG_FILE_NAME));
+    }
+
+    set_structured(cli.headless);
+    let console = Console::detect(cli.headless);
+
+    // Until this launcher holds the data directory, the log belongs to the one running
+    let instance = match InstanceLock::try_acquire(&paths.state_dir) {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => match claim_running_instance(&paths.state_dir, cli.if_running.unwrap_or_default(), &console)? {
+            Some(lock) => Some(lock),
+            None => return Ok(0),
+        },
+        Err(e) => {
+            console.warn(&format!("{}; not checking for another launcher", e));
+            None
+        }
+    };
+
+    let _ = std::fs::remove_file(log_path()); // Start fresh on new run
     log("Launcher: Starting Voicebox Server wrapper...");
+    if cli.headless {
+        log("Launcher: Headless mode, no dialogs will be shown");
+    }
+    if let Err(e) = dirs_result {
+        log(&format!("Launcher: {}", e));
+    }
+    log(&format!("Launcher: Writing generated files under {:?}", paths.data_dir));
+    if running_in_wsl() {
+        log("Launcher: Running inside WSL; Windows paths are translated to /mnt/<drive>");
+    }
+    if let Some(warning) = check_data_dir(&paths.data_dir) {
+        log(&format!("Launcher: WARNING: {}", warning.message));
+        console.warn(&warning.message);
+    }
+
+    let config = LauncherConfig::load_from_data_dir(&paths.data_dir).unwrap_or_else(|e| {
+        log(&format!("Launcher: {}; continuing with defaults", e));
+        console.warn(&e);
+        LauncherConfig::default()
+    });
+    set_level(config.log_level);
+    if cli.debug_env {
+        let config_file = LauncherConfig::path(&paths.data_dir);
+        let config_error = LauncherConfig::load(&config_file).err();
+        log_section("environment", &EnvDump::collect(&paths, ConfigLayers::new(&cli, config_file, &config, config_error)));
+    }
+    let exit_hook = cli.exit_hook().or(config.on_backend_exit.clone());
 
     // 1. Locate the executable and base directories
     let exe_path = env::current_exe().unwrap_or_else(|e| {
@@ -33,195 +1627,438 @@ fn main() {
     log(&format!("Launcher: Executable Dir at {:?}", exe_dir));
 
     // 2. Locate the 'backend' directory
-    let possible_paths = vec![
-        exe_dir.join("resources").join("backend"),      // Windows installed
-        exe_dir.join("backend"),                        // Dev/Flat
-        exe_dir.parent().unwrap_or(exe_dir).join("resources").join("backend"), 
-        exe_dir.parent().unwrap_or(exe_dir).join("backend"),
-    ];
-
-    let mut backend_path: Option<PathBuf> = None;
-    for p in &possible_paths {
-        if p.exists() {
-            log(&format!("Launcher: Found backend at {:?}", p));
-            backend_path = Some(p.clone());
-            break;
-        } else {
-            log(&format!("Launcher: Checked {:?} (not found)", p));
+    let phase = console.phase("Locating backend");
+    let backend_dir = match find_backend_dir(exe_dir) {
+        Some(dir) => {
+            phase.finish(&dir.display().to_string());
+            dir
         }
-    }
+        None => {
+            phase.fail("not found next to the executable");
+            return Err(LauncherError::BackendNotFound { searched: backend_candidates(exe_dir) });
+        }
+    };
+    verify_backend(&backend_dir, &cli, &console)?;
 
-    if backend_path.is_none() {
-        log("Error: 'backend' directory not found in any expected location.");
-        std::process::exit(1);
+    match read_backend_version(&backend_dir) {
+        Ok(version) => {
+            log(&format!("Launcher: Backend version {} (API {:?})", version.version, version.api_version));
+            if let Err(e) = check_compatible(&version) {
+                if !cli.skip_version_check {
+                    return Err(LauncherError::IncompatibleBackend(e));
+                }
+                log(&format!("Launcher: WARNING: {}; continuing because of --skip-version-check", e));
+                console.warn(&e);
+            }
+        }
+        Err(e) => log(&format!("Launcher: Could not determine backend version: {}", e)),
     }
-
-    let backend_dir = backend_path.unwrap();
     
-    // We need to run `python -m backend.main`.
-    // This requires the cwd to be the PARENT of the `backend` folder.
+    // We need to run `python -m backend.main` with the PARENT of the `backend` folder
+    // importable. It goes on PYTHONPATH and is also used as the cwd when Win32 allows.
     let root_dir = backend_dir.parent().unwrap();
-    log(&format!("Launcher: Setting CWD to {:?}", root_dir));
+    let cwd = if root_dir.as_os_str().len() < MAX_PATH { root_dir } else { paths.data_dir.as_path() };
+    log(&format!("Launcher: Setting CWD to {:?}", cwd));
+    if !is_dir_writable(&backend_dir) {
+        log("Launcher: Backend directory is read-only, which is fine for installed builds");
+    }
+
+    let project = open_project(&cli, &config, &paths, &console)?;
+    let locked = project.as_ref().is_some_and(|project| project.locked);
+    let backend_data_dir = project.map_or_else(|| paths.data_dir.clone(), |project| project.dir);
 
-    let args: Vec<String> = env::args().skip(1).collect();
-    let python_cmd = "python"; // Assume global python
+    let mut proxy_config = config.proxy.clone();
+    proxy_config.enabled |= cli.proxy;
+    proxy_config.read_only |= locked;
+    proxy_config.cors.allow_any_localhost |= cli.dev;
+    let proxy_backend_port = proxy_config.enabled.then_some(proxy_config.backend_port);
+    let args = cli.backend_args(&config.workspace.to_backend(&backend_data_dir), proxy_backend_port);
+    let runner = SystemRunner;
+    let preflight = TimeoutRunner::new(&runner, preflight_timeout(&cli, &config));
+    let choice = PythonChoice::from_env(cli.conda_env.clone().or(config.conda_env.clone()));
+    let python = find_python(&preflight, &backend_dir, &choice).map_err(LauncherError::PythonUnusable)?;
+    let python = match choice.uses_as_is(&python) {
+        true => python,
+        false => picked_python(&preflight, &backend_dir, &choice, &paths.state_dir, &cli, python),
+    };
+    log(&format!("Launcher: Using Python {}", python.describe()));
+    let python_cmd = python.python.as_path();
+    let python_spec = python.command();
+
+    check_interpreter(python_cmd).map_err(LauncherError::PythonUnusable)?;
+
+    let phase = console.phase("Checking Python version");
+    match python_version(&preflight, &python_spec) {
+        Ok(version) => {
+            log(&format!("Launcher: Python version {}", version));
+            if let Err(e) = check_min_version(python_cmd, version) {
+                phase.fail(&format!("Python {} is too old", version));
+                if !cli.headless {
+                    dialogs::error("Python Too Old", &format!("{}.\n\nInstall a newer Python from python.org and start Voicebox again.", e));
+                }
+                return Err(LauncherError::PythonUnusable(e));
+            }
+            phase.finish(&version.to_string());
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            phase.fail("Python did not respond");
+            return Err(LauncherError::PythonUnusable(hung_python_message(python_cmd, &e)));
+        }
+        Err(e) => {
+            // Leave it to the dependency check and spawn to report an interpreter that doesn't run
+            phase.skip("could not determine it");
+            log(&format!("Launcher: Could not determine the Python version: {}", e));
+        }
+    }
+
+    let package_index = PackageIndexConfig::resolve(&config.package_index);
+    // `python` is the interpreter packages go into, which decides whether PyTorch is needed
+    let install_plan = |python: &CommandSpec| InstallPlan {
+        installer: Installer::detect(&preflight, cli.installer.unwrap_or(config.installer), exe_dir),
+        source: PackageSource::choose(&backend_dir, || index_reachable(&package_index)),
+        torch: torch_to_install(&preflight, python),
+        index: package_index.clone(),
+    };
+
+    // An interpreter the user picked explicitly, or the embedded one, is used as is
+    let venv = ManagedVenv::new(&paths.venv_dir, &paths.state_dir);
+    let use_venv = use_managed_venv(&cli, &config, &choice, &python, &venv);
+
+    // The first-run setup, or what is left of one that was interrupted
+    let setup = Setup::new(&paths.state_dir);
+    let setup_only = matches!(cli.command, Some(Commands::Setup { .. }));
+    if let Some(Commands::Setup { reset: true }) = &cli.command {
+        setup.reset().map_err(LauncherError::Setup)?;
+    }
+    if !setup.is_complete() {
+        let mut actions = LauncherSetup {
+            runner: &runner,
+            preflight: &preflight,
+            python: &python,
+            venv: use_venv.then_some(&venv),
+            backend_dir: &backend_dir,
+            paths: &paths,
+            cli: &cli,
+            config: &config,
+            console: &console,
+            install_plan: &install_plan,
+        };
+        let mut on_step = |step: SetupStep| log(&format!("Launcher: Setup: {}", step.label()));
+        setup.run(&mut actions, &mut on_step).map_err(LauncherError::Setup)?;
+        log("Launcher: Setup complete");
+    }
+    if setup_only {
+        console.success("Setup complete");
+        return Ok(0);
+    }
+
+    let python = match use_venv {
+        true => {
+            let python = managed_python(&runner, &venv, &python, &backend_dir, &paths.state_dir, &console, install_plan)?;
+            log(&format!("Launcher: Using Python {}", python.describe()));
+            python
+        }
+        false => python,
+    };
+    let python_cmd = python.python.as_path();
+    let python_spec = python.command();
+    if cli.debug_env {
+        let details = serde_json::json!({
+            "interpreter": python.describe(),
+            "backend_dir": backend_dir,
+            "cwd": cwd,
+            "args": args.iter().map(|arg| arg.to_string_lossy()).collect::<Vec<_>>(),
+            "probe": probe_python(&preflight, &python_spec),
+        });
+        log_section("python", &details);
+    }
 
     // 3. Pre-flight dependency check & Auto-install
     log("Launcher: Performing pre-flight dependency check...");
+    let phase = console.phase("Checking dependencies");
     
-    let check_script = "
-import sys
-try:
-    import fastapi, uvicorn, sqlalchemy, alembic, python_multipart, numpy
-except ImportError:
-    sys.exit(1)
-";
-    let check_cmd = Command::new(python_cmd)
-        .arg("-c")
-        .arg(check_script)
-        .output();
-
-    if let Ok(output) = check_cmd {
-        if !output.status.success() {
+    let requirements = read_requirements(&backend_dir);
+    let check_hash = requirements_hash(&backend_dir, &python_spec);
+    let check_cache = DependencyCheckCache::new(&paths.state_dir);
+    let passed_at = if cli.recheck_deps { None } else { check_cache.passed(&check_hash) };
+    let check = match passed_at {
+        Some(_) => None,
+        None => Some(check_dependencies(&preflight, &python_spec, &requirements).inspect_err(|e| log(&format!("Launcher: {}", e)))),
+    };
+    if let Some(Err(e)) = check.as_ref().filter(|check| check.as_ref().is_err_and(|e| e.kind() == std::io::ErrorKind::TimedOut)) {
+        phase.fail("Python did not respond");
+        return Err(LauncherError::PythonUnusable(hung_python_message(python_cmd, e)));
+    }
+    if let Some(passed_at) = passed_at {
+        phase.finish("requirements and interpreter unchanged");
+        log(&format!("Launcher: Skipping the dependency check, it passed at {} with the same requirements and interpreter", passed_at));
+    } else if let Some(Ok(report)) = check {
+        let deps_ok = report.is_ok();
+        if deps_ok {
+            phase.finish(&report.summary());
+            if let Err(e) = check_cache.record(&check_hash) {
+                log(&format!("Launcher: Failed to remember the dependency check: {}", e));
+            }
+        } else {
+            phase.fail(&report.summary());
+            for problem in report.details() {
+                log(&format!("Launcher: Dependency {}", problem));
+                console.hint(&problem);
+            }
+        }
+
+        if !deps_ok && cli.headless {
+            let requirements = Some(backend_dir.join("requirements.txt")).filter(|p| p.exists());
+            return Err(LauncherError::MissingDependencies { requirements });
+        } else if !deps_ok {
             log("Launcher: Missing dependencies. Prompting user...");
             
-            // Show Native Dialog via PowerShell
-            let ps_script = "
-Add-Type -AssemblyName System.Windows.Forms
-$result = [System.Windows.Forms.MessageBox]::Show('Voicebox requires Python dependencies (FastAPI, SQLAlchemy, etc.) that are missing in your global environment.\n\nDo you want to install them now using pip?\n(This will try to protect your existing PyTorch installation)', 'Missing Dependencies', 'YesNo', 'Question')
-Write-Output $result
-";
-            let ps_output = Command::new("powershell")
-                .args(&["-NoProfile", "-Command", ps_script])
-                .output();
-            
-            match ps_output {
-                Ok(out) => {
-                    let result = String::from_utf8_lossy(&out.stdout).trim().to_string();
-                    log(&format!("Launcher: User response: {}", result));
-
-                    if result == "Yes" {
-                        log("Launcher: Starting dependency installation...");
-                        
-                        let req_path = backend_dir.join("requirements.txt");
-                        if req_path.exists() {
-                            let safe_req_path = backend_dir.join("requirements_install.txt");
-                            
-                            // Filter out torch lines to prevent overwrites
-                            let mut made_safe_file = false;
-                            if let Ok(content) = std::fs::read_to_string(&req_path) {
-                                let filtered_lines: Vec<&str> = content.lines()
-                                    .filter(|l| !l.trim().starts_with("torch"))
-                                    .collect();
-                                let filtered_content = filtered_lines.join("\n");
-                                if std::fs::write(&safe_req_path, filtered_content).is_ok() {
-                                    made_safe_file = true;
-                                }
-                            }
-                            
-                            let install_target = if made_safe_file { safe_req_path.clone() } else { req_path };
-
-                            log("Launcher: Creating installation batch file...");
-                            let bat_path = backend_dir.join("install_deps.bat");
-                            let batch_content = format!(
-                                "@echo off\r\n\
-                                 title Voicebox Dependency Installer\r\n\
-                                 echo Installing missing Python dependencies...\r\n\
-                                 echo Target: {}\r\n\
-                                 pip install -r \"{}\"\r\n\
-                                 if %errorlevel% neq 0 (\r\n\
-                                    echo.\r\n\
-                                    echo Installation FAILED. Please check the error messages above.\r\n\
-                                    pause\r\n\
-                                    exit /b %errorlevel%\r\n\
-                                 )\r\n\
-                                 echo.\r\n\
-                                 echo Installation successful!\r\n\
-                                 timeout /t 5\r\n",
-                                install_target.display(),
-                                install_target.display()
-                            );
-                            
-                            if let Err(e) = std::fs::write(&bat_path, batch_content) {
-                                log(&format!("Launcher: Failed to write batch file: {}", e));
-                            } else {
-                                log("Launcher: Running batch file...");
-                                let _ = Command::new("cmd")
-                                    .args(&["/C", "start", "/wait", "cmd", "/c", &bat_path.display().to_string()])
-                                    .status();
-                                
-                                let _ = std::fs::remove_file(bat_path);
-                            }
-                                
-                            if made_safe_file {
-                                let _ = std::fs::remove_file(safe_req_path);
-                            }
-                        } else {
-                            log("Launcher: Warning: requirements.txt not found.");
+            let install = dialogs::confirm(
+                "Missing Dependencies",
+                &format!(
+                    "Voicebox requires Python packages that are missing or outdated in your environment:\n\n{}\n\nDo you want to install them now?\n(This will try to protect your existing PyTorch installation)",
+                    report.details().join("\n")
+                ),
+            );
+            if install {
+                log("Launcher: Starting dependency installation...");
+                
+                let req_path = backend_dir.join("requirements.txt");
+                if req_path.exists() {
+                    // Filter out torch lines to prevent overwrites
+                    let safe_req_path = match write_filtered_requirements(&req_path, &paths.work_dir) {
+                        Ok(path) => Some(path),
+                        Err(e) => {
+                            log(&format!("Launcher: {}", e));
+                            None
+                        }
+                    };
+
+                    let install_target = long_path(&safe_req_path.clone().unwrap_or(req_path));
+
+                    let plan = install_plan(&python_spec);
+                    let phase = console.phase("Installing Python packages");
+                    let progress = TaskReporter::start(
+                        &paths.state_dir,
+                        "install",
+                        ProgressKind::Install,
+                        "Installing Python packages",
+                        Some(0.0),
+                    );
+                    let on_progress = |fraction: f32| progress.set(Some(fraction));
+                    match install_requirements(&runner, &plan, python_cmd, &install_target, &on_progress) {
+                        Ok(()) => {
+                            phase.finish("done");
+                            drop(progress);
+                        }
+                        Err(e) => {
+                            log(&format!("Launcher: {}", e));
+                            phase.fail("see the log for the installer's output");
+                            progress.fail();
                         }
-                    } else {
-                        log("Launcher: User declined installation. Backend will likely fail.");
                     }
+
+                    if let Some(safe_req_path) = safe_req_path {
+                        let _ = std::fs::remove_file(safe_req_path);
+                    }
+                } else {
+                    log("Launcher: Warning: requirements.txt not found.");
                 }
-                Err(e) => {
-                    log(&format!("Launcher: Failed to show dialog: {}", e));
-                }
+            } else {
+                log("Launcher: User declined installation. Backend will likely fail.");
             }
         } else {
             log("Launcher: Dependencies look OK.");
         }
     } else {
+        phase.skip("could not run python");
         log("Launcher: Failed to run python check script. Is python installed?");
     }
 
+    let model_env = match &cli.shared_models {
+        Some(dir) => {
+            let env = shared_models_env(dir).map_err(LauncherError::InvalidConfig)?;
+            log(&format!("Launcher: Using shared read-only model cache at {:?}", dir));
+            env
+        }
+        None => Vec::new(),
+    };
+
+    // A backend of a launcher that is gone would hold the port; runtime state on disk
+    // is stale while this launcher holds the data directory and hasn't started its own
+    if instance.is_some() {
+        let state_file = runtime_state(&paths.state_dir);
+        let stale = state_file.read().ok().flatten();
+        let port = proxy_backend_port.unwrap_or(cli.port.unwrap_or(DEFAULT_PORT));
+        if let Some(left) = zombie::find(&preflight, stale.as_ref(), port) {
+            if !replace_left_backend(&cli, &console, &preflight, &left)? {
+                return Ok(0);
+            }
+        }
+        if let Some(stale) = stale {
+            let _ = state::withdraw(&paths.state_dir, stale.pid);
+        }
+    }
+
+    if proxy_config.enabled {
+        let filter = ContentFilter::new(&config.content_filter).map_err(LauncherError::InvalidConfig)?;
+        let host = cli.host.as_deref().unwrap_or("127.0.0.1");
+        format!("{}:{}", host, cli.port.unwrap_or(DEFAULT_PORT))
+            .parse()
+            .map_err(|e| format!("Invalid listen address {}: {}", host, e))
+            .and_then(|addr| proxy::spawn(proxy_config.clone(), addr, &paths.state_dir, config.presets.clone(), filter))
+            .map_err(LauncherError::Proxy)?;
+    }
+
     // 4. Execute Server
-    log(&format!("Launcher: Running '{} -m backend.main' with args: {:?}", python_cmd, args));
+    log(&format!("Launcher: Running '{} -m backend.main' with args: {:?}", python_cmd.display(), args));
     
-    let mut cmd = Command::new(python_cmd);
-    cmd.arg("-m")
-       .arg("backend.main")
-       .args(&args)
-       .current_dir(root_dir)
-       .stdout(Stdio::piped())
-       .stderr(Stdio::piped());
-
-    match cmd.spawn() {
-        Ok(mut child) => {
-            log("Launcher: Python process spawned. Monitoring output...");
-            
-            let stdout = child.stdout.take().expect("Failed to capture stdout");
-            let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-            std::thread::spawn(move || {
-                use std::io::{BufRead, BufReader};
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    if let Ok(l) = line {
-                        log(&format!("STDOUT: {}", l));
-                        println!("{}", l);
-                    }
+    let backend = python_spec
+        .clone()
+        .args(["-m", "backend.main"])
+        .args(&args)
+        .current_dir(cwd)
+        .env("PYTHONPATH", python_path_with(root_dir))
+        .env("PYTHONUTF8", "1")
+        .env(DB_PATH_ENV, config.workspace.to_backend(&backend_data_dir.join(DB_FILE_NAME)))
+        .envs(model_env)
+        .own_process_group();
+    let backend = match config.require_voice_consent {
+        true => backend.env(REQUIRE_CONSENT_ENV, "1"),
+        false => backend,
+    };
+    let backend = match locked {
+        true => backend.env(READ_ONLY_ENV, "1"),
+        false => backend,
+    };
+    let backend = match config.backend_log_level {
+        Some(level) => backend.env(LOG_LEVEL_ENV, level.as_str()),
+        None => backend,
+    };
+    let gpus = match config.devices.wants_gpu() {
+        true => GpuInfo::detect(&preflight),
+        false => GpuInfo::default(),
+    };
+    let devices = config.devices.plan(&gpus);
+    for warning in &devices.warnings {
+        log(&format!("Launcher: {}", warning));
+        console.warn(warning);
+    }
+    let backend = backend.envs(devices.env);
+
+    // From here on Ctrl+C and SIGTERM stop the backend before the launcher exits
+    if let Err(e) = shutdown::install_handler() {
+        log(&format!("Launcher: {}", e));
+    }
+
+    // A crashed backend is started again as the restart policy allows
+    let mut supervisor = Supervisor::new(config.restart.clone());
+    loop {
+        let phase = console.phase(if supervisor.restarts() == 0 { "Starting backend" } else { "Restarting backend" });
+        let started_at = std::time::Instant::now();
+        let mut child = match with_io_retry("Spawning python process", || runner.spawn(&backend)) {
+            Ok(child) => child,
+            Err(e) => {
+                phase.fail(&e.to_string());
+                return Err(LauncherError::Spawn(e));
+            }
+        };
+        log("Launcher: Python process spawned. Monitoring output...");
+        phase.finish(&format!("PID {}", child.id()));
+        let port = cli.port.unwrap_or(DEFAULT_PORT);
+
+        let state = RuntimeState {
+            pid: std::process::id(),
+            backend_pid: child.id(),
+            host: cli.host.clone().unwrap_or_else(|| "127.0.0.1".to_string()),
+            port,
+            backend_port: proxy_backend_port,
+            interpreter: Some(python_cmd.to_path_buf()),
+            data_dir: paths.data_dir.clone(),
+            log_path: log_path(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            restarts: supervisor.restarts(),
+        };
+        if let Err(e) = state::publish(&paths.state_dir, &state) {
+            log(&format!("Launcher: {}", e));
+        }
+
+        let stdout = child.take_stdout().expect("Failed to capture stdout");
+        let stderr = child.take_stderr().expect("Failed to capture stderr");
+
+        let filter = config.backend_log.clone();
+        std::thread::spawn(move || {
+            use std::io::{BufRead, BufReader};
+            let reader = BufReader::new(stdout);
+            for l in reader.lines().map_while(Result::ok) {
+                if pass_through("STDOUT", &l, &filter) {
+                    println!("{}", l);
+                    announce_if_ready(&console, port, &l);
                 }
-            });
+            }
+        });
 
-            std::thread::spawn(move || {
-                use std::io::{BufRead, BufReader};
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(l) = line {
-                        log(&format!("STDERR: {}", l));
-                        eprintln!("{}", l);
-                    }
+        let filter = config.backend_log.clone();
+        std::thread::spawn(move || {
+            use std::io::{BufRead, BufReader};
+            let reader = BufReader::new(stderr);
+            for l in reader.lines().map_while(Result::ok) {
+                if pass_through("STDERR", &l, &filter) {
+                    eprintln!("{}", l);
+                    // Uvicorn logs to stderr
+                    announce_if_ready(&console, port, &l);
                 }
-            });
+            }
+        });
 
-            let status = child.wait().expect("Failed to wait on child process");
-            log(&format!("Launcher: Process exited with code {:?}", status.code()));
-            std::process::exit(status.code().unwrap_or(1));
+        let backend_port = proxy_backend_port.unwrap_or(port);
+        let status = loop {
+            if let Some(status) = child.try_wait().expect("Failed to wait on child process") {
+                break status;
+            }
+            if shutdown::requested() {
+                let ask = || shutdown::request_backend_shutdown(backend_port);
+                break shutdown::stop(child.as_mut(), ask, SHUTDOWN_GRACE).map_err(LauncherError::Stop)?;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        };
+        log(&format!("Launcher: Process exited with code {:?}", status.code));
+        if let Err(e) = state::withdraw(&paths.state_dir, state.pid) {
+            log(&format!("Launcher: {}", e));
         }
-        Err(e) => {
-            log(&format!("Launcher: Failed to spawn python process: {}", e));
-            log("Make sure 'python' is in your system PATH.");
-            std::process::exit(1);
+        if shutdown::requested() {
+            console.success("Backend stopped");
+            return Ok(0);
+        }
+
+        let report = ExitReport::new(status, child.id(), port, started_at.elapsed(), log_path());
+        if let Some(hook) = exit_hook.as_ref().filter(|_| report.is_unexpected()) {
+            run_exit_hook(&runner, hook, &report);
+        }
+        if !report.is_unexpected() {
+            return Ok(status.code.unwrap_or(1));
+        }
+        match supervisor.on_crash(report.uptime, std::time::Instant::now()) {
+            Decision::Restart { after, attempt } => {
+                let message = format!(
+                    "Backend {} after {}s; restarting in {}s (restart {})",
+                    report.reason().replace('_', " "),
+                    report.uptime.as_secs(),
+                    after.as_secs(),
+                    attempt
+                );
+                log(&format!("Launcher: {}", message));
+                console.warn(&message);
+                if shutdown::sleep(after) {
+                    return Ok(0);
+                }
+                READY_ANNOUNCED.store(false, Ordering::Relaxed);
+            }
+            Decision::GiveUp(reason) => {
+                log(&format!("Launcher: Not restarting the backend: {} (restarts: {})", reason, supervisor.restarts()));
+                return Ok(status.code.unwrap_or(1));
+            }
         }
     }
 }
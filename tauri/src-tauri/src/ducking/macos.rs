@@ -0,0 +1,44 @@
+use std::process::Command;
+
+/// Players whose volume can be set through AppleScript. macOS has no public per-app
+/// volume control, so only these are ducked.
+const PLAYERS: &[&str] = &["Music", "Spotify"];
+
+fn osascript(script: &str) -> Option<String> {
+    let output = Command::new("osascript").args(["-e", script]).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The players running at the time of ducking
+pub struct Mixer {
+    players: Vec<&'static str>,
+}
+
+impl Mixer {
+    pub fn open() -> Result<Self, String> {
+        let players: Vec<_> = PLAYERS
+            .iter()
+            .copied()
+            .filter(|player| osascript(&format!("application \"{}\" is running", player)).as_deref() == Some("true"))
+            .collect();
+        Ok(Self { players })
+    }
+
+    pub fn volumes(&self) -> Vec<f32> {
+        self.players
+            .iter()
+            .map(|player| {
+                osascript(&format!("tell application \"{}\" to get sound volume", player))
+                    .and_then(|volume| volume.parse::<f32>().ok())
+                    .map_or(1.0, |volume| volume / 100.0)
+            })
+            .collect()
+    }
+
+    pub fn set(&self, volumes: &[f32]) {
+        for (player, volume) in self.players.iter().zip(volumes) {
+            let volume = (volume * 100.0).round() as u32;
+            osascript(&format!("tell application \"{}\" to set sound volume to {}", player, volume));
+        }
+    }
+}
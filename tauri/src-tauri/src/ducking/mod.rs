@@ -0,0 +1,117 @@
+// Ducking of other applications' audio while Voicebox speaks.
+//
+// Each platform provides a `Mixer` over the volumes of the audio sessions of other
+// applications at the time of ducking. A ducking thread fades them down to a fraction
+// of their volume, holds them there until playback ends or is stopped, and fades them
+// back to what they were. Playback starting while audio is ducked extends the ducking
+// rather than ducking the already lowered volumes again.
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "macos")]
+use macos::Mixer;
+#[cfg(target_os = "windows")]
+use windows::Mixer;
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DUCK_FADE: Duration = Duration::from_millis(300);
+const RESTORE_FADE: Duration = Duration::from_millis(600);
+/// Volume changes per fade
+const FADE_STEPS: u32 = 10;
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+struct Mixer;
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+impl Mixer {
+    fn open() -> Result<Self, String> {
+        Err("Ducking other applications isn't supported on this platform".to_string())
+    }
+
+    fn volumes(&self) -> Vec<f32> {
+        Vec::new()
+    }
+
+    fn set(&self, _volumes: &[f32]) {}
+}
+
+/// What a running ducking thread is told
+enum Hold {
+    /// Keep the audio ducked until then
+    Until(Instant),
+    /// Restore now; playback was stopped
+    Release,
+}
+
+/// Volumes on the way from `from` to `to`, `step` of `steps` in
+fn blend(from: &[f32], to: &[f32], step: u32, steps: u32) -> Vec<f32> {
+    let t = step as f32 / steps as f32;
+    from.iter().zip(to).map(|(from, to)| from + (to - from) * t).collect()
+}
+
+fn fade(mixer: &Mixer, from: &[f32], to: &[f32], duration: Duration) {
+    for step in 1..=FADE_STEPS {
+        mixer.set(&blend(from, to, step, FADE_STEPS));
+        thread::sleep(duration / FADE_STEPS);
+    }
+}
+
+fn run(level: f32, mut until: Instant, holds: mpsc::Receiver<Hold>, opened: mpsc::Sender<Result<(), String>>) {
+    let mixer = match Mixer::open() {
+        Ok(mixer) => mixer,
+        Err(e) => {
+            let _ = opened.send(Err(e));
+            return;
+        }
+    };
+    let _ = opened.send(Ok(()));
+    let original = mixer.volumes();
+    let ducked: Vec<f32> = original.iter().map(|volume| volume * level).collect();
+    fade(&mixer, &original, &ducked, DUCK_FADE);
+    loop {
+        match holds.recv_timeout(until.saturating_duration_since(Instant::now())) {
+            Ok(Hold::Until(later)) => until = until.max(later),
+            // Playback ended, was stopped, or the app is closing
+            Ok(Hold::Release) | Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    fade(&mixer, &ducked, &original, RESTORE_FADE);
+}
+
+/// Ducks other applications for the playback of `AudioOutputState`
+#[derive(Default)]
+pub struct Ducker {
+    holds: Mutex<Option<mpsc::Sender<Hold>>>,
+}
+
+impl Ducker {
+    /// Lower other applications' audio to `level` of its volume for `duration`, or
+    /// extend the ducking already in place
+    pub fn duck(&self, level: f32, duration: Duration) -> Result<(), String> {
+        let until = Instant::now() + duration;
+        let mut holds = self.holds.lock().unwrap();
+        // A thread that finished has dropped its receiver, and the send fails
+        if holds.as_ref().is_some_and(|running| running.send(Hold::Until(until)).is_ok()) {
+            return Ok(());
+        }
+        let (sender, receiver) = mpsc::channel();
+        let (opened, was_opened) = mpsc::channel();
+        thread::spawn(move || run(level.clamp(0.0, 1.0), until, receiver, opened));
+        was_opened.recv().map_err(|_| "The ducking thread exited".to_string())??;
+        *holds = Some(sender);
+        Ok(())
+    }
+
+    /// Restore other applications' audio now
+    pub fn release(&self) {
+        if let Some(running) = self.holds.lock().unwrap().take() {
+            let _ = running.send(Hold::Release);
+        }
+    }
+}
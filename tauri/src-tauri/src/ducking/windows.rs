@@ -0,0 +1,65 @@
+use windows::core::Interface;
+use windows::Win32::Media::Audio::{
+    eMultimedia, eRender, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator, ISimpleAudioVolume,
+    MMDeviceEnumerator,
+};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+/// The volume controls of other processes' sessions on the default output device.
+/// COM is initialized for the thread that opens it, which must also use and drop it.
+pub struct Mixer {
+    sessions: Vec<ISimpleAudioVolume>,
+}
+
+impl Mixer {
+    pub fn open() -> Result<Self, String> {
+        unsafe {
+            CoInitializeEx(None, COINIT_MULTITHREADED).ok().map_err(|e| format!("Failed to initialize COM: {}", e))?;
+            let sessions = Self::other_sessions().map_err(|e| format!("Failed to list audio sessions: {}", e));
+            if sessions.is_err() {
+                CoUninitialize();
+            }
+            Ok(Self { sessions: sessions? })
+        }
+    }
+
+    unsafe fn other_sessions() -> windows::core::Result<Vec<ISimpleAudioVolume>> {
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let device = enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia)?;
+        let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+        let sessions = manager.GetSessionEnumerator()?;
+        let own_pid = std::process::id();
+        let mut volumes = Vec::new();
+        for i in 0..sessions.GetCount()? {
+            let control = sessions.GetSession(i)?;
+            let Ok(control) = control.cast::<IAudioSessionControl2>() else { continue };
+            // The system sounds session has PID 0 and is left alone too
+            match control.GetProcessId() {
+                Ok(pid) if pid != own_pid && pid != 0 => {}
+                _ => continue,
+            }
+            if let Ok(volume) = control.cast::<ISimpleAudioVolume>() {
+                volumes.push(volume);
+            }
+        }
+        Ok(volumes)
+    }
+
+    pub fn volumes(&self) -> Vec<f32> {
+        self.sessions.iter().map(|session| unsafe { session.GetMasterVolume() }.unwrap_or(1.0)).collect()
+    }
+
+    /// Sessions that ended meanwhile fail quietly
+    pub fn set(&self, volumes: &[f32]) {
+        for (session, volume) in self.sessions.iter().zip(volumes) {
+            let _ = unsafe { session.SetMasterVolume(*volume, std::ptr::null()) };
+        }
+    }
+}
+
+impl Drop for Mixer {
+    fn drop(&mut self) {
+        self.sessions.clear();
+        unsafe { CoUninitialize() };
+    }
+}
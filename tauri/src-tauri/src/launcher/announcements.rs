@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Event the app emits every announcement on, for the UI's live regions
+pub const ANNOUNCEMENT_EVENT: &str = "announcement";
+
+/// Something a screen reader user should hear about without looking for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StateChange {
+    ServerStarting,
+    ServerReady,
+    ServerStartFailed,
+    ServerStopped,
+    /// The server exited without being asked to
+    ServerCrashed,
+    RecordingStarted,
+    RecordingStopped,
+    JobFinished { label: String },
+    JobFailed { label: String },
+}
+
+/// How urgently assistive technology should speak an announcement, as in `aria-live`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Politeness {
+    /// Spoken once the user is idle
+    Polite,
+    /// Interrupts whatever is being spoken
+    Assertive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Announcement {
+    /// Stable across releases, so the UI can translate or reword by it
+    pub id: &'static str,
+    /// English text, used when the UI has no wording of its own for `id`
+    pub message: String,
+    pub politeness: Politeness,
+    /// Increases with each announcement, so repeating the same one still gets read
+    pub sequence: u64,
+}
+
+impl StateChange {
+    /// Message ID; never change one that has been released
+    pub fn id(&self) -> &'static str {
+        match self {
+            StateChange::ServerStarting => "server.starting",
+            StateChange::ServerReady => "server.ready",
+            StateChange::ServerStartFailed => "server.start_failed",
+            StateChange::ServerStopped => "server.stopped",
+            StateChange::ServerCrashed => "server.crashed",
+            StateChange::RecordingStarted => "recording.started",
+            StateChange::RecordingStopped => "recording.stopped",
+            StateChange::JobFinished { .. } => "job.finished",
+            StateChange::JobFailed { .. } => "job.failed",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            StateChange::ServerStarting => "Starting the voice server".to_string(),
+            StateChange::ServerReady => "Voice server ready".to_string(),
+            StateChange::ServerStartFailed => "The voice server failed to start".to_string(),
+            StateChange::ServerStopped => "Voice server stopped".to_string(),
+            StateChange::ServerCrashed => "The voice server stopped unexpectedly".to_string(),
+            StateChange::RecordingStarted => "Recording".to_string(),
+            StateChange::RecordingStopped => "Recording stopped".to_string(),
+            StateChange::JobFinished { label } => format!("{} finished", label),
+            StateChange::JobFailed { label } => format!("{} failed", label),
+        }
+    }
+
+    pub fn politeness(&self) -> Politeness {
+        match self {
+            StateChange::ServerStartFailed
+            | StateChange::ServerCrashed
+            | StateChange::RecordingStarted
+            | StateChange::JobFailed { .. } => Politeness::Assertive,
+            _ => Politeness::Polite,
+        }
+    }
+}
+
+/// Turns state changes into announcements, skipping a change that only repeats the
+/// last one, such as a stop reported both by the UI and the server
+#[derive(Default)]
+pub struct Announcer {
+    sequence: AtomicU64,
+    last: Mutex<Option<StateChange>>,
+}
+
+impl Announcer {
+    pub fn announce(&self, change: StateChange) -> Option<Announcement> {
+        let mut last = self.last.lock().unwrap();
+        if last.as_ref() == Some(&change) {
+            return None;
+        }
+        let announcement = Announcement {
+            id: change.id(),
+            message: change.message(),
+            politeness: change.politeness(),
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed) + 1,
+        };
+        *last = Some(change);
+        Some(announcement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announces_each_change_once() {
+        let announcer = Announcer::default();
+        let ready = announcer.announce(StateChange::ServerReady).unwrap();
+        assert_eq!((ready.id, ready.sequence, ready.politeness), ("server.ready", 1, Politeness::Polite));
+        assert!(announcer.announce(StateChange::ServerReady).is_none());
+
+        let job = StateChange::JobFinished { label: "Chapter 1".to_string() };
+        let finished = announcer.announce(job.clone()).unwrap();
+        assert_eq!((finished.id, finished.message.as_str()), ("job.finished", "Chapter 1 finished"));
+        assert!(announcer.announce(StateChange::JobFinished { label: "Chapter 2".to_string() }).is_some());
+        assert_eq!(announcer.announce(job).unwrap().sequence, 4);
+    }
+
+    #[test]
+    fn reads_changes_reported_by_the_ui() {
+        let change: StateChange = serde_json::from_str(r#"{"event":"recording_started"}"#).unwrap();
+        assert_eq!(change, StateChange::RecordingStarted);
+        let change: StateChange = serde_json::from_str(r#"{"event":"job_failed","label":"Export"}"#).unwrap();
+        assert_eq!(change.politeness(), Politeness::Assertive);
+    }
+}
@@ -0,0 +1,218 @@
+// Structured log lines from the backend.
+//
+// With JSON logging turned on in the backend every line it writes is an object such as
+// `{"level": "WARNING", "name": "backend.tts", "message": "..."}`. The reader threads
+// recognize those, log them with their level and module as fields instead of as opaque
+// text, and drop the ones below the level configured for their module, so chatty
+// libraries can be quieted without losing the backend's own warnings.
+//
+// How much the backend logs in the first place is its own log level, passed in
+// `VOICEBOX_LOG_LEVEL` when it starts and changed while it runs through
+// `/admin/log-level`.
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Environment variable the backend reads its log level from when it starts
+pub const LOG_LEVEL_ENV: &str = "VOICEBOX_LOG_LEVEL";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Python's logging levels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendLevel {
+    #[default]
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl BackendLevel {
+    /// The name the backend takes, e.g. `warning`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+
+    /// A level as Python and other loggers spell it, by name or number
+    pub fn parse(value: &Value) -> Option<Self> {
+        if let Some(number) = value.as_u64() {
+            return Some(match number {
+                0..=10 => Self::Debug,
+                11..=20 => Self::Info,
+                21..=30 => Self::Warning,
+                31..=40 => Self::Error,
+                _ => Self::Critical,
+            });
+        }
+        match value.as_str()?.to_ascii_lowercase().as_str() {
+            "debug" | "trace" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warning" | "warn" => Some(Self::Warning),
+            "error" => Some(Self::Error),
+            "critical" | "fatal" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BackendLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Debug => "DEBUG",
+            Self::Info => "INFO",
+            Self::Warning => "WARNING",
+            Self::Error => "ERROR",
+            Self::Critical => "CRITICAL",
+        })
+    }
+}
+
+/// One JSON log line of the backend
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendRecord {
+    pub level: BackendLevel,
+    /// Logger name, e.g. `backend.tts` or `uvicorn.error`
+    pub module: Option<String>,
+    pub message: String,
+    /// The line's other keys
+    pub fields: Map<String, Value>,
+}
+
+const LEVEL_KEYS: &[&str] = &["level", "levelname", "severity"];
+const MODULE_KEYS: &[&str] = &["name", "logger", "module"];
+const MESSAGE_KEYS: &[&str] = &["message", "msg", "event"];
+
+fn take_first(object: &mut Map<String, Value>, keys: &[&str]) -> Option<Value> {
+    keys.iter().find_map(|key| object.remove(*key))
+}
+
+impl BackendRecord {
+    /// `line` as a log record, if it is a JSON object with a message. Other lines, such as
+    /// tracebacks and output of libraries that print directly, are `None`.
+    pub fn parse(line: &str) -> Option<Self> {
+        if !line.trim_start().starts_with('{') {
+            return None;
+        }
+        let Ok(Value::Object(mut object)) = serde_json::from_str(line) else { return None };
+        let message = match take_first(&mut object, MESSAGE_KEYS)? {
+            Value::String(message) => message,
+            other => other.to_string(),
+        };
+        let level = take_first(&mut object, LEVEL_KEYS).and_then(|level| BackendLevel::parse(&level)).unwrap_or(BackendLevel::Info);
+        let module = take_first(&mut object, MODULE_KEYS).and_then(|module| module.as_str().map(str::to_string));
+        Some(Self { level, module, message, fields: object })
+    }
+
+    /// e.g. `WARNING backend.tts: Falling back to CPU`
+    pub fn summary(&self) -> String {
+        match &self.module {
+            Some(module) => format!("{} {}: {}", self.level, module, self.message),
+            None => format!("{} {}", self.level, self.message),
+        }
+    }
+}
+
+/// The `backend_log` section of the launcher config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendLogFilter {
+    /// Lowest level kept from modules without a level of their own
+    pub level: BackendLevel,
+    /// Levels by logger name; a name also covers its children, so `transformers`
+    /// applies to `transformers.modeling_utils`
+    pub modules: BTreeMap<String, BackendLevel>,
+}
+
+impl BackendLogFilter {
+    /// The level for `module`: that of its closest configured parent, or the default
+    pub fn level_for(&self, module: Option<&str>) -> BackendLevel {
+        let Some(module) = module else { return self.level };
+        self.modules
+            .iter()
+            .filter(|(name, _)| module == name.as_str() || module.strip_prefix(name.as_str()).is_some_and(|rest| rest.starts_with('.')))
+            .max_by_key(|(name, _)| name.len())
+            .map_or(self.level, |(_, level)| *level)
+    }
+
+    pub fn allows(&self, record: &BackendRecord) -> bool {
+        record.level >= self.level_for(record.module.as_deref())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LevelBody {
+    level: String,
+}
+
+async fn level_request(request: reqwest::RequestBuilder, url: &str) -> Result<BackendLevel, String> {
+    let body: LevelBody = request
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to reach {}: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))?;
+    BackendLevel::parse(&Value::String(body.level.clone())).ok_or_else(|| format!("Unknown log level '{}' from {}", body.level, url))
+}
+
+/// The log level of the backend at `base_url`
+pub async fn get_level(base_url: &str) -> Result<BackendLevel, String> {
+    let url = format!("{}/admin/log-level", base_url.trim_end_matches('/'));
+    level_request(reqwest::Client::new().get(&url), &url).await
+}
+
+/// Change the log level of the backend at `base_url` while it runs
+pub async fn set_level(base_url: &str, level: BackendLevel) -> Result<BackendLevel, String> {
+    let url = format!("{}/admin/log-level", base_url.trim_end_matches('/'));
+    let body = LevelBody { level: level.as_str().to_string() };
+    level_request(reqwest::Client::new().put(&url).json(&body), &url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_json_lines_from_python_loggers() {
+        let record = BackendRecord::parse(r#"{"levelname": "WARNING", "name": "backend.tts", "message": "Falling back to CPU", "device": "cuda:0"}"#).unwrap();
+        assert_eq!(record.level, BackendLevel::Warning);
+        assert_eq!(record.module.as_deref(), Some("backend.tts"));
+        assert_eq!(record.fields["device"], "cuda:0");
+        assert_eq!(record.summary(), "WARNING backend.tts: Falling back to CPU");
+
+        let record = BackendRecord::parse(r#"{"level": 40, "msg": "boom"}"#).unwrap();
+        assert_eq!((record.level, record.summary()), (BackendLevel::Error, "ERROR boom".to_string()));
+        assert!(BackendRecord::parse("INFO:     Uvicorn running on http://127.0.0.1:17493").is_none());
+        assert!(BackendRecord::parse(r#"{"progress": 0.5}"#).is_none());
+        assert!(BackendRecord::parse("{not json").is_none());
+    }
+
+    #[test]
+    fn filters_by_the_closest_module_level() {
+        let filter = BackendLogFilter {
+            level: BackendLevel::Info,
+            modules: BTreeMap::from([
+                ("transformers".to_string(), BackendLevel::Error),
+                ("transformers.generation".to_string(), BackendLevel::Warning),
+            ]),
+        };
+        let record = |level, module: &str| BackendRecord { level, module: Some(module.to_string()), message: String::new(), fields: Map::new() };
+        assert!(!filter.allows(&record(BackendLevel::Warning, "transformers.modeling_utils")));
+        assert!(filter.allows(&record(BackendLevel::Warning, "transformers.generation.utils")));
+        assert!(filter.allows(&record(BackendLevel::Info, "transformers_extra")));
+        assert!(!filter.allows(&record(BackendLevel::Debug, "backend.main")));
+        assert_eq!(filter.level_for(None), BackendLevel::Info);
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Features and limits a backend reports on `GET /capabilities`.
+///
+/// Fields missing from the response (older or newer backends) fall back to the
+/// conservative values of `Capabilities::default()`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Capabilities {
+    pub version: Option<String>,
+    pub api_version: u32,
+    pub languages: Vec<String>,
+    pub model_sizes: Vec<String>,
+    pub max_text_length: usize,
+    pub max_instruct_length: usize,
+    pub voice_cloning: bool,
+    pub streaming_tts: bool,
+    pub transcription: bool,
+    pub prompt_enhancer: bool,
+    pub gpu_available: bool,
+    /// Reference audio is refused until the profile has a consent record
+    pub consent_required: bool,
+}
+
+impl Default for Capabilities {
+    /// What every backend since 0.2.0 supports, used for backends without the endpoint
+    fn default() -> Self {
+        Self {
+            version: None,
+            api_version: 1,
+            languages: ["zh", "en", "ja", "ko", "de", "fr", "ru", "pt", "es", "it"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            model_sizes: vec!["1.7B".to_string(), "0.6B".to_string()],
+            max_text_length: 5000,
+            max_instruct_length: 500,
+            voice_cloning: true,
+            streaming_tts: false,
+            transcription: true,
+            prompt_enhancer: false,
+            gpu_available: false,
+            consent_required: false,
+        }
+    }
+}
+
+impl Capabilities {
+    pub fn supports_language(&self, language: &str) -> bool {
+        self.languages.iter().any(|l| l == language)
+    }
+}
+
+/// Ask the backend at `base_url` what it supports. Backends that predate the endpoint
+/// (404) get the defaults; anything else that fails is an error.
+pub async fn fetch(base_url: &str) -> Result<Capabilities, String> {
+    let url = format!("{}/capabilities", base_url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to query {}: {}", url, e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Capabilities::default());
+    }
+    response
+        .error_for_status()
+        .map_err(|e| format!("Failed to query {}: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid capabilities from {}: {}", url, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let caps: Capabilities =
+            serde_json::from_str(r#"{"api_version": 2, "streaming_tts": true, "languages": ["en"], "future": 1}"#)
+                .unwrap();
+        assert_eq!(caps.api_version, 2);
+        assert!(caps.streaming_tts);
+        assert!(caps.supports_language("en") && !caps.supports_language("de"));
+        assert_eq!(caps.max_text_length, 5000);
+    }
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Kept in the output directory, so the checkpoint travels with the files it describes
+pub const CHECKPOINT_FILE_NAME: &str = ".voicebox-render.jsonl";
+
+/// A script line's output file as it was written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderedFile {
+    /// Relative to the output directory
+    pub filename: String,
+    /// Of the `/generate` request the file was rendered from
+    pub request_sha256: String,
+    /// Of the file as written, so edited or replaced files are rendered again
+    pub sha256: String,
+    pub generation_id: String,
+    pub duration_secs: f64,
+}
+
+/// Files a script has already rendered into an output directory, so running it again
+/// after editing a few lines only renders those.
+///
+/// Stored as JSON lines appended one per file as soon as it is written: a batch of
+/// thousands of lines doesn't rewrite the whole checkpoint each time, and a run that is
+/// killed mid-write loses only its last line. Later lines win over earlier ones.
+pub struct RenderCheckpoint {
+    path: PathBuf,
+    files: HashMap<String, RenderedFile>,
+}
+
+pub fn sha256(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+pub fn request_sha256(request: &Map<String, Value>) -> String {
+    sha256(&serde_json::to_vec(request).unwrap_or_default())
+}
+
+impl RenderCheckpoint {
+    /// The checkpoint of `output_dir`; a missing file is an empty checkpoint and lines
+    /// that can't be read are ignored
+    pub fn load(output_dir: &Path) -> Self {
+        let path = output_dir.join(CHECKPOINT_FILE_NAME);
+        let files = std::fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<RenderedFile>(line).ok())
+            .map(|file| (file.filename.clone(), file))
+            .collect();
+        Self { path, files }
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The earlier render of `filename` if it came from the same request and the file
+    /// is still exactly what was written
+    pub fn unchanged(&self, filename: &str, request: &Map<String, Value>) -> Option<&RenderedFile> {
+        let file = self.files.get(filename)?;
+        if file.request_sha256 != request_sha256(request) {
+            return None;
+        }
+        let output_dir = self.path.parent().unwrap_or(Path::new("."));
+        let data = std::fs::read(output_dir.join(filename)).ok()?;
+        (sha256(&data) == file.sha256).then_some(file)
+    }
+
+    pub fn record(&mut self, file: RenderedFile) -> Result<(), String> {
+        let line = serde_json::to_string(&file).map_err(|e| e.to_string())?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut out| writeln!(out, "{}", line))
+            .map_err(|e| format!("Failed to update {}: {}", self.path.display(), e))?;
+        self.files.insert(file.filename.clone(), file);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> Map<String, Value> {
+        Map::from_iter([("text".to_string(), Value::String(text.to_string()))])
+    }
+
+    fn rendered(filename: &str, text: &str, data: &[u8]) -> RenderedFile {
+        RenderedFile {
+            filename: filename.to_string(),
+            request_sha256: request_sha256(&request(text)),
+            sha256: sha256(data),
+            generation_id: "g".to_string(),
+            duration_secs: 1.5,
+        }
+    }
+
+    #[test]
+    fn skips_only_files_that_match_their_request_and_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.wav"), b"a").unwrap();
+        std::fs::write(dir.path().join("b.wav"), b"b").unwrap();
+        let mut checkpoint = RenderCheckpoint::load(dir.path());
+        assert!(checkpoint.is_empty());
+        checkpoint.record(rendered("a.wav", "A", b"a")).unwrap();
+        checkpoint.record(rendered("b.wav", "B", b"old")).unwrap();
+        checkpoint.record(rendered("b.wav", "B", b"b")).unwrap();
+
+        // Survives a run that was killed halfway through writing a line
+        let path = dir.path().join(CHECKPOINT_FILE_NAME);
+        let mut content = std::fs::read_to_string(&path).unwrap();
+        content.push_str("{\"filename\": \"c.wa");
+        std::fs::write(&path, content).unwrap();
+
+        let checkpoint = RenderCheckpoint::load(dir.path());
+        assert_eq!(checkpoint.len(), 2);
+        assert_eq!(checkpoint.unchanged("a.wav", &request("A")).unwrap().duration_secs, 1.5);
+        assert!(checkpoint.unchanged("b.wav", &request("B")).is_some());
+        assert!(checkpoint.unchanged("a.wav", &request("A, edited")).is_none());
+        std::fs::write(dir.path().join("a.wav"), b"replaced").unwrap();
+        assert!(checkpoint.unchanged("a.wav", &request("A")).is_none());
+        assert!(checkpoint.unchanged("c.wav", &request("C")).is_none());
+    }
+}
@@ -0,0 +1,404 @@
+use crate::launcher::hooks::ExitHook;
+use crate::launcher::installer::InstallerChoice;
+use crate::launcher::instance::IfRunning;
+use clap::{Parser, Subcommand};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Parser)]
+#[command(name = "voicebox-server", version, about = "Launcher for the Voicebox Python backend")]
+pub struct Cli {
+    /// Data directory for database, profiles, generated audio and config
+    #[arg(long, global = true)]
+    pub data_dir: Option<PathBuf>,
+
+    /// Port the backend listens on
+    #[arg(long, global = true)]
+    pub port: Option<u16>,
+
+    /// Host to bind to (use 0.0.0.0 for remote access)
+    #[arg(long)]
+    pub host: Option<String>,
+
+    /// Never show dialogs or console windows; report problems through exit codes and
+    /// JSON log lines on stderr only
+    #[arg(long, env = "VOICEBOX_HEADLESS", value_parser = clap::builder::FalseyValueParser::new())]
+    pub headless: bool,
+
+    /// Program to run when the backend exits unexpectedly; overrides `on_backend_exit`
+    /// from the config file. Exit details are passed as VOICEBOX_* environment variables.
+    /// The value is the program alone; give its arguments with `--on-exit-arg`.
+    #[arg(long, value_name = "PROGRAM")]
+    pub on_exit: Option<String>,
+
+    /// Argument for the `--on-exit` program; repeat for each argument
+    #[arg(long, value_name = "ARG", requires = "on_exit", allow_hyphen_values = true)]
+    pub on_exit_arg: Vec<String>,
+
+    /// Serve the API through the launcher's reverse proxy (same as `proxy.enabled`)
+    #[arg(long)]
+    pub proxy: bool,
+
+    /// Development mode: the proxy accepts API calls from any localhost origin (the Vite
+    /// dev server) instead of only the packaged app
+    #[arg(long)]
+    pub dev: bool,
+
+    /// Start the backend even if its version doesn't match what this launcher supports
+    #[arg(long)]
+    pub skip_version_check: bool,
+
+    /// Restore backend files that don't match the installed manifest without asking
+    #[arg(long)]
+    pub repair: bool,
+
+    /// Run the dependency check even if it passed before with the same requirements.txt
+    /// and interpreter
+    #[arg(long)]
+    pub recheck_deps: bool,
+
+    /// Seconds a pre-flight check such as the dependency check may take before the
+    /// interpreter is considered hung and stopped (default 30)
+    #[arg(long, env = "VOICEBOX_PREFLIGHT_TIMEOUT", value_name = "SECS")]
+    pub preflight_timeout: Option<u64>,
+
+    /// Find the backend, interpreter, packages and port a launch would use and print
+    /// them as JSON, without installing or starting anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Log the environment, resolved paths, config and interpreter details at startup,
+    /// with credentials masked, for diagnosing a launch that fails on one machine
+    #[arg(long, env = "VOICEBOX_DEBUG_ENV", value_parser = clap::builder::FalseyValueParser::new())]
+    pub debug_env: bool,
+
+    /// Machine-wide, read-only Hugging Face model cache shared by all users
+    #[arg(long, value_name = "DIR")]
+    pub shared_models: Option<PathBuf>,
+
+    /// Open this project and run the backend on its database and audio, kept in the
+    /// root the config assigns it to. Without it the project opened last is used. The
+    /// launcher's own config, logs and state stay in the data dir.
+    #[arg(long, value_name = "NAME")]
+    pub project: Option<String>,
+
+    /// Run the backend in this conda or mamba environment, by name or prefix (same as
+    /// `conda_env` in the config file)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub conda_env: Option<String>,
+
+    /// Run the backend in a private virtual environment under the app data directory,
+    /// creating it and installing the requirements on first run (same as `managed_venv`
+    /// in the config file). Once created it is used on later launches too.
+    #[arg(long, env = "VOICEBOX_MANAGED_VENV", value_parser = clap::builder::FalseyValueParser::new())]
+    pub managed_venv: bool,
+
+    /// Forget the interpreter picked when several were found and ask again
+    #[arg(long)]
+    pub pick_python: bool,
+
+    /// Install the backend's packages with `uv` (much faster, used when found with
+    /// `auto`) or `pip`; same as `installer` in the config file
+    #[arg(long, value_enum, value_name = "TOOL")]
+    pub installer: Option<InstallerChoice>,
+
+    /// What to do when another launcher already runs on this data directory, or a
+    /// backend an earlier one left behind still does: leave it in charge and exit
+    /// (`reuse`), stop it and its backend and start anew (`take-over`), or exit with an
+    /// error (`fail`). Without it a left-behind backend is asked about, and another
+    /// launcher or a headless launch means `reuse`.
+    #[arg(long, env = "VOICEBOX_IF_RUNNING", value_enum, value_name = "ACTION")]
+    pub if_running: Option<IfRunning>,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Generate speech on a running backend using a named preset
+    Speak {
+        /// Text to synthesize
+        text: String,
+
+        /// Preset from the config file to resolve into request parameters
+        #[arg(long)]
+        preset: String,
+    },
+    /// List the presets defined in the config file
+    Presets,
+    /// List recent projects; the open one is marked with `*`
+    Projects,
+    /// Lock a finished project for review: the backend opens it read-only and refuses changes
+    Lock { project: String },
+    /// Allow changes to a locked project again
+    Unlock { project: String },
+    /// Show whether a launcher runs the backend on this data directory and whether it
+    /// answers; exits non-zero if not
+    Status {
+        /// Print the runtime state and the backend's health as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List batch jobs that were interrupted before finishing
+    Jobs,
+    /// List conda environments; the one `--conda-env` or the config selects is marked with `*`
+    CondaEnvs,
+    /// Continue an interrupted batch job on a running backend from its last completed segment
+    Resume {
+        /// Job id as shown by `jobs`
+        id: String,
+    },
+    /// Fine-tune a voice on a dataset exported from the app with the trainer configured
+    /// under `fine_tune`, until it finishes or Ctrl+C stops it to be continued later
+    FineTune {
+        /// Dataset directory with `metadata.csv` and `wavs/`
+        #[arg(required_unless_present = "resume")]
+        dataset: Option<PathBuf>,
+
+        /// Directory the trainer writes checkpoints to; defaults to `training` in the dataset
+        #[arg(long, short, conflicts_with = "resume")]
+        output: Option<PathBuf>,
+
+        /// Continue the run with this ID, as shown by `fine-tunes`, from its newest checkpoint
+        #[arg(long, value_name = "ID", conflicts_with = "dataset")]
+        resume: Option<String>,
+    },
+    /// List fine-tuning runs, newest first
+    FineTunes,
+    /// Watermark a generated WAV file as synthetic, signed with this user's key
+    Watermark {
+        input: PathBuf,
+        /// Where to write the watermarked file; defaults to replacing the input
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Generation ID to record in the watermark
+        #[arg(long)]
+        generation_id: Option<String>,
+    },
+    /// Check a WAV file for a Voicebox watermark; exits non-zero if none is found
+    Verify { file: PathBuf },
+    /// Render an exported file again on a running backend with the settings stored in it
+    Rerender {
+        file: PathBuf,
+        /// Where to save the new audio; without it only the generation is printed
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Render the same text with two presets side by side and report how they differ
+    Compare {
+        /// Text to synthesize
+        text: String,
+
+        /// Preset for rendering A
+        #[arg(long)]
+        a: String,
+
+        /// Preset for rendering B
+        #[arg(long)]
+        b: String,
+
+        /// Voice profile ID to use for A instead of the preset's
+        #[arg(long)]
+        voice_a: Option<String>,
+
+        /// Voice profile ID to use for B instead of the preset's
+        #[arg(long)]
+        voice_b: Option<String>,
+
+        /// Directory for the aligned renderings; defaults to the current directory
+        #[arg(long, short)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Render every line of a CSV or JSON dialogue script on a running backend as one
+    /// batch job. Columns: `text`, `filename`, `voice` and/or `preset`; any other column,
+    /// such as `language` or `seed`, is passed to the backend as a generation parameter.
+    /// With a voice table, lines name a `character` instead of a voice and the script is
+    /// rendered once per language into `<output dir>/<language>/`.
+    Render {
+        #[arg(long)]
+        script: PathBuf,
+
+        /// CSV (`character,en,de,...`) or JSON table of the voice each character
+        /// speaks with in each language
+        #[arg(long, value_name = "FILE")]
+        voices: Option<PathBuf>,
+
+        /// Language to render with the voice table; repeat for several. Defaults to
+        /// every language in the table.
+        #[arg(long = "language", value_name = "LANG", requires = "voices")]
+        languages: Vec<String>,
+
+        /// Directory the files are written to; defaults to the script's directory
+        #[arg(long, short)]
+        output_dir: Option<PathBuf>,
+
+        /// Where to write the results report; defaults to `render-report.json` in the
+        /// output directory
+        #[arg(long)]
+        report: Option<PathBuf>,
+
+        /// Render every line again, even those whose file is unchanged since the last run
+        #[arg(long)]
+        force: bool,
+
+        /// Lines to render at the same time, for a backend with several workers. They
+        /// are scheduled by the GPU memory each needs, as measured while rendering.
+        #[arg(long, default_value_t = 1, value_name = "N")]
+        workers: usize,
+    },
+    /// Serve speak, transcribe, list_voices and job_status as JSON-RPC 2.0 on stdin and
+    /// stdout, one message per line, for editor plugins. Talks to the running backend.
+    Rpc,
+    /// Serve synthesize, transcribe and list_voices as Model Context Protocol tools on
+    /// stdin and stdout, for LLM agent frontends. Talks to the running backend.
+    Mcp,
+    /// Register the backend's voice profiles as Windows SAPI voices, so screen readers
+    /// and other SAPI applications can speak with them. Needs the Voicebox SAPI engine
+    /// installed and the backend running.
+    Sapi {
+        /// Remove the Voicebox voices from SAPI instead
+        #[arg(long)]
+        unregister: bool,
+    },
+    /// Print a Speech Dispatcher module configuration offering the backend's voice
+    /// profiles, so Linux screen readers can speak with them
+    Speechd {
+        /// Write it to the user's Speech Dispatcher configuration and register it
+        #[arg(long)]
+        install: bool,
+    },
+    /// Speak the text on stdin into a WAV file; run by the Speech Dispatcher module
+    #[command(hide = true)]
+    SpeechdSay {
+        /// Voice profile ID
+        #[arg(long)]
+        voice: String,
+
+        #[arg(long)]
+        language: Option<String>,
+
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Pair a phone companion: shows a QR code to scan within five minutes. The proxy
+    /// must be running and reachable from the phone's network.
+    Pair {
+        /// List the paired devices instead
+        #[arg(long, conflicts_with = "revoke")]
+        list: bool,
+
+        /// Unpair the device with this ID
+        #[arg(long, value_name = "ID")]
+        revoke: Option<String>,
+    },
+    /// Copy library folders to the destinations configured under `mirrors`
+    Mirror {
+        /// Mirror to run; runs all of them when omitted
+        name: Option<String>,
+
+        /// Only report what would be copied
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Run the first-run setup (find Python, create the virtual environment, install
+    /// packages, download models, update the database), or finish an interrupted one
+    Setup {
+        /// Forget the progress of an earlier setup and start from the first step
+        #[arg(long)]
+        reset: bool,
+    },
+    /// Print a shell completion script for bash, zsh, fish, powershell or elvish
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print the man page in roff format
+    Man,
+}
+
+impl Cli {
+    /// The exit hook given with `--on-exit` and `--on-exit-arg`
+    pub fn exit_hook(&self) -> Option<ExitHook> {
+        let command = self.on_exit.clone()?;
+        Some(ExitHook { command, args: self.on_exit_arg.clone() })
+    }
+
+    /// Arguments forwarded to `python -m backend.main`.
+    ///
+    /// The data dir is always passed explicitly; otherwise the backend falls back to a
+    /// `data/` folder relative to the install dir that every user on the machine shares.
+    /// Paths are kept as `OsString` so non-UTF-8 and non-ASCII locations survive intact.
+    /// Behind the proxy the backend listens on loopback at `proxy_backend_port` instead
+    /// of the public host/port.
+    pub fn backend_args(&self, data_dir: &Path, proxy_backend_port: Option<u16>) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["--data-dir".into(), data_dir.as_os_str().into()];
+        let (host, port) = match proxy_backend_port {
+            Some(port) => (Some("127.0.0.1".to_string()), Some(port)),
+            None => (self.host.clone(), self.port),
+        };
+        if let Some(port) = port {
+            args.push("--port".into());
+            args.push(port.to_string().into());
+        }
+        if let Some(host) = host {
+            args.push("--host".into());
+            args.push(host.into());
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn hostile_string() -> impl Strategy<Value = String> {
+        prop::collection::vec(
+            prop_oneof![
+                Just(' '), Just('"'), Just('\''), Just('\\'), Just('\n'), Just('\t'), Just('$'),
+                Just('%'), Just('&'), Just('-'), Just('='), Just('ü'), Just('语'), Just('🎙'),
+                any::<char>(),
+            ],
+            1..40,
+        )
+        .prop_map(|chars| chars.into_iter().collect())
+    }
+
+    #[test]
+    fn exit_hook_takes_its_arguments_separately() {
+        let cli = Cli::try_parse_from(["voicebox-server", "--on-exit", "notify.sh", "--on-exit-arg", "--urgent", "--on-exit-arg", "backend down"]).unwrap();
+        let hook = cli.exit_hook().unwrap();
+        assert_eq!(hook.command, "notify.sh");
+        assert_eq!(hook.args, ["--urgent", "backend down"]);
+
+        assert!(Cli::try_parse_from(["voicebox-server"]).unwrap().exit_hook().is_none());
+        assert!(Cli::try_parse_from(["voicebox-server", "--on-exit-arg", "x"]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn data_dir_and_host_are_passed_as_single_arguments(
+            data_dir in hostile_string(),
+            host in hostile_string(),
+            port in any::<u16>(),
+        ) {
+            let cli = Cli::try_parse_from(["voicebox-server".to_string(), format!("--host={}", host), format!("--port={}", port)]).unwrap();
+            let args = cli.backend_args(Path::new(&data_dir), None);
+            prop_assert_eq!(&args[..2], &[OsString::from("--data-dir"), OsString::from(&data_dir)]);
+            let host_at = args.iter().position(|a| a == "--host").unwrap();
+            prop_assert_eq!(&args[host_at + 1], &OsString::from(&host));
+            prop_assert_eq!(args.len(), 6);
+        }
+
+        #[test]
+        fn proxy_mode_pins_the_backend_to_loopback(data_dir in hostile_string(), backend_port in 1u16..) {
+            let cli = Cli::try_parse_from(["voicebox-server", "--host", "0.0.0.0"]).unwrap();
+            let args = cli.backend_args(Path::new(&data_dir), Some(backend_port));
+            prop_assert_eq!(args[1].clone(), OsString::from(&data_dir));
+            prop_assert!(args.windows(2).any(|w| w[0] == "--host" && w[1] == "127.0.0.1"));
+            prop_assert!(args.windows(2).any(|w| w[0] == "--port" && w[1] == backend_port.to_string().as_str()));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::launcher::process::{CommandSpec, ProcessRunner};
+use serde::Deserialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the conda environment active in the shell the launcher was started from
+pub const CONDA_PREFIX_ENV: &str = "CONDA_PREFIX";
+/// Conda environment to run the backend in, by name or prefix
+pub const CONDA_ENV_ENV: &str = "VOICEBOX_CONDA_ENV";
+/// Set by `conda init` / `mamba init` to the manager's own executable, which is the
+/// only reliable way to find it on Windows where `conda` is a batch file
+const MANAGER_ENV_VARS: &[&str] = &["CONDA_EXE", "MAMBA_EXE"];
+/// Tried on PATH when neither variable is set
+const MANAGER_COMMANDS: &[&str] = &["conda", "mamba", "micromamba"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CondaEnv {
+    /// `base` for the installation's root environment
+    pub name: String,
+    pub prefix: PathBuf,
+}
+
+impl CondaEnv {
+    pub fn from_prefix(prefix: &Path) -> Self {
+        let in_envs_dir = prefix.parent().and_then(Path::file_name).is_some_and(|dir| dir == "envs");
+        let name = match (in_envs_dir, prefix.file_name()) {
+            (true, Some(name)) => name.to_string_lossy().into_owned(),
+            _ => "base".to_string(),
+        };
+        Self { name, prefix: prefix.to_path_buf() }
+    }
+
+    /// The environment's interpreter, if it has one
+    pub fn python(&self) -> Option<PathBuf> {
+        let candidates = if cfg!(windows) {
+            vec![self.prefix.join("python.exe")]
+        } else {
+            vec![self.prefix.join("bin").join("python3"), self.prefix.join("bin").join("python")]
+        };
+        candidates.into_iter().find(|python| python.is_file())
+    }
+
+    /// Directories `conda activate` puts in front of PATH. On Windows the DLLs of
+    /// packages like PyTorch and NumPy live in `Library\bin` and fail to load without it.
+    pub fn path_entries(&self) -> Vec<PathBuf> {
+        if cfg!(windows) {
+            let library = self.prefix.join("Library");
+            vec![
+                self.prefix.clone(),
+                library.join("mingw-w64").join("bin"),
+                library.join("usr").join("bin"),
+                library.join("bin"),
+                self.prefix.join("Scripts"),
+                self.prefix.join("bin"),
+            ]
+        } else {
+            vec![self.prefix.join("bin")]
+        }
+    }
+
+    /// Environment of a process running in this environment, as if activated, given the
+    /// launcher's own PATH
+    pub fn activation_env(&self, current_path: Option<OsString>) -> Result<Vec<(OsString, OsString)>, String> {
+        let mut path = self.path_entries();
+        path.extend(current_path.iter().flat_map(std::env::split_paths));
+        let path = std::env::join_paths(path).map_err(|e| format!("Cannot activate {}: {}", self.prefix.display(), e))?;
+        Ok(vec![
+            ("PATH".into(), path),
+            (CONDA_PREFIX_ENV.into(), self.prefix.clone().into_os_string()),
+            ("CONDA_DEFAULT_ENV".into(), self.name.clone().into()),
+        ])
+    }
+}
+
+#[derive(Deserialize)]
+struct EnvList {
+    envs: Vec<PathBuf>,
+}
+
+/// Environments in the output of `conda env list --json` (same for mamba and micromamba)
+pub fn parse_env_list(json: &str) -> Result<Vec<CondaEnv>, String> {
+    let list: EnvList = serde_json::from_str(json).map_err(|e| format!("Unexpected `env list` output: {}", e))?;
+    Ok(list.envs.iter().map(|prefix| CondaEnv::from_prefix(prefix)).collect())
+}
+
+/// Environments known to the first conda-style manager that answers; empty when none
+/// is installed
+pub fn list_envs(runner: &dyn ProcessRunner) -> Vec<CondaEnv> {
+    let from_vars = MANAGER_ENV_VARS.iter().filter_map(std::env::var_os).filter(|exe| !exe.is_empty());
+    let managers: Vec<OsString> = from_vars.chain(MANAGER_COMMANDS.iter().map(OsString::from)).collect();
+    for manager in managers {
+        let spec = CommandSpec::new(&manager).args(["env", "list", "--json"]);
+        let Ok(output) = runner.output(&spec) else { continue };
+        if !output.status.success() {
+            continue;
+        }
+        if let Ok(envs) = parse_env_list(&String::from_utf8_lossy(&output.stdout)) {
+            return envs;
+        }
+    }
+    Vec::new()
+}
+
+/// The environment `wanted` names, by name or by prefix; a prefix works even when no
+/// manager lists it
+pub fn find_env(envs: &[CondaEnv], wanted: &str) -> Option<CondaEnv> {
+    envs.iter()
+        .find(|env| env.name == wanted || env.prefix == Path::new(wanted))
+        .cloned()
+        .or_else(|| Path::new(wanted).is_dir().then(|| CondaEnv::from_prefix(Path::new(wanted))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    #[test]
+    fn names_environments_and_finds_them_by_name() {
+        let json = r#"{"envs": ["/opt/miniconda3", "/opt/miniconda3/envs/torch", "/home/me/envs/tts"]}"#;
+        let envs = parse_env_list(json).unwrap();
+        let names: Vec<_> = envs.iter().map(|env| env.name.as_str()).collect();
+        assert_eq!(names, ["base", "torch", "tts"]);
+        assert_eq!(find_env(&envs, "torch").unwrap().prefix, PathBuf::from("/opt/miniconda3/envs/torch"));
+        assert_eq!(find_env(&envs, "/home/me/envs/tts").unwrap().name, "tts");
+        assert_eq!(find_env(&envs, "missing"), None);
+        assert!(parse_env_list("conda: command not found").is_err());
+
+        let runner = FakeRunner::new();
+        runner.script("mamba", Script::exits(0).stdout(json));
+        assert_eq!(list_envs(&runner).len(), 3);
+    }
+
+    #[test]
+    fn activation_puts_the_environment_first_on_path() {
+        let env = CondaEnv::from_prefix(Path::new("/opt/conda/envs/torch"));
+        let vars = env.activation_env(Some(OsString::from("/usr/bin"))).unwrap();
+        let path: Vec<PathBuf> = std::env::split_paths(&vars[0].1).collect();
+        assert_eq!(path.first(), env.path_entries().first());
+        assert_eq!(path.last(), Some(&PathBuf::from("/usr/bin")));
+        assert_eq!(vars[2], (OsString::from("CONDA_DEFAULT_ENV"), OsString::from("torch")));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::launcher::backend_log::{BackendLevel, BackendLogFilter};
+use crate::launcher::devices::DeviceConfig;
+use crate::launcher::fine_tune::FineTuneConfig;
+use crate::launcher::hooks::ExitHook;
+use crate::launcher::installer::{InstallerChoice, PackageIndexConfig};
+use crate::launcher::log::LogLevel;
+use crate::launcher::notifications::NotificationSettings;
+use crate::launcher::presets::Preset;
+use crate::launcher::proxy::ProxyConfig;
+use crate::launcher::supervisor::RestartPolicy;
+use crate::launcher::workspace::WorkspaceConfig;
+use crate::library::chat::ChatConfig;
+use crate::library::dataset::DatasetOptions;
+use crate::library::game_export::GameExportProfile;
+use crate::library::mirror::MirrorConfig;
+use crate::library::narration::{NarrationWatch, SourcePriority};
+use crate::library::reminders::Reminder;
+use crate::library::soundboard::SoundboardClip;
+use crate::text::filter::ContentFilterConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// Matches the Tauri bundle identifier so the launcher and the app share a data dir
+const APP_IDENTIFIER: &str = "com.voicebox";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LauncherConfig {
+    pub presets: BTreeMap<String, Preset>,
+    /// Command run when the backend exits unexpectedly
+    pub on_backend_exit: Option<ExitHook>,
+    /// Whether and how often a crashed backend is started again
+    pub restart: RestartPolicy,
+    pub proxy: ProxyConfig,
+    /// Refuse reference audio for voice profiles until consent has been recorded
+    pub require_voice_consent: bool,
+    /// Days deleted generations and samples stay in the trash (default 30)
+    pub trash_retention_days: Option<u32>,
+    /// Destinations library folders are copied to
+    pub mirrors: Vec<MirrorConfig>,
+    /// Data roots besides the data dir and the projects kept in them
+    pub workspace: WorkspaceConfig,
+    /// Game engine export profiles by name, besides the built-in `unity` and `unreal`
+    pub game_exports: BTreeMap<String, GameExportProfile>,
+    /// How recordings are prepared for fine-tuning datasets
+    pub dataset: DatasetOptions,
+    /// The trainer fine-tuning runs start on an exported dataset
+    pub fine_tune: FineTuneConfig,
+    /// Soundboard clips by name, rendered ahead of time so they play instantly
+    pub soundboard: BTreeMap<String, SoundboardClip>,
+    /// Announcements spoken on a schedule while the app runs
+    pub reminders: Vec<Reminder>,
+    /// Text files whose new lines are spoken as they are written
+    pub narration: Vec<NarrationWatch>,
+    /// Twitch or YouTube chat read aloud
+    pub chat: Option<ChatConfig>,
+    /// Words and patterns filtered from chat, remote, Home Assistant and narrated text
+    pub content_filter: ContentFilterConfig,
+    /// Priorities of what the app speaks by source: `hotkey`, `scheduler`, `api`, `chat`
+    /// or a narration watch's name
+    pub input_queues: BTreeMap<String, SourcePriority>,
+    /// `debug` to log more detail, e.g. for a bug report
+    pub log_level: LogLevel,
+    /// Levels below which JSON log lines of the backend are dropped, by logger
+    pub backend_log: BackendLogFilter,
+    /// How much the backend itself logs (default `info`); `debug` for a bug report
+    pub backend_log_level: Option<BackendLevel>,
+    /// Whether synthesis and transcription run on the GPU (`gpu`), the CPU (`cpu`) or
+    /// wherever the backend finds best (`auto`)
+    pub devices: DeviceConfig,
+    /// Notification policies by category, applied over those chosen in settings when the
+    /// config is loaded
+    pub notifications: Option<NotificationSettings>,
+    /// Conda environment to run the backend in, by name or prefix
+    pub conda_env: Option<String>,
+    /// Run the backend in a virtual environment the launcher creates and installs the
+    /// requirements into, instead of the global Python
+    pub managed_venv: bool,
+    /// Tool that installs the backend's packages: `auto` (uv if found), `uv` or `pip`
+    pub installer: InstallerChoice,
+    /// Proxy and index overrides for installing the backend's packages
+    pub package_index: PackageIndexConfig,
+    /// Seconds pre-flight checks of the interpreter may take (default 30)
+    pub preflight_timeout_secs: Option<u64>,
+    /// Hugging Face repos the first-run setup downloads, instead of the default model
+    pub setup_models: Option<Vec<String>>,
+}
+
+impl LauncherConfig {
+    /// Location of the config file, honoring the `VOICEBOX_CONFIG` override
+    pub fn path(data_dir: &Path) -> PathBuf {
+        match std::env::var_os("VOICEBOX_CONFIG") {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => data_dir.join(CONFIG_FILE_NAME),
+        }
+    }
+
+    /// Load the config, treating a missing file as an empty config
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse config {}: {}", path.display(), e))
+    }
+
+    pub fn load_from_data_dir(data_dir: &Path) -> Result<Self, String> {
+        Self::load(&Self::path(data_dir))
+    }
+}
+
+/// Same directory Tauri resolves via `app_data_dir()`, for use outside the app
+pub fn default_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
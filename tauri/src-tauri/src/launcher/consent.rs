@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+/// Revision of the consent terms shown before cloning. Bump when the wording changes
+/// in a way users need to acknowledge again.
+pub const TERMS_VERSION: u32 = 1;
+
+/// Environment variable that makes the backend refuse reference audio for profiles
+/// without a consent record
+pub const REQUIRE_CONSENT_ENV: &str = "VOICEBOX_REQUIRE_CONSENT";
+
+/// Why the user may clone this voice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentBasis {
+    /// The user's own voice
+    OwnVoice,
+    /// The speaker agreed to have their voice cloned
+    SpeakerConsent,
+    /// The recordings are licensed for this use
+    Licensed,
+    PublicDomain,
+}
+
+/// Consent and license metadata stored next to a voice profile (`consent.json` in its
+/// profile directory) and carried inside exported archives
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsentRecord {
+    pub basis: ConsentBasis,
+    /// Whose voice it is
+    pub speaker: Option<String>,
+    /// License name or URL of the reference audio
+    pub license: Option<String>,
+    /// Where the reference audio came from
+    pub source: Option<String>,
+    /// Revision of the terms the user acknowledged
+    pub terms_version: u32,
+    /// RFC 3339 timestamp of the acknowledgment
+    pub acknowledged_at: String,
+}
+
+fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|v| v.trim().is_empty())
+}
+
+impl ConsentRecord {
+    /// A record acknowledging the current terms now
+    pub fn acknowledge(basis: ConsentBasis, speaker: Option<String>, license: Option<String>, source: Option<String>) -> Self {
+        Self {
+            basis,
+            speaker,
+            license,
+            source,
+            terms_version: TERMS_VERSION,
+            acknowledged_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.basis {
+            ConsentBasis::SpeakerConsent if is_blank(&self.speaker) => {
+                return Err("Consent record must name the speaker who gave consent".to_string())
+            }
+            ConsentBasis::Licensed if is_blank(&self.license) => {
+                return Err("Consent record for licensed audio must name the license".to_string())
+            }
+            _ => {}
+        }
+        if self.terms_version == 0 || self.terms_version > TERMS_VERSION {
+            return Err(format!(
+                "Consent record acknowledges unknown terms version {} (current: {})",
+                self.terms_version, TERMS_VERSION
+            ));
+        }
+        chrono::DateTime::parse_from_rfc3339(&self.acknowledged_at)
+            .map_err(|_| format!("Invalid acknowledgment time in consent record: {}", self.acknowledged_at))?;
+        Ok(())
+    }
+}
+
+fn consent_url(base_url: &str, profile_id: &str) -> String {
+    format!("{}/profiles/{}/consent", base_url.trim_end_matches('/'), profile_id)
+}
+
+/// The consent record of a profile, or `None` if it has none yet
+pub async fn fetch(base_url: &str, profile_id: &str) -> Result<Option<ConsentRecord>, String> {
+    let url = consent_url(base_url, profile_id);
+    let response =
+        reqwest::Client::new().get(&url).send().await.map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    response
+        .error_for_status()
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .json()
+        .await
+        .map(Some)
+        .map_err(|e| format!("Invalid consent record from {}: {}", url, e))
+}
+
+/// Validate and store the consent record of a profile
+pub async fn store(base_url: &str, profile_id: &str, record: &ConsentRecord) -> Result<(), String> {
+    record.validate()?;
+    let url = consent_url(base_url, profile_id);
+    reqwest::Client::new()
+        .put(&url)
+        .json(record)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map(|_| ())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_the_details_each_basis_needs() {
+        let mut record = ConsentRecord::acknowledge(ConsentBasis::SpeakerConsent, None, None, None);
+        assert!(record.validate().unwrap_err().contains("speaker"));
+        record.speaker = Some("Ada".to_string());
+        record.validate().unwrap();
+
+        let record = ConsentRecord::acknowledge(ConsentBasis::Licensed, None, Some(" ".to_string()), None);
+        assert!(record.validate().unwrap_err().contains("license"));
+
+        let mut record = ConsentRecord::acknowledge(ConsentBasis::OwnVoice, None, None, None);
+        record.terms_version = TERMS_VERSION + 1;
+        assert!(record.validate().is_err());
+    }
+
+    #[test]
+    fn reads_the_backend_format() {
+        let record: ConsentRecord = serde_json::from_str(
+            r#"{"basis": "public_domain", "speaker": null, "license": null, "source": "archive.org",
+                "terms_version": 1, "acknowledged_at": "2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(record.basis, ConsentBasis::PublicDomain);
+        record.validate().unwrap();
+    }
+}
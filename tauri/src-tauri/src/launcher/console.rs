@@ -0,0 +1,114 @@
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+/// Width phase labels are padded to so results line up in a column
+const LABEL_WIDTH: usize = 28;
+
+/// Human-friendly progress output for people running the launcher by hand.
+///
+/// Only active when stderr is a terminal. When the launcher is piped (as the Tauri
+/// sidecar, or in scripts) phases are silent and warnings are printed as plain text,
+/// so nothing parsing our output sees escape codes or spinner frames.
+#[derive(Debug, Clone, Copy)]
+pub struct Console {
+    interactive: bool,
+}
+
+impl Console {
+    pub fn detect(headless: bool) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self {
+            interactive: !headless && !no_color && std::io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Start a phase, shown as a spinner until it is finished or failed
+    pub fn phase(&self, label: &str) -> Phase {
+        let bar = self.interactive.then(|| {
+            let bar = ProgressBar::new_spinner();
+            bar.set_style(
+                ProgressStyle::with_template("{spinner:.cyan} {msg} {elapsed:.dim}")
+                    .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+            );
+            bar.set_message(format!("{:<width$}", label, width = LABEL_WIDTH));
+            bar.enable_steady_tick(Duration::from_millis(80));
+            bar
+        });
+        Phase {
+            label: label.to_string(),
+            bar,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn success(&self, msg: &str) {
+        if self.interactive {
+            eprintln!("{} {}", style("✔").green().bold(), style(msg).bold());
+        }
+    }
+
+    pub fn warn(&self, msg: &str) {
+        if self.interactive {
+            eprintln!("{} {}", style("⚠").yellow().bold(), style(msg).yellow());
+        } else {
+            eprintln!("WARNING: {}", msg);
+        }
+    }
+
+    pub fn error(&self, msg: &str) {
+        if self.interactive {
+            eprintln!("{} {}", style("✖").red().bold(), style(msg).red());
+        } else {
+            eprintln!("{}", msg);
+        }
+    }
+
+    /// Follow-up to an error telling the user what to do next
+    pub fn hint(&self, msg: &str) {
+        if self.interactive {
+            eprintln!("  {} {}", style("→").dim(), msg);
+        } else {
+            eprintln!("HINT: {}", msg);
+        }
+    }
+}
+
+pub struct Phase {
+    label: String,
+    bar: Option<ProgressBar>,
+    started: Instant,
+}
+
+impl Phase {
+    pub fn finish(self, detail: &str) {
+        self.end(style("✔").green().bold().to_string(), detail);
+    }
+
+    pub fn skip(self, detail: &str) {
+        self.end(style("–").dim().to_string(), detail);
+    }
+
+    pub fn fail(self, detail: &str) {
+        self.end(style("✖").red().bold().to_string(), &style(detail).red().to_string());
+    }
+
+    fn end(self, marker: String, detail: &str) {
+        if let Some(bar) = self.bar {
+            bar.finish_and_clear();
+            eprintln!(
+                "{} {:<width$} {} {}",
+                marker,
+                self.label,
+                detail,
+                style(format!("({:.1}s)", self.started.elapsed().as_secs_f32())).dim(),
+                width = LABEL_WIDTH
+            );
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use crate::launcher::process::{CommandSpec, ProcessRunner};
+use crate::launcher::retry::with_io_retry;
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+pub const FILTERED_REQUIREMENTS_NAME: &str = "requirements_install.txt";
+pub const DEPS_CHECK_STATE_FILE_NAME: &str = "deps_check.json";
+
+/// Packages checked when the backend ships no requirements.txt
+const CORE_PACKAGES: &[&str] = &["fastapi", "uvicorn", "sqlalchemy", "alembic", "python-multipart", "numpy"];
+
+/// Prints `{name: installed version or null}` as JSON for the distributions named in argv
+const CHECK_SCRIPT: &str = "
+import json, sys
+from importlib import metadata
+found = {}
+for name in sys.argv[1:]:
+    try:
+        found[name] = metadata.version(name)
+    except metadata.PackageNotFoundError:
+        found[name] = None
+print(json.dumps(found))
+";
+
+/// One version clause of a requirement, such as `>=0.34.0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Specifier {
+    pub op: String,
+    pub version: String,
+}
+
+/// A package line of requirements.txt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub name: String,
+    pub specifiers: Vec<Specifier>,
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        let clauses: Vec<String> = self.specifiers.iter().map(|s| format!("{}{}", s.op, s.version)).collect();
+        f.write_str(&clauses.join(","))
+    }
+}
+
+/// Release segments of a PEP 440 version, ignoring epochs and pre/post/dev/local parts:
+/// `2.0.0rc1` and `2.0.0+cu121` both give `[2, 0, 0]`
+fn release(version: &str) -> Vec<u64> {
+    let version = version.split('+').next().unwrap_or(version);
+    let version = version.rsplit('!').next().unwrap_or(version);
+    version
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+fn compare(a: &[u64], b: &[u64]) -> Ordering {
+    let len = a.len().max(b.len());
+    let padded = |v: &[u64]| (0..len).map(|i| v.get(i).copied().unwrap_or(0)).collect::<Vec<_>>();
+    padded(a).cmp(&padded(b))
+}
+
+impl Specifier {
+    pub fn matches(&self, installed: &str) -> bool {
+        let have = release(installed);
+        // `==2.0.*` and `!=2.0.*` compare a prefix
+        if let Some(prefix) = self.version.strip_suffix(".*") {
+            let prefix = release(prefix);
+            let same = have.len() >= prefix.len() && have[..prefix.len()] == prefix[..];
+            return if self.op == "!=" { !same } else { same };
+        }
+        let want = release(&self.version);
+        let order = compare(&have, &want);
+        match self.op.as_str() {
+            "==" | "===" => order == Ordering::Equal,
+            "!=" => order != Ordering::Equal,
+            ">=" => order != Ordering::Less,
+            "<=" => order != Ordering::Greater,
+            ">" => order == Ordering::Greater,
+            "<" => order == Ordering::Less,
+            // `~=1.4.2` means `>=1.4.2,==1.4.*`
+            "~=" => {
+                let prefix = &want[..want.len().saturating_sub(1).max(1).min(want.len())];
+                order != Ordering::Less && have.len() >= prefix.len() && have[..prefix.len()] == prefix[..]
+            }
+            _ => true,
+        }
+    }
+}
+
+/// PEP 503 normalized package name, so `Python_Multipart` and `python-multipart` match
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '-' | '_' | '.' if normalized.ends_with('-') => {}
+            '-' | '_' | '.' => normalized.push('-'),
+            c => normalized.push(c.to_ascii_lowercase()),
+        }
+    }
+    normalized
+}
+
+/// Package requirements of a requirements.txt. Options (`-r`, `--index-url`), URLs and
+/// environment markers are skipped; extras are dropped since only the base package is
+/// checked.
+pub fn parse_requirements(content: &str) -> Vec<Requirement> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.split(" #").next().unwrap_or(line).trim();
+            let line = line.split(';').next().unwrap_or(line).trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('-') || line.contains("://") {
+                return None;
+            }
+            let name_end = line.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))).unwrap_or(line.len());
+            let name = normalize_name(&line[..name_end]);
+            let rest = line[name_end..].trim_start();
+            let rest = match rest.strip_prefix('[') {
+                Some(extras) => extras.split_once(']').map(|(_, rest)| rest).unwrap_or_default(),
+                None => rest,
+            };
+            let specifiers = rest
+                .split(',')
+                .map(str::trim)
+                .filter(|clause| !clause.is_empty())
+                .filter_map(|clause| {
+                    let op_end = clause.find(|c: char| !matches!(c, '=' | '!' | '<' | '>' | '~'))?;
+                    Some(Specifier { op: clause[..op_end].to_string(), version: clause[op_end..].trim().to_string() })
+                })
+                .collect();
+            Some(Requirement { name, specifiers })
+        })
+        .collect()
+}
+
+/// The backend's requirements, or its core packages without versions when it ships no
+/// requirements.txt
+pub fn read_requirements(backend_dir: &Path) -> Vec<Requirement> {
+    match std::fs::read_to_string(backend_dir.join("requirements.txt")) {
+        Ok(content) => parse_requirements(&content),
+        Err(_) => CORE_PACKAGES
+            .iter()
+            .map(|name| Requirement { name: name.to_string(), specifiers: Vec::new() })
+            .collect(),
+    }
+}
+
+/// What the dependency check found, package by package
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyReport {
+    pub missing: Vec<Requirement>,
+    /// Installed at a version the requirement doesn't allow, with that version
+    pub outdated: Vec<(Requirement, String)>,
+    pub satisfied: usize,
+}
+
+impl DependencyReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.outdated.is_empty()
+    }
+
+    /// `2 missing, 1 outdated`
+    pub fn summary(&self) -> String {
+        match (self.missing.len(), self.outdated.len()) {
+            (0, 0) => format!("all {} required packages present", self.satisfied),
+            (missing, 0) => format!("{} missing", missing),
+            (0, outdated) => format!("{} outdated", outdated),
+            (missing, outdated) => format!("{} missing, {} outdated", missing, outdated),
+        }
+    }
+
+    /// One line per problem, e.g. `fastapi==0.128.0: installed 0.110.0`
+    pub fn details(&self) -> Vec<String> {
+        let missing = self.missing.iter().map(|r| format!("{}: not installed", r));
+        let outdated = self.outdated.iter().map(|(r, installed)| format!("{}: installed {}", r, installed));
+        missing.chain(outdated).collect()
+    }
+}
+
+/// Compare the versions `installed` reports against `requirements`
+pub fn compare_installed(requirements: &[Requirement], installed: &HashMap<String, Option<String>>) -> DependencyReport {
+    let mut report = DependencyReport::default();
+    for requirement in requirements {
+        match installed.get(&requirement.name).cloned().flatten() {
+            None => report.missing.push(requirement.clone()),
+            Some(version) if requirement.specifiers.iter().all(|s| s.matches(&version)) => report.satisfied += 1,
+            Some(version) => report.outdated.push((requirement.clone(), version)),
+        }
+    }
+    report
+}
+
+/// Check each of `requirements` in the interpreter `python` runs. `Err` means the
+/// interpreter itself couldn't be run or its answer couldn't be read.
+pub fn check_dependencies(
+    runner: &dyn ProcessRunner,
+    python: &CommandSpec,
+    requirements: &[Requirement],
+) -> std::io::Result<DependencyReport> {
+    let spec = python.clone().arg("-c").arg(CHECK_SCRIPT).args(requirements.iter().map(|r| r.name.as_str()));
+    let output = with_io_retry("Dependency check", || runner.output(&spec))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(format!("Dependency check failed: {}", stderr.trim())));
+    }
+    let installed: HashMap<String, Option<String>> =
+        serde_json::from_slice(&output.stdout).map_err(std::io::Error::other)?;
+    Ok(compare_installed(requirements, &installed))
+}
+
+/// Fingerprint of what the dependency check depends on: the backend's requirements.txt
+/// and the interpreter command. A missing requirements.txt hashes differently from an
+/// empty one, since the core packages are checked instead.
+pub fn requirements_hash(backend_dir: &Path, python: &CommandSpec) -> String {
+    let mut hasher = Sha256::new();
+    match std::fs::read(backend_dir.join("requirements.txt")) {
+        Ok(content) => {
+            hasher.update(b"requirements\0");
+            hasher.update(&content);
+        }
+        Err(_) => hasher.update(CORE_PACKAGES.join(",").as_bytes()),
+    }
+    for part in std::iter::once(&python.program).chain(&python.args) {
+        hasher.update(b"\0");
+        hasher.update(part.to_string_lossy().as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PassedCheck {
+    requirements_sha256: String,
+    checked_at: String,
+}
+
+/// The last dependency check that passed, so launches with unchanged requirements and
+/// interpreter can skip running Python before the backend
+pub struct DependencyCheckCache {
+    file: StateFile<PassedCheck>,
+}
+
+impl DependencyCheckCache {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { file: StateFile::new(state_dir.join(DEPS_CHECK_STATE_FILE_NAME)) }
+    }
+
+    /// When the check last passed for `hash`, if it did
+    pub fn passed(&self, hash: &str) -> Option<String> {
+        let check = self.file.read().ok()??;
+        (check.requirements_sha256 == hash).then_some(check.checked_at)
+    }
+
+    pub fn record(&self, hash: &str) -> Result<(), String> {
+        self.file.write(&PassedCheck { requirements_sha256: hash.to_string(), checked_at: chrono::Utc::now().to_rfc3339() })
+    }
+
+    /// Forget the passed check, so the next launch runs it again
+    pub fn clear(&self) -> Result<(), String> {
+        self.file.remove()
+    }
+}
+
+/// Copy `requirements.txt` into `work_dir` without torch lines, so installing it
+/// doesn't overwrite a user's existing (often CUDA-specific) PyTorch build.
+pub fn write_filtered_requirements(req_path: &Path, work_dir: &Path) -> Result<PathBuf, String> {
+    let content = std::fs::read_to_string(req_path)
+        .map_err(|e| format!("Failed to read {}: {}", req_path.display(), e))?;
+    let filtered_lines: Vec<&str> = content
+        .lines()
+        .filter(|l| !l.trim().starts_with("torch"))
+        .collect();
+
+    let safe_req_path = work_dir.join(FILTERED_REQUIREMENTS_NAME);
+    let filtered = filtered_lines.join("\n");
+    with_io_retry("Writing filtered requirements", || std::fs::write(&safe_req_path, &filtered))
+        .map_err(|e| format!("Failed to write {}: {}", safe_req_path.display(), e))?;
+    Ok(safe_req_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    #[test]
+    fn reports_missing_and_outdated_packages() {
+        let requirements = parse_requirements("fastapi==0.128.0\nuvicorn[standard]==0.40.0\nnumpy\n");
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(0).stdout(r#"{"fastapi": "0.110.0", "uvicorn": "0.40.0", "numpy": null}"#));
+        let report = check_dependencies(&runner, &CommandSpec::new("python"), &requirements).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.summary(), "1 missing, 1 outdated");
+        assert_eq!(report.details(), ["numpy: not installed", "fastapi==0.128.0: installed 0.110.0"]);
+        assert_eq!(runner.calls()[0].args[2..], ["fastapi", "uvicorn", "numpy"]);
+
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(1).stderr("SyntaxError"));
+        assert!(check_dependencies(&runner, &CommandSpec::new("python"), &requirements).is_err());
+        let err = check_dependencies(&FakeRunner::new(), &CommandSpec::new("python"), &requirements).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn parses_requirements_and_matches_specifiers() {
+        let requirements = parse_requirements(
+            "# Server\nuvicorn[standard]==0.40.0\nhuggingface-hub>=0.34.0,<1.0  # hub\n-r extra.txt\nPython_Multipart ; python_version >= '3.9'\n",
+        );
+        assert_eq!(requirements.len(), 3);
+        assert_eq!(requirements[0].to_string(), "uvicorn==0.40.0");
+        assert_eq!(requirements[1].specifiers.len(), 2);
+        assert_eq!(requirements[2], Requirement { name: "python-multipart".to_string(), specifiers: Vec::new() });
+
+        let hub = &requirements[1];
+        assert!(hub.specifiers.iter().all(|s| s.matches("0.36.2")));
+        assert!(!hub.specifiers.iter().all(|s| s.matches("1.0.0")));
+        let spec = |op: &str, version: &str| Specifier { op: op.to_string(), version: version.to_string() };
+        assert!(spec("==", "2.0").matches("2.0.0+cu121"));
+        assert!(spec("==", "2.0.*").matches("2.0.3"));
+        assert!(spec("~=", "1.4.2").matches("1.4.9"));
+        assert!(!spec("~=", "1.4.2").matches("1.5.0"));
+        assert!(spec("~=", "1.4").matches("1.9"));
+    }
+
+    #[test]
+    fn remembers_passed_checks_per_requirements_and_interpreter() {
+        let backend = tempfile::tempdir().unwrap();
+        let state = tempfile::tempdir().unwrap();
+        let python = CommandSpec::new("/usr/bin/python3");
+        std::fs::write(backend.path().join("requirements.txt"), "fastapi==0.128.0\n").unwrap();
+        let hash = requirements_hash(backend.path(), &python);
+        assert_ne!(hash, requirements_hash(backend.path(), &CommandSpec::new("/opt/venv/bin/python")));
+
+        let cache = DependencyCheckCache::new(state.path());
+        assert!(cache.passed(&hash).is_none());
+        cache.record(&hash).unwrap();
+        assert!(cache.passed(&hash).is_some());
+
+        std::fs::write(backend.path().join("requirements.txt"), "fastapi==0.129.0\n").unwrap();
+        assert!(cache.passed(&requirements_hash(backend.path(), &python)).is_none());
+        cache.clear().unwrap();
+        assert!(cache.passed(&hash).is_none());
+    }
+}
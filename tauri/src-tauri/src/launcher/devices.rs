@@ -0,0 +1,103 @@
+// Whether synthesis and transcription run on the GPU or the CPU.
+//
+// The backend loads its speech and Whisper models once, each on one device, so the
+// choice is made per kind of job when the backend starts. Moving transcription to the
+// CPU leaves the GPU's memory and time to synthesis, so speaking stays quick while long
+// recordings are transcribed. A job set to the GPU falls back to the CPU, with a
+// warning, when there is no GPU the installed PyTorch can use.
+use crate::launcher::gpu::{GpuInfo, GpuVendor};
+use serde::{Deserialize, Serialize};
+
+pub const TTS_DEVICE_ENV: &str = "VOICEBOX_TTS_DEVICE";
+pub const STT_DEVICE_ENV: &str = "VOICEBOX_STT_DEVICE";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DevicePolicy {
+    /// The GPU if the backend finds one, else the CPU
+    #[default]
+    Auto,
+    /// The GPU, falling back to the CPU when there is none
+    Gpu,
+    Cpu,
+}
+
+/// The `devices` section of the launcher config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceConfig {
+    /// Speech generation
+    pub synthesis: DevicePolicy,
+    /// Whisper transcription of samples and recordings
+    pub transcription: DevicePolicy,
+}
+
+/// Environment telling the backend where each kind of job runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DevicePlan {
+    pub env: Vec<(&'static str, &'static str)>,
+    /// Jobs that wanted the GPU and run on the CPU instead
+    pub warnings: Vec<String>,
+}
+
+/// The backend's name for a GPU it can use: Metal on macOS, CUDA for NVIDIA cards and
+/// for AMD cards with ROCm, which PyTorch exposes as CUDA
+fn gpu_device(gpus: &GpuInfo) -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        return Some("mps");
+    }
+    let rocm = gpus.has(GpuVendor::Amd) && gpus.rocm.is_some();
+    (gpus.cuda.is_some() || rocm).then_some("cuda")
+}
+
+impl DeviceConfig {
+    /// Whether the plan depends on the GPUs, which are slow to detect
+    pub fn wants_gpu(&self) -> bool {
+        self.synthesis == DevicePolicy::Gpu || self.transcription == DevicePolicy::Gpu
+    }
+
+    /// Where each kind of job runs on a machine with `gpus`. `auto` jobs are left to
+    /// the backend.
+    pub fn plan(&self, gpus: &GpuInfo) -> DevicePlan {
+        let mut plan = DevicePlan::default();
+        for (policy, env, job) in [(self.synthesis, TTS_DEVICE_ENV, "Synthesis"), (self.transcription, STT_DEVICE_ENV, "Transcription")] {
+            let device = match policy {
+                DevicePolicy::Auto => continue,
+                DevicePolicy::Cpu => "cpu",
+                DevicePolicy::Gpu => gpu_device(gpus).unwrap_or_else(|| {
+                    plan.warnings.push(format!("{} is set to run on the GPU, but no usable GPU was found; it runs on the CPU", job));
+                    "cpu"
+                }),
+            };
+            plan.env.push((env, device));
+        }
+        plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::gpu::Gpu;
+
+    #[test]
+    fn plans_each_kind_of_job_and_falls_back_to_the_cpu() {
+        let config = DeviceConfig { synthesis: DevicePolicy::Gpu, transcription: DevicePolicy::Cpu };
+        assert!(config.wants_gpu());
+        let nvidia = GpuInfo { gpus: vec![Gpu { vendor: GpuVendor::Nvidia, name: "RTX".to_string() }], cuda: Some((12, 4)), rocm: None };
+        let gpu = if cfg!(target_os = "macos") { "mps" } else { "cuda" };
+        assert_eq!(config.plan(&nvidia), DevicePlan { env: vec![(TTS_DEVICE_ENV, gpu), (STT_DEVICE_ENV, "cpu")], warnings: Vec::new() });
+
+        let auto = DeviceConfig::default();
+        assert!(!auto.wants_gpu());
+        assert_eq!(auto.plan(&nvidia), DevicePlan::default());
+
+        if !cfg!(target_os = "macos") {
+            let plan = config.plan(&GpuInfo::default());
+            assert_eq!(plan.env, [(TTS_DEVICE_ENV, "cpu"), (STT_DEVICE_ENV, "cpu")]);
+            assert!(plan.warnings[0].starts_with("Synthesis is set to run on the GPU"));
+        }
+        let parsed: DeviceConfig = serde_json::from_str(r#"{"transcription": "cpu"}"#).unwrap();
+        assert_eq!(parsed, DeviceConfig { synthesis: DevicePolicy::Auto, transcription: DevicePolicy::Cpu });
+    }
+}
@@ -0,0 +1,155 @@
+// Native message boxes for the launcher's few questions: a Win32 message box on Windows
+// and NSAlert on macOS through rfd, and zenity or kdialog on Linux. No script host is
+// involved, so they also work where PowerShell is blocked by policy.
+//
+// On Linux the dialog programs are run rather than linked, so the same launcher binary
+// starts on headless servers and in containers without a desktop; there the boxes are
+// skipped and logged, and a question counts as answered no.
+use crate::launcher::log::log;
+
+#[cfg(any(windows, target_os = "macos"))]
+pub use native::{choose, confirm, error};
+#[cfg(not(any(windows, target_os = "macos")))]
+pub use desktop_tools::{choose, confirm, error};
+
+#[cfg(any(windows, target_os = "macos"))]
+mod native {
+    use super::log;
+    use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+
+    /// Show a blocking error message box
+    pub fn error(title: &str, message: &str) {
+        MessageDialog::new()
+            .set_level(MessageLevel::Error)
+            .set_title(title)
+            .set_description(message)
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+
+    /// Ask a yes/no question, blocking until it is answered. Closing the box counts as no.
+    pub fn confirm(title: &str, message: &str) -> bool {
+        let result = MessageDialog::new()
+            .set_level(MessageLevel::Info)
+            .set_title(title)
+            .set_description(message)
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        log(&format!("Launcher: {} dialog answered {:?}", title, result));
+        result == MessageDialogResult::Yes
+    }
+
+    /// Let the user pick one of `options`. Message boxes have no lists, so each box shows
+    /// the whole list with one option marked and offers to use it or move on to the next.
+    /// `None` when the box is cancelled or closed.
+    pub fn choose(title: &str, message: &str, options: &[String]) -> Option<usize> {
+        const USE: &str = "Use this one";
+        const NEXT: &str = "Next";
+        if options.is_empty() {
+            return None;
+        }
+        let mut index = 0;
+        loop {
+            let list: Vec<String> = options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| format!("{} {}", if i == index { "\u{25b6}" } else { "    " }, option))
+                .collect();
+            let result = MessageDialog::new()
+                .set_level(MessageLevel::Info)
+                .set_title(title)
+                .set_description(format!("{}\n\n{}", message, list.join("\n")))
+                .set_buttons(MessageButtons::YesNoCancelCustom(USE.to_string(), NEXT.to_string(), "Cancel".to_string()))
+                .show();
+            log(&format!("Launcher: {} dialog answered {:?} for option {}", title, result, index + 1));
+            match result {
+                MessageDialogResult::Yes => return Some(index),
+                MessageDialogResult::Custom(label) if label == USE => return Some(index),
+                MessageDialogResult::No => {}
+                MessageDialogResult::Custom(label) if label == NEXT => {}
+                _ => return None,
+            }
+            index = (index + 1) % options.len();
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+mod desktop_tools {
+    use super::log;
+    use std::process::{Command, Stdio};
+
+    /// The first dialog program on `PATH`, when there is a desktop to show it on
+    fn tool() -> Option<&'static str> {
+        let desktop = ["DISPLAY", "WAYLAND_DISPLAY"].iter().any(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()));
+        if !desktop {
+            return None;
+        }
+        ["zenity", "kdialog"].into_iter().find(|tool| {
+            Command::new(tool).arg("--version").stdout(Stdio::null()).stderr(Stdio::null()).status().is_ok()
+        })
+    }
+
+    /// Run the dialog tool's `args`, returning whether it was answered with OK or yes
+    /// and what it printed. `None` when no dialog could be shown.
+    fn show(title: &str, args: impl FnOnce(&str) -> Vec<String>) -> Option<(bool, String)> {
+        let Some(tool) = tool() else {
+            log(&format!("Launcher: No zenity or kdialog to show the {} dialog with, skipping it", title));
+            return None;
+        };
+        match Command::new(tool).args(args(tool)).stderr(Stdio::null()).output() {
+            Ok(output) => Some((output.status.success(), String::from_utf8_lossy(&output.stdout).trim().to_string())),
+            Err(e) => {
+                log(&format!("Launcher: Failed to show the {} dialog with {}: {}", title, tool, e));
+                None
+            }
+        }
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// Show a blocking error message box
+    pub fn error(title: &str, message: &str) {
+        show(title, |tool| match tool {
+            "zenity" => strings(&["--error", "--no-markup", "--title", title, "--text", message]),
+            _ => strings(&["--title", title, "--error", message]),
+        });
+    }
+
+    /// Ask a yes/no question, blocking until it is answered. Closing the box counts as no.
+    pub fn confirm(title: &str, message: &str) -> bool {
+        let answered = show(title, |tool| match tool {
+            "zenity" => strings(&["--question", "--no-markup", "--title", title, "--text", message]),
+            _ => strings(&["--title", title, "--yesno", message]),
+        });
+        let yes = answered.is_some_and(|(yes, _)| yes);
+        log(&format!("Launcher: {} dialog answered {}", title, if yes { "yes" } else { "no" }));
+        yes
+    }
+
+    /// Let the user pick one of `options` from a list. `None` when the dialog is
+    /// cancelled or closed.
+    pub fn choose(title: &str, message: &str, options: &[String]) -> Option<usize> {
+        if options.is_empty() {
+            return None;
+        }
+        // Each row is tagged with its index, which is what the tools print when it is picked
+        let rows = options.iter().enumerate().flat_map(|(i, option)| [i.to_string(), option.clone()]);
+        let answered = show(title, |tool| {
+            let mut args = match tool {
+                "zenity" => strings(&[
+                    "--list", "--title", title, "--text", message, "--column", "", "--column", "Option", "--hide-column", "1",
+                    "--print-column", "1", "--hide-header",
+                ]),
+                _ => strings(&["--title", title, "--menu", message]),
+            };
+            args.extend(rows);
+            args
+        });
+        let chosen = answered.filter(|(ok, _)| *ok).and_then(|(_, picked)| picked.parse::<usize>().ok()).filter(|&i| i < options.len());
+        log(&format!("Launcher: {} dialog answered {:?}", title, chosen.map(|i| i + 1)));
+        chosen
+    }
+}
@@ -0,0 +1,109 @@
+use crate::launcher::log::log;
+use std::path::{Path, PathBuf};
+
+/// Directory name Tauri uses for bundle resources on Linux (`productName`)
+const PRODUCT_NAME: &str = "voicebox";
+
+/// Places the bundled `backend` folder can live relative to the launcher executable:
+///
+/// - Windows installer: resources next to the exe, or under `resources/`
+/// - macOS bundle: exe in `Contents/MacOS`, resources in `Contents/Resources`
+/// - deb/rpm and AppImage: exe in `usr/bin`, resources in `usr/lib/voicebox`
+/// - Dev/flat: `backend/` next to the exe or one level up
+/// - Cargo build: exe in `<repo>/tauri/src-tauri/target/<profile>`, backend at `<repo>`
+pub fn backend_candidates(exe_dir: &Path) -> Vec<PathBuf> {
+    let parent = exe_dir.parent().unwrap_or(exe_dir);
+    let mut candidates = vec![
+        exe_dir.join("resources").join("backend"), // Windows installed
+        exe_dir.join("backend"),                   // Dev/Flat
+        parent.join("resources").join("backend"),
+        parent.join("Resources").join("backend"), // macOS bundle
+        parent.join("lib").join(PRODUCT_NAME).join("backend"), // Linux packages, AppImage
+        parent.join("backend"),
+    ];
+
+    // Running straight out of a cargo target dir: look above it for the checkout
+    let target = exe_dir.ancestors().take(3).find(|dir| dir.file_name().is_some_and(|n| n == "target"));
+    if let Some(crate_dir) = target.and_then(Path::parent) {
+        candidates.extend(crate_dir.ancestors().take(3).map(|dir| dir.join("backend")));
+    }
+    candidates
+}
+
+/// Return the first existing backend directory, logging every location checked
+pub fn find_backend_dir(exe_dir: &Path) -> Option<PathBuf> {
+    for p in backend_candidates(exe_dir) {
+        if p.is_dir() {
+            log(&format!("Launcher: Found backend at {:?}", p));
+            return Some(p);
+        }
+        log(&format!("Launcher: Checked {:?} (not found)", p));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn install_layout(exe_dir: &Path) {
+        std::fs::create_dir_all(exe_dir.join("resources").join("backend")).unwrap();
+    }
+
+    /// Create the `backend` and `exe` directories under a fresh temp root
+    fn layout(backend: &str, exe: &str) -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend_dir = tmp.path().join(backend);
+        std::fs::create_dir_all(&backend_dir).unwrap();
+        let exe_dir = tmp.path().join(exe);
+        std::fs::create_dir_all(&exe_dir).unwrap();
+        (tmp, backend_dir, exe_dir)
+    }
+
+    #[test]
+    fn finds_backend_in_every_packaged_layout() {
+        let cases = [
+            ("windows installer", "Voicebox/backend", "Voicebox"),
+            ("windows resources dir", "Voicebox/resources/backend", "Voicebox"),
+            ("macos bundle", "Voicebox.app/Contents/Resources/backend", "Voicebox.app/Contents/MacOS"),
+            ("deb", "usr/lib/voicebox/backend", "usr/bin"),
+            ("appimage", "squashfs-root/usr/lib/voicebox/backend", "squashfs-root/usr/bin"),
+            ("cargo run", "repo/backend", "repo/tauri/src-tauri/target/debug"),
+            ("cargo cross build", "repo/backend", "repo/tauri/src-tauri/target/x86_64-pc-windows-gnu/release"),
+        ];
+        for (name, backend, exe) in cases {
+            let (_tmp, backend_dir, exe_dir) = layout(backend, exe);
+            assert_eq!(find_backend_dir(&exe_dir), Some(backend_dir), "{}", name);
+        }
+    }
+
+    #[test]
+    fn reports_nothing_for_an_empty_layout() {
+        let (_tmp, _, exe_dir) = layout("elsewhere/backend", "usr/bin");
+        assert_eq!(find_backend_dir(&exe_dir), None);
+    }
+
+    #[test]
+    fn finds_backend_under_non_ascii_install_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let exe_dir = tmp.path().join("Пользователь").join("音声 アプリ").join("Voicebox");
+        install_layout(&exe_dir);
+
+        let found = find_backend_dir(&exe_dir).unwrap();
+        assert_eq!(found, exe_dir.join("resources").join("backend"));
+    }
+
+    #[test]
+    fn finds_backend_beyond_max_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut exe_dir = tmp.path().to_path_buf();
+        while exe_dir.as_os_str().len() <= 300 {
+            exe_dir.push("very long directory name for the voicebox install");
+        }
+        install_layout(&exe_dir);
+
+        let found = find_backend_dir(&exe_dir).unwrap();
+        assert!(found.as_os_str().len() > 260);
+        assert!(found.join("..").join("backend").is_dir());
+    }
+}
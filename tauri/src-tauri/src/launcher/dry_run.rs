@@ -0,0 +1,113 @@
+// What `voicebox-server --dry-run` reports: everything the launcher would discover
+// and decide before starting the backend, as one JSON document for packagers and
+// support. Discovery runs Python to ask for its version and packages, but nothing is
+// installed, created or started.
+use serde::Serialize;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackendReport {
+    pub dir: Option<PathBuf>,
+    pub version: Option<String>,
+    pub api_version: Option<u32>,
+    /// Set when no backend was found, it is incompatible or its files are damaged
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PythonReport {
+    pub path: Option<PathBuf>,
+    /// How the interpreter was found
+    pub source: Option<String>,
+    pub version: Option<String>,
+    /// What would happen to the managed virtual environment, when one is used
+    pub managed_venv: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyStatus {
+    /// Whether the check ran, rather than being skipped because it passed before
+    pub checked: bool,
+    pub missing: Vec<String>,
+    /// Installed versions the requirements don't allow
+    pub outdated: Vec<String>,
+    /// The launch would offer to install packages
+    pub would_install: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListenReport {
+    pub host: String,
+    pub port: u16,
+    /// Nothing else is listening there
+    pub available: bool,
+    pub proxy: bool,
+    /// Where the backend listens behind the proxy
+    pub backend_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DryRunReport {
+    pub launcher_version: String,
+    pub data_dir: PathBuf,
+    pub config_file: PathBuf,
+    pub config_error: Option<String>,
+    pub backend: BackendReport,
+    pub python: PythonReport,
+    pub dependencies: DependencyStatus,
+    pub listen: ListenReport,
+    /// Command line the backend would be started with
+    pub command: Vec<String>,
+}
+
+impl DryRunReport {
+    /// What would stop or spoil the launch, in the order it would happen
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = [&self.config_error, &self.backend.error, &self.python.error, &self.dependencies.error]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect();
+        if self.dependencies.would_install {
+            problems.push(format!(
+                "{} missing and {} outdated packages",
+                self.dependencies.missing.len(),
+                self.dependencies.outdated.len()
+            ));
+        }
+        if !self.listen.available {
+            problems.push(format!("{}:{} is already in use", self.listen.host, self.listen.port));
+        }
+        problems
+    }
+}
+
+/// Whether a server could bind `addr` now. The socket is closed again right away.
+pub fn port_available(addr: SocketAddr) -> bool {
+    TcpListener::bind(addr).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_what_would_go_wrong() {
+        let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = taken.local_addr().unwrap();
+        assert!(!port_available(addr));
+
+        let mut report = DryRunReport {
+            listen: ListenReport { host: "127.0.0.1".to_string(), port: addr.port(), available: true, ..ListenReport::default() },
+            ..DryRunReport::default()
+        };
+        assert!(report.problems().is_empty());
+        report.dependencies = DependencyStatus { missing: vec!["fastapi".to_string()], would_install: true, ..DependencyStatus::default() };
+        report.listen.available = false;
+        assert_eq!(report.problems(), ["1 missing and 0 outdated packages".to_string(), format!("127.0.0.1:{} is already in use", addr.port())]);
+        assert!(serde_json::to_value(&report).unwrap()["backend"]["dir"].is_null());
+    }
+}
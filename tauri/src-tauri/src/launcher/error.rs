@@ -0,0 +1,234 @@
+use crate::launcher::exit_code;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Step of the launch sequence an error came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchPhase {
+    Config,
+    /// Making sure no other launcher runs on the data directory
+    CheckInstance,
+    LocateBackend,
+    VerifyBackend,
+    CheckVersion,
+    CheckPython,
+    CheckDependencies,
+    /// A step of the first-run setup
+    Setup,
+    StartProxy,
+    StartBackend,
+    StopBackend,
+    /// A one-shot subcommand such as `speak`
+    Command,
+}
+
+impl fmt::Display for LaunchPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LaunchPhase::Config => "Loading configuration",
+            LaunchPhase::CheckInstance => "Checking for a running launcher",
+            LaunchPhase::LocateBackend => "Locating backend",
+            LaunchPhase::VerifyBackend => "Verifying backend files",
+            LaunchPhase::CheckVersion => "Checking backend version",
+            LaunchPhase::CheckPython => "Checking Python",
+            LaunchPhase::CheckDependencies => "Checking dependencies",
+            LaunchPhase::Setup => "Setting up",
+            LaunchPhase::StartProxy => "Starting proxy",
+            LaunchPhase::StartBackend => "Starting backend",
+            LaunchPhase::StopBackend => "Stopping backend",
+            LaunchPhase::Command => "Running command",
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LauncherError {
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// Another launcher holds the data directory
+    #[error("{0}")]
+    AlreadyRunning(String),
+
+    #[error("'backend' directory not found in any of {} expected locations", searched.len())]
+    BackendNotFound { searched: Vec<PathBuf> },
+
+    /// Backend files don't match the manifest installed with them
+    #[error("{0}")]
+    DamagedBackend(String),
+
+    #[error("{0}")]
+    IncompatibleBackend(String),
+
+    #[error("{0}")]
+    PythonUnusable(String),
+
+    #[error("Required Python packages are missing")]
+    MissingDependencies { requirements: Option<PathBuf> },
+
+    /// A first-run setup step failed; the setup continues with it on the next launch
+    #[error("{0}")]
+    Setup(String),
+
+    #[error("{0}")]
+    Proxy(String),
+
+    #[error("Failed to spawn python process: {0}")]
+    Spawn(#[source] std::io::Error),
+
+    #[error("Failed to stop the backend: {0}")]
+    Stop(#[source] std::io::Error),
+
+    /// Launcher state such as a batch job or the paired devices can't be read or written
+    #[error("{0}")]
+    State(String),
+
+    #[error("Request to {url} failed: {message}")]
+    Request { url: String, message: String },
+
+    #[error("{0}")]
+    Output(#[source] std::io::Error),
+
+    /// A file or value handed to a subcommand can't be used
+    #[error("{0}")]
+    InvalidInput(String),
+}
+
+impl LauncherError {
+    pub fn phase(&self) -> LaunchPhase {
+        match self {
+            LauncherError::InvalidConfig(_) => LaunchPhase::Config,
+            LauncherError::AlreadyRunning(_) => LaunchPhase::CheckInstance,
+            LauncherError::BackendNotFound { .. } => LaunchPhase::LocateBackend,
+            LauncherError::DamagedBackend(_) => LaunchPhase::VerifyBackend,
+            LauncherError::IncompatibleBackend(_) => LaunchPhase::CheckVersion,
+            LauncherError::PythonUnusable(_) => LaunchPhase::CheckPython,
+            LauncherError::MissingDependencies { .. } => LaunchPhase::CheckDependencies,
+            LauncherError::Setup(_) => LaunchPhase::Setup,
+            LauncherError::Proxy(_) => LaunchPhase::StartProxy,
+            LauncherError::Spawn(_) => LaunchPhase::StartBackend,
+            LauncherError::Stop(_) => LaunchPhase::StopBackend,
+            LauncherError::Request { .. }
+            | LauncherError::Output(_)
+            | LauncherError::InvalidInput(_)
+            | LauncherError::State(_) => LaunchPhase::Command,
+        }
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            LauncherError::InvalidConfig(_) => {
+                Some("Fix or remove config.json in the data directory".to_string())
+            }
+            LauncherError::AlreadyRunning(_) => Some(
+                "Use the running launcher, stop it first, or pass --if-running take-over to replace it".to_string(),
+            ),
+            LauncherError::BackendNotFound { .. } => Some(
+                "Reinstall Voicebox, or run the launcher from a checkout that contains backend/".to_string(),
+            ),
+            LauncherError::DamagedBackend(_) => Some(
+                "Run voicebox-server --repair, or reinstall Voicebox; if an antivirus quarantined the files, restore them and exclude the install folder".to_string(),
+            ),
+            LauncherError::IncompatibleBackend(_) => Some(
+                "Install matching versions of the app and backend, or pass --skip-version-check".to_string(),
+            ),
+            LauncherError::PythonUnusable(_) => {
+                Some("Install Python 3.11 or newer, create a .venv next to the backend folder, or point VOICEBOX_PYTHON at an interpreter".to_string())
+            }
+            LauncherError::MissingDependencies { requirements } => Some(match requirements {
+                Some(path) => format!("Run: pip install -r \"{}\"", path.display()),
+                None => "Install backend/requirements.txt into the interpreter and retry".to_string(),
+            }),
+            LauncherError::Setup(_) => Some(
+                "Start Voicebox again to continue the setup where it stopped, or run voicebox-server setup --reset to start over".to_string(),
+            ),
+            LauncherError::Proxy(_) => {
+                Some("Another program may be using the port; choose one with --port".to_string())
+            }
+            LauncherError::Spawn(_) => Some("Make sure 'python' is in your system PATH".to_string()),
+            LauncherError::Stop(_) => {
+                Some("The backend may still be running; end its python process before starting Voicebox again".to_string())
+            }
+            LauncherError::State(_) => Some(
+                "Check that the state folder in the data directory is writable and not full; a damaged file named above can be deleted".to_string(),
+            ),
+            LauncherError::Request { .. } => {
+                Some("Is the backend running? Start it with voicebox-server first".to_string())
+            }
+            LauncherError::Output(_) | LauncherError::InvalidInput(_) => None,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            LauncherError::InvalidConfig(_) => exit_code::INVALID_CONFIG,
+            LauncherError::AlreadyRunning(_) => exit_code::ALREADY_RUNNING,
+            LauncherError::BackendNotFound { .. } => exit_code::BACKEND_NOT_FOUND,
+            LauncherError::DamagedBackend(_) => exit_code::DAMAGED_BACKEND,
+            LauncherError::IncompatibleBackend(_) => exit_code::INCOMPATIBLE_BACKEND,
+            LauncherError::PythonUnusable(_) => exit_code::PYTHON_UNUSABLE,
+            LauncherError::MissingDependencies { .. } => exit_code::MISSING_DEPENDENCIES,
+            LauncherError::Setup(_) => exit_code::SETUP_FAILED,
+            LauncherError::Proxy(_) | LauncherError::Spawn(_) => exit_code::SPAWN_FAILED,
+            LauncherError::State(_) => exit_code::STATE_UNUSABLE,
+            LauncherError::Request { .. }
+            | LauncherError::Output(_)
+            | LauncherError::InvalidInput(_)
+            | LauncherError::Stop(_) => exit_code::FAILURE,
+        }
+    }
+}
+
+/// Serialized as `{phase, message, hint, exit_code}` so Tauri commands can return it
+/// to the frontend as-is
+impl Serialize for LauncherError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("LauncherError", 4)?;
+        s.serialize_field("phase", &self.phase())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("hint", &self.hint())?;
+        s.serialize_field("exit_code", &self.exit_code())?;
+        s.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_variants_to_exit_codes_and_phases() {
+        let err = LauncherError::BackendNotFound { searched: vec![PathBuf::from("a"), PathBuf::from("b")] };
+        assert_eq!(err.exit_code(), exit_code::BACKEND_NOT_FOUND);
+        assert_eq!(err.phase(), LaunchPhase::LocateBackend);
+        assert!(err.to_string().contains("2 expected locations"));
+
+        let err = LauncherError::Spawn(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(err.exit_code(), exit_code::SPAWN_FAILED);
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn state_failures_are_not_config_errors() {
+        let err = LauncherError::State("Failed to write jobs/abc.json: disk full".to_string());
+        assert_eq!(err.exit_code(), exit_code::STATE_UNUSABLE);
+        assert!(!err.hint().unwrap().contains("config.json"));
+
+        let err = LauncherError::Stop(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert_eq!(err.phase(), LaunchPhase::StopBackend);
+        assert_eq!(serde_json::to_value(err.phase()).unwrap(), "stop_backend");
+    }
+
+    #[test]
+    fn serializes_for_the_frontend() {
+        let err = LauncherError::MissingDependencies { requirements: None };
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["phase"], "check_dependencies");
+        assert_eq!(json["exit_code"], exit_code::MISSING_DEPENDENCIES);
+        assert!(json["hint"].as_str().unwrap().contains("requirements.txt"));
+    }
+}
@@ -0,0 +1,466 @@
+// Fine-tuning runs supervised by the launcher.
+//
+// The backend serves speech and doesn't train. A fine-tune runs a trainer the user
+// configures, such as a Piper, Coqui or StyleTTS training script, on a dataset exported
+// from the app. The trainer is a process of its own next to the backend, so an
+// out-of-memory kill or crash of either leaves the other running. The launcher reads
+// the trainer's output for progress and appends it to `trainer.log`. It also watches
+// the output directory for checkpoints and the disk space they take, and continues an
+// interrupted run from the newest checkpoint.
+//
+// Runs are kept in `state/fine-tune/<id>.json`, so the app and the CLI see the same ones.
+use crate::launcher::log::log;
+use crate::launcher::process::{CommandSpec, ExitInfo, ProcessRunner};
+use crate::launcher::shutdown;
+use crate::launcher::state::StateFile;
+use crate::launcher::storage::free_space;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
+
+pub const FINE_TUNE_DIR_NAME: &str = "fine-tune";
+pub const TRAINER_LOG_FILE_NAME: &str = "trainer.log";
+/// Matches `step 120/5000`, `Epoch 3/100`, `iter: 7 / 10` and the like
+pub const DEFAULT_PROGRESS_PATTERN: &str = r"(?i)\b(?:step|epoch|iter(?:ation)?)\s*:?\s*(?P<done>\d+)\s*/\s*(?P<total>\d+)";
+/// How often checkpoints and free space are looked at and the run's state is saved
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// How long a stopped trainer gets to write a last checkpoint before it is killed
+const STOP_GRACE: Duration = Duration::from_secs(30);
+/// Directory levels below the output directory searched for checkpoints
+const CHECKPOINT_DEPTH: usize = 4;
+/// Trainer output lines kept to explain a failure
+const TAIL_LINES: usize = 5;
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// The `fine_tune` section of the launcher config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FineTuneConfig {
+    /// Trainer program, e.g. `python` or a training script
+    pub command: String,
+    /// Its arguments; `{dataset}` and `{output}` are replaced with the directories
+    pub args: Vec<String>,
+    /// Arguments added when continuing from a checkpoint; `{checkpoint}` is replaced
+    /// with its path. Without them an interrupted run starts over.
+    pub resume_args: Vec<String>,
+    /// Extensions of the checkpoint files the trainer writes below its output directory
+    pub checkpoint_extensions: Vec<String>,
+    /// Newest checkpoints kept; older ones are deleted as new ones appear (`None` keeps all)
+    pub keep_checkpoints: Option<usize>,
+    /// Regular expression finding progress in the trainer's output, with `done` and
+    /// `total` groups
+    pub progress_pattern: String,
+    /// The run is stopped, to be continued later, when less space is left on the disk
+    /// of the output directory, in GB; 0 turns the check off
+    pub min_free_gb: f64,
+}
+
+impl Default for FineTuneConfig {
+    fn default() -> Self {
+        Self {
+            command: "python".to_string(),
+            args: Vec::new(),
+            resume_args: Vec::new(),
+            checkpoint_extensions: ["ckpt", "pt", "pth", "safetensors"].iter().map(|ext| ext.to_string()).collect(),
+            keep_checkpoints: None,
+            progress_pattern: DEFAULT_PROGRESS_PATTERN.to_string(),
+            min_free_gb: 2.0,
+        }
+    }
+}
+
+impl FineTuneConfig {
+    /// The trainer's command for `job`, continuing from `checkpoint` if given
+    pub fn command(&self, job: &FineTuneJob, checkpoint: Option<&Path>) -> Result<CommandSpec, String> {
+        if self.command.is_empty() || self.args.is_empty() {
+            return Err("No trainer configured; set fine_tune.command and fine_tune.args in the config file".to_string());
+        }
+        let fill = |arg: &str| {
+            arg.replace("{dataset}", &job.dataset.to_string_lossy()).replace("{output}", &job.output.to_string_lossy())
+        };
+        let mut spec = CommandSpec::new(&self.command).args(self.args.iter().map(|arg| fill(arg)));
+        if let Some(checkpoint) = checkpoint {
+            let checkpoint = checkpoint.to_string_lossy();
+            spec = spec.args(self.resume_args.iter().map(|arg| fill(arg).replace("{checkpoint}", &checkpoint)));
+        }
+        Ok(spec.current_dir(&job.output).own_process_group())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum FineTuneStatus {
+    /// Created and not started yet
+    New,
+    Running { pid: u32 },
+    Finished,
+    Failed { error: String },
+    /// Stopped on request or for lack of disk space, to be continued
+    Stopped {
+        reason: String,
+        #[serde(default)]
+        low_disk: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FineTuneJob {
+    pub id: String,
+    pub dataset: PathBuf,
+    pub output: PathBuf,
+    pub status: FineTuneStatus,
+    /// Share done from 0 to 1 as the trainer last reported it
+    pub fraction: Option<f32>,
+    pub latest_checkpoint: Option<PathBuf>,
+    /// Times the run was continued from a checkpoint
+    pub resumes: u32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+static NEXT_RUN: AtomicU32 = AtomicU32::new(0);
+
+impl FineTuneJob {
+    pub fn new(dataset: &Path, output: &Path) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: format!(
+                "{}-{}-{}",
+                now.format("%Y%m%d-%H%M%S"),
+                std::process::id(),
+                NEXT_RUN.fetch_add(1, Ordering::Relaxed)
+            ),
+            dataset: dataset.to_path_buf(),
+            output: output.to_path_buf(),
+            status: FineTuneStatus::New,
+            fraction: None,
+            latest_checkpoint: None,
+            resumes: 0,
+            created_at: now.to_rfc3339(),
+            updated_at: now.to_rfc3339(),
+        }
+    }
+}
+
+/// Fine-tune runs under `state/fine-tune`, one file each
+pub struct FineTuneStore {
+    dir: PathBuf,
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl FineTuneStore {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { dir: state_dir.join(FINE_TUNE_DIR_NAME) }
+    }
+
+    fn file(&self, id: &str) -> StateFile<FineTuneJob> {
+        StateFile::new(self.dir.join(format!("{}.json", id)))
+    }
+
+    pub fn save(&self, job: &FineTuneJob) -> Result<(), String> {
+        self.file(&job.id).write(job)
+    }
+
+    pub fn load(&self, id: &str) -> Result<FineTuneJob, String> {
+        let job = match is_valid_id(id) {
+            true => self.file(id).read()?,
+            false => None,
+        };
+        job.ok_or_else(|| format!("No fine-tune run {:?}", id))
+    }
+
+    /// Every run, newest first
+    pub fn list(&self) -> Vec<FineTuneJob> {
+        let mut jobs: Vec<FineTuneJob> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.path().file_stem().and_then(|id| id.to_str()).map(str::to_string))
+            .filter_map(|id| self.load(&id).ok())
+            .collect();
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        jobs
+    }
+}
+
+/// Finds progress in trainer output
+pub struct ProgressParser(Regex);
+
+impl ProgressParser {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid fine_tune.progress_pattern: {}", e))?;
+        let groups: Vec<&str> = regex.capture_names().flatten().collect();
+        if !groups.contains(&"done") || !groups.contains(&"total") {
+            return Err("fine_tune.progress_pattern needs groups named done and total".to_string());
+        }
+        Ok(Self(regex))
+    }
+
+    /// Share done according to `line`, if it tells
+    pub fn fraction(&self, line: &str) -> Option<f32> {
+        let captures = self.0.captures(line)?;
+        let done: f32 = captures["done"].parse().ok()?;
+        let total: f32 = captures["total"].parse().ok()?;
+        (total > 0.0).then(|| (done / total).clamp(0.0, 1.0))
+    }
+}
+
+fn collect_checkpoints(dir: &Path, extensions: &[String], depth: usize, found: &mut Vec<(SystemTime, PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let (path, Ok(meta)) = (entry.path(), entry.metadata()) else { continue };
+        if meta.is_dir() {
+            if depth < CHECKPOINT_DEPTH {
+                collect_checkpoints(&path, extensions, depth + 1, found);
+            }
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.iter().any(|known| known.eq_ignore_ascii_case(ext)))
+        {
+            found.push((meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), path));
+        }
+    }
+}
+
+/// Checkpoint files below `dir`, oldest first
+pub fn checkpoints(dir: &Path, extensions: &[String]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    collect_checkpoints(dir, extensions, 0, &mut found);
+    found.sort();
+    found.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Delete all but the newest `keep` of `checkpoints` (oldest first). Returns how many
+/// were deleted.
+pub fn prune_checkpoints(checkpoints: &[PathBuf], keep: usize) -> usize {
+    let old = &checkpoints[..checkpoints.len().saturating_sub(keep)];
+    old.iter().filter(|path| std::fs::remove_file(path).is_ok()).count()
+}
+
+/// Lines of `stream`, also split at the carriage returns progress bars redraw with.
+/// Stops when `each` returns false.
+fn read_lines(stream: impl Read, mut each: impl FnMut(String) -> bool) {
+    let mut reader = BufReader::new(stream);
+    let (mut line, mut byte) = (Vec::new(), [0u8; 1]);
+    while reader.read(&mut byte).is_ok_and(|read| read == 1) {
+        if byte[0] != b'\n' && byte[0] != b'\r' {
+            line.push(byte[0]);
+            continue;
+        }
+        if !line.is_empty() && !each(String::from_utf8_lossy(&line).into_owned()) {
+            return;
+        }
+        line.clear();
+    }
+    if !line.is_empty() {
+        each(String::from_utf8_lossy(&line).into_owned());
+    }
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// Run `job`, continuing from its newest checkpoint if it ran before, until the
+/// trainer exits or is stopped. The run's state is kept in `store`; `stop` is polled
+/// to stop it to be continued later, and `on_update` gets the job whenever its
+/// progress, checkpoint or state changes. Only failing to start is an error; how the
+/// run ended is in the job's status.
+pub fn run(
+    runner: &dyn ProcessRunner,
+    config: &FineTuneConfig,
+    store: &FineTuneStore,
+    job: &mut FineTuneJob,
+    stop: impl Fn() -> bool,
+    mut on_update: impl FnMut(&FineTuneJob),
+) -> Result<(), String> {
+    let parser = ProgressParser::new(&config.progress_pattern)?;
+    std::fs::create_dir_all(&job.output).map_err(|e| format!("Failed to create {}: {}", job.output.display(), e))?;
+    let resume_from = checkpoints(&job.output, &config.checkpoint_extensions).pop().filter(|_| !config.resume_args.is_empty());
+    let spec = config.command(job, resume_from.as_deref())?;
+    let log_path = job.output.join(TRAINER_LOG_FILE_NAME);
+    let mut trainer_log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .map_err(|e| format!("Failed to open {}: {}", log_path.display(), e))?;
+
+    let mut child = runner.spawn(&spec).map_err(|e| format!("Failed to start the trainer {}: {}", config.command, e))?;
+    if let Some(checkpoint) = &resume_from {
+        job.resumes += 1;
+        log(&format!("Launcher: Continuing fine-tune {} from {}", job.id, checkpoint.display()));
+    }
+    log(&format!("Launcher: Fine-tune {} running as PID {}", job.id, child.id()));
+    job.status = FineTuneStatus::Running { pid: child.id() };
+    job.latest_checkpoint = resume_from;
+    job.updated_at = now();
+    store.save(job)?;
+    on_update(job);
+
+    let (sender, lines) = mpsc::channel();
+    for stream in [child.take_stdout(), child.take_stderr()].into_iter().flatten() {
+        let sender = sender.clone();
+        std::thread::spawn(move || read_lines(stream, |line| sender.send(line).is_ok()));
+    }
+    drop(sender);
+
+    // Returns whether the line moved the progress
+    let mut tail = VecDeque::new();
+    let mut handle_line = |job: &mut FineTuneJob, line: String| {
+        let _ = writeln!(trainer_log, "{}", line);
+        let fraction = parser.fraction(&line).filter(|fraction| job.fraction != Some(*fraction));
+        tail.push_back(line);
+        if tail.len() > TAIL_LINES {
+            tail.pop_front();
+        }
+        job.fraction = fraction.or(job.fraction);
+        fraction.is_some()
+    };
+
+    let mut checked: Option<Instant> = None;
+    let mut stopped_because = None;
+    let status: ExitInfo = loop {
+        match lines.recv_timeout(Duration::from_millis(200)) {
+            Ok(line) => {
+                if handle_line(job, line) {
+                    on_update(job)
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => std::thread::sleep(Duration::from_millis(200)),
+        }
+        if let Some(status) = child.try_wait().map_err(|e| format!("Failed to wait for the trainer: {}", e))? {
+            break status;
+        }
+        if checked.is_none_or(|at| at.elapsed() >= CHECK_INTERVAL) {
+            checked = Some(Instant::now());
+            let found = checkpoints(&job.output, &config.checkpoint_extensions);
+            if let Some(keep) = config.keep_checkpoints {
+                prune_checkpoints(&found, keep);
+            }
+            if found.last() != job.latest_checkpoint.as_ref() {
+                job.latest_checkpoint = found.last().cloned();
+                on_update(job);
+            }
+            job.updated_at = now();
+            store.save(job)?;
+            let free = free_space(&job.output).map(|free| free as f64 / GB);
+            if let Some(free) = free.filter(|free| *free < config.min_free_gb) {
+                stopped_because = Some((format!("Only {:.1} GB left on the disk", free), true));
+            }
+        }
+        if stopped_because.is_none() && stop() {
+            stopped_because = Some(("Stopped on request".to_string(), false));
+        }
+        if let Some((reason, _)) = &stopped_because {
+            log(&format!("Launcher: Stopping fine-tune {}: {}", job.id, reason));
+            break shutdown::stop(child.as_mut(), || false, STOP_GRACE).map_err(|e| format!("Failed to stop the trainer: {}", e))?;
+        }
+    };
+    while let Ok(line) = lines.recv_timeout(Duration::from_secs(1)) {
+        handle_line(job, line);
+    }
+
+    job.latest_checkpoint = checkpoints(&job.output, &config.checkpoint_extensions).pop().or(job.latest_checkpoint.take());
+    job.status = match stopped_because {
+        Some((reason, low_disk)) => FineTuneStatus::Stopped { reason, low_disk },
+        None if status.success() => {
+            job.fraction = Some(1.0);
+            FineTuneStatus::Finished
+        }
+        None => {
+            let last = tail.back().map(|line| format!(": {}", line)).unwrap_or_default();
+            FineTuneStatus::Failed { error: format!("Trainer ended with {}{}", status, last) }
+        }
+    };
+    log(&format!("Launcher: Fine-tune {} ended with {}", job.id, status));
+    job.updated_at = now();
+    store.save(job)?;
+    on_update(job);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    fn config() -> FineTuneConfig {
+        FineTuneConfig {
+            command: "trainer".to_string(),
+            args: vec!["--data".to_string(), "{dataset}/metadata.csv".to_string(), "--out={output}".to_string()],
+            resume_args: vec!["--resume".to_string(), "{checkpoint}".to_string()],
+            min_free_gb: 0.0,
+            ..FineTuneConfig::default()
+        }
+    }
+
+    #[test]
+    fn parses_progress_from_common_trainers() {
+        let parser = ProgressParser::new(DEFAULT_PROGRESS_PATTERN).unwrap();
+        assert_eq!(parser.fraction("Epoch 3/100: loss=0.42"), Some(0.03));
+        assert_eq!(parser.fraction(" step: 250 / 1000 "), Some(0.25));
+        assert_eq!(parser.fraction("Iteration 7/7"), Some(1.0));
+        assert_eq!(parser.fraction("loaded 300/300 files"), None);
+        assert!(ProgressParser::new(r"(?P<done>\d+)").is_err());
+    }
+
+    #[test]
+    fn keeps_the_newest_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("lightning_logs/version_0/checkpoints");
+        std::fs::create_dir_all(&nested).unwrap();
+        for (i, name) in ["epoch=1.ckpt", "epoch=2.ckpt", "epoch=3.ckpt"].iter().enumerate() {
+            let path = nested.join(name);
+            std::fs::write(&path, b"weights").unwrap();
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(100 * (i as u64 + 1))).unwrap();
+        }
+        std::fs::write(dir.path().join("config.json"), b"{}").unwrap();
+
+        let found = checkpoints(dir.path(), &config().checkpoint_extensions);
+        assert_eq!(found.len(), 3);
+        assert!(found[2].ends_with("epoch=3.ckpt"));
+        assert_eq!(prune_checkpoints(&found, 1), 2);
+        assert_eq!(checkpoints(dir.path(), &config().checkpoint_extensions), [nested.join("epoch=3.ckpt")]);
+    }
+
+    #[test]
+    fn runs_and_continues_from_the_last_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FineTuneStore::new(&dir.path().join("state"));
+        let mut job = FineTuneJob::new(&dir.path().join("dataset"), &dir.path().join("training"));
+        let runner = FakeRunner::new();
+        runner.script("trainer", Script::exits(1).stdout("Epoch 1/4\rEpoch 2/4\nCUDA out of memory\n").delay(Duration::from_millis(300)));
+        runner.script("trainer", Script::exits(0).stdout("Epoch 3/4\nEpoch 4/4\n"));
+
+        let mut updates = Vec::new();
+        run(&runner, &config(), &store, &mut job, || false, |job| updates.push(job.fraction)).unwrap();
+        assert_eq!(job.status, FineTuneStatus::Failed { error: "Trainer ended with exit code 1: CUDA out of memory".to_string() });
+        assert_eq!(job.fraction, Some(0.5));
+        assert!(updates.contains(&Some(0.25)));
+        assert_eq!(store.load(&job.id).unwrap(), job);
+        let log = std::fs::read_to_string(job.output.join(TRAINER_LOG_FILE_NAME)).unwrap();
+        assert_eq!(log, "Epoch 1/4\nEpoch 2/4\nCUDA out of memory\n");
+
+        std::fs::write(job.output.join("last.ckpt"), b"weights").unwrap();
+        run(&runner, &config(), &store, &mut job, || false, |_| {}).unwrap();
+        assert_eq!((job.status.clone(), job.fraction, job.resumes), (FineTuneStatus::Finished, Some(1.0), 1));
+        assert_eq!(job.latest_checkpoint, Some(job.output.join("last.ckpt")));
+        let calls = runner.calls();
+        let args: Vec<String> = calls[1].args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        assert_eq!(args[1], format!("{}/metadata.csv", job.dataset.display()));
+        assert_eq!(args[2], format!("--out={}", job.output.display()));
+        assert_eq!(args[3..], ["--resume".to_string(), job.output.join("last.ckpt").display().to_string()]);
+        assert_eq!(store.list(), [job]);
+    }
+}
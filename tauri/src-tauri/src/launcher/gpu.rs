@@ -0,0 +1,241 @@
+use crate::launcher::deps::{check_dependencies, Requirement};
+use crate::launcher::log::log;
+use crate::launcher::process::{CommandSpec, ProcessRunner};
+use std::fmt;
+use std::path::Path;
+
+/// Packages installed from the PyTorch index; the backend's requirements.txt leaves
+/// them out so a user's existing CUDA build isn't replaced
+pub const TORCH_PACKAGES: &[&str] = &["torch", "torchaudio"];
+const PYTORCH_INDEX: &str = "https://download.pytorch.org/whl";
+/// Written by ROCm installs on Linux
+const ROCM_VERSION_FILE: &str = "/opt/rocm/.info/version";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+}
+
+impl GpuVendor {
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name.contains("nvidia") || name.contains("geforce") || name.contains("quadro") {
+            Some(GpuVendor::Nvidia)
+        } else if name.contains("amd") || name.contains("radeon") || name.contains("ati technologies") {
+            Some(GpuVendor::Amd)
+        } else if name.contains("intel") {
+            Some(GpuVendor::Intel)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gpu {
+    pub vendor: GpuVendor,
+    pub name: String,
+}
+
+/// Graphics hardware and the compute runtimes its driver supports
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GpuInfo {
+    pub gpus: Vec<Gpu>,
+    /// Highest CUDA version the NVIDIA driver supports, as `(major, minor)`
+    pub cuda: Option<(u32, u32)>,
+    pub rocm: Option<(u32, u32)>,
+}
+
+/// Where PyTorch wheels come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TorchIndex {
+    /// A CUDA build, e.g. `cu121`
+    Cuda(&'static str),
+    /// A ROCm build, e.g. `rocm6.1`
+    Rocm(&'static str),
+    Cpu,
+    /// PyPI's own wheels, which already use Metal on macOS
+    Default,
+}
+
+impl TorchIndex {
+    pub fn index_url(&self) -> Option<String> {
+        match self {
+            TorchIndex::Cuda(tag) | TorchIndex::Rocm(tag) => Some(format!("{}/{}", PYTORCH_INDEX, tag)),
+            TorchIndex::Cpu => Some(format!("{}/cpu", PYTORCH_INDEX)),
+            TorchIndex::Default => None,
+        }
+    }
+}
+
+impl fmt::Display for TorchIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TorchIndex::Cuda(tag) | TorchIndex::Rocm(tag) => f.write_str(tag),
+            TorchIndex::Cpu => f.write_str("cpu"),
+            TorchIndex::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// `(12, 4)` from `12.4` or `6.1.2-119`
+fn major_minor(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// The `CUDA Version: 12.4` from the banner of plain `nvidia-smi`
+pub fn parse_cuda_version(banner: &str) -> Option<(u32, u32)> {
+    let rest = banner.split("CUDA Version:").nth(1)?;
+    major_minor(rest.split_whitespace().next()?)
+}
+
+/// Display adapters in `lspci` output
+pub fn parse_lspci(listing: &str) -> Vec<Gpu> {
+    listing
+        .lines()
+        .filter(|line| ["VGA compatible controller", "3D controller", "Display controller"].iter().any(|c| line.contains(c)))
+        .filter_map(|line| {
+            let name = line.split_once(": ")?.1.trim().to_string();
+            Some(Gpu { vendor: GpuVendor::from_name(&name)?, name })
+        })
+        .collect()
+}
+
+fn stdout(runner: &dyn ProcessRunner, spec: &CommandSpec) -> Option<String> {
+    let output = runner.output(spec).ok().filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+impl GpuInfo {
+    /// Ask the installed drivers. NVIDIA cards are found through `nvidia-smi` on every
+    /// platform; other adapters through WMI on Windows and `lspci` on Linux.
+    pub fn detect(runner: &dyn ProcessRunner) -> Self {
+        let mut info = GpuInfo::default();
+        let nvidia_names = CommandSpec::new("nvidia-smi").args(["--query-gpu=name", "--format=csv,noheader"]);
+        if let Some(names) = stdout(runner, &nvidia_names) {
+            info.gpus.extend(names.lines().filter(|n| !n.trim().is_empty()).map(|name| Gpu {
+                vendor: GpuVendor::Nvidia,
+                name: name.trim().to_string(),
+            }));
+            info.cuda = stdout(runner, &CommandSpec::new("nvidia-smi")).as_deref().and_then(parse_cuda_version);
+        }
+
+        let others = if cfg!(windows) {
+            let query = CommandSpec::new("powershell")
+                .args(["-NoProfile", "-Command", "Get-CimInstance Win32_VideoController | ForEach-Object Name"]);
+            stdout(runner, &query)
+                .map(|names| {
+                    names
+                        .lines()
+                        .filter_map(|name| Some(Gpu { vendor: GpuVendor::from_name(name)?, name: name.trim().to_string() }))
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else if cfg!(target_os = "linux") {
+            stdout(runner, &CommandSpec::new("lspci")).map(|listing| parse_lspci(&listing)).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        // nvidia-smi already named the NVIDIA cards, more precisely
+        info.gpus.extend(others.into_iter().filter(|gpu| gpu.vendor != GpuVendor::Nvidia || info.cuda.is_none()));
+
+        if cfg!(target_os = "linux") {
+            info.rocm = std::fs::read_to_string(Path::new(ROCM_VERSION_FILE)).ok().as_deref().and_then(major_minor);
+        }
+        log(&format!("Launcher: Detected GPUs {:?}, CUDA {:?}, ROCm {:?}", info.gpus, info.cuda, info.rocm));
+        info
+    }
+
+    pub fn has(&self, vendor: GpuVendor) -> bool {
+        self.gpus.iter().any(|gpu| gpu.vendor == vendor)
+    }
+
+    /// The PyTorch build to install: the newest CUDA build the driver can run, a ROCm
+    /// build for AMD cards with ROCm installed (Linux only), and the CPU build otherwise
+    pub fn torch_index(&self) -> TorchIndex {
+        if cfg!(target_os = "macos") {
+            return TorchIndex::Default;
+        }
+        match self.cuda {
+            Some(cuda) if cuda >= (12, 1) => return TorchIndex::Cuda("cu121"),
+            Some(cuda) if cuda >= (11, 8) => return TorchIndex::Cuda("cu118"),
+            _ => {}
+        }
+        if self.has(GpuVendor::Amd) && cfg!(target_os = "linux") {
+            match self.rocm {
+                Some(rocm) if rocm >= (6, 1) => return TorchIndex::Rocm("rocm6.1"),
+                Some(rocm) if rocm >= (6, 0) => return TorchIndex::Rocm("rocm6.0"),
+                _ => {}
+            }
+        }
+        TorchIndex::Cpu
+    }
+}
+
+/// The PyTorch build to install when the interpreter `python` runs has no torch yet.
+/// An existing install is left alone, whichever build it is.
+pub fn torch_to_install(runner: &dyn ProcessRunner, python: &CommandSpec) -> Option<TorchIndex> {
+    let torch = [Requirement { name: "torch".to_string(), specifiers: Vec::new() }];
+    match check_dependencies(runner, python, &torch) {
+        Ok(report) if !report.missing.is_empty() => {
+            let index = GpuInfo::detect(runner).torch_index();
+            log(&format!("Launcher: PyTorch is not installed; installing the {} build", index));
+            Some(index)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    #[test]
+    fn reads_driver_output() {
+        let banner = "| NVIDIA-SMI 550.54.14    Driver Version: 550.54.14    CUDA Version: 12.4     |";
+        assert_eq!(parse_cuda_version(banner), Some((12, 4)));
+        assert_eq!(parse_cuda_version("No devices were found"), None);
+
+        let lspci = "00:02.0 VGA compatible controller: Intel Corporation UHD Graphics 630 (rev 02)\n\
+                     01:00.0 VGA compatible controller: Advanced Micro Devices, Inc. [AMD/ATI] Navi 21 [Radeon RX 6800]\n\
+                     00:1f.3 Audio device: Intel Corporation Cannon Lake PCH cAVS (rev 10)\n";
+        let gpus = parse_lspci(lspci);
+        assert_eq!(gpus.iter().map(|g| g.vendor).collect::<Vec<_>>(), [GpuVendor::Intel, GpuVendor::Amd]);
+        assert_eq!(major_minor("6.1.2-119"), Some((6, 1)));
+    }
+
+    #[test]
+    fn detects_nvidia_through_nvidia_smi() {
+        let runner = FakeRunner::new();
+        runner.script("nvidia-smi", Script::exits(0).stdout("NVIDIA GeForce RTX 4090\n| Driver Version: 550.54  CUDA Version: 12.4 |\n"));
+        let info = GpuInfo::detect(&runner);
+        assert!(info.has(GpuVendor::Nvidia));
+        assert_eq!(info.cuda, Some((12, 4)));
+    }
+
+    #[test]
+    fn picks_the_torch_build_for_the_hardware() {
+        if cfg!(target_os = "macos") {
+            return;
+        }
+        let nvidia = |cuda| GpuInfo {
+            gpus: vec![Gpu { vendor: GpuVendor::Nvidia, name: "RTX".to_string() }],
+            cuda: Some(cuda),
+            rocm: None,
+        };
+        assert_eq!(nvidia((12, 4)).torch_index(), TorchIndex::Cuda("cu121"));
+        assert_eq!(nvidia((11, 8)).torch_index(), TorchIndex::Cuda("cu118"));
+        assert_eq!(nvidia((11, 4)).torch_index(), TorchIndex::Cpu);
+        assert_eq!(GpuInfo::default().torch_index().index_url().unwrap(), "https://download.pytorch.org/whl/cpu");
+        if cfg!(target_os = "linux") {
+            let amd = GpuInfo { gpus: vec![Gpu { vendor: GpuVendor::Amd, name: "RX".to_string() }], cuda: None, rocm: Some((6, 2)) };
+            assert_eq!(amd.torch_index(), TorchIndex::Rocm("rocm6.1"));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::launcher::log::log;
+use crate::launcher::process::{CommandSpec, ExitInfo, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long the launcher waits for a hook before leaving it running and exiting
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A user command run when the backend exits unexpectedly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitHook {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// What happened to the backend, handed to the hook as `VOICEBOX_*` env vars
+#[derive(Debug, Clone)]
+pub struct ExitReport {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub pid: u32,
+    pub port: u16,
+    pub uptime: Duration,
+    pub log_path: PathBuf,
+}
+
+impl ExitReport {
+    pub fn new(status: ExitInfo, pid: u32, port: u16, uptime: Duration, log_path: PathBuf) -> Self {
+        Self {
+            code: status.code,
+            signal: status.signal,
+            pid,
+            port,
+            uptime,
+            log_path,
+        }
+    }
+
+    /// A clean exit is requested shutdown, not something worth alerting on
+    pub fn is_unexpected(&self) -> bool {
+        self.code != Some(0)
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match (self.code, self.signal) {
+            (Some(0), _) => "exited",
+            (_, Some(_)) => "killed_by_signal",
+            _ => "crashed",
+        }
+    }
+
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("VOICEBOX_EXIT_REASON", self.reason().to_string()),
+            ("VOICEBOX_BACKEND_PID", self.pid.to_string()),
+            ("VOICEBOX_PORT", self.port.to_string()),
+            ("VOICEBOX_UPTIME_SECS", self.uptime.as_secs().to_string()),
+            ("VOICEBOX_LOG_PATH", self.log_path.display().to_string()),
+        ];
+        if let Some(code) = self.code {
+            env.push(("VOICEBOX_EXIT_CODE", code.to_string()));
+        }
+        if let Some(signal) = self.signal {
+            env.push(("VOICEBOX_EXIT_SIGNAL", signal.to_string()));
+        }
+        env
+    }
+}
+
+/// Run `hook` for `report`, waiting up to 30 seconds for it to finish
+pub fn run_exit_hook(runner: &dyn ProcessRunner, hook: &ExitHook, report: &ExitReport) {
+    log(&format!(
+        "Launcher: Running exit hook '{}' (reason: {})",
+        hook.command,
+        report.reason()
+    ));
+
+    let spec = CommandSpec::new(&hook.command).args(&hook.args).envs(report.env());
+    let mut child = match runner.spawn_inherited(&spec) {
+        Ok(child) => child,
+        Err(e) => {
+            log(&format!("Launcher: Failed to start exit hook: {}", e));
+            return;
+        }
+    };
+
+    let started = Instant::now();
+    while started.elapsed() < HOOK_TIMEOUT {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                log(&format!("Launcher: Exit hook finished with {}", status));
+                return;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                log(&format!("Launcher: Failed to wait for exit hook: {}", e));
+                return;
+            }
+        }
+    }
+    log("Launcher: Exit hook still running after 30s, leaving it in the background");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    #[test]
+    fn hook_receives_exit_details() {
+        let runner = FakeRunner::new();
+        runner.script("notify", Script::exits(0));
+        let hook = ExitHook { command: "notify".to_string(), args: vec!["--urgent".to_string()] };
+        let report = ExitReport::new(ExitInfo::code(3), 42, 17493, Duration::from_secs(90), PathBuf::from("x.log"));
+        assert!(report.is_unexpected());
+
+        run_exit_hook(&runner, &hook, &report);
+        let call = &runner.calls()[0];
+        assert_eq!(call.args, vec!["--urgent"]);
+        assert_eq!(call.env_value("VOICEBOX_EXIT_REASON").unwrap(), "crashed");
+        assert_eq!(call.env_value("VOICEBOX_EXIT_CODE").unwrap(), "3");
+        assert_eq!(call.env_value("VOICEBOX_UPTIME_SECS").unwrap(), "90");
+    }
+
+    #[test]
+    fn reason_tells_a_crash_from_a_signal() {
+        let report = |status| ExitReport::new(status, 42, 17493, Duration::from_secs(5), PathBuf::from("x.log"));
+        let clean = report(ExitInfo::code(0));
+        assert!(!clean.is_unexpected());
+        assert_eq!(clean.reason(), "exited");
+        assert_eq!(report(ExitInfo::code(1)).reason(), "crashed");
+        let killed = report(ExitInfo { code: None, signal: Some(9) });
+        assert!(killed.is_unexpected());
+        assert_eq!(killed.reason(), "killed_by_signal");
+    }
+
+    #[test]
+    fn env_describes_the_exit() {
+        let report = ExitReport::new(ExitInfo { code: None, signal: Some(9) }, 42, 17493, Duration::from_millis(90_500), PathBuf::from("x.log"));
+        let env = report.env();
+        let value = |name| env.iter().find(|(key, _)| *key == name).map(|(_, value)| value.as_str());
+        assert_eq!(value("VOICEBOX_EXIT_REASON"), Some("killed_by_signal"));
+        assert_eq!(value("VOICEBOX_BACKEND_PID"), Some("42"));
+        assert_eq!(value("VOICEBOX_PORT"), Some("17493"));
+        assert_eq!(value("VOICEBOX_UPTIME_SECS"), Some("90"));
+        assert_eq!(value("VOICEBOX_LOG_PATH"), Some("x.log"));
+        assert_eq!(value("VOICEBOX_EXIT_SIGNAL"), Some("9"));
+        assert_eq!(value("VOICEBOX_EXIT_CODE"), None);
+    }
+}
@@ -0,0 +1,112 @@
+// One launcher per data directory.
+//
+// Two launchers on the same data directory would start two backends fighting over the
+// port and the database. The first one holds an exclusive lock on `state/launcher.lock`
+// for as long as it runs. The system releases the lock when the process exits in any
+// way, so a crashed launcher leaves nothing stale behind. A second launcher either
+// leaves the running one in charge, or asks it to stop and takes over once the lock is
+// free.
+use crate::launcher::shutdown::{self, SHUTDOWN_GRACE};
+use crate::launcher::state::{runtime_state, RuntimeState};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const INSTANCE_LOCK_FILE_NAME: &str = "launcher.lock";
+/// How long a launcher being taken over gets to stop its backend and exit
+pub const TAKEOVER_TIMEOUT: Duration = Duration::from_secs(SHUTDOWN_GRACE.as_secs() + 5);
+
+/// What to do when another launcher already runs on the same data directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum IfRunning {
+    /// Leave the running launcher in charge and exit successfully
+    #[default]
+    Reuse,
+    /// Stop the running launcher and its backend, then start
+    TakeOver,
+    /// Exit with an error
+    Fail,
+}
+
+/// Held by the launcher that runs the backend; dropping it lets another one start
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// The lock for `state_dir`, or `None` while another launcher holds it
+    pub fn try_acquire(state_dir: &Path) -> Result<Option<Self>, String> {
+        std::fs::create_dir_all(state_dir).map_err(|e| format!("Failed to create {}: {}", state_dir.display(), e))?;
+        let path = state_dir.join(INSTANCE_LOCK_FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(format!("Failed to lock {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Wait up to `timeout` for the launcher holding the lock to exit
+    pub fn acquire_within(state_dir: &Path, timeout: Duration) -> Result<Option<Self>, String> {
+        let until = Instant::now() + timeout;
+        loop {
+            if let Some(lock) = Self::try_acquire(state_dir)? {
+                return Ok(Some(lock));
+            }
+            if Instant::now() >= until {
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+/// The running launcher as it describes itself in `runtime.json`; `None` while it is
+/// still starting the backend
+pub fn running(state_dir: &Path) -> Option<RuntimeState> {
+    runtime_state(state_dir).read().ok().flatten()
+}
+
+/// Ask the launcher described by `instance` to stop. Its backend is asked to shut
+/// down, after which the launcher exits, and on Unix the launcher gets SIGTERM as well.
+/// Returns whether either request was delivered.
+pub fn request_stop(instance: &RuntimeState) -> bool {
+    let asked = shutdown::request_backend_shutdown(instance.backend_port.unwrap_or(instance.port));
+    #[cfg(unix)]
+    // SAFETY: only sends a signal to the PID
+    let signalled = unsafe { libc::kill(instance.pid as libc::pid_t, libc::SIGTERM) } == 0;
+    #[cfg(not(unix))]
+    let signalled = false;
+    asked || signalled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_one_holder_at_a_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = InstanceLock::try_acquire(dir.path()).unwrap().unwrap();
+        assert!(InstanceLock::try_acquire(dir.path()).unwrap().is_none());
+
+        let started = Instant::now();
+        assert!(InstanceLock::acquire_within(dir.path(), Duration::from_millis(200)).unwrap().is_none());
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        let release = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            drop(lock);
+        });
+        assert!(InstanceLock::acquire_within(dir.path(), Duration::from_secs(5)).unwrap().is_some());
+        release.join().unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+// Integrity of the installed backend.
+//
+// The build hashes every file of the bundled backend into `manifest.json`, installed
+// next to them, and embeds the files in the launcher. Before starting, the launcher
+// checks the backend folder against the manifest, so a truncated install or a file an
+// antivirus quarantined is reported as such instead of as an import error from deep
+// inside Python, and can restore the damaged files from its own copy.
+use crate::launcher::log::log;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// The backend files this launcher was built with, by path relative to the backend folder
+pub static BUNDLED_FILES: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/backend_files.rs"));
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Size and hash of each backend file, by path relative to the backend folder with `/`
+/// separators
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendManifest {
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+impl BackendManifest {
+    /// The manifest installed with the backend. `None` for a checkout, which has none.
+    pub fn load(backend_dir: &Path) -> Result<Option<Self>, String> {
+        let path = backend_dir.join(MANIFEST_FILE_NAME);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&content).map(Some).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+}
+
+/// What is wrong with a backend file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Damage {
+    Missing,
+    /// Shorter than it should be, as a cut-off copy is
+    Truncated { size: u64, expected: u64 },
+    /// Different content, or unreadable
+    Modified,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    /// Damaged files by relative path
+    pub damaged: BTreeMap<String, Damage>,
+}
+
+impl IntegrityReport {
+    pub fn is_intact(&self) -> bool {
+        self.damaged.is_empty()
+    }
+
+    /// e.g. `2 of 27 backend files are damaged: main.py (missing), tts.py (truncated)`
+    pub fn summary(&self) -> String {
+        let files: Vec<String> = self
+            .damaged
+            .iter()
+            .map(|(path, damage)| {
+                let damage = match damage {
+                    Damage::Missing => "missing",
+                    Damage::Truncated { .. } => "truncated",
+                    Damage::Modified => "modified",
+                };
+                format!("{} ({})", path, damage)
+            })
+            .collect();
+        format!("{} of {} backend files are damaged: {}", self.damaged.len(), self.checked, files.join(", "))
+    }
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Compare the files in `backend_dir` with `manifest`. Files the manifest doesn't list,
+/// such as bytecode caches, are ignored.
+pub fn verify(backend_dir: &Path, manifest: &BackendManifest) -> IntegrityReport {
+    let mut report = IntegrityReport { checked: manifest.files.len(), damaged: BTreeMap::new() };
+    for (relative, expected) in &manifest.files {
+        let path = backend_dir.join(relative);
+        let size = match std::fs::metadata(&path) {
+            Ok(meta) if meta.is_file() => meta.len(),
+            Ok(_) => {
+                report.damaged.insert(relative.clone(), Damage::Modified);
+                continue;
+            }
+            Err(_) => {
+                report.damaged.insert(relative.clone(), Damage::Missing);
+                continue;
+            }
+        };
+        let damage = if size < expected.size {
+            Some(Damage::Truncated { size, expected: expected.size })
+        } else if size != expected.size || sha256_file(&path).ok().as_ref() != Some(&expected.sha256) {
+            Some(Damage::Modified)
+        } else {
+            None
+        };
+        if let Some(damage) = damage {
+            report.damaged.insert(relative.clone(), damage);
+        }
+    }
+    report
+}
+
+/// Write the damaged files of `report` back from `bundled`, where it has them with the
+/// content the manifest expects. Returns the files that couldn't be restored.
+pub fn repair(
+    backend_dir: &Path,
+    manifest: &BackendManifest,
+    report: &IntegrityReport,
+    bundled: &[(&str, &[u8])],
+) -> Vec<String> {
+    let mut unrepaired = Vec::new();
+    for relative in report.damaged.keys() {
+        let expected = &manifest.files[relative];
+        let contents = bundled
+            .iter()
+            .find(|(path, _)| path == relative)
+            .map(|(_, contents)| *contents)
+            .filter(|contents| format!("{:x}", Sha256::digest(contents)) == expected.sha256);
+        let Some(contents) = contents else {
+            log(&format!("Launcher: No copy of backend file {} to restore", relative));
+            unrepaired.push(relative.clone());
+            continue;
+        };
+        let path = backend_dir.join(relative);
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, contents));
+        match written {
+            Ok(()) => log(&format!("Launcher: Restored backend file {}", relative)),
+            Err(e) => {
+                log(&format!("Launcher: Failed to restore {}: {}", path.display(), e));
+                unrepaired.push(relative.clone());
+            }
+        }
+    }
+    unrepaired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN: &[u8] = b"import backend.server\n";
+    const AUDIO: &[u8] = b"def load(path):\n    return open(path, 'rb').read()\n";
+
+    fn entry(contents: &[u8]) -> ManifestEntry {
+        ManifestEntry { size: contents.len() as u64, sha256: format!("{:x}", Sha256::digest(contents)) }
+    }
+
+    fn manifest() -> BackendManifest {
+        BackendManifest {
+            files: BTreeMap::from([
+                ("main.py".to_string(), entry(MAIN)),
+                ("utils/audio.py".to_string(), entry(AUDIO)),
+                ("tts.py".to_string(), entry(b"speak = print\n")),
+            ]),
+        }
+    }
+
+    #[test]
+    fn finds_missing_truncated_and_modified_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        assert_eq!(BackendManifest::load(dir).unwrap(), None);
+        std::fs::write(dir.join(MANIFEST_FILE_NAME), serde_json::to_string(&manifest()).unwrap()).unwrap();
+        let manifest = BackendManifest::load(dir).unwrap().unwrap();
+
+        std::fs::write(dir.join("main.py"), MAIN).unwrap();
+        std::fs::create_dir(dir.join("utils")).unwrap();
+        std::fs::write(dir.join("utils/audio.py"), &AUDIO[..10]).unwrap();
+        std::fs::write(dir.join("tts.py"), b"speak = input\n").unwrap();
+        std::fs::write(dir.join("extra.pyc"), b"\0").unwrap();
+
+        let report = verify(dir, &manifest);
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.damaged["utils/audio.py"], Damage::Truncated { size: 10, expected: AUDIO.len() as u64 });
+        assert_eq!(report.damaged["tts.py"], Damage::Modified);
+        assert!(!report.damaged.contains_key("main.py"));
+
+        std::fs::remove_file(dir.join("main.py")).unwrap();
+        let report = verify(dir, &manifest);
+        assert_eq!(report.damaged["main.py"], Damage::Missing);
+        assert_eq!(report.summary(), "3 of 3 backend files are damaged: main.py (missing), tts.py (modified), utils/audio.py (truncated)");
+    }
+
+    #[test]
+    fn restores_files_it_has_the_expected_copy_of() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let manifest = manifest();
+        let report = verify(dir, &manifest);
+        assert_eq!(report.damaged.len(), 3);
+
+        // The bundled tts.py is from another build, so it must not be used
+        let bundled: &[(&str, &[u8])] = &[("main.py", MAIN), ("utils/audio.py", AUDIO), ("tts.py", b"old\n")];
+        assert_eq!(repair(dir, &manifest, &report, bundled), ["tts.py"]);
+        assert_eq!(std::fs::read(dir.join("utils/audio.py")).unwrap(), AUDIO);
+        assert_eq!(verify(dir, &manifest).damaged.keys().collect::<Vec<_>>(), ["tts.py"]);
+    }
+}
@@ -0,0 +1,554 @@
+use crate::launcher::conda::{self, CondaEnv, CONDA_ENV_ENV, CONDA_PREFIX_ENV};
+use crate::launcher::log::log;
+use crate::launcher::process::{CommandSpec, ProcessRunner};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Path of the Python interpreter to run the backend with, overriding discovery
+pub const PYTHON_ENV: &str = "VOICEBOX_PYTHON";
+/// Virtual environment directories looked for next to the `backend` folder, in order
+const VENV_DIR_NAMES: &[&str] = &[".venv", "venv", "env"];
+/// Interpreter looked up on PATH when nothing else is found
+const PATH_PYTHON: &str = "python";
+/// Directory of the CPython distribution `embedded-python` builds ship, next to `backend`
+pub const EMBEDDED_PYTHON_DIR_NAME: &str = "python";
+/// Oldest Python the backend runs on
+pub const MIN_PYTHON_VERSION: PythonVersion = PythonVersion { major: 3, minor: 11 };
+/// Prints e.g. `sys.version_info(major=3, minor=11, micro=4, releaselevel='final', serial=0)`
+const VERSION_SCRIPT: &str = "import sys; print(sys.version_info)";
+/// Registry keys Windows Python installers register under, per user first
+const REGISTRY_KEYS: &[&str] = &[
+    r"HKCU\Software\Python\PythonCore",
+    r"HKLM\Software\Python\PythonCore",
+    r"HKLM\Software\WOW6432Node\Python\PythonCore",
+];
+
+/// `major.minor` of an interpreter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PythonVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl PythonVersion {
+    /// Leading `3.12` of a version tag such as `3.12`, `3.12-64` or `3.13t`
+    pub fn parse_tag(tag: &str) -> Option<Self> {
+        let mut parts = tag.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?;
+        let digits = minor.find(|c: char| !c.is_ascii_digit()).unwrap_or(minor.len());
+        Some(Self { major, minor: minor[..digits].parse().ok()? })
+    }
+}
+
+/// `major.minor` from what `VERSION_SCRIPT` prints, or from a bare `(3, 11, 4, ...)`
+/// tuple as very old interpreters print it
+pub fn parse_version_info(output: &str) -> Option<PythonVersion> {
+    let output = output.trim();
+    let field = |name: &str| {
+        let start = output.find(&format!("{}=", name))? + name.len() + 1;
+        let digits: String = output[start..].chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    };
+    if let (Some(major), Some(minor)) = (field("major"), field("minor")) {
+        return Some(PythonVersion { major, minor });
+    }
+    let mut numbers = output.trim_start_matches('(').split(',').map(|n| n.trim().parse().ok());
+    Some(PythonVersion { major: numbers.next()??, minor: numbers.next()?? })
+}
+
+/// Version of the interpreter `python` runs. `Err` means it couldn't be run or printed
+/// something that isn't a version.
+pub fn python_version(runner: &dyn ProcessRunner, python: &CommandSpec) -> std::io::Result<PythonVersion> {
+    let output = runner.output(&python.clone().arg("-c").arg(VERSION_SCRIPT))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_version_info(&stdout).filter(|_| output.status.success()).ok_or_else(|| {
+        let printed = match stdout.trim() {
+            "" => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            stdout => stdout.to_string(),
+        };
+        std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unexpected version output: {}", printed))
+    })
+}
+
+/// Refuse interpreters the backend would fail on with a syntax error at startup
+pub fn check_min_version(python: &Path, version: PythonVersion) -> Result<(), String> {
+    if version >= MIN_PYTHON_VERSION {
+        return Ok(());
+    }
+    Err(format!(
+        "{} is Python {}, but Voicebox needs Python {} or newer",
+        python.display(),
+        version,
+        MIN_PYTHON_VERSION
+    ))
+}
+
+impl fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Where an interpreter was found, most preferred first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PythonSource {
+    /// The CPython distribution bundled with the app in this directory
+    Embedded(PathBuf),
+    /// `VOICEBOX_PYTHON`
+    Override,
+    /// The virtual environment the launcher created and manages in this directory
+    Managed(PathBuf),
+    /// The conda environment picked with `--conda-env` or `conda_env`
+    Conda(CondaEnv),
+    /// A virtual environment in this directory
+    Venv(PathBuf),
+    /// The conda environment active in the launcher's shell (`CONDA_PREFIX`)
+    ActiveConda(CondaEnv),
+    /// Listed by the Windows `py` launcher
+    PyLauncher(PythonVersion),
+    /// Registered under `Software\Python\PythonCore` in the Windows registry
+    Registry(PythonVersion),
+    /// Whatever `python` resolves to on PATH
+    Path,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonCandidate {
+    pub python: PathBuf,
+    pub source: PythonSource,
+}
+
+impl PythonCandidate {
+    /// How the candidate was found, for logs and error messages
+    pub fn describe(&self) -> String {
+        match &self.source {
+            PythonSource::Embedded(dir) => format!("{} (embedded runtime {})", self.python.display(), dir.display()),
+            PythonSource::Override => format!("{} (from {})", self.python.display(), PYTHON_ENV),
+            PythonSource::Managed(dir) => format!("{} (managed virtual environment {})", self.python.display(), dir.display()),
+            PythonSource::Conda(env) => format!("{} (conda environment {})", self.python.display(), env.name),
+            PythonSource::Venv(dir) => format!("{} (virtual environment {})", self.python.display(), dir.display()),
+            PythonSource::ActiveConda(env) => format!("{} (active conda environment {})", self.python.display(), env.name),
+            PythonSource::PyLauncher(version) => format!("{} (Python {} from the py launcher)", self.python.display(), version),
+            PythonSource::Registry(version) => format!("{} (Python {} from the registry)", self.python.display(), version),
+            PythonSource::Path => format!("{} (from PATH)", self.python.display()),
+        }
+    }
+
+    /// The build's own runtime, which packages were installed into when it was built
+    /// and which gets no managed virtual environment
+    pub fn is_embedded(&self) -> bool {
+        matches!(self.source, PythonSource::Embedded(_))
+    }
+
+    /// Command running this interpreter, inside its conda environment if it has one
+    pub fn command(&self) -> CommandSpec {
+        let spec = CommandSpec::new(&self.python);
+        let (PythonSource::Conda(env) | PythonSource::ActiveConda(env)) = &self.source else {
+            return spec;
+        };
+        match env.activation_env(std::env::var_os("PATH")) {
+            Ok(vars) => spec.envs(vars),
+            Err(e) => {
+                log(&format!("Launcher: {}", e));
+                spec
+            }
+        }
+    }
+}
+
+/// What the user asked for instead of, or on top of, plain discovery
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PythonChoice {
+    /// Interpreter named by `VOICEBOX_PYTHON`
+    pub override_python: Option<PathBuf>,
+    /// Conda environment to use, by name or prefix
+    pub conda_env: Option<String>,
+    /// `CONDA_PREFIX` of the launcher's shell
+    pub active_conda: Option<PathBuf>,
+    /// The build ships its own Python, so a missing one is an error rather than a
+    /// reason to look for another
+    pub require_embedded: bool,
+}
+
+impl PythonChoice {
+    /// The choice made through environment variables, with `conda_env` from the
+    /// command line or config winning over `VOICEBOX_CONDA_ENV`
+    pub fn from_env(conda_env: Option<String>) -> Self {
+        let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+        Self {
+            override_python: var(PYTHON_ENV).map(PathBuf::from),
+            conda_env: conda_env.or_else(|| var(CONDA_ENV_ENV).map(|env| env.to_string_lossy().into_owned())),
+            active_conda: var(CONDA_PREFIX_ENV).map(PathBuf::from),
+            require_embedded: cfg!(feature = "embedded-python"),
+        }
+    }
+
+    /// Whether the user named an interpreter or conda environment
+    pub fn is_explicit(&self) -> bool {
+        self.override_python.is_some() || self.conda_env.is_some()
+    }
+
+    /// Whether `python` is used as is, without an earlier pick or the managed virtual
+    /// environment taking its place: the user chose it, or it is the embedded runtime
+    pub fn uses_as_is(&self, python: &PythonCandidate) -> bool {
+        self.is_explicit() || python.is_embedded()
+    }
+}
+
+/// Interpreter inside a virtual environment, whichever OS created it
+pub fn venv_python(venv: &Path) -> Option<PathBuf> {
+    let candidates = if cfg!(windows) {
+        [venv.join("Scripts").join("python.exe"), venv.join("bin").join("python.exe")]
+    } else {
+        [venv.join("bin").join("python3"), venv.join("bin").join("python")]
+    };
+    candidates.into_iter().find(|python| python.is_file())
+}
+
+/// Interpreter of the embedded CPython distribution in `dir`: the Windows embeddable
+/// package keeps `python.exe` at the top, standalone builds for macOS and Linux keep
+/// theirs in `bin`
+pub fn embedded_python(dir: &Path) -> Option<PathBuf> {
+    let candidates = if cfg!(windows) {
+        vec![dir.join("python.exe")]
+    } else {
+        vec![dir.join("bin").join("python3"), dir.join("bin").join("python")]
+    };
+    candidates.into_iter().find(|python| python.is_file())
+}
+
+/// The embedded runtime shipped with the backend in `backend_dir`, if the build has one
+pub fn find_embedded_python(backend_dir: &Path) -> Option<PythonCandidate> {
+    let dir = backend_dir.parent().unwrap_or(backend_dir).join(EMBEDDED_PYTHON_DIR_NAME);
+    let python = embedded_python(&dir)?;
+    Some(PythonCandidate { python, source: PythonSource::Embedded(dir) })
+}
+
+/// Interpreters listed by `py -0p`, whose lines look like ` -V:3.12 *   C:\...\python.exe`
+/// (or ` -3.9-64 *   C:\...` from older launchers). Tags that aren't a version, e.g.
+/// of Anaconda, are left out.
+pub fn parse_py_launcher(output: &str) -> Vec<(PythonVersion, PathBuf)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let tag_line = line.strip_prefix("-V:").or_else(|| line.strip_prefix('-'))?;
+            let (tag, path) = tag_line.split_once(char::is_whitespace)?;
+            let path = path.trim_start().trim_start_matches('*').trim();
+            let version = PythonVersion::parse_tag(tag)?;
+            (!path.is_empty()).then(|| (version, PathBuf::from(path)))
+        })
+        .collect()
+}
+
+/// Interpreters in `reg query <key> /s` output: the `ExecutablePath` of each
+/// `<version>\InstallPath` key, or `python.exe` in its default value
+pub fn parse_registry(output: &str) -> Vec<(PythonVersion, PathBuf)> {
+    let mut found: Vec<(PythonVersion, PathBuf)> = Vec::new();
+    let mut key: Option<PythonVersion> = None;
+    for line in output.lines() {
+        if line.starts_with("HKEY_") {
+            let lower = line.to_lowercase();
+            key = lower
+                .split_once(r"\pythoncore\")
+                .and_then(|(_, rest)| rest.strip_suffix(r"\installpath"))
+                .and_then(PythonVersion::parse_tag);
+            continue;
+        }
+        let Some(version) = key else { continue };
+        let mut fields = line.trim().splitn(3, "    ");
+        let (Some(name), Some("REG_SZ"), Some(value)) = (fields.next(), fields.next().map(str::trim), fields.next()) else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim() {
+            "ExecutablePath" => {
+                found.retain(|(v, _)| *v != version);
+                found.push((version, PathBuf::from(value)));
+            }
+            "(Default)" if !found.iter().any(|(v, _)| *v == version) => {
+                found.push((version, Path::new(value).join("python.exe")));
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Interpreters the `py` launcher and the registry know about, newest first and
+/// without duplicates. Versions older than `MIN_PYTHON_VERSION` are logged and dropped.
+pub fn windows_candidates(runner: &dyn ProcessRunner) -> Vec<PythonCandidate> {
+    let stdout = |spec: CommandSpec| match runner.output(&spec) {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+        _ => String::new(),
+    };
+    let mut found: Vec<_> = parse_py_launcher(&stdout(CommandSpec::new("py").arg("-0p")))
+        .into_iter()
+        .map(|(version, python)| (version, PythonCandidate { python, source: PythonSource::PyLauncher(version) }))
+        .collect();
+    for key in REGISTRY_KEYS {
+        let output = stdout(CommandSpec::new("reg").args(["query", key, "/s"]));
+        for (version, python) in parse_registry(&output) {
+            found.push((version, PythonCandidate { python, source: PythonSource::Registry(version) }));
+        }
+    }
+
+    let mut candidates: Vec<(PythonVersion, PythonCandidate)> = Vec::new();
+    for (version, candidate) in found {
+        let python = candidate.python.to_string_lossy();
+        if candidates.iter().any(|(_, other)| other.python.to_string_lossy().eq_ignore_ascii_case(&python)) {
+            continue;
+        }
+        if version < MIN_PYTHON_VERSION {
+            log(&format!("Launcher: Skipping {}, the backend needs Python {}+", candidate.describe(), MIN_PYTHON_VERSION));
+            continue;
+        }
+        candidates.push((version, candidate));
+    }
+    // Stable, so the py launcher's pick wins among equal versions
+    candidates.sort_by_key(|(version, _)| std::cmp::Reverse(*version));
+    candidates.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Interpreters that could run the backend in `backend_dir`, most preferred first:
+/// the override, the chosen conda environment, virtual environments next to the backend,
+/// the active conda environment, on Windows the ones the `py` launcher and registry know
+/// (since `python` on PATH is often the Store shim), then PATH
+pub fn python_candidates(runner: &dyn ProcessRunner, backend_dir: &Path, choice: &PythonChoice) -> Vec<PythonCandidate> {
+    let mut candidates = Vec::new();
+    if let Some(python) = &choice.override_python {
+        candidates.push(PythonCandidate { python: python.clone(), source: PythonSource::Override });
+    }
+    if let Some(wanted) = &choice.conda_env {
+        let envs = conda::list_envs(runner);
+        for env in &envs {
+            log(&format!("Launcher: Conda environment {} at {:?}", env.name, env.prefix));
+        }
+        if let Some(env) = conda::find_env(&envs, wanted) {
+            if let Some(python) = env.python() {
+                candidates.push(PythonCandidate { python, source: PythonSource::Conda(env) });
+            }
+        }
+    }
+    let root = backend_dir.parent().unwrap_or(backend_dir);
+    for name in VENV_DIR_NAMES {
+        let venv = root.join(name);
+        if let Some(python) = venv_python(&venv) {
+            candidates.push(PythonCandidate { python, source: PythonSource::Venv(venv) });
+        }
+    }
+    if let Some(prefix) = &choice.active_conda {
+        let env = CondaEnv::from_prefix(prefix);
+        if let Some(python) = env.python() {
+            candidates.push(PythonCandidate { python, source: PythonSource::ActiveConda(env) });
+        }
+    }
+    if cfg!(windows) {
+        candidates.extend(windows_candidates(runner));
+    }
+    candidates.push(PythonCandidate { python: PathBuf::from(PATH_PYTHON), source: PythonSource::Path });
+    candidates
+}
+
+/// The interpreter to run the backend with. An embedded runtime is used exclusively,
+/// whatever else is installed. An override or conda environment that can't be used is
+/// an error rather than silently falling back to another Python.
+pub fn find_python(runner: &dyn ProcessRunner, backend_dir: &Path, choice: &PythonChoice) -> Result<PythonCandidate, String> {
+    if let Some(embedded) = find_embedded_python(backend_dir) {
+        if choice.is_explicit() {
+            log(&format!("Launcher: Ignoring {} and conda environments, this build ships its own Python", PYTHON_ENV));
+        }
+        return Ok(embedded);
+    }
+    if choice.require_embedded {
+        let dir = backend_dir.parent().unwrap_or(backend_dir).join(EMBEDDED_PYTHON_DIR_NAME);
+        return Err(format!("The Python runtime bundled with Voicebox is missing from {}; reinstall Voicebox", dir.display()));
+    }
+    if let Some(python) = &choice.override_python {
+        // A bare command name is left for the OS to resolve on PATH
+        if python.components().count() > 1 && !python.is_file() {
+            return Err(format!("{} is set to {}, which does not exist", PYTHON_ENV, python.display()));
+        }
+    }
+    let candidates = python_candidates(runner, backend_dir, choice);
+    for candidate in &candidates {
+        log(&format!("Launcher: Python candidate {}", candidate.describe()));
+    }
+    if let Some(wanted) = choice.conda_env.as_ref().filter(|_| choice.override_python.is_none()) {
+        if !candidates.iter().any(|c| matches!(c.source, PythonSource::Conda(_))) {
+            return Err(format!(
+                "Conda environment {:?} was not found or has no Python; list them with `conda env list`",
+                wanted
+            ));
+        }
+    }
+    Ok(candidates.into_iter().next().expect("PATH is always a candidate"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    fn make_venv(dir: &Path) -> PathBuf {
+        let bin = dir.join(if cfg!(windows) { "Scripts" } else { "bin" });
+        std::fs::create_dir_all(&bin).unwrap();
+        let python = bin.join(if cfg!(windows) { "python.exe" } else { "python3" });
+        std::fs::write(&python, "").unwrap();
+        python
+    }
+
+    #[test]
+    fn prefers_virtual_environments_over_path() {
+        let root = tempfile::tempdir().unwrap();
+        let backend = root.path().join("backend");
+        std::fs::create_dir_all(&backend).unwrap();
+        assert_eq!(find_python(&FakeRunner::new(), &backend, &PythonChoice::default()).unwrap().source, PythonSource::Path);
+
+        let env_python = make_venv(&root.path().join("env"));
+        let dot_venv_python = make_venv(&root.path().join(".venv"));
+        let candidates = python_candidates(&FakeRunner::new(), &backend, &PythonChoice::default());
+        let found: Vec<_> = candidates.iter().map(|c| c.python.clone()).collect();
+        assert_eq!(found, [dot_venv_python.clone(), env_python, PathBuf::from("python")]);
+        let chosen = find_python(&FakeRunner::new(), &backend, &PythonChoice::default()).unwrap();
+        assert_eq!(chosen.source, PythonSource::Venv(root.path().join(".venv")));
+    }
+
+    #[test]
+    fn embedded_runtime_is_used_exclusively() {
+        let root = tempfile::tempdir().unwrap();
+        let backend = root.path().join("backend");
+        make_venv(&root.path().join(".venv"));
+        let custom = make_venv(&root.path().join("custom"));
+        let required = PythonChoice { require_embedded: true, ..overridden(&custom) };
+        assert!(find_python(&FakeRunner::new(), &backend, &required).unwrap_err().contains("reinstall"));
+
+        let dir = root.path().join(EMBEDDED_PYTHON_DIR_NAME);
+        let python = match cfg!(windows) {
+            true => dir.join("python.exe"),
+            false => dir.join("bin").join("python3"),
+        };
+        std::fs::create_dir_all(python.parent().unwrap()).unwrap();
+        std::fs::write(&python, "").unwrap();
+        for choice in [required, PythonChoice::default()] {
+            let chosen = find_python(&FakeRunner::new(), &backend, &choice).unwrap();
+            assert_eq!(chosen, PythonCandidate { python: python.clone(), source: PythonSource::Embedded(dir.clone()) });
+            assert!(choice.uses_as_is(&chosen));
+        }
+    }
+
+    fn overridden(python: &Path) -> PythonChoice {
+        PythonChoice { override_python: Some(python.to_path_buf()), ..Default::default() }
+    }
+
+    #[test]
+    fn override_wins_but_must_exist() {
+        let root = tempfile::tempdir().unwrap();
+        let backend = root.path().join("backend");
+        make_venv(&root.path().join("venv"));
+        let custom = make_venv(&root.path().join("custom"));
+        let chosen = find_python(&FakeRunner::new(), &backend, &overridden(&custom)).unwrap();
+        assert_eq!(chosen.python, custom);
+        assert!(overridden(&custom).uses_as_is(&chosen));
+        assert!(!PythonChoice::default().uses_as_is(&chosen));
+        assert_eq!(find_python(&FakeRunner::new(), &backend, &overridden(Path::new("python3.12"))).unwrap().source, PythonSource::Override);
+        let missing = root.path().join("missing").join("python");
+        assert!(find_python(&FakeRunner::new(), &backend, &overridden(&missing)).unwrap_err().contains(PYTHON_ENV));
+    }
+
+    #[test]
+    fn chosen_conda_environment_beats_venvs_and_active_one_follows_them() {
+        let root = tempfile::tempdir().unwrap();
+        let backend = root.path().join("backend");
+        let venv_python = make_venv(&root.path().join(".venv"));
+        let conda_dir = root.path().join("conda");
+        let base_python = make_conda_env(&conda_dir);
+        let torch = conda_dir.join("envs").join("torch");
+        let torch_python = make_conda_env(&torch);
+
+        let runner = FakeRunner::new();
+        let list = serde_json::json!({ "envs": [conda_dir, torch] }).to_string();
+        runner.script("conda", Script::exits(0).stdout(&list));
+        let choice = PythonChoice {
+            conda_env: Some("torch".to_string()),
+            active_conda: Some(conda_dir.clone()),
+            ..Default::default()
+        };
+        let found: Vec<_> = python_candidates(&runner, &backend, &choice).into_iter().map(|c| c.python).collect();
+        assert_eq!(found, [torch_python, venv_python, base_python, PathBuf::from("python")]);
+        let chosen = find_python(&runner, &backend, &choice).unwrap();
+        assert!(matches!(&chosen.source, PythonSource::Conda(env) if env.name == "torch"));
+        assert!(chosen.command().env_value(CONDA_PREFIX_ENV).is_some());
+
+        let missing = PythonChoice { conda_env: Some("tts".to_string()), ..Default::default() };
+        assert!(find_python(&runner, &backend, &missing).unwrap_err().contains("\"tts\""));
+    }
+
+    fn make_conda_env(prefix: &Path) -> PathBuf {
+        let python = match cfg!(windows) {
+            true => prefix.join("python.exe"),
+            false => prefix.join("bin").join("python3"),
+        };
+        std::fs::create_dir_all(python.parent().unwrap()).unwrap();
+        std::fs::write(&python, "").unwrap();
+        python
+    }
+
+    #[test]
+    fn reads_py_launcher_and_registry_listings() {
+        let py = " -V:3.12 *        C:\\Python312\\python.exe\r\n -V:3.10          C:\\Python310\\python.exe\r\n \
+                  -V:ContinuumAnalytics/Anaconda39-64 C:\\conda\\python.exe\r\n -3.13-64   C:\\Program Files\\Python313\\python.exe\r\n";
+        let found = parse_py_launcher(py);
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0], (PythonVersion { major: 3, minor: 12 }, PathBuf::from(r"C:\Python312\python.exe")));
+        assert_eq!(found[2].1, PathBuf::from(r"C:\Program Files\Python313\python.exe"));
+
+        let reg = "\r\nHKEY_CURRENT_USER\\Software\\Python\\PythonCore\\3.11\r\n    DisplayName    REG_SZ    Python 3.11\r\n\r\n\
+                   HKEY_CURRENT_USER\\Software\\Python\\PythonCore\\3.11\\InstallPath\r\n    (Default)    REG_SZ    C:\\Py311\\\r\n    \
+                   ExecutablePath    REG_SZ    C:\\Py311\\python.exe\r\n\r\nHKEY_CURRENT_USER\\Software\\Python\\PythonCore\\3.13t\\InstallPath\r\n    \
+                   (Default)    REG_SZ    C:\\Py313\\\r\n";
+        let found = parse_registry(reg);
+        assert_eq!(found, [
+            (PythonVersion { major: 3, minor: 11 }, PathBuf::from(r"C:\Py311\python.exe")),
+            (PythonVersion { major: 3, minor: 13 }, Path::new(r"C:\Py313\").join("python.exe")),
+        ]);
+    }
+
+    #[test]
+    fn windows_candidates_are_newest_compatible_first() {
+        let runner = FakeRunner::new();
+        runner.script("py", Script::exits(0).stdout(" -V:3.11 *  C:\\Py311\\python.exe\n -V:3.9   C:\\Py39\\python.exe\n"));
+        runner.script(
+            "reg",
+            Script::exits(0).stdout("HKEY_LOCAL_MACHINE\\SOFTWARE\\Python\\PythonCore\\3.12\\InstallPath\n    ExecutablePath    REG_SZ    C:\\Py312\\python.exe\n\
+                                     HKEY_LOCAL_MACHINE\\SOFTWARE\\Python\\PythonCore\\3.11\\InstallPath\n    ExecutablePath    REG_SZ    c:\\py311\\python.exe\n"),
+        );
+        let candidates = windows_candidates(&runner);
+        let found: Vec<_> = candidates.iter().map(|c| c.python.clone()).collect();
+        assert_eq!(found, [PathBuf::from(r"C:\Py312\python.exe"), PathBuf::from(r"C:\Py311\python.exe")]);
+        assert_eq!(candidates[1].source, PythonSource::PyLauncher(PythonVersion { major: 3, minor: 11 }));
+        assert!(windows_candidates(&FakeRunner::new()).is_empty());
+    }
+
+    #[test]
+    fn reads_and_checks_interpreter_versions() {
+        let info = "sys.version_info(major=3, minor=12, micro=1, releaselevel='final', serial=0)\n";
+        assert_eq!(parse_version_info(info), Some(PythonVersion { major: 3, minor: 12 }));
+        assert_eq!(parse_version_info("(2, 6, 9, 'final', 0)"), Some(PythonVersion { major: 2, minor: 6 }));
+        assert_eq!(parse_version_info("Python was not found"), None);
+
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(0).stdout("sys.version_info(major=3, minor=8, micro=10, releaselevel='final', serial=0)"));
+        let version = python_version(&runner, &CommandSpec::new("python")).unwrap();
+        let err = check_min_version(Path::new("python"), version).unwrap_err();
+        assert!(err.contains("Python 3.8") && err.contains("3.11 or newer"));
+        assert!(check_min_version(Path::new("python"), MIN_PYTHON_VERSION).is_ok());
+
+        // The Store shim prints an advert and fails
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(9009).stderr("Python was not found; run without arguments to install"));
+        assert_eq!(python_version(&runner, &CommandSpec::new("python")).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(python_version(&FakeRunner::new(), &CommandSpec::new("python")).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,369 @@
+use crate::launcher::scheduler::{is_out_of_memory, Scheduler};
+use crate::launcher::state::StateFile;
+use crate::postprocess::snapshot::GenerationSnapshot;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Instant;
+
+pub const JOBS_DIR_NAME: &str = "jobs";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SegmentStatus {
+    Pending,
+    Done {
+        generation_id: String,
+        /// Settings the segment was rendered with, for re-rendering it later
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        snapshot: Option<GenerationSnapshot>,
+    },
+    Failed { error: String },
+}
+
+/// One generation request of a batch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub index: usize,
+    /// `/generate` request body
+    pub request: Map<String, Value>,
+    pub status: SegmentStatus,
+}
+
+impl Segment {
+    pub fn is_done(&self) -> bool {
+        matches!(self.status, SegmentStatus::Done { .. })
+    }
+}
+
+/// A batch of generations persisted segment by segment, so a crash or restart loses
+/// at most the segment that was in flight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchJob {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub segments: Vec<Segment>,
+}
+
+static NEXT_JOB: AtomicU32 = AtomicU32::new(0);
+
+impl BatchJob {
+    pub fn new(name: &str, requests: Vec<Map<String, Value>>) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: format!(
+                "{}-{}-{}",
+                now.format("%Y%m%d-%H%M%S"),
+                std::process::id(),
+                NEXT_JOB.fetch_add(1, Ordering::Relaxed)
+            ),
+            name: name.to_string(),
+            created_at: now.to_rfc3339(),
+            segments: requests
+                .into_iter()
+                .enumerate()
+                .map(|(index, request)| Segment { index, request, status: SegmentStatus::Pending })
+                .collect(),
+        }
+    }
+
+    pub fn completed(&self) -> usize {
+        self.segments.iter().filter(|s| s.is_done()).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.segments.iter().all(Segment::is_done)
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Batch jobs stored as one state file each under `state/jobs`
+pub struct JobStore {
+    dir: PathBuf,
+}
+
+impl JobStore {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { dir: state_dir.join(JOBS_DIR_NAME) }
+    }
+
+    fn file(&self, id: &str) -> Result<StateFile<BatchJob>, String> {
+        if !is_valid_id(id) {
+            return Err(format!("Invalid job id {:?}", id));
+        }
+        Ok(StateFile::new(self.dir.join(format!("{}.json", id))))
+    }
+
+    pub fn save(&self, job: &BatchJob) -> Result<(), String> {
+        self.file(&job.id)?.write(job)
+    }
+
+    pub fn load(&self, id: &str) -> Result<Option<BatchJob>, String> {
+        self.file(id)?.read()
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        self.file(id)?.remove()
+    }
+
+    /// Jobs with segments left to run, oldest first
+    pub fn incomplete(&self) -> Result<Vec<BatchJob>, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to list {}: {}", self.dir.display(), e)),
+        };
+        let mut jobs = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            if let Some(job) = StateFile::<BatchJob>::new(path).read()? {
+                if !job.is_complete() {
+                    jobs.push(job);
+                }
+            }
+        }
+        jobs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(jobs)
+    }
+
+    fn set_status(&self, id: &str, index: usize, status: SegmentStatus) -> Result<(), String> {
+        let mut missing = false;
+        self.file(id)?.update(|job| {
+            let mut job = job?;
+            match job.segments.get_mut(index) {
+                Some(segment) => segment.status = status,
+                None => missing = true,
+            }
+            Some(job)
+        })?;
+        if missing {
+            return Err(format!("Job {} has no segment {}", id, index));
+        }
+        Ok(())
+    }
+}
+
+fn finished(result: Result<GenerationSnapshot, String>) -> SegmentStatus {
+    match result {
+        Ok(snapshot) => match snapshot.generation_id.clone() {
+            Some(generation_id) => SegmentStatus::Done { generation_id, snapshot: Some(snapshot) },
+            None => SegmentStatus::Failed { error: "Backend response has no generation id".to_string() },
+        },
+        Err(error) => SegmentStatus::Failed { error },
+    }
+}
+
+/// Run every segment of job `id` that isn't done yet, in order, recording each result
+/// as soon as it is known. Failed segments are retried on the next resume.
+pub fn resume(
+    store: &JobStore,
+    id: &str,
+    mut generate: impl FnMut(&Segment) -> Result<GenerationSnapshot, String>,
+) -> Result<BatchJob, String> {
+    let job = store.load(id)?.ok_or_else(|| format!("No job {}", id))?;
+    for segment in job.segments.iter().filter(|s| !s.is_done()) {
+        store.set_status(id, segment.index, finished(generate(segment)))?;
+    }
+    store.load(id)?.ok_or_else(|| format!("Job {} disappeared while running", id))
+}
+
+/// Like `resume`, but with every unfinished segment running at the same time on its
+/// own thread. Meant for small jobs such as comparison renders.
+pub fn resume_concurrent(
+    store: &JobStore,
+    id: &str,
+    generate: impl Fn(&Segment) -> Result<GenerationSnapshot, String> + Sync,
+) -> Result<BatchJob, String> {
+    let job = store.load(id)?.ok_or_else(|| format!("No job {}", id))?;
+    std::thread::scope(|scope| {
+        let running: Vec<_> = job
+            .segments
+            .iter()
+            .filter(|s| !s.is_done())
+            .map(|segment| scope.spawn(|| store.set_status(id, segment.index, finished(generate(segment)))))
+            .collect();
+        running
+            .into_iter()
+            .try_for_each(|thread| thread.join().unwrap_or_else(|_| Err("Segment thread panicked".to_string())))
+    })?;
+    store.load(id)?.ok_or_else(|| format!("Job {} disappeared while running", id))
+}
+
+/// Like `resume`, but with up to `workers` segments running at once on a backend with
+/// several workers, picked so they fit into `budget_mb` of free GPU memory (see
+/// `Scheduler`). `generate` also returns the GPU memory the segment needed, if known.
+/// Segments that run out of memory are retried once the others made room.
+pub fn resume_scheduled(
+    store: &JobStore,
+    id: &str,
+    workers: usize,
+    budget_mb: Option<f64>,
+    generate: impl Fn(&Segment) -> Result<(GenerationSnapshot, Option<f64>), String> + Sync,
+) -> Result<BatchJob, String> {
+    let job = store.load(id)?.ok_or_else(|| format!("No job {}", id))?;
+    let items = job
+        .segments
+        .iter()
+        .filter(|s| !s.is_done())
+        .map(|s| (s.index, s.request.get("text").and_then(Value::as_str).map_or(0, |text| text.chars().count())))
+        .collect();
+    let scheduler = Mutex::new(Scheduler::new(items, workers, budget_mb));
+    let changed = Condvar::new();
+    let errors = Mutex::new(Vec::new());
+
+    let work = || loop {
+        let index = {
+            let mut scheduler = scheduler.lock().unwrap();
+            loop {
+                if scheduler.is_done() {
+                    return;
+                }
+                if let Some(index) = scheduler.next_ready() {
+                    break index;
+                }
+                scheduler = changed.wait(scheduler).unwrap();
+            }
+        };
+        let segment = &job.segments[index];
+        let started = Instant::now();
+        let result = generate(segment);
+        let status = {
+            let mut scheduler = scheduler.lock().unwrap();
+            match result {
+                Ok((snapshot, vram_mb)) => {
+                    scheduler.finish(index, started.elapsed().as_secs_f64(), vram_mb);
+                    Some(finished(Ok(snapshot)))
+                }
+                Err(error) if is_out_of_memory(&error) && scheduler.out_of_memory(index) => None,
+                Err(error) => {
+                    scheduler.abandon(index);
+                    Some(finished(Err(error)))
+                }
+            }
+        };
+        changed.notify_all();
+        if let Some(status) = status {
+            if let Err(e) = store.set_status(id, index, status) {
+                errors.lock().unwrap().push(e);
+            }
+        }
+    };
+    std::thread::scope(|scope| {
+        for _ in 0..workers.max(1) {
+            scope.spawn(work);
+        }
+    });
+
+    if let Some(error) = errors.into_inner().unwrap().into_iter().next() {
+        return Err(error);
+    }
+    store.load(id)?.ok_or_else(|| format!("Job {} disappeared while running", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rendered(segment: &Segment) -> Result<GenerationSnapshot, String> {
+        let generation = json!({"id": format!("gen-{}", segment.index), "seed": 7});
+        Ok(GenerationSnapshot::capture(&segment.request, &generation))
+    }
+
+    fn request(text: &str) -> Map<String, Value> {
+        let mut request = Map::new();
+        request.insert("text".to_string(), Value::String(text.to_string()));
+        request
+    }
+
+    #[test]
+    fn resumes_after_the_last_completed_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let job = BatchJob::new("chapter", vec![request("one"), request("two"), request("three")]);
+        store.save(&job).unwrap();
+
+        // First run "crashes" on the second segment
+        let first = resume(&store, &job.id, |s| match s.index {
+            0 => rendered(s),
+            _ => Err("backend went away".to_string()),
+        })
+        .unwrap();
+        assert_eq!(first.completed(), 1);
+        assert_eq!(store.incomplete().unwrap().len(), 1);
+
+        let mut ran = Vec::new();
+        let second = resume(&store, &job.id, |s| {
+            ran.push(s.index);
+            rendered(s)
+        })
+        .unwrap();
+        assert_eq!(ran, vec![1, 2]);
+        assert!(second.is_complete());
+        let SegmentStatus::Done { generation_id, snapshot: Some(snapshot) } = &second.segments[2].status else {
+            panic!("segment not recorded: {:?}", second.segments[2]);
+        };
+        assert_eq!(generation_id, "gen-2");
+        assert_eq!(snapshot.request["seed"], json!(7));
+        assert_eq!(snapshot.request["text"], json!("three"));
+        assert!(store.incomplete().unwrap().is_empty());
+    }
+
+    #[test]
+    fn runs_segments_concurrently() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let job = BatchJob::new("a/b", vec![request("a"), request("b")]);
+        store.save(&job).unwrap();
+
+        // Each segment waits for the other to start, so this only finishes if they overlap
+        let barrier = std::sync::Barrier::new(2);
+        let done = resume_concurrent(&store, &job.id, |s| {
+            barrier.wait();
+            rendered(s)
+        })
+        .unwrap();
+        assert!(done.is_complete());
+        assert_eq!(store.load(&job.id).unwrap().unwrap(), done);
+    }
+
+    #[test]
+    fn scheduled_segments_retry_after_running_out_of_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        let job = BatchJob::new("script", vec![request("a long line of dialogue"), request("short"), request("mid line")]);
+        store.save(&job).unwrap();
+
+        let attempts = Mutex::new(Vec::new());
+        let done = resume_scheduled(&store, &job.id, 2, Some(8_000.0), |s| {
+            let mut attempts = attempts.lock().unwrap();
+            attempts.push(s.index);
+            if s.index == 1 && attempts.iter().filter(|&&i| i == 1).count() == 1 {
+                return Err("CUDA out of memory".to_string());
+            }
+            rendered(s).map(|snapshot| (snapshot, Some(400.0)))
+        })
+        .unwrap();
+        assert!(done.is_complete());
+        let attempts = attempts.into_inner().unwrap();
+        assert_eq!(attempts.len(), 4);
+        assert_eq!(attempts.iter().filter(|&&i| i == 1).count(), 2);
+    }
+
+    #[test]
+    fn rejects_ids_that_escape_the_jobs_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::new(dir.path());
+        assert!(store.load("../config").is_err());
+        assert_eq!(store.load("missing-job").unwrap(), None);
+    }
+}
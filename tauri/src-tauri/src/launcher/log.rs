@@ -0,0 +1,123 @@
+use crate::launcher::backend_log::BackendRecord;
+use chrono::Local;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+pub const LOG_FILE_NAME: &str = "voicebox-launch.log";
+
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+static STRUCTURED: AtomicBool = AtomicBool::new(false);
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// How much the launcher logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    #[default]
+    Info,
+    /// Also the lines written with `debug`
+    Debug,
+}
+
+/// Direct launcher logging to `path`. Only the first call takes effect.
+pub fn set_log_path(path: PathBuf) {
+    let _ = LOG_PATH.set(path);
+}
+
+pub fn log_path() -> PathBuf {
+    LOG_PATH
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join(LOG_FILE_NAME))
+}
+
+/// Mirror launcher messages to stderr as JSON lines, for headless runs where nobody
+/// reads the log file
+pub fn set_structured(enabled: bool) {
+    STRUCTURED.store(enabled, Ordering::Relaxed);
+}
+
+/// Log lines up to `level` from now on
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> LogLevel {
+    if LEVEL.load(Ordering::Relaxed) == LogLevel::Debug as u8 {
+        LogLevel::Debug
+    } else {
+        LogLevel::Info
+    }
+}
+
+/// Append one timestamped line to the log at `path`
+fn append_line(path: &Path, msg: &str) {
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "[{}] {}", Local::now().format("%Y-%m-%d %H:%M:%S"), msg);
+    }
+}
+
+fn write_to_file(msg: &str) {
+    append_line(&log_path(), msg);
+}
+
+pub fn log(msg: &str) {
+    log_with(msg, serde_json::Map::new());
+}
+
+/// Log `msg` only when the level is `debug`
+pub fn debug(msg: &str) {
+    if level() >= LogLevel::Debug {
+        log(msg);
+    }
+}
+
+/// Log `msg` with `fields` that structured output carries as JSON keys, and the log
+/// file appends as `key=value`
+pub fn log_with(msg: &str, fields: serde_json::Map<String, serde_json::Value>) {
+    let suffix: String = fields.iter().map(|(key, value)| format!(" {}={}", key, value)).collect();
+    write_to_file(&format!("{}{}", msg, suffix));
+    if STRUCTURED.load(Ordering::Relaxed) {
+        let mut event = fields;
+        event.insert("timestamp".to_string(), Local::now().to_rfc3339().into());
+        event.insert("source".to_string(), "launcher".into());
+        event.insert("message".to_string(), msg.into());
+        eprintln!("{}", serde_json::Value::Object(event));
+    }
+}
+
+/// Record a line of backend output. It is already passed through to our own
+/// stdout/stderr, so it only goes to the log file.
+pub fn log_backend(stream: &str, line: &str) {
+    write_to_file(&format!("{}: {}", stream, line));
+}
+
+/// Record a JSON log line of the backend by its level and module, with its other keys
+/// appended as `key=value`
+pub fn log_backend_record(stream: &str, record: &BackendRecord) {
+    let suffix: String = record.fields.iter().map(|(key, value)| format!(" {}={}", key, value)).collect();
+    write_to_file(&format!("{}: {}{}", stream, record.summary(), suffix));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_timestamped_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(LOG_FILE_NAME);
+        append_line(&path, "Launcher: first");
+        append_line(&path, "STDERR: второй");
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with('[') && lines[0].ends_with("] Launcher: first"));
+        assert!(lines[1].ends_with("STDERR: второй"));
+    }
+}
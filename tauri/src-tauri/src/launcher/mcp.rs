@@ -0,0 +1,156 @@
+// Model Context Protocol server.
+//
+// Agent frontends start `voicebox-server mcp` and talk JSON-RPC to it over stdio. The
+// tools are thin wrappers around `Operations`, the same calls `rpc` serves, so both
+// integrations stay in step.
+use crate::launcher::operations::{Operations, SpeakParams, TranscribeParams};
+use crate::launcher::rpc::{parse_params, RpcError, INVALID_PARAMS, METHOD_NOT_FOUND};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Protocol revisions this server speaks, newest first
+pub const PROTOCOL_VERSIONS: &[&str] = &["2025-03-26", "2024-11-05"];
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// Tools as listed by `tools/list`, with the JSON schema of their arguments
+pub fn tools() -> Value {
+    json!([
+        {
+            "name": "synthesize",
+            "description": "Speak text with a Voicebox voice profile or preset. Returns the generation; with `output` the WAV file is saved there.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string", "description": "Text to speak" },
+                    "voice": { "type": "string", "description": "Voice profile ID, as returned by list_voices" },
+                    "preset": { "type": "string", "description": "Preset from the Voicebox config" },
+                    "language": { "type": "string", "description": "Language code such as en or de" },
+                    "output": { "type": "string", "description": "Path of the WAV file to write" }
+                },
+                "required": ["text"]
+            }
+        },
+        {
+            "name": "transcribe",
+            "description": "Transcribe an audio file on this machine to text.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path of the audio file" },
+                    "language": { "type": "string", "description": "Language code, detected when omitted" }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "list_voices",
+            "description": "List the voice profiles available for synthesize.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }
+    ])
+}
+
+/// Result of a tool call. Failures of the tool itself are results flagged `isError`,
+/// so the agent sees the message instead of a protocol error.
+fn tool_result(result: Result<Value, String>) -> Value {
+    match result {
+        Ok(value) => json!({
+            "content": [{ "type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default() }],
+            "isError": false,
+        }),
+        Err(message) => json!({ "content": [{ "type": "text", "text": message }], "isError": true }),
+    }
+}
+
+fn call_tool(ops: &dyn Operations, call: ToolCall) -> Result<Value, RpcError> {
+    let result = match call.name.as_str() {
+        "synthesize" => ops.speak(&parse_params::<SpeakParams>(call.arguments)?),
+        "transcribe" => ops.transcribe(&parse_params::<TranscribeParams>(call.arguments)?),
+        "list_voices" => ops.list_voices(),
+        name => return Err(RpcError::new(INVALID_PARAMS, format!("Unknown tool {:?}", name))),
+    };
+    Ok(tool_result(result))
+}
+
+/// Answer an MCP request. Notifications such as `notifications/initialized` are
+/// accepted and get no response from the transport.
+pub fn dispatch(ops: &dyn Operations, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "initialize" => {
+            let requested = params.get("protocolVersion").and_then(Value::as_str);
+            let version = requested.filter(|v| PROTOCOL_VERSIONS.contains(v)).unwrap_or(PROTOCOL_VERSIONS[0]);
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": { "name": "voicebox", "version": env!("CARGO_PKG_VERSION") },
+            }))
+        }
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call_tool(ops, parse_params(params)?),
+        method if method.starts_with("notifications/") => Ok(Value::Null),
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method {:?}", method))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeOps;
+
+    impl Operations for FakeOps {
+        fn speak(&self, params: &SpeakParams) -> Result<Value, String> {
+            match &params.voice {
+                Some(voice) => Ok(json!({ "id": "gen-1", "profile_id": voice })),
+                None => Err("speak needs a voice or a preset that names one".to_string()),
+            }
+        }
+
+        fn transcribe(&self, params: &TranscribeParams) -> Result<Value, String> {
+            Ok(json!({ "text": format!("contents of {}", params.path.display()) }))
+        }
+
+        fn list_voices(&self) -> Result<Value, String> {
+            Ok(json!([]))
+        }
+
+        fn job_status(&self, _id: &str) -> Result<Value, String> {
+            unreachable!("not an MCP tool")
+        }
+    }
+
+    #[test]
+    fn negotiates_and_lists_tools() {
+        let init = dispatch(&FakeOps, "initialize", json!({ "protocolVersion": "2024-11-05" })).unwrap();
+        assert_eq!(init["protocolVersion"], "2024-11-05");
+        let init = dispatch(&FakeOps, "initialize", json!({ "protocolVersion": "1999-01-01" })).unwrap();
+        assert_eq!(init["protocolVersion"], PROTOCOL_VERSIONS[0]);
+
+        let listed = dispatch(&FakeOps, "tools/list", Value::Null).unwrap();
+        let names: Vec<_> = listed["tools"].as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["synthesize", "transcribe", "list_voices"]);
+        assert!(dispatch(&FakeOps, "notifications/initialized", Value::Null).is_ok());
+        assert_eq!(dispatch(&FakeOps, "resources/list", Value::Null).unwrap_err().code, METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn tool_failures_are_results_not_protocol_errors() {
+        let call = |arguments: Value| dispatch(&FakeOps, "tools/call", json!({ "name": "synthesize", "arguments": arguments }));
+        let ok = call(json!({ "text": "Hello", "voice": "anna" })).unwrap();
+        assert_eq!(ok["isError"], false);
+        assert!(ok["content"][0]["text"].as_str().unwrap().contains("anna"));
+        let failed = call(json!({ "text": "Hello" })).unwrap();
+        assert_eq!(failed["isError"], true);
+        assert_eq!(call(json!({ "words": "Hello" })).unwrap_err().code, INVALID_PARAMS);
+
+        let unknown = dispatch(&FakeOps, "tools/call", json!({ "name": "sing" }));
+        assert_eq!(unknown.unwrap_err().code, INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,80 @@
+// Shared launcher logic used by the voicebox-server wrapper binary and the Tauri app
+pub mod announcements;
+pub mod backend_log;
+pub mod capabilities;
+pub mod checkpoint;
+pub mod cli;
+pub mod conda;
+pub mod config;
+pub mod consent;
+pub mod console;
+pub mod debug_env;
+pub mod deps;
+pub mod devices;
+pub mod dialogs;
+pub mod discovery;
+pub mod dry_run;
+pub mod error;
+pub mod fine_tune;
+pub mod gpu;
+pub mod hooks;
+pub mod installer;
+pub mod instance;
+pub mod integrity;
+pub mod interpreter;
+pub mod jobs;
+pub mod log;
+pub mod mcp;
+pub mod notifications;
+pub mod operations;
+pub mod pairing;
+pub mod paths;
+pub mod picker;
+pub mod presets;
+pub mod process;
+pub mod profile_archive;
+pub mod progress;
+pub mod projects;
+pub mod proxy;
+pub mod retry;
+pub mod reload;
+pub mod rpc;
+pub mod sapi;
+pub mod scheduler;
+pub mod script;
+pub mod setup;
+pub mod shutdown;
+pub mod signing;
+pub mod speechd;
+pub mod state;
+pub mod status;
+pub mod storage;
+pub mod supervisor;
+pub mod venv;
+pub mod version;
+pub mod voice_map;
+pub mod workspace;
+pub mod wsl;
+pub mod zombie;
+
+/// Port the backend listens on unless overridden with `--port`
+pub const DEFAULT_PORT: u16 = 17493;
+
+/// Process exit codes of voicebox-server, so scripts and CI can tell failures apart
+pub mod exit_code {
+    /// Anything without a more specific code, e.g. a failed `speak` request
+    pub const FAILURE: i32 = 1;
+    pub const BACKEND_NOT_FOUND: i32 = 2;
+    pub const MISSING_DEPENDENCIES: i32 = 3;
+    pub const PYTHON_UNUSABLE: i32 = 4;
+    pub const SPAWN_FAILED: i32 = 5;
+    pub const INVALID_CONFIG: i32 = 6;
+    pub const INCOMPATIBLE_BACKEND: i32 = 7;
+    pub const DAMAGED_BACKEND: i32 = 8;
+    pub const SETUP_FAILED: i32 = 9;
+    /// Another launcher runs on the same data directory and `--if-running fail` was given
+    pub const ALREADY_RUNNING: i32 = 10;
+    /// Launcher state in the data directory, such as batch jobs or paired devices, can't
+    /// be read or written
+    pub const STATE_UNUSABLE: i32 = 11;
+}
@@ -0,0 +1,209 @@
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+pub const NOTIFICATIONS_STATE_FILE_NAME: &str = "notifications.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    UpdateAvailable,
+    /// A mirror of the library finished copying
+    BackupFinished,
+    JobFinished,
+    BackendCrashed,
+    OutOfDisk,
+}
+
+impl NotificationCategory {
+    /// Problems the user has to act on, shown even during focus time by default
+    pub fn is_critical(self) -> bool {
+        matches!(self, NotificationCategory::BackendCrashed | NotificationCategory::OutOfDisk)
+    }
+
+    fn default_policy(self) -> NotificationPolicy {
+        if self.is_critical() {
+            NotificationPolicy::Always
+        } else {
+            NotificationPolicy::RespectFocus
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationPolicy {
+    Always,
+    /// Held back while do-not-disturb or focus assist is on, then shown
+    RespectFocus,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Show,
+    Defer,
+    Drop,
+}
+
+pub fn delivery(policy: NotificationPolicy, focus_active: bool) -> Delivery {
+    match policy {
+        NotificationPolicy::Always => Delivery::Show,
+        NotificationPolicy::RespectFocus if focus_active => Delivery::Defer,
+        NotificationPolicy::RespectFocus => Delivery::Show,
+        NotificationPolicy::Never => Delivery::Drop,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub category: NotificationCategory,
+    pub title: String,
+    pub body: String,
+}
+
+/// Policy per category as chosen in settings; categories not listed use their default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub policies: BTreeMap<NotificationCategory, NotificationPolicy>,
+}
+
+impl NotificationSettings {
+    pub fn policy(&self, category: NotificationCategory) -> NotificationPolicy {
+        self.policies.get(&category).copied().unwrap_or_else(|| category.default_policy())
+    }
+}
+
+/// Decides whether each notification is shown now, later or not at all, and holds
+/// the deferred ones until focus time ends
+pub struct NotificationCenter {
+    settings: StateFile<NotificationSettings>,
+    deferred: Mutex<Vec<Notification>>,
+}
+
+impl NotificationCenter {
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            settings: StateFile::new(state_dir.join(NOTIFICATIONS_STATE_FILE_NAME)),
+            deferred: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn settings(&self) -> Result<NotificationSettings, String> {
+        Ok(self.settings.read()?.unwrap_or_default())
+    }
+
+    pub fn set_policy(&self, category: NotificationCategory, policy: NotificationPolicy) -> Result<(), String> {
+        self.settings.update(|settings| {
+            let mut settings = settings.unwrap_or_default();
+            settings.policies.insert(category, policy);
+            Some(settings)
+        })?;
+        Ok(())
+    }
+
+    /// What to do with `notification` now. Deferred ones are kept, replacing an
+    /// older one with the same category and title.
+    pub fn submit(&self, notification: Notification, focus_active: bool) -> Delivery {
+        let policy = self.settings().unwrap_or_default().policy(notification.category);
+        let delivery = delivery(policy, focus_active);
+        if delivery == Delivery::Defer {
+            let mut deferred = self.deferred.lock().unwrap();
+            deferred.retain(|n| (n.category, &n.title) != (notification.category, &notification.title));
+            deferred.push(notification);
+        }
+        delivery
+    }
+
+    /// Deferred notifications that may be shown now that focus time is over
+    pub fn release(&self, focus_active: bool) -> Vec<Notification> {
+        if focus_active {
+            return Vec::new();
+        }
+        let settings = self.settings().unwrap_or_default();
+        let released = std::mem::take(&mut *self.deferred.lock().unwrap());
+        // A category switched to `Never` meanwhile stays quiet
+        released.into_iter().filter(|n| settings.policy(n.category) != NotificationPolicy::Never).collect()
+    }
+}
+
+/// Whether the OS is holding back notifications: Focus Assist or presentation mode
+/// on Windows, a Focus mode on macOS, GNOME's do-not-disturb on Linux
+pub fn focus_active() -> bool {
+    #[cfg(windows)]
+    {
+        use windows::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS};
+        // SAFETY: takes no arguments and only reports the shell's state
+        match unsafe { SHQueryUserNotificationState() } {
+            Ok(state) => state != QUNS_ACCEPTS_NOTIFICATIONS,
+            Err(_) => false,
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // Active Focus modes are recorded as assertions; the file is absent or empty
+        // when none is on
+        let Some(home) = std::env::var_os("HOME") else { return false };
+        let path = Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+        let Ok(contents) = std::fs::read_to_string(path) else { return false };
+        let Ok(assertions) = serde_json::from_str::<serde_json::Value>(&contents) else { return false };
+        assertions["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|entry| entry["storeAssertionRecords"].as_array().is_some_and(|records| !records.is_empty()))
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        std::process::Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+            .output()
+            .is_ok_and(|out| out.status.success() && String::from_utf8_lossy(&out.stdout).trim() == "false")
+    }
+    #[cfg(not(any(windows, unix)))]
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(category: NotificationCategory, title: &str) -> Notification {
+        Notification { category, title: title.to_string(), body: String::new() }
+    }
+
+    #[test]
+    fn defers_routine_notifications_during_focus_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let center = NotificationCenter::new(dir.path());
+        let crash = notification(NotificationCategory::BackendCrashed, "Backend stopped");
+        assert_eq!(center.submit(crash, true), Delivery::Show);
+        let update = notification(NotificationCategory::UpdateAvailable, "Update available");
+        assert_eq!(center.submit(update.clone(), true), Delivery::Defer);
+        assert_eq!(center.submit(update.clone(), true), Delivery::Defer);
+        let backup = notification(NotificationCategory::BackupFinished, "Mirror nas finished");
+        assert_eq!(center.submit(backup, true), Delivery::Defer);
+
+        assert!(center.release(true).is_empty());
+        center.set_policy(NotificationCategory::BackupFinished, NotificationPolicy::Never).unwrap();
+        assert_eq!(center.release(false), std::slice::from_ref(&update));
+        assert!(center.release(false).is_empty());
+        assert_eq!(center.submit(update, false), Delivery::Show);
+    }
+
+    #[test]
+    fn settings_override_category_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let center = NotificationCenter::new(dir.path());
+        center.set_policy(NotificationCategory::OutOfDisk, NotificationPolicy::RespectFocus).unwrap();
+        center.set_policy(NotificationCategory::JobFinished, NotificationPolicy::Always).unwrap();
+        let settings = NotificationCenter::new(dir.path()).settings().unwrap();
+        assert_eq!(settings.policy(NotificationCategory::OutOfDisk), NotificationPolicy::RespectFocus);
+        assert_eq!(settings.policy(NotificationCategory::JobFinished), NotificationPolicy::Always);
+        assert_eq!(settings.policy(NotificationCategory::BackendCrashed), NotificationPolicy::Always);
+        assert_eq!(delivery(settings.policy(NotificationCategory::UpdateAvailable), true), Delivery::Defer);
+    }
+}
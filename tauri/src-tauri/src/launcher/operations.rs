@@ -0,0 +1,115 @@
+use crate::launcher::config::LauncherConfig;
+use crate::launcher::jobs::{BatchJob, SegmentStatus};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::path::PathBuf;
+
+/// Arguments of `speak`: a preset, a voice profile, or a preset with its voice replaced
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpeakParams {
+    pub text: String,
+    pub preset: Option<String>,
+    /// Voice profile ID
+    pub voice: Option<String>,
+    pub language: Option<String>,
+    /// WAV file to save the audio to; without it only the generation is returned
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TranscribeParams {
+    /// Audio file to transcribe, read by the launcher
+    pub path: PathBuf,
+    pub language: Option<String>,
+}
+
+/// What integrations other than the app (editor plugins, agent tools) can ask of a
+/// running backend, independent of the protocol they speak
+pub trait Operations {
+    /// The generation the backend returns, with `path` added if it was saved
+    fn speak(&self, params: &SpeakParams) -> Result<Value, String>;
+    fn transcribe(&self, params: &TranscribeParams) -> Result<Value, String>;
+    /// Voice profiles as the backend lists them
+    fn list_voices(&self) -> Result<Value, String>;
+    fn job_status(&self, id: &str) -> Result<Value, String>;
+}
+
+/// The `/generate` request body for `params`
+pub fn speak_request(config: &LauncherConfig, params: &SpeakParams) -> Result<Value, String> {
+    if params.text.trim().is_empty() {
+        return Err("Nothing to speak: text is empty".to_string());
+    }
+    let mut request = match &params.preset {
+        Some(preset) => config.resolve_preset(preset)?.generate_request(&params.text),
+        None => json!({ "text": params.text }),
+    };
+    if let Some(voice) = &params.voice {
+        request["profile_id"] = json!(voice);
+    }
+    if let Some(language) = &params.language {
+        request["language"] = json!(language);
+    }
+    if request.get("profile_id").is_none() {
+        return Err("speak needs a voice or a preset that names one".to_string());
+    }
+    Ok(request)
+}
+
+/// Progress of a batch job as reported to integrations
+pub fn job_summary(job: &BatchJob) -> Value {
+    let failed: Vec<Value> = job
+        .segments
+        .iter()
+        .filter_map(|segment| match &segment.status {
+            SegmentStatus::Failed { error } => Some(json!({ "index": segment.index, "error": error })),
+            _ => None,
+        })
+        .collect();
+    let mut summary = Map::new();
+    summary.insert("id".to_string(), json!(job.id));
+    summary.insert("name".to_string(), json!(job.name));
+    summary.insert("created_at".to_string(), json!(job.created_at));
+    summary.insert("total".to_string(), json!(job.segments.len()));
+    summary.insert("completed".to_string(), json!(job.completed()));
+    summary.insert("complete".to_string(), json!(job.is_complete()));
+    summary.insert("failed".to_string(), Value::Array(failed));
+    Value::Object(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::presets::Preset;
+
+    #[test]
+    fn builds_requests_from_presets_and_voices() {
+        let mut config = LauncherConfig::default();
+        let preset = Preset { voice: Some("narrator".to_string()), language: Some("de".to_string()), ..Default::default() };
+        config.presets.insert("news".to_string(), preset);
+
+        let params = SpeakParams { text: "Hallo".to_string(), preset: Some("news".to_string()), ..Default::default() };
+        let request = speak_request(&config, &params).unwrap();
+        assert_eq!((request["profile_id"].as_str(), request["language"].as_str()), (Some("narrator"), Some("de")));
+
+        let params = SpeakParams { voice: Some("anna".to_string()), language: Some("en".to_string()), ..params };
+        let request = speak_request(&config, &params).unwrap();
+        assert_eq!((request["profile_id"].as_str(), request["language"].as_str()), (Some("anna"), Some("en")));
+
+        let bare = SpeakParams { text: "Hi".to_string(), ..Default::default() };
+        assert!(speak_request(&config, &bare).unwrap_err().contains("needs a voice"));
+        let empty = SpeakParams { text: " ".to_string(), voice: Some("anna".to_string()), ..Default::default() };
+        assert!(speak_request(&config, &empty).is_err());
+    }
+
+    #[test]
+    fn summarizes_job_progress() {
+        let mut job = BatchJob::new("render", vec![Map::new(), Map::new()]);
+        job.segments[1].status = SegmentStatus::Failed { error: "CUDA out of memory".to_string() };
+        let summary = job_summary(&job);
+        assert_eq!(summary["total"], 2);
+        assert_eq!(summary["completed"], 0);
+        assert_eq!(summary["failed"][0]["index"], 1);
+    }
+}
@@ -0,0 +1,210 @@
+// Pairing of phone companions.
+//
+// `voicebox-server pair` shows a QR code with the launcher's LAN address and a one-time
+// token. The companion scans it and posts the token to `/pair` on the proxy, which
+// trades it for a device token the companion keeps. Only SHA-256 hashes of tokens are
+// stored, so a copy of the state dir can't be used to impersonate a device.
+use crate::launcher::state::StateFile;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, UdpSocket};
+use std::path::Path;
+
+pub const PAIRING_STATE_FILE_NAME: &str = "pairing.json";
+/// How long a pairing code can be scanned
+pub const PAIRING_TTL_SECS: i64 = 300;
+
+/// A device that completed pairing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    token_sha256: String,
+    pub paired_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingPairing {
+    token_sha256: String,
+    expires_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PairingState {
+    pending: Option<PendingPairing>,
+    #[serde(default)]
+    devices: Vec<PairedDevice>,
+}
+
+/// What the QR code carries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingOffer {
+    pub uri: String,
+    pub expires_at: String,
+}
+
+/// The companion's request to `/pair`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PairRequest {
+    pub token: String,
+    /// Shown in the device list, e.g. the phone's model
+    pub name: String,
+}
+
+/// Sent back to the companion once; it authenticates later requests with the token
+#[derive(Debug, Clone, Serialize)]
+pub struct PairResponse {
+    pub device_id: String,
+    pub device_token: String,
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn sha256(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Address other devices on the LAN reach this machine at: that of the interface the
+/// default route uses. Nothing is sent; connecting a UDP socket only picks the route.
+pub fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip()).filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+}
+
+/// `uri` as a QR code drawn with block characters for a terminal
+pub fn qr_text(uri: &str) -> Result<String, String> {
+    let code = qrcode::QrCode::new(uri.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(code.render::<qrcode::render::unicode::Dense1x2>().quiet_zone(true).build())
+}
+
+pub struct Pairing {
+    file: StateFile<PairingState>,
+}
+
+impl Pairing {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { file: StateFile::new(state_dir.join(PAIRING_STATE_FILE_NAME)) }
+    }
+
+    /// Open pairing for `PAIRING_TTL_SECS` with a new one-time token, replacing any
+    /// earlier offer. `endpoint` is the `host:port` the companion connects to.
+    pub fn offer(&self, endpoint: &str) -> Result<PairingOffer, String> {
+        let token = random_token();
+        let expires_at = (chrono::Utc::now() + chrono::Duration::seconds(PAIRING_TTL_SECS)).to_rfc3339();
+        let pending = PendingPairing { token_sha256: sha256(&token), expires_at: expires_at.clone() };
+        self.file.update(|state| {
+            let mut state = state.unwrap_or_default();
+            state.pending = Some(pending);
+            Some(state)
+        })?;
+        Ok(PairingOffer { uri: format!("voicebox://pair?host={}&token={}", endpoint, token), expires_at })
+    }
+
+    /// Check a companion's request against the open offer and add the device. The
+    /// offer is used up whether or not the token matched, so it can't be guessed at.
+    pub fn complete(&self, request: &PairRequest) -> Result<PairResponse, String> {
+        let name = request.name.trim();
+        if name.is_empty() || name.chars().count() > 64 {
+            return Err("Device name must be 1 to 64 characters".to_string());
+        }
+        let device_token = random_token();
+        let device = PairedDevice {
+            id: random_token()[..12].to_string(),
+            name: name.to_string(),
+            token_sha256: sha256(&device_token),
+            paired_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut outcome = Err("No pairing in progress".to_string());
+        self.file.update(|state| {
+            let mut state = state.unwrap_or_default();
+            if let Some(pending) = state.pending.take() {
+                let expired = chrono::DateTime::parse_from_rfc3339(&pending.expires_at)
+                    .map_or(true, |at| at < chrono::Utc::now());
+                outcome = match (expired, pending.token_sha256 == sha256(&request.token)) {
+                    (true, _) => Err("The pairing code has expired".to_string()),
+                    (false, false) => Err("Invalid pairing token".to_string()),
+                    (false, true) => {
+                        state.devices.push(device.clone());
+                        Ok(PairResponse { device_id: device.id.clone(), device_token: device_token.clone() })
+                    }
+                };
+            }
+            Some(state)
+        })?;
+        outcome
+    }
+
+    /// The paired device `device_token` belongs to
+    pub fn authenticate(&self, device_token: &str) -> Option<PairedDevice> {
+        let hash = sha256(device_token);
+        self.devices().ok()?.into_iter().find(|device| device.token_sha256 == hash)
+    }
+
+    pub fn devices(&self) -> Result<Vec<PairedDevice>, String> {
+        Ok(self.file.read()?.map(|state| state.devices).unwrap_or_default())
+    }
+
+    /// Forget device `id`; its token stops working right away. Returns whether it existed.
+    pub fn revoke(&self, id: &str) -> Result<bool, String> {
+        let mut found = false;
+        self.file.update(|state| {
+            let mut state = state.unwrap_or_default();
+            let before = state.devices.len();
+            state.devices.retain(|device| device.id != id);
+            found = state.devices.len() < before;
+            Some(state)
+        })?;
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_of(offer: &PairingOffer) -> String {
+        offer.uri.split("token=").nth(1).unwrap().to_string()
+    }
+
+    #[test]
+    fn pairs_once_per_offer_and_revokes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pairing = Pairing::new(tmp.path());
+        let request = |token: &str| PairRequest { token: token.to_string(), name: "Pixel 8".to_string() };
+        assert!(pairing.complete(&request("guess")).is_err());
+
+        let offer = pairing.offer("192.168.1.20:17493").unwrap();
+        assert!(offer.uri.starts_with("voicebox://pair?host=192.168.1.20:17493&token="));
+        let paired = pairing.complete(&request(&token_of(&offer))).unwrap();
+        assert!(pairing.complete(&request(&token_of(&offer))).is_err());
+        assert_eq!(pairing.authenticate(&paired.device_token).unwrap().id, paired.device_id);
+        assert!(pairing.authenticate("not a token").is_none());
+
+        // A wrong guess uses the offer up
+        let offer = pairing.offer("192.168.1.20:17493").unwrap();
+        assert!(pairing.complete(&request("guess")).is_err());
+        assert!(pairing.complete(&request(&token_of(&offer))).is_err());
+
+        assert!(pairing.revoke(&paired.device_id).unwrap());
+        assert!(pairing.authenticate(&paired.device_token).is_none());
+        assert!(!pairing.revoke(&paired.device_id).unwrap());
+    }
+
+    #[test]
+    fn stores_only_token_hashes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pairing = Pairing::new(tmp.path());
+        let offer = pairing.offer("10.0.0.2:17493").unwrap();
+        let paired = pairing.complete(&PairRequest { token: token_of(&offer), name: "iPad".to_string() }).unwrap();
+        let stored = std::fs::read_to_string(tmp.path().join(PAIRING_STATE_FILE_NAME)).unwrap();
+        assert!(!stored.contains(&paired.device_token));
+        assert!(!stored.contains(&token_of(&offer)));
+        assert!(qr_text(&offer.uri).unwrap().contains('█'));
+    }
+}
@@ -0,0 +1,295 @@
+use crate::launcher::config::default_data_dir;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Per-user writable locations for everything the launcher generates.
+///
+/// The install directory (and the bundled `backend/` folder inside it) is treated as
+/// read-only: under Program Files or /usr it usually is, and writing there would
+/// require elevation.
+#[derive(Debug, Clone)]
+pub struct LauncherPaths {
+    pub data_dir: PathBuf,
+    pub log_dir: PathBuf,
+    /// Scratch space for generated installer inputs (filtered requirements, scripts)
+    pub work_dir: PathBuf,
+    pub state_dir: PathBuf,
+    pub venv_dir: PathBuf,
+    /// Data rebuilt from the backend on demand, such as the library search index
+    pub cache_dir: PathBuf,
+    /// Deleted audio kept until it is restored or purged
+    pub trash_dir: PathBuf,
+}
+
+impl LauncherPaths {
+    /// Resolve paths under `data_dir` (or the default app data dir), falling back to the
+    /// temp dir when that location can't be written. A data dir the launcher creates is
+    /// limited to its owner; an existing one given as `data_dir` may be shared on
+    /// purpose and keeps its permissions.
+    pub fn resolve(data_dir: Option<&Path>) -> Self {
+        let supplied_existing = data_dir.filter(|dir| dir.is_dir());
+        let base = data_dir
+            .map(Path::to_path_buf)
+            .or_else(default_data_dir)
+            .filter(|dir| ensure_writable_dir(dir))
+            .unwrap_or_else(fallback_data_dir);
+        if supplied_existing != Some(base.as_path()) {
+            restrict_to_owner(&base);
+        }
+        let mut paths = Self::under(base);
+        if let Some(venv_dir) = default_venv_dir().filter(|_| data_dir.is_none()) {
+            paths.venv_dir = venv_dir;
+        }
+        paths
+    }
+
+    pub fn under(base: PathBuf) -> Self {
+        Self {
+            log_dir: base.join("logs"),
+            work_dir: base.join("launcher"),
+            state_dir: base.join("state"),
+            venv_dir: base.join("venv"),
+            cache_dir: base.join("cache"),
+            trash_dir: base.join("trash"),
+            data_dir: base,
+        }
+    }
+
+    /// Create the generated-file directories, returning the first failure. The state
+    /// dir, which holds pairings and the running processes, is limited to its owner.
+    pub fn create_dirs(&self) -> Result<(), String> {
+        for dir in [&self.log_dir, &self.work_dir, &self.state_dir] {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        restrict_to_owner(&self.state_dir);
+        Ok(())
+    }
+}
+
+/// Where the managed virtual environment goes unless a data dir is given. On Windows
+/// the default data dir is in the roaming profile, which must not sync gigabytes of
+/// packages, so it goes to `%LOCALAPPDATA%\Voicebox\venv` instead.
+pub fn default_venv_dir() -> Option<PathBuf> {
+    if !cfg!(windows) {
+        return None;
+    }
+    dirs::data_local_dir().map(|dir| dir.join("Voicebox").join("venv"))
+}
+
+/// Temp-dir location used when the data dir is unusable, namespaced by OS user so
+/// shared machines don't mix databases in a common /tmp
+pub fn fallback_data_dir() -> PathBuf {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    let name: String = user
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    if name.is_empty() {
+        std::env::temp_dir().join("voicebox")
+    } else {
+        std::env::temp_dir().join(format!("voicebox-{}", name))
+    }
+}
+
+/// Limit the data dir to its owner on Unix. Windows per-user profile directories
+/// already carry owner-only ACLs.
+pub fn restrict_to_owner(dir: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700));
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+/// Environment for the backend when using a machine-wide model cache.
+///
+/// The shared cache is treated as read-only: Hugging Face is put in offline mode so
+/// the backend never tries to download into (or lock files in) another user's cache.
+pub fn shared_models_env(shared_models: &Path) -> Result<Vec<(&'static str, OsString)>, String> {
+    if !shared_models.is_dir() {
+        return Err(format!(
+            "Shared model cache {} does not exist or is not a directory",
+            shared_models.display()
+        ));
+    }
+    Ok(vec![
+        ("HF_HUB_CACHE", shared_models.as_os_str().to_os_string()),
+        ("HF_HUB_OFFLINE", OsString::from("1")),
+    ])
+}
+
+/// Classic Win32 path length limit; longer paths need the `\\?\` prefix
+pub const MAX_PATH: usize = 260;
+
+/// Windows extended-length form of an absolute path string, if it needs one.
+///
+/// Drive paths become `\\?\C:\...` and UNC shares become `\\?\UNC\server\...`.
+/// Paths that are short, relative, or already prefixed are left alone.
+pub fn extended_length_str(path: &str) -> Option<String> {
+    if path.encode_utf16().count() < MAX_PATH
+        || path.starts_with(r"\\?\")
+        || path.starts_with(r"\\.\")
+    {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{}", unc));
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\' {
+        return Some(format!(r"\\?\{}", path));
+    }
+    None
+}
+
+/// Path usable by tools that are not long-path aware (pip, Win32 APIs without the
+/// manifest opt-in). A no-op outside Windows.
+pub fn long_path(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(extended) = path.to_str().and_then(extended_length_str) {
+            return PathBuf::from(extended);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// `PYTHONPATH` with `root_dir` prepended, so `python -m backend.main` resolves the
+/// package without depending on the working directory (which Win32 can't set to a
+/// path beyond MAX_PATH)
+pub fn python_path_with(root_dir: &Path) -> OsString {
+    let mut entries = vec![root_dir.to_path_buf()];
+    if let Some(existing) = std::env::var_os("PYTHONPATH") {
+        entries.extend(std::env::split_paths(&existing));
+    }
+    std::env::join_paths(entries).unwrap_or_else(|_| root_dir.as_os_str().to_os_string())
+}
+
+/// Check whether files can be created in an existing directory
+pub fn is_dir_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".voicebox-write-test-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Create `dir` if needed and confirm it can be written to
+pub fn ensure_writable_dir(dir: &Path) -> bool {
+    std::fs::create_dir_all(dir).is_ok() && is_dir_writable(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::deps::write_filtered_requirements;
+
+    /// Lay out an install dir with a bundled backend and mark it read-only
+    fn read_only_install(root: &Path) -> PathBuf {
+        let backend = root.join("install").join("resources").join("backend");
+        std::fs::create_dir_all(&backend).unwrap();
+        std::fs::write(backend.join("requirements.txt"), "fastapi\n").unwrap();
+        for dir in [backend.as_path(), backend.parent().unwrap()] {
+            let mut perms = std::fs::metadata(dir).unwrap().permissions();
+            perms.set_readonly(true);
+            std::fs::set_permissions(dir, perms).unwrap();
+        }
+        backend
+    }
+
+    fn restore_writable(dir: &Path) {
+        let mut perms = std::fs::metadata(dir).unwrap().permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        perms.set_readonly(false);
+        std::fs::set_permissions(dir, perms).unwrap();
+    }
+
+    #[test]
+    fn generated_files_stay_out_of_read_only_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = read_only_install(tmp.path());
+        let data_dir = tmp.path().join("user-data");
+
+        let paths = LauncherPaths::resolve(Some(&data_dir));
+        paths.create_dirs().unwrap();
+
+        let install_root = backend.parent().unwrap().parent().unwrap();
+        for dir in [&paths.log_dir, &paths.work_dir, &paths.state_dir, &paths.venv_dir] {
+            assert!(dir.starts_with(&data_dir), "{} not under data dir", dir.display());
+            assert!(!dir.starts_with(install_root));
+        }
+        assert!(is_dir_writable(&paths.work_dir));
+
+        let filtered =
+            write_filtered_requirements(&backend.join("requirements.txt"), &paths.work_dir)
+                .unwrap();
+        assert!(filtered.starts_with(&paths.work_dir));
+        assert_eq!(std::fs::read_to_string(filtered).unwrap(), "fastapi");
+
+        restore_writable(&backend);
+        restore_writable(backend.parent().unwrap());
+    }
+
+    #[test]
+    fn extended_length_prefix_for_long_windows_paths() {
+        let long = format!(r"C:\Users\Пользователь\{}\backend", "ü".repeat(250));
+        assert_eq!(extended_length_str(&long), Some(format!(r"\\?\{}", long)));
+
+        let unc = format!(r"\\nas\share\{}", "长".repeat(300));
+        assert_eq!(
+            extended_length_str(&unc),
+            Some(format!(r"\\?\UNC\nas\share\{}", "长".repeat(300)))
+        );
+
+        let mixed = format!("D:/{}", "a".repeat(300));
+        assert_eq!(extended_length_str(&mixed), Some(format!(r"\\?\D:\{}", "a".repeat(300))));
+    }
+
+    #[test]
+    fn short_relative_and_prefixed_paths_are_untouched() {
+        assert_eq!(extended_length_str(r"C:\Program Files\Voicebox"), None);
+        assert_eq!(extended_length_str(&format!(r"\\?\C:\{}", "x".repeat(300))), None);
+        assert_eq!(extended_length_str(&"relative\\".repeat(40)), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_restricts_directories_the_launcher_creates() {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |dir: &Path| std::fs::metadata(dir).unwrap().permissions().mode() & 0o777;
+        let tmp = tempfile::tempdir().unwrap();
+        let shared = tmp.path().join("shared");
+        std::fs::create_dir(&shared).unwrap();
+        std::fs::set_permissions(&shared, std::fs::Permissions::from_mode(0o770)).unwrap();
+
+        let paths = LauncherPaths::resolve(Some(&shared));
+        paths.create_dirs().unwrap();
+        assert_eq!(mode(&shared), 0o770);
+        assert_eq!(mode(&paths.state_dir), 0o700);
+
+        let created = tmp.path().join("created");
+        LauncherPaths::resolve(Some(&created));
+        assert_eq!(mode(&created), 0o700);
+    }
+
+    #[test]
+    fn unwritable_data_dir_falls_back_to_temp() {
+        let tmp = tempfile::tempdir().unwrap();
+        // A directory can never be created beneath a regular file, even as root
+        let blocker = tmp.path().join("not-a-dir");
+        std::fs::write(&blocker, b"").unwrap();
+
+        let paths = LauncherPaths::resolve(Some(&blocker.join("data")));
+
+        assert_eq!(paths.data_dir, fallback_data_dir());
+        assert!(!paths.work_dir.starts_with(&blocker));
+    }
+}
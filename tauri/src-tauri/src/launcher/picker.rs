@@ -0,0 +1,150 @@
+// Choosing between several installed interpreters.
+//
+// When discovery finds more than one Python the backend could run on and the user
+// hasn't named one, the launcher asks which to use, showing each one's version and
+// whether PyTorch with CUDA is installed in it, and remembers the answer. Later launches
+// use the remembered interpreter as long as it is still found.
+use crate::launcher::interpreter::{PythonCandidate, PythonVersion, MIN_PYTHON_VERSION};
+use crate::launcher::process::ProcessRunner;
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const PICK_STATE_FILE_NAME: &str = "python_pick.json";
+/// Prints the interpreter's version and its PyTorch build as JSON. Importing torch takes
+/// a few seconds, which is why only the picker asks.
+const PROBE_SCRIPT: &str = "import json, sys
+info = {'version': '%d.%d' % sys.version_info[:2], 'torch': None, 'cuda': False}
+try:
+    import torch
+    info['torch'] = torch.__version__
+    info['cuda'] = torch.cuda.is_available()
+except Exception:
+    pass
+print(json.dumps(info))";
+
+#[derive(Debug, Deserialize)]
+struct Probe {
+    version: String,
+    torch: Option<String>,
+    cuda: bool,
+}
+
+/// An interpreter the backend could run on, as the picker lists it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterpreterInfo {
+    pub candidate: PythonCandidate,
+    pub version: PythonVersion,
+    /// Installed PyTorch version
+    pub torch: Option<String>,
+    pub cuda: bool,
+}
+
+impl InterpreterInfo {
+    /// One line of the picker's list
+    pub fn label(&self) -> String {
+        let torch = match (&self.torch, self.cuda) {
+            (Some(torch), true) => format!("PyTorch {} with CUDA", torch),
+            (Some(torch), false) => format!("PyTorch {}, no CUDA", torch),
+            (None, _) => "no PyTorch".to_string(),
+        };
+        format!("Python {}: {}; {}", self.version, self.candidate.describe(), torch)
+    }
+}
+
+/// Ask `candidate` for its version and PyTorch build. `None` when it doesn't run or is
+/// too old for the backend.
+pub fn probe(runner: &dyn ProcessRunner, candidate: &PythonCandidate) -> Option<InterpreterInfo> {
+    let output = runner.output(&candidate.command().arg("-c").arg(PROBE_SCRIPT)).ok().filter(|o| o.status.success())?;
+    let probe: Probe = serde_json::from_slice(&output.stdout).ok()?;
+    let version = PythonVersion::parse_tag(&probe.version).filter(|version| *version >= MIN_PYTHON_VERSION)?;
+    Some(InterpreterInfo { candidate: candidate.clone(), version, torch: probe.torch, cuda: probe.cuda })
+}
+
+/// The candidates that run and are new enough, in discovery order, each path once
+pub fn viable_interpreters(runner: &dyn ProcessRunner, candidates: &[PythonCandidate]) -> Vec<InterpreterInfo> {
+    let mut viable: Vec<InterpreterInfo> = Vec::new();
+    for candidate in candidates {
+        if viable.iter().any(|info| info.candidate.python == candidate.python) {
+            continue;
+        }
+        viable.extend(probe(runner, candidate));
+    }
+    viable
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedPick {
+    python: PathBuf,
+    picked_at: String,
+}
+
+/// The interpreter the user picked, remembered across launches
+pub struct InterpreterPick {
+    file: StateFile<SavedPick>,
+}
+
+impl InterpreterPick {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { file: StateFile::new(state_dir.join(PICK_STATE_FILE_NAME)) }
+    }
+
+    /// The candidate among `candidates` that was picked before, if it is still found
+    pub fn find<'a>(&self, candidates: &'a [PythonCandidate]) -> Option<&'a PythonCandidate> {
+        let saved = self.file.read().ok()??;
+        candidates.iter().find(|candidate| candidate.python == saved.python)
+    }
+
+    pub fn save(&self, python: &Path) -> Result<(), String> {
+        self.file.write(&SavedPick { python: python.to_path_buf(), picked_at: chrono::Utc::now().to_rfc3339() })
+    }
+
+    /// Forget the pick, so the next launch asks again
+    pub fn clear(&self) -> Result<(), String> {
+        self.file.remove()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::interpreter::PythonSource;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    fn candidate(python: &str, source: PythonSource) -> PythonCandidate {
+        PythonCandidate { python: PathBuf::from(python), source }
+    }
+
+    #[test]
+    fn lists_interpreters_that_run_with_their_torch_build() {
+        let runner = FakeRunner::new();
+        runner.script("/venv/bin/python3", Script::exits(0).stdout(r#"{"version": "3.12", "torch": "2.4.0+cu121", "cuda": true}"#));
+        runner.script("/usr/bin/python3.9", Script::exits(0).stdout(r#"{"version": "3.9", "torch": null, "cuda": false}"#));
+        runner.script("python", Script::exits(0).stdout(r#"{"version": "3.11", "torch": null, "cuda": false}"#));
+        let candidates = [
+            candidate("/venv/bin/python3", PythonSource::Venv(PathBuf::from("/venv"))),
+            candidate("/usr/bin/python3.9", PythonSource::Path),
+            candidate("/missing/python", PythonSource::Path),
+            candidate("python", PythonSource::Path),
+        ];
+        let viable = viable_interpreters(&runner, &candidates);
+        let found: Vec<_> = viable.iter().map(|info| info.candidate.python.clone()).collect();
+        assert_eq!(found, [PathBuf::from("/venv/bin/python3"), PathBuf::from("python")]);
+        assert!(viable[0].label().starts_with("Python 3.12: /venv/bin/python3 (virtual environment /venv)"));
+        assert!(viable[0].label().ends_with("PyTorch 2.4.0+cu121 with CUDA"));
+        assert!(viable[1].label().ends_with("no PyTorch"));
+    }
+
+    #[test]
+    fn remembers_the_pick_while_it_is_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pick = InterpreterPick::new(tmp.path());
+        let candidates = [candidate("/venv/bin/python3", PythonSource::Path), candidate("python", PythonSource::Path)];
+        assert!(pick.find(&candidates).is_none());
+        pick.save(Path::new("python")).unwrap();
+        assert_eq!(pick.find(&candidates), Some(&candidates[1]));
+        assert!(pick.find(&candidates[..1]).is_none());
+        pick.clear().unwrap();
+        assert!(pick.find(&candidates).is_none());
+    }
+}
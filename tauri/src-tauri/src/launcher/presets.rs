@@ -0,0 +1,200 @@
+use crate::launcher::config::LauncherConfig;
+use crate::text::normalize::{self, Rule};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// A named bundle of generation and playback settings defined in the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    /// Voice profile ID as known to the backend
+    pub voice: Option<String>,
+    pub language: Option<String>,
+    pub model_size: Option<String>,
+    pub seed: Option<u64>,
+    pub instruct: Option<String>,
+    pub speed: Option<f32>,
+    /// Output device IDs as returned by `list_audio_output_devices`
+    pub output_devices: Vec<String>,
+    /// Lower other applications' audio to this fraction of its volume while speaking,
+    /// e.g. `0.3` (Windows, and Music and Spotify on macOS)
+    pub duck: Option<f32>,
+    /// Post-processing steps applied in order after generation
+    pub post_processing: Vec<String>,
+    /// Text normalization applied before generation, e.g. `["numbers", "units"]`
+    pub normalize: Vec<Rule>,
+    /// Shortcut that makes this the selected preset, e.g. `"CmdOrCtrl+Alt+1"`
+    pub hotkey: Option<String>,
+}
+
+/// A configured preset as the tray menu and the UI list it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresetStatus {
+    pub name: String,
+    pub hotkey: Option<String>,
+    /// Why it can't be used, such as a missing voice
+    pub error: Option<String>,
+}
+
+/// A preset resolved into something callers can act on directly
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedPreset {
+    pub name: String,
+    /// Body fields for the backend's `/generate` endpoint (everything except `text`)
+    pub request: Map<String, Value>,
+    pub speed: Option<f32>,
+    pub output_devices: Vec<String>,
+    pub duck: Option<f32>,
+    pub post_processing: Vec<String>,
+    pub normalize: Vec<Rule>,
+}
+
+impl ResolvedPreset {
+    /// Build the full `/generate` request body for the given text, normalized per the
+    /// preset's rules
+    pub fn generate_request(&self, text: &str) -> Value {
+        let mut body = self.request.clone();
+        let text = match self.normalize.is_empty() {
+            true => text.to_string(),
+            false => normalize::normalize(text, self.language(), &self.normalize),
+        };
+        body.insert("text".to_string(), json!(text));
+        Value::Object(body)
+    }
+
+    /// Language of the request, defaulting to English like the backend
+    pub fn language(&self) -> &str {
+        self.request.get("language").and_then(Value::as_str).unwrap_or("en")
+    }
+}
+
+impl Preset {
+    pub fn resolve(&self, name: &str) -> Result<ResolvedPreset, String> {
+        let voice = self
+            .voice
+            .as_ref()
+            .filter(|v| !v.trim().is_empty())
+            .ok_or_else(|| format!("Preset '{}' does not specify a voice", name))?;
+
+        if let Some(speed) = self.speed {
+            if !(speed > 0.0 && speed.is_finite()) {
+                return Err(format!("Preset '{}' has an invalid speed: {}", name, speed));
+            }
+        }
+
+        if let Some(duck) = self.duck {
+            if !(0.0..=1.0).contains(&duck) {
+                return Err(format!("Preset '{}' has an invalid duck level: {} (use 0 to 1)", name, duck));
+            }
+        }
+
+        let mut request = Map::new();
+        request.insert("profile_id".to_string(), json!(voice));
+        if let Some(language) = &self.language {
+            request.insert("language".to_string(), json!(language));
+        }
+        if let Some(model_size) = &self.model_size {
+            request.insert("model_size".to_string(), json!(model_size));
+        }
+        if let Some(seed) = self.seed {
+            request.insert("seed".to_string(), json!(seed));
+        }
+        if let Some(instruct) = &self.instruct {
+            request.insert("instruct".to_string(), json!(instruct));
+        }
+
+        let language = self.language.as_deref().unwrap_or("en");
+        if !self.normalize.is_empty() && !normalize::supports_language(language) {
+            return Err(format!(
+                "Preset '{}' enables normalization, which isn't available for language '{}'",
+                name, language
+            ));
+        }
+
+        Ok(ResolvedPreset {
+            name: name.to_string(),
+            request,
+            speed: self.speed,
+            output_devices: self.output_devices.clone(),
+            duck: self.duck,
+            post_processing: self.post_processing.clone(),
+            normalize: self.normalize.clone(),
+        })
+    }
+}
+
+impl LauncherConfig {
+    pub fn preset_names(&self) -> Vec<String> {
+        self.presets.keys().cloned().collect()
+    }
+
+    /// Every preset, in name order
+    pub fn preset_status(&self) -> Vec<PresetStatus> {
+        self.presets
+            .iter()
+            .map(|(name, preset)| PresetStatus {
+                name: name.clone(),
+                hotkey: preset.hotkey.clone(),
+                error: preset.resolve(name).err(),
+            })
+            .collect()
+    }
+
+    pub fn resolve_preset(&self, name: &str) -> Result<ResolvedPreset, String> {
+        let preset = self.presets.get(name).ok_or_else(|| {
+            format!(
+                "Unknown preset '{}' (available: {})",
+                name,
+                self.preset_names().join(", ")
+            )
+        })?;
+        preset.resolve(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(voice: &str) -> Preset {
+        Preset { voice: Some(voice.to_string()), ..Preset::default() }
+    }
+
+    #[test]
+    fn resolves_into_a_generate_request() {
+        let preset = Preset { language: Some("de".to_string()), seed: Some(7), speed: Some(1.2), ..preset("narrator") };
+        let resolved = preset.resolve("host").unwrap();
+        assert_eq!(resolved.language(), "de");
+        assert_eq!(resolved.speed, Some(1.2));
+        assert_eq!(resolved.generate_request("Hallo"), json!({ "profile_id": "narrator", "language": "de", "seed": 7, "text": "Hallo" }));
+    }
+
+    #[test]
+    fn rejects_a_preset_without_a_voice() {
+        assert_eq!(Preset::default().resolve("host").unwrap_err(), "Preset 'host' does not specify a voice");
+        assert!(preset("  ").resolve("host").is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_speed_and_duck_levels() {
+        for speed in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let error = Preset { speed: Some(speed), ..preset("narrator") }.resolve("host").unwrap_err();
+            assert!(error.starts_with("Preset 'host' has an invalid speed"), "{}", error);
+        }
+        let error = Preset { duck: Some(1.5), ..preset("narrator") }.resolve("host").unwrap_err();
+        assert!(error.contains("invalid duck level"), "{}", error);
+    }
+
+    #[test]
+    fn names_the_available_presets_for_an_unknown_one() {
+        let mut config = LauncherConfig::default();
+        config.presets.insert("host".to_string(), Preset { hotkey: Some("CmdOrCtrl+Alt+1".to_string()), ..preset("narrator") });
+        config.presets.insert("guest".to_string(), Preset::default());
+        assert_eq!(config.resolve_preset("villain").unwrap_err(), "Unknown preset 'villain' (available: guest, host)");
+
+        let status = config.preset_status();
+        assert_eq!(status[0].name, "guest");
+        assert!(status[0].error.is_some());
+        assert_eq!(status[1], PresetStatus { name: "host".to_string(), hotkey: Some("CmdOrCtrl+Alt+1".to_string()), error: None });
+    }
+}
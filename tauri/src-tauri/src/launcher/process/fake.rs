@@ -0,0 +1,211 @@
+use super::{ChildProcess, CommandSpec, ExitInfo, ProcessOutput, ProcessRunner};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::OsString;
+use std::io::{self, Cursor, Read};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Exit status reported for a fake child that was killed
+pub const KILLED: ExitInfo = ExitInfo { code: None, signal: Some(9) };
+
+/// Scripted behavior of one fake process run
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub exit: ExitInfo,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// How long the process "runs" before exiting
+    pub delay: Duration,
+    /// Fail at spawn time instead of running
+    pub spawn_error: Option<io::ErrorKind>,
+}
+
+impl Script {
+    pub fn exits(code: i32) -> Self {
+        Self {
+            exit: ExitInfo::code(code),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            delay: Duration::ZERO,
+            spawn_error: None,
+        }
+    }
+
+    pub fn fails_to_spawn(kind: io::ErrorKind) -> Self {
+        Self { spawn_error: Some(kind), ..Self::exits(0) }
+    }
+
+    pub fn stdout(mut self, text: &str) -> Self {
+        self.stdout = text.as_bytes().to_vec();
+        self
+    }
+
+    pub fn stderr(mut self, text: &str) -> Self {
+        self.stderr = text.as_bytes().to_vec();
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// `ProcessRunner` that plays back scripts instead of starting processes.
+///
+/// Scripts are queued per program name and consumed in order; the last one keeps
+/// repeating. Programs with no script fail to spawn with `NotFound`, like a missing
+/// binary. Every spawn is recorded for assertions.
+#[derive(Default)]
+pub struct FakeRunner {
+    scripts: Mutex<HashMap<OsString, VecDeque<Script>>>,
+    calls: Mutex<Vec<CommandSpec>>,
+    next_pid: AtomicU32,
+}
+
+impl FakeRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn script(&self, program: &str, script: Script) -> &Self {
+        self.scripts
+            .lock()
+            .unwrap()
+            .entry(OsString::from(program))
+            .or_default()
+            .push_back(script);
+        self
+    }
+
+    /// Everything started so far, in order
+    pub fn calls(&self) -> Vec<CommandSpec> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn start(&self, spec: &CommandSpec) -> io::Result<FakeChild> {
+        self.calls.lock().unwrap().push(spec.clone());
+        let script = {
+            let mut scripts = self.scripts.lock().unwrap();
+            let queue = scripts.get_mut(&spec.program).filter(|q| !q.is_empty());
+            match queue {
+                Some(queue) if queue.len() > 1 => queue.pop_front(),
+                Some(queue) => queue.front().cloned(),
+                None => None,
+            }
+        };
+        let script = script.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no script for {:?}", spec.program))
+        })?;
+        if let Some(kind) = script.spawn_error {
+            return Err(io::Error::from(kind));
+        }
+        Ok(FakeChild {
+            pid: 1000 + self.next_pid.fetch_add(1, Ordering::Relaxed),
+            stdout: Some(script.stdout.clone()),
+            stderr: Some(script.stderr.clone()),
+            script,
+            started: Instant::now(),
+            killed: false,
+        })
+    }
+}
+
+pub struct FakeChild {
+    pid: u32,
+    script: Script,
+    stdout: Option<Vec<u8>>,
+    stderr: Option<Vec<u8>>,
+    started: Instant,
+    killed: bool,
+}
+
+impl ChildProcess for FakeChild {
+    fn id(&self) -> u32 {
+        self.pid
+    }
+
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.stdout.take().map(|b| Box::new(Cursor::new(b)) as Box<dyn Read + Send>)
+    }
+
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.stderr.take().map(|b| Box::new(Cursor::new(b)) as Box<dyn Read + Send>)
+    }
+
+    fn wait(&mut self) -> io::Result<ExitInfo> {
+        if !self.killed {
+            std::thread::sleep(self.script.delay.saturating_sub(self.started.elapsed()));
+        }
+        Ok(if self.killed { KILLED } else { self.script.exit })
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitInfo>> {
+        if self.killed {
+            return Ok(Some(KILLED));
+        }
+        Ok((self.started.elapsed() >= self.script.delay).then_some(self.script.exit))
+    }
+
+    fn kill(&mut self) -> io::Result<()> {
+        self.killed = true;
+        Ok(())
+    }
+}
+
+impl ProcessRunner for FakeRunner {
+    fn spawn(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>> {
+        Ok(Box::new(self.start(spec)?))
+    }
+
+    fn spawn_inherited(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>> {
+        Ok(Box::new(self.start(spec)?))
+    }
+
+    fn output(&self, spec: &CommandSpec) -> io::Result<ProcessOutput> {
+        let mut child = self.start(spec)?;
+        let status = child.wait()?;
+        Ok(ProcessOutput {
+            status,
+            stdout: child.stdout.take().unwrap_or_default(),
+            stderr: child.stderr.take().unwrap_or_default(),
+        })
+    }
+
+    fn status(&self, spec: &CommandSpec) -> io::Result<ExitInfo> {
+        self.start(spec)?.wait()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_scripts_in_order_and_repeats_the_last() {
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(1)).script("python", Script::exits(0).stdout("ok"));
+        let spec = CommandSpec::new("python").arg("-c").arg("pass");
+
+        assert_eq!(runner.output(&spec).unwrap().status, ExitInfo::code(1));
+        let output = runner.output(&spec).unwrap();
+        assert_eq!(output.stdout, b"ok");
+        assert!(runner.status(&spec).unwrap().success());
+        assert_eq!(runner.calls().len(), 3);
+        assert_eq!(runner.calls()[0].args, vec![OsString::from("-c"), OsString::from("pass")]);
+
+        let err = runner.output(&CommandSpec::new("pip")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn killed_children_report_a_signal() {
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(0).delay(Duration::from_secs(60)));
+        let mut child = runner.spawn(&CommandSpec::new("python")).unwrap();
+        assert_eq!(child.try_wait().unwrap(), None);
+        child.kill().unwrap();
+        assert_eq!(child.wait().unwrap(), KILLED);
+    }
+}
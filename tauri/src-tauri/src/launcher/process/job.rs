@@ -0,0 +1,60 @@
+// Windows Job Object holding a child and everything it starts.
+//
+// Windows has no process groups that die with their parent: a launcher that crashes or
+// is ended from Task Manager would leave the backend and its workers running and
+// holding the port. A job created with `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE` ends all
+// of its processes when its last handle is closed, which the system does for a process
+// that exits in any way. Processes the child starts join the job on their own.
+//
+// The child is assigned right after it is spawned, so anything it starts in the first
+// instant could escape; the backend takes seconds to import before it starts workers.
+use std::io;
+use std::os::windows::io::AsRawHandle;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+    TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+};
+
+pub(super) struct Job(HANDLE);
+
+// SAFETY: a job handle may be used and closed from any thread
+unsafe impl Send for Job {}
+
+impl Job {
+    /// Put `child` into a new job that kills it and its descendants once closed
+    pub(super) fn contain(child: &std::process::Child) -> io::Result<Self> {
+        // SAFETY: creates an unnamed job with default security; the handle is owned by
+        // the returned `Job`, which closes it
+        let job = Self(unsafe { CreateJobObjectW(None, PCWSTR::null()) }.map_err(io::Error::other)?);
+        let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        // SAFETY: `limits` is the structure the information class expects, with its size
+        unsafe {
+            SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                &limits as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+        }
+        .map_err(io::Error::other)?;
+        // SAFETY: the process handle stays valid while `child` is borrowed
+        unsafe { AssignProcessToJobObject(job.0, HANDLE(child.as_raw_handle())) }.map_err(io::Error::other)?;
+        Ok(job)
+    }
+
+    /// End every process in the job
+    pub(super) fn terminate(&self) -> io::Result<()> {
+        // SAFETY: the handle is open until `drop`
+        unsafe { TerminateJobObject(self.0, 1) }.map_err(io::Error::other)
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        // SAFETY: closes the handle `contain` created, once; this ends what is left in the job
+        let _ = unsafe { CloseHandle(self.0) };
+    }
+}
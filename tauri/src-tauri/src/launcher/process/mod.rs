@@ -0,0 +1,436 @@
+// Child process abstraction.
+//
+// Launcher code starts processes through `ProcessRunner` rather than `std::process`
+// directly, so supervision, readiness and installer logic can be exercised in tests
+// against `fake::FakeRunner` without spawning anything.
+pub mod fake;
+#[cfg(windows)]
+mod job;
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a pre-flight check, such as asking Python for its version, may take
+pub const DEFAULT_PREFLIGHT_TIMEOUT_SECS: u64 = 30;
+
+/// Program, arguments, environment and working directory of a child process
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub program: OsString,
+    pub args: Vec<OsString>,
+    /// Added to (not replacing) the launcher's own environment
+    pub env: Vec<(OsString, OsString)>,
+    pub cwd: Option<PathBuf>,
+    /// Open a separate console window for the child (Windows only)
+    pub new_console: bool,
+    /// Start the child in a process group of its own, so Ctrl+C in the launcher's
+    /// console reaches only the launcher and the child is stopped with its workers
+    pub own_process_group: bool,
+}
+
+impl CommandSpec {
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Self { program: program.as_ref().to_os_string(), ..Default::default() }
+    }
+
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args.extend(args.into_iter().map(|a| a.as_ref().to_os_string()));
+        self
+    }
+
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.env.push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    pub fn envs<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        for (key, value) in vars {
+            self = self.env(key, value);
+        }
+        self
+    }
+
+    pub fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.cwd = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn new_console(mut self) -> Self {
+        self.new_console = true;
+        self
+    }
+
+    pub fn own_process_group(mut self) -> Self {
+        self.own_process_group = true;
+        self
+    }
+
+    /// Value this spec sets for `key`, if any
+    pub fn env_value(&self, key: &str) -> Option<&OsStr> {
+        self.env.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.as_os_str())
+    }
+
+    fn to_command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args).envs(self.env.iter().map(|(k, v)| (k, v)));
+        if let Some(cwd) = &self.cwd {
+            cmd.current_dir(cwd);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            let mut flags = 0;
+            if self.new_console {
+                flags |= CREATE_NEW_CONSOLE;
+            }
+            if self.own_process_group {
+                flags |= CREATE_NEW_PROCESS_GROUP;
+            }
+            cmd.creation_flags(flags);
+        }
+        #[cfg(unix)]
+        if self.own_process_group {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        cmd
+    }
+}
+
+/// How a process ended, independent of the platform's `ExitStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitInfo {
+    pub code: Option<i32>,
+    /// Unix signal that terminated the process
+    pub signal: Option<i32>,
+}
+
+impl ExitInfo {
+    pub fn code(code: i32) -> Self {
+        Self { code: Some(code), signal: None }
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+impl From<ExitStatus> for ExitInfo {
+    fn from(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+        Self { code: status.code(), signal }
+    }
+}
+
+impl fmt::Display for ExitInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.code, self.signal) {
+            (Some(code), _) => write!(f, "exit code {}", code),
+            (None, Some(signal)) => write!(f, "signal {}", signal),
+            (None, None) => f.write_str("unknown status"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessOutput {
+    pub status: ExitInfo,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// A running child started by a `ProcessRunner`
+pub trait ChildProcess: Send {
+    fn id(&self) -> u32;
+    /// The piped stdout stream; `None` once taken
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>>;
+    /// The piped stderr stream; `None` once taken
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>>;
+    fn wait(&mut self) -> io::Result<ExitInfo>;
+    fn try_wait(&mut self) -> io::Result<Option<ExitInfo>>;
+    fn kill(&mut self) -> io::Result<()>;
+    /// Ask the child and the processes it started to exit, as SIGTERM to its process
+    /// group does. Unsupported where there is no such request, as on Windows.
+    fn terminate(&mut self) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+    /// Kill the child together with the processes it started
+    fn kill_tree(&mut self) -> io::Result<()> {
+        self.kill()
+    }
+}
+
+pub trait ProcessRunner: Send + Sync {
+    /// Start a long-running child with stdin closed and stdout/stderr piped
+    fn spawn(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>>;
+    /// Start a child with stdin closed and the launcher's stdout/stderr
+    fn spawn_inherited(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>>;
+    /// Run to completion, capturing stdout and stderr
+    fn output(&self, spec: &CommandSpec) -> io::Result<ProcessOutput>;
+    /// Run to completion with inherited stdio, e.g. for interactive installers
+    fn status(&self, spec: &CommandSpec) -> io::Result<ExitInfo>;
+}
+
+/// Runs real processes through `std::process`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemRunner;
+
+struct SystemChild {
+    child: std::process::Child,
+    /// Whether the child leads a process group of its own
+    group: bool,
+    /// Job the child and its descendants run in, ended with it
+    #[cfg(windows)]
+    job: Option<job::Job>,
+}
+
+impl SystemChild {
+    fn new(child: std::process::Child, spec: &CommandSpec) -> Self {
+        let group = spec.own_process_group;
+        #[cfg(windows)]
+        let job = match group {
+            true => job::Job::contain(&child)
+                .inspect_err(|e| crate::launcher::log::log(&format!("Launcher: Failed to put PID {} into a job: {}", child.id(), e)))
+                .ok(),
+            false => None,
+        };
+        Self {
+            child,
+            group,
+            #[cfg(windows)]
+            job,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: i32) -> io::Result<()> {
+    // SAFETY: only sends a signal; the group is the one the child was started in
+    match unsafe { libc::killpg(pgid as libc::pid_t, signal) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+impl ChildProcess for SystemChild {
+    fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>)
+    }
+
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>)
+    }
+
+    fn wait(&mut self) -> io::Result<ExitInfo> {
+        self.child.wait().map(ExitInfo::from)
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitInfo>> {
+        self.child.try_wait().map(|s| s.map(ExitInfo::from))
+    }
+
+    fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()
+    }
+
+    #[cfg(unix)]
+    fn terminate(&mut self) -> io::Result<()> {
+        match self.group {
+            true => signal_group(self.child.id(), libc::SIGTERM),
+            // SAFETY: only sends a signal to our own child
+            false => match unsafe { libc::kill(self.child.id() as libc::pid_t, libc::SIGTERM) } {
+                0 => Ok(()),
+                _ => Err(io::Error::last_os_error()),
+            },
+        }
+    }
+
+    fn kill_tree(&mut self) -> io::Result<()> {
+        #[cfg(unix)]
+        if self.group {
+            // The group outlives its leader while workers are left, so signal it either way
+            let _ = signal_group(self.child.id(), libc::SIGKILL);
+        }
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            job.terminate()?;
+        } else if self.group {
+            let pid = self.child.id().to_string();
+            let _ = Command::new("taskkill").args(["/PID", pid.as_str(), "/T", "/F"]).stdout(Stdio::null()).stderr(Stdio::null()).status();
+        }
+        match self.child.kill() {
+            Err(e) if e.kind() != io::ErrorKind::InvalidInput => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ProcessRunner for SystemRunner {
+    fn spawn(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>> {
+        let child = spec
+            .to_command()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        Ok(Box::new(SystemChild::new(child, spec)))
+    }
+
+    fn spawn_inherited(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>> {
+        let child = spec.to_command().stdin(Stdio::null()).spawn()?;
+        Ok(Box::new(SystemChild::new(child, spec)))
+    }
+
+    fn output(&self, spec: &CommandSpec) -> io::Result<ProcessOutput> {
+        let output = spec.to_command().stdin(Stdio::null()).output()?;
+        Ok(ProcessOutput { status: output.status.into(), stdout: output.stdout, stderr: output.stderr })
+    }
+
+    fn status(&self, spec: &CommandSpec) -> io::Result<ExitInfo> {
+        spec.to_command().status().map(ExitInfo::from)
+    }
+}
+
+/// Runs `output` calls of another runner with a time limit, killing children that don't
+/// finish in time. Pre-flight checks go through it: a hung interpreter, such as a broken
+/// Microsoft Store alias or one held up by a virus scanner, would otherwise block the
+/// launch forever. Spawned and interactive processes are left to run as long as they do.
+pub struct TimeoutRunner<'a> {
+    inner: &'a dyn ProcessRunner,
+    timeout: Duration,
+}
+
+impl<'a> TimeoutRunner<'a> {
+    pub fn new(inner: &'a dyn ProcessRunner, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+}
+
+fn read_in_background(mut stream: Box<dyn Read + Send>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stream.read_to_end(&mut buf);
+        buf
+    })
+}
+
+impl ProcessRunner for TimeoutRunner<'_> {
+    fn spawn(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>> {
+        self.inner.spawn(spec)
+    }
+
+    fn spawn_inherited(&self, spec: &CommandSpec) -> io::Result<Box<dyn ChildProcess>> {
+        self.inner.spawn_inherited(spec)
+    }
+
+    /// `Err` of kind `TimedOut` when the child had to be killed. Its output is dropped
+    /// then, since processes it started may keep the pipes open.
+    fn output(&self, spec: &CommandSpec) -> io::Result<ProcessOutput> {
+        let mut child = self.inner.spawn(spec)?;
+        let stdout = child.take_stdout().map(read_in_background);
+        let stderr = child.take_stderr().map(read_in_background);
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if started.elapsed() >= self.timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "{} did not finish within {} seconds and was stopped",
+                        Path::new(&spec.program).display(),
+                        self.timeout.as_secs()
+                    ),
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        let collect = |reader: Option<std::thread::JoinHandle<Vec<u8>>>| reader.and_then(|r| r.join().ok()).unwrap_or_default();
+        Ok(ProcessOutput { status, stdout: collect(stdout), stderr: collect(stderr) })
+    }
+
+    fn status(&self, spec: &CommandSpec) -> io::Result<ExitInfo> {
+        self.inner.status(spec)
+    }
+}
+
+#[cfg(test)]
+mod timeout_tests {
+    use super::fake::{FakeRunner, Script};
+    use super::*;
+
+    #[test]
+    fn kills_checks_that_hang() {
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(0).stdout("3.12"));
+        runner.script("python", Script::exits(0).delay(Duration::from_secs(60)));
+        let limited = TimeoutRunner::new(&runner, Duration::from_millis(100));
+        let spec = CommandSpec::new("python").arg("--version");
+        assert_eq!(limited.output(&spec).unwrap().stdout, b"3.12");
+
+        let started = Instant::now();
+        let err = limited.output(&spec).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(err.to_string().starts_with("python did not finish"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        /// Whatever we put in a spec is exactly the argv the child sees
+        #[test]
+        fn children_receive_the_exact_argv(
+            args in prop::collection::vec("[^\u{0}]{0,24}", 0..6),
+        ) {
+            let spec = CommandSpec::new("/bin/sh")
+                .args(["-c", r#"for a in "$@"; do printf '%s\0' "$a"; done"#, "sh"])
+                .args(&args);
+            let output = SystemRunner.output(&spec).unwrap();
+            prop_assert!(output.status.success());
+
+            let received: Vec<String> = String::from_utf8(output.stdout)
+                .unwrap()
+                .split_terminator('\0')
+                .map(str::to_string)
+                .collect();
+            prop_assert_eq!(received, args);
+        }
+    }
+}
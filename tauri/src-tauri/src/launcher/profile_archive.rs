@@ -0,0 +1,370 @@
+use crate::launcher::capabilities::{self, Capabilities};
+use crate::launcher::consent::ConsentRecord;
+use crate::launcher::signing::fingerprint;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const ARCHIVE_EXTENSION: &str = "voicebox";
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const SIGNATURE: &str = "signature.json";
+/// The backend's own profile export (reference audio and metadata), stored untouched
+const PROFILE_EXPORT: &str = "profile.zip";
+/// Consent record inside the backend's export
+const CONSENT: &str = "consent.json";
+/// Same limit the backend applies to profile imports
+const MAX_ENTRY_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub language: String,
+}
+
+/// What an archive claims to contain. Covered by the signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub profile: ProfileInfo,
+    pub created_at: String,
+    /// Version of the app that wrote the archive
+    pub app_version: String,
+    /// API version of the backend the profile was exported from
+    pub api_version: u32,
+    /// Hex SHA-256 of every other entry
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SignatureFile {
+    public_key: String,
+    signature: String,
+}
+
+/// An archive whose signature and contents have been checked
+#[derive(Debug)]
+pub struct VerifiedArchive {
+    pub manifest: ArchiveManifest,
+    /// Fingerprint of the key that signed the archive
+    pub signer: String,
+    pub consent: ConsentRecord,
+    pub profile_export: Vec<u8>,
+}
+
+/// A profile registered with the backend from an archive
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedProfile {
+    pub id: String,
+    pub name: String,
+    pub signer: String,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn decode_hex<const N: usize>(value: &str, what: &str) -> Result<[u8; N], String> {
+    hex::decode(value)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| format!("Invalid {} in archive signature", what))
+}
+
+/// The validated consent record of a backend profile export. Voices are only shared
+/// together with one.
+fn export_consent(profile_export: &[u8]) -> Result<ConsentRecord, String> {
+    let mut inner = ZipArchive::new(Cursor::new(profile_export))
+        .map_err(|e| format!("Invalid profile export: {}", e))?;
+    for required in ["manifest.json", "samples.json"] {
+        if inner.index_for_name(required).is_none() {
+            return Err(format!("Profile export is missing {}", required));
+        }
+    }
+    if inner.index_for_name(CONSENT).is_none() {
+        return Err("Profile has no consent record; record consent for the voice before sharing it".to_string());
+    }
+    let record: ConsentRecord = serde_json::from_slice(&read_entry(&mut inner, CONSENT)?)
+        .map_err(|e| format!("Invalid consent record in profile export: {}", e))?;
+    record.validate()?;
+    Ok(record)
+}
+
+/// Wrap a backend profile export into a signed archive
+pub fn write_archive(
+    profile: ProfileInfo,
+    api_version: u32,
+    profile_export: &[u8],
+    key: &SigningKey,
+) -> Result<(ArchiveManifest, Vec<u8>), String> {
+    export_consent(profile_export)?;
+    let manifest = ArchiveManifest {
+        format_version: FORMAT_VERSION,
+        profile,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        api_version,
+        files: BTreeMap::from([(PROFILE_EXPORT.to_string(), sha256_hex(profile_export))]),
+    };
+    let manifest_json =
+        serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    let signature = SignatureFile {
+        public_key: hex::encode(key.verifying_key().as_bytes()),
+        signature: hex::encode(key.sign(&manifest_json).to_bytes()),
+    };
+    let signature_json =
+        serde_json::to_vec_pretty(&signature).map_err(|e| format!("Failed to serialize signature: {}", e))?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let deflated = SimpleFileOptions::default();
+    // Already compressed
+    let stored = deflated.compression_method(CompressionMethod::Stored);
+    for (name, bytes, options) in [
+        (MANIFEST, manifest_json.as_slice(), deflated),
+        (SIGNATURE, signature_json.as_slice(), deflated),
+        (PROFILE_EXPORT, profile_export, stored),
+    ] {
+        zip.start_file(name, options)
+            .and_then(|_| zip.write_all(bytes).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {} to archive: {}", name, e))?;
+    }
+    let archive = zip.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok((manifest, archive.into_inner()))
+}
+
+fn read_entry(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Vec<u8>, String> {
+    let entry = zip.by_name(name).map_err(|_| format!("Archive is missing {}", name))?;
+    let mut bytes = Vec::new();
+    entry
+        .take(MAX_ENTRY_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {} from archive: {}", name, e))?;
+    if bytes.len() as u64 > MAX_ENTRY_BYTES {
+        return Err(format!("{} in archive is larger than {} MB", name, MAX_ENTRY_BYTES / (1024 * 1024)));
+    }
+    Ok(bytes)
+}
+
+/// Check an archive's structure, signature and content hashes
+pub fn read_archive(bytes: &[u8]) -> Result<VerifiedArchive, String> {
+    let mut zip =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Not a Voicebox profile archive: {}", e))?;
+    if let Some(extra) = zip.file_names().find(|name| ![MANIFEST, SIGNATURE, PROFILE_EXPORT].contains(name)) {
+        return Err(format!("Unexpected file in archive: {}", extra));
+    }
+
+    let manifest_json = read_entry(&mut zip, MANIFEST)?;
+    let signature: SignatureFile = serde_json::from_slice(&read_entry(&mut zip, SIGNATURE)?)
+        .map_err(|e| format!("Invalid signature.json: {}", e))?;
+    let public_key = VerifyingKey::from_bytes(&decode_hex(&signature.public_key, "public key")?)
+        .map_err(|_| "Invalid public key in archive signature".to_string())?;
+    let signed = Signature::from_bytes(&decode_hex(&signature.signature, "signature")?);
+    public_key
+        .verify(&manifest_json, &signed)
+        .map_err(|_| "Archive signature doesn't match; the file was changed after export".to_string())?;
+
+    let manifest: ArchiveManifest =
+        serde_json::from_slice(&manifest_json).map_err(|e| format!("Invalid manifest.json: {}", e))?;
+    if manifest.format_version != FORMAT_VERSION {
+        return Err(format!(
+            "Archive format {} isn't supported by this version of Voicebox (expected {})",
+            manifest.format_version, FORMAT_VERSION
+        ));
+    }
+
+    let profile_export = read_entry(&mut zip, PROFILE_EXPORT)?;
+    let listed: Vec<&str> = manifest.files.keys().map(String::as_str).collect();
+    if listed != [PROFILE_EXPORT] || manifest.files[PROFILE_EXPORT] != sha256_hex(&profile_export) {
+        return Err("Archive contents don't match its manifest".to_string());
+    }
+    let consent = export_consent(&profile_export)?;
+
+    Ok(VerifiedArchive { manifest, signer: fingerprint(&public_key), consent, profile_export })
+}
+
+/// Whether a backend with `capabilities` can use the profile an archive carries
+pub fn check_compatible(manifest: &ArchiveManifest, capabilities: &Capabilities) -> Result<(), String> {
+    if manifest.api_version > capabilities.api_version {
+        return Err(format!(
+            "Profile was exported from a newer backend (API {}); this backend supports API {}",
+            manifest.api_version, capabilities.api_version
+        ));
+    }
+    if !capabilities.voice_cloning {
+        return Err("This backend doesn't support voice cloning".to_string());
+    }
+    if !capabilities.supports_language(&manifest.profile.language) {
+        return Err(format!(
+            "Profile language '{}' isn't supported by this backend",
+            manifest.profile.language
+        ));
+    }
+    Ok(())
+}
+
+fn request_error(url: &str, e: reqwest::Error) -> String {
+    format!("Request to {} failed: {}", url, e)
+}
+
+/// Export profile `profile_id` from the backend at `base_url` to a signed archive at `dest`
+pub async fn export_profile(
+    base_url: &str,
+    profile_id: &str,
+    dest: &Path,
+    key: &SigningKey,
+) -> Result<ArchiveManifest, String> {
+    let base_url = base_url.trim_end_matches('/');
+    let client = reqwest::Client::new();
+
+    let url = format!("{}/profiles/{}", base_url, profile_id);
+    let profile: ProfileInfo = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| request_error(&url, e))?
+        .json()
+        .await
+        .map_err(|e| request_error(&url, e))?;
+    let capabilities = capabilities::fetch(base_url).await?;
+
+    let url = format!("{}/profiles/{}/export", base_url, profile_id);
+    let export = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| request_error(&url, e))?
+        .bytes()
+        .await
+        .map_err(|e| request_error(&url, e))?;
+
+    let (manifest, archive) = write_archive(profile, capabilities.api_version, &export, key)?;
+    std::fs::write(dest, archive).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+    Ok(manifest)
+}
+
+/// Verify the archive at `path` and register its profile with the backend at `base_url`
+pub async fn import_profile(base_url: &str, path: &Path) -> Result<ImportedProfile, String> {
+    let base_url = base_url.trim_end_matches('/');
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let archive = read_archive(&bytes)?;
+    check_compatible(&archive.manifest, &capabilities::fetch(base_url).await?)?;
+
+    let url = format!("{}/profiles/import", base_url);
+    let part = reqwest::multipart::Part::bytes(archive.profile_export)
+        .file_name(PROFILE_EXPORT)
+        .mime_str("application/zip")
+        .map_err(|e| request_error(&url, e))?;
+    let created: serde_json::Value = reqwest::Client::new()
+        .post(&url)
+        .multipart(reqwest::multipart::Form::new().part("file", part))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| request_error(&url, e))?
+        .json()
+        .await
+        .map_err(|e| request_error(&url, e))?;
+
+    Ok(ImportedProfile {
+        id: created["id"].as_str().unwrap_or_default().to_string(),
+        name: created["name"].as_str().unwrap_or(&archive.manifest.profile.name).to_string(),
+        signer: archive.signer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::consent::ConsentBasis;
+
+    fn export_with(consent: Option<&ConsentRecord>) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in ["manifest.json", "samples.json"] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(b"{}").unwrap();
+        }
+        if let Some(consent) = consent {
+            zip.start_file(CONSENT, SimpleFileOptions::default()).unwrap();
+            zip.write_all(&serde_json::to_vec(consent).unwrap()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn consent() -> ConsentRecord {
+        ConsentRecord::acknowledge(ConsentBasis::OwnVoice, None, None, None)
+    }
+
+    fn profile_export() -> Vec<u8> {
+        export_with(Some(&consent()))
+    }
+
+    fn profile() -> ProfileInfo {
+        ProfileInfo { name: "Narrator".to_string(), description: None, language: "de".to_string() }
+    }
+
+    #[test]
+    fn round_trips_and_reports_the_signer() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let export = profile_export();
+        let (manifest, bytes) = write_archive(profile(), 1, &export, &key).unwrap();
+        let archive = read_archive(&bytes).unwrap();
+        assert_eq!(archive.manifest, manifest);
+        assert_eq!(archive.signer, fingerprint(&key.verifying_key()));
+        assert_eq!(archive.profile_export, export);
+        assert_eq!(archive.consent.basis, ConsentBasis::OwnVoice);
+        check_compatible(&archive.manifest, &Capabilities::default()).unwrap();
+    }
+
+    #[test]
+    fn rejects_tampered_manifests() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let export = profile_export();
+        let (mut manifest, bytes) = write_archive(profile(), 1, &export, &key).unwrap();
+
+        // Re-pack with an edited manifest but the original signature
+        let mut original = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        let signature = read_entry(&mut original, SIGNATURE).unwrap();
+        manifest.profile.name = "Someone else".to_string();
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in [
+            (MANIFEST, serde_json::to_vec_pretty(&manifest).unwrap()),
+            (SIGNATURE, signature),
+            (PROFILE_EXPORT, export),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(&bytes).unwrap();
+        }
+        let tampered = zip.finish().unwrap().into_inner();
+        assert!(read_archive(&tampered).unwrap_err().contains("signature"));
+    }
+
+    #[test]
+    fn requires_a_valid_consent_record() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let err = write_archive(profile(), 1, &export_with(None), &key).unwrap_err();
+        assert!(err.contains("consent"), "{}", err);
+
+        let unnamed = ConsentRecord::acknowledge(ConsentBasis::SpeakerConsent, None, None, None);
+        assert!(write_archive(profile(), 1, &export_with(Some(&unnamed)), &key).is_err());
+    }
+
+    #[test]
+    fn checks_the_target_backend() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let (mut manifest, _) = write_archive(profile(), 2, &profile_export(), &key).unwrap();
+        assert!(check_compatible(&manifest, &Capabilities::default()).unwrap_err().contains("newer backend"));
+
+        manifest.api_version = 1;
+        let capabilities = Capabilities { languages: vec!["en".to_string()], ..Capabilities::default() };
+        assert!(check_compatible(&manifest, &capabilities).unwrap_err().contains("'de'"));
+    }
+}
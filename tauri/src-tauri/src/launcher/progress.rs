@@ -0,0 +1,263 @@
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const PROGRESS_STATE_FILE_NAME: &str = "progress.json";
+/// Tasks not reported for this long belong to a process that died and are dropped
+const STALE_AFTER_SECS: i64 = 120;
+/// How often a `TaskReporter` repeats its last report while nothing changes
+const HEARTBEAT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressKind {
+    /// Installing the backend's Python packages
+    Install,
+    ModelDownload,
+    BatchJob,
+    /// Saving a large file the backend produced, such as stems or a dataset
+    FileDownload,
+    /// A fine-tuning run of an external trainer
+    FineTune,
+}
+
+/// A long-running operation as last reported by whichever process runs it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub kind: ProgressKind,
+    pub label: String,
+    /// Share done from 0 to 1; `None` while it can't be told
+    pub fraction: Option<f32>,
+    /// Finished unsuccessfully; shown until cleared or stale so the failure is noticed
+    pub failed: bool,
+    /// RFC 3339 timestamp of the last report
+    pub updated_at: String,
+}
+
+/// All running operations combined, as the taskbar or dock shows them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OverallProgress {
+    /// Average over the tasks that know how far along they are; `None` when none do
+    pub fraction: Option<f32>,
+    pub tasks: usize,
+    pub failed: bool,
+}
+
+/// Progress of long-running operations shared between the launcher, CLI commands
+/// and the app through `state/progress.json`, so the app can show work done by
+/// other processes too
+pub struct ProgressBus {
+    file: StateFile<BTreeMap<String, TaskProgress>>,
+}
+
+impl ProgressBus {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { file: StateFile::new(state_dir.join(PROGRESS_STATE_FILE_NAME)) }
+    }
+
+    fn set(&self, id: &str, task: Option<TaskProgress>) -> Result<(), String> {
+        self.file.update(|tasks| {
+            let mut tasks = tasks.unwrap_or_default();
+            match task {
+                Some(task) => tasks.insert(id.to_string(), task),
+                None => tasks.remove(id),
+            };
+            (!tasks.is_empty()).then_some(tasks)
+        })?;
+        Ok(())
+    }
+
+    /// Report that task `id` is running and how far it is
+    pub fn report(&self, id: &str, kind: ProgressKind, label: &str, fraction: Option<f32>) -> Result<(), String> {
+        let task = TaskProgress {
+            kind,
+            label: label.to_string(),
+            fraction: fraction.map(|f| f.clamp(0.0, 1.0)),
+            failed: false,
+            updated_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.set(id, Some(task))
+    }
+
+    pub fn fail(&self, id: &str, kind: ProgressKind, label: &str) -> Result<(), String> {
+        let task = TaskProgress {
+            kind,
+            label: label.to_string(),
+            fraction: None,
+            failed: true,
+            updated_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.set(id, Some(task))
+    }
+
+    /// Task `id` is done, or its failure has been seen
+    pub fn finish(&self, id: &str) -> Result<(), String> {
+        self.set(id, None)
+    }
+
+    /// Tasks reported recently enough to still be running
+    pub fn tasks(&self) -> Result<BTreeMap<String, TaskProgress>, String> {
+        let now = chrono::Utc::now();
+        let mut tasks = self.file.read()?.unwrap_or_default();
+        tasks.retain(|_, task| {
+            chrono::DateTime::parse_from_rfc3339(&task.updated_at)
+                .is_ok_and(|at| now.signed_duration_since(at).num_seconds() < STALE_AFTER_SECS)
+        });
+        Ok(tasks)
+    }
+
+    pub fn overall(&self) -> Result<Option<OverallProgress>, String> {
+        Ok(overall(&self.tasks()?))
+    }
+}
+
+/// Reports one task for as long as it lives, repeating the last report so the task
+/// doesn't go stale while a single step takes long. Dropping it finishes the task.
+pub struct TaskReporter {
+    state_dir: PathBuf,
+    id: String,
+    kind: ProgressKind,
+    label: String,
+    fraction: Arc<Mutex<Option<f32>>>,
+    stop: Arc<AtomicBool>,
+    heartbeat: Option<std::thread::JoinHandle<()>>,
+}
+
+impl TaskReporter {
+    pub fn start(state_dir: &Path, id: &str, kind: ProgressKind, label: &str, fraction: Option<f32>) -> Self {
+        let fraction = Arc::new(Mutex::new(fraction));
+        let stop = Arc::new(AtomicBool::new(false));
+        let heartbeat = {
+            let (state_dir, id, label) = (state_dir.to_path_buf(), id.to_string(), label.to_string());
+            let (fraction, stop) = (fraction.clone(), stop.clone());
+            std::thread::spawn(move || {
+                let bus = ProgressBus::new(&state_dir);
+                while !stop.load(Ordering::Relaxed) {
+                    let current = *fraction.lock().unwrap();
+                    let _ = bus.report(&id, kind, &label, current);
+                    std::thread::park_timeout(HEARTBEAT);
+                }
+            })
+        };
+        Self {
+            state_dir: state_dir.to_path_buf(),
+            id: id.to_string(),
+            kind,
+            label: label.to_string(),
+            fraction,
+            stop,
+            heartbeat: Some(heartbeat),
+        }
+    }
+
+    pub fn set(&self, fraction: Option<f32>) {
+        *self.fraction.lock().unwrap() = fraction;
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.thread().unpark();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.thread().unpark();
+            let _ = heartbeat.join();
+        }
+    }
+
+    /// End the task as failed; the failure stays visible until someone finishes it
+    pub fn fail(mut self) {
+        self.stop();
+        let _ = ProgressBus::new(&self.state_dir).fail(&self.id, self.kind, &self.label);
+        // Already reported, so dropping must not clear it
+        self.id.clear();
+    }
+}
+
+impl Drop for TaskReporter {
+    fn drop(&mut self) {
+        self.stop();
+        if !self.id.is_empty() {
+            let _ = ProgressBus::new(&self.state_dir).finish(&self.id);
+        }
+    }
+}
+
+pub fn overall(tasks: &BTreeMap<String, TaskProgress>) -> Option<OverallProgress> {
+    if tasks.is_empty() {
+        return None;
+    }
+    let known: Vec<f32> = tasks.values().filter(|t| !t.failed).filter_map(|t| t.fraction).collect();
+    Some(OverallProgress {
+        fraction: (!known.is_empty()).then(|| known.iter().sum::<f32>() / known.len() as f32),
+        tasks: tasks.values().filter(|t| !t.failed).count(),
+        failed: tasks.values().any(|t| t.failed),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combines_tasks_from_every_reporter() {
+        let dir = tempfile::tempdir().unwrap();
+        let bus = ProgressBus::new(dir.path());
+        assert_eq!(bus.overall().unwrap(), None);
+
+        bus.report("job-1", ProgressKind::BatchJob, "Chapter 1", Some(0.25)).unwrap();
+        // A second process, such as the launcher installing packages
+        ProgressBus::new(dir.path()).report("install", ProgressKind::Install, "Installing", None).unwrap();
+        bus.report("model", ProgressKind::ModelDownload, "qwen-tts-1.7B", Some(0.75)).unwrap();
+        let overall = bus.overall().unwrap().unwrap();
+        assert_eq!((overall.fraction, overall.tasks, overall.failed), (Some(0.5), 3, false));
+
+        bus.fail("model", ProgressKind::ModelDownload, "qwen-tts-1.7B").unwrap();
+        bus.finish("job-1").unwrap();
+        let overall = bus.overall().unwrap().unwrap();
+        assert_eq!((overall.fraction, overall.tasks, overall.failed), (None, 1, true));
+
+        bus.finish("install").unwrap();
+        bus.finish("model").unwrap();
+        assert_eq!(bus.overall().unwrap(), None);
+        assert!(!dir.path().join(PROGRESS_STATE_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn reporters_finish_or_fail_their_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let bus = ProgressBus::new(dir.path());
+        let reporter = TaskReporter::start(dir.path(), "job-1", ProgressKind::BatchJob, "Chapter 1", Some(0.0));
+        reporter.set(Some(0.5));
+        let failing = TaskReporter::start(dir.path(), "install", ProgressKind::Install, "Installing", None);
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while bus.tasks().unwrap().get("job-1").and_then(|t| t.fraction) != Some(0.5) {
+            assert!(std::time::Instant::now() < deadline, "the update was never reported");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        drop(reporter);
+        failing.fail();
+        let tasks = bus.tasks().unwrap();
+        assert_eq!(tasks.keys().collect::<Vec<_>>(), ["install"]);
+        assert!(tasks["install"].failed);
+    }
+
+    #[test]
+    fn drops_tasks_of_processes_that_stopped_reporting() {
+        let dir = tempfile::tempdir().unwrap();
+        let bus = ProgressBus::new(dir.path());
+        bus.report("job-1", ProgressKind::BatchJob, "Chapter 1", Some(0.5)).unwrap();
+        bus.file
+            .update(|tasks| {
+                let mut tasks = tasks.unwrap();
+                tasks.get_mut("job-1").unwrap().updated_at = "2020-01-01T00:00:00+00:00".to_string();
+                Some(tasks)
+            })
+            .unwrap();
+        assert!(bus.tasks().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::launcher::state::StateFile;
+use crate::launcher::workspace::WorkspaceConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const PROJECTS_STATE_FILE_NAME: &str = "projects.json";
+/// Environment variable pointing the backend at the database of the open project
+pub const DB_PATH_ENV: &str = "VOICEBOX_DB_PATH";
+/// Database file name inside a data dir, as the backend names it
+pub const DB_FILE_NAME: &str = "voicebox.db";
+/// Environment variable that makes the backend refuse changes to a locked project
+pub const READ_ONLY_ENV: &str = "VOICEBOX_READ_ONLY";
+/// How many projects the recent list shows
+pub const RECENT_LIMIT: usize = 10;
+
+/// A project with its own database and audio, as listed for the frontend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    /// Data root the project is kept in
+    pub root: String,
+    pub dir: PathBuf,
+    /// RFC 3339 timestamps
+    pub created_at: String,
+    pub last_opened_at: Option<String>,
+    /// Finished project opened for review only; the backend refuses changes to it
+    pub locked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RegisteredProject {
+    /// Root chosen when the project was created; the config's assignment wins
+    root: String,
+    created_at: String,
+    last_opened_at: Option<String>,
+    #[serde(default)]
+    locked: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Registry {
+    /// Project the backend runs on; the data dir itself when `None`
+    active: Option<String>,
+    projects: BTreeMap<String, RegisteredProject>,
+}
+
+/// Known projects and which one is open, in `state/projects.json`
+pub struct Projects<'a> {
+    workspace: &'a WorkspaceConfig,
+    data_dir: PathBuf,
+    file: StateFile<Registry>,
+}
+
+impl<'a> Projects<'a> {
+    pub fn new(workspace: &'a WorkspaceConfig, data_dir: &Path, state_dir: &Path) -> Self {
+        Self {
+            workspace,
+            data_dir: data_dir.to_path_buf(),
+            file: StateFile::new(state_dir.join(PROJECTS_STATE_FILE_NAME)),
+        }
+    }
+
+    fn root_of<'r>(&'r self, name: &str, registered: &'r RegisteredProject) -> &'r str {
+        self.workspace.projects.get(name).map_or(registered.root.as_str(), String::as_str)
+    }
+
+    fn info(&self, name: &str, registered: &RegisteredProject) -> Result<ProjectInfo, String> {
+        let root = self.root_of(name, registered);
+        Ok(ProjectInfo {
+            name: name.to_string(),
+            root: root.to_string(),
+            dir: self.workspace.project_dir_in(&self.data_dir, root, name)?,
+            created_at: registered.created_at.clone(),
+            last_opened_at: registered.last_opened_at.clone(),
+            locked: registered.locked,
+        })
+    }
+
+    /// Start an empty project in `root` (the config's assignment, or `default`)
+    pub fn create(&self, name: &str, root: Option<&str>) -> Result<ProjectInfo, String> {
+        let assigned = self.workspace.project_root(name);
+        if root.is_some_and(|root| self.workspace.projects.contains_key(name) && root != assigned) {
+            return Err(format!("The config keeps project {} in root {}", name, assigned));
+        }
+        let root = root.unwrap_or(assigned).to_string();
+        let dir = self.workspace.project_dir_in(&self.data_dir, &root, name)?;
+        if self.file.read()?.unwrap_or_default().projects.contains_key(name) || dir.exists() {
+            return Err(format!("A project named {} already exists", name));
+        }
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let registered = RegisteredProject {
+            root,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_opened_at: None,
+            locked: false,
+        };
+        let info = self.info(name, &registered)?;
+        self.file.update(|registry| {
+            let mut registry = registry.unwrap_or_default();
+            registry.projects.insert(name.to_string(), registered);
+            Some(registry)
+        })?;
+        Ok(info)
+    }
+
+    /// Make `name` the project the backend runs on. Projects the config assigns a
+    /// root, or whose dir is already in their root, are registered on first open.
+    /// `None` goes back to the data dir.
+    pub fn open(&self, name: Option<&str>) -> Result<Option<ProjectInfo>, String> {
+        let Some(name) = name else {
+            self.file.update(|registry| Some(Registry { active: None, ..registry.unwrap_or_default() }))?;
+            return Ok(None);
+        };
+        if !self.file.read()?.unwrap_or_default().projects.contains_key(name) {
+            let dir = self.workspace.project_dir(&self.data_dir, name)?;
+            if !dir.is_dir() && !self.workspace.projects.contains_key(name) {
+                return Err(format!("No project named {}", name));
+            }
+            std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        let registry = self.file.update(|registry| {
+            let mut registry = registry.unwrap_or_default();
+            let project = registry.projects.entry(name.to_string()).or_insert_with(|| RegisteredProject {
+                root: self.workspace.project_root(name).to_string(),
+                created_at: now.clone(),
+                last_opened_at: None,
+                locked: false,
+            });
+            project.last_opened_at = Some(now.clone());
+            registry.active = Some(name.to_string());
+            Some(registry)
+        })?;
+        let registry = registry.unwrap_or_default();
+        self.info(name, &registry.projects[name]).map(Some)
+    }
+
+    /// Lock a project for review, or unlock it. A running backend keeps its mode
+    /// until it is restarted.
+    pub fn set_locked(&self, name: &str, locked: bool) -> Result<ProjectInfo, String> {
+        let registry = self.file.update(|registry| {
+            let mut registry = registry.unwrap_or_default();
+            if let Some(project) = registry.projects.get_mut(name) {
+                project.locked = locked;
+            }
+            Some(registry)
+        })?;
+        match registry.unwrap_or_default().projects.get(name) {
+            Some(registered) => self.info(name, registered),
+            None => Err(format!("No project named {}", name)),
+        }
+    }
+
+    /// The open project, if any
+    pub fn active(&self) -> Result<Option<ProjectInfo>, String> {
+        let registry = self.file.read()?.unwrap_or_default();
+        match registry.active.as_deref().and_then(|name| Some((name, registry.projects.get(name)?))) {
+            Some((name, registered)) => self.info(name, registered).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Projects by when they were last opened, most recent first, then those never opened
+    pub fn recent(&self) -> Result<Vec<ProjectInfo>, String> {
+        let registry = self.file.read()?.unwrap_or_default();
+        let mut projects: Vec<ProjectInfo> = registry
+            .projects
+            .iter()
+            .filter_map(|(name, registered)| self.info(name, registered).ok())
+            .collect();
+        projects.sort_by(|a, b| b.last_opened_at.cmp(&a.last_opened_at).then(b.created_at.cmp(&a.created_at)));
+        projects.truncate(RECENT_LIMIT);
+        Ok(projects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::workspace::DataRoot;
+
+    #[test]
+    fn creates_opens_and_lists_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let (data_dir, state_dir) = (dir.path().join("data"), dir.path().join("data/state"));
+        let mut workspace = WorkspaceConfig::default();
+        workspace.roots.insert("nas".to_string(), DataRoot { path: dir.path().join("nas"), backend_path: None });
+        workspace.projects.insert("assigned".to_string(), "nas".to_string());
+        let projects = Projects::new(&workspace, &data_dir, &state_dir);
+
+        let client = projects.create("client-a", Some("nas")).unwrap();
+        assert_eq!(client.dir, dir.path().join("nas/projects/client-a"));
+        assert!(client.dir.is_dir());
+        assert!(projects.create("client-a", None).is_err());
+        projects.create("client-b", None).unwrap();
+        assert!(projects.create("bad", Some("usb")).is_err());
+        assert!(projects.create("assigned", Some("default")).is_err());
+        assert!(projects.open(Some("missing")).is_err());
+        assert_eq!(projects.active().unwrap(), None);
+
+        projects.open(Some("client-a")).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let assigned = projects.open(Some("assigned")).unwrap().unwrap();
+        assert_eq!(assigned.dir, dir.path().join("nas/projects/assigned"));
+        assert_eq!(projects.active().unwrap().unwrap().name, "assigned");
+        let names: Vec<String> = projects.recent().unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, ["assigned", "client-a", "client-b"]);
+
+        assert!(projects.set_locked("client-a", true).unwrap().locked);
+        assert!(projects.set_locked("missing", true).is_err());
+        assert!(projects.recent().unwrap().iter().all(|p| p.locked == (p.name == "client-a")));
+
+        projects.open(None).unwrap();
+        assert_eq!(projects.active().unwrap(), None);
+        assert_eq!(projects.recent().unwrap().len(), 3);
+    }
+}
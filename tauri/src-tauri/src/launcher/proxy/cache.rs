@@ -0,0 +1,176 @@
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::Response;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Responses larger than this are streamed through instead of cached
+const MAX_CACHED_BODY: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// How long a cached response is served before asking the backend again
+    pub ttl_ms: u64,
+    /// GET paths (and everything below them) whose JSON responses may be cached
+    pub paths: Vec<String>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_ms: 2000,
+            paths: vec![
+                "/profiles".to_string(),
+                "/channels".to_string(),
+                "/models/status".to_string(),
+                "/prompt-enhancer/status".to_string(),
+            ],
+        }
+    }
+}
+
+struct Entry {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    stored: Instant,
+}
+
+pub(super) struct ResponseCache {
+    config: CacheConfig,
+    entries: Mutex<HashMap<String, Entry>>,
+    /// Bumped on every invalidation so responses fetched before a write aren't stored
+    generation: AtomicU64,
+}
+
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    let path = path.split('?').next().unwrap_or(path);
+    path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Whether a request may change backend state and so invalidate cached reads
+pub(super) fn is_mutating(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+impl ResponseCache {
+    pub(super) fn new(config: CacheConfig) -> Self {
+        Self { config, entries: Mutex::new(HashMap::new()), generation: AtomicU64::new(0) }
+    }
+
+    /// If this request is cacheable, the generation to pass back to `store`
+    pub(super) fn cacheable(&self, method: &Method, path: &str) -> Option<u64> {
+        let cacheable = self.config.enabled
+            && *method == Method::GET
+            && self.config.paths.iter().any(|prefix| matches_prefix(path, prefix));
+        cacheable.then(|| self.generation.load(Ordering::SeqCst))
+    }
+
+    pub(super) fn lookup(&self, path: &str) -> Option<Response> {
+        let ttl = Duration::from_millis(self.config.ttl_ms);
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get(path)?;
+        if entry.stored.elapsed() > ttl {
+            entries.remove(path);
+            return None;
+        }
+
+        let mut response = Response::new(Body::from(entry.body.clone()));
+        *response.status_mut() = entry.status;
+        *response.headers_mut() = entry.headers.clone();
+        response.headers_mut().insert("x-voicebox-cache", HeaderValue::from_static("hit"));
+        Some(response)
+    }
+
+    /// Only successful, reasonably small JSON responses are worth keeping
+    pub(super) fn is_storable(status: StatusCode, headers: &HeaderMap) -> bool {
+        let is_json = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        let small = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .is_none_or(|len| len <= MAX_CACHED_BODY);
+        status.is_success() && is_json && small
+    }
+
+    pub(super) fn store(&self, path: &str, generation: u64, status: StatusCode, headers: &HeaderMap, body: Bytes) {
+        let mut entries = self.entries.lock().unwrap();
+        // Checked under the lock so a concurrent invalidate can't slip in between
+        if self.generation.load(Ordering::SeqCst) != generation || body.len() as u64 > MAX_CACHED_BODY {
+            return;
+        }
+        entries.retain(|_, e| e.stored.elapsed() <= Duration::from_millis(self.config.ttl_ms));
+        entries.insert(
+            path.to_string(),
+            Entry { status, headers: headers.clone(), body, stored: Instant::now() },
+        );
+    }
+
+    /// Drop everything. Backend resources reference each other (profiles, channels,
+    /// models), so a write anywhere may change what any cached read would return.
+    pub(super) fn invalidate(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers
+    }
+
+    #[test]
+    fn serves_hits_until_a_write_invalidates() {
+        let cache = ResponseCache::new(CacheConfig::default());
+        assert!(cache.cacheable(&Method::GET, "/history").is_none());
+        assert!(cache.cacheable(&Method::POST, "/profiles").is_none());
+        assert!(cache.cacheable(&Method::GET, "/profilesx").is_none());
+
+        let generation = cache.cacheable(&Method::GET, "/profiles/abc").unwrap();
+        cache.store("/profiles/abc", generation, StatusCode::OK, &json_headers(), Bytes::from("{}"));
+        let hit = cache.lookup("/profiles/abc").unwrap();
+        assert_eq!(hit.headers()["x-voicebox-cache"], "hit");
+
+        cache.invalidate();
+        assert!(cache.lookup("/profiles/abc").is_none());
+
+        // A read that started before the write must not repopulate the cache
+        cache.store("/profiles/abc", generation, StatusCode::OK, &json_headers(), Bytes::from("{}"));
+        assert!(cache.lookup("/profiles/abc").is_none());
+    }
+
+    #[test]
+    fn expires_after_ttl() {
+        let cache = ResponseCache::new(CacheConfig { ttl_ms: 0, ..Default::default() });
+        let generation = cache.cacheable(&Method::GET, "/channels").unwrap();
+        cache.store("/channels", generation, StatusCode::OK, &json_headers(), Bytes::from("[]"));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.lookup("/channels").is_none());
+    }
+
+    #[test]
+    fn only_stores_small_json_successes() {
+        assert!(ResponseCache::is_storable(StatusCode::OK, &json_headers()));
+        assert!(!ResponseCache::is_storable(StatusCode::NOT_FOUND, &json_headers()));
+        assert!(!ResponseCache::is_storable(StatusCode::OK, &HeaderMap::new()));
+
+        let mut large = json_headers();
+        large.insert(header::CONTENT_LENGTH, HeaderValue::from_static("5000000"));
+        assert!(!ResponseCache::is_storable(StatusCode::OK, &large));
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+
+/// Responses smaller than this aren't worth compressing
+const MIN_COMPRESSED_SIZE: u16 = 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// gzip/deflate responses to clients that accept it
+    pub enabled: bool,
+    /// Also ask the backend for compressed responses. Only helps if the backend is
+    /// running with compression middleware; costs CPU on both sides otherwise.
+    pub upstream: bool,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self { enabled: true, upstream: false }
+    }
+}
+
+/// Compression for text and JSON responses. Audio and images are already compressed
+/// (or large enough that streaming them unchanged matters more) and are left alone.
+pub(super) fn layer(config: &CompressionConfig) -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(MIN_COMPRESSED_SIZE)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::const_new("audio/"))
+        .and(NotForContentType::const_new("video/"))
+        .and(NotForContentType::const_new("application/zip"))
+        .and(NotForContentType::const_new("application/octet-stream"));
+    CompressionLayer::new()
+        .gzip(config.enabled)
+        .deflate(config.enabled)
+        .compress_when(predicate)
+}
@@ -0,0 +1,91 @@
+use axum::http::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+/// Origins the packaged Tauri webview uses on each platform
+const APP_ORIGINS: &[&str] = &["tauri://localhost", "http://tauri.localhost", "https://tauri.localhost"];
+
+/// Sent with every API response; nothing the backend returns should be rendered as a page
+const API_CSP: &str = "default-src 'none'; frame-ancestors 'none'";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Accept any http://localhost, 127.0.0.1 or [::1] origin on any port. Set by
+    /// `--dev` for the Vite dev server; off in packaged builds.
+    pub allow_any_localhost: bool,
+    /// Additional origins allowed to call the API, e.g. "http://localhost:3000" for a
+    /// local tool embedding it
+    pub extra_origins: Vec<String>,
+}
+
+fn is_localhost_origin(origin: &str) -> bool {
+    let Some(rest) = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")) else {
+        return false;
+    };
+    let host = match rest.strip_prefix("[::1]") {
+        Some(port) => return port.is_empty() || port.starts_with(':'),
+        None => rest.split(':').next().unwrap_or(rest),
+    };
+    host == "localhost" || host == "127.0.0.1"
+}
+
+impl CorsConfig {
+    pub fn allows(&self, origin: &str) -> bool {
+        APP_ORIGINS.contains(&origin)
+            || self.extra_origins.iter().any(|o| o.trim_end_matches('/') == origin)
+            || (self.allow_any_localhost && is_localhost_origin(origin))
+    }
+}
+
+/// CORS for the API, replacing whatever the backend sends. Preflights are answered by
+/// the proxy without reaching the backend.
+pub(super) fn layer(config: &CorsConfig) -> CorsLayer {
+    let config = config.clone();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+            origin.to_str().is_ok_and(|o| config.allows(o))
+        }))
+        .allow_methods(AllowMethods::mirror_request())
+        .allow_headers(AllowHeaders::mirror_request())
+        .allow_credentials(true)
+}
+
+/// Drop the backend's CORS headers so only the proxy's policy applies, and mark the
+/// response as not for rendering
+pub(super) fn apply_response_policy(headers: &mut HeaderMap) {
+    let upstream_cors: Vec<_> = headers
+        .keys()
+        .filter(|name| name.as_str().starts_with("access-control-"))
+        .cloned()
+        .collect();
+    for name in upstream_cors {
+        headers.remove(name);
+    }
+    headers.insert("content-security-policy", HeaderValue::from_static(API_CSP));
+    headers.insert("x-content-type-options", HeaderValue::from_static("nosniff"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packaged_builds_only_allow_the_app_and_extras() {
+        let config = CorsConfig { extra_origins: vec!["http://localhost:3000/".to_string()], ..Default::default() };
+        assert!(config.allows("tauri://localhost"));
+        assert!(config.allows("http://localhost:3000"));
+        assert!(!config.allows("http://localhost:5173"));
+        assert!(!config.allows("https://example.com"));
+    }
+
+    #[test]
+    fn dev_mode_allows_any_local_port() {
+        let config = CorsConfig { allow_any_localhost: true, ..Default::default() };
+        assert!(config.allows("http://localhost:5173"));
+        assert!(config.allows("http://127.0.0.1:8080"));
+        assert!(config.allows("http://[::1]:5173"));
+        assert!(!config.allows("http://localhost.evil.com"));
+        assert!(!config.allows("http://192.168.1.5:5173"));
+    }
+}
@@ -0,0 +1,238 @@
+// Endpoints for paired companion devices: `/pair` to pair, and the `/remote`
+// WebSocket through which a paired phone or tablet works as a soundboard remote.
+//
+// The remote channel is deliberately narrow. A device can speak text with one of the
+// configured presets, stop what it started and ask for status; everything else on the
+// API stays out of its reach.
+use super::{generate, ProxyState};
+use crate::launcher::log::log;
+use crate::launcher::pairing::{PairRequest, PairedDevice};
+use crate::text::filter::source;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    /// Accept remote control connections from paired devices
+    pub enabled: bool,
+    /// Commands each device may send per minute; 0 means unlimited
+    pub commands_per_minute: u32,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self { enabled: false, commands_per_minute: 30 }
+    }
+}
+
+/// Commands per device in the current one-minute window
+pub(super) struct DeviceLimiter {
+    per_minute: u32,
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl DeviceLimiter {
+    pub(super) fn new(per_minute: u32) -> Self {
+        Self { per_minute, windows: Mutex::new(HashMap::new()) }
+    }
+
+    /// Count a command from `device`, or refuse it when the device used up its minute
+    pub(super) fn allow(&self, device: &str) -> bool {
+        if self.per_minute == 0 {
+            return true;
+        }
+        let mut windows = self.windows.lock().unwrap();
+        let now = Instant::now();
+        let window = windows.entry(device.to_string()).or_insert((now, 0));
+        if now.duration_since(window.0) >= Duration::from_secs(60) {
+            *window = (now, 0);
+        }
+        window.1 += 1;
+        window.1 <= self.per_minute
+    }
+}
+
+/// What a remote may ask for
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(super) enum RemoteCommand {
+    Speak { preset: String, text: String },
+    /// Abandon the generations this device started that haven't finished
+    Stop,
+    Status,
+}
+
+pub(super) fn routes(router: Router<Arc<ProxyState>>) -> Router<Arc<ProxyState>> {
+    router.route("/pair", post(pair)).route("/remote", get(remote))
+}
+
+/// Trade the one-time token from the QR code for a device token
+async fn pair(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Json(request): Json<PairRequest>,
+) -> Response {
+    if let Some(rejected) = state.limiter.check_rate(client.ip()) {
+        return rejected;
+    }
+    match state.pairing.complete(&request) {
+        Ok(paired) => {
+            log(&format!("Launcher: Paired device {} ({}) from {}", paired.device_id, request.name.trim(), client.ip()));
+            Json(paired).into_response()
+        }
+        Err(e) => {
+            log(&format!("Proxy: Rejected pairing from {}: {}", client.ip(), e));
+            (StatusCode::FORBIDDEN, e).into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RemoteQuery {
+    token: Option<String>,
+}
+
+/// The device token from `Authorization: Bearer` or, for WebSocket clients that can't
+/// set headers, the `token` query parameter
+fn device_token(headers: &HeaderMap, query: RemoteQuery) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string);
+    bearer.or(query.token)
+}
+
+async fn remote(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(query): Query<RemoteQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let Some(device) = device_token(&headers, query).and_then(|token| state.pairing.authenticate(&token)) else {
+        log(&format!("Proxy: Rejected remote connection from {}, not paired", client.ip()));
+        return (StatusCode::UNAUTHORIZED, "Unknown device").into_response();
+    };
+    log(&format!("Launcher: Remote {} ({}) connected from {}", device.id, device.name, client.ip()));
+    upgrade.on_upgrade(move |socket| serve_remote(state, client, device, socket))
+}
+
+async fn serve_remote(state: Arc<ProxyState>, client: SocketAddr, device: PairedDevice, mut socket: WebSocket) {
+    let (finished, mut results) = mpsc::unbounded_channel::<Value>();
+    let mut speaking: Vec<JoinHandle<()>> = Vec::new();
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    speaking.retain(|task| !task.is_finished());
+                    if !state.device_limiter.allow(&device.id) {
+                        json!({ "type": "error", "message": "Too many commands, slow down" })
+                    } else {
+                        match serde_json::from_str::<RemoteCommand>(&text) {
+                            Ok(command) => run_command(&state, client, command, &mut speaking, &finished),
+                            Err(e) => json!({ "type": "error", "message": e.to_string() }),
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => continue,
+            },
+            Some(result) = results.recv() => result,
+        };
+        if socket.send(Message::Text(reply.to_string().into())).await.is_err() {
+            break;
+        }
+    }
+    for task in speaking {
+        task.abort();
+    }
+    log(&format!("Launcher: Remote {} disconnected", device.id));
+}
+
+fn run_command(
+    state: &Arc<ProxyState>,
+    client: SocketAddr,
+    command: RemoteCommand,
+    speaking: &mut Vec<JoinHandle<()>>,
+    finished: &mpsc::UnboundedSender<Value>,
+) -> Value {
+    match command {
+        RemoteCommand::Speak { preset, text } => {
+            let request = match state.presets.get(&preset).map(|p| p.resolve(&preset)) {
+                Some(Ok(resolved)) => match state.filter.apply(source::REMOTE, Some(resolved.language()), &text) {
+                    Ok(text) => resolved.generate_request(&text),
+                    Err(e) => return json!({ "type": "error", "message": e }),
+                },
+                Some(Err(e)) => return json!({ "type": "error", "message": e }),
+                None => return json!({ "type": "error", "message": format!("Unknown preset '{}'", preset) }),
+            };
+            let reply = json!({ "type": "speaking", "preset": preset });
+            let (state, finished) = (state.clone(), finished.clone());
+            speaking.push(tokio::spawn(async move {
+                let upstream = state.client.post(format!("{}/generate", state.upstream)).json(&request);
+                let result = match generate(&state, client, "/generate", upstream).await {
+                    Ok(response) => match response.json::<Value>().await {
+                        Ok(generation) => json!({ "type": "spoken", "preset": preset, "generation_id": generation["id"] }),
+                        Err(e) => json!({ "type": "error", "message": e.to_string() }),
+                    },
+                    Err(rejected) => json!({ "type": "error", "message": format!("Backend answered {}", rejected.status()) }),
+                };
+                let _ = finished.send(result);
+            }));
+            reply
+        }
+        RemoteCommand::Stop => {
+            let cancelled = speaking.len();
+            for task in speaking.drain(..) {
+                task.abort();
+            }
+            json!({ "type": "stopped", "cancelled": cancelled })
+        }
+        RemoteCommand::Status => {
+            let presets: Vec<&String> = state.presets.keys().collect();
+            json!({ "type": "status", "speaking": speaking.len(), "presets": presets })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_commands_per_device() {
+        let limiter = DeviceLimiter::new(2);
+        assert!(limiter.allow("phone"));
+        assert!(limiter.allow("phone"));
+        assert!(!limiter.allow("phone"));
+        assert!(limiter.allow("tablet"));
+        assert!(DeviceLimiter::new(0).allow("phone"));
+    }
+
+    #[test]
+    fn accepts_only_the_remote_commands() {
+        let speak: RemoteCommand = serde_json::from_str(r#"{"type":"speak","preset":"narrator","text":"Hi"}"#).unwrap();
+        assert!(matches!(speak, RemoteCommand::Speak { preset, .. } if preset == "narrator"));
+        assert!(matches!(serde_json::from_str(r#"{"type":"stop"}"#).unwrap(), RemoteCommand::Stop));
+        assert!(serde_json::from_str::<RemoteCommand>(r#"{"type":"delete_profile","id":"anna"}"#).is_err());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer abc".parse().unwrap());
+        assert_eq!(device_token(&headers, RemoteQuery { token: Some("xyz".to_string()) }).as_deref(), Some("abc"));
+        assert_eq!(device_token(&HeaderMap::new(), RemoteQuery { token: Some("xyz".to_string()) }).as_deref(), Some("xyz"));
+    }
+}
@@ -0,0 +1,143 @@
+use super::{generate, ProxyState};
+use crate::text::filter::source;
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Form, Query, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Speech endpoints in the shape Home Assistant and similar platforms already speak:
+/// MaryTTS' `/process` (Home Assistant's `marytts` platform) and a plain `/api/tts`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HomeAssistantConfig {
+    pub enabled: bool,
+    /// Voice profile ID used when a request names no voice
+    pub default_voice: Option<String>,
+    /// Voice names configured in Home Assistant, mapped to voice profile IDs. Names
+    /// that aren't mapped are taken as profile IDs.
+    pub voices: BTreeMap<String, String>,
+}
+
+/// `de` for `de_DE`, `de-AT` or `DE`
+pub(super) fn language_of(locale: &str) -> String {
+    locale.split(['_', '-']).next().unwrap_or(locale).to_lowercase()
+}
+
+impl HomeAssistantConfig {
+    /// Body for the backend's `/tts`, which returns the WAV without keeping a history entry
+    pub(super) fn tts_request(&self, text: &str, voice: Option<&str>, locale: Option<&str>) -> Result<Value, String> {
+        if text.trim().is_empty() {
+            return Err("No text to speak".to_string());
+        }
+        let voice = voice
+            .filter(|voice| !voice.is_empty())
+            .map(|voice| self.voices.get(voice).map(String::as_str).unwrap_or(voice))
+            .or(self.default_voice.as_deref())
+            .ok_or("No voice given and no default_voice configured")?;
+        let mut request = json!({ "text": text, "profile_id": voice });
+        if let Some(locale) = locale.filter(|locale| !locale.is_empty()) {
+            request["language"] = json!(language_of(locale));
+        }
+        Ok(request)
+    }
+}
+
+pub(super) fn routes(router: Router<Arc<ProxyState>>) -> Router<Arc<ProxyState>> {
+    router.route("/process", get(process_query).post(process_form)).route("/api/tts", post(api_tts))
+}
+
+async fn process_query(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    marytts(&state, client, params).await
+}
+
+async fn process_form(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Form(params): Form<HashMap<String, String>>,
+) -> Response {
+    marytts(&state, client, params).await
+}
+
+/// MaryTTS parameters: `INPUT_TEXT`, `VOICE` and `LOCALE`. The output type parameters
+/// are ignored since the backend always returns WAV, which is what they ask for.
+async fn marytts(state: &ProxyState, client: SocketAddr, params: HashMap<String, String>) -> Response {
+    let text = params.get("INPUT_TEXT").map(String::as_str).unwrap_or_default();
+    let request = state.config.home_assistant.tts_request(
+        text,
+        params.get("VOICE").map(String::as_str),
+        params.get("LOCALE").map(String::as_str),
+    );
+    synthesize(state, client, request).await
+}
+
+#[derive(Deserialize)]
+struct ApiTtsRequest {
+    text: String,
+    voice: Option<String>,
+    language: Option<String>,
+}
+
+async fn api_tts(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Json(body): Json<ApiTtsRequest>,
+) -> Response {
+    let request = state.config.home_assistant.tts_request(&body.text, body.voice.as_deref(), body.language.as_deref());
+    synthesize(&state, client, request).await
+}
+
+/// Run `request` on the backend's `/tts`, its text passed through the content filter
+async fn synthesize(state: &ProxyState, client: SocketAddr, request: Result<Value, String>) -> Response {
+    let filtered = request.and_then(|mut request| {
+        let language = request["language"].as_str().map(str::to_string);
+        let text = request["text"].as_str().unwrap_or_default();
+        request["text"] = json!(state.filter.apply(source::HOME_ASSISTANT, language.as_deref(), text)?);
+        Ok(request)
+    });
+    let request = match filtered {
+        Ok(request) => request,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let upstream = state.client.post(format!("{}/tts", state.upstream)).json(&request);
+    match generate(state, client, "/tts", upstream).await {
+        Ok(upstream) => {
+            let mut response = Response::new(Body::from_stream(upstream.bytes_stream()));
+            response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("audio/wav"));
+            response
+        }
+        Err(rejected) => rejected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_voices_and_locales_to_backend_requests() {
+        let config = HomeAssistantConfig {
+            enabled: true,
+            default_voice: Some("narrator".to_string()),
+            voices: BTreeMap::from([("cmu-slt-hsmm".to_string(), "anna".to_string())]),
+        };
+        let request = config.tts_request("Licht an", Some("cmu-slt-hsmm"), Some("de_DE")).unwrap();
+        assert_eq!(request, json!({ "text": "Licht an", "profile_id": "anna", "language": "de" }));
+        assert_eq!(config.tts_request("Hi", Some("p-123"), None).unwrap()["profile_id"], "p-123");
+        assert_eq!(config.tts_request("Hi", Some(""), None).unwrap()["profile_id"], "narrator");
+        assert!(config.tts_request(" ", None, None).is_err());
+        assert!(HomeAssistantConfig::default().tts_request("Hi", None, None).is_err());
+        assert_eq!(language_of("en-GB"), "en");
+        assert_eq!(language_of("FR"), "fr");
+    }
+}
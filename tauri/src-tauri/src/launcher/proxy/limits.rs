@@ -0,0 +1,289 @@
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use futures_util::{Stream, StreamExt};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Endpoints that run a model and tie up the backend for seconds at a time
+const GENERATION_PATHS: &[&str] = &["/generate", "/tts", "/transcribe"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Requests per minute allowed per client address; 0 disables rate limiting
+    pub requests_per_minute: u32,
+    /// Short bursts allowed above the steady rate
+    pub burst: u32,
+    /// Generations the backend works on at once; 0 means unlimited
+    pub max_concurrent_generations: usize,
+    /// Generations that may wait for a free slot before new ones get 429
+    pub max_queued_generations: usize,
+    /// Largest JSON or form body accepted, in MB
+    pub max_body_mb: u64,
+    /// Largest audio or multipart upload accepted, in MB. Uploads are streamed, so
+    /// this bounds disk use on the backend rather than launcher memory.
+    pub max_upload_mb: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 0,
+            burst: 20,
+            max_concurrent_generations: 0,
+            max_queued_generations: 8,
+            max_body_mb: 16,
+            max_upload_mb: 4096,
+        }
+    }
+}
+
+/// Token bucket refilled continuously at `rate_per_sec`
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+pub(super) struct Limiter {
+    config: LimitsConfig,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+    generations: std::sync::Arc<Semaphore>,
+    queued: AtomicUsize,
+}
+
+fn too_many_requests(retry_after: Duration, msg: &str) -> Response {
+    let secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [("retry-after", secs.to_string())],
+        msg.to_string(),
+    )
+        .into_response()
+}
+
+fn payload_too_large(limit: u64) -> Response {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!("Request body exceeds the {} MB limit", limit / (1024 * 1024)),
+    )
+        .into_response()
+}
+
+/// Error raised mid-stream when a body without Content-Length runs past its limit
+#[derive(Debug)]
+pub(super) struct BodyTooLarge(pub u64);
+
+impl std::fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request body exceeds {} bytes", self.0)
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+/// Whether an upstream send failed because `limited_stream` cut the body off
+pub(super) fn is_body_too_large(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(e) = source {
+        if e.is::<BodyTooLarge>() {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// Pass `body` through chunk by chunk, failing once more than `limit` bytes have gone by
+pub(super) fn limited_stream(
+    body: Body,
+    limit: u64,
+) -> impl Stream<Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> {
+    let mut seen: u64 = 0;
+    body.into_data_stream().map(move |chunk| {
+        let chunk = chunk?;
+        seen += chunk.len() as u64;
+        if seen > limit {
+            return Err(BodyTooLarge(limit).into());
+        }
+        Ok(chunk)
+    })
+}
+
+impl Limiter {
+    pub(super) fn new(config: LimitsConfig) -> Self {
+        let permits = match config.max_concurrent_generations {
+            0 => Semaphore::MAX_PERMITS,
+            n => n,
+        };
+        Self {
+            generations: std::sync::Arc::new(Semaphore::new(permits)),
+            buckets: Mutex::new(HashMap::new()),
+            queued: AtomicUsize::new(0),
+            config,
+        }
+    }
+
+    /// Take a token for `client`, or say how long until one is available
+    fn take_token(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.config.requests_per_minute == 0 {
+            return Ok(());
+        }
+        let rate_per_sec = f64::from(self.config.requests_per_minute) / 60.0;
+        let capacity = f64::from(self.config.burst.max(1));
+
+        let mut buckets = self.buckets.lock().unwrap();
+        // Forget idle clients so the map doesn't grow without bound
+        if buckets.len() > 1024 {
+            buckets.retain(|_, b| now.duration_since(b.updated) < Duration::from_secs(600));
+        }
+        let bucket = buckets.entry(client).or_insert(Bucket { tokens: capacity, updated: now });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate_per_sec).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate_per_sec))
+        }
+    }
+
+    pub(super) fn check_rate(&self, client: IpAddr) -> Option<Response> {
+        self.take_token(client, Instant::now())
+            .err()
+            .map(|wait| too_many_requests(wait, "Rate limit exceeded"))
+    }
+
+    /// Byte limit for a request body, chosen by its content type
+    pub(super) fn body_limit(&self, headers: &HeaderMap) -> u64 {
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        let is_upload = content_type.starts_with("multipart/")
+            || content_type.starts_with("audio/")
+            || content_type.starts_with("application/octet-stream")
+            || content_type.starts_with("application/zip");
+        let mb = if is_upload { self.config.max_upload_mb } else { self.config.max_body_mb };
+        mb.saturating_mul(1024 * 1024)
+    }
+
+    /// Reject bodies whose declared length is over the limit before reading any of it
+    pub(super) fn check_declared_length(&self, headers: &HeaderMap) -> Option<Response> {
+        let limit = self.body_limit(headers);
+        headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|len| *len > limit)
+            .map(|_| payload_too_large(limit))
+    }
+
+    /// Wait for a generation slot, queueing up to `max_queued_generations` requests.
+    /// Non-generation requests pass straight through.
+    pub(super) async fn generation_slot(
+        &self,
+        method: &Method,
+        path: &str,
+    ) -> Result<Option<OwnedSemaphorePermit>, Response> {
+        let path = path.split('?').next().unwrap_or(path);
+        if self.config.max_concurrent_generations == 0
+            || method != Method::POST
+            || !GENERATION_PATHS.contains(&path)
+        {
+            return Ok(None);
+        }
+
+        if let Ok(permit) = self.generations.clone().try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.config.max_queued_generations {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(too_many_requests(
+                Duration::from_secs(5),
+                "Too many generations in progress, try again shortly",
+            ));
+        }
+        let permit = self.generations.clone().acquire_owned().await;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        permit.map(Some).map_err(|_| StatusCode::SERVICE_UNAVAILABLE.into_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn bucket_allows_burst_then_refills() {
+        let limiter = Limiter::new(LimitsConfig {
+            requests_per_minute: 60,
+            burst: 3,
+            ..Default::default()
+        });
+        let client: IpAddr = "127.0.0.1".parse().unwrap();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.take_token(client, start).is_ok());
+        }
+        let wait = limiter.take_token(client, start).unwrap_err();
+        assert!(wait <= Duration::from_secs(1));
+
+        assert!(limiter.take_token(client, start + Duration::from_secs(1)).is_ok());
+        let other: IpAddr = "192.168.1.20".parse().unwrap();
+        assert!(limiter.take_token(other, start).is_ok());
+    }
+
+    #[tokio::test]
+    async fn generations_queue_then_reject() {
+        let limiter = Limiter::new(LimitsConfig {
+            max_concurrent_generations: 1,
+            max_queued_generations: 0,
+            ..Default::default()
+        });
+
+        let held = limiter.generation_slot(&Method::POST, "/generate").await.unwrap();
+        assert!(held.is_some());
+        let rejected = limiter.generation_slot(&Method::POST, "/generate").await.unwrap_err();
+        assert_eq!(rejected.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limiter.generation_slot(&Method::GET, "/history").await.unwrap().is_none());
+
+        drop(held);
+        assert!(limiter.generation_slot(&Method::POST, "/generate").await.unwrap().is_some());
+    }
+
+    #[test]
+    fn body_limits_depend_on_content_type() {
+        let limiter = Limiter::new(LimitsConfig { max_body_mb: 1, max_upload_mb: 10, ..Default::default() });
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("2097152"));
+        let rejected = limiter.check_declared_length(&headers).unwrap();
+        assert_eq!(rejected.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=x"),
+        );
+        assert!(limiter.check_declared_length(&headers).is_none());
+    }
+
+    #[tokio::test]
+    async fn limited_stream_stops_past_the_limit() {
+        let chunks = limited_stream(Body::from(vec![0u8; 100]), 10).collect::<Vec<_>>().await;
+        assert!(chunks.last().unwrap().is_err());
+
+        let chunks = limited_stream(Body::from(vec![0u8; 10]), 10).collect::<Vec<_>>().await;
+        assert!(chunks.iter().all(|c| c.is_ok()));
+    }
+}
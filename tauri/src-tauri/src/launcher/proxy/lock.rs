@@ -0,0 +1,32 @@
+use super::cache::is_mutating;
+use axum::http::{Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+
+/// Endpoints that take a POST or PUT without changing the project: shutting down,
+/// changing the log level, transcribing uploaded audio and loading models into memory
+const ALLOWED_WHILE_LOCKED: &[&str] = &["/shutdown", "/admin/log-level", "/transcribe", "/prompt-enhancer/", "/models/load", "/models/unload"];
+
+/// 423 for a request that would change a locked project
+pub(super) fn reject(method: &Method, path: &str) -> Option<Response> {
+    let path = path.split('?').next().unwrap_or(path);
+    if !is_mutating(method) || ALLOWED_WHILE_LOCKED.iter().any(|allowed| path.starts_with(allowed)) {
+        return None;
+    }
+    let body = serde_json::json!({ "detail": "This project is locked for review; unlock it to make changes" });
+    Some((StatusCode::LOCKED, axum::Json(body)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_only_requests_that_change_the_project() {
+        assert!(reject(&Method::GET, "/history?limit=10").is_none());
+        assert!(reject(&Method::POST, "/transcribe").is_none());
+        assert!(reject(&Method::POST, "/models/load?model_size=1.7B").is_none());
+        assert_eq!(reject(&Method::POST, "/generate").unwrap().status(), StatusCode::LOCKED);
+        assert!(reject(&Method::DELETE, "/history/abc").is_some());
+        assert!(reject(&Method::PUT, "/stories/s1").is_some());
+    }
+}
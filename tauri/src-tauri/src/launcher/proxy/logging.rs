@@ -0,0 +1,168 @@
+use crate::launcher::log::log;
+use crate::launcher::proxy::limits::limited_stream;
+use crate::launcher::proxy::ProxyConfig;
+use axum::body::{Body, Bytes};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Bodies larger than this are streamed through untouched and never dumped
+const MAX_CAPTURED_BODY: usize = 64 * 1024;
+/// Long strings (base64 audio, transcripts) are cut down in dumps
+const MAX_DUMPED_STRING: usize = 256;
+
+const SENSITIVE_KEYS: &[&str] = &["password", "token", "secret", "authorization", "api_key", "apikey", "cookie"];
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "cookie", "x-api-key", "proxy-authorization"];
+
+/// What of a request body is kept around for slow-request dumps
+pub(super) enum LoggedBody {
+    Json(Bytes),
+    Empty,
+    NotCaptured,
+}
+
+impl LoggedBody {
+    /// The full body if it was buffered, so the request can be sent again
+    pub(super) fn replayable(&self) -> Option<Bytes> {
+        match self {
+            LoggedBody::Json(bytes) => Some(bytes.clone()),
+            LoggedBody::Empty => Some(Bytes::new()),
+            LoggedBody::NotCaptured => None,
+        }
+    }
+}
+
+/// Turn a request body into the upstream body, buffering small JSON bodies so they can
+/// be dumped if the request turns out slow. Everything else is streamed through, cut
+/// off once it passes `limit` bytes.
+pub(super) async fn split_request_body(
+    headers: &HeaderMap,
+    body: Body,
+    limit: u64,
+) -> Result<(reqwest::Body, LoggedBody), axum::Error> {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    let small = length.is_some_and(|len| len <= MAX_CAPTURED_BODY);
+
+    // No length and not chunked (typical GETs) means there is no body at all
+    if length == Some(0) || (length.is_none() && !headers.contains_key(header::TRANSFER_ENCODING)) {
+        return Ok((reqwest::Body::from(Bytes::new()), LoggedBody::Empty));
+    }
+
+    if is_json && small {
+        let bytes = axum::body::to_bytes(body, MAX_CAPTURED_BODY).await?;
+        return Ok((reqwest::Body::from(bytes.clone()), LoggedBody::Json(bytes)));
+    }
+    Ok((reqwest::Body::wrap_stream(limited_stream(body, limit)), LoggedBody::NotCaptured))
+}
+
+/// Mask credentials and shorten bulky strings in a JSON document
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_KEYS.iter().any(|s| key.contains(s)) {
+                    *value = Value::String("[REDACTED]".to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        Value::String(s) if s.chars().count() > MAX_DUMPED_STRING => {
+            let total = s.chars().count();
+            *s = format!("{}… ({} chars)", s.chars().take(MAX_DUMPED_STRING).collect::<String>(), total);
+        }
+        _ => {}
+    }
+}
+
+fn redacted_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                "[REDACTED]"
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(super) fn record(
+    config: &ProxyConfig,
+    parts: &Parts,
+    path: &str,
+    status: StatusCode,
+    latency: Duration,
+    body: &LoggedBody,
+) {
+    if config.log_requests {
+        log(&format!(
+            "Proxy: {} {} -> {} in {}ms",
+            parts.method,
+            path,
+            status.as_u16(),
+            latency.as_millis()
+        ));
+    }
+
+    if latency < Duration::from_millis(config.slow_request_ms) {
+        return;
+    }
+
+    let dumped_body = match body {
+        LoggedBody::Json(bytes) => match serde_json::from_slice::<Value>(bytes) {
+            Ok(mut json) => {
+                redact_json(&mut json);
+                json.to_string()
+            }
+            Err(_) => format!("<{} bytes, not JSON>", bytes.len()),
+        },
+        LoggedBody::Empty => "<empty>".to_string(),
+        LoggedBody::NotCaptured => "<streamed, not captured>".to_string(),
+    };
+    log(&format!(
+        "Proxy: SLOW REQUEST {} {} took {}ms (threshold {}ms)\n  headers: {}\n  body: {}",
+        parts.method,
+        path,
+        latency.as_millis(),
+        config.slow_request_ms,
+        redacted_headers(&parts.headers),
+        dumped_body
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_credentials_and_truncates_blobs() {
+        let mut body = json!({
+            "profile_id": "abc",
+            "api_key": "sk-123",
+            "nested": { "Auth_Token": "t", "items": [{ "password": "p" }] },
+            "audio": "A".repeat(1000),
+        });
+        redact_json(&mut body);
+
+        assert_eq!(body["profile_id"], "abc");
+        assert_eq!(body["api_key"], "[REDACTED]");
+        assert_eq!(body["nested"]["Auth_Token"], "[REDACTED]");
+        assert_eq!(body["nested"]["items"][0]["password"], "[REDACTED]");
+        assert!(body["audio"].as_str().unwrap().ends_with("(1000 chars)"));
+    }
+}
@@ -0,0 +1,385 @@
+// Reverse proxy in front of the Python backend.
+//
+// When enabled, the launcher listens on the public port and forwards to the backend on
+// a loopback-only port, which gives the Rust side one place to observe and shape API
+// traffic.
+mod cache;
+mod compression;
+mod cors;
+mod devices;
+mod hass;
+mod limits;
+mod lock;
+mod logging;
+mod openai;
+mod restart;
+
+use crate::launcher::log::log;
+use crate::launcher::pairing::Pairing;
+use crate::launcher::presets::Preset;
+use crate::text::filter::ContentFilter;
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderMap, HeaderName, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+pub use cache::CacheConfig;
+pub use compression::CompressionConfig;
+pub use cors::CorsConfig;
+pub use devices::RemoteConfig;
+pub use hass::HomeAssistantConfig;
+pub use limits::LimitsConfig;
+pub use logging::redact_json;
+pub use openai::OpenAiConfig;
+pub use restart::RestartConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub enabled: bool,
+    /// Loopback port the Python backend listens on behind the proxy
+    pub backend_port: u16,
+    /// Log method, path, status and latency of every request
+    pub log_requests: bool,
+    /// Requests slower than this get their (redacted) bodies dumped to the log
+    pub slow_request_ms: u64,
+    pub limits: LimitsConfig,
+    pub cache: CacheConfig,
+    pub compression: CompressionConfig,
+    pub cors: CorsConfig,
+    pub restart: RestartConfig,
+    /// Refuse requests that change data, for a project that is locked for review
+    pub read_only: bool,
+    /// Home Assistant compatible speech endpoints
+    pub home_assistant: HomeAssistantConfig,
+    /// OpenAI compatible `/v1/audio` endpoints
+    pub openai: OpenAiConfig,
+    /// WebSocket remote control for paired devices
+    pub remote: RemoteConfig,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend_port: crate::launcher::DEFAULT_PORT + 1,
+            log_requests: true,
+            slow_request_ms: 5000,
+            limits: LimitsConfig::default(),
+            cache: CacheConfig::default(),
+            compression: CompressionConfig::default(),
+            cors: CorsConfig::default(),
+            restart: RestartConfig::default(),
+            read_only: false,
+            home_assistant: HomeAssistantConfig::default(),
+            openai: OpenAiConfig::default(),
+            remote: RemoteConfig::default(),
+        }
+    }
+}
+
+struct ProxyState {
+    config: ProxyConfig,
+    limiter: limits::Limiter,
+    cache: cache::ResponseCache,
+    client: reqwest::Client,
+    upstream: String,
+    pairing: Pairing,
+    device_limiter: devices::DeviceLimiter,
+    /// Presets paired remotes may speak with
+    presets: BTreeMap<String, Preset>,
+    /// Applied to text from paired remotes and Home Assistant before it is spoken
+    filter: ContentFilter,
+}
+
+impl ProxyState {
+    fn new(config: ProxyConfig, state_dir: &Path, presets: BTreeMap<String, Preset>, filter: ContentFilter) -> Result<Arc<Self>, String> {
+        Ok(Arc::new(Self {
+            upstream: format!("http://127.0.0.1:{}", config.backend_port),
+            // Redirects from the backend go back to the client as they are
+            client: reqwest::Client::builder()
+                .gzip(config.compression.upstream)
+                .deflate(config.compression.upstream)
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .map_err(|e| format!("Failed to create proxy client: {}", e))?,
+            limiter: limits::Limiter::new(config.limits.clone()),
+            cache: cache::ResponseCache::new(config.cache.clone()),
+            pairing: Pairing::new(state_dir),
+            device_limiter: devices::DeviceLimiter::new(config.remote.commands_per_minute),
+            presets,
+            filter,
+            config,
+        }))
+    }
+}
+
+/// Headers that describe a single hop and must not be forwarded
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+    // The proxy negotiates encoding with each side itself
+    "accept-encoding",
+];
+
+fn forwardable_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut out = headers.clone();
+    for name in HOP_BY_HOP {
+        out.remove(HeaderName::from_static(name));
+    }
+    out
+}
+
+/// Bind `listen` and serve the proxy on a background thread. Binding happens before
+/// returning so a busy port is reported to the caller. Paired devices are kept in
+/// `state_dir`; `presets` are those paired remotes can speak with, and `filter` is
+/// applied to the text they and Home Assistant send.
+pub fn spawn(
+    config: ProxyConfig,
+    listen: SocketAddr,
+    state_dir: &Path,
+    presets: BTreeMap<String, Preset>,
+    filter: ContentFilter,
+) -> Result<(), String> {
+    let listener = std::net::TcpListener::bind(listen)
+        .map_err(|e| format!("Failed to bind proxy on {}: {}", listen, e))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure proxy socket: {}", e))?;
+
+    let state = ProxyState::new(config, state_dir, presets, filter)?;
+    log(&format!("Launcher: Proxy listening on {} -> {}", listen, state.upstream));
+
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                log(&format!("Launcher: Failed to start proxy runtime: {}", e));
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    log(&format!("Launcher: Failed to register proxy socket: {}", e));
+                    return;
+                }
+            };
+            let app = router(state).into_make_service_with_connect_info::<SocketAddr>();
+            if let Err(e) = axum::serve(listener, app).await {
+                log(&format!("Launcher: Proxy stopped: {}", e));
+            }
+        });
+    });
+    Ok(())
+}
+
+fn router(state: Arc<ProxyState>) -> Router {
+    let compression = compression::layer(&state.config.compression);
+    let cors = cors::layer(&state.config.cors);
+    let mut router = Router::new();
+    if state.config.remote.enabled {
+        router = devices::routes(router);
+    }
+    if state.config.home_assistant.enabled {
+        router = hass::routes(router);
+    }
+    if state.config.openai.enabled {
+        router = openai::routes(router, &state.config.limits);
+    }
+    router.fallback(forward).layer(compression).layer(cors).with_state(state)
+}
+
+/// Send a request of a compatibility endpoint to the backend's `path`, under the same
+/// rate limit and generation queue as calling `path` directly. Backend errors are
+/// passed on with their status.
+async fn generate(
+    state: &ProxyState,
+    client: SocketAddr,
+    path: &str,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, Response> {
+    if let Some(rejected) = state.limiter.check_rate(client.ip()) {
+        log(&format!("Proxy: Rate limited POST {} from {}", path, client.ip()));
+        return Err(rejected);
+    }
+    // Held until the upstream response headers arrive
+    let _generation_slot = match state.limiter.generation_slot(&Method::POST, path).await {
+        Ok(slot) => slot,
+        Err(rejected) => {
+            log(&format!("Proxy: Rejected POST {}, generation queue is full", path));
+            return Err(rejected);
+        }
+    };
+    match request.send().await {
+        Ok(upstream) if upstream.status().is_success() => Ok(upstream),
+        Ok(upstream) => {
+            let status = upstream.status();
+            let detail = upstream.text().await.unwrap_or_default();
+            log(&format!("Proxy: POST {} failed with {}: {}", path, status, detail));
+            Err((status, detail).into_response())
+        }
+        Err(e) => {
+            log(&format!("Launcher: Proxy upstream error for POST {}: {}", path, e));
+            Err((StatusCode::BAD_GATEWAY, format!("Backend unavailable: {}", e)).into_response())
+        }
+    }
+}
+
+async fn forward(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    req: Request,
+) -> Response {
+    let started = Instant::now();
+    let (parts, body) = req.into_parts();
+    let path = parts
+        .uri
+        .path_and_query()
+        .map(|pq| pq.as_str().to_string())
+        .unwrap_or_else(|| "/".to_string());
+
+    if let Some(rejected) = state.limiter.check_rate(client.ip()) {
+        log(&format!("Proxy: Rate limited {} {} from {}", parts.method, path, client.ip()));
+        return rejected;
+    }
+
+    if state.config.read_only {
+        if let Some(rejected) = lock::reject(&parts.method, &path) {
+            log(&format!("Proxy: Rejected {} {}, the project is locked", parts.method, path));
+            return rejected;
+        }
+    }
+
+    let cache_generation = state.cache.cacheable(&parts.method, &path);
+    if cache_generation.is_some() {
+        if let Some(hit) = state.cache.lookup(&path) {
+            logging::record(&state.config, &parts, &path, hit.status(), started.elapsed(), &logging::LoggedBody::NotCaptured);
+            return hit;
+        }
+    }
+
+    // Held until the upstream response headers arrive
+    let _generation_slot = match state.limiter.generation_slot(&parts.method, &path).await {
+        Ok(slot) => slot,
+        Err(rejected) => {
+            log(&format!("Proxy: Rejected {} {}, generation queue is full", parts.method, path));
+            return rejected;
+        }
+    };
+
+    if let Some(rejected) = state.limiter.check_declared_length(&parts.headers) {
+        log(&format!("Proxy: Rejected {} {}, body too large", parts.method, path));
+        return rejected;
+    }
+    let body_limit = state.limiter.body_limit(&parts.headers);
+    let (upstream_body, logged_body) = match logging::split_request_body(&parts.headers, body, body_limit).await {
+        Ok(split) => split,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Failed to read request body: {}", e)).into_response(),
+    };
+
+    let send = |body: reqwest::Body| {
+        state
+            .client
+            .request(parts.method.clone(), format!("{}{}", state.upstream, path))
+            .headers(forwardable_headers(&parts.headers))
+            .body(body)
+            .send()
+    };
+    let mut upstream = send(upstream_body).await;
+
+    // A refused connection usually means the backend is restarting; hold requests
+    // that are safe to repeat until it is back
+    let restart = &state.config.restart;
+    if let (Err(e), Some(body)) = (&upstream, logged_body.replayable()) {
+        if e.is_connect() && restart.retry_during_restart && restart::is_idempotent(&parts.method) {
+            log(&format!("Proxy: Backend unreachable, holding {} {} until it is healthy", parts.method, path));
+            if restart::wait_for_backend(&state.client, &state.upstream, restart).await {
+                upstream = send(reqwest::Body::from(body)).await;
+            }
+        }
+    }
+
+    let response = match upstream {
+        Ok(upstream) => {
+            let status = upstream.status();
+            let mut headers = forwardable_headers(upstream.headers());
+            cors::apply_response_policy(&mut headers);
+            let body = match cache_generation {
+                Some(generation) if cache::ResponseCache::is_storable(status, &headers) => {
+                    match upstream.bytes().await {
+                        Ok(bytes) => {
+                            state.cache.store(&path, generation, status, &headers, bytes.clone());
+                            Body::from(bytes)
+                        }
+                        Err(e) => {
+                            log(&format!("Launcher: Proxy failed reading {} {}: {}", parts.method, path, e));
+                            return (StatusCode::BAD_GATEWAY, format!("Backend response failed: {}", e)).into_response();
+                        }
+                    }
+                }
+                _ => Body::from_stream(upstream.bytes_stream()),
+            };
+            let mut response = Response::new(body);
+            *response.status_mut() = status;
+            *response.headers_mut() = headers;
+            response
+        }
+        Err(e) if limits::is_body_too_large(&e) => {
+            log(&format!("Proxy: Rejected {} {}, body too large", parts.method, path));
+            (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+        }
+        Err(e) => {
+            log(&format!("Launcher: Proxy upstream error for {} {}: {}", parts.method, path, e));
+            (StatusCode::BAD_GATEWAY, format!("Backend unavailable: {}", e)).into_response()
+        }
+    };
+
+    if cache::is_mutating(&parts.method) {
+        state.cache.invalidate();
+    }
+    logging::record(&state.config, &parts, &path, response.status(), started.elapsed(), &logged_body);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::Redirect;
+    use axum::routing::post;
+
+    #[tokio::test]
+    async fn forward_passes_backend_redirects_through() {
+        let backend = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let backend_port = backend.local_addr().unwrap().port();
+        let routes = Router::new().route("/profiles", post(|| async { Redirect::temporary("/profiles/") }));
+        tokio::spawn(async move { axum::serve(backend, routes).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = ProxyConfig { backend_port, log_requests: false, ..ProxyConfig::default() };
+        let state = ProxyState::new(config, dir.path(), BTreeMap::new(), ContentFilter::default()).unwrap();
+        let proxy = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = proxy.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(proxy, router(state).into_make_service_with_connect_info::<SocketAddr>()).await });
+
+        let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+        let response = client.post(format!("http://{}/profiles", address)).body("{}").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(response.headers()["location"], "/profiles/");
+    }
+}
@@ -0,0 +1,201 @@
+use super::limits::LimitsConfig;
+use super::{generate, ProxyState};
+use axum::body::Body;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Multipart, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// `/v1/audio/speech` and `/v1/audio/transcriptions` in OpenAI's request and response
+/// format, so clients written for that API only need a different base URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAiConfig {
+    pub enabled: bool,
+    /// Voice profile ID used for voices that aren't mapped, instead of taking the name
+    /// as a profile ID
+    pub default_voice: Option<String>,
+    /// OpenAI voice names (`alloy`, `nova`, ...) mapped to voice profile IDs
+    pub voices: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct SpeechRequest {
+    input: String,
+    voice: String,
+    /// Only `wav` can be served; OpenAI's default of `mp3` is answered with WAV too,
+    /// since clients that don't ask for a format play whatever comes back
+    response_format: Option<String>,
+}
+
+/// Error body in OpenAI's format, which client libraries turn into their exceptions
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    let body = json!({
+        "error": { "message": message.into(), "type": "invalid_request_error", "param": null, "code": null }
+    });
+    (status, Json(body)).into_response()
+}
+
+impl OpenAiConfig {
+    /// Body for the backend's `/tts`
+    pub(super) fn tts_request(&self, request: &SpeechRequest) -> Result<Value, String> {
+        if request.input.trim().is_empty() {
+            return Err("input must not be empty".to_string());
+        }
+        if let Some(format) = request.response_format.as_deref().filter(|f| !matches!(*f, "wav" | "mp3")) {
+            return Err(format!("response_format {:?} is not supported, use \"wav\"", format));
+        }
+        let profile = match self.voices.get(&request.voice) {
+            Some(profile) => profile,
+            None => self.default_voice.as_ref().unwrap_or(&request.voice),
+        };
+        Ok(json!({ "text": request.input, "profile_id": profile }))
+    }
+}
+
+/// A transcription in the `response_format` the client asked for
+pub(super) fn transcription_response(transcription: &Value, format: &str, language: Option<&str>) -> Result<Response, String> {
+    let text = transcription["text"].as_str().unwrap_or_default();
+    Ok(match format {
+        "json" => Json(json!({ "text": text })).into_response(),
+        "text" => text.to_string().into_response(),
+        "verbose_json" => Json(json!({
+            "task": "transcribe",
+            "language": language,
+            "duration": transcription["duration"],
+            "text": text,
+        }))
+        .into_response(),
+        format => return Err(format!("response_format {:?} is not supported, use json, text or verbose_json", format)),
+    })
+}
+
+pub(super) fn routes(router: Router<Arc<ProxyState>>, limits: &LimitsConfig) -> Router<Arc<ProxyState>> {
+    let upload_limit = limits.max_upload_mb.saturating_mul(1024 * 1024) as usize;
+    router.route("/v1/audio/speech", post(speech)).route(
+        "/v1/audio/transcriptions",
+        post(transcriptions).layer(DefaultBodyLimit::max(upload_limit)),
+    )
+}
+
+async fn speech(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Json(request): Json<SpeechRequest>,
+) -> Response {
+    let request = match state.config.openai.tts_request(&request) {
+        Ok(request) => request,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+    let upstream = state.client.post(format!("{}/tts", state.upstream)).json(&request);
+    match generate(&state, client, "/tts", upstream).await {
+        Ok(upstream) => {
+            let mut response = Response::new(Body::from_stream(upstream.bytes_stream()));
+            response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("audio/wav"));
+            response
+        }
+        Err(rejected) => rejected,
+    }
+}
+
+/// Multipart fields OpenAI clients send; `model` and `prompt` are accepted and ignored
+#[derive(Default)]
+struct TranscriptionForm {
+    file: Option<(String, Vec<u8>)>,
+    language: Option<String>,
+    response_format: Option<String>,
+}
+
+async fn read_form(mut multipart: Multipart) -> Result<TranscriptionForm, String> {
+    let mut form = TranscriptionForm::default();
+    while let Some(field) = multipart.next_field().await.map_err(|e| e.to_string())? {
+        match field.name().unwrap_or_default() {
+            "file" => {
+                let name = field.file_name().unwrap_or("audio.wav").to_string();
+                form.file = Some((name, field.bytes().await.map_err(|e| e.to_string())?.to_vec()));
+            }
+            "language" => form.language = Some(field.text().await.map_err(|e| e.to_string())?),
+            "response_format" => form.response_format = Some(field.text().await.map_err(|e| e.to_string())?),
+            _ => {}
+        }
+    }
+    Ok(form)
+}
+
+async fn transcriptions(
+    State(state): State<Arc<ProxyState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    multipart: Multipart,
+) -> Response {
+    let form = match read_form(multipart).await {
+        Ok(form) => form,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+    let Some((file_name, audio)) = form.file else {
+        return error(StatusCode::BAD_REQUEST, "file is required");
+    };
+    let format = form.response_format.unwrap_or_else(|| "json".to_string());
+    // Check the format before the transcription runs rather than after
+    if let Err(e) = transcription_response(&Value::Null, &format, None) {
+        return error(StatusCode::BAD_REQUEST, e);
+    }
+
+    let mut upload = reqwest::multipart::Form::new().part("file", reqwest::multipart::Part::bytes(audio).file_name(file_name));
+    if let Some(language) = &form.language {
+        upload = upload.text("language", language.clone());
+    }
+    let upstream = state.client.post(format!("{}/transcribe", state.upstream)).multipart(upload);
+    let transcription: Value = match generate(&state, client, "/transcribe", upstream).await {
+        Ok(upstream) => match upstream.json().await {
+            Ok(transcription) => transcription,
+            Err(e) => return error(StatusCode::BAD_GATEWAY, format!("Invalid transcription from the backend: {}", e)),
+        },
+        Err(rejected) => return rejected,
+    };
+    match transcription_response(&transcription, &format, form.language.as_deref()) {
+        Ok(response) => response,
+        Err(e) => error(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speech(input: &str, voice: &str, format: Option<&str>) -> SpeechRequest {
+        SpeechRequest { input: input.to_string(), voice: voice.to_string(), response_format: format.map(str::to_string) }
+    }
+
+    #[test]
+    fn translates_speech_requests() {
+        let mut config = OpenAiConfig {
+            enabled: true,
+            default_voice: None,
+            voices: BTreeMap::from([("alloy".to_string(), "anna".to_string())]),
+        };
+        let request = config.tts_request(&speech("Hello", "alloy", Some("wav"))).unwrap();
+        assert_eq!(request, json!({ "text": "Hello", "profile_id": "anna" }));
+        assert_eq!(config.tts_request(&speech("Hello", "p-123", None)).unwrap()["profile_id"], "p-123");
+        config.default_voice = Some("narrator".to_string());
+        assert_eq!(config.tts_request(&speech("Hello", "nova", Some("mp3"))).unwrap()["profile_id"], "narrator");
+        assert!(config.tts_request(&speech("Hello", "alloy", Some("opus"))).is_err());
+        assert!(config.tts_request(&speech(" ", "alloy", None)).is_err());
+    }
+
+    #[test]
+    fn shapes_transcriptions_by_response_format() {
+        let transcription = json!({ "text": "Hallo Welt", "duration": 1.5 });
+        let json = transcription_response(&transcription, "json", None).unwrap();
+        assert_eq!(json.headers()[header::CONTENT_TYPE], "application/json");
+        let text = transcription_response(&transcription, "text", None).unwrap();
+        assert_eq!(text.headers()[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+        assert!(transcription_response(&transcription, "verbose_json", Some("de")).is_ok());
+        assert!(transcription_response(&transcription, "srt", None).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use axum::http::Method;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartConfig {
+    /// Hold idempotent requests while the backend is unreachable and retry them once
+    /// it is healthy again, instead of failing them with 502
+    pub retry_during_restart: bool,
+    /// Longest a request is held waiting for the backend to come back
+    pub max_wait_ms: u64,
+    /// How often /health is polled while waiting
+    pub poll_interval_ms: u64,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self { retry_during_restart: true, max_wait_ms: 15_000, poll_interval_ms: 250 }
+    }
+}
+
+/// Requests that can be sent twice without changing the outcome. Generations are
+/// POSTs and are never retried.
+pub(super) fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE)
+}
+
+/// Poll the backend's /health until it answers successfully or `max_wait_ms` passes
+pub(super) async fn wait_for_backend(client: &reqwest::Client, upstream: &str, config: &RestartConfig) -> bool {
+    let deadline = Instant::now() + Duration::from_millis(config.max_wait_ms);
+    let health = format!("{}/health", upstream);
+    loop {
+        let healthy = client
+            .get(&health)
+            .timeout(HEALTH_TIMEOUT)
+            .send()
+            .await
+            .is_ok_and(|r| r.status().is_success());
+        if healthy {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn gives_up_when_the_backend_stays_down() {
+        // Bind and drop a listener to get a port nothing is listening on
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let config = RestartConfig { max_wait_ms: 300, poll_interval_ms: 50, ..Default::default() };
+        let started = Instant::now();
+        let upstream = format!("http://127.0.0.1:{}", port);
+        assert!(!wait_for_backend(&reqwest::Client::new(), &upstream, &config).await);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn only_idempotent_methods_are_retried() {
+        assert!(is_idempotent(&Method::GET));
+        assert!(is_idempotent(&Method::DELETE));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::PATCH));
+    }
+}
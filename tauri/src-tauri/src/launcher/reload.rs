@@ -0,0 +1,146 @@
+// Applying config changes while the app runs.
+//
+// Most sections are read where they are used: presets and soundboard hotkeys when a clip
+// plays, the content filter when a line is spoken. Editing them takes effect right away.
+// Others configure the backend process or what it was started with and only apply once
+// it restarts. The watcher notices when the config file is written and tells which
+// sections changed and which of those wait for a restart.
+use crate::launcher::config::LauncherConfig;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Config sections the running backend doesn't pick up
+pub const RESTART_SECTIONS: &[&str] = &[
+    "proxy",
+    "on_backend_exit",
+    "restart",
+    "backend_log",
+    "require_voice_consent",
+    "workspace",
+    "conda_env",
+    "managed_venv",
+    "installer",
+    "package_index",
+    "preflight_timeout_secs",
+    "setup_models",
+];
+
+/// Sections that differ between two configs, by their key in the file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigChange {
+    pub changed: Vec<String>,
+    /// The changed sections that take effect when the backend is restarted
+    pub restart_required: Vec<String>,
+}
+
+impl ConfigChange {
+    pub fn between(old: &LauncherConfig, new: &LauncherConfig) -> Self {
+        let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+            (serde_json::to_value(old), serde_json::to_value(new))
+        else {
+            return Self::default();
+        };
+        let changed: Vec<String> = old
+            .keys()
+            .chain(new.keys().filter(|key| !old.contains_key(*key)))
+            .filter(|key| old.get(*key) != new.get(*key))
+            .cloned()
+            .collect();
+        let restart_required = changed.iter().filter(|key| RESTART_SECTIONS.contains(&key.as_str())).cloned().collect();
+        Self { changed, restart_required }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+}
+
+/// Follows the config file by its modification time
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    config: LauncherConfig,
+}
+
+impl ConfigWatcher {
+    /// Start from the config at `path` as it is now; an unreadable one counts as empty
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified(path),
+            config: LauncherConfig::load(path).unwrap_or_default(),
+        }
+    }
+
+    pub fn config(&self) -> &LauncherConfig {
+        &self.config
+    }
+
+    /// Reload the config if the file was written since the last call. `None` when it
+    /// wasn't or nothing in it changed; `Err` when it no longer parses, in which case
+    /// the last good config is kept.
+    pub fn poll(&mut self) -> Option<Result<ConfigChange, String>> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let config = match LauncherConfig::load(&self.path) {
+            Ok(config) => config,
+            Err(e) => return Some(Err(e)),
+        };
+        let change = ConfigChange::between(&self.config, &config);
+        self.config = config;
+        (!change.is_empty()).then_some(Ok(change))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn write(path: &Path, contents: &str, at_secs: u64) {
+        std::fs::write(path, contents).unwrap();
+        // Set the time explicitly, as quick writes can share one on coarse file systems
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(at_secs)).unwrap();
+    }
+
+    #[test]
+    fn flags_sections_that_need_a_restart() {
+        let old = LauncherConfig::default();
+        let new: LauncherConfig = serde_json::from_str(r#"{"managed_venv": true, "log_level": "debug", "content_filter": {"wordlists": {"en": ["heck"]}}}"#).unwrap();
+        let change = ConfigChange::between(&old, &new);
+        assert_eq!(change.changed, ["content_filter", "log_level", "managed_venv"]);
+        assert_eq!(change.restart_required, ["managed_venv"]);
+        assert!(ConfigChange::between(&new, &new.clone()).is_empty());
+    }
+
+    #[test]
+    fn reports_changes_when_the_file_is_written() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+        let mut watcher = ConfigWatcher::new(&path);
+        assert!(watcher.poll().is_none());
+
+        write(&path, r#"{"soundboard": {"hi": {"text": "Hi", "preset": "narrator"}}}"#, 100);
+        let change = watcher.poll().unwrap().unwrap();
+        assert_eq!(change.changed, ["soundboard"]);
+        assert!(change.restart_required.is_empty());
+        assert!(watcher.poll().is_none());
+
+        // Rewritten unchanged
+        write(&path, r#"{"soundboard": {"hi": {"text": "Hi", "preset": "narrator"}}}"#, 200);
+        assert!(watcher.poll().is_none());
+
+        write(&path, r#"{"soundboard": "#, 300);
+        assert!(watcher.poll().unwrap().is_err());
+        assert!(watcher.config().soundboard.contains_key("hi"));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::launcher::log::log;
+use std::io;
+use std::time::Duration;
+
+/// Attempts made before a transient error is reported
+pub const TRANSIENT_ATTEMPTS: u32 = 5;
+const INITIAL_DELAY: Duration = Duration::from_millis(100);
+
+/// Errors on-access antivirus scanners cause for a few hundred milliseconds after a
+/// file is created or first executed
+pub fn is_transient_io_error(e: &io::Error) -> bool {
+    #[cfg(windows)]
+    {
+        const ERROR_ACCESS_DENIED: i32 = 5;
+        const ERROR_SHARING_VIOLATION: i32 = 32;
+        const ERROR_LOCK_VIOLATION: i32 = 33;
+        if matches!(
+            e.raw_os_error(),
+            Some(ERROR_ACCESS_DENIED | ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+        ) {
+            return true;
+        }
+    }
+    #[cfg(unix)]
+    {
+        // "Text file busy" while a freshly written executable is still open for scanning
+        const ETXTBSY: i32 = 26;
+        if e.raw_os_error() == Some(ETXTBSY) {
+            return true;
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    let _ = e;
+    false
+}
+
+/// Run `op`, retrying transient failures with a short exponential backoff.
+/// Retries are logged under `what` so support can see when AV interference happened.
+pub fn with_io_retry<T>(what: &str, mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut delay = INITIAL_DELAY;
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Ok(value) => {
+                if attempt > 1 {
+                    log(&format!("Launcher: {} succeeded after {} attempts", what, attempt));
+                }
+                return Ok(value);
+            }
+            Err(e) if attempt < TRANSIENT_ATTEMPTS && is_transient_io_error(&e) => {
+                log(&format!(
+                    "Launcher: {} failed ({}), retrying in {}ms (attempt {}/{})",
+                    what,
+                    e,
+                    delay.as_millis(),
+                    attempt,
+                    TRANSIENT_ATTEMPTS
+                ));
+                std::thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn a_plain_permission_error_is_not_transient() {
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "read-only install");
+        assert!(!is_transient_io_error(&denied));
+        #[cfg(unix)]
+        {
+            assert!(!is_transient_io_error(&io::Error::from_raw_os_error(13)));
+            assert!(is_transient_io_error(&io::Error::from_raw_os_error(26)));
+        }
+        #[cfg(windows)]
+        for code in [5, 32, 33] {
+            assert!(is_transient_io_error(&io::Error::from_raw_os_error(code)));
+        }
+    }
+
+    #[test]
+    fn retries_only_transient_errors() {
+        let attempts = Cell::new(0);
+        let result: io::Result<()> = with_io_retry("Writing", || {
+            attempts.set(attempts.get() + 1);
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied"))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+
+        #[cfg(unix)]
+        {
+            attempts.set(0);
+            let result = with_io_retry("Running", || {
+                attempts.set(attempts.get() + 1);
+                match attempts.get() {
+                    1 => Err(io::Error::from_raw_os_error(26)),
+                    n => Ok(n),
+                }
+            });
+            assert_eq!(result.unwrap(), 2);
+        }
+    }
+}
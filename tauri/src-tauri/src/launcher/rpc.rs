@@ -0,0 +1,168 @@
+use crate::launcher::operations::{Operations, SpeakParams, TranscribeParams};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+/// Invalid JSON was received
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON is not a valid request object
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The backend refused or failed the operation
+pub const OPERATION_FAILED: i64 = -32000;
+
+/// Methods served by `voicebox-server rpc`
+pub const METHODS: &[&str] = &["speak", "transcribe", "list_voices", "job_status"];
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// `params` as the type a method takes
+pub fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobStatusParams {
+    id: String,
+}
+
+/// Run `method` against `ops`
+pub fn dispatch(ops: &dyn Operations, method: &str, params: Value) -> Result<Value, RpcError> {
+    let failed = |e: String| RpcError::new(OPERATION_FAILED, e);
+    match method {
+        "speak" => ops.speak(&parse_params::<SpeakParams>(params)?).map_err(failed),
+        "transcribe" => ops.transcribe(&parse_params::<TranscribeParams>(params)?).map_err(failed),
+        "list_voices" => ops.list_voices().map_err(failed),
+        "job_status" => ops.job_status(&parse_params::<JobStatusParams>(params)?.id).map_err(failed),
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method {:?} (available: {})", method, METHODS.join(", ")))),
+    }
+}
+
+/// Answers a method call with its result
+pub type Handler<'a> = dyn Fn(&str, Value) -> Result<Value, RpcError> + 'a;
+
+/// The response to one line of input, or `None` for a notification
+pub fn handle_line(handler: &Handler, line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(response(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())))),
+    };
+    let id = message.get("id").cloned().unwrap_or(Value::Null);
+    let request: Request = match serde_json::from_value(message) {
+        Ok(request) => request,
+        Err(e) => return Some(response(id, Err(RpcError::new(INVALID_REQUEST, e.to_string())))),
+    };
+    if request.jsonrpc != "2.0" {
+        return Some(response(id, Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))));
+    }
+    let result = handler(&request.method, request.params);
+    request.id.map(|id| response(id, result))
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+/// Serve JSON-RPC 2.0 requests, one JSON object per line, until `input` ends. Each
+/// response is written as one line and flushed right away, so editor plugins can
+/// read it as soon as it is ready.
+pub fn serve(handler: &Handler, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(handler, &line) {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeOps;
+
+    impl Operations for FakeOps {
+        fn speak(&self, params: &SpeakParams) -> Result<Value, String> {
+            Ok(json!({ "id": "gen-1", "text": params.text }))
+        }
+
+        fn transcribe(&self, _params: &TranscribeParams) -> Result<Value, String> {
+            Err("Request to http://127.0.0.1:17493/transcribe failed: connection refused".to_string())
+        }
+
+        fn list_voices(&self) -> Result<Value, String> {
+            Ok(json!([{ "id": "anna", "name": "Anna" }]))
+        }
+
+        fn job_status(&self, id: &str) -> Result<Value, String> {
+            Ok(json!({ "id": id, "complete": true }))
+        }
+    }
+
+    fn handler(method: &str, params: Value) -> Result<Value, RpcError> {
+        dispatch(&FakeOps, method, params)
+    }
+
+    fn call(line: &str) -> Value {
+        handle_line(&handler, line).unwrap()
+    }
+
+    #[test]
+    fn answers_requests_and_skips_notifications() {
+        let reply = call(r#"{"jsonrpc":"2.0","id":7,"method":"speak","params":{"text":"Hi","voice":"anna"}}"#);
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["result"]["text"], "Hi");
+        assert_eq!(call(r#"{"jsonrpc":"2.0","id":"a","method":"list_voices"}"#)["result"][0]["id"], "anna");
+        assert!(handle_line(&handler, r#"{"jsonrpc":"2.0","method":"list_voices"}"#).is_none());
+
+        let mut output = Vec::new();
+        let input = "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"job_status\",\"params\":{\"id\":\"j\"}}\n\n{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"list_voices\"}\n";
+        serve(&handler, input.as_bytes(), &mut output).unwrap();
+        let lines: Vec<Value> = String::from_utf8(output).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["result"]["id"], "j");
+    }
+
+    #[test]
+    fn reports_protocol_and_operation_errors() {
+        assert_eq!(call("{not json")["error"]["code"], PARSE_ERROR);
+        assert_eq!(call(r#"{"jsonrpc":"1.0","id":1,"method":"speak"}"#)["error"]["code"], INVALID_REQUEST);
+        assert_eq!(call(r#"{"jsonrpc":"2.0","id":1,"method":"sing"}"#)["error"]["code"], METHOD_NOT_FOUND);
+        let reply = call(r#"{"jsonrpc":"2.0","id":1,"method":"speak","params":{"txt":"Hi"}}"#);
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+        let reply = call(r#"{"jsonrpc":"2.0","id":1,"method":"transcribe","params":{"path":"a.wav"}}"#);
+        assert_eq!(reply["error"]["code"], OPERATION_FAILED);
+        assert!(reply["error"]["message"].as_str().unwrap().contains("connection refused"));
+    }
+}
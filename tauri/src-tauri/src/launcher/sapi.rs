@@ -0,0 +1,171 @@
+// Windows SAPI 5 voice registration.
+//
+// SAPI lists the voices it finds as tokens under `TOKENS_KEY`. Each Voicebox profile
+// gets a token whose CLSID is the engine shim, a small ISpTTSEngine DLL that posts the
+// text to the launcher's `/tts` and hands the WAV back to SAPI. The token's
+// `VoiceboxProfile` and `VoiceboxUrl` values tell the shim which profile to speak with
+// and where. Tokens live in HKCU and are written with reg.exe, so registering voices
+// needs no elevation; only installing the shim itself does.
+use crate::launcher::process::{CommandSpec, ProcessRunner};
+
+pub const TOKENS_KEY: &str = r"HKCU\SOFTWARE\Microsoft\Speech\Voices\Tokens";
+/// Prefix of the token names, marking the tokens `unregister` may remove
+pub const TOKEN_PREFIX: &str = "Voicebox_";
+/// Class ID of the engine shim, as registered by `regsvr32 voicebox_sapi.dll`
+pub const SHIM_CLSID: &str = "{5C3E7F2A-9B1D-4E6A-8C2F-3A7D1B9E4F60}";
+pub const SHIM_DLL: &str = "voicebox_sapi.dll";
+
+/// A voice profile as SAPI should list it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SapiVoice {
+    pub profile_id: String,
+    pub name: String,
+    pub language: String,
+}
+
+/// Windows language ID SAPI filters voices by, as the hex string tokens store
+pub fn language_id(language: &str) -> Option<&'static str> {
+    Some(match language.split(['_', '-']).next()?.to_lowercase().as_str() {
+        "en" => "409",
+        "de" => "407",
+        "fr" => "40C",
+        "es" => "C0A",
+        "it" => "410",
+        "pt" => "416",
+        "nl" => "413",
+        "pl" => "415",
+        "ru" => "419",
+        "ja" => "411",
+        "ko" => "412",
+        "zh" => "804",
+        _ => return None,
+    })
+}
+
+/// Registry key of the token for `profile_id`. Characters a key name can't hold are
+/// replaced, so IDs stay recognizable.
+pub fn token_key(profile_id: &str) -> String {
+    let name: String = profile_id.chars().map(|c| if c == '\\' || c.is_control() { '_' } else { c }).collect();
+    format!(r"{}\{}{}", TOKENS_KEY, TOKEN_PREFIX, name)
+}
+
+fn reg_add(key: &str, value: Option<&str>, data: &str) -> CommandSpec {
+    let spec = CommandSpec::new("reg").args(["add", key]);
+    let spec = match value {
+        Some(value) => spec.args(["/v", value]),
+        None => spec.arg("/ve"),
+    };
+    spec.args(["/t", "REG_SZ", "/d", data, "/f"])
+}
+
+/// reg.exe calls writing the token of `voice`, speaking through the API at `url`
+pub fn register_commands(voice: &SapiVoice, url: &str) -> Vec<CommandSpec> {
+    let key = token_key(&voice.profile_id);
+    let display = format!("Voicebox {}", voice.name);
+    let attributes = format!(r"{}\Attributes", key);
+    let mut commands = vec![
+        reg_add(&key, None, &display),
+        reg_add(&key, Some("CLSID"), SHIM_CLSID),
+        reg_add(&key, Some("VoiceboxProfile"), &voice.profile_id),
+        reg_add(&key, Some("VoiceboxUrl"), url),
+        reg_add(&attributes, Some("Name"), &display),
+        reg_add(&attributes, Some("Vendor"), "Voicebox"),
+        reg_add(&attributes, Some("Age"), "Adult"),
+    ];
+    if let Some(language) = language_id(&voice.language) {
+        commands.push(reg_add(&attributes, Some("Language"), language));
+    }
+    commands
+}
+
+fn run(runner: &dyn ProcessRunner, spec: &CommandSpec) -> Result<String, String> {
+    let output = runner.output(spec).map_err(|e| format!("Failed to run reg: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("reg failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether the engine shim is installed; tokens pointing at a missing class make
+/// SAPI clients fail when the voice is picked
+pub fn shim_installed(runner: &dyn ProcessRunner) -> bool {
+    let key = format!(r"HKCR\CLSID\{}\InprocServer32", SHIM_CLSID);
+    run(runner, &CommandSpec::new("reg").args(["query", key.as_str()])).is_ok()
+}
+
+/// Keys of the Voicebox tokens currently registered
+pub fn registered(runner: &dyn ProcessRunner) -> Result<Vec<String>, String> {
+    let listing = match run(runner, &CommandSpec::new("reg").args(["query", TOKENS_KEY])) {
+        Ok(listing) => listing,
+        // The key doesn't exist until some voice is registered per user
+        Err(_) => return Ok(Vec::new()),
+    };
+    let prefix = format!(r"{}\{}", TOKENS_KEY, TOKEN_PREFIX).to_lowercase();
+    Ok(listing
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.to_lowercase().starts_with(&prefix))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Remove all Voicebox tokens, returning how many there were
+pub fn unregister(runner: &dyn ProcessRunner) -> Result<usize, String> {
+    let tokens = registered(runner)?;
+    for key in &tokens {
+        run(runner, &CommandSpec::new("reg").args(["delete", key.as_str(), "/f"]))?;
+    }
+    Ok(tokens.len())
+}
+
+/// Replace the Voicebox tokens with one per voice in `voices`
+pub fn register(runner: &dyn ProcessRunner, voices: &[SapiVoice], url: &str) -> Result<(), String> {
+    if !shim_installed(runner) {
+        return Err(format!("The Voicebox SAPI engine is not installed; register it with `regsvr32 {}` first", SHIM_DLL));
+    }
+    unregister(runner)?;
+    for voice in voices {
+        for spec in register_commands(voice, url) {
+            run(runner, &spec)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    fn anna() -> SapiVoice {
+        SapiVoice { profile_id: "p-1".to_string(), name: "Anna".to_string(), language: "de".to_string() }
+    }
+
+    #[test]
+    fn writes_a_token_per_voice() {
+        let commands = register_commands(&anna(), "http://127.0.0.1:17493");
+        let args = |spec: &CommandSpec| spec.args.iter().map(|a| a.to_string_lossy().into_owned()).collect::<Vec<_>>();
+        assert_eq!(args(&commands[0]), ["add", &token_key("p-1"), "/ve", "/t", "REG_SZ", "/d", "Voicebox Anna", "/f"]);
+        assert!(commands.iter().any(|c| args(c).contains(&SHIM_CLSID.to_string())));
+        assert!(args(commands.last().unwrap()).contains(&"407".to_string()));
+        assert!(token_key(r"a\b").ends_with(r"\Voicebox_a_b"));
+        assert_eq!(language_id("pt_BR"), Some("416"));
+        assert_eq!(language_id("xx"), None);
+    }
+
+    #[test]
+    fn refuses_without_the_shim_and_replaces_old_tokens() {
+        let runner = FakeRunner::new();
+        runner.script("reg", Script::exits(1).stderr("ERROR: The system was unable to find the specified registry key"));
+        assert!(register(&runner, &[anna()], "http://127.0.0.1:17493").unwrap_err().contains("regsvr32"));
+
+        let runner = FakeRunner::new();
+        let listing = format!("\r\n{0}\\MSTTS_V110_enUS_DavidM\r\n{0}\\Voicebox_old\r\n", TOKENS_KEY);
+        runner.script("reg", Script::exits(0).stdout(&listing));
+        assert_eq!(registered(&runner).unwrap(), [format!(r"{}\Voicebox_old", TOKENS_KEY)]);
+        register(&runner, &[anna()], "http://127.0.0.1:17493").unwrap();
+        let deleted: Vec<_> = runner.calls().into_iter().filter(|c| c.args[0] == "delete").collect();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].args[1], format!(r"{}\Voicebox_old", TOKENS_KEY).as_str());
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+/// GPU memory a render is expected to need before anything was measured
+const DEFAULT_BASE_VRAM_MB: f64 = 300.0;
+const DEFAULT_VRAM_PER_CHAR_MB: f64 = 1.0;
+const DEFAULT_SECS_PER_CHAR: f64 = 0.05;
+/// Weight of each new measurement in the running estimate
+const LEARNING_RATE: f64 = 0.3;
+/// Each out-of-memory failure scales the per-character estimate by this much
+const OOM_GROWTH: f64 = 1.25;
+/// Out-of-memory failures of one item before it is reported as failed
+const MAX_OOM_RETRIES: u32 = 2;
+
+/// Expected cost of rendering one item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cost {
+    pub vram_mb: f64,
+    pub secs: f64,
+}
+
+/// Estimates an item's cost from its text length, refined with every item that
+/// finishes. Memory is modelled as a fixed part plus a part per character, since longer
+/// lines need longer attention windows and audio buffers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostModel {
+    pub base_vram_mb: f64,
+    pub vram_per_char_mb: f64,
+    pub secs_per_char: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            base_vram_mb: DEFAULT_BASE_VRAM_MB,
+            vram_per_char_mb: DEFAULT_VRAM_PER_CHAR_MB,
+            secs_per_char: DEFAULT_SECS_PER_CHAR,
+        }
+    }
+}
+
+impl CostModel {
+    pub fn estimate(&self, chars: usize) -> Cost {
+        let chars = chars.max(1) as f64;
+        Cost { vram_mb: self.base_vram_mb + self.vram_per_char_mb * chars, secs: self.secs_per_char * chars }
+    }
+
+    /// Take a finished item's measured time and, if the backend reported it, memory
+    pub fn observe(&mut self, chars: usize, secs: f64, vram_mb: Option<f64>) {
+        let chars = chars.max(1) as f64;
+        let blend = |current: f64, measured: f64| current + LEARNING_RATE * (measured - current);
+        self.secs_per_char = blend(self.secs_per_char, secs / chars);
+        if let Some(vram_mb) = vram_mb {
+            let per_char = ((vram_mb - self.base_vram_mb) / chars).max(0.0);
+            self.vram_per_char_mb = blend(self.vram_per_char_mb, per_char);
+        }
+    }
+}
+
+/// Whether a backend error is the GPU running out of memory
+pub fn is_out_of_memory(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("out of memory") || error.contains("outofmemory")
+}
+
+/// Decides which batch items run at the same time on a backend with several workers.
+///
+/// Items start longest first, so the long ones don't end up running alone at the end.
+/// An item only starts while the estimated memory of everything running fits the GPU's
+/// free memory; when the longest waiting item doesn't fit, the longest one that does is
+/// started instead. Something always runs, so an item bigger than the budget still gets
+/// its chance alone. An out-of-memory failure lowers the budget to what was running
+/// and puts the item back in the queue.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    workers: usize,
+    /// Free GPU memory in MB; `None` when the backend has no GPU or doesn't say, which
+    /// leaves only the worker count to limit concurrency
+    budget_mb: Option<f64>,
+    model: CostModel,
+    /// `(item, characters)`, longest first
+    queue: Vec<(usize, usize)>,
+    running: HashMap<usize, (usize, Cost)>,
+    oom_failures: HashMap<usize, u32>,
+}
+
+impl Scheduler {
+    pub fn new(items: Vec<(usize, usize)>, workers: usize, budget_mb: Option<f64>) -> Self {
+        let mut queue = items;
+        queue.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Self {
+            workers: workers.max(1),
+            budget_mb,
+            model: CostModel::default(),
+            queue,
+            running: HashMap::new(),
+            oom_failures: HashMap::new(),
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    pub fn budget_mb(&self) -> Option<f64> {
+        self.budget_mb
+    }
+
+    fn in_use_mb(&self) -> f64 {
+        self.running.values().map(|(_, cost)| cost.vram_mb).sum()
+    }
+
+    /// The item to start now, if one may
+    pub fn next_ready(&mut self) -> Option<usize> {
+        if self.running.len() >= self.workers || self.queue.is_empty() {
+            return None;
+        }
+        let position = match (self.budget_mb, self.running.is_empty()) {
+            (Some(budget), false) => {
+                let free = budget - self.in_use_mb();
+                self.queue.iter().position(|&(_, chars)| self.model.estimate(chars).vram_mb <= free)?
+            }
+            _ => 0,
+        };
+        let (item, chars) = self.queue.remove(position);
+        self.running.insert(item, (chars, self.model.estimate(chars)));
+        Some(item)
+    }
+
+    pub fn finish(&mut self, item: usize, secs: f64, vram_mb: Option<f64>) {
+        if let Some((chars, _)) = self.running.remove(&item) {
+            self.model.observe(chars, secs, vram_mb);
+        }
+    }
+
+    /// The item failed for some other reason; it isn't retried
+    pub fn abandon(&mut self, item: usize) {
+        self.running.remove(&item);
+    }
+
+    /// The item ran out of GPU memory. Returns whether it was queued again.
+    pub fn out_of_memory(&mut self, item: usize) -> bool {
+        let in_use = self.in_use_mb();
+        let Some((chars, _)) = self.running.remove(&item) else { return false };
+        if let Some(budget) = self.budget_mb.as_mut() {
+            *budget = budget.min(in_use * 0.9);
+        }
+        self.model.vram_per_char_mb *= OOM_GROWTH;
+        let failures = self.oom_failures.entry(item).or_default();
+        *failures += 1;
+        if *failures > MAX_OOM_RETRIES {
+            return false;
+        }
+        // Back to the front, to run once the others have made room
+        self.queue.insert(0, (item, chars));
+        true
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.queue.is_empty() && self.running.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_longest_first_within_the_memory_budget() {
+        // Default estimates: 1300, 700 and 400 MB
+        let mut scheduler = Scheduler::new(vec![(0, 100), (1, 1000), (2, 400)], 3, Some(1800.0));
+        assert_eq!(scheduler.next_ready(), Some(1));
+        // 700 MB would exceed the budget next to the 1300 MB item, 400 MB fits
+        assert_eq!(scheduler.next_ready(), Some(0));
+        assert_eq!(scheduler.next_ready(), None);
+        scheduler.finish(1, 20.0, Some(1300.0));
+        assert_eq!(scheduler.next_ready(), Some(2));
+        scheduler.finish(0, 2.0, None);
+        scheduler.finish(2, 8.0, None);
+        assert!(scheduler.is_done());
+
+        // Without a budget only the worker count limits; an oversized item still runs alone
+        let mut scheduler = Scheduler::new(vec![(0, 10), (1, 20)], 1, None);
+        assert_eq!(scheduler.next_ready(), Some(1));
+        assert_eq!(scheduler.next_ready(), None);
+        let mut scheduler = Scheduler::new(vec![(0, 10_000)], 2, Some(500.0));
+        assert_eq!(scheduler.next_ready(), Some(0));
+    }
+
+    #[test]
+    fn running_out_of_memory_shrinks_the_budget_and_retries() {
+        let mut scheduler = Scheduler::new(vec![(0, 200), (1, 200)], 2, Some(10_000.0));
+        assert_eq!((scheduler.next_ready(), scheduler.next_ready()), (Some(0), Some(1)));
+        assert!(scheduler.out_of_memory(1));
+        assert_eq!(scheduler.budget_mb(), Some(900.0));
+        // The retry waits until the other item is done
+        assert_eq!(scheduler.next_ready(), None);
+        scheduler.finish(0, 10.0, Some(500.0));
+        assert_eq!(scheduler.next_ready(), Some(1));
+        assert!(scheduler.out_of_memory(1));
+        assert_eq!(scheduler.next_ready(), Some(1));
+        assert!(!scheduler.out_of_memory(1));
+        assert!(scheduler.is_done());
+
+        assert!(is_out_of_memory("CUDA out of memory. Tried to allocate 2.00 GiB"));
+        assert!(!is_out_of_memory("Profile not found"));
+    }
+
+    #[test]
+    fn learns_costs_from_measurements() {
+        let mut model = CostModel::default();
+        model.observe(100, 10.0, Some(800.0));
+        assert!((model.secs_per_char - (0.05 + 0.3 * (0.1 - 0.05))).abs() < 1e-9);
+        assert!((model.vram_per_char_mb - (1.0 + 0.3 * (5.0 - 1.0))).abs() < 1e-9);
+        assert!(model.estimate(100).vram_mb > CostModel::default().estimate(100).vram_mb);
+    }
+}
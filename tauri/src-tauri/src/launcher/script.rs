@@ -0,0 +1,296 @@
+use crate::launcher::config::LauncherConfig;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::{Component, Path};
+
+/// Columns with a meaning of their own; any other column is passed to `/generate` as is
+const TEXT: &str = "text";
+const VOICE: &str = "voice";
+const PRESET: &str = "preset";
+const FILENAME: &str = "filename";
+/// Speaker looked up in a voice table for each language the script is rendered in
+const CHARACTER: &str = "character";
+/// `/generate` parameters that are numbers, so CSV cells are converted
+const NUMERIC_PARAMS: &[&str] = &["seed", "speed"];
+
+/// Cells of a row by column name, with the number of the row they start on
+type Row = (usize, Map<String, Value>);
+
+/// One line of dialogue of a render script
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptLine {
+    /// Row in the CSV file or position in the JSON array, counting from 1
+    pub line: usize,
+    pub text: String,
+    /// Voice profile ID, overriding the preset's
+    pub voice: Option<String>,
+    pub character: Option<String>,
+    pub preset: Option<String>,
+    /// Output file relative to the output directory, always ending in `.wav`
+    pub filename: String,
+    pub params: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// What became of one line, in the results report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineResult {
+    pub line: usize,
+    /// Language the line was rendered in when a voice table was used
+    pub language: Option<String>,
+    pub filename: String,
+    pub text: String,
+    pub generation_id: Option<String>,
+    pub duration_secs: Option<f64>,
+    /// The file was kept from an earlier run of the same line
+    pub unchanged: bool,
+    /// Why the line has no audio file
+    pub error: Option<String>,
+}
+
+impl LineResult {
+    /// Result of `line` before anything is known about it
+    pub fn new(line: &ScriptLine, language: Option<String>) -> Self {
+        Self {
+            line: line.line,
+            language,
+            filename: line.filename.clone(),
+            text: line.text.clone(),
+            generation_id: None,
+            duration_secs: None,
+            unchanged: false,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenderReport {
+    pub script: String,
+    pub job_id: String,
+    pub output_dir: String,
+    pub rendered: usize,
+    /// Lines among `rendered` whose file was kept from an earlier run
+    pub unchanged: usize,
+    pub failed: usize,
+    pub lines: Vec<LineResult>,
+}
+
+impl RenderReport {
+    pub fn new(script: &Path, job_id: &str, output_dir: &Path, lines: Vec<LineResult>) -> Self {
+        let failed = lines.iter().filter(|line| line.error.is_some()).count();
+        Self {
+            script: script.display().to_string(),
+            job_id: job_id.to_string(),
+            output_dir: output_dir.display().to_string(),
+            rendered: lines.len() - failed,
+            unchanged: lines.iter().filter(|line| line.unchanged).count(),
+            failed,
+            lines,
+        }
+    }
+}
+
+/// Read a script from CSV with a header row, or from a JSON array of objects when
+/// `path` ends in `.json`
+pub fn parse(path: &Path, content: &str) -> Result<Vec<ScriptLine>, String> {
+    let rows: Vec<Row> = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+        let rows: Vec<Map<String, Value>> =
+            serde_json::from_str(content).map_err(|e| format!("Expected a JSON array of objects: {}", e))?;
+        rows.into_iter().enumerate().map(|(i, row)| (i + 1, row)).collect()
+    } else {
+        csv_rows(content)?
+    };
+    Ok(rows.into_iter().map(|(line, row)| script_line(line, row)).collect())
+}
+
+fn script_line(line: usize, mut row: Map<String, Value>) -> ScriptLine {
+    let mut take = |column: &str| match row.remove(column) {
+        Some(Value::String(s)) if s.trim().is_empty() => None,
+        Some(Value::String(s)) => Some(s.trim().to_string()),
+        Some(Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    };
+    let text = take(TEXT).unwrap_or_default();
+    let voice = take(VOICE);
+    let character = take(CHARACTER);
+    let preset = take(PRESET);
+    let filename = take(FILENAME).map(|name| {
+        if Path::new(&name).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav")) {
+            name
+        } else {
+            format!("{}.wav", name)
+        }
+    });
+    row.retain(|_, value| !matches!(value, Value::Null) && value.as_str().is_none_or(|s| !s.trim().is_empty()));
+    ScriptLine { line, text, voice, character, preset, filename: filename.unwrap_or_default(), params: row }
+}
+
+/// CSV records with the number of the row they start on, blank ones left out; quoted
+/// cells may contain commas, doubled quotes and line breaks
+pub(crate) fn csv_records(content: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let (mut record, mut cell) = (Vec::new(), String::new());
+    let (mut quoted, mut row, mut start) = (false, 1, 1);
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut cell)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut cell));
+                records.push((start, std::mem::take(&mut record)));
+                row += 1;
+                start = row;
+            }
+            (_, c) => {
+                if c == '\n' {
+                    row += 1;
+                }
+                cell.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(format!("Row {}: unterminated quoted cell", start));
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell);
+        records.push((start, record));
+    }
+    records.retain(|(_, record)| record.iter().any(|cell| !cell.trim().is_empty()));
+    Ok(records)
+}
+
+/// CSV rows as column name to cell, numbered by the row they start on
+fn csv_rows(content: &str) -> Result<Vec<Row>, String> {
+    let mut records = csv_records(content)?.into_iter();
+    let (_, header) = records.next().ok_or("The script is empty")?;
+    let header: Vec<String> = header.iter().map(|name| name.trim().to_lowercase()).collect();
+    if !header.iter().any(|name| name == TEXT) || !header.iter().any(|name| name == FILENAME) {
+        return Err(format!("The header row needs `{}` and `{}` columns", TEXT, FILENAME));
+    }
+    records
+        .map(|(row, record)| {
+            if record.len() > header.len() {
+                return Err(format!("Row {}: {} cells but only {} columns", row, record.len(), header.len()));
+            }
+            let cells = header.iter().zip(record).map(|(name, cell)| {
+                let value = match NUMERIC_PARAMS.contains(&name.as_str()) {
+                    true => serde_json::from_str::<serde_json::Number>(cell.trim()).map(Value::Number).unwrap_or(Value::String(cell)),
+                    false => Value::String(cell),
+                };
+                (name.clone(), value)
+            });
+            Ok((row, cells.collect()))
+        })
+        .collect()
+}
+
+/// Every problem with the script at once, so it can be fixed in one go before
+/// anything is rendered
+pub fn validate(lines: &[ScriptLine], config: &LauncherConfig) -> Vec<ScriptError> {
+    let mut errors = Vec::new();
+    let mut seen = BTreeSet::new();
+    if lines.is_empty() {
+        errors.push(ScriptError { line: 0, message: "The script has no lines".to_string() });
+    }
+    for line in lines {
+        let mut error = |message: String| errors.push(ScriptError { line: line.line, message });
+        if line.text.is_empty() {
+            error("No text".to_string());
+        }
+        let path = Path::new(&line.filename);
+        if line.filename.is_empty() {
+            error("No filename".to_string());
+        } else if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            error(format!("Filename {:?} must stay inside the output directory", line.filename));
+        } else if !seen.insert(line.filename.to_lowercase()) {
+            error(format!("Filename {:?} is used by an earlier line", line.filename));
+        }
+        match &line.preset {
+            Some(preset) => {
+                if let Err(e) = config.resolve_preset(preset) {
+                    error(e);
+                } else if line.voice.is_none() && config.presets[preset].voice.is_none() {
+                    error(format!("No voice, and preset {} has none either", preset));
+                }
+            }
+            None if line.voice.is_none() => match &line.character {
+                Some(character) => error(format!("No voice for character {:?}; pass a voice table with --voices", character)),
+                None => error("No voice or preset".to_string()),
+            },
+            None => {}
+        }
+    }
+    errors
+}
+
+/// `/generate` request body of a validated line
+pub fn generate_request(line: &ScriptLine, config: &LauncherConfig) -> Result<Map<String, Value>, String> {
+    let mut request = match &line.preset {
+        Some(preset) => config.resolve_preset(preset)?.generate_request(&line.text).as_object().cloned().unwrap_or_default(),
+        None => Map::from_iter([("text".to_string(), Value::String(line.text.clone()))]),
+    };
+    if let Some(voice) = &line.voice {
+        request.insert("profile_id".to_string(), Value::String(voice.clone()));
+    }
+    request.extend(line.params.clone());
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "\u{feff}filename,voice,text,language,seed\n\
+        guard_01,knight,\"Halt, who goes there?\",en,7\r\n\
+        guard_02.wav,knight,\"He said \"\"stop\"\"\nand left\",de,\n\
+        \n\
+        guard_01,,Again,,\n";
+
+    #[test]
+    fn reads_quoted_csv_cells_and_parameters() {
+        let lines = parse(Path::new("lines.csv"), SCRIPT).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!((lines[0].line, lines[0].filename.as_str()), (2, "guard_01.wav"));
+        assert_eq!(lines[0].text, "Halt, who goes there?");
+        assert_eq!(lines[0].params["seed"], serde_json::json!(7));
+        assert_eq!(lines[1].text, "He said \"stop\"\nand left");
+        assert!(!lines[1].params.contains_key("seed"));
+        assert_eq!(lines[2].line, 6);
+
+        let request = generate_request(&lines[1], &LauncherConfig::default()).unwrap();
+        assert_eq!(request["profile_id"], "knight");
+        assert_eq!(request["language"], "de");
+
+        let errors = validate(&lines, &LauncherConfig::default());
+        let messages: Vec<_> = errors.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(messages, [(6, "Filename \"guard_01.wav\" is used by an earlier line"), (6, "No voice or preset")]);
+    }
+
+    #[test]
+    fn reads_json_scripts_and_rejects_escaping_filenames() {
+        let script = r#"[{"text": "Hi", "voice": "v", "filename": "../hi"}, {"text": "", "preset": "x", "filename": "b"}]"#;
+        let lines = parse(Path::new("lines.JSON"), script).unwrap();
+        let errors = validate(&lines, &LauncherConfig::default());
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].line, 1);
+        assert!(errors[0].message.contains("inside the output directory"));
+        assert_eq!((errors[1].line, errors[1].message.as_str()), (2, "No text"));
+        assert!(errors[2].message.starts_with("Unknown preset 'x'"));
+        assert!(parse(Path::new("lines.csv"), "text,voice\nhi,v\n").is_err());
+    }
+}
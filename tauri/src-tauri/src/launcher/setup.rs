@@ -0,0 +1,308 @@
+// First-run setup.
+//
+// The first launch has the most to do: find an interpreter, create the virtual
+// environment, install the backend's packages, download the default model and bring the
+// database up to date. Several of these take minutes, and a closed window, a dropped
+// connection or a reboot can cut any of them short. The setup runs them as the steps of
+// a state machine saved after each one, so the next launch continues with the step that
+// didn't finish instead of starting over.
+use crate::launcher::paths::python_path_with;
+use crate::launcher::process::CommandSpec;
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const SETUP_STATE_FILE_NAME: &str = "setup.json";
+/// Downloaded unless the config's `setup_models` names others
+pub const DEFAULT_SETUP_MODELS: &[&str] = &["Qwen/Qwen3-TTS-12Hz-1.7B-Base"];
+/// Fetches the Hugging Face repos given as arguments into the model cache
+const DOWNLOAD_SCRIPT: &str = "import sys
+from huggingface_hub import snapshot_download
+for repo in sys.argv[1:]:
+    print('Downloading ' + repo, flush=True)
+    snapshot_download(repo)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    FindPython,
+    CreateVenv,
+    InstallDependencies,
+    DownloadModels,
+    RunMigrations,
+}
+
+impl SetupStep {
+    /// In the order they run
+    pub const ALL: [SetupStep; 5] = [
+        SetupStep::FindPython,
+        SetupStep::CreateVenv,
+        SetupStep::InstallDependencies,
+        SetupStep::DownloadModels,
+        SetupStep::RunMigrations,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SetupStep::FindPython => "Finding Python",
+            SetupStep::CreateVenv => "Creating virtual environment",
+            SetupStep::InstallDependencies => "Installing Python packages",
+            SetupStep::DownloadModels => "Downloading models",
+            SetupStep::RunMigrations => "Updating the database",
+        }
+    }
+}
+
+/// A step that finished
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub step: SetupStep,
+    pub finished_at: String,
+    /// How it went, e.g. that it had nothing to do
+    pub detail: Option<String>,
+}
+
+/// Progress of the setup, saved after every step
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetupState {
+    pub completed: Vec<StepRecord>,
+    /// Interpreter the backend runs on, once found
+    pub python: Option<PathBuf>,
+    /// Step that was running, or failed, when the setup last stopped
+    pub current: Option<SetupStep>,
+    pub error: Option<String>,
+}
+
+impl SetupState {
+    pub fn is_done(&self, step: SetupStep) -> bool {
+        self.completed.iter().any(|record| record.step == step)
+    }
+
+    pub fn next_step(&self) -> Option<SetupStep> {
+        SetupStep::ALL.into_iter().find(|step| !self.is_done(*step))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.next_step().is_none()
+    }
+}
+
+/// The work behind each step. Each returns a note for the state file, if any.
+pub trait SetupActions {
+    fn find_python(&mut self) -> Result<PathBuf, String>;
+    /// The interpreter of the environment made from `python`, or `None` to run the
+    /// backend on `python` itself
+    fn create_venv(&mut self, python: &Path) -> Result<Option<PathBuf>, String>;
+    fn install_dependencies(&mut self, python: &Path) -> Result<Option<String>, String>;
+    fn download_models(&mut self, python: &Path) -> Result<Option<String>, String>;
+    fn run_migrations(&mut self, python: &Path) -> Result<Option<String>, String>;
+}
+
+pub struct Setup {
+    file: StateFile<SetupState>,
+}
+
+impl Setup {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { file: StateFile::new(state_dir.join(SETUP_STATE_FILE_NAME)) }
+    }
+
+    /// The saved state; a missing or unreadable file is a setup that hasn't started
+    pub fn state(&self) -> SetupState {
+        self.file.read().ok().flatten().unwrap_or_default()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.state().is_complete()
+    }
+
+    /// Start over on the next launch
+    pub fn reset(&self) -> Result<(), String> {
+        self.file.remove()
+    }
+
+    /// Run the steps that haven't finished, in order, saving the state before and after
+    /// each. `on_step` is told which one starts. An interpreter recorded earlier that is
+    /// gone now, e.g. a deleted environment, sends the setup back to the start. Returns
+    /// the interpreter the backend runs on.
+    pub fn run(&self, actions: &mut dyn SetupActions, on_step: &mut dyn FnMut(SetupStep)) -> Result<PathBuf, String> {
+        let mut state = self.state();
+        if state.python.as_ref().is_some_and(|python| python.is_absolute() && !python.exists()) {
+            state = SetupState::default();
+        }
+        while let Some(step) = state.next_step() {
+            on_step(step);
+            state.current = Some(step);
+            state.error = None;
+            self.file.write(&state)?;
+
+            let python = state.python.clone();
+            let result = match (step, python.as_deref()) {
+                (SetupStep::FindPython, _) => actions.find_python().map(|python| {
+                    state.python = Some(python);
+                    None
+                }),
+                (_, None) => Err("No interpreter was found".to_string()),
+                (SetupStep::CreateVenv, Some(python)) => actions.create_venv(python).map(|venv| match venv {
+                    Some(venv) => {
+                        state.python = Some(venv);
+                        None
+                    }
+                    None => Some("using the interpreter as is".to_string()),
+                }),
+                (SetupStep::InstallDependencies, Some(python)) => actions.install_dependencies(python),
+                (SetupStep::DownloadModels, Some(python)) => actions.download_models(python),
+                (SetupStep::RunMigrations, Some(python)) => actions.run_migrations(python),
+            };
+            match result {
+                Ok(detail) => {
+                    let finished_at = chrono::Utc::now().to_rfc3339();
+                    state.completed.push(StepRecord { step, finished_at, detail });
+                    state.current = None;
+                    self.file.write(&state)?;
+                }
+                Err(e) => {
+                    state.error = Some(e.clone());
+                    self.file.write(&state)?;
+                    return Err(format!("{} failed: {}", step.label(), e));
+                }
+            }
+        }
+        state.python.ok_or_else(|| "No interpreter was found".to_string())
+    }
+}
+
+/// Download `models` from Hugging Face with `python`
+pub fn download_command(python: &CommandSpec, models: &[String]) -> CommandSpec {
+    python.clone().arg("-c").arg(DOWNLOAD_SCRIPT).args(models)
+}
+
+/// The backend's migration modules (`migrate_*.py`), by name in the order they run
+pub fn migration_modules(backend_dir: &Path) -> Vec<String> {
+    let mut modules: Vec<String> = std::fs::read_dir(backend_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let module = name.strip_suffix(".py")?;
+            module.starts_with("migrate_").then(|| module.to_string())
+        })
+        .collect();
+    modules.sort();
+    modules
+}
+
+/// Run the migration `module` of the backend in `backend_dir` on the database in `data_dir`
+pub fn migration_command(python: &CommandSpec, backend_dir: &Path, module: &str, data_dir: &Path) -> CommandSpec {
+    let root_dir = backend_dir.parent().unwrap_or(backend_dir);
+    python
+        .clone()
+        .args(["-m", &format!("backend.{}", module)])
+        .current_dir(root_dir)
+        .env("PYTHONPATH", python_path_with(root_dir))
+        .env("VOICEBOX_DATA_DIR", data_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the steps it ran and fails the ones in `failing`
+    #[derive(Default)]
+    struct FakeActions {
+        ran: Vec<SetupStep>,
+        failing: Vec<SetupStep>,
+        python: PathBuf,
+    }
+
+    impl FakeActions {
+        fn run(&mut self, step: SetupStep) -> Result<Option<String>, String> {
+            self.ran.push(step);
+            match self.failing.contains(&step) {
+                true => Err("connection reset".to_string()),
+                false => Ok(None),
+            }
+        }
+    }
+
+    impl SetupActions for FakeActions {
+        fn find_python(&mut self) -> Result<PathBuf, String> {
+            self.run(SetupStep::FindPython).map(|_| self.python.clone())
+        }
+        fn create_venv(&mut self, python: &Path) -> Result<Option<PathBuf>, String> {
+            self.run(SetupStep::CreateVenv).map(|_| Some(python.with_file_name("venv-python")))
+        }
+        fn install_dependencies(&mut self, _: &Path) -> Result<Option<String>, String> {
+            self.run(SetupStep::InstallDependencies)
+        }
+        fn download_models(&mut self, _: &Path) -> Result<Option<String>, String> {
+            self.run(SetupStep::DownloadModels)
+        }
+        fn run_migrations(&mut self, _: &Path) -> Result<Option<String>, String> {
+            self.run(SetupStep::RunMigrations)
+        }
+    }
+
+    #[test]
+    fn resumes_with_the_step_that_failed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let python = tmp.path().join("python3");
+        std::fs::write(&python, "").unwrap();
+        std::fs::write(tmp.path().join("venv-python"), "").unwrap();
+        let setup = Setup::new(tmp.path());
+        assert_eq!(setup.state().next_step(), Some(SetupStep::FindPython));
+
+        let mut actions = FakeActions { failing: vec![SetupStep::DownloadModels], python: python.clone(), ..Default::default() };
+        let mut started = Vec::new();
+        let e = setup.run(&mut actions, &mut |step| started.push(step)).unwrap_err();
+        assert_eq!(e, "Downloading models failed: connection reset");
+        assert_eq!(started, &SetupStep::ALL[..4]);
+        let state = setup.state();
+        assert_eq!((state.current, state.error.as_deref()), (Some(SetupStep::DownloadModels), Some("connection reset")));
+        assert_eq!(state.python, Some(tmp.path().join("venv-python")));
+
+        let mut actions = FakeActions { python, ..Default::default() };
+        assert_eq!(setup.run(&mut actions, &mut |_| {}).unwrap(), tmp.path().join("venv-python"));
+        assert_eq!(actions.ran, [SetupStep::DownloadModels, SetupStep::RunMigrations]);
+        assert!(setup.is_complete());
+        assert_eq!(setup.state().current, None);
+
+        // Nothing left to do
+        assert!(setup.run(&mut actions, &mut |_| panic!("no step should run")).is_ok());
+        setup.reset().unwrap();
+        assert!(!setup.is_complete());
+    }
+
+    #[test]
+    fn starts_over_when_the_interpreter_is_gone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let setup = Setup::new(tmp.path());
+        let mut actions = FakeActions { failing: vec![SetupStep::InstallDependencies], python: tmp.path().join("python3"), ..Default::default() };
+        assert!(setup.run(&mut actions, &mut |_| {}).is_err());
+
+        // The environment created in the first run was never written, as if deleted since
+        let mut actions = FakeActions { python: tmp.path().join("python3"), ..Default::default() };
+        setup.run(&mut actions, &mut |_| {}).unwrap();
+        assert_eq!(actions.ran, SetupStep::ALL);
+    }
+
+    #[test]
+    fn builds_the_download_and_migration_commands() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend_dir = tmp.path().join("backend");
+        std::fs::create_dir(&backend_dir).unwrap();
+        for name in ["main.py", "migrate_b.py", "migrate_a.py", "migrate_notes.txt"] {
+            std::fs::write(backend_dir.join(name), "").unwrap();
+        }
+        assert_eq!(migration_modules(&backend_dir), ["migrate_a", "migrate_b"]);
+        assert!(migration_modules(&tmp.path().join("missing")).is_empty());
+
+        let python = CommandSpec::new("python3");
+        let migrate = migration_command(&python, &backend_dir, "migrate_a", Path::new("/data"));
+        assert_eq!(migrate.args, ["-m", "backend.migrate_a"]);
+        assert_eq!(migrate.env_value("VOICEBOX_DATA_DIR"), Some(std::ffi::OsStr::new("/data")));
+        let download = download_command(&python, &["Qwen/Qwen3-TTS-12Hz-1.7B-Base".to_string()]);
+        assert_eq!(download.args.last().map(|arg| arg.as_os_str()), Some(std::ffi::OsStr::new("Qwen/Qwen3-TTS-12Hz-1.7B-Base")));
+    }
+}
@@ -0,0 +1,124 @@
+// Stopping the backend together with the launcher.
+//
+// The backend runs in a process group of its own, so Ctrl+C, SIGTERM or closing the
+// console reach only the launcher. Its handler records the request; the supervising
+// loop then asks the backend to shut down through its `/shutdown` endpoint, signals
+// its group if that doesn't work, and kills the whole tree, uvicorn workers included,
+// if it is still running when the grace period is up. Nothing is left orphaned.
+use crate::launcher::log::log;
+use crate::launcher::process::{ChildProcess, ExitInfo};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How long the backend gets to finish what it is writing before it is killed
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Record Ctrl+C, Ctrl+Break, SIGTERM, SIGHUP and closing the console as a shutdown
+/// request instead of ending the launcher on the spot. Only the first call takes effect.
+pub fn install_handler() -> Result<(), String> {
+    ctrlc::set_handler(request).map_err(|e| format!("Failed to install the shutdown handler: {}", e))
+}
+
+pub fn request() {
+    if !REQUESTED.swap(true, Ordering::Relaxed) {
+        log("Launcher: Shutdown requested");
+    }
+}
+
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Wait for `duration` unless a shutdown is requested meanwhile. Returns whether one was.
+pub fn sleep(duration: Duration) -> bool {
+    let until = Instant::now() + duration;
+    while Instant::now() < until {
+        if requested() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100).min(until.saturating_duration_since(Instant::now())));
+    }
+    requested()
+}
+
+/// Ask the backend listening on `port` to shut down. Returns whether it accepted.
+pub fn request_backend_shutdown(port: u16) -> bool {
+    let url = format!("http://127.0.0.1:{}/shutdown", port);
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .and_then(|client| client.post(&url).send())
+        .is_ok_and(|response| response.status().is_success())
+}
+
+fn wait_for(child: &mut dyn ChildProcess, timeout: Duration) -> io::Result<Option<ExitInfo>> {
+    let until = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= until {
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Stop `child` gracefully: ask it with `ask` (e.g. `request_backend_shutdown`), give
+/// it half of `grace`, signal its process group and give it the rest, then kill it
+/// and everything it started.
+pub fn stop(child: &mut dyn ChildProcess, ask: impl FnOnce() -> bool, grace: Duration) -> io::Result<ExitInfo> {
+    if ask() {
+        log("Launcher: Asked the backend to shut down");
+        if let Some(status) = wait_for(child, grace / 2)? {
+            return Ok(status);
+        }
+    }
+    if child.terminate().is_ok() {
+        log("Launcher: Sent the backend's processes a termination signal");
+        if let Some(status) = wait_for(child, grace / 2)? {
+            return Ok(status);
+        }
+    }
+    log(&format!("Launcher: Backend still running after {}s; killing its process tree", grace.as_secs()));
+    child.kill_tree()?;
+    child.wait()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+    use crate::launcher::process::{CommandSpec, ProcessRunner};
+
+    #[test]
+    fn kills_a_backend_that_ignores_the_request() {
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(0).delay(Duration::from_millis(50)));
+        runner.script("python", Script::exits(0).delay(Duration::from_secs(60)));
+        let spec = CommandSpec::new("python").own_process_group();
+
+        let mut child = runner.spawn(&spec).unwrap();
+        let status = stop(child.as_mut(), || true, Duration::from_secs(5)).unwrap();
+        assert!(status.success());
+
+        let mut child = runner.spawn(&spec).unwrap();
+        let started = Instant::now();
+        let status = stop(child.as_mut(), || false, Duration::from_millis(200)).unwrap();
+        assert!(!status.success());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn sleep_ends_early_on_a_request() {
+        assert!(!requested());
+        assert!(!sleep(Duration::from_millis(10)));
+        request();
+        let started = Instant::now();
+        assert!(sleep(Duration::from_secs(60)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
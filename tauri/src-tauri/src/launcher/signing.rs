@@ -0,0 +1,56 @@
+use crate::launcher::state::StateFile;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+pub const SIGNING_KEY_FILE_NAME: &str = "signing-key.json";
+
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    secret_key: String,
+}
+
+/// Short, stable identifier of a signing key for display
+pub fn fingerprint(key: &VerifyingKey) -> String {
+    hex::encode(fingerprint_bytes(key))
+}
+
+pub fn fingerprint_bytes(key: &VerifyingKey) -> [u8; 8] {
+    let digest = Sha256::digest(key.as_bytes());
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    bytes
+}
+
+/// The user's signing key for profile archives and audio watermarks, created on first
+/// use. It lives in the state dir, which is readable by its owner only.
+pub fn load_or_create_signing_key(state_dir: &Path) -> Result<SigningKey, String> {
+    let file = StateFile::<StoredKey>::new(state_dir.join(SIGNING_KEY_FILE_NAME));
+    let stored = file
+        .update(|stored| {
+            Some(stored.unwrap_or_else(|| StoredKey {
+                secret_key: hex::encode(SigningKey::generate(&mut OsRng).to_bytes()),
+            }))
+        })?
+        .ok_or_else(|| format!("Failed to create {}", file.path().display()))?;
+    let bytes = hex::decode(&stored.secret_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| format!("Invalid signing key in {}", file.path().display()))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_the_key_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = load_or_create_signing_key(dir.path()).unwrap();
+        assert_eq!(load_or_create_signing_key(dir.path()).unwrap().to_bytes(), key.to_bytes());
+        assert_eq!(fingerprint(&key.verifying_key()).len(), 16);
+    }
+}
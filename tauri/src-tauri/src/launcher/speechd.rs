@@ -0,0 +1,132 @@
+// Speech Dispatcher output module.
+//
+// Speech Dispatcher's `sd_generic` module runs a shell command per message. The
+// generated `voicebox.conf` makes that command pipe the text into
+// `voicebox-server speechd-say`, which speaks it with the running backend into a WAV
+// file that Speech Dispatcher's player then plays. Screen readers such as Orca and
+// desktop read-aloud tools pick Voicebox like any other synthesizer.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const MODULE_NAME: &str = "voicebox";
+pub const MODULE_FILE_NAME: &str = "voicebox.conf";
+/// The line registering the module in `speechd.conf`
+pub const ADD_MODULE_LINE: &str = "AddModule \"voicebox\" \"sd_generic\" \"voicebox.conf\"";
+/// Speech Dispatcher's voice types, which clients ask for instead of a voice name
+const VOICE_TYPES: &[&str] = &["MALE1", "FEMALE1", "MALE2", "FEMALE2", "MALE3", "FEMALE3"];
+
+/// A voice profile as Speech Dispatcher should offer it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeechdVoice {
+    pub profile_id: String,
+    pub language: String,
+}
+
+/// `value` in single quotes for the shell command Speech Dispatcher runs. The config
+/// string itself is double-quoted, so embedded double quotes are escaped for it too.
+fn shell_quoted(value: &str) -> String {
+    format!("\\'{}\\'", value.replace('\'', "'\\''").replace('"', "\\\""))
+}
+
+/// The module configuration: the synth command, and each language's profiles as the
+/// voice types clients ask for, in profile order
+pub fn module_config(exe: &Path, voices: &[SpeechdVoice], output: &Path) -> String {
+    let command = format!(
+        "printf %s \\'$DATA\\' | {} speechd-say --voice \\'$VOICE\\' --language \\'$LANGUAGE\\' --output {} && $PLAY_COMMAND {}",
+        shell_quoted(&exe.to_string_lossy()),
+        shell_quoted(&output.to_string_lossy()),
+        shell_quoted(&output.to_string_lossy()),
+    );
+    let mut config = format!(
+        "# Generated by voicebox-server speechd; regenerate after adding voice profiles\n\
+         GenericExecuteSynth \"{}\"\n\
+         GenericCmdDependency \"printf\"\n\
+         GenericPunctNone \"\"\n\
+         GenericPunctSome \"\"\n\
+         GenericPunctMost \"\"\n\
+         GenericPunctAll \"\"\n\
+         GenericStripPunctChars \"\"\n",
+        command
+    );
+
+    let mut by_language: BTreeMap<&str, Vec<&SpeechdVoice>> = BTreeMap::new();
+    for voice in voices {
+        by_language.entry(voice.language.as_str()).or_default().push(voice);
+    }
+    for language in by_language.keys() {
+        config.push_str(&format!("GenericLanguage \"{0}\" \"{0}\" \"utf-8\"\n", language));
+    }
+    for (language, voices) in &by_language {
+        for (voice_type, voice) in VOICE_TYPES.iter().zip(voices) {
+            config.push_str(&format!("AddVoice \"{}\" \"{}\" \"{}\"\n", language, voice_type, voice.profile_id));
+        }
+    }
+    config
+}
+
+/// The user's Speech Dispatcher configuration directory
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("speech-dispatcher"))
+}
+
+/// Write the module to `config_dir` and register it in the user's `speechd.conf`,
+/// which has to exist already (`spd-conf -u` creates it). Returns the files written.
+pub fn install(config_dir: &Path, module: &str) -> Result<Vec<PathBuf>, String> {
+    let speechd_conf = config_dir.join("speechd.conf");
+    let settings = std::fs::read_to_string(&speechd_conf).map_err(|e| {
+        format!(
+            "Failed to read {} ({}); create your user configuration with `spd-conf -u` first",
+            speechd_conf.display(),
+            e
+        )
+    })?;
+
+    let module_path = config_dir.join("modules").join(MODULE_FILE_NAME);
+    std::fs::create_dir_all(config_dir.join("modules")).map_err(|e| e.to_string())?;
+    std::fs::write(&module_path, module).map_err(|e| format!("Failed to write {}: {}", module_path.display(), e))?;
+    let mut written = vec![module_path];
+
+    if !settings.lines().any(|line| line.trim() == ADD_MODULE_LINE) {
+        let mut settings = settings;
+        if !settings.is_empty() && !settings.ends_with('\n') {
+            settings.push('\n');
+        }
+        settings.push_str(ADD_MODULE_LINE);
+        settings.push('\n');
+        std::fs::write(&speechd_conf, settings).map_err(|e| format!("Failed to write {}: {}", speechd_conf.display(), e))?;
+        written.push(speechd_conf);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice(profile_id: &str, language: &str) -> SpeechdVoice {
+        SpeechdVoice { profile_id: profile_id.to_string(), language: language.to_string() }
+    }
+
+    #[test]
+    fn maps_profiles_to_voice_types_per_language() {
+        let voices = [voice("anna", "de"), voice("max", "de"), voice("kate", "en")];
+        let config = module_config(Path::new("/opt/Voice Box/voicebox-server"), &voices, Path::new("/tmp/voicebox-speechd.wav"));
+        assert!(config.contains(r#"printf %s \'$DATA\' | \'/opt/Voice Box/voicebox-server\' speechd-say"#));
+        assert!(config.contains("AddVoice \"de\" \"MALE1\" \"anna\"\nAddVoice \"de\" \"FEMALE1\" \"max\"\nAddVoice \"en\" \"MALE1\" \"kate\""));
+        assert_eq!(config.matches("GenericLanguage").count(), 2);
+        assert_eq!(shell_quoted("it's"), r"\'it'\''s\'");
+    }
+
+    #[test]
+    fn registers_the_module_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(install(tmp.path(), "").unwrap_err().contains("spd-conf -u"));
+
+        std::fs::write(tmp.path().join("speechd.conf"), "DefaultModule espeak-ng").unwrap();
+        assert_eq!(install(tmp.path(), "GenericExecuteSynth \"\"\n").unwrap().len(), 2);
+        assert_eq!(install(tmp.path(), "GenericExecuteSynth \"\"\n").unwrap().len(), 1);
+        let settings = std::fs::read_to_string(tmp.path().join("speechd.conf")).unwrap();
+        assert_eq!(settings, format!("DefaultModule espeak-ng\n{}\n", ADD_MODULE_LINE));
+        assert!(tmp.path().join("modules").join(MODULE_FILE_NAME).is_file());
+    }
+}
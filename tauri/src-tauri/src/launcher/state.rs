@@ -0,0 +1,287 @@
+use crate::launcher::retry::with_io_retry;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+pub const RUNTIME_STATE_FILE_NAME: &str = "runtime.json";
+/// The backend's PID as plain text next to the runtime state, for shell scripts and
+/// service managers
+pub const BACKEND_PID_FILE_NAME: &str = "backend.pid";
+
+/// What a running launcher publishes about itself for the CLI, the frontend and
+/// second instances
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeState {
+    /// PID of the launcher process
+    pub pid: u32,
+    pub backend_pid: u32,
+    pub host: String,
+    /// Port clients should connect to
+    pub port: u16,
+    /// Loopback port of the backend when it sits behind the proxy
+    pub backend_port: Option<u16>,
+    /// Python interpreter running the backend
+    #[serde(default)]
+    pub interpreter: Option<PathBuf>,
+    pub data_dir: PathBuf,
+    pub log_path: PathBuf,
+    /// RFC 3339 timestamp of when the backend was started
+    pub started_at: String,
+    pub version: String,
+    /// Times the backend was restarted after crashing
+    #[serde(default)]
+    pub restarts: u32,
+}
+
+/// JSON state file shared between processes.
+///
+/// Writers replace the file atomically (write a temp file, then rename over it), so
+/// readers never see a half-written document. An advisory lock on a sibling `.lock`
+/// file serializes writers and lets `update` do read-modify-write without losing
+/// concurrent changes. The data file itself is never locked: Windows locks are
+/// mandatory and would make plain reads fail.
+pub struct StateFile<T> {
+    path: PathBuf,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned> StateFile<T> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), _marker: PhantomData }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        self.path.with_file_name(name)
+    }
+
+    /// Open and lock the sibling lock file; the lock is released when it is dropped
+    fn lock(&self, exclusive: bool) -> Result<File, String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let lock_path = self.lock_path();
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| format!("Failed to open {}: {}", lock_path.display(), e))?;
+        let locked = if exclusive { file.lock() } else { file.lock_shared() };
+        locked.map_err(|e| format!("Failed to lock {}: {}", lock_path.display(), e))?;
+        Ok(file)
+    }
+
+    fn read_unlocked(&self) -> Result<Option<T>, String> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.path.display(), e)),
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| format!("Invalid state file {}: {}", self.path.display(), e))
+    }
+
+    fn write_unlocked(&self, value: &T) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.path.display(), e))?;
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(self.path.file_name().unwrap_or_default());
+        tmp_name.push(format!(".{}.tmp", std::process::id()));
+        let tmp = self.path.with_file_name(tmp_name);
+
+        std::fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+        // Readers on Windows may briefly hold the old file open
+        with_io_retry("Replacing state file", || std::fs::rename(&tmp, &self.path)).map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            format!("Failed to replace {}: {}", self.path.display(), e)
+        })
+    }
+
+    /// Current contents, or `None` if the file doesn't exist
+    pub fn read(&self) -> Result<Option<T>, String> {
+        let _lock = self.lock(false)?;
+        self.read_unlocked()
+    }
+
+    pub fn write(&self, value: &T) -> Result<(), String> {
+        let _lock = self.lock(true)?;
+        self.write_unlocked(value)
+    }
+
+    /// Read-modify-write under the exclusive lock. Returning `None` from `f` deletes
+    /// the file.
+    pub fn update(&self, f: impl FnOnce(Option<T>) -> Option<T>) -> Result<Option<T>, String> {
+        let _lock = self.lock(true)?;
+        let updated = f(self.read_unlocked()?);
+        match &updated {
+            Some(value) => self.write_unlocked(value)?,
+            None => self.remove_unlocked()?,
+        }
+        Ok(updated)
+    }
+
+    fn remove_unlocked(&self) -> Result<(), String> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {}", self.path.display(), e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn remove(&self) -> Result<(), String> {
+        let _lock = self.lock(true)?;
+        self.remove_unlocked()
+    }
+}
+
+/// The runtime state file in `state_dir`
+pub fn runtime_state(state_dir: &Path) -> StateFile<RuntimeState> {
+    StateFile::new(state_dir.join(RUNTIME_STATE_FILE_NAME))
+}
+
+/// Write the runtime state and the PID file of a backend that was just started
+pub fn publish(state_dir: &Path, state: &RuntimeState) -> Result<(), String> {
+    runtime_state(state_dir).write(state)?;
+    let path = state_dir.join(BACKEND_PID_FILE_NAME);
+    std::fs::write(&path, format!("{}\n", state.backend_pid)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Remove the runtime state and the PID file published by the launcher with
+/// `launcher_pid`. Files a newer launcher has replaced them with are left alone.
+pub fn withdraw(state_dir: &Path, launcher_pid: u32) -> Result<(), String> {
+    let mut ours = true;
+    runtime_state(state_dir).update(|current| {
+        ours = current.as_ref().is_none_or(|state| state.pid == launcher_pid);
+        current.filter(|state| state.pid != launcher_pid)
+    })?;
+    let path = state_dir.join(BACKEND_PID_FILE_NAME);
+    match ours.then(|| std::fs::remove_file(&path)) {
+        Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove {}: {}", path.display(), e)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Counter {
+        value: u32,
+    }
+
+    #[test]
+    fn missing_file_reads_as_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = StateFile::<Counter>::new(dir.path().join("nested").join("counter.json"));
+        assert_eq!(file.read().unwrap(), None);
+        file.remove().unwrap();
+    }
+
+    #[test]
+    fn write_replaces_and_remove_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = StateFile::new(dir.path().join("counter.json"));
+        file.write(&Counter { value: 1 }).unwrap();
+        file.write(&Counter { value: 2 }).unwrap();
+        assert_eq!(file.read().unwrap(), Some(Counter { value: 2 }));
+
+        // No temp files are left next to the state file
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert!(names.iter().all(|n| !n.ends_with(".tmp")), "{:?}", names);
+
+        file.remove().unwrap();
+        assert_eq!(file.read().unwrap(), None);
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counter.json");
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let file = StateFile::<Counter>::new(path);
+                    for _ in 0..25 {
+                        file.update(|c| Some(Counter { value: c.unwrap_or_default().value + 1 }))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(StateFile::<Counter>::new(path).read().unwrap(), Some(Counter { value: 200 }));
+    }
+
+    #[test]
+    fn corrupt_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counter.json");
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(StateFile::<Counter>::new(path).read().is_err());
+    }
+
+    #[test]
+    fn runtime_state_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = RuntimeState {
+            pid: 10,
+            backend_pid: 11,
+            host: "127.0.0.1".to_string(),
+            port: 17493,
+            backend_port: Some(17494),
+            interpreter: Some(dir.path().join("venv/bin/python")),
+            data_dir: dir.path().to_path_buf(),
+            log_path: dir.path().join("voicebox-launch.log"),
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+            version: "0.1.0".to_string(),
+            restarts: 2,
+        };
+        let file = runtime_state(dir.path());
+        file.write(&state).unwrap();
+        assert_eq!(file.read().unwrap(), Some(state));
+    }
+
+    #[test]
+    fn withdraw_leaves_a_newer_launchers_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join(BACKEND_PID_FILE_NAME);
+        let state = |pid, backend_pid| RuntimeState {
+            pid,
+            backend_pid,
+            host: "127.0.0.1".to_string(),
+            port: 17493,
+            backend_port: None,
+            interpreter: None,
+            data_dir: dir.path().to_path_buf(),
+            log_path: dir.path().join("voicebox-launch.log"),
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+            version: "0.1.0".to_string(),
+            restarts: 0,
+        };
+        publish(dir.path(), &state(10, 11)).unwrap();
+        assert_eq!(std::fs::read_to_string(&pid_file).unwrap(), "11\n");
+
+        withdraw(dir.path(), 20).unwrap();
+        assert!(pid_file.exists() && runtime_state(dir.path()).read().unwrap().is_some());
+        withdraw(dir.path(), 10).unwrap();
+        assert!(!pid_file.exists() && runtime_state(dir.path()).read().unwrap().is_none());
+    }
+}
@@ -0,0 +1,134 @@
+// Whether a launcher runs a backend on a data directory, and how it is doing.
+//
+// The launcher publishes `runtime.json` and `backend.pid` when it starts a backend and
+// withdraws them when the backend exits. A launcher killed outright leaves them
+// behind, so the PIDs they name are checked against the running processes before the
+// backend is reported running, and `/health` tells a backend that answers from one
+// still loading or stuck.
+use crate::launcher::process::ProcessRunner;
+use crate::launcher::state::{runtime_state, RuntimeState};
+use crate::launcher::zombie::{looks_like_backend, process_name};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackendStatus {
+    /// As the launcher published it; `None` when no launcher has started a backend
+    pub state: Option<RuntimeState>,
+    pub launcher_running: bool,
+    pub backend_running: bool,
+    /// What the backend answered on `/health`
+    pub health: Option<serde_json::Value>,
+    /// Seconds since the backend was started
+    pub uptime_secs: Option<i64>,
+}
+
+impl BackendStatus {
+    pub fn is_healthy(&self) -> bool {
+        self.backend_running && self.health.is_some()
+    }
+
+    /// One line for people
+    pub fn summary(&self) -> String {
+        let Some(state) = &self.state else {
+            return "No backend running".to_string();
+        };
+        let url = format!("http://{}:{}", state.host, state.port);
+        let uptime = self.uptime_secs.map(|secs| format!(", up {}", uptime(secs))).unwrap_or_default();
+        match (self.backend_running, &self.health) {
+            (true, Some(_)) => format!("Running on {} (PID {}{})", url, state.backend_pid, uptime),
+            (true, None) => format!("Starting or not answering on {} (PID {}{})", url, state.backend_pid, uptime),
+            (false, _) if self.launcher_running => format!("Backend not running; the launcher (PID {}) may be restarting it", state.pid),
+            (false, _) => "Not running; the runtime state was left by a launcher that is gone".to_string(),
+        }
+    }
+}
+
+fn uptime(secs: i64) -> String {
+    match secs {
+        ..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn health(port: u16) -> Option<serde_json::Value> {
+    let url = format!("http://127.0.0.1:{}/health", port);
+    reqwest::blocking::Client::builder()
+        .timeout(HEALTH_TIMEOUT)
+        .build()
+        .and_then(|client| client.get(&url).send())
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .ok()
+}
+
+/// Status of the backend published in `state_dir`. Blocks for up to two seconds while
+/// asking the backend.
+pub fn check(runner: &dyn ProcessRunner, state_dir: &Path) -> Result<BackendStatus, String> {
+    let Some(state) = runtime_state(state_dir).read()? else {
+        return Ok(BackendStatus { state: None, launcher_running: false, backend_running: false, health: None, uptime_secs: None });
+    };
+    let running = |pid| process_name(runner, pid).is_some_and(|name| looks_like_backend(&name));
+    let backend_running = running(state.backend_pid);
+    let uptime_secs = chrono::DateTime::parse_from_rfc3339(&state.started_at)
+        .ok()
+        .map(|started| (chrono::Utc::now() - started.with_timezone(&chrono::Utc)).num_seconds().max(0));
+    Ok(BackendStatus {
+        launcher_running: running(state.pid),
+        health: backend_running.then(|| health(state.backend_port.unwrap_or(state.port))).flatten(),
+        uptime_secs: uptime_secs.filter(|_| backend_running),
+        backend_running,
+        state: Some(state),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+    use crate::launcher::state::publish;
+
+    fn published(dir: &Path) {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let state = RuntimeState {
+            pid: 10,
+            backend_pid: 11,
+            host: "127.0.0.1".to_string(),
+            port,
+            backend_port: None,
+            interpreter: Some(dir.join("venv/bin/python")),
+            data_dir: dir.to_path_buf(),
+            log_path: dir.join("voicebox-launch.log"),
+            started_at: (chrono::Utc::now() - chrono::Duration::minutes(90)).to_rfc3339(),
+            version: "0.1.0".to_string(),
+            restarts: 0,
+        };
+        publish(dir, &state).unwrap();
+    }
+
+    #[test]
+    fn tells_a_running_backend_from_a_stale_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = FakeRunner::new();
+        assert_eq!(check(&runner, dir.path()).unwrap().summary(), "No backend running");
+
+        published(dir.path());
+        runner.script("ps", Script::exits(0).stdout("python3\n"));
+        runner.script("tasklist", Script::exits(0).stdout("\"python.exe\",\"11\",\"Console\",\"1\",\"1 K\"\r\n"));
+        let status = check(&runner, dir.path()).unwrap();
+        assert!(status.backend_running && status.launcher_running && !status.is_healthy());
+        assert!(status.summary().starts_with("Starting or not answering"), "{}", status.summary());
+        assert!(status.summary().ends_with("(PID 11, up 1h 30m)"), "{}", status.summary());
+
+        let runner = FakeRunner::new();
+        runner.script("ps", Script::exits(1));
+        runner.script("tasklist", Script::exits(0).stdout("INFO: No tasks are running which match the specified criteria.\r\n"));
+        let status = check(&runner, dir.path()).unwrap();
+        assert!(!status.backend_running && status.uptime_secs.is_none());
+        assert!(status.summary().contains("left by a launcher that is gone"));
+    }
+}
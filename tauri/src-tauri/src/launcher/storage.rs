@@ -0,0 +1,236 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Why a data directory is a poor home for the SQLite database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StorageRisk {
+    /// Network filesystem (SMB/NFS share, mapped drive); SQLite locking is unreliable
+    NetworkDrive { filesystem: String },
+    /// Folder managed by a sync client that may replace the DB file mid-write
+    SyncedFolder { provider: String },
+}
+
+impl StorageRisk {
+    pub fn describe(&self, path: &Path) -> String {
+        match self {
+            StorageRisk::NetworkDrive { filesystem } => format!(
+                "Data directory {} is on a network drive ({}). SQLite databases can be corrupted on network filesystems; consider moving it to a local disk.",
+                path.display(),
+                filesystem
+            ),
+            StorageRisk::SyncedFolder { provider } => format!(
+                "Data directory {} is inside a {} folder. Sync clients can corrupt the database while it is open; consider moving it outside the synced folder.",
+                path.display(),
+                provider
+            ),
+        }
+    }
+}
+
+/// A storage risk bundled with a user-facing explanation
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageWarning {
+    pub path: PathBuf,
+    pub risk: StorageRisk,
+    pub message: String,
+}
+
+pub fn check_data_dir(path: &Path) -> Option<StorageWarning> {
+    detect_storage_risk(path).map(|risk| StorageWarning {
+        path: path.to_path_buf(),
+        message: risk.describe(path),
+        risk,
+    })
+}
+
+/// Folder names used by common sync clients
+const SYNC_FOLDER_MARKERS: &[(&str, &str)] = &[
+    ("onedrive", "OneDrive"),
+    ("dropbox", "Dropbox"),
+    ("google drive", "Google Drive"),
+    ("googledrive", "Google Drive"),
+    ("mobile documents", "iCloud Drive"),
+    ("icloud drive", "iCloud Drive"),
+    ("icloudd", "iCloud Drive"),
+    ("pcloud drive", "pCloud"),
+    ("nextcloud", "Nextcloud"),
+    ("owncloud", "ownCloud"),
+    ("cloudstorage", "cloud storage"),
+    ("megasync", "MEGA"),
+    ("syncthing", "Syncthing"),
+];
+
+/// Filesystem types that indicate network storage in mount tables
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb", "smb2", "smb3", "smbfs", "afpfs", "webdav", "davfs",
+    "fuse.sshfs", "sshfs", "9p", "afs", "ncpfs", "fuse.rclone",
+];
+
+/// Check `path` for network or synced-folder storage
+pub fn detect_storage_risk(path: &Path) -> Option<StorageRisk> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    synced_folder_provider(&path)
+        .map(|provider| StorageRisk::SyncedFolder { provider })
+        .or_else(|| network_filesystem(&path).map(|filesystem| StorageRisk::NetworkDrive { filesystem }))
+}
+
+fn synced_folder_provider(path: &Path) -> Option<String> {
+    // OneDrive exports its root folders, which may be localized ("OneDrive - Contoso")
+    for var in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Some(root) = std::env::var_os(var).filter(|r| !r.is_empty()) {
+            if path.starts_with(PathBuf::from(root)) {
+                return Some("OneDrive".to_string());
+            }
+        }
+    }
+
+    path.components().find_map(|component| {
+        let name = component.as_os_str().to_string_lossy().to_lowercase();
+        SYNC_FOLDER_MARKERS
+            .iter()
+            .find(|(marker, _)| name == *marker || name.starts_with(&format!("{} ", marker)) || name.starts_with(&format!("{}-", marker)))
+            .map(|(_, provider)| provider.to_string())
+    })
+}
+
+/// Find the filesystem type of the mount containing `path` in a `/proc/mounts` style
+/// table (`device mountpoint fstype ...` per line)
+pub fn filesystem_from_mounts(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Spaces in mount points are octal-escaped in /proc/mounts
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fstype = fields.next()?;
+            path.starts_with(&mount_point).then(|| (mount_point.len(), fstype.to_string()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fstype)| fstype)
+}
+
+pub fn is_network_filesystem(fstype: &str) -> bool {
+    NETWORK_FILESYSTEMS.contains(&fstype.to_lowercase().as_str())
+}
+
+#[cfg(target_os = "linux")]
+fn network_filesystem(path: &Path) -> Option<String> {
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    filesystem_from_mounts(&mounts, path).filter(|fstype| is_network_filesystem(fstype))
+}
+
+#[cfg(target_os = "macos")]
+fn network_filesystem(path: &Path) -> Option<String> {
+    // `mount` prints "//user@nas/share on /Volumes/share (smbfs, nodev, ...)"
+    use crate::launcher::process::{CommandSpec, ProcessRunner, SystemRunner};
+    let output = SystemRunner.output(&CommandSpec::new("/sbin/mount")).ok()?;
+    let table: String = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (device, rest) = line.split_once(" on ")?;
+            let (mount_point, opts) = rest.rsplit_once(" (")?;
+            let fstype = opts.split([',', ')']).next()?.trim();
+            Some(format!("{} {} {}\n", device.replace(' ', "\\040"), mount_point.replace(' ', "\\040"), fstype))
+        })
+        .collect();
+    filesystem_from_mounts(&table, path).filter(|fstype| is_network_filesystem(fstype))
+}
+
+#[cfg(windows)]
+fn network_filesystem(path: &Path) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    let path_str = path.to_string_lossy();
+    let path_str = path_str.strip_prefix(r"\\?\").unwrap_or(&path_str);
+    if path_str.starts_with(r"UNC\") || path_str.starts_with(r"\\") {
+        return Some("UNC share".to_string());
+    }
+
+    // GetDriveTypeW wants the root with a trailing backslash, e.g. "Z:\"
+    let root: Vec<u16> = std::ffi::OsStr::new(&format!("{}\\", path_str.get(..2)?))
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    const DRIVE_REMOTE: u32 = 4;
+    let drive_type = unsafe { GetDriveTypeW(PCWSTR(root.as_ptr())) };
+    (drive_type == DRIVE_REMOTE).then(|| "mapped network drive".to_string())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn network_filesystem(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Bytes this user may still write to the file system holding `path`
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs is plain data, and all zeroes is a valid value of it
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL-terminated and `stat` is the buffer statvfs fills
+    let ok = unsafe { libc::statvfs(path.as_ptr(), &mut stat) } == 0;
+    ok.then(|| stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Bytes this user may still write to the file system holding `path`
+#[cfg(windows)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let path: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut free = 0u64;
+    // SAFETY: `path` is NUL-terminated and outlives the call, which writes one u64
+    unsafe { GetDiskFreeSpaceExW(PCWSTR(path.as_ptr()), Some(&mut free as *mut u64), None, None) }.ok()?;
+    Some(free)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+//nas/media /mnt/nas\\040share cifs rw,vers=3.0 0 0
+nas:/export /home/user/nfs nfs4 rw 0 0
+";
+
+    #[test]
+    fn longest_mount_prefix_wins() {
+        assert_eq!(filesystem_from_mounts(MOUNTS, Path::new("/home/user/data")).as_deref(), Some("ext4"));
+        assert_eq!(filesystem_from_mounts(MOUNTS, Path::new("/home/user/nfs/voicebox")).as_deref(), Some("nfs4"));
+        assert_eq!(filesystem_from_mounts(MOUNTS, Path::new("/mnt/nas share/vb")).as_deref(), Some("cifs"));
+    }
+
+    #[test]
+    fn tells_the_free_space() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(free_space(dir.path()).is_some_and(|free| free > 0));
+        assert_eq!(free_space(&dir.path().join("missing")), None);
+    }
+
+    #[test]
+    fn detects_sync_client_folders() {
+        assert_eq!(synced_folder_provider(Path::new("/Users/me/Dropbox/voicebox")).as_deref(), Some("Dropbox"));
+        assert_eq!(
+            synced_folder_provider(Path::new("/home/me/OneDrive - Contoso/vb")).as_deref(),
+            Some("OneDrive")
+        );
+        assert_eq!(synced_folder_provider(Path::new("/home/me/.local/share/com.voicebox")), None);
+        assert_eq!(
+            synced_folder_provider(Path::new("/Users/me/Library/CloudStorage/Box-Box/vb")).as_deref(),
+            Some("cloud storage")
+        );
+    }
+}
@@ -0,0 +1,137 @@
+// Restarting a backend that crashed.
+//
+// A crash from a transient cause, such as a driver hiccup or running out of memory
+// on one long generation, shouldn't take the launcher down with it. The supervisor
+// decides after each crash whether to start the backend again and how long to wait
+// first, backing off between attempts and giving up when the backend keeps crashing
+// right after it starts, which no amount of restarting fixes.
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The `restart` section of the launcher config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    pub enabled: bool,
+    /// Restarts over the launcher's lifetime before it gives up
+    pub max_restarts: u32,
+    /// Wait before the first restart, doubled before each further one
+    pub backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    /// This many crashes within `crash_loop_window_secs` are a crash loop, which ends
+    /// the restarts
+    pub crash_loop_crashes: u32,
+    pub crash_loop_window_secs: u64,
+    /// A backend that ran this long before crashing starts the backoff over
+    pub stable_after_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_restarts: 5,
+            backoff_secs: 1,
+            max_backoff_secs: 60,
+            crash_loop_crashes: 3,
+            crash_loop_window_secs: 60,
+            stable_after_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// Start the backend again after waiting `after`; `attempt` counts the restarts
+    Restart { after: Duration, attempt: u32 },
+    /// Let the launcher exit, for the reason given
+    GiveUp(String),
+}
+
+pub struct Supervisor {
+    policy: RestartPolicy,
+    restarts: u32,
+    backoff: Duration,
+    crashes: VecDeque<Instant>,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        let backoff = Duration::from_secs(policy.backoff_secs);
+        Self { policy, restarts: 0, backoff, crashes: VecDeque::new() }
+    }
+
+    /// Restarts so far
+    pub fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    /// What to do about a backend that crashed at `now` after running for `uptime`
+    pub fn on_crash(&mut self, uptime: Duration, now: Instant) -> Decision {
+        if !self.policy.enabled {
+            return Decision::GiveUp("automatic restarts are off".to_string());
+        }
+        let window = Duration::from_secs(self.policy.crash_loop_window_secs);
+        self.crashes.push_back(now);
+        while self.crashes.front().is_some_and(|crash| now.duration_since(*crash) > window) {
+            self.crashes.pop_front();
+        }
+        if self.crashes.len() as u32 >= self.policy.crash_loop_crashes.max(1) {
+            return Decision::GiveUp(format!(
+                "it crashed {} times within {}s",
+                self.crashes.len(),
+                self.policy.crash_loop_window_secs
+            ));
+        }
+        if self.restarts >= self.policy.max_restarts {
+            return Decision::GiveUp(format!("it was restarted {} times already", self.restarts));
+        }
+
+        if uptime >= Duration::from_secs(self.policy.stable_after_secs) {
+            self.backoff = Duration::from_secs(self.policy.backoff_secs);
+        }
+        let after = self.backoff;
+        self.backoff = (self.backoff * 2).min(Duration::from_secs(self.policy.max_backoff_secs));
+        self.restarts += 1;
+        Decision::Restart { after, attempt: self.restarts }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn backs_off_and_resets_after_a_stable_run() {
+        let mut supervisor = Supervisor::new(RestartPolicy { max_backoff_secs: 3, ..RestartPolicy::default() });
+        let start = Instant::now();
+        let delays: Vec<_> = (0..4)
+            .map(|i| match supervisor.on_crash(MINUTE, start + MINUTE * 2 * i) {
+                Decision::Restart { after, .. } => after.as_secs(),
+                Decision::GiveUp(reason) => panic!("gave up: {}", reason),
+            })
+            .collect();
+        assert_eq!(delays, [1, 2, 3, 3]);
+        assert_eq!(supervisor.on_crash(10 * MINUTE, start + MINUTE * 10), Decision::Restart { after: Duration::from_secs(1), attempt: 5 });
+        assert_eq!(supervisor.restarts(), 5);
+        assert!(matches!(supervisor.on_crash(10 * MINUTE, start + MINUTE * 20), Decision::GiveUp(reason) if reason.contains("5 times")));
+    }
+
+    #[test]
+    fn gives_up_on_a_crash_loop() {
+        let mut supervisor = Supervisor::new(RestartPolicy::default());
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        assert!(matches!(supervisor.on_crash(second, start), Decision::Restart { attempt: 1, .. }));
+        assert!(matches!(supervisor.on_crash(second, start + 2 * MINUTE), Decision::Restart { attempt: 2, .. }));
+        assert!(matches!(supervisor.on_crash(second, start + 2 * MINUTE + 5 * second), Decision::Restart { attempt: 3, .. }));
+        let decision = supervisor.on_crash(second, start + 2 * MINUTE + 10 * second);
+        assert_eq!(decision, Decision::GiveUp("it crashed 3 times within 60s".to_string()));
+
+        let mut off = Supervisor::new(RestartPolicy { enabled: false, ..RestartPolicy::default() });
+        assert!(matches!(off.on_crash(second, start), Decision::GiveUp(_)));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::launcher::installer::{install_requirements, InstallPlan};
+use crate::launcher::interpreter::{venv_python, PythonCandidate, PythonSource};
+use crate::launcher::paths::long_path;
+use crate::launcher::process::{CommandSpec, ProcessRunner};
+use crate::launcher::retry::with_io_retry;
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const VENV_STATE_FILE_NAME: &str = "venv.json";
+
+/// What the launcher remembers about the virtual environment it created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenvRecord {
+    pub dir: PathBuf,
+    /// Interpreter inside the environment
+    pub python: PathBuf,
+    /// Interpreter the environment was created from
+    pub base_python: PathBuf,
+    /// RFC 3339 timestamp of when it was created
+    pub created_at: String,
+    /// The backend's requirements finished installing; an environment whose install was
+    /// interrupted is installed into again
+    pub installed: bool,
+}
+
+/// State of the managed environment as found at startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VenvHealth {
+    /// Never created
+    Missing,
+    /// Its interpreter is gone or doesn't start, e.g. because the Python it was
+    /// created from was uninstalled
+    Broken(String),
+    /// Created, but the requirements never finished installing
+    Incomplete(PathBuf),
+    Ready(PathBuf),
+}
+
+/// Private virtual environment the launcher creates and installs the backend's
+/// requirements into, so nothing is installed into the user's global Python
+pub struct ManagedVenv {
+    dir: PathBuf,
+    record: StateFile<VenvRecord>,
+}
+
+impl ManagedVenv {
+    pub fn new(dir: &Path, state_dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf(), record: StateFile::new(state_dir.join(VENV_STATE_FILE_NAME)) }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether an earlier launch created the environment, so later launches keep using it
+    pub fn is_recorded(&self) -> bool {
+        matches!(self.record.read(), Ok(Some(_)))
+    }
+
+    pub fn check(&self, runner: &dyn ProcessRunner) -> VenvHealth {
+        let record = self.record.read().ok().flatten().filter(|record| record.dir == self.dir);
+        let Some(python) = venv_python(&self.dir) else {
+            return match record.is_some() || self.dir.exists() {
+                true => VenvHealth::Broken(format!("{} has no Python interpreter", self.dir.display())),
+                false => VenvHealth::Missing,
+            };
+        };
+        match runner.output(&CommandSpec::new(&python).args(["-c", "import sys"])) {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return VenvHealth::Broken(format!("{} fails to start: {}", python.display(), stderr.trim()));
+            }
+            Err(e) => return VenvHealth::Broken(format!("{} fails to start: {}", python.display(), e)),
+        }
+        match record {
+            Some(record) if record.installed => VenvHealth::Ready(python),
+            _ => VenvHealth::Incomplete(python),
+        }
+    }
+
+    /// Create the environment from `base`, replacing whatever is left of a broken one.
+    /// Returns its interpreter.
+    pub fn create(&self, runner: &dyn ProcessRunner, base: &PythonCandidate) -> Result<PathBuf, String> {
+        if self.dir.exists() {
+            with_io_retry("Removing broken virtual environment", || std::fs::remove_dir_all(&self.dir))
+                .map_err(|e| format!("Failed to remove {}: {}", self.dir.display(), e))?;
+        }
+        if let Some(parent) = self.dir.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let spec = base.command().args(["-m", "venv"]).arg(long_path(&self.dir));
+        let output = runner.output(&spec).map_err(|e| format!("Failed to run {}: {}", base.python.display(), e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Creating a virtual environment with {} failed: {}", base.python.display(), stderr.trim()));
+        }
+        let python = venv_python(&self.dir)
+            .ok_or_else(|| format!("{} -m venv did not create an interpreter in {}", base.python.display(), self.dir.display()))?;
+        self.record.write(&VenvRecord {
+            dir: self.dir.clone(),
+            python: python.clone(),
+            base_python: base.python.clone(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            installed: false,
+        })?;
+        Ok(python)
+    }
+
+    /// Install `requirements` into the environment as `plan` says, reporting progress
+    /// to `on_progress`
+    pub fn install(
+        &self,
+        runner: &dyn ProcessRunner,
+        plan: &InstallPlan,
+        python: &Path,
+        requirements: &Path,
+        on_progress: &dyn Fn(f32),
+    ) -> Result<(), String> {
+        install_requirements(runner, plan, python, &long_path(requirements), on_progress)?;
+        self.record.update(|record| record.map(|record| VenvRecord { installed: true, ..record }))?;
+        Ok(())
+    }
+
+    pub fn candidate(&self, python: PathBuf) -> PythonCandidate {
+        PythonCandidate { python, source: PythonSource::Managed(self.dir.clone()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::gpu::TorchIndex;
+    use crate::launcher::installer::{Installer, PackageSource};
+    use crate::launcher::process::fake::{FakeRunner, Script};
+
+    fn fake_python(venv: &Path) -> PathBuf {
+        let bin = venv.join(if cfg!(windows) { "Scripts" } else { "bin" });
+        std::fs::create_dir_all(&bin).unwrap();
+        let python = bin.join(if cfg!(windows) { "python.exe" } else { "python3" });
+        std::fs::write(&python, "").unwrap();
+        python
+    }
+
+    #[test]
+    fn tells_missing_broken_incomplete_and_ready_apart() {
+        let tmp = tempfile::tempdir().unwrap();
+        let venv = ManagedVenv::new(&tmp.path().join("venv"), tmp.path());
+        assert_eq!(venv.check(&FakeRunner::new()), VenvHealth::Missing);
+
+        std::fs::create_dir_all(venv.dir()).unwrap();
+        assert!(matches!(venv.check(&FakeRunner::new()), VenvHealth::Broken(_)));
+
+        let python = fake_python(venv.dir());
+        let runner = FakeRunner::new();
+        runner.script(&python.to_string_lossy(), Script::exits(1).stderr("No Python at 'C:\\Python312\\python.exe'"));
+        let VenvHealth::Broken(reason) = venv.check(&runner) else { panic!("expected a broken venv") };
+        assert!(reason.contains("No Python at"));
+
+        let runner = FakeRunner::new();
+        runner.script(&python.to_string_lossy(), Script::exits(0));
+        assert_eq!(venv.check(&runner), VenvHealth::Incomplete(python.clone()));
+        venv.record
+            .write(&VenvRecord {
+                dir: venv.dir().to_path_buf(),
+                python: python.clone(),
+                base_python: PathBuf::from("python"),
+                created_at: String::new(),
+                installed: false,
+            })
+            .unwrap();
+        let plan = InstallPlan { installer: Installer::Pip, source: PackageSource::Index, torch: Some(TorchIndex::Cpu), index: Default::default() };
+        venv.install(&runner, &plan, &python, Path::new("requirements.txt"), &|_| {}).unwrap();
+        assert_eq!(runner.calls().last().unwrap().args[..3], ["-m", "pip", "install"]);
+        assert!(runner.calls().iter().any(|c| c.args.iter().any(|a| a == "https://download.pytorch.org/whl/cpu")));
+        assert_eq!(venv.check(&runner), VenvHealth::Ready(python));
+        assert!(venv.is_recorded());
+    }
+
+    #[test]
+    fn creation_replaces_a_broken_environment_and_checks_the_result() {
+        let tmp = tempfile::tempdir().unwrap();
+        let venv = ManagedVenv::new(&tmp.path().join("venv"), tmp.path());
+        std::fs::create_dir_all(venv.dir().join("Lib")).unwrap();
+        let base = PythonCandidate { python: PathBuf::from("python"), source: PythonSource::Path };
+
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(0));
+        let err = venv.create(&runner, &base).unwrap_err();
+        assert!(err.contains("did not create an interpreter"), "{}", err);
+        assert!(!venv.dir().join("Lib").exists());
+        assert_eq!(runner.calls()[0].args[..2], ["-m", "venv"]);
+        assert!(!venv.is_recorded());
+
+        let runner = FakeRunner::new();
+        runner.script("python", Script::exits(1).stderr("ensurepip is not available"));
+        assert!(venv.create(&runner, &base).unwrap_err().contains("ensurepip"));
+    }
+}
@@ -0,0 +1,102 @@
+use semver::Version;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// Backend HTTP API versions this launcher can talk to (`API_VERSION` in
+/// backend/__init__.py)
+pub const SUPPORTED_API_VERSIONS: RangeInclusive<u32> = 1..=1;
+/// Oldest backend release this launcher works with
+pub const MIN_BACKEND_VERSION: &str = "0.2.0";
+
+/// Versions a backend checkout declares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendVersion {
+    pub version: Version,
+    /// `None` for backends that predate the handshake
+    pub api_version: Option<u32>,
+}
+
+/// Value of a top-level `NAME = value` assignment, with string quotes removed
+fn assignment<'a>(source: &'a str, name: &str) -> Option<&'a str> {
+    source.lines().find_map(|line| {
+        let (lhs, rhs) = line.split_once('=')?;
+        (lhs.trim() == name).then(|| {
+            let value = rhs.split('#').next().unwrap_or(rhs).trim();
+            value.trim_matches(|c| c == '"' || c == '\'')
+        })
+    })
+}
+
+pub fn parse_backend_version(init_py: &str) -> Result<BackendVersion, String> {
+    let raw = assignment(init_py, "__version__").ok_or("backend/__init__.py has no __version__")?;
+    let version = Version::parse(raw).map_err(|e| format!("Invalid backend version {:?}: {}", raw, e))?;
+    let api_version = match assignment(init_py, "API_VERSION") {
+        Some(raw) => Some(raw.parse().map_err(|_| format!("Invalid backend API_VERSION {:?}", raw))?),
+        None => None,
+    };
+    Ok(BackendVersion { version, api_version })
+}
+
+/// Read the versions declared by the backend package in `backend_dir`
+pub fn read_backend_version(backend_dir: &Path) -> Result<BackendVersion, String> {
+    let path = backend_dir.join("__init__.py");
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_backend_version(&source)
+}
+
+/// Check a backend against what this launcher supports, describing any mismatch in
+/// terms a user can act on
+pub fn check_compatible(backend: &BackendVersion) -> Result<(), String> {
+    let app = env!("CARGO_PKG_VERSION");
+    let min = Version::parse(MIN_BACKEND_VERSION).expect("MIN_BACKEND_VERSION is valid semver");
+    if backend.version < min {
+        return Err(format!(
+            "Voicebox {} requires backend >= {}, but the installed backend is {}",
+            app, min, backend.version
+        ));
+    }
+    // Backends without API_VERSION predate the handshake and speak API 1
+    let api = backend.api_version.unwrap_or(1);
+    if !SUPPORTED_API_VERSIONS.contains(&api) {
+        let newer = api > *SUPPORTED_API_VERSIONS.end();
+        return Err(format!(
+            "Voicebox {} supports backend API {}-{}, but backend {} uses API {}; {}",
+            app,
+            SUPPORTED_API_VERSIONS.start(),
+            SUPPORTED_API_VERSIONS.end(),
+            backend.version,
+            api,
+            if newer { "update the app" } else { "update the backend" }
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions_from_init_py() {
+        let parsed = parse_backend_version("# Backend package\n\n__version__ = \"0.3.1\"\nAPI_VERSION = 1  # comment\n").unwrap();
+        assert_eq!(parsed.version, Version::new(0, 3, 1));
+        assert_eq!(parsed.api_version, Some(1));
+
+        let legacy = parse_backend_version("__version__ = '0.2.0'\n").unwrap();
+        assert_eq!(legacy.api_version, None);
+        assert!(parse_backend_version("API_VERSION = 1\n").is_err());
+    }
+
+    #[test]
+    fn rejects_old_backends_and_unknown_apis() {
+        let backend = |v: &str, api| BackendVersion { version: Version::parse(v).unwrap(), api_version: api };
+        assert!(check_compatible(&backend("0.2.0", Some(1))).is_ok());
+        assert!(check_compatible(&backend("0.2.0", None)).is_ok());
+
+        let old = check_compatible(&backend("0.1.9", Some(1))).unwrap_err();
+        assert!(old.contains("requires backend >= 0.2.0"), "{}", old);
+        let newer = check_compatible(&backend("0.9.0", Some(7))).unwrap_err();
+        assert!(newer.ends_with("update the app"), "{}", newer);
+    }
+}
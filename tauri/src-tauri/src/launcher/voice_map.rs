@@ -0,0 +1,166 @@
+use crate::launcher::script::{csv_records, ScriptError, ScriptLine};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// First column of a CSV voice table; every other column is a language
+const CHARACTER: &str = "character";
+
+/// Which voice speaks each character of a dialogue script in each language, so one
+/// script can be rendered for every localization.
+///
+/// Read from CSV (`character,en,de,...` with voice profile IDs in the cells) or from
+/// JSON (`{"knight": {"en": "...", "de": "..."}}`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VoiceMap {
+    /// Character -> language -> voice profile ID
+    voices: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl VoiceMap {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read voice table {}: {}", path.display(), e))?;
+        Self::parse(path, &content).map_err(|e| format!("Voice table {}: {}", path.display(), e))
+    }
+
+    pub fn parse(path: &Path, content: &str) -> Result<Self, String> {
+        let mut map = Self::default();
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            let table: BTreeMap<String, BTreeMap<String, Value>> = serde_json::from_str(content)
+                .map_err(|e| format!("Expected an object of characters to {{language: voice}}: {}", e))?;
+            for (character, languages) in table {
+                for (language, voice) in languages {
+                    match voice {
+                        Value::String(voice) => map.insert(&character, &language, &voice),
+                        Value::Null => {}
+                        other => return Err(format!("Voice of {} in {} is not a string: {}", character, language, other)),
+                    }
+                }
+            }
+            return Ok(map);
+        }
+
+        let mut records = csv_records(content)?.into_iter();
+        let (_, header) = records.next().ok_or("The voice table is empty")?;
+        let header: Vec<String> = header.iter().map(|name| name.trim().to_string()).collect();
+        if header.first().map(|name| name.to_lowercase()).as_deref() != Some(CHARACTER) || header.len() < 2 {
+            return Err(format!("The header row needs a `{}` column followed by one column per language", CHARACTER));
+        }
+        for (row, record) in records {
+            if record.len() > header.len() {
+                return Err(format!("Row {}: {} cells but only {} columns", row, record.len(), header.len()));
+            }
+            let character = record[0].trim();
+            if character.is_empty() {
+                return Err(format!("Row {}: no character", row));
+            }
+            if map.voices.contains_key(character) {
+                return Err(format!("Row {}: character {:?} is listed twice", row, character));
+            }
+            for (language, voice) in header.iter().zip(&record).skip(1) {
+                map.insert(character, language, voice);
+            }
+        }
+        Ok(map)
+    }
+
+    fn insert(&mut self, character: &str, language: &str, voice: &str) {
+        let (character, language, voice) = (character.trim(), language.trim(), voice.trim());
+        if character.is_empty() || language.is_empty() || voice.is_empty() {
+            return;
+        }
+        self.voices.entry(character.to_string()).or_default().insert(language.to_string(), voice.to_string());
+    }
+
+    /// Every language at least one character has a voice for
+    pub fn languages(&self) -> BTreeSet<String> {
+        self.voices.values().flat_map(|languages| languages.keys().cloned()).collect()
+    }
+
+    pub fn voice(&self, character: &str, language: &str) -> Option<&str> {
+        self.voices.get(character)?.get(language).map(String::as_str)
+    }
+
+    /// The script's lines as rendered in `language`: characters get their voice for it,
+    /// the backend is asked for that language and files go into a `<language>/`
+    /// subdirectory. A line's own `voice` wins over its character's. Characters without
+    /// a voice in this language are reported together, like the script's own problems.
+    pub fn localize(&self, lines: &[ScriptLine], language: &str) -> Result<Vec<ScriptLine>, Vec<ScriptError>> {
+        let mut errors = Vec::new();
+        let localized = lines
+            .iter()
+            .map(|line| {
+                let mut line = line.clone();
+                if let (None, Some(character)) = (&line.voice, &line.character) {
+                    match self.voice(character, language) {
+                        Some(voice) => line.voice = Some(voice.to_string()),
+                        None if !self.voices.contains_key(character) => errors.push(ScriptError {
+                            line: line.line,
+                            message: format!("Character {:?} is not in the voice table", character),
+                        }),
+                        None => errors.push(ScriptError {
+                            line: line.line,
+                            message: format!("Character {:?} has no voice for language {}", character, language),
+                        }),
+                    }
+                }
+                line.params.insert("language".to_string(), Value::String(language.to_string()));
+                if !line.filename.is_empty() {
+                    line.filename = format!("{}/{}", language, line.filename);
+                }
+                line
+            })
+            .collect();
+        match errors.is_empty() {
+            true => Ok(localized),
+            false => Err(errors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::script;
+
+    #[test]
+    fn reads_csv_and_json_tables() {
+        let csv = VoiceMap::parse(Path::new("voices.csv"), "Character,en,de\nknight,knight-en,ritter\nguard,guard-en,\n").unwrap();
+        let json = VoiceMap::parse(
+            Path::new("voices.json"),
+            r#"{"knight": {"en": "knight-en", "de": "ritter"}, "guard": {"en": "guard-en", "de": null}}"#,
+        )
+        .unwrap();
+        assert_eq!(csv, json);
+        assert_eq!(csv.languages(), BTreeSet::from(["de".to_string(), "en".to_string()]));
+        assert_eq!(csv.voice("knight", "de"), Some("ritter"));
+        assert_eq!(csv.voice("guard", "de"), None);
+
+        assert!(VoiceMap::parse(Path::new("voices.csv"), "name,en\nknight,a\n").is_err());
+        let twice = VoiceMap::parse(Path::new("voices.csv"), "character,en\nknight,a\nknight,b\n").unwrap_err();
+        assert!(twice.contains("listed twice"));
+    }
+
+    #[test]
+    fn localizes_lines_per_language() {
+        let table = VoiceMap::parse(Path::new("voices.csv"), "character,en,de\nknight,knight-en,ritter\nguard,guard-en,\n").unwrap();
+        let lines = script::parse(
+            Path::new("lines.csv"),
+            "filename,character,voice,text\nk1,knight,,Halt\ng1,guard,,Who?\nn1,,narrator,Later\n",
+        )
+        .unwrap();
+
+        let en = table.localize(&lines, "en").unwrap();
+        assert_eq!(en[0].voice.as_deref(), Some("knight-en"));
+        assert_eq!(en[0].filename, "en/k1.wav");
+        assert_eq!(en[0].params["language"], "en");
+        assert_eq!(en[2].voice.as_deref(), Some("narrator"));
+        assert!(!en[0].params.contains_key("character"));
+
+        let errors = table.localize(&lines, "de").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+        assert!(errors[0].message.contains("no voice for language de"));
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Root that stands for the data dir itself; projects not assigned elsewhere live there
+pub const DEFAULT_ROOT: &str = "default";
+/// Folder of a root that holds one data dir per project
+pub const PROJECTS_DIR_NAME: &str = "projects";
+
+/// A location projects can be kept in, such as a fast local disk or a NAS share
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataRoot {
+    pub path: PathBuf,
+    /// Where the backend sees the same location when it runs elsewhere, e.g. a share
+    /// mounted at `/mnt/nas` inside WSL or a container; `path` when unset
+    #[serde(default)]
+    pub backend_path: Option<PathBuf>,
+}
+
+/// Data roots and which of them each project is kept in, from the `workspace`
+/// section of the launcher config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    /// Roots by name, besides the implicit `default` root at the data dir
+    pub roots: BTreeMap<String, DataRoot>,
+    /// Root name by project name
+    pub projects: BTreeMap<String, String>,
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) && name != ".."
+}
+
+/// Replace the leading `from` of `path` with `to`, if `path` is under `from`
+fn rebase(path: &Path, from: &Path, to: &Path) -> Option<PathBuf> {
+    path.strip_prefix(from).ok().map(|rest| if rest.as_os_str().is_empty() { to.to_path_buf() } else { to.join(rest) })
+}
+
+impl WorkspaceConfig {
+    /// The root called `name`; `default` is `data_dir` unless the config redefines it
+    pub fn root(&self, data_dir: &Path, name: &str) -> Result<DataRoot, String> {
+        match self.roots.get(name) {
+            Some(root) => Ok(root.clone()),
+            None if name == DEFAULT_ROOT => Ok(DataRoot { path: data_dir.to_path_buf(), backend_path: None }),
+            None => Err(format!("Unknown data root {:?}", name)),
+        }
+    }
+
+    /// Name of the root `project` is assigned to
+    pub fn project_root(&self, project: &str) -> &str {
+        self.projects.get(project).map_or(DEFAULT_ROOT, String::as_str)
+    }
+
+    /// Data dir of `project` under its root
+    pub fn project_dir(&self, data_dir: &Path, project: &str) -> Result<PathBuf, String> {
+        self.project_dir_in(data_dir, self.project_root(project), project)
+    }
+
+    /// Data dir `project` has, or would have, in the root called `root`
+    pub fn project_dir_in(&self, data_dir: &Path, root: &str, project: &str) -> Result<PathBuf, String> {
+        if !is_valid_name(project) {
+            return Err(format!("Invalid project name {:?}: use letters, digits, -, _ and .", project));
+        }
+        Ok(self.root(data_dir, root)?.path.join(PROJECTS_DIR_NAME).join(project))
+    }
+
+    /// Roots with a separate backend location, deepest first so nested roots win
+    fn translated_roots(&self) -> Vec<(&Path, &Path)> {
+        let mut roots: Vec<(&Path, &Path)> = self
+            .roots
+            .values()
+            .filter_map(|root| root.backend_path.as_deref().map(|backend| (root.path.as_path(), backend)))
+            .collect();
+        roots.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+        roots
+    }
+
+    /// `path` as the backend sees it. Paths outside every translated root are
+    /// passed through unchanged.
+    pub fn to_backend(&self, path: &Path) -> PathBuf {
+        self.translated_roots()
+            .into_iter()
+            .find_map(|(host, backend)| rebase(path, host, backend))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// A path the backend reported, such as a generation's `audio_path`, as seen here
+    pub fn from_backend(&self, path: &Path) -> PathBuf {
+        let mut roots = self.translated_roots();
+        roots.sort_by_key(|(_, backend)| std::cmp::Reverse(backend.components().count()));
+        roots
+            .into_iter()
+            .find_map(|(host, backend)| rebase(path, backend, host))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> WorkspaceConfig {
+        let mut config = WorkspaceConfig::default();
+        config.roots.insert(
+            "nas".to_string(),
+            DataRoot { path: PathBuf::from("/Volumes/studio"), backend_path: Some(PathBuf::from("/mnt/studio")) },
+        );
+        config.roots.insert("ssd".to_string(), DataRoot { path: PathBuf::from("/fast/voicebox"), backend_path: None });
+        config.projects.insert("archive-2025".to_string(), "nas".to_string());
+        config.projects.insert("spot".to_string(), "ssd".to_string());
+        config.projects.insert("lost".to_string(), "usb".to_string());
+        config
+    }
+
+    #[test]
+    fn places_projects_in_their_roots() {
+        let config = workspace();
+        let data_dir = Path::new("/home/ada/.voicebox");
+        assert_eq!(config.project_dir(data_dir, "archive-2025").unwrap(), Path::new("/Volumes/studio/projects/archive-2025"));
+        assert_eq!(config.project_dir(data_dir, "spot").unwrap(), Path::new("/fast/voicebox/projects/spot"));
+        assert_eq!(config.project_dir(data_dir, "new").unwrap(), Path::new("/home/ada/.voicebox/projects/new"));
+        assert!(config.project_dir(data_dir, "lost").unwrap_err().contains("usb"));
+        assert!(config.project_dir(data_dir, "..").is_err());
+        assert!(config.project_dir(data_dir, "a/b").is_err());
+    }
+
+    #[test]
+    fn translates_paths_for_the_backend() {
+        let config = workspace();
+        let host = Path::new("/Volumes/studio/projects/archive-2025");
+        let backend = config.to_backend(host);
+        assert_eq!(backend, Path::new("/mnt/studio/projects/archive-2025"));
+        assert_eq!(config.from_backend(&backend.join("generations/a.wav")), host.join("generations/a.wav"));
+        // Only whole path components match
+        assert_eq!(config.to_backend(Path::new("/Volumes/studio2/x")), Path::new("/Volumes/studio2/x"));
+        assert_eq!(config.to_backend(Path::new("/fast/voicebox/x")), Path::new("/fast/voicebox/x"));
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+
+/// Whether this process runs inside the Windows Subsystem for Linux
+pub fn running_in_wsl() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+    if std::env::var_os("WSL_DISTRO_NAME").is_some() || std::env::var_os("WSL_INTEROP").is_some() {
+        return true;
+    }
+    std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|release| release.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+/// `C:\Users\me` (or `C:/Users/me`) -> `/mnt/c/Users/me`
+pub fn windows_to_wsl(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    if bytes.len() < 2 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' {
+        return None;
+    }
+    let rest = path[2..].replace('\\', "/");
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    Some(format!("/mnt/{}{}", (bytes[0] as char).to_ascii_lowercase(), rest))
+}
+
+/// `/mnt/c/Users/me` -> `C:\Users\me`
+pub fn wsl_to_windows(path: &str) -> Option<String> {
+    let rest = path.strip_prefix("/mnt/")?;
+    let mut chars = rest.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    let tail = chars.as_str();
+    if !tail.is_empty() && !tail.starts_with('/') {
+        return None;
+    }
+    let tail = if tail.is_empty() { "\\".to_string() } else { tail.replace('/', "\\") };
+    Some(format!("{}:{}", drive.to_ascii_uppercase(), tail))
+}
+
+/// Interpret a path handed to us by a Windows caller when we're running inside WSL
+pub fn host_path(path: PathBuf) -> PathBuf {
+    if running_in_wsl() {
+        if let Some(translated) = path.to_str().and_then(windows_to_wsl) {
+            return PathBuf::from(translated);
+        }
+    }
+    path
+}
+
+/// Whether `python` lives inside a WSL distro (`\\wsl$\...`, `\\wsl.localhost\...`) or
+/// is the `wsl.exe` bridge itself
+pub fn is_wsl_interpreter(python: &Path) -> bool {
+    let lower = python.to_string_lossy().to_lowercase().replace('/', "\\");
+    let lower = lower.strip_prefix(r"\\?\unc\").map(|s| format!(r"\\{}", s)).unwrap_or(lower);
+    let file_name = lower.rsplit('\\').next().unwrap_or_default();
+    lower.starts_with(r"\\wsl$\")
+        || lower.starts_with(r"\\wsl.localhost\")
+        || file_name == "wsl.exe"
+}
+
+/// Refuse mixed Windows/WSL setups that can't work, with guidance on fixing them
+pub fn check_interpreter(python: &Path) -> Result<(), String> {
+    if cfg!(windows) && is_wsl_interpreter(python) {
+        return Err(format!(
+            "The selected Python interpreter {} runs inside WSL, but the Voicebox launcher is a Windows program. \
+             Install Python for Windows (python.org or `winget install Python.Python.3.12`), or run voicebox-server from inside WSL instead.",
+            python.display()
+        ));
+    }
+    if running_in_wsl() && python.to_string_lossy().to_lowercase().ends_with(".exe") {
+        return Err(format!(
+            "The selected Python interpreter {} is a Windows executable, but the launcher is running inside WSL. \
+             Use a Linux Python inside the distro (e.g. `sudo apt install python3 python3-venv`).",
+            python.display()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_between_windows_and_wsl_paths() {
+        assert_eq!(windows_to_wsl(r"C:\Users\me\AppData").as_deref(), Some("/mnt/c/Users/me/AppData"));
+        assert_eq!(windows_to_wsl("D:/data").as_deref(), Some("/mnt/d/data"));
+        assert_eq!(windows_to_wsl(r"C:relative"), None);
+        assert_eq!(windows_to_wsl("/home/me"), None);
+
+        assert_eq!(wsl_to_windows("/mnt/c/Users/me").as_deref(), Some(r"C:\Users\me"));
+        assert_eq!(wsl_to_windows("/mnt/e").as_deref(), Some(r"E:\"));
+        assert_eq!(wsl_to_windows("/mnt/wsl/shared"), None);
+        assert_eq!(wsl_to_windows("/home/me"), None);
+    }
+
+    #[test]
+    fn recognizes_wsl_interpreters() {
+        assert!(is_wsl_interpreter(Path::new(r"\\wsl$\Ubuntu\usr\bin\python3")));
+        assert!(is_wsl_interpreter(Path::new(r"\\wsl.localhost\Debian\usr\bin\python3")));
+        assert!(is_wsl_interpreter(Path::new(r"C:\Windows\System32\wsl.exe")));
+        assert!(!is_wsl_interpreter(Path::new(r"C:\Python312\python.exe")));
+    }
+}
@@ -0,0 +1,222 @@
+// Backends left running by a launcher that is gone.
+//
+// A launcher that is killed outright, by SIGKILL, a crash or a debugger, can't stop its
+// backend. On Windows the job object ends it together with the launcher; elsewhere it
+// keeps running and holding the port, and the next backend fails with "address already
+// in use". Once a launcher holds the instance lock, a `runtime.json` still on disk was
+// left by one that is gone. Its backend is still there if the PID runs a process named
+// like Python or Voicebox, or if a Voicebox backend answers `/health` on its port. The
+// name check keeps a PID the system has since given to another program from counting.
+use crate::launcher::log::log;
+use crate::launcher::process::{CommandSpec, ProcessRunner};
+use crate::launcher::shutdown;
+use crate::launcher::state::RuntimeState;
+use serde::Serialize;
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+/// How long a killed backend gets to disappear
+const KILL_WAIT: Duration = Duration::from_secs(3);
+
+/// A backend an earlier launch left running
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LeftBackend {
+    /// Known from the stale runtime state and still running
+    pub pid: Option<u32>,
+    /// Process name of `pid`
+    pub name: Option<String>,
+    pub port: u16,
+    /// Whether a Voicebox backend answers `/health` on the port
+    pub answers_health: bool,
+}
+
+impl LeftBackend {
+    pub fn describe(&self) -> String {
+        match (self.pid, &self.name) {
+            (Some(pid), Some(name)) => format!("A backend left running by an earlier launch ({} with PID {}) uses port {}", name, pid, self.port),
+            _ => format!("A Voicebox backend from an earlier launch answers on port {}", self.port),
+        }
+    }
+}
+
+/// Name of the program in `ps -o comm=` output
+fn parse_ps(stdout: &str) -> Option<String> {
+    let name = stdout.lines().next()?.trim();
+    let name = name.rsplit('/').next().unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Image name in `tasklist /FO CSV /NH` output, which is an `INFO:` line when no
+/// process matches
+fn parse_tasklist(stdout: &str) -> Option<String> {
+    let line = stdout.lines().map(str::trim).find(|line| line.starts_with('"'))?;
+    let name = line.trim_start_matches('"').split('"').next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Name of the process with `pid`, or `None` if none runs
+pub fn process_name(runner: &dyn ProcessRunner, pid: u32) -> Option<String> {
+    let pid = pid.to_string();
+    let filter = format!("PID eq {}", pid);
+    let (spec, parse): (CommandSpec, fn(&str) -> Option<String>) = if cfg!(windows) {
+        (CommandSpec::new("tasklist").args(["/FI", filter.as_str(), "/FO", "CSV", "/NH"]), parse_tasklist)
+    } else {
+        (CommandSpec::new("ps").args(["-p", pid.as_str(), "-o", "comm="]), parse_ps)
+    };
+    let output = runner.output(&spec).ok().filter(|output| output.status.success())?;
+    parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Whether a process of this name could be a backend: Python, or a frozen Voicebox server
+pub fn looks_like_backend(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["python", "voicebox", "uvicorn"].iter().any(|known| name.contains(known))
+}
+
+fn port_open(port: u16) -> bool {
+    TcpStream::connect_timeout(&SocketAddr::from(([127, 0, 0, 1], port)), PROBE_TIMEOUT).is_ok()
+}
+
+/// Whether what listens on `port` answers `/health` the way a Voicebox backend does
+fn answers_health(port: u16) -> bool {
+    if !port_open(port) {
+        return false;
+    }
+    let url = format!("http://127.0.0.1:{}/health", port);
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .and_then(|client| client.get(&url).send())
+        .and_then(|response| response.json::<serde_json::Value>())
+        .is_ok_and(|health| health.get("model_loaded").is_some())
+}
+
+/// The backend of the launcher that left `stale` behind, or a backend answering on
+/// `port` when there is no stale state, if one still runs
+pub fn find(runner: &dyn ProcessRunner, stale: Option<&RuntimeState>, port: u16) -> Option<LeftBackend> {
+    let port = stale.map_or(port, |state| state.backend_port.unwrap_or(state.port));
+    let running = stale.and_then(|state| Some(state.backend_pid).zip(process_name(runner, state.backend_pid)));
+    let (pid, name) = running.filter(|(_, name)| looks_like_backend(name)).unzip();
+    let answers_health = answers_health(port);
+    (pid.is_some() || answers_health).then_some(LeftBackend { pid, name, port, answers_health })
+}
+
+fn gone(runner: &dyn ProcessRunner, left: &LeftBackend) -> bool {
+    match left.pid {
+        Some(pid) => !process_name(runner, pid).is_some_and(|name| looks_like_backend(&name)),
+        None => !port_open(left.port),
+    }
+}
+
+fn wait_until_gone(runner: &dyn ProcessRunner, left: &LeftBackend, timeout: Duration) -> bool {
+    let until = Instant::now() + timeout;
+    loop {
+        if gone(runner, left) {
+            return true;
+        }
+        if Instant::now() >= until {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+/// Signal the process tree of `pid`; it leads a process group of its own on Unix
+fn signal_tree(runner: &dyn ProcessRunner, pid: u32, force: bool) {
+    #[cfg(unix)]
+    {
+        let _ = runner;
+        let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+        // SAFETY: only sends signals to the group and the process
+        unsafe {
+            libc::killpg(pid as libc::pid_t, signal);
+            libc::kill(pid as libc::pid_t, signal);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let pid = pid.to_string();
+        let spec = CommandSpec::new("taskkill").args(["/PID", pid.as_str(), "/T"]);
+        let _ = runner.output(&if force { spec.arg("/F") } else { spec });
+    }
+}
+
+/// Stop a left-behind backend: ask it through `/shutdown`, then signal its processes,
+/// then kill them, giving it half of `grace` after each of the first two
+pub fn terminate(runner: &dyn ProcessRunner, left: &LeftBackend, grace: Duration) -> Result<(), String> {
+    if left.answers_health && shutdown::request_backend_shutdown(left.port) {
+        log("Launcher: Asked the left-behind backend to shut down");
+        if wait_until_gone(runner, left, grace / 2) {
+            return Ok(());
+        }
+    }
+    let Some(pid) = left.pid else {
+        return Err(format!("The backend on port {} did not shut down, and which process it is isn't known", left.port));
+    };
+    signal_tree(runner, pid, false);
+    if wait_until_gone(runner, left, grace / 2) {
+        return Ok(());
+    }
+    log(&format!("Launcher: Left-behind backend {} still running; killing its process tree", pid));
+    signal_tree(runner, pid, true);
+    match wait_until_gone(runner, left, KILL_WAIT) {
+        true => Ok(()),
+        false => Err(format!("Backend process {} is still running after being killed", pid)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::process::fake::{FakeRunner, Script};
+    use std::path::PathBuf;
+
+    fn unused_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    fn stale(backend_pid: u32, port: u16) -> RuntimeState {
+        RuntimeState {
+            pid: 1,
+            backend_pid,
+            host: "127.0.0.1".to_string(),
+            port,
+            backend_port: None,
+            interpreter: None,
+            data_dir: PathBuf::from("data"),
+            log_path: PathBuf::from("launcher.log"),
+            started_at: "2026-10-01T10:00:00Z".to_string(),
+            version: "0.1.0".to_string(),
+            restarts: 0,
+        }
+    }
+
+    #[test]
+    fn reads_process_names() {
+        assert_eq!(parse_ps("/usr/bin/python3.11\n").as_deref(), Some("python3.11"));
+        assert_eq!(parse_ps(""), None);
+        assert_eq!(parse_tasklist("\"python.exe\",\"4321\",\"Console\",\"1\",\"812,104 K\"\r\n").as_deref(), Some("python.exe"));
+        assert_eq!(parse_tasklist("INFO: No tasks are running which match the specified criteria.\r\n"), None);
+        assert!(looks_like_backend("Python.exe") && looks_like_backend("voicebox-server"));
+        assert!(!looks_like_backend("bash"));
+    }
+
+    #[test]
+    fn finds_the_backend_of_a_stale_state_while_it_runs() {
+        let port = unused_port();
+        let runner = FakeRunner::new();
+        runner.script("ps", Script::exits(0).stdout("python3\n"));
+        runner.script("tasklist", Script::exits(0).stdout("\"python.exe\",\"4321\",\"Console\",\"1\",\"1 K\"\r\n"));
+        let left = find(&runner, Some(&stale(4321, port)), 0).unwrap();
+        assert_eq!((left.pid, left.port, left.answers_health), (Some(4321), port, false));
+        assert!(left.describe().contains("PID 4321"));
+
+        // The PID now belongs to another program
+        let runner = FakeRunner::new();
+        runner.script("ps", Script::exits(0).stdout("bash\n"));
+        runner.script("tasklist", Script::exits(0).stdout("\"explorer.exe\",\"4321\",\"Console\",\"1\",\"1 K\"\r\n"));
+        assert!(find(&runner, Some(&stale(4321, port)), 0).is_none());
+        assert!(find(&runner, None, port).is_none());
+    }
+}
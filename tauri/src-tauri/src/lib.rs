@@ -1 +1,5 @@
 pub mod audio_capture;
+pub mod launcher;
+pub mod library;
+pub mod postprocess;
+pub mod text;
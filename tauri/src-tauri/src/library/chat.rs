@@ -0,0 +1,364 @@
+// Chat reader for live streams.
+//
+// Reads a Twitch channel's chat anonymously over IRC, or a YouTube live stream's chat
+// through the Data API, and queues the messages the filter lets through for narration.
+// The filter keeps spam and abuse off the air: blocked words, overlong messages, bot
+// commands and users who just had a message read are dropped. Moderators can pause
+// reading with a hotkey, which also drops what was waiting.
+use crate::library::narration::QueueLimits;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Name chat lines are queued under for narration
+pub const CHAT_SOURCE: &str = "chat";
+const TWITCH_IRC: &str = "irc.chat.twitch.tv:6667";
+/// Twitch lets `justinfan` logins with any number read chat without an account
+const TWITCH_ANONYMOUS_NICK: &str = "justinfan48151";
+const YOUTUBE_API: &str = "https://www.googleapis.com/youtube/v3";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChatSource {
+    Twitch { channel: String },
+    /// A live stream's video ID, read with a YouTube Data API key
+    Youtube { video_id: String, api_key: String },
+}
+
+/// Which messages are read aloud
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatFilter {
+    /// Messages containing any of these words, ignoring case, are dropped
+    pub blocklist: Vec<String>,
+    /// Longer messages are dropped rather than cut off
+    pub max_length: usize,
+    /// Seconds after a user's message is read before the next one from them is
+    pub user_cooldown_secs: u64,
+    /// Users never read, such as bots
+    pub ignore_users: Vec<String>,
+    /// Drop bot commands like `!discord`
+    pub skip_commands: bool,
+}
+
+impl Default for ChatFilter {
+    fn default() -> Self {
+        Self { blocklist: Vec::new(), max_length: 200, user_cooldown_secs: 30, ignore_users: Vec::new(), skip_commands: true }
+    }
+}
+
+/// The `chat` section of the launcher config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub source: ChatSource,
+    pub preset: String,
+    #[serde(default)]
+    pub filter: ChatFilter,
+    /// Messages read per minute at most
+    #[serde(default = "default_lines_per_minute")]
+    pub lines_per_minute: u32,
+    /// Messages waiting to be read before the oldest are skipped
+    #[serde(default = "default_max_queue")]
+    pub max_queue: usize,
+    /// Accelerator the app registers to pause and resume reading, e.g. `Ctrl+Alt+P`
+    pub pause_hotkey: Option<String>,
+}
+
+impl ChatConfig {
+    pub fn limits(&self) -> QueueLimits {
+        QueueLimits { lines_per_minute: self.lines_per_minute, max_queue: self.max_queue }
+    }
+}
+
+fn default_lines_per_minute() -> u32 {
+    10
+}
+
+fn default_max_queue() -> usize {
+    3
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    pub user: String,
+    pub text: String,
+}
+
+impl ChatMessage {
+    /// How the message is read aloud
+    pub fn spoken(&self) -> String {
+        format!("{} says {}", self.user, self.text)
+    }
+}
+
+/// A Twitch chat message from an IRC `PRIVMSG` line, named after the user's display
+/// name when the `twitch.tv/tags` capability sent one
+pub fn parse_privmsg(line: &str) -> Option<ChatMessage> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (tags, rest) = match line.strip_prefix('@') {
+        Some(tagged) => {
+            let (tags, rest) = tagged.split_once(' ')?;
+            (Some(tags), rest)
+        }
+        None => (None, line),
+    };
+    let (prefix, command) = rest.strip_prefix(':')?.split_once(' ')?;
+    let (_channel, text) = command.strip_prefix("PRIVMSG ")?.split_once(" :")?;
+    let login = prefix.split('!').next()?;
+    let display_name = tags
+        .and_then(|tags| tags.split(';').find_map(|tag| tag.strip_prefix("display-name=")))
+        .filter(|name| !name.is_empty());
+    // `/me` messages arrive as CTCP ACTION
+    let text = text.strip_prefix("\u{1}ACTION ").map_or(text, |action| action.trim_end_matches('\u{1}'));
+    Some(ChatMessage { user: display_name.unwrap_or(login).to_string(), text: text.trim().to_string() })
+}
+
+/// Messages of a `liveChat/messages` response, the token of the next page, and how
+/// long YouTube asks to wait before fetching it
+pub fn parse_youtube_messages(response: &Value) -> (Vec<ChatMessage>, Option<String>, Duration) {
+    let messages = response["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            Some(ChatMessage {
+                user: item["authorDetails"]["displayName"].as_str()?.to_string(),
+                text: item["snippet"]["displayMessage"].as_str()?.trim().to_string(),
+            })
+        })
+        .collect();
+    let next_page = response["nextPageToken"].as_str().map(str::to_string);
+    let interval = Duration::from_millis(response["pollingIntervalMillis"].as_u64().unwrap_or(5000).max(1000));
+    (messages, next_page, interval)
+}
+
+/// Applies the filter, remembering when each user was last read
+#[derive(Default)]
+struct Gate {
+    last_read: HashMap<String, Instant>,
+}
+
+impl Gate {
+    fn admit(&mut self, filter: &ChatFilter, message: &ChatMessage, now: Instant) -> bool {
+        let user = message.user.to_lowercase();
+        let text = message.text.to_lowercase();
+        if message.text.is_empty() {
+            return false;
+        }
+        if message.text.chars().count() > filter.max_length {
+            return false;
+        }
+        if filter.skip_commands && message.text.starts_with('!') {
+            return false;
+        }
+        if filter.ignore_users.iter().any(|ignored| ignored.to_lowercase() == user) {
+            return false;
+        }
+        if filter.blocklist.iter().any(|word| !word.is_empty() && text.contains(&word.to_lowercase())) {
+            return false;
+        }
+        let cooldown = Duration::from_secs(filter.user_cooldown_secs);
+        if self.last_read.get(&user).is_some_and(|at| now.duration_since(*at) < cooldown) {
+            return false;
+        }
+        self.last_read.insert(user, now);
+        true
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChatStatus {
+    pub connected: bool,
+    pub paused: bool,
+    /// Messages queued for reading since the app started
+    pub admitted: usize,
+    /// Messages the filter or a pause dropped
+    pub filtered: usize,
+    /// From the config, for the UI to register
+    pub pause_hotkey: Option<String>,
+}
+
+/// State of the chat reader shared with the app's commands
+#[derive(Default)]
+pub struct ChatBridge {
+    gate: Mutex<Gate>,
+    connected: AtomicBool,
+    paused: AtomicBool,
+    admitted: AtomicUsize,
+    filtered: AtomicUsize,
+}
+
+impl ChatBridge {
+    /// Whether `message` should be read, counting it either way
+    pub fn admit(&self, filter: &ChatFilter, message: &ChatMessage, now: Instant) -> bool {
+        let admitted = !self.paused.load(Ordering::Relaxed) && self.gate.lock().unwrap().admit(filter, message, now);
+        let counter = if admitted { &self.admitted } else { &self.filtered };
+        counter.fetch_add(1, Ordering::Relaxed);
+        admitted
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> ChatStatus {
+        ChatStatus {
+            connected: self.connected.load(Ordering::Relaxed),
+            paused: self.paused.load(Ordering::Relaxed),
+            admitted: self.admitted.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            pause_hotkey: None,
+        }
+    }
+}
+
+/// Read `channel`'s chat until `stop` says so or the connection drops, passing each
+/// message to `on_message`
+pub fn read_twitch(channel: &str, on_message: &mut dyn FnMut(ChatMessage), stop: &dyn Fn() -> bool) -> Result<(), String> {
+    let channel = channel.trim_start_matches('#').to_lowercase();
+    let stream = TcpStream::connect(TWITCH_IRC).map_err(|e| format!("Failed to connect to {}: {}", TWITCH_IRC, e))?;
+    // Wakes the read loop up regularly to check `stop`
+    stream.set_read_timeout(Some(Duration::from_secs(1))).map_err(|e| e.to_string())?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let login = format!("CAP REQ :twitch.tv/tags\r\nNICK {}\r\nJOIN #{}\r\n", TWITCH_ANONYMOUS_NICK, channel);
+    writer.write_all(login.as_bytes()).map_err(|e| format!("Failed to join #{}: {}", channel, e))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while !stop() {
+        match reader.read_line(&mut line) {
+            Ok(0) => return Err("Twitch closed the connection".to_string()),
+            Ok(_) => {
+                if let Some(server) = line.strip_prefix("PING ") {
+                    writer.write_all(format!("PONG {}", server).as_bytes()).map_err(|e| e.to_string())?;
+                } else if let Some(message) = parse_privmsg(&line) {
+                    on_message(message);
+                }
+                line.clear();
+            }
+            // A partial line stays in `line` and is completed by the next read
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(format!("Reading Twitch chat failed: {}", e)),
+        }
+    }
+    Ok(())
+}
+
+fn get_json(client: &reqwest::blocking::Client, url: &str, query: &[(&str, &str)]) -> Result<Value, String> {
+    client
+        .get(url)
+        .query(query)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .json()
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))
+}
+
+/// Read the chat of live stream `video_id` until `stop` says so or the stream ends.
+/// Messages sent before reading started are skipped.
+pub fn read_youtube(
+    video_id: &str,
+    api_key: &str,
+    on_message: &mut dyn FnMut(ChatMessage),
+    stop: &dyn Fn() -> bool,
+) -> Result<(), String> {
+    let client = reqwest::blocking::Client::new();
+    let videos = get_json(
+        &client,
+        &format!("{}/videos", YOUTUBE_API),
+        &[("part", "liveStreamingDetails"), ("id", video_id), ("key", api_key)],
+    )?;
+    let chat_id = videos["items"][0]["liveStreamingDetails"]["activeLiveChatId"]
+        .as_str()
+        .ok_or_else(|| format!("YouTube video {} has no active live chat", video_id))?
+        .to_string();
+
+    let url = format!("{}/liveChat/messages", YOUTUBE_API);
+    let mut page: Option<String> = None;
+    let mut first = true;
+    while !stop() {
+        let mut query = vec![("liveChatId", chat_id.as_str()), ("part", "snippet,authorDetails"), ("key", api_key)];
+        if let Some(token) = &page {
+            query.push(("pageToken", token));
+        }
+        let response = get_json(&client, &url, &query)?;
+        let (messages, next_page, interval) = parse_youtube_messages(&response);
+        if !first {
+            for message in messages {
+                on_message(message);
+            }
+        }
+        first = false;
+        page = next_page;
+        let until = Instant::now() + interval;
+        while Instant::now() < until && !stop() {
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(user: &str, text: &str) -> ChatMessage {
+        ChatMessage { user: user.to_string(), text: text.to_string() }
+    }
+
+    #[test]
+    fn parses_twitch_and_youtube_messages() {
+        let tagged = "@badge-info=;color=#1E90FF;display-name=AnnaK;mod=0 :annak!annak@annak.tmi.twitch.tv PRIVMSG #streamer :hello there\r\n";
+        assert_eq!(parse_privmsg(tagged), Some(message("AnnaK", "hello there")));
+        let action = ":max!max@max.tmi.twitch.tv PRIVMSG #streamer :\u{1}ACTION waves\u{1}";
+        assert_eq!(parse_privmsg(action), Some(message("max", "waves")));
+        assert_eq!(parse_privmsg(":tmi.twitch.tv 001 justinfan48151 :Welcome, GLHF!"), None);
+
+        let response = serde_json::json!({
+            "nextPageToken": "abc",
+            "pollingIntervalMillis": 3000,
+            "items": [{ "snippet": { "displayMessage": "First!" }, "authorDetails": { "displayName": "Kate" } }],
+        });
+        let (messages, next_page, interval) = parse_youtube_messages(&response);
+        assert_eq!(messages, [message("Kate", "First!")]);
+        assert_eq!((next_page.as_deref(), interval), (Some("abc"), Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn filters_spam_and_pauses() {
+        let bridge = ChatBridge::default();
+        let filter = ChatFilter {
+            blocklist: vec!["Spoiler".to_string()],
+            max_length: 20,
+            ignore_users: vec!["Nightbot".to_string()],
+            ..ChatFilter::default()
+        };
+        let now = Instant::now();
+        assert!(bridge.admit(&filter, &message("anna", "hi all"), now));
+        assert!(!bridge.admit(&filter, &message("anna", "me again"), now + Duration::from_secs(10)));
+        assert!(bridge.admit(&filter, &message("Anna", "me again"), now + Duration::from_secs(30)));
+        assert!(!bridge.admit(&filter, &message("max", "huge SPOILER ahead"), now));
+        assert!(!bridge.admit(&filter, &message("max", "this message is far too long"), now));
+        assert!(!bridge.admit(&filter, &message("max", "!discord"), now));
+        assert!(!bridge.admit(&filter, &message("nightbot", "Follow us"), now));
+
+        bridge.set_paused(true);
+        assert!(!bridge.admit(&filter, &message("kate", "hello"), now));
+        bridge.set_paused(false);
+        assert!(bridge.admit(&filter, &message("kate", "hello"), now));
+        let status = bridge.status();
+        assert_eq!((status.admitted, status.filtered, status.paused), (3, 6, false));
+    }
+}
@@ -0,0 +1,237 @@
+// Fine-tuning datasets from a voice's recordings.
+//
+// Training tools outside the app take a folder of uniform WAV files and a
+// `metadata.csv` in the LJSpeech layout, one `id|transcript` line per file in `wavs/`.
+// Each recording is downmixed to mono, trimmed of leading and trailing silence,
+// resampled and normalized to a common peak. Clips that are too short or too long to
+// train on, silent or without a transcript are left out. `report.json` lists what was
+// kept, what was left out and why, and what looked suspicious.
+use crate::postprocess::pcm::{self, Pcm};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const METADATA_FILE_NAME: &str = "metadata.csv";
+pub const REPORT_FILE_NAME: &str = "report.json";
+pub const WAVS_DIR_NAME: &str = "wavs";
+/// Recordings peaking this close to full scale were probably clipped
+const CLIPPING_DBFS: f64 = -0.1;
+/// Speech quieter than this (RMS, dBFS) is mostly noise once normalized
+const QUIET_DBFS: f64 = -40.0;
+
+/// The `dataset` section of the launcher config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatasetOptions {
+    pub sample_rate: u32,
+    /// Silence kept around the speech when trimming, in seconds; `None` keeps the
+    /// recording's own
+    pub trim_padding_secs: Option<f64>,
+    /// Peak level every clip is scaled to, in dBFS; `None` keeps the recorded levels
+    pub peak_dbfs: Option<f64>,
+    /// Shortest clip kept, in seconds after trimming
+    pub min_secs: f64,
+    /// Longest clip kept; most training tools run out of memory on longer ones
+    pub max_secs: f64,
+}
+
+impl Default for DatasetOptions {
+    fn default() -> Self {
+        Self { sample_rate: 22_050, trim_padding_secs: Some(0.1), peak_dbfs: Some(-1.0), min_secs: 1.0, max_secs: 15.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetClip {
+    pub id: String,
+    /// Relative to the dataset directory
+    pub file: String,
+    pub transcript: String,
+    pub duration_secs: f64,
+    /// Problems that didn't keep the clip out
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedClip {
+    pub id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetReport {
+    pub sample_rate: u32,
+    pub clips: Vec<DatasetClip>,
+    pub rejected: Vec<RejectedClip>,
+    pub total_secs: f64,
+    pub created_at: String,
+}
+
+/// A dataset being written into one directory
+pub struct DatasetBuilder {
+    options: DatasetOptions,
+    dir: PathBuf,
+    clips: Vec<DatasetClip>,
+    rejected: Vec<RejectedClip>,
+}
+
+impl DatasetBuilder {
+    pub fn create(options: DatasetOptions, dir: &Path) -> Result<Self, String> {
+        let wavs = dir.join(WAVS_DIR_NAME);
+        std::fs::create_dir_all(&wavs).map_err(|e| format!("Failed to create {}: {}", wavs.display(), e))?;
+        Ok(Self { options, dir: dir.to_path_buf(), clips: Vec::new(), rejected: Vec::new() })
+    }
+
+    /// Prepare a recording and add it with its transcript, or record why it was left
+    /// out. Only failing to write is an error.
+    pub fn add(&mut self, id: &str, wav: &[u8], transcript: &str) -> Result<(), String> {
+        let transcript = transcript.replace('|', " ").split_whitespace().collect::<Vec<_>>().join(" ");
+        let prepared = match transcript.is_empty() {
+            true => Err("No transcript".to_string()),
+            false => self.prepare(wav),
+        };
+        let (audio, warnings) = match prepared {
+            Ok(prepared) => prepared,
+            Err(reason) => {
+                self.rejected.push(RejectedClip { id: id.to_string(), reason });
+                return Ok(());
+            }
+        };
+
+        let name = file_stem(id, self.clips.len());
+        let file = format!("{}/{}.wav", WAVS_DIR_NAME, name);
+        write(&self.dir.join(&file), &audio.write()?)?;
+        self.clips.push(DatasetClip {
+            id: name,
+            file,
+            transcript,
+            duration_secs: audio.secs(audio.frames()),
+            warnings,
+        });
+        Ok(())
+    }
+
+    /// The recording in the dataset's format, with warnings about it; `Err` with the
+    /// reason to leave it out
+    fn prepare(&self, wav: &[u8]) -> Result<(Pcm, Vec<String>), String> {
+        let audio = Pcm::read(wav).map_err(|e| format!("Unreadable audio: {}", e))?;
+        let stats = audio.stats();
+        let (start, end) = audio.speech_frames();
+        if start == end {
+            return Err("No speech found".to_string());
+        }
+        let mut warnings = Vec::new();
+        if stats.peak_dbfs >= CLIPPING_DBFS {
+            warnings.push(format!("Probably clipped: peaks at {:.1} dBFS", stats.peak_dbfs));
+        }
+        if stats.rms_dbfs < QUIET_DBFS {
+            warnings.push(format!("Very quiet: speech at {:.1} dBFS", stats.rms_dbfs));
+        }
+
+        let (start, end) = match self.options.trim_padding_secs {
+            Some(padding) => {
+                let padding = (padding.max(0.0) * f64::from(audio.spec.sample_rate)) as usize;
+                (start.saturating_sub(padding), (end + padding).min(audio.frames()))
+            }
+            None => (0, audio.frames()),
+        };
+        let channels = audio.channels();
+        let mono: Vec<f32> = audio.samples[start * channels..end * channels]
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect();
+        let mut samples = pcm::resample(&mono, 1, audio.spec.sample_rate, self.options.sample_rate);
+        if let Some(target) = self.options.peak_dbfs {
+            let peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+            if peak > 0.0 {
+                let gain = 10f32.powf(target as f32 / 20.0) / peak;
+                samples.iter_mut().for_each(|s| *s *= gain);
+            }
+        }
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: self.options.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let out = Pcm { spec, samples };
+
+        let secs = out.secs(out.frames());
+        if secs < self.options.min_secs {
+            return Err(format!("Too short: {:.2}s, at least {}s needed", secs, self.options.min_secs));
+        }
+        if secs > self.options.max_secs {
+            return Err(format!("Too long: {:.2}s, at most {}s allowed", secs, self.options.max_secs));
+        }
+        Ok((out, warnings))
+    }
+
+    /// Write `metadata.csv` and the report
+    pub fn finish(self) -> Result<DatasetReport, String> {
+        let metadata: String = self.clips.iter().map(|clip| format!("{}|{}\n", clip.id, clip.transcript)).collect();
+        write(&self.dir.join(METADATA_FILE_NAME), metadata.as_bytes())?;
+        let report = DatasetReport {
+            sample_rate: self.options.sample_rate,
+            total_secs: self.clips.iter().map(|clip| clip.duration_secs).sum(),
+            clips: self.clips,
+            rejected: self.rejected,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        write(&self.dir.join(REPORT_FILE_NAME), json.as_bytes())?;
+        Ok(report)
+    }
+}
+
+/// `id` if it makes a safe file name, otherwise a numbered one
+fn file_stem(id: &str, index: usize) -> String {
+    let safe = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if safe { id.to_string() } else { format!("clip_{:04}", index + 1) }
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stereo recording with `silence` seconds around `speech` seconds of a tone
+    fn recording(silence: f64, speech: f64) -> Vec<u8> {
+        let rate = 44_100;
+        let spec = hound::WavSpec { channels: 2, sample_rate: rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let (quiet, loud) = ((silence * f64::from(rate)) as usize, (speech * f64::from(rate)) as usize);
+        let tone = (0..loud).map(|i| 0.25 * (i as f32 * 440.0 * std::f32::consts::TAU / rate as f32).sin());
+        let frames: Vec<f32> = std::iter::repeat_n(0.0, quiet).chain(tone).chain(std::iter::repeat_n(0.0, quiet)).collect();
+        Pcm { spec, samples: frames.iter().flat_map(|&s| [s, s]).collect() }.write().unwrap()
+    }
+
+    #[test]
+    fn writes_prepared_clips_and_reports_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = DatasetBuilder::create(DatasetOptions::default(), dir.path()).unwrap();
+        builder.add("s-1", &recording(1.0, 2.0), "Hello there |\n general Kenobi.").unwrap();
+        builder.add("s-2", &recording(1.0, 0.3), "Too short.").unwrap();
+        builder.add("s-3", &recording(0.0, 2.0), "  ").unwrap();
+        builder.add("s-4", &recording(1.0, 0.0), "Silence.").unwrap();
+        builder.add("s-5", b"not a wav", "Broken.").unwrap();
+        let report = builder.finish().unwrap();
+
+        assert_eq!(report.clips.len(), 1);
+        let clip = &report.clips[0];
+        assert_eq!((clip.file.as_str(), clip.transcript.as_str()), ("wavs/s-1.wav", "Hello there general Kenobi."));
+        assert!((clip.duration_secs - 2.2).abs() < 0.02, "{}", clip.duration_secs);
+        let reasons: Vec<(&str, &str)> = report.rejected.iter().map(|r| (r.id.as_str(), r.reason.as_str())).collect();
+        assert!(reasons[0].0 == "s-2" && reasons[0].1.starts_with("Too short"));
+        assert_eq!(&reasons[1..3], [("s-3", "No transcript"), ("s-4", "No speech found")]);
+        assert!(reasons[3].1.starts_with("Unreadable audio"));
+
+        let metadata = std::fs::read_to_string(dir.path().join(METADATA_FILE_NAME)).unwrap();
+        assert_eq!(metadata, "s-1|Hello there general Kenobi.\n");
+        let audio = Pcm::read(&std::fs::read(dir.path().join("wavs/s-1.wav")).unwrap()).unwrap();
+        assert_eq!((audio.spec.sample_rate, audio.spec.channels, audio.spec.bits_per_sample), (22_050, 1, 16));
+        assert!((audio.stats().peak_dbfs + 1.0).abs() < 0.1);
+        let written: DatasetReport = serde_json::from_slice(&std::fs::read(dir.path().join(REPORT_FILE_NAME)).unwrap()).unwrap();
+        assert_eq!(written, report);
+    }
+}
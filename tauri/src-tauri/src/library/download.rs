@@ -0,0 +1,235 @@
+// Downloads of large backend files to a place the user picked.
+//
+// Stems and datasets can be gigabytes, too much to pass through the webview as one
+// blob. They are streamed to `<destination>.part` instead, which is renamed once the
+// download is complete and its SHA-256 matches. A paused or interrupted download
+// continues where the part file ends with a `Range` request; a server that ignores the
+// range sends the whole file again, which then replaces the part.
+use futures_util::StreamExt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::io::AsyncWriteExt;
+
+/// Header the backend may send with the file's SHA-256, used when the caller has none
+pub const CHECKSUM_HEADER: &str = "x-content-sha256";
+const PART_SUFFIX: &str = ".part";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadRequest {
+    pub url: String,
+    pub destination: PathBuf,
+    /// Expected SHA-256 as hex; the download fails when the file doesn't match
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum DownloadOutcome {
+    Finished { bytes: u64, sha256: String },
+    /// Stopped with `bytes` in the part file, to be continued by downloading again
+    Paused { bytes: u64 },
+    /// Stopped and the part file removed
+    Cancelled,
+}
+
+/// Lets another task pause or cancel a running download between chunks
+#[derive(Debug, Default)]
+pub struct DownloadControl {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl DownloadControl {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn part_path(destination: &Path) -> PathBuf {
+    let mut name = destination.as_os_str().to_os_string();
+    name.push(PART_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Hash of the part file so far and its length, to continue hashing after it
+fn hash_part(path: &Path) -> Result<(Sha256, u64), String> {
+    let mut hasher = Sha256::new();
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((hasher, 0)),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut buf = vec![0u8; 1 << 16];
+    let mut len = 0;
+    loop {
+        let read = file.read(&mut buf).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            return Ok((hasher, len));
+        }
+        hasher.update(&buf[..read]);
+        len += read as u64;
+    }
+}
+
+/// Download `request`, continuing a part file left by an earlier attempt. `on_progress`
+/// gets the bytes written so far and the total size when the server tells it.
+pub async fn download(
+    client: &reqwest::Client,
+    request: &DownloadRequest,
+    control: &DownloadControl,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<DownloadOutcome, String> {
+    let part = part_path(&request.destination);
+    let part_for_hash = part.clone();
+    let (mut hasher, mut written) = tokio::task::spawn_blocking(move || hash_part(&part_for_hash))
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", part.display(), e))??;
+
+    let mut get = client.get(&request.url);
+    if written > 0 {
+        get = get.header(reqwest::header::RANGE, format!("bytes={}-", written));
+    }
+    let response = get
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", request.url, e))?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT && written > 0 {
+        // The range was ignored, so the body is the whole file
+        hasher = Sha256::new();
+        written = 0;
+    }
+    let expected = request.sha256.clone().or_else(|| {
+        response.headers().get(CHECKSUM_HEADER).and_then(|v| v.to_str().ok()).map(str::to_string)
+    });
+    let total = response.content_length().map(|len| len + written);
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(written > 0)
+        .truncate(written == 0)
+        .open(&part)
+        .await
+        .map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+    on_progress(written, total);
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        if control.cancelled.load(Ordering::Relaxed) {
+            drop(file);
+            let _ = tokio::fs::remove_file(&part).await;
+            return Ok(DownloadOutcome::Cancelled);
+        }
+        if control.paused.swap(false, Ordering::Relaxed) {
+            file.flush().await.map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+            return Ok(DownloadOutcome::Paused { bytes: written });
+        }
+        let chunk = chunk.map_err(|e| format!("Download from {} was interrupted: {}", request.url, e))?;
+        file.write_all(&chunk).await.map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+        hasher.update(&chunk);
+        written += chunk.len() as u64;
+        on_progress(written, total);
+    }
+    file.flush().await.map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+    drop(file);
+
+    let sha256 = format!("{:x}", hasher.finalize());
+    if let Some(expected) = expected.filter(|expected| !expected.eq_ignore_ascii_case(&sha256)) {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(format!("Download from {} is damaged: SHA-256 {} instead of {}", request.url, sha256, expected));
+    }
+    tokio::fs::rename(&part, &request.destination)
+        .await
+        .map_err(|e| format!("Failed to move {} to {}: {}", part.display(), request.destination.display(), e))?;
+    Ok(DownloadOutcome::Finished { bytes: written, sha256 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, Bytes};
+    use axum::http::{header, HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+
+    const CHUNK: usize = 64 * 1024;
+
+    fn contents() -> Vec<u8> {
+        (0..8 * CHUNK).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Serves `contents()` in chunks, honoring `Range: bytes=N-`
+    async fn serve(headers: HeaderMap) -> Response {
+        let data = contents();
+        let start = headers
+            .get(header::RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes="))
+            .and_then(|v| v.trim_end_matches('-').parse::<usize>().ok());
+        let status = if start.is_some() { StatusCode::PARTIAL_CONTENT } else { StatusCode::OK };
+        let body: Vec<Bytes> = data[start.unwrap_or(0)..].chunks(CHUNK).map(Bytes::copy_from_slice).collect();
+        let stream = futures_util::stream::iter(body.into_iter().map(Ok::<_, std::io::Error>));
+        let length = data.len() - start.unwrap_or(0);
+        (status, [(header::CONTENT_LENGTH, length.to_string())], Body::from_stream(stream)).into_response()
+    }
+
+    async fn server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, axum::Router::new().route("/export", get(serve))).await });
+        format!("http://{}/export", addr)
+    }
+
+    #[tokio::test]
+    async fn pauses_and_resumes_where_it_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let destination = dir.path().join("stems.zip");
+        let sha256 = format!("{:x}", Sha256::digest(contents()));
+        let request = DownloadRequest { url: server().await, destination: destination.clone(), sha256: Some(sha256.clone()) };
+        let client = reqwest::Client::new();
+
+        let control = DownloadControl::default();
+        let outcome = download(&client, &request, &control, |written, _| {
+            if written >= 2 * CHUNK as u64 {
+                control.pause();
+            }
+        })
+        .await
+        .unwrap();
+        let DownloadOutcome::Paused { bytes } = outcome else { panic!("not paused: {:?}", outcome) };
+        assert!(bytes >= 2 * CHUNK as u64 && bytes < contents().len() as u64);
+        assert_eq!(std::fs::metadata(part_path(&destination)).unwrap().len(), bytes);
+
+        let mut first = None;
+        let outcome = download(&client, &request, &DownloadControl::default(), |written, total| {
+            first.get_or_insert((written, total));
+        })
+        .await
+        .unwrap();
+        assert_eq!(first, Some((bytes, Some(contents().len() as u64))));
+        assert_eq!(outcome, DownloadOutcome::Finished { bytes: contents().len() as u64, sha256 });
+        assert_eq!(std::fs::read(&destination).unwrap(), contents());
+        assert!(!part_path(&destination).exists());
+    }
+
+    #[tokio::test]
+    async fn rejects_a_file_with_the_wrong_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let destination = dir.path().join("dataset.zip");
+        let request = DownloadRequest { url: server().await, destination: destination.clone(), sha256: Some("00".repeat(32)) };
+        let err = download(&reqwest::Client::new(), &request, &DownloadControl::default(), |_, _| {}).await.unwrap_err();
+        assert!(err.contains("is damaged"));
+        assert!(!destination.exists() && !part_path(&destination).exists());
+
+        let control = DownloadControl::default();
+        control.cancel();
+        assert_eq!(download(&reqwest::Client::new(), &request, &control, |_, _| {}).await.unwrap(), DownloadOutcome::Cancelled);
+    }
+}
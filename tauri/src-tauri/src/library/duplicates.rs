@@ -0,0 +1,216 @@
+use crate::launcher::state::StateFile;
+use crate::postprocess::fingerprint::Fingerprint;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+pub const FINGERPRINTS_DIR_NAME: &str = "fingerprints";
+
+/// A reference sample of a voice profile, as the backend lists it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleInfo {
+    pub id: String,
+    pub profile_id: String,
+    pub profile_name: String,
+    pub audio_path: String,
+    /// Hash of the file that was uploaded, before the backend resampled it
+    pub source_sha256: Option<String>,
+}
+
+/// A library sample that an imported file duplicates
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateMatch {
+    pub sample_id: String,
+    pub profile_id: String,
+    pub profile_name: String,
+    /// The file is byte for byte one that was imported before
+    pub exact: bool,
+    /// Share of matching acoustic fingerprint bits, when both are WAV
+    pub similarity: Option<f32>,
+}
+
+/// Fingerprint of a sample's stored audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedFingerprint {
+    /// `SampleInfo::audio_path` it was computed from
+    source: String,
+    fingerprint: Fingerprint,
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+async fn get(client: &reqwest::Client, url: &str) -> Result<reqwest::Response, String> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))
+}
+
+async fn get_json(client: &reqwest::Client, url: &str) -> Result<Value, String> {
+    get(client, url).await?.json().await.map_err(|e| format!("Invalid response from {}: {}", url, e))
+}
+
+/// Every live sample of every profile of the backend at `base_url`
+pub async fn fetch_samples(base_url: &str) -> Result<Vec<SampleInfo>, String> {
+    let base_url = base_url.trim_end_matches('/');
+    let client = reqwest::Client::new();
+    let profiles = get_json(&client, &format!("{}/profiles", base_url)).await?;
+    let mut samples = Vec::new();
+    for profile in profiles.as_array().into_iter().flatten() {
+        let field = |value: &Value, name: &str| value.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+        let profile_id = field(profile, "id");
+        let listed = get_json(&client, &format!("{}/profiles/{}/samples", base_url, profile_id)).await?;
+        for sample in listed.as_array().into_iter().flatten() {
+            samples.push(SampleInfo {
+                id: field(sample, "id"),
+                profile_id: profile_id.clone(),
+                profile_name: field(profile, "name"),
+                audio_path: field(sample, "audio_path"),
+                source_sha256: sample.get("source_sha256").and_then(Value::as_str).map(str::to_string),
+            });
+        }
+    }
+    Ok(samples)
+}
+
+/// Fingerprints of the library's reference samples under `cache/fingerprints`, one
+/// file per sample, so checking an import only downloads samples added since
+pub struct FingerprintCache {
+    dir: PathBuf,
+}
+
+impl FingerprintCache {
+    pub fn new(cache_dir: &Path) -> Self {
+        Self { dir: cache_dir.join(FINGERPRINTS_DIR_NAME) }
+    }
+
+    fn file(&self, id: &str) -> Option<StateFile<CachedFingerprint>> {
+        is_valid_id(id).then(|| StateFile::new(self.dir.join(format!("{}.json", id))))
+    }
+
+    /// Fingerprint of `sample`'s current audio, if cached
+    fn get(&self, sample: &SampleInfo) -> Option<Fingerprint> {
+        let cached = self.file(&sample.id)?.read().ok()??;
+        (cached.source == sample.audio_path).then_some(cached.fingerprint)
+    }
+
+    fn put(&self, sample: &SampleInfo, fingerprint: &Fingerprint) -> Result<(), String> {
+        let cached = CachedFingerprint { source: sample.audio_path.clone(), fingerprint: fingerprint.clone() };
+        self.file(&sample.id).ok_or_else(|| format!("Invalid sample id {:?}", sample.id))?.write(&cached)
+    }
+
+    /// Delete the fingerprints of samples that aren't in `keep`
+    pub fn prune(&self, keep: &HashSet<&str>) -> Result<usize, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to list {}: {}", self.dir.display(), e)),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let id = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            if let Some(file) = self.file(id).filter(|_| !keep.contains(id)) {
+                file.remove()?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Samples among `samples` that `audio` duplicates, best match first. Fingerprints
+    /// missing from the cache are computed from audio downloaded from `base_url`;
+    /// samples that can't be downloaded are skipped.
+    pub async fn find_duplicates(
+        &self,
+        base_url: &str,
+        samples: &[SampleInfo],
+        audio: &[u8],
+    ) -> Result<Vec<DuplicateMatch>, String> {
+        let candidate = Fingerprint::of(audio);
+        let client = reqwest::Client::new();
+        let mut matches = Vec::new();
+        for sample in samples {
+            let fingerprint = match self.get(sample) {
+                Some(fingerprint) => fingerprint,
+                None => {
+                    // Samples whose audio can't be fetched can't be compared, which
+                    // shouldn't stop the others from being checked
+                    let url = format!("{}/samples/{}", base_url.trim_end_matches('/'), sample.id);
+                    let Ok(response) = get(&client, &url).await else { continue };
+                    let Ok(stored) = response.bytes().await else { continue };
+                    let fingerprint = Fingerprint::of(&stored);
+                    self.put(sample, &fingerprint)?;
+                    fingerprint
+                }
+            };
+            let exact = sample.source_sha256.as_deref() == Some(candidate.sha256.as_str())
+                || fingerprint.sha256 == candidate.sha256;
+            if exact || candidate.matches(&fingerprint) {
+                matches.push(DuplicateMatch {
+                    sample_id: sample.id.clone(),
+                    profile_id: sample.profile_id.clone(),
+                    profile_name: sample.profile_name.clone(),
+                    exact,
+                    similarity: candidate.similarity(&fingerprint),
+                });
+            }
+        }
+        matches.sort_by(|a, b| {
+            b.exact.cmp(&a.exact).then(b.similarity.unwrap_or(1.0).total_cmp(&a.similarity.unwrap_or(1.0)))
+        });
+
+        let keep = samples.iter().map(|sample| sample.id.as_str()).collect();
+        self.prune(&keep)?;
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(id: &str, audio_path: &str) -> SampleInfo {
+        SampleInfo {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            profile_name: "Ada".to_string(),
+            audio_path: audio_path.to_string(),
+            source_sha256: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn matches_cached_fingerprints_without_the_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FingerprintCache::new(dir.path());
+        let audio = b"not a wav, compared by hash".to_vec();
+        let imported = sample("s1", "/profiles/p1/s1.wav");
+        cache.put(&imported, &Fingerprint::of(&audio)).unwrap();
+        cache.put(&sample("old", "/profiles/p1/old.wav"), &Fingerprint::of(b"other")).unwrap();
+        let uploaded = SampleInfo { source_sha256: Some(Fingerprint::of(b"upload").sha256), ..sample("s2", "b") };
+        cache.put(&uploaded, &Fingerprint::of(b"resampled upload")).unwrap();
+
+        // Nothing listens on this port, so any download would fail the lookup
+        let samples = vec![imported.clone(), uploaded.clone()];
+        let found = cache.find_duplicates("http://127.0.0.1:9", &samples, &audio).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].exact && found[0].sample_id == "s1");
+        let found = cache.find_duplicates("http://127.0.0.1:9", &samples, b"upload").await.unwrap();
+        assert_eq!(found[0].sample_id, "s2");
+        assert!(FingerprintCache::new(dir.path()).file("old").unwrap().read().unwrap().is_none());
+
+        // A sample whose audio moved needs a new fingerprint
+        let moved = sample("s1", "/profiles/p1/restored.wav");
+        assert!(cache.get(&moved).is_none());
+        assert!(cache.find_duplicates("http://127.0.0.1:9", &[moved], &audio).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+use crate::launcher::config::LauncherConfig;
+use crate::postprocess::lipsync::{LipSyncFormat, LipSyncTrack};
+use crate::postprocess::pcm::{self, Pcm};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+/// Words of a generation's text used for its clip name
+const NAME_WORDS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    Unity,
+    Unreal,
+}
+
+impl Engine {
+    /// Where banks go below the export directory, as each engine's projects usually
+    /// keep voice-over
+    fn bank_dir(self) -> &'static str {
+        match self {
+            Engine::Unity => "Audio/Voice",
+            Engine::Unreal => "Audio/VO",
+        }
+    }
+
+    /// Asset name prefix; Unreal projects mark sound waves with `A_`
+    fn clip_prefix(self) -> &'static str {
+        match self {
+            Engine::Unity => "",
+            Engine::Unreal => "A_",
+        }
+    }
+}
+
+/// How generations are written for a game engine, from `game_exports` in the launcher
+/// config or one of the built-in `unity` and `unreal` profiles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameExportProfile {
+    pub engine: Engine,
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+    /// 1 downmixes to mono, 2 makes stereo
+    #[serde(default = "default_channels")]
+    pub channels: u16,
+    /// Clip file name without extension; `{voice}`, `{text}`, `{id}` and `{index}` are
+    /// replaced, then anything but letters, digits and `_` becomes `_`
+    #[serde(default = "default_naming")]
+    pub naming: String,
+    /// Timing tracks written next to each clip
+    #[serde(default)]
+    pub lip_sync: Vec<LipSyncFormat>,
+}
+
+fn default_sample_rate() -> u32 {
+    48_000
+}
+
+fn default_channels() -> u16 {
+    1
+}
+
+fn default_naming() -> String {
+    "{voice}_{text}".to_string()
+}
+
+impl GameExportProfile {
+    pub fn builtin(name: &str) -> Option<Self> {
+        let engine = match name {
+            "unity" => Engine::Unity,
+            "unreal" => Engine::Unreal,
+            _ => return None,
+        };
+        Some(Self {
+            engine,
+            sample_rate: default_sample_rate(),
+            channels: default_channels(),
+            naming: default_naming(),
+            lip_sync: Vec::new(),
+        })
+    }
+}
+
+impl LauncherConfig {
+    /// A configured profile, or the built-in one of that name
+    pub fn game_export_profile(&self, name: &str) -> Result<GameExportProfile, String> {
+        self.game_exports.get(name).cloned().or_else(|| GameExportProfile::builtin(name)).ok_or_else(|| {
+            let names: BTreeSet<&str> = self.game_exports.keys().map(String::as_str).chain(["unity", "unreal"]).collect();
+            format!("Unknown game export profile '{}' (available: {})", name, names.into_iter().collect::<Vec<_>>().join(", "))
+        })
+    }
+}
+
+/// One clip of a bank as listed in its manifest and in the clip's own metadata file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipInfo {
+    pub name: String,
+    /// Relative to the bank directory
+    pub file: String,
+    pub generation_id: String,
+    pub voice: String,
+    pub text: String,
+    pub language: Option<String>,
+    pub duration_secs: f64,
+    /// Timing tracks next to the clip, relative to the bank directory
+    pub lip_sync: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BankManifest {
+    pub bank: String,
+    pub engine: Engine,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    pub exported_at: String,
+    pub clips: Vec<ClipInfo>,
+}
+
+/// A bank being written: one directory of uniformly formatted clips, each with a
+/// metadata JSON, and a manifest listing them all
+pub struct GameBank {
+    profile: GameExportProfile,
+    name: String,
+    dir: PathBuf,
+    clips: Vec<ClipInfo>,
+    taken: BTreeSet<String>,
+}
+
+impl GameBank {
+    /// Start bank `name` below `export_dir`, in the layout the profile's engine uses
+    pub fn create(profile: GameExportProfile, name: &str, export_dir: &Path) -> Result<Self, String> {
+        let name = sanitize(name);
+        let dir = export_dir.join(profile.engine.bank_dir()).join(&name);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(Self { profile, name, dir, clips: Vec::new(), taken: BTreeSet::new() })
+    }
+
+    /// Add a generation given its `/history` entry and audio, converted to the
+    /// profile's format. `lip_sync` is needed if the profile writes timing tracks.
+    pub fn add(&mut self, entry: &Value, wav: &[u8], lip_sync: Option<&LipSyncTrack>) -> Result<&ClipInfo, String> {
+        let field = |name: &str| entry.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+        let (id, voice, text) = (field("id"), field("profile_name"), field("text"));
+        let name = self.unique_name(&id, &voice, &text);
+        let (wav, duration_secs) = convert(wav, self.profile.sample_rate, self.profile.channels)?;
+        let file = format!("{}.wav", name);
+        write(&self.dir.join(&file), wav.as_slice())?;
+
+        let mut tracks = Vec::new();
+        if !self.profile.lip_sync.is_empty() {
+            let track = lip_sync.ok_or_else(|| format!("No timing for generation {}", id))?;
+            for format in &self.profile.lip_sync {
+                let track_file = format!("{}.{}", name, format.extension());
+                write(&self.dir.join(&track_file), format.render(track, &file).as_bytes())?;
+                tracks.push(track_file);
+            }
+        }
+
+        let clip = ClipInfo {
+            name: name.clone(),
+            file,
+            generation_id: id,
+            voice,
+            text,
+            language: entry.get("language").and_then(Value::as_str).map(str::to_string),
+            duration_secs,
+            lip_sync: tracks,
+        };
+        let metadata = serde_json::to_string_pretty(&clip).map_err(|e| e.to_string())?;
+        write(&self.dir.join(format!("{}.json", name)), metadata.as_bytes())?;
+        self.clips.push(clip);
+        Ok(self.clips.last().unwrap())
+    }
+
+    fn unique_name(&mut self, id: &str, voice: &str, text: &str) -> String {
+        let text: Vec<&str> = text.split_whitespace().take(NAME_WORDS).collect();
+        let index = format!("{:03}", self.clips.len() + 1);
+        let name = self
+            .profile
+            .naming
+            .replace("{voice}", voice)
+            .replace("{text}", &text.join("_"))
+            .replace("{id}", id)
+            .replace("{index}", &index);
+        let base = format!("{}{}", self.profile.engine.clip_prefix(), sanitize(&name));
+        let mut name = base.clone();
+        let mut n = 2;
+        // Engines key assets by name regardless of case
+        while !self.taken.insert(name.to_lowercase()) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+        name
+    }
+
+    /// Write the manifest
+    pub fn finish(self) -> Result<BankManifest, String> {
+        let manifest = BankManifest {
+            bank: self.name,
+            engine: self.profile.engine,
+            sample_rate: self.profile.sample_rate,
+            channels: self.profile.channels,
+            bits_per_sample: 16,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            clips: self.clips,
+        };
+        let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        write(&self.dir.join(MANIFEST_FILE_NAME), json.as_bytes())?;
+        Ok(manifest)
+    }
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Letters, digits and `_` only, with runs of anything else collapsed into one `_`
+fn sanitize(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    let out = out.trim_matches('_');
+    if out.is_empty() { "clip".to_string() } else { out.to_string() }
+}
+
+/// 16-bit PCM at `sample_rate` with `channels`, which every engine imports, and its
+/// duration
+fn convert(wav: &[u8], sample_rate: u32, channels: u16) -> Result<(Vec<u8>, f64), String> {
+    let audio = Pcm::read(wav)?;
+    let from = audio.channels();
+    let to = usize::from(channels.max(1));
+    let samples: Vec<f32> = if from == to {
+        audio.samples
+    } else {
+        audio
+            .samples
+            .chunks(from)
+            .flat_map(|frame| {
+                let mono = frame.iter().sum::<f32>() / frame.len() as f32;
+                (0..to).map(move |channel| if from > 1 && to > 1 { frame[channel.min(from - 1)] } else { mono })
+            })
+            .collect()
+    };
+    let samples = pcm::resample(&samples, to, audio.spec.sample_rate, sample_rate);
+    let spec = hound::WavSpec {
+        channels: to as u16,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let out = Pcm { spec, samples };
+    Ok((out.write()?, out.secs(out.frames())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stereo_wav(sample_rate: u32, secs: f64) -> Vec<u8> {
+        let spec = hound::WavSpec { channels: 2, sample_rate, bits_per_sample: 32, sample_format: hound::SampleFormat::Float };
+        let frames = (f64::from(sample_rate) * secs) as usize;
+        Pcm { spec, samples: vec![0.25; frames * 2] }.write().unwrap()
+    }
+
+    #[test]
+    fn writes_a_bank_in_the_engines_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile = GameExportProfile::builtin("unreal").unwrap();
+        let mut bank = GameBank::create(profile, "Chapter 1", dir.path()).unwrap();
+        let entry = serde_json::json!({
+            "id": "g1", "profile_name": "Old Knight", "text": "Halt! Who goes there, stranger?", "language": "en"
+        });
+        let clip = bank.add(&entry, &stereo_wav(24_000, 0.5), None).unwrap();
+        assert_eq!(clip.name, "A_Old_Knight_Halt_Who_goes_there");
+        assert_eq!(clip.duration_secs, 0.5);
+        let again = bank.add(&entry, &stereo_wav(24_000, 0.5), None).unwrap();
+        assert_eq!(again.name, "A_Old_Knight_Halt_Who_goes_there_2");
+
+        let bank_dir = dir.path().join("Audio/VO/Chapter_1");
+        let manifest = bank.finish().unwrap();
+        assert_eq!((manifest.bank.as_str(), manifest.clips.len()), ("Chapter_1", 2));
+        let written: BankManifest =
+            serde_json::from_slice(&std::fs::read(bank_dir.join(MANIFEST_FILE_NAME)).unwrap()).unwrap();
+        assert_eq!(written, manifest);
+        assert!(bank_dir.join("A_Old_Knight_Halt_Who_goes_there.json").exists());
+
+        let audio = Pcm::read(&std::fs::read(bank_dir.join("A_Old_Knight_Halt_Who_goes_there.wav")).unwrap()).unwrap();
+        assert_eq!((audio.spec.sample_rate, audio.spec.channels, audio.spec.bits_per_sample), (48_000, 1, 16));
+        assert_eq!(audio.frames(), 24_000);
+        assert!((audio.samples[100] - 0.25).abs() < 0.001);
+    }
+}
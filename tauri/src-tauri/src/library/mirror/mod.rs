@@ -0,0 +1,332 @@
+pub mod remote;
+
+use crate::launcher::state::StateFile;
+use remote::{Destination, DestinationConfig};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const MIRRORS_STATE_DIR_NAME: &str = "mirrors";
+/// Give up finding a free name for a conflicting file after this many tries
+const MAX_CONFLICT_SUFFIX: u32 = 1000;
+
+/// Library folders under the data dir that can be mirrored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryFolder {
+    /// Generated audio
+    Generations,
+    /// Reference samples of voice profiles
+    Profiles,
+}
+
+impl LibraryFolder {
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            LibraryFolder::Generations => "generations",
+            LibraryFolder::Profiles => "profiles",
+        }
+    }
+}
+
+/// One-way copy of library folders to an external destination, from the `mirrors`
+/// list of the launcher config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MirrorConfig {
+    /// Identifies the mirror in commands and names its state file
+    pub name: String,
+    pub folders: Vec<LibraryFolder>,
+    pub destination: DestinationConfig,
+    /// Mirror this often while the app runs; only on demand without it
+    #[serde(default)]
+    pub interval_minutes: Option<u32>,
+}
+
+/// What a mirror run copied, or would copy in a dry run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MirrorAction {
+    /// Relative to the data dir, `/`-separated
+    pub path: String,
+    pub remote_path: String,
+    /// Another file already had the name at the destination, so this one got a new one
+    pub renamed: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MirrorReport {
+    pub name: String,
+    pub dry_run: bool,
+    pub copied: Vec<MirrorAction>,
+    /// Files already at the destination as they are now
+    pub unchanged: usize,
+    /// Files that couldn't be copied, with the reason; they are retried next run
+    pub failed: Vec<(String, String)>,
+}
+
+/// A file as it was last copied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct MirroredFile {
+    size: u64,
+    /// Modification time in seconds, so unchanged files aren't hashed again
+    modified: u64,
+    sha256: String,
+    remote_path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MirrorState {
+    last_run_at: Option<String>,
+    files: BTreeMap<String, MirroredFile>,
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Files under `dir` as `/`-separated paths relative to `base`, skipping partial writes
+fn walk(base: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<(), String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to list {}: {}", dir.display(), e)),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || name.ends_with(".partial") || name.ends_with(".tmp") {
+            continue;
+        }
+        if path.is_dir() {
+            walk(base, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(base) {
+            let relative: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+            files.push((relative.join("/"), path));
+        }
+    }
+    Ok(())
+}
+
+/// `stem (n).ext` for the `n`th alternative to `path`
+fn conflict_name(path: &str, n: u32) -> String {
+    let (dir, name) = path.rsplit_once('/').map_or(("", path), |(dir, name)| (dir, name));
+    let renamed = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{} ({}).{}", stem, n, ext),
+        _ => format!("{} ({})", name, n),
+    };
+    if dir.is_empty() {
+        renamed
+    } else {
+        format!("{}/{}", dir, renamed)
+    }
+}
+
+/// A configured mirror with its state under `state/mirrors/<name>.json`
+pub struct Mirror<'a> {
+    config: &'a MirrorConfig,
+    data_dir: PathBuf,
+    state: StateFile<MirrorState>,
+}
+
+impl<'a> Mirror<'a> {
+    pub fn new(config: &'a MirrorConfig, data_dir: &Path, state_dir: &Path) -> Result<Self, String> {
+        if !is_valid_name(&config.name) {
+            return Err(format!("Invalid mirror name {:?}: use letters, digits, - and _", config.name));
+        }
+        let state = StateFile::new(state_dir.join(MIRRORS_STATE_DIR_NAME).join(format!("{}.json", config.name)));
+        Ok(Self { config, data_dir: data_dir.to_path_buf(), state })
+    }
+
+    /// Whether a scheduled mirror is due to run
+    pub fn is_due(&self) -> bool {
+        let Some(interval) = self.config.interval_minutes else {
+            return false;
+        };
+        let last_run = self.state.read().ok().flatten().and_then(|state| state.last_run_at);
+        last_run
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(&at).ok())
+            .is_none_or(|at| chrono::Utc::now().signed_duration_since(at) >= chrono::Duration::minutes(i64::from(interval)))
+    }
+
+    /// Where `path` goes at the destination: its previous copy if that is untouched,
+    /// otherwise its own name or, when another file took that, the first free
+    /// `name (n).ext`. Returns the path and whether it was renamed.
+    fn target(
+        &self,
+        destination: &dyn Destination,
+        path: &str,
+        size: u64,
+        previous: Option<&MirroredFile>,
+    ) -> Result<(String, bool), String> {
+        if let Some(previous) = previous {
+            match destination.size(&previous.remote_path)? {
+                None => return Ok((previous.remote_path.clone(), previous.remote_path != path)),
+                Some(remote) if remote == previous.size => {
+                    return Ok((previous.remote_path.clone(), previous.remote_path != path))
+                }
+                Some(_) => {}
+            }
+        } else {
+            match destination.size(path)? {
+                None => return Ok((path.to_string(), false)),
+                // Library files are named by id, so the same name and size means a
+                // copy made before this mirror kept state
+                Some(remote) if remote == size => return Ok((path.to_string(), false)),
+                Some(_) => {}
+            }
+        }
+        for n in 1..=MAX_CONFLICT_SUFFIX {
+            let candidate = conflict_name(path, n);
+            if destination.size(&candidate)?.is_none() {
+                return Ok((candidate, true));
+            }
+        }
+        Err(format!("No free name for {} at the destination", path))
+    }
+
+    /// Copy new and changed files to `destination`. Nothing at the destination is
+    /// ever deleted or overwritten unless this mirror put it there and it is
+    /// unchanged. A dry run only reports what would be copied.
+    pub fn run(&self, destination: &dyn Destination, dry_run: bool) -> Result<MirrorReport, String> {
+        let mut state = self.state.read()?.unwrap_or_default();
+        let mut report = MirrorReport { name: self.config.name.clone(), dry_run, ..Default::default() };
+        let mut files = Vec::new();
+        for folder in &self.config.folders {
+            walk(&self.data_dir, &self.data_dir.join(folder.dir_name()), &mut files)?;
+        }
+        files.sort();
+
+        for (path, full) in files {
+            let meta = match std::fs::metadata(&full) {
+                Ok(meta) => meta,
+                Err(e) => {
+                    report.failed.push((path, format!("Failed to read {}: {}", full.display(), e)));
+                    continue;
+                }
+            };
+            let modified = meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map_or(0, |d| d.as_secs());
+            let previous = state.files.get(&path).cloned();
+            if previous.as_ref().is_some_and(|p| p.size == meta.len() && p.modified == modified) {
+                report.unchanged += 1;
+                continue;
+            }
+            let data = match std::fs::read(&full) {
+                Ok(data) => data,
+                Err(e) => {
+                    report.failed.push((path, format!("Failed to read {}: {}", full.display(), e)));
+                    continue;
+                }
+            };
+            let sha256 = hex::encode(Sha256::digest(&data));
+            if let Some(previous) = previous.as_ref().filter(|p| p.sha256 == sha256) {
+                // Touched but not changed
+                if !dry_run {
+                    state.files.insert(path, MirroredFile { modified, ..previous.clone() });
+                }
+                report.unchanged += 1;
+                continue;
+            }
+
+            let (remote_path, renamed) = match self.target(destination, &path, meta.len(), previous.as_ref()) {
+                Ok(target) => target,
+                Err(e) => {
+                    report.failed.push((path, e));
+                    continue;
+                }
+            };
+            if !dry_run {
+                if let Err(e) = destination.put(&remote_path, &data) {
+                    report.failed.push((path, e));
+                    continue;
+                }
+                let mirrored = MirroredFile { size: meta.len(), modified, sha256, remote_path: remote_path.clone() };
+                state.files.insert(path.clone(), mirrored);
+            }
+            report.copied.push(MirrorAction { path, remote_path, renamed });
+        }
+
+        if !dry_run {
+            state.last_run_at = Some(chrono::Utc::now().to_rfc3339());
+            self.state.write(&state)?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirror_config(destination: &Path) -> MirrorConfig {
+        MirrorConfig {
+            name: "nas".to_string(),
+            folders: vec![LibraryFolder::Generations],
+            destination: DestinationConfig::Local { path: destination.to_path_buf() },
+            interval_minutes: Some(60),
+        }
+    }
+
+    #[test]
+    fn copies_new_and_changed_files_without_clobbering() {
+        let dir = tempfile::tempdir().unwrap();
+        let (data, nas) = (dir.path().join("data"), dir.path().join("nas"));
+        std::fs::create_dir_all(data.join("generations")).unwrap();
+        std::fs::create_dir_all(data.join("profiles")).unwrap();
+        std::fs::write(data.join("generations/a.wav"), b"first").unwrap();
+        std::fs::write(data.join("generations/b.wav"), b"second").unwrap();
+        std::fs::write(data.join("generations/c.wav.partial"), b"half").unwrap();
+        std::fs::write(data.join("profiles/p.wav"), b"not selected").unwrap();
+        // Something else already put a different b.wav on the NAS
+        std::fs::create_dir_all(nas.join("generations")).unwrap();
+        std::fs::write(nas.join("generations/b.wav"), b"someone else's").unwrap();
+
+        let config = mirror_config(&nas);
+        let mirror = Mirror::new(&config, &data, &dir.path().join("state")).unwrap();
+        let destination = config.destination.open().unwrap();
+        assert!(mirror.is_due());
+
+        let plan = mirror.run(destination.as_ref(), true).unwrap();
+        assert_eq!(plan.copied.len(), 2);
+        assert!(!nas.join("generations/a.wav").exists());
+        assert!(mirror.is_due());
+
+        let report = mirror.run(destination.as_ref(), false).unwrap();
+        assert_eq!(report.copied, plan.copied);
+        assert_eq!(report.copied[1].remote_path, "generations/b (1).wav");
+        assert!(report.copied[1].renamed);
+        assert_eq!(std::fs::read(nas.join("generations/b.wav")).unwrap(), b"someone else's");
+        assert_eq!(std::fs::read(nas.join("generations/b (1).wav")).unwrap(), b"second");
+        assert!(!nas.join("profiles").exists());
+        assert!(!mirror.is_due());
+
+        let again = mirror.run(destination.as_ref(), false).unwrap();
+        assert_eq!((again.copied.len(), again.unchanged), (0, 2));
+
+        // A changed file replaces the copy this mirror made...
+        std::fs::write(data.join("generations/a.wav"), b"first, redone").unwrap();
+        let report = mirror.run(destination.as_ref(), false).unwrap();
+        assert_eq!(report.copied[0].remote_path, "generations/a.wav");
+        assert_eq!(std::fs::read(nas.join("generations/a.wav")).unwrap(), b"first, redone");
+
+        // ...unless that copy was edited at the destination
+        std::fs::write(nas.join("generations/a.wav"), b"edited on the NAS").unwrap();
+        std::fs::write(data.join("generations/a.wav"), b"first, third take").unwrap();
+        let report = mirror.run(destination.as_ref(), false).unwrap();
+        assert_eq!(report.copied[0].remote_path, "generations/a (1).wav");
+        assert_eq!(std::fs::read(nas.join("generations/a.wav")).unwrap(), b"edited on the NAS");
+    }
+
+    #[test]
+    fn names_conflicts_and_rejects_bad_mirror_names() {
+        assert_eq!(conflict_name("generations/a.wav", 2), "generations/a (2).wav");
+        assert_eq!(conflict_name("README", 1), "README (1)");
+        assert_eq!(conflict_name("profiles/p/.hidden", 1), "profiles/p/.hidden (1)");
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = MirrorConfig { name: "../escape".to_string(), ..mirror_config(dir.path()) };
+        assert!(Mirror::new(&config, dir.path(), dir.path()).is_err());
+        let manual = MirrorConfig { interval_minutes: None, ..mirror_config(dir.path()) };
+        assert!(!Mirror::new(&manual, dir.path(), dir.path()).unwrap().is_due());
+    }
+}
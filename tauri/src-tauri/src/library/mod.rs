@@ -0,0 +1,132 @@
+pub mod chat;
+pub mod dataset;
+pub mod download;
+pub mod duplicates;
+pub mod game_export;
+pub mod mirror;
+pub mod narration;
+pub mod preview;
+pub mod reminders;
+pub mod search;
+pub mod soundboard;
+pub mod trash;
+pub mod waveform;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Largest page the backend's `/history` endpoint returns
+const HISTORY_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Generation,
+    Story,
+}
+
+impl ItemKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ItemKind::Generation => "generation",
+            ItemKind::Story => "story",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "generation" => Some(ItemKind::Generation),
+            "story" => Some(ItemKind::Story),
+            _ => None,
+        }
+    }
+}
+
+/// A searchable entry of the backend library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryItem {
+    pub id: String,
+    pub kind: ItemKind,
+    /// Voice name for generations, story name for stories
+    pub title: String,
+    /// Transcript of a generation, description of a story
+    pub text: String,
+    pub language: Option<String>,
+    /// Labels to filter on, such as `favorite`
+    pub tags: Vec<String>,
+    pub created_at: String,
+    /// Identifies a generation's audio file; changes whenever the audio does
+    pub audio: Option<String>,
+}
+
+fn str_field(value: &Value, field: &str) -> String {
+    value.get(field).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+impl LibraryItem {
+    /// Item for a `/history` entry
+    pub fn from_generation(entry: &Value) -> Self {
+        let favorite = entry.get("is_favorite").and_then(Value::as_bool).unwrap_or(false);
+        Self {
+            id: str_field(entry, "id"),
+            kind: ItemKind::Generation,
+            title: str_field(entry, "profile_name"),
+            text: str_field(entry, "text"),
+            language: entry.get("language").and_then(Value::as_str).map(str::to_string),
+            tags: favorite.then(|| "favorite".to_string()).into_iter().collect(),
+            created_at: str_field(entry, "created_at"),
+            audio: entry.get("audio_path").and_then(Value::as_str).map(|path| {
+                let duration = entry.get("duration").and_then(Value::as_f64).unwrap_or_default();
+                format!("{}#{}", path, duration)
+            }),
+        }
+    }
+
+    /// Item for a `/stories` entry
+    pub fn from_story(entry: &Value) -> Self {
+        Self {
+            id: str_field(entry, "id"),
+            kind: ItemKind::Story,
+            title: str_field(entry, "name"),
+            text: str_field(entry, "description"),
+            language: None,
+            tags: Vec::new(),
+            created_at: str_field(entry, "created_at"),
+            audio: None,
+        }
+    }
+}
+
+async fn get_json(client: &reqwest::Client, url: &str) -> Result<Value, String> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))
+}
+
+/// Every generation and story the backend at `base_url` knows about
+pub async fn fetch_items(base_url: &str) -> Result<Vec<LibraryItem>, String> {
+    let base_url = base_url.trim_end_matches('/');
+    let client = reqwest::Client::new();
+    let mut items = Vec::new();
+    let mut offset = 0;
+    loop {
+        let url = format!("{}/history?limit={}&offset={}", base_url, HISTORY_PAGE_SIZE, offset);
+        let page = get_json(&client, &url).await?;
+        let entries = page.get("items").and_then(Value::as_array).cloned().unwrap_or_default();
+        items.extend(entries.iter().map(LibraryItem::from_generation));
+        offset += entries.len();
+        let total = page.get("total").and_then(Value::as_u64).unwrap_or(0) as usize;
+        if entries.is_empty() || offset >= total {
+            break;
+        }
+    }
+    let stories = get_json(&client, &format!("{}/stories", base_url)).await?;
+    items.extend(stories.as_array().into_iter().flatten().map(LibraryItem::from_story));
+    Ok(items)
+}
@@ -0,0 +1,509 @@
+// Live narration of text files.
+//
+// A watch follows a file as it grows, like `tail -f`: a game's log, a chat client's
+// export or a subtitle feed. New lines are matched against the watch's rules, which pick
+// the preset that speaks them or drop them, and queue up for the app to speak one after
+// the other. Chatty files are kept in check by a per-watch rate and queue limit; the
+// oldest queued lines are skipped first, so narration stays close to what is happening.
+//
+// The same queue carries everything else the app speaks on its own: soundboard clips
+// fired by hotkey, scheduled reminders, chat and text queued by the UI. Each source has a
+// priority and a policy for when something is already playing, so a hotkey announcement
+// can cut into a long chat backlog instead of waiting behind it.
+use crate::launcher::config::LauncherConfig;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Longest line read from a watched file; anything beyond is cut off
+const MAX_LINE_CHARS: usize = 500;
+
+/// Soundboard clips played by hotkey
+pub const HOTKEY_SOURCE: &str = "hotkey";
+/// Reminders coming due
+pub const SCHEDULER_SOURCE: &str = "scheduler";
+/// Text queued by the UI or scripts through the app
+pub const API_SOURCE: &str = "api";
+
+/// Which lines a watch speaks, and with which preset
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NarrationRule {
+    /// Regular expression matched against each line. A `text` capture group, if there
+    /// is one, is what gets spoken, e.g. the message without the timestamp.
+    pub pattern: String,
+    /// Preset speaking matching lines instead of the watch's
+    pub preset: Option<String>,
+    /// Drop matching lines instead of speaking them
+    pub exclude: bool,
+}
+
+/// A text file narrated as it grows, from the `narration` list of the launcher config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NarrationWatch {
+    pub name: String,
+    pub path: PathBuf,
+    /// Preset for lines no rule gives one
+    pub preset: String,
+    /// Checked in order, the first match decides. Without rules every line is spoken;
+    /// with them, lines no rule matches are not.
+    #[serde(default)]
+    pub rules: Vec<NarrationRule>,
+    #[serde(default = "default_lines_per_minute")]
+    pub lines_per_minute: u32,
+    /// Lines waiting to be spoken before the oldest are skipped
+    #[serde(default = "default_max_queue")]
+    pub max_queue: usize,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn default_lines_per_minute() -> u32 {
+    20
+}
+
+fn default_max_queue() -> usize {
+    5
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// How many of a source's lines are spoken per minute and may wait in the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueLimits {
+    pub lines_per_minute: u32,
+    pub max_queue: usize,
+}
+
+impl QueueLimits {
+    /// For sources the user triggers: no rate limit, ten lines waiting
+    pub const DIRECT: QueueLimits = QueueLimits { lines_per_minute: 0, max_queue: 10 };
+}
+
+impl NarrationWatch {
+    pub fn limits(&self) -> QueueLimits {
+        QueueLimits { lines_per_minute: self.lines_per_minute, max_queue: self.max_queue }
+    }
+}
+
+/// What a source's line does when another line is playing or waiting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BusyPolicy {
+    /// Wait behind lines of the same or higher priority
+    #[default]
+    Enqueue,
+    /// Like `enqueue`, and cut off a line of lower priority that is playing
+    Interrupt,
+    /// Don't speak it at all
+    Drop,
+}
+
+/// How a source's lines are ordered against other sources', from the `input_queues`
+/// section of the launcher config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourcePriority {
+    /// Higher goes first
+    pub priority: u8,
+    pub when_busy: BusyPolicy,
+}
+
+impl Default for SourcePriority {
+    fn default() -> Self {
+        Self { priority: 20, when_busy: BusyPolicy::Enqueue }
+    }
+}
+
+impl SourcePriority {
+    /// Priority of `source` when the config doesn't set one. Watches get the default.
+    pub fn default_for(source: &str) -> Self {
+        match source {
+            HOTKEY_SOURCE => Self { priority: 100, when_busy: BusyPolicy::Interrupt },
+            SCHEDULER_SOURCE | API_SOURCE => Self { priority: 50, when_busy: BusyPolicy::Enqueue },
+            crate::library::chat::CHAT_SOURCE => Self { priority: 10, when_busy: BusyPolicy::Enqueue },
+            _ => Self::default(),
+        }
+    }
+}
+
+impl LauncherConfig {
+    pub fn source_priority(&self, source: &str) -> SourcePriority {
+        self.input_queues.get(source).copied().unwrap_or_else(|| SourcePriority::default_for(source))
+    }
+}
+
+/// A line to speak
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Utterance {
+    /// The watch, or other source such as the chat bridge, the line came from
+    pub watch: String,
+    pub preset: String,
+    pub text: String,
+    /// Soundboard clip or reminder whose rendered audio is played instead of generating
+    /// `text`
+    pub clip: Option<String>,
+}
+
+/// A watch with its rules compiled
+pub struct CompiledWatch {
+    pub watch: NarrationWatch,
+    rules: Vec<(Regex, NarrationRule)>,
+}
+
+impl CompiledWatch {
+    pub fn new(watch: &NarrationWatch) -> Result<Self, String> {
+        let rules = watch
+            .rules
+            .iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .map_err(|e| format!("Narration '{}': invalid pattern {:?}: {}", watch.name, rule.pattern, e))?;
+                Ok((regex, rule.clone()))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { watch: watch.clone(), rules })
+    }
+
+    /// What `line` should be spoken as, if at all
+    pub fn route(&self, line: &str) -> Option<Utterance> {
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        let utterance = |preset: &Option<String>, text: &str| {
+            let text = text.trim();
+            (!text.is_empty()).then(|| Utterance {
+                watch: self.watch.name.clone(),
+                preset: preset.clone().unwrap_or_else(|| self.watch.preset.clone()),
+                text: text.to_string(),
+                clip: None,
+            })
+        };
+        if self.rules.is_empty() {
+            return utterance(&None, line);
+        }
+        let (captures, rule) = self.rules.iter().find_map(|(regex, rule)| Some((regex.captures(line)?, rule)))?;
+        if rule.exclude {
+            return None;
+        }
+        utterance(&rule.preset, captures.name("text").map_or(line, |text| text.as_str()))
+    }
+}
+
+/// Follows a file from where it ended when the tail was created. A file that shrinks
+/// was truncated or rotated and is read again from the start.
+pub struct Tail {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl Tail {
+    pub fn at_end(path: &Path) -> Self {
+        let offset = std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        Self { path: path.to_path_buf(), offset, partial: Vec::new() }
+    }
+
+    /// Lines completed since the last call. A missing file has none yet.
+    pub fn read_lines(&mut self) -> std::io::Result<Vec<String>> {
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut added = Vec::new();
+        self.offset += file.read_to_end(&mut added)? as u64;
+        self.partial.extend_from_slice(&added);
+
+        let Some(end) = self.partial.iter().rposition(|b| *b == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let complete: Vec<u8> = self.partial.drain(..=end).collect();
+        Ok(String::from_utf8_lossy(&complete)
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().take(MAX_LINE_CHARS).collect())
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NarrationStatus {
+    pub pending: usize,
+    /// Lines dropped since the app started because the queue was full, their source's
+    /// policy drops lines while busy, or they were skipped
+    pub skipped: usize,
+    /// Source of the line playing
+    pub speaking: Option<String>,
+}
+
+/// What became of a pushed line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Queued {
+    Waiting,
+    /// Waiting, and the line playing should be stopped for it
+    Interrupting,
+    Dropped,
+}
+
+struct Pending {
+    utterance: Utterance,
+    lines_per_minute: u32,
+    priority: u8,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// Highest priority first, in order of arrival within a priority
+    pending: VecDeque<Pending>,
+    /// When each watch's lines were last spoken, within the past minute
+    spoken: HashMap<String, VecDeque<Instant>>,
+    /// Source and priority of the line playing
+    speaking: Option<(String, u8)>,
+    skipped: usize,
+}
+
+/// Lines waiting to be spoken, shared by the watches and the thread speaking them
+#[derive(Default)]
+pub struct Narrator {
+    state: Mutex<QueueState>,
+    skip_current: AtomicBool,
+}
+
+impl Narrator {
+    /// Queue `utterance` by its source's priority, skipping the source's oldest line
+    /// when it already has `max_queue` waiting. An interrupting line marks the line
+    /// playing as skipped; the caller stops its audio.
+    pub fn push(&self, utterance: Utterance, limits: QueueLimits, priority: SourcePriority) -> Queued {
+        let mut state = self.state.lock().unwrap();
+        if priority.when_busy == BusyPolicy::Drop && (state.speaking.is_some() || !state.pending.is_empty()) {
+            state.skipped += 1;
+            return Queued::Dropped;
+        }
+        let queued = state.pending.iter().filter(|queued| queued.utterance.watch == utterance.watch).count();
+        if queued >= limits.max_queue.max(1) {
+            if let Some(oldest) = state.pending.iter().position(|queued| queued.utterance.watch == utterance.watch) {
+                state.pending.remove(oldest);
+                state.skipped += 1;
+            }
+        }
+        let at = state.pending.iter().position(|queued| queued.priority < priority.priority).unwrap_or(state.pending.len());
+        state.pending.insert(at, Pending { utterance, lines_per_minute: limits.lines_per_minute, priority: priority.priority });
+
+        let interrupts = priority.when_busy == BusyPolicy::Interrupt
+            && state.speaking.as_ref().is_some_and(|(_, speaking)| *speaking < priority.priority);
+        if !interrupts {
+            return Queued::Waiting;
+        }
+        self.skip_current.store(true, Ordering::Relaxed);
+        state.skipped += 1;
+        Queued::Interrupting
+    }
+
+    /// Drop the queued lines of one source. Returns how many were dropped.
+    pub fn clear(&self, source: &str) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let before = state.pending.len();
+        state.pending.retain(|queued| queued.utterance.watch != source);
+        let dropped = before - state.pending.len();
+        state.skipped += dropped;
+        dropped
+    }
+
+    /// The first line in priority order whose watch hasn't used up its rate, counted as
+    /// spoken at `now`. It counts as playing until `finished`.
+    pub fn next(&self, now: Instant) -> Option<Utterance> {
+        let mut state = self.state.lock().unwrap();
+        for times in state.spoken.values_mut() {
+            while times.front().is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60)) {
+                times.pop_front();
+            }
+        }
+        let index = state.pending.iter().position(|queued| {
+            queued.lines_per_minute == 0
+                || state.spoken.get(&queued.utterance.watch).map_or(0, VecDeque::len) < queued.lines_per_minute as usize
+        })?;
+        let Pending { utterance, priority, .. } = state.pending.remove(index)?;
+        state.spoken.entry(utterance.watch.clone()).or_default().push_back(now);
+        state.speaking = Some((utterance.watch.clone(), priority));
+        self.skip_current.store(false, Ordering::Relaxed);
+        Some(utterance)
+    }
+
+    /// The line from `next` is done playing
+    pub fn finished(&self) {
+        self.state.lock().unwrap().speaking = None;
+    }
+
+    /// Stop waiting for the line being spoken, and with `all` drop the queue too.
+    /// Returns how many queued lines were dropped.
+    pub fn skip(&self, all: bool) -> usize {
+        self.skip_current.store(true, Ordering::Relaxed);
+        if !all {
+            return 0;
+        }
+        let mut state = self.state.lock().unwrap();
+        let dropped = state.pending.len();
+        state.pending.clear();
+        state.skipped += dropped;
+        dropped
+    }
+
+    /// Whether the line being spoken was skipped
+    pub fn current_skipped(&self) -> bool {
+        self.skip_current.load(Ordering::Relaxed)
+    }
+
+    pub fn status(&self) -> NarrationStatus {
+        let state = self.state.lock().unwrap();
+        NarrationStatus {
+            pending: state.pending.len(),
+            skipped: state.skipped,
+            speaking: state.speaking.as_ref().map(|(source, _)| source.clone()),
+        }
+    }
+}
+
+/// How long a WAV file plays
+pub fn wav_duration(wav: &[u8]) -> Option<Duration> {
+    let reader = hound::WavReader::new(std::io::Cursor::new(wav)).ok()?;
+    let rate = reader.spec().sample_rate;
+    (rate > 0).then(|| Duration::from_secs_f64(reader.duration() as f64 / rate as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(rules: Vec<NarrationRule>) -> NarrationWatch {
+        NarrationWatch {
+            name: "chat".to_string(),
+            path: PathBuf::from("chat.log"),
+            preset: "host".to_string(),
+            rules,
+            lines_per_minute: 2,
+            max_queue: 2,
+            enabled: true,
+        }
+    }
+
+    fn rule(pattern: &str, preset: Option<&str>, exclude: bool) -> NarrationRule {
+        NarrationRule { pattern: pattern.to_string(), preset: preset.map(str::to_string), exclude }
+    }
+
+    #[test]
+    fn rules_pick_lines_and_voices() {
+        let all = CompiledWatch::new(&watch(Vec::new())).unwrap();
+        assert_eq!(all.route("  gg  ").unwrap().text, "gg");
+        assert!(all.route("   ").is_none());
+
+        let chat = CompiledWatch::new(&watch(vec![
+            rule(r"^\[\d+:\d+\] bot:", None, true),
+            rule(r"^\[\d+:\d+\] mod_\w+: (?P<text>.*)", Some("moderator"), false),
+            rule(r"^\[\d+:\d+\] \w+: (?P<text>.*)", None, false),
+        ]))
+        .unwrap();
+        assert!(chat.route("[12:01] bot: Welcome!").is_none());
+        let moderator = chat.route("[12:02] mod_anna: Please be nice").unwrap();
+        assert_eq!((moderator.preset.as_str(), moderator.text.as_str()), ("moderator", "Please be nice"));
+        assert_eq!(chat.route("[12:03] max: hello").unwrap().preset, "host");
+        assert!(chat.route("Connected to server").is_none());
+        assert!(CompiledWatch::new(&watch(vec![rule("(", None, false)])).is_err());
+    }
+
+    #[test]
+    fn tails_new_complete_lines_and_follows_truncation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("game.log");
+        std::fs::write(&path, "old line\n").unwrap();
+        let mut tail = Tail::at_end(&path);
+        assert!(tail.read_lines().unwrap().is_empty());
+
+        let append = |text: &str| {
+            use std::io::Write;
+            std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(text.as_bytes()).unwrap();
+        };
+        append("first\r\nsec");
+        assert_eq!(tail.read_lines().unwrap(), ["first"]);
+        append("ond\n");
+        assert_eq!(tail.read_lines().unwrap(), ["second"]);
+
+        std::fs::write(&path, "rotated\n").unwrap();
+        assert_eq!(tail.read_lines().unwrap(), ["rotated"]);
+        assert!(Tail::at_end(&tmp.path().join("missing.log")).read_lines().unwrap().is_empty());
+    }
+
+    #[test]
+    fn queue_skips_oldest_and_limits_rate() {
+        let narrator = Narrator::default();
+        let chat = watch(Vec::new());
+        let priority = SourcePriority::default();
+        let line = |text: &str| Utterance { watch: "chat".to_string(), preset: "host".to_string(), text: text.to_string(), clip: None };
+        for text in ["one", "two", "three"] {
+            narrator.push(line(text), chat.limits(), priority);
+        }
+        assert_eq!(narrator.status(), NarrationStatus { pending: 2, skipped: 1, speaking: None });
+
+        let start = Instant::now();
+        assert_eq!(narrator.next(start).unwrap().text, "two");
+        assert_eq!(narrator.next(start).unwrap().text, "three");
+        narrator.push(line("four"), chat.limits(), priority);
+        assert!(narrator.next(start + Duration::from_secs(30)).is_none());
+        assert_eq!(narrator.next(start + Duration::from_secs(60)).unwrap().text, "four");
+
+        narrator.push(line("five"), chat.limits(), priority);
+        assert_eq!(narrator.clear("chat"), 1);
+        narrator.push(line("six"), chat.limits(), priority);
+        assert_eq!(narrator.skip(true), 1);
+        assert!(narrator.current_skipped());
+        narrator.finished();
+        assert_eq!(narrator.status(), NarrationStatus { pending: 0, skipped: 3, speaking: None });
+    }
+
+    #[test]
+    fn higher_priorities_go_first_and_interrupt() {
+        let narrator = Narrator::default();
+        let line = |source: &str, text: &str| Utterance {
+            watch: source.to_string(),
+            preset: "host".to_string(),
+            text: text.to_string(),
+            clip: None,
+        };
+        let chat = SourcePriority::default_for(crate::library::chat::CHAT_SOURCE);
+        let limits = QueueLimits { lines_per_minute: 0, max_queue: 10 };
+        for text in ["one", "two", "three"] {
+            assert_eq!(narrator.push(line("chat", text), limits, chat), Queued::Waiting);
+        }
+        let now = Instant::now();
+        assert_eq!(narrator.next(now).unwrap().text, "one");
+
+        let reminder = SourcePriority::default_for(SCHEDULER_SOURCE);
+        assert_eq!(narrator.push(line(SCHEDULER_SOURCE, "stretch"), QueueLimits::DIRECT, reminder), Queued::Waiting);
+        assert!(!narrator.current_skipped());
+        let hotkey = SourcePriority::default_for(HOTKEY_SOURCE);
+        assert_eq!(narrator.push(line(HOTKEY_SOURCE, "brb"), QueueLimits::DIRECT, hotkey), Queued::Interrupting);
+        assert!(narrator.current_skipped());
+        narrator.finished();
+
+        let order: Vec<_> = std::iter::from_fn(|| narrator.next(now)).map(|utterance| utterance.text).collect();
+        assert_eq!(order, ["brb", "stretch", "two", "three"]);
+        assert_eq!(narrator.status().speaking.as_deref(), Some("chat"));
+        let quiet = SourcePriority { priority: 0, when_busy: BusyPolicy::Drop };
+        assert_eq!(narrator.push(line("game", "gg"), limits, quiet), Queued::Dropped);
+        narrator.finished();
+        assert_eq!(narrator.push(line("game", "gg"), limits, quiet), Queued::Waiting);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::launcher::state::StateFile;
+use crate::library::{ItemKind, LibraryItem};
+use crate::postprocess::opus::{encode_ogg_opus, PREVIEW_BITRATE_BPS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+pub const PREVIEWS_DIR_NAME: &str = "previews";
+pub const PREVIEW_EXTENSION: &str = "opus";
+
+/// Which audio a preview was encoded from, stored next to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PreviewInfo {
+    /// `LibraryItem::audio` of the master
+    source: String,
+    bitrate_bps: i32,
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Opus previews of generation audio under `cache/previews`, one `<id>.opus` per
+/// generation. The WAV masters stay in the backend and are what exports use.
+pub struct PreviewCache {
+    dir: PathBuf,
+}
+
+impl PreviewCache {
+    pub fn new(cache_dir: &Path) -> Self {
+        Self { dir: cache_dir.join(PREVIEWS_DIR_NAME) }
+    }
+
+    fn preview_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, PREVIEW_EXTENSION))
+    }
+
+    fn info(&self, id: &str) -> StateFile<PreviewInfo> {
+        StateFile::new(self.dir.join(format!("{}.json", id)))
+    }
+
+    /// Preview of generation `id`, if one has been encoded
+    pub fn get(&self, id: &str) -> Option<PathBuf> {
+        let path = self.preview_path(id);
+        (is_valid_id(id) && path.is_file()).then_some(path)
+    }
+
+    /// Whether `item` has a preview of its current audio
+    pub fn is_current(&self, item: &LibraryItem) -> bool {
+        is_valid_id(&item.id)
+            && self.preview_path(&item.id).is_file()
+            && self
+                .info(&item.id)
+                .read()
+                .ok()
+                .flatten()
+                .is_some_and(|info| Some(&info.source) == item.audio.as_ref() && info.bitrate_bps == PREVIEW_BITRATE_BPS)
+    }
+
+    /// Store the preview of generation `id` encoded from `source`
+    pub fn put(&self, id: &str, source: &str, preview: &[u8]) -> Result<PathBuf, String> {
+        if !is_valid_id(id) {
+            return Err(format!("Invalid generation id {:?}", id));
+        }
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        // Written aside and renamed, so a player never sees a half-written preview
+        let path = self.preview_path(id);
+        let partial = path.with_extension("opus.partial");
+        std::fs::write(&partial, preview).map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        std::fs::rename(&partial, &path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        self.info(id).write(&PreviewInfo { source: source.to_string(), bitrate_bps: PREVIEW_BITRATE_BPS })?;
+        Ok(path)
+    }
+
+    /// Delete the previews of generations that aren't in `keep`
+    pub fn prune(&self, keep: &HashSet<&str>) -> Result<usize, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to list {}: {}", self.dir.display(), e)),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != PREVIEW_EXTENSION) {
+                continue;
+            }
+            let id = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            if is_valid_id(id) && !keep.contains(id) {
+                std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                self.info(id).remove()?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Download the WAV master of generation `id` from the backend at `base_url`
+pub fn download_audio(base_url: &str, id: &str) -> Result<Vec<u8>, String> {
+    let url = format!("{}/audio/{}", base_url.trim_end_matches('/'), id);
+    reqwest::blocking::get(&url)
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))
+}
+
+/// A finished preview, or why it couldn't be made
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreviewEvent {
+    pub generation_id: String,
+    pub path: Option<PathBuf>,
+    pub error: Option<String>,
+}
+
+struct PreviewJob {
+    base_url: String,
+    id: String,
+    source: String,
+}
+
+/// Encodes previews one at a time on a background thread, so the library can show
+/// items immediately and switch to previews as they become ready. The thread stops
+/// when the queue is dropped.
+pub struct PreviewQueue {
+    cache: Arc<PreviewCache>,
+    sender: mpsc::Sender<PreviewJob>,
+    /// Generation ids queued or being encoded
+    queued: Arc<Mutex<HashSet<String>>>,
+}
+
+impl PreviewQueue {
+    /// Start the worker. `fetch` gets a master's WAV bytes from a base URL and
+    /// generation id (normally `download_audio`); `on_done` hears about each preview.
+    pub fn start(
+        cache: PreviewCache,
+        fetch: impl Fn(&str, &str) -> Result<Vec<u8>, String> + Send + 'static,
+        on_done: impl Fn(PreviewEvent) + Send + 'static,
+    ) -> Self {
+        let cache = Arc::new(cache);
+        let queued = Arc::new(Mutex::new(HashSet::new()));
+        let (sender, receiver) = mpsc::channel::<PreviewJob>();
+        let (worker_cache, worker_queued) = (cache.clone(), queued.clone());
+        std::thread::spawn(move || {
+            for job in receiver {
+                let result = fetch(&job.base_url, &job.id)
+                    .and_then(|wav| encode_ogg_opus(&wav, PREVIEW_BITRATE_BPS))
+                    .and_then(|preview| worker_cache.put(&job.id, &job.source, &preview));
+                worker_queued.lock().unwrap().remove(&job.id);
+                let (path, error) = match result {
+                    Ok(path) => (Some(path), None),
+                    Err(e) => (None, Some(e)),
+                };
+                on_done(PreviewEvent { generation_id: job.id, path, error });
+            }
+        });
+        Self { cache, sender, queued }
+    }
+
+    pub fn cache(&self) -> &PreviewCache {
+        &self.cache
+    }
+
+    /// Queue a preview for `item` unless it has a current one or is already queued.
+    /// Returns whether it was queued.
+    pub fn enqueue(&self, base_url: &str, item: &LibraryItem) -> bool {
+        let Some(source) = item.audio.clone().filter(|_| item.kind == ItemKind::Generation) else {
+            return false;
+        };
+        if self.cache.is_current(item) || !self.queued.lock().unwrap().insert(item.id.clone()) {
+            return false;
+        }
+        let job = PreviewJob { base_url: base_url.to_string(), id: item.id.clone(), source };
+        if self.sender.send(job).is_err() {
+            self.queued.lock().unwrap().remove(&item.id);
+            return false;
+        }
+        true
+    }
+
+    /// Previews queued or being encoded
+    pub fn pending(&self) -> usize {
+        self.queued.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postprocess::pcm::Pcm;
+    use std::time::Duration;
+
+    fn generation(id: &str, audio: &str) -> LibraryItem {
+        LibraryItem {
+            id: id.to_string(),
+            kind: ItemKind::Generation,
+            title: "Ada".to_string(),
+            text: "Hello".to_string(),
+            language: None,
+            tags: Vec::new(),
+            created_at: String::new(),
+            audio: Some(audio.to_string()),
+        }
+    }
+
+    fn master(_: &str, id: &str) -> Result<Vec<u8>, String> {
+        if id == "broken" {
+            return Err("backend said no".to_string());
+        }
+        let spec = hound::WavSpec { channels: 1, sample_rate: 24000, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let samples = (0..24000).map(|i| (i as f32 * 0.05).sin() * 0.3).collect();
+        Pcm { spec, samples }.write()
+    }
+
+    #[test]
+    fn encodes_queued_previews_in_the_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let (events, received) = mpsc::channel();
+        let queue = PreviewQueue::start(PreviewCache::new(dir.path()), master, move |event| {
+            let _ = events.send(event);
+        });
+
+        let item = generation("gen-1", "gen-1.wav#1");
+        assert!(queue.enqueue("http://backend", &item));
+        assert!(queue.enqueue("http://backend", &generation("broken", "broken.wav#1")));
+        let done = received.recv_timeout(Duration::from_secs(30)).unwrap();
+        let failed = received.recv_timeout(Duration::from_secs(30)).unwrap();
+        assert_eq!(done.path, queue.cache().get("gen-1"));
+        assert!(failed.error.unwrap().contains("backend said no"));
+        assert_eq!(queue.pending(), 0);
+
+        // Current previews aren't redone, changed audio is
+        assert!(!queue.enqueue("http://backend", &item));
+        assert!(queue.enqueue("http://backend", &generation("gen-1", "gen-1.wav#2")));
+    }
+
+    #[test]
+    fn prunes_previews_of_deleted_generations() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PreviewCache::new(dir.path());
+        cache.put("gen-1", "a#1", b"OggS").unwrap();
+        cache.put("gen-2", "b#1", b"OggS").unwrap();
+        assert!(cache.put("../gen", "c#1", b"OggS").is_err());
+
+        assert_eq!(cache.prune(&HashSet::from(["gen-2"])).unwrap(), 1);
+        assert_eq!(cache.get("gen-1"), None);
+        assert!(cache.is_current(&generation("gen-2", "b#1")));
+    }
+}
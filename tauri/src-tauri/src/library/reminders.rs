@@ -0,0 +1,192 @@
+use crate::launcher::config::LauncherConfig;
+use crate::launcher::state::StateFile;
+use crate::library::soundboard::{ResolvedClip, SoundboardClip};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const REMINDERS_STATE_FILE_NAME: &str = "reminders.json";
+/// Prefix of the soundboard cache entries of reminders, keeping them apart from clips
+pub const REMINDER_CLIP_PREFIX: &str = "reminder-";
+/// How late a daily or one-off reminder is still spoken, e.g. after the computer woke
+/// up; older ones are skipped rather than spoken out of context
+const GRACE_MINUTES: i64 = 10;
+
+/// When a reminder speaks, in local time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Schedule {
+    /// At `time` (`HH:MM`) every day, or only on `days` (`mon` ... `sun`) when given
+    Daily {
+        time: String,
+        #[serde(default)]
+        days: Vec<Weekday>,
+    },
+    /// Every `minutes` while the app runs
+    Every { minutes: u32 },
+    /// Once, at `at` (`YYYY-MM-DDTHH:MM`)
+    Once { at: String },
+}
+
+/// A spoken announcement on a schedule, from the `reminders` list of the launcher config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reminder {
+    /// Identifies the reminder; letters, digits, - and _
+    pub name: String,
+    pub text: String,
+    pub preset: String,
+    pub schedule: Schedule,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Schedule {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Schedule::Daily { time, .. } => parse_time(time).map(|_| ()),
+            Schedule::Every { minutes: 0 } => Err("A reminder can't repeat every 0 minutes".to_string()),
+            Schedule::Every { .. } => Ok(()),
+            Schedule::Once { at } => parse_at(at).map(|_| ()),
+        }
+    }
+
+    /// The latest time at or before `now` the reminder was meant to speak
+    fn last_occurrence(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Schedule::Daily { time, days } => {
+                let time = parse_time(time).ok()?;
+                (0..=7)
+                    .map(|back| now.date_naive() - Duration::days(back))
+                    .filter(|date| days.is_empty() || days.contains(&date.weekday()))
+                    .filter_map(|date| Local.from_local_datetime(&date.and_time(time)).earliest())
+                    .find(|at| *at <= now)
+            }
+            Schedule::Once { at } => Local.from_local_datetime(&parse_at(at).ok()?).earliest().filter(|at| *at <= now),
+            Schedule::Every { .. } => None,
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("Invalid reminder time {:?}, use HH:MM", time))
+}
+
+fn parse_at(at: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M").map_err(|_| format!("Invalid reminder date {:?}, use YYYY-MM-DDTHH:MM", at))
+}
+
+impl Reminder {
+    /// Whether it should speak at `now`, having last spoken at `last_spoken`
+    pub fn is_due(&self, now: DateTime<Local>, last_spoken: Option<DateTime<Local>>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match &self.schedule {
+            Schedule::Every { minutes } => {
+                last_spoken.is_none_or(|last| now - last >= Duration::minutes(i64::from(*minutes)))
+            }
+            schedule => schedule.last_occurrence(now).is_some_and(|at| {
+                now - at <= Duration::minutes(GRACE_MINUTES) && last_spoken.is_none_or(|last| last < at)
+            }),
+        }
+    }
+
+    /// Name of its rendered audio in the soundboard cache
+    pub fn clip_name(&self) -> String {
+        format!("{}{}", REMINDER_CLIP_PREFIX, self.name)
+    }
+}
+
+impl LauncherConfig {
+    /// What `reminder` speaks, rendered and played like a soundboard clip
+    pub fn reminder_clip(&self, reminder: &Reminder) -> Result<ResolvedClip, String> {
+        reminder.schedule.validate().map_err(|e| format!("Reminder '{}': {}", reminder.name, e))?;
+        let clip = SoundboardClip { text: reminder.text.clone(), preset: reminder.preset.clone(), hotkey: None };
+        self.clip_for(&reminder.clip_name(), &clip)
+    }
+}
+
+/// When each reminder last spoke
+pub struct ReminderLog {
+    file: StateFile<BTreeMap<String, String>>,
+}
+
+impl ReminderLog {
+    pub fn new(state_dir: &Path) -> Self {
+        Self { file: StateFile::new(state_dir.join(REMINDERS_STATE_FILE_NAME)) }
+    }
+
+    pub fn last_spoken(&self, name: &str) -> Option<DateTime<Local>> {
+        let spoken = self.file.read().ok()??;
+        DateTime::parse_from_rfc3339(spoken.get(name)?).ok().map(|at| at.with_timezone(&Local))
+    }
+
+    pub fn record(&self, name: &str, at: DateTime<Local>) -> Result<(), String> {
+        self.file.update(|spoken| {
+            let mut spoken = spoken.unwrap_or_default();
+            spoken.insert(name.to_string(), at.to_rfc3339());
+            Some(spoken)
+        })?;
+        Ok(())
+    }
+
+    /// The reminders in `reminders` due at `now`
+    pub fn due<'a>(&self, reminders: &'a [Reminder], now: DateTime<Local>) -> Vec<&'a Reminder> {
+        reminders.iter().filter(|reminder| reminder.is_due(now, self.last_spoken(&reminder.name))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(at: &str) -> DateTime<Local> {
+        Local.from_local_datetime(&parse_at(at).unwrap()).earliest().unwrap()
+    }
+
+    fn reminder(schedule: Schedule) -> Reminder {
+        Reminder { name: "water".to_string(), text: "Drink some water".to_string(), preset: "host".to_string(), schedule, enabled: true }
+    }
+
+    #[test]
+    fn daily_reminders_speak_once_per_occurrence() {
+        // 2026-10-12 is a Monday
+        let weekdays = reminder(Schedule::Daily { time: "09:30".to_string(), days: vec![Weekday::Mon, Weekday::Tue] });
+        assert!(!weekdays.is_due(local("2026-10-12T09:29"), None));
+        assert!(weekdays.is_due(local("2026-10-12T09:31"), None));
+        assert!(!weekdays.is_due(local("2026-10-12T09:31"), Some(local("2026-10-12T09:30"))));
+        // Too late to still make sense, and not on Wednesdays
+        assert!(!weekdays.is_due(local("2026-10-12T11:00"), None));
+        assert!(!weekdays.is_due(local("2026-10-14T09:31"), Some(local("2026-10-13T09:30"))));
+        assert!(reminder(Schedule::Daily { time: "9:30pm".to_string(), days: Vec::new() }).schedule.validate().is_err());
+    }
+
+    #[test]
+    fn repeating_and_one_off_reminders() {
+        let every = reminder(Schedule::Every { minutes: 45 });
+        assert!(every.is_due(local("2026-10-12T10:00"), None));
+        assert!(!every.is_due(local("2026-10-12T10:44"), Some(local("2026-10-12T10:00"))));
+        assert!(every.is_due(local("2026-10-12T10:45"), Some(local("2026-10-12T10:00"))));
+
+        let once = reminder(Schedule::Once { at: "2026-10-12T18:00".to_string() });
+        assert!(once.is_due(local("2026-10-12T18:02"), None));
+        assert!(!once.is_due(local("2026-10-12T18:02"), Some(local("2026-10-12T18:00"))));
+        assert!(!Reminder { enabled: false, ..once }.is_due(local("2026-10-12T18:02"), None));
+    }
+
+    #[test]
+    fn remembers_when_each_reminder_spoke() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = ReminderLog::new(tmp.path());
+        let reminders = [reminder(Schedule::Every { minutes: 30 })];
+        assert_eq!(log.due(&reminders, local("2026-10-12T10:00")).len(), 1);
+        log.record("water", local("2026-10-12T10:00")).unwrap();
+        assert!(log.due(&reminders, local("2026-10-12T10:10")).is_empty());
+        assert_eq!(log.last_spoken("water"), Some(local("2026-10-12T10:00")));
+    }
+}
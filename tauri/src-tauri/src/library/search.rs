@@ -0,0 +1,269 @@
+use crate::library::{ItemKind, LibraryItem};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{AllQuery, QueryParser};
+use tantivy::schema::{Field, Schema, Value, STORED, STRING, TEXT};
+use tantivy::snippet::SnippetGenerator;
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+
+/// Index directory under the cache dir. The version is bumped whenever the schema
+/// changes, which makes the next sync rebuild the index from scratch.
+pub const INDEX_DIR_NAME: &str = "library-index-v1";
+const WRITER_MEMORY_BYTES: usize = 50_000_000;
+
+struct Fields {
+    id: Field,
+    kind: Field,
+    title: Field,
+    text: Field,
+    language: Field,
+    tags: Field,
+    created_at: Field,
+    /// Hash of the indexed content, so syncs only rewrite items that changed
+    fingerprint: Field,
+}
+
+fn schema() -> (Schema, Fields) {
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        id: builder.add_text_field("id", STRING | STORED),
+        kind: builder.add_text_field("kind", STRING | STORED),
+        title: builder.add_text_field("title", TEXT | STORED),
+        text: builder.add_text_field("text", TEXT | STORED),
+        language: builder.add_text_field("language", STRING | STORED),
+        tags: builder.add_text_field("tags", TEXT | STORED),
+        created_at: builder.add_text_field("created_at", STRING | STORED),
+        fingerprint: builder.add_text_field("fingerprint", STORED),
+    };
+    (builder.build(), fields)
+}
+
+fn fingerprint(item: &LibraryItem) -> String {
+    hex::encode(Sha256::digest(serde_json::to_vec(item).unwrap_or_default()))
+}
+
+/// What a sync changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    /// Items in the index after the sync
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub kind: ItemKind,
+    pub title: String,
+    pub language: Option<String>,
+    pub created_at: String,
+    pub score: f32,
+    /// Best matching passage of the text
+    pub snippet: String,
+    /// Byte ranges of the matched words in `snippet`
+    pub highlights: Vec<(usize, usize)>,
+}
+
+/// Full-text index over the library, kept on disk so searches don't need the backend
+pub struct LibraryIndex {
+    index: Index,
+    reader: IndexReader,
+    fields: Fields,
+}
+
+fn index_error(e: tantivy::TantivyError) -> String {
+    format!("Library index error: {}", e)
+}
+
+impl LibraryIndex {
+    /// Open the index under `cache_dir`, creating it if needed
+    pub fn open(cache_dir: &Path) -> Result<Self, String> {
+        let dir = cache_dir.join(INDEX_DIR_NAME);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let directory =
+            MmapDirectory::open(&dir).map_err(|e| format!("Failed to open library index {}: {}", dir.display(), e))?;
+        let (schema, fields) = schema();
+        let index = Index::open_or_create(directory, schema).map_err(index_error)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(index_error)?;
+        Ok(Self { index, reader, fields })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn value(&self, doc: &TantivyDocument, field: Field) -> Option<String> {
+        doc.get_first(field).and_then(|v| v.as_str()).map(str::to_string)
+    }
+
+    /// Fingerprint of every indexed item, by id
+    fn indexed(&self) -> Result<HashMap<String, String>, String> {
+        let searcher = self.reader.searcher();
+        let addresses = searcher.search(&AllQuery, &DocSetCollector).map_err(index_error)?;
+        let mut indexed = HashMap::new();
+        for address in addresses {
+            let doc: TantivyDocument = searcher.doc(address).map_err(index_error)?;
+            if let Some(id) = self.value(&doc, self.fields.id) {
+                indexed.insert(id, self.value(&doc, self.fields.fingerprint).unwrap_or_default());
+            }
+        }
+        Ok(indexed)
+    }
+
+    fn document(&self, item: &LibraryItem, fingerprint: &str) -> TantivyDocument {
+        let f = &self.fields;
+        let mut doc = TantivyDocument::default();
+        doc.add_text(f.id, &item.id);
+        doc.add_text(f.kind, item.kind.as_str());
+        doc.add_text(f.title, &item.title);
+        doc.add_text(f.text, &item.text);
+        if let Some(language) = &item.language {
+            doc.add_text(f.language, language);
+        }
+        for tag in &item.tags {
+            doc.add_text(f.tags, tag);
+        }
+        doc.add_text(f.created_at, &item.created_at);
+        doc.add_text(f.fingerprint, fingerprint);
+        doc
+    }
+
+    /// Make the index match `items`, the full library as the backend reports it
+    pub fn sync(&self, items: &[LibraryItem]) -> Result<SyncReport, String> {
+        let indexed = self.indexed()?;
+        let mut writer: IndexWriter<TantivyDocument> =
+            self.index.writer_with_num_threads(1, WRITER_MEMORY_BYTES).map_err(index_error)?;
+        let mut report = SyncReport::default();
+        let mut seen = HashSet::new();
+        for item in items {
+            if !seen.insert(item.id.as_str()) {
+                continue;
+            }
+            let fingerprint = fingerprint(item);
+            match indexed.get(&item.id) {
+                Some(existing) if *existing == fingerprint => continue,
+                Some(_) => {
+                    writer.delete_term(Term::from_field_text(self.fields.id, &item.id));
+                    report.updated += 1;
+                }
+                None => report.added += 1,
+            }
+            writer.add_document(self.document(item, &fingerprint)).map_err(index_error)?;
+        }
+        for id in indexed.keys().filter(|id| !seen.contains(id.as_str())) {
+            writer.delete_term(Term::from_field_text(self.fields.id, id));
+            report.removed += 1;
+        }
+        if report.added + report.updated + report.removed > 0 {
+            writer.commit().map_err(index_error)?;
+            self.reader.reload().map_err(index_error)?;
+        }
+        report.total = self.len();
+        Ok(report)
+    }
+
+    /// Best matches for `query` over titles, text and tags. The query syntax allows
+    /// phrases, `+required`/`-excluded` words and `field:value`; invalid parts are
+    /// ignored rather than failing the search.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+        let f = &self.fields;
+        let searcher = self.reader.searcher();
+        let mut parser = QueryParser::for_index(&self.index, vec![f.title, f.text, f.tags]);
+        parser.set_field_boost(f.title, 2.0);
+        let (query, _) = parser.parse_query_lenient(query);
+
+        let top = searcher.search(&query, &TopDocs::with_limit(limit.max(1))).map_err(index_error)?;
+        let snippets = SnippetGenerator::create(&searcher, &query, f.text).map_err(index_error)?;
+        let mut hits = Vec::new();
+        for (score, address) in top {
+            let doc: TantivyDocument = searcher.doc(address).map_err(index_error)?;
+            let Some(kind) = self.value(&doc, f.kind).and_then(|k| ItemKind::parse(&k)) else {
+                continue;
+            };
+            let snippet = snippets.snippet_from_doc(&doc);
+            hits.push(SearchHit {
+                id: self.value(&doc, f.id).unwrap_or_default(),
+                kind,
+                title: self.value(&doc, f.title).unwrap_or_default(),
+                language: self.value(&doc, f.language),
+                created_at: self.value(&doc, f.created_at).unwrap_or_default(),
+                score,
+                snippet: snippet.fragment().to_string(),
+                highlights: snippet.highlighted().iter().map(|r| (r.start, r.end)).collect(),
+            });
+        }
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generation(id: &str, voice: &str, text: &str, tags: &[&str]) -> LibraryItem {
+        LibraryItem {
+            id: id.to_string(),
+            kind: ItemKind::Generation,
+            title: voice.to_string(),
+            text: text.to_string(),
+            language: Some("en".to_string()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: "2026-01-01T00:00:00".to_string(),
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn finds_items_by_text_title_and_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = LibraryIndex::open(dir.path()).unwrap();
+        index
+            .sync(&[
+                generation("1", "Narrator", "The lighthouse keeper climbed the stairs.", &["favorite"]),
+                generation("2", "Ada", "A storm rolled in over the lighthouse.", &[]),
+                generation("3", "Ada", "Breakfast was served at eight.", &[]),
+            ])
+            .unwrap();
+
+        let hits = index.search("lighthouse", 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        let hit = hits.iter().find(|h| h.id == "2").unwrap();
+        let (start, end) = hit.highlights[0];
+        assert_eq!(&hit.snippet[start..end], "lighthouse");
+
+        assert_eq!(index.search("narrator", 10).unwrap()[0].id, "1");
+        assert_eq!(index.search("tags:favorite", 10).unwrap()[0].id, "1");
+        assert!(index.search("\"unbalanced", 10).is_ok());
+    }
+
+    #[test]
+    fn sync_only_rewrites_changes_and_survives_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let items = vec![generation("1", "Ada", "first take", &[]), generation("2", "Ada", "second take", &[])];
+        let report = LibraryIndex::open(dir.path()).unwrap().sync(&items).unwrap();
+        assert_eq!((report.added, report.total), (2, 2));
+
+        let index = LibraryIndex::open(dir.path()).unwrap();
+        assert_eq!(index.len(), 2);
+        let changed = vec![generation("1", "Ada", "first take, redone", &["favorite"])];
+        let report = index.sync(&changed).unwrap();
+        assert_eq!(report, SyncReport { added: 0, updated: 1, removed: 1, total: 1 });
+        assert_eq!(index.sync(&changed).unwrap().updated, 0);
+        assert_eq!(index.search("redone", 5).unwrap().len(), 1);
+        assert!(index.search("second", 5).unwrap().is_empty());
+    }
+}
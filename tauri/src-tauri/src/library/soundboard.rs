@@ -0,0 +1,235 @@
+use crate::launcher::config::LauncherConfig;
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+pub const SOUNDBOARD_DIR_NAME: &str = "soundboard";
+
+/// A phrase on the soundboard, spoken with a preset's voice and settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundboardClip {
+    pub text: String,
+    pub preset: String,
+    /// Accelerator the app registers for the clip, e.g. `Ctrl+Alt+1`
+    pub hotkey: Option<String>,
+}
+
+/// A clip ready to render: the `/generate` request it is made from and the devices it
+/// plays on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedClip {
+    pub name: String,
+    pub request: Value,
+    pub output_devices: Vec<String>,
+    /// The preset's ducking of other applications
+    pub duck: Option<f32>,
+    /// Changes whenever anything that shapes the audio does, which invalidates the
+    /// rendered clip
+    pub key: String,
+}
+
+/// `/generate` requests are hashed as serialized; `serde_json` keeps object keys sorted
+pub fn clip_key(request: &Value) -> String {
+    hex::encode(Sha256::digest(request.to_string().as_bytes()))
+}
+
+impl LauncherConfig {
+    pub fn resolve_clip(&self, name: &str) -> Result<ResolvedClip, String> {
+        let clip = self.soundboard.get(name).ok_or_else(|| format!("Unknown soundboard clip '{}'", name))?;
+        self.clip_for(name, clip)
+    }
+
+    /// `clip` under `name`, for clips that aren't on the soundboard itself
+    pub fn clip_for(&self, name: &str, clip: &SoundboardClip) -> Result<ResolvedClip, String> {
+        if clip.text.trim().is_empty() {
+            return Err(format!("Soundboard clip '{}' has no text", name));
+        }
+        let preset = self.resolve_preset(&clip.preset)?;
+        let request = preset.generate_request(&clip.text);
+        Ok(ResolvedClip {
+            name: name.to_string(),
+            key: clip_key(&request),
+            request,
+            output_devices: preset.output_devices,
+            duck: preset.duck,
+        })
+    }
+}
+
+/// A configured clip as the soundboard UI lists it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClipStatus {
+    pub name: String,
+    pub text: String,
+    pub hotkey: Option<String>,
+    /// Whether triggering it plays at once rather than generating first
+    pub rendered: bool,
+    /// Why it can't be rendered, such as an unknown preset
+    pub error: Option<String>,
+}
+
+/// What a rendered clip was made from, stored next to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RenderedClip {
+    key: String,
+    generation_id: String,
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Rendered soundboard clips under `cache/soundboard`, one `<name>.wav` per clip, so
+/// triggering one plays a file instead of waiting for a generation
+pub struct Soundboard {
+    dir: PathBuf,
+}
+
+impl Soundboard {
+    pub fn new(cache_dir: &Path) -> Self {
+        Self { dir: cache_dir.join(SOUNDBOARD_DIR_NAME) }
+    }
+
+    fn clip_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.wav", name))
+    }
+
+    fn info(&self, name: &str) -> StateFile<RenderedClip> {
+        StateFile::new(self.dir.join(format!("{}.json", name)))
+    }
+
+    /// The rendered audio of `clip`, unless it is missing or was rendered from other
+    /// settings or text
+    pub fn get(&self, clip: &ResolvedClip) -> Option<PathBuf> {
+        let path = self.clip_path(&clip.name);
+        let current = is_valid_name(&clip.name)
+            && path.is_file()
+            && self.info(&clip.name).read().ok().flatten().is_some_and(|info| info.key == clip.key);
+        current.then_some(path)
+    }
+
+    /// Store the audio of `clip`, rendered as generation `generation_id`
+    pub fn put(&self, clip: &ResolvedClip, generation_id: &str, wav: &[u8]) -> Result<PathBuf, String> {
+        if !is_valid_name(&clip.name) {
+            return Err(format!("Invalid soundboard clip name {:?}; use letters, digits, - and _", clip.name));
+        }
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        // Written aside and renamed, so a trigger never plays a half-written clip
+        let path = self.clip_path(&clip.name);
+        let partial = path.with_extension("wav.partial");
+        std::fs::write(&partial, wav).map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+        std::fs::rename(&partial, &path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        self.info(&clip.name).write(&RenderedClip { key: clip.key.clone(), generation_id: generation_id.to_string() })?;
+        Ok(path)
+    }
+
+    /// Every clip in `config`, in name order
+    pub fn status(&self, config: &LauncherConfig) -> Vec<ClipStatus> {
+        config
+            .soundboard
+            .iter()
+            .map(|(name, clip)| {
+                let resolved = config.resolve_clip(name);
+                ClipStatus {
+                    name: name.clone(),
+                    text: clip.text.clone(),
+                    hotkey: clip.hotkey.clone(),
+                    rendered: resolved.as_ref().is_ok_and(|resolved| self.get(resolved).is_some()),
+                    error: resolved.err(),
+                }
+            })
+            .collect()
+    }
+
+    /// Delete the rendered clips that aren't in `keep`, such as clips removed from the config
+    pub fn prune(&self, keep: &HashSet<&str>) -> Result<usize, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to list {}: {}", self.dir.display(), e)),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "wav") {
+                continue;
+            }
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            if is_valid_name(name) && !keep.contains(name) {
+                std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                self.info(name).remove()?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Generate `clip` with the backend at `base_url` and download its audio. Returns the
+/// generation ID with the WAV.
+pub async fn render(base_url: &str, clip: &ResolvedClip) -> Result<(String, Vec<u8>), String> {
+    let base_url = base_url.trim_end_matches('/');
+    let client = reqwest::Client::new();
+    let url = format!("{}/generate", base_url);
+    let generation: Value = client
+        .post(&url)
+        .json(&clip.request)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))?;
+    let id = generation["id"].as_str().ok_or_else(|| format!("{} returned no generation id", url))?.to_string();
+
+    let url = format!("{}/audio/{}", base_url, id);
+    let wav = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    Ok((id, wav.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launcher::presets::Preset;
+
+    fn config(text: &str, voice: &str) -> LauncherConfig {
+        let mut config = LauncherConfig::default();
+        let preset = Preset { voice: Some(voice.to_string()), ..Preset::default() };
+        config.presets.insert("host".to_string(), preset);
+        let clip = SoundboardClip { text: text.to_string(), preset: "host".to_string(), hotkey: None };
+        config.soundboard.insert("welcome".to_string(), clip);
+        config
+    }
+
+    #[test]
+    fn rendered_clips_go_stale_when_voice_or_text_change() {
+        let tmp = tempfile::tempdir().unwrap();
+        let board = Soundboard::new(tmp.path());
+        let clip = config("Welcome to the stream!", "anna").resolve_clip("welcome").unwrap();
+        assert!(board.get(&clip).is_none());
+        board.put(&clip, "gen-1", b"RIFF").unwrap();
+        assert_eq!(board.get(&clip), Some(tmp.path().join(SOUNDBOARD_DIR_NAME).join("welcome.wav")));
+
+        assert!(board.get(&config("Welcome back!", "anna").resolve_clip("welcome").unwrap()).is_none());
+        assert!(board.get(&config("Welcome to the stream!", "max").resolve_clip("welcome").unwrap()).is_none());
+        assert!(config("", "anna").resolve_clip("welcome").is_err());
+        assert!(config("Hi", "anna").resolve_clip("goodbye").is_err());
+
+        assert_eq!(board.prune(&HashSet::from(["welcome"])).unwrap(), 0);
+        assert_eq!(board.prune(&HashSet::new()).unwrap(), 1);
+        assert!(board.get(&clip).is_none());
+    }
+}
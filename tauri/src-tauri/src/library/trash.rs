@@ -0,0 +1,325 @@
+use crate::launcher::state::StateFile;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE_NAME: &str = "trash.json";
+/// Days a deleted item is kept when the config doesn't say otherwise
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    Generation,
+    /// Reference recording of a voice profile
+    Sample,
+}
+
+impl TrashKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrashKind::Generation => "generation",
+            TrashKind::Sample => "sample",
+        }
+    }
+
+    /// Backend route of items of this kind
+    fn route(self) -> &'static str {
+        match self {
+            TrashKind::Generation => "history",
+            TrashKind::Sample => "profiles/samples",
+        }
+    }
+}
+
+/// A deleted item whose row is tombstoned in the backend database and whose audio
+/// sits in the trash directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub kind: TrashKind,
+    pub id: String,
+    /// Where the audio lived, and goes back to on restore
+    pub original_path: PathBuf,
+    /// File name of the audio in the trash; `None` if it was already missing
+    pub stored: Option<String>,
+    /// RFC 3339
+    pub deleted_at: String,
+}
+
+impl TrashEntry {
+    fn is(&self, kind: TrashKind, id: &str) -> bool {
+        self.kind == kind && self.id == id
+    }
+
+    fn expired(&self, now: chrono::DateTime<chrono::Utc>, retention_days: u32) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.deleted_at)
+            .map(|deleted| now.signed_duration_since(deleted) >= chrono::Duration::days(i64::from(retention_days)))
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Manifest {
+    entries: Vec<TrashEntry>,
+}
+
+/// What a purge removed for good
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PurgeReport {
+    pub purged: usize,
+    /// Entries that couldn't be purged, with the reason; they are retried next time
+    pub failed: Vec<(String, String)>,
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Rename, falling back to copy and delete when `to` is on another filesystem
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to).map_err(|e| format!("Failed to move {} to {}: {}", from.display(), to.display(), e))?;
+    std::fs::remove_file(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+async fn post(client: &reqwest::Client, url: &str, body: Option<Value>) -> Result<Value, String> {
+    let request = match body {
+        Some(body) => client.post(url).json(&body),
+        None => client.post(url),
+    };
+    request
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))
+}
+
+/// Deleted generations and samples under the trash dir, listed in `trash.json`.
+/// Deleting through the trash tombstones the backend row and moves the audio here;
+/// restoring reverses both, and purging deletes them for good.
+pub struct Trash {
+    dir: PathBuf,
+    manifest: StateFile<Manifest>,
+}
+
+impl Trash {
+    pub fn new(trash_dir: &Path) -> Self {
+        Self { dir: trash_dir.to_path_buf(), manifest: StateFile::new(trash_dir.join(MANIFEST_FILE_NAME)) }
+    }
+
+    /// Trashed items, most recently deleted first
+    pub fn entries(&self) -> Result<Vec<TrashEntry>, String> {
+        let mut entries = self.manifest.read()?.unwrap_or_default().entries;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    fn find(&self, kind: TrashKind, id: &str) -> Result<TrashEntry, String> {
+        self.manifest
+            .read()?
+            .unwrap_or_default()
+            .entries
+            .into_iter()
+            .find(|entry| entry.is(kind, id))
+            .ok_or_else(|| format!("No {} {} in the trash", kind.as_str(), id))
+    }
+
+    fn forget(&self, kind: TrashKind, id: &str) -> Result<(), String> {
+        self.manifest.update(|manifest| {
+            let mut manifest = manifest.unwrap_or_default();
+            manifest.entries.retain(|entry| !entry.is(kind, id));
+            (!manifest.entries.is_empty()).then_some(manifest)
+        })?;
+        Ok(())
+    }
+
+    /// Move the audio of a tombstoned item into the trash and record it
+    pub fn stash(&self, kind: TrashKind, id: &str, audio_path: &Path) -> Result<TrashEntry, String> {
+        if !is_valid_id(id) {
+            return Err(format!("Invalid {} id {:?}", kind.as_str(), id));
+        }
+        let stored = match audio_path.is_file() {
+            true => {
+                let extension = audio_path.extension().and_then(|e| e.to_str()).unwrap_or("wav");
+                let name = format!("{}-{}.{}", kind.as_str(), id, extension);
+                move_file(audio_path, &self.dir.join(&name))?;
+                Some(name)
+            }
+            false => None,
+        };
+        let entry = TrashEntry {
+            kind,
+            id: id.to_string(),
+            original_path: audio_path.to_path_buf(),
+            stored,
+            deleted_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.manifest.update(|manifest| {
+            let mut manifest = manifest.unwrap_or_default();
+            manifest.entries.retain(|existing| !existing.is(kind, id));
+            manifest.entries.push(entry.clone());
+            Some(manifest)
+        })?;
+        Ok(entry)
+    }
+
+    /// Move the audio of a trashed item back to where it was and drop its record
+    pub fn unstash(&self, kind: TrashKind, id: &str) -> Result<TrashEntry, String> {
+        let entry = self.find(kind, id)?;
+        if let Some(stored) = &entry.stored {
+            if entry.original_path.exists() {
+                return Err(format!("Can't restore {}: another file is there now", entry.original_path.display()));
+            }
+            move_file(&self.dir.join(stored), &entry.original_path)?;
+        }
+        self.forget(kind, id)?;
+        Ok(entry)
+    }
+
+    /// Delete the audio of a trashed item and drop its record
+    pub fn discard(&self, kind: TrashKind, id: &str) -> Result<(), String> {
+        let entry = self.find(kind, id)?;
+        if let Some(stored) = &entry.stored {
+            let path = self.dir.join(stored);
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(format!("Failed to remove {}: {}", path.display(), e))
+                }
+                _ => {}
+            }
+        }
+        self.forget(kind, id)
+    }
+
+    /// Entries deleted at least `retention_days` ago
+    pub fn expired(&self, retention_days: u32) -> Result<Vec<TrashEntry>, String> {
+        let now = chrono::Utc::now();
+        Ok(self.entries()?.into_iter().filter(|entry| entry.expired(now, retention_days)).collect())
+    }
+
+    /// Delete an item through the trash: tombstone it in the backend at `base_url`,
+    /// then move its audio aside
+    pub async fn trash_item(&self, base_url: &str, kind: TrashKind, id: &str) -> Result<TrashEntry, String> {
+        if !is_valid_id(id) {
+            return Err(format!("Invalid {} id {:?}", kind.as_str(), id));
+        }
+        let client = reqwest::Client::new();
+        let base_url = base_url.trim_end_matches('/');
+        let trashed = post(&client, &format!("{}/{}/{}/trash", base_url, kind.route(), id), None).await?;
+        let audio_path = trashed.get("audio_path").and_then(Value::as_str).unwrap_or_default();
+        match self.stash(kind, id, Path::new(audio_path)) {
+            Ok(entry) => Ok(entry),
+            Err(e) => {
+                // Without its audio in the trash the item couldn't be restored, so undo the tombstone
+                let restore = json!({ "audio_path": audio_path });
+                let _ = post(&client, &format!("{}/{}/{}/restore", base_url, kind.route(), id), Some(restore)).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Put a trashed item's audio back and lift its tombstone in the backend
+    pub async fn restore_item(&self, base_url: &str, kind: TrashKind, id: &str) -> Result<TrashEntry, String> {
+        let entry = self.unstash(kind, id)?;
+        let url = format!("{}/{}/{}/restore", base_url.trim_end_matches('/'), kind.route(), id);
+        let restore = json!({ "audio_path": entry.original_path.to_string_lossy() });
+        if let Err(e) = post(&reqwest::Client::new(), &url, Some(restore)).await {
+            // Keep it restorable rather than leaving the audio orphaned
+            self.stash(kind, id, &entry.original_path)?;
+            return Err(e);
+        }
+        Ok(entry)
+    }
+
+    /// Delete a trashed item for good, from the backend at `base_url` and from disk
+    pub async fn purge_item(&self, base_url: &str, kind: TrashKind, id: &str) -> Result<(), String> {
+        let url = format!("{}/{}/{}", base_url.trim_end_matches('/'), kind.route(), id);
+        let response = reqwest::Client::new()
+            .delete(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+        // Already gone from the database counts as purged
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(format!("Request to {} failed: {}", url, response.status()));
+        }
+        self.discard(kind, id)
+    }
+
+    /// Purge everything deleted at least `retention_days` ago
+    pub async fn purge_expired(&self, base_url: &str, retention_days: u32) -> Result<PurgeReport, String> {
+        let mut report = PurgeReport::default();
+        for entry in self.expired(retention_days)? {
+            match self.purge_item(base_url, entry.kind, &entry.id).await {
+                Ok(()) => report.purged += 1,
+                Err(e) => report.failed.push((entry.id, e)),
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stashes_and_restores_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio = dir.path().join("generations").join("gen-1.wav");
+        std::fs::create_dir_all(audio.parent().unwrap()).unwrap();
+        std::fs::write(&audio, b"RIFF").unwrap();
+        let trash = Trash::new(&dir.path().join("trash"));
+
+        let entry = trash.stash(TrashKind::Generation, "gen-1", &audio).unwrap();
+        assert!(!audio.exists());
+        assert!(dir.path().join("trash").join("generation-gen-1.wav").is_file());
+        trash.stash(TrashKind::Sample, "missing", &dir.path().join("gone.wav")).unwrap();
+        assert_eq!(trash.entries().unwrap().len(), 2);
+        assert!(trash.stash(TrashKind::Sample, "../x", &audio).is_err());
+
+        assert_eq!(trash.unstash(TrashKind::Generation, "gen-1").unwrap(), entry);
+        assert_eq!(std::fs::read(&audio).unwrap(), b"RIFF");
+        assert!(trash.unstash(TrashKind::Generation, "gen-1").is_err());
+
+        // A restore never overwrites a file that took the old place
+        trash.stash(TrashKind::Generation, "gen-1", &audio).unwrap();
+        std::fs::write(&audio, b"new").unwrap();
+        assert!(trash.unstash(TrashKind::Generation, "gen-1").is_err());
+        assert_eq!(trash.entries().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn expires_entries_after_the_retention_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = Trash::new(dir.path());
+        let audio = dir.path().join("old.wav");
+        std::fs::write(&audio, b"RIFF").unwrap();
+        trash.stash(TrashKind::Generation, "old", &audio).unwrap();
+        trash.stash(TrashKind::Generation, "new", &dir.path().join("none.wav")).unwrap();
+        trash
+            .manifest
+            .update(|manifest| {
+                let mut manifest = manifest.unwrap();
+                manifest.entries[0].deleted_at = (chrono::Utc::now() - chrono::Duration::days(31)).to_rfc3339();
+                Some(manifest)
+            })
+            .unwrap();
+
+        let expired = trash.expired(DEFAULT_RETENTION_DAYS).unwrap();
+        assert_eq!(expired.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["old"]);
+        assert_eq!(trash.expired(0).unwrap().len(), 2);
+
+        trash.discard(TrashKind::Generation, "old").unwrap();
+        assert!(!dir.path().join("generation-old.wav").exists());
+        assert_eq!(trash.entries().unwrap()[0].id, "new");
+    }
+}
@@ -0,0 +1,210 @@
+use crate::launcher::state::StateFile;
+use crate::library::{ItemKind, LibraryItem};
+use crate::postprocess::pcm::{AudioStats, Pcm};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub const WAVEFORMS_DIR_NAME: &str = "waveforms";
+/// Bumped when the cached format or its computation changes, which invalidates every
+/// cached file
+pub const WAVEFORM_VERSION: u32 = 1;
+/// Peaks per waveform, enough for a library card at 2x pixel density
+pub const DEFAULT_BUCKETS: usize = 256;
+
+/// Peaks and stats of one library item, precomputed so the library view doesn't have
+/// to decode audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waveform {
+    pub version: u32,
+    /// `LibraryItem::audio` of the audio the waveform was computed from
+    pub source: String,
+    pub sample_rate: u32,
+    pub stats: AudioStats,
+    /// Lowest and highest sample over all channels, per equal slice of the audio
+    pub peaks: Vec<(f32, f32)>,
+}
+
+/// Compute the waveform of a WAV file in `buckets` slices
+pub fn compute(wav: &[u8], source: &str, buckets: usize) -> Result<Waveform, String> {
+    let pcm = Pcm::read(wav)?;
+    let frames = pcm.frames();
+    let channels = pcm.channels();
+    let buckets = buckets.clamp(1, frames.max(1));
+    let peaks = (0..buckets)
+        .map(|bucket| {
+            let (start, end) = (bucket * frames / buckets, (bucket + 1) * frames / buckets);
+            pcm.samples[start * channels..end * channels]
+                .iter()
+                .fold((0f32, 0f32), |(low, high), &s| (low.min(s), high.max(s)))
+        })
+        .collect();
+    Ok(Waveform {
+        version: WAVEFORM_VERSION,
+        source: source.to_string(),
+        sample_rate: pcm.spec.sample_rate,
+        stats: pcm.stats(),
+        peaks,
+    })
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// What a refresh did
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RefreshReport {
+    pub computed: usize,
+    pub cached: usize,
+    pub removed: usize,
+    /// Items whose audio couldn't be downloaded or decoded, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Waveforms stored as one file per generation under `cache/waveforms`
+pub struct WaveformCache {
+    dir: PathBuf,
+}
+
+impl WaveformCache {
+    pub fn new(cache_dir: &Path) -> Self {
+        Self { dir: cache_dir.join(WAVEFORMS_DIR_NAME) }
+    }
+
+    fn file(&self, id: &str) -> Option<StateFile<Waveform>> {
+        is_valid_id(id).then(|| StateFile::new(self.dir.join(format!("{}.json", id))))
+    }
+
+    /// The cached waveform of generation `id`, if it is current for this version
+    pub fn get(&self, id: &str) -> Option<Waveform> {
+        let waveform = self.file(id)?.read().ok()??;
+        (waveform.version == WAVEFORM_VERSION).then_some(waveform)
+    }
+
+    /// Cached waveforms of `ids`; ids without a current waveform are left out
+    pub fn get_many(&self, ids: &[String]) -> HashMap<String, Waveform> {
+        ids.iter().filter_map(|id| Some((id.clone(), self.get(id)?))).collect()
+    }
+
+    pub fn put(&self, id: &str, waveform: &Waveform) -> Result<(), String> {
+        self.file(id).ok_or_else(|| format!("Invalid generation id {:?}", id))?.write(waveform)
+    }
+
+    /// Delete the waveforms of generations that aren't in `keep`
+    pub fn prune(&self, keep: &HashSet<&str>) -> Result<usize, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to list {}: {}", self.dir.display(), e)),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let id = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            if let Some(file) = self.file(id).filter(|_| !keep.contains(id)) {
+                file.remove()?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Compute the waveform of every generation in `items` whose audio changed since it
+    /// was cached, downloading the audio from the backend at `base_url`, and drop the
+    /// waveforms of generations that are gone
+    pub async fn refresh(&self, base_url: &str, items: &[LibraryItem]) -> Result<RefreshReport, String> {
+        let client = reqwest::Client::new();
+        let mut report = RefreshReport::default();
+        let generations: Vec<(&LibraryItem, &str)> = items
+            .iter()
+            .filter(|item| item.kind == ItemKind::Generation)
+            .filter_map(|item| Some((item, item.audio.as_deref()?)))
+            .collect();
+
+        for &(item, source) in &generations {
+            if self.get(&item.id).is_some_and(|w| w.source == source) {
+                report.cached += 1;
+                continue;
+            }
+            let url = format!("{}/audio/{}", base_url.trim_end_matches('/'), item.id);
+            let downloaded = async {
+                client
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| format!("Request to {} failed: {}", url, e))?
+                    .bytes()
+                    .await
+                    .map_err(|e| format!("Failed to download {}: {}", url, e))
+            };
+            let waveform = match downloaded.await {
+                Ok(wav) => compute(&wav, source, DEFAULT_BUCKETS),
+                Err(e) => Err(e),
+            };
+            match waveform {
+                Ok(waveform) => {
+                    self.put(&item.id, &waveform)?;
+                    report.computed += 1;
+                }
+                Err(e) => report.failed.push((item.id.clone(), e)),
+            }
+        }
+
+        let keep = generations.iter().map(|(item, _)| item.id.as_str()).collect();
+        report.removed = self.prune(&keep)?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(samples: &[f32]) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        Pcm { spec, samples: samples.to_vec() }.write().unwrap()
+    }
+
+    #[test]
+    fn computes_peaks_per_slice_over_all_channels() {
+        // Two stereo frames per bucket
+        let samples = [0.5, -0.25, 0.1, 0.0, 0.0, 0.0, -0.75, 0.25];
+        let waveform = compute(&wav(&samples), "a.wav#0.004", 2).unwrap();
+        assert_eq!(waveform.peaks.len(), 2);
+        assert!((waveform.peaks[0].0 + 0.25).abs() < 0.001 && (waveform.peaks[0].1 - 0.5).abs() < 0.001);
+        assert!((waveform.peaks[1].0 + 0.75).abs() < 0.001 && (waveform.peaks[1].1 - 0.25).abs() < 0.001);
+        assert_eq!(waveform.stats.duration_secs, 0.004);
+
+        // Never more buckets than frames, and empty audio still has one
+        assert_eq!(compute(&wav(&samples), "", 100).unwrap().peaks.len(), 4);
+        assert_eq!(compute(&wav(&[]), "", 100).unwrap().peaks, vec![(0.0, 0.0)]);
+    }
+
+    #[test]
+    fn serves_current_waveforms_and_prunes_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = WaveformCache::new(dir.path());
+        let waveform = compute(&wav(&[0.1, 0.2]), "a.wav#1", DEFAULT_BUCKETS).unwrap();
+        cache.put("gen-1", &waveform).unwrap();
+        cache.put("gen-2", &waveform).unwrap();
+        cache.put("gen-3", &Waveform { version: WAVEFORM_VERSION + 1, ..waveform.clone() }).unwrap();
+        assert!(cache.put("../escape", &waveform).is_err());
+
+        let found = cache.get_many(&["gen-1".to_string(), "gen-3".to_string(), "gen-4".to_string()]);
+        assert_eq!(found.keys().collect::<Vec<_>>(), vec!["gen-1"]);
+
+        assert_eq!(cache.prune(&HashSet::from(["gen-2"])).unwrap(), 2);
+        assert_eq!(cache.get("gen-1"), None);
+        assert_eq!(cache.get("gen-2"), Some(waveform));
+    }
+}
@@ -3,21 +3,96 @@
 
 mod audio_capture;
 mod audio_output;
+mod ducking;
 
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::{command, State, Manager, WindowEvent, Emitter, RunEvent};
+use tauri::menu::{CheckMenuItem, Menu};
+use tauri::tray::TrayIconBuilder;
+use tauri_plugin_notification::NotificationExt;
 use tauri_plugin_shell::ShellExt;
+use voicebox::launcher::announcements::{self, Announcer, StateChange};
+use voicebox::launcher::backend_log::{self, BackendLevel};
+use voicebox::launcher::capabilities::Capabilities;
+use voicebox::launcher::config::LauncherConfig;
+use voicebox::launcher::consent::{self, ConsentBasis, ConsentRecord};
+use voicebox::launcher::error::LauncherError;
+use voicebox::launcher::fine_tune::{self, FineTuneJob, FineTuneStatus, FineTuneStore};
+use voicebox::launcher::gpu::GpuInfo;
+use voicebox::launcher::log;
+use voicebox::launcher::notifications::{self, Delivery, Notification, NotificationCategory, NotificationCenter, NotificationPolicy, NotificationSettings};
+use voicebox::launcher::paths::LauncherPaths;
+use voicebox::launcher::presets::{PresetStatus, ResolvedPreset};
+use voicebox::launcher::process::SystemRunner;
+use voicebox::launcher::profile_archive::{self, ArchiveManifest, ImportedProfile};
+use voicebox::launcher::progress::{OverallProgress, ProgressBus, ProgressKind, TaskProgress, TaskReporter};
+use voicebox::launcher::projects::{self, ProjectInfo, Projects};
+use voicebox::launcher::reload::ConfigWatcher;
+use voicebox::launcher::signing;
+use voicebox::launcher::status::{self, BackendStatus};
+use voicebox::launcher::storage::StorageWarning;
+use voicebox::library::chat::{read_twitch, read_youtube, ChatBridge, ChatMessage, ChatSource, ChatStatus, CHAT_SOURCE};
+use voicebox::library::dataset::{DatasetBuilder, DatasetReport, METADATA_FILE_NAME};
+use voicebox::library::download::{self, DownloadControl, DownloadOutcome, DownloadRequest};
+use voicebox::library::duplicates::{self, DuplicateMatch, FingerprintCache};
+use voicebox::library::game_export::{BankManifest, GameBank};
+use voicebox::library::mirror::{Mirror, MirrorConfig, MirrorReport};
+use voicebox::library::narration::{
+    self, CompiledWatch, NarrationStatus, Narrator, QueueLimits, Queued, SourcePriority, Tail, Utterance, API_SOURCE,
+    HOTKEY_SOURCE, SCHEDULER_SOURCE,
+};
+use voicebox::library::preview::{self, PreviewCache, PreviewQueue};
+use voicebox::library::reminders::{Reminder, ReminderLog};
+use voicebox::library::soundboard::{self, ClipStatus, ResolvedClip, Soundboard, SoundboardClip};
+use voicebox::library::trash::{self, PurgeReport, Trash, TrashEntry, TrashKind};
+use voicebox::library::waveform::{RefreshReport, Waveform, WaveformCache};
+use voicebox::library::{self, search::{LibraryIndex, SearchHit, SyncReport}};
+use voicebox::postprocess::lipsync::{self, GenerationTiming, LipSyncFormat};
+use voicebox::postprocess::snapshot::{self, GenerationSnapshot};
+use voicebox::postprocess::{self, watermark::{self, Verification}};
+use voicebox::text::chunk::{self, Chunk, ChunkOptions};
+use voicebox::text::filter::{self, ContentFilter};
+use voicebox::text::normalize::{self, Rule};
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
 const LEGACY_PORT: u16 = 8000;
 const SERVER_PORT: u16 = 17493;
+const TRAY_ID: &str = "main";
+/// Tray menu entries that select a preset have this in front of its name
+const TRAY_PRESET_PREFIX: &str = "preset:";
 
 struct ServerState {
     child: Mutex<Option<tauri_plugin_shell::process::CommandChild>>,
     server_pid: Mutex<Option<u32>>,
     keep_running_on_close: Mutex<bool>,
+    /// What the local backend reported on /capabilities, cleared when it stops
+    capabilities: Mutex<Option<Capabilities>>,
+}
+
+/// Library search index, opened on first use
+#[derive(Default)]
+struct LibraryState {
+    index: Mutex<Option<Arc<LibraryIndex>>>,
+    /// Whether the index has been synced with the backend since the app started
+    synced: AtomicBool,
+}
+
+fn library_index(app: &tauri::AppHandle, state: &LibraryState) -> Result<Arc<LibraryIndex>, String> {
+    let mut index = state.index.lock().unwrap();
+    if let Some(index) = index.as_ref() {
+        return Ok(index.clone());
+    }
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let opened = Arc::new(LibraryIndex::open(&LauncherPaths::under(data_dir).cache_dir)?);
+    *index = Some(opened.clone());
+    Ok(opened)
 }
 
 #[command]
@@ -176,6 +251,16 @@ async fn start_server(
     std::fs::create_dir_all(&data_dir)
         .map_err(|e| format!("Failed to create data dir: {}", e))?;
 
+    // Run on the open project's database and audio instead, if there is one
+    let config = LauncherConfig::load_from_data_dir(&data_dir).unwrap_or_default();
+    let state_dir = LauncherPaths::under(data_dir.clone()).state_dir;
+    let project = Projects::new(&config.workspace, &data_dir, &state_dir).active()?;
+    let locked = project.as_ref().is_some_and(|project| project.locked);
+    let data_dir = project.map_or(data_dir, |project| project.dir);
+    std::fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create data dir: {}", e))?;
+
+    announce(&app, StateChange::ServerStarting);
     println!("=================================================================");
     println!("Starting voicebox-server sidecar");
     println!("Data directory: {:?}", data_dir);
@@ -195,12 +280,30 @@ async fn start_server(
     // Pass data directory and port to Python server
     sidecar = sidecar.args([
         "--data-dir",
-        data_dir
+        config
+            .workspace
+            .to_backend(&data_dir)
             .to_str()
             .ok_or_else(|| "Invalid data dir path".to_string())?,
         "--port",
         &SERVER_PORT.to_string(),
     ]);
+    sidecar = sidecar.env(projects::DB_PATH_ENV, config.workspace.to_backend(&data_dir.join(projects::DB_FILE_NAME)));
+    if locked {
+        println!("Project is locked, starting the server read-only");
+        sidecar = sidecar.env(projects::READ_ONLY_ENV, "1");
+    }
+    let gpus = match config.devices.wants_gpu() {
+        true => tauri::async_runtime::spawn_blocking(|| GpuInfo::detect(&SystemRunner))
+            .await
+            .map_err(|e| format!("GPU detection stopped: {}", e))?,
+        false => GpuInfo::default(),
+    };
+    let devices = config.devices.plan(&gpus);
+    for warning in &devices.warnings {
+        eprintln!("{}", warning);
+    }
+    sidecar = sidecar.envs(devices.env);
 
     if remote.unwrap_or(false) {
         sidecar = sidecar.args(["--host", "0.0.0.0"]);
@@ -242,6 +345,7 @@ async fn start_server(
                     eprintln!("  {}", line);
                 }
             }
+            announce(&app, StateChange::ServerStartFailed);
             return Err("Server startup timeout - check Console.app for detailed logs".to_string());
         }
 
@@ -279,6 +383,7 @@ async fn start_server(
                 eprintln!("Server process ended unexpectedly during startup!");
                 eprintln!("The server binary may have crashed or exited with an error.");
                 eprintln!("Check Console.app logs for more details (search for 'voicebox')");
+                announce(&app, StateChange::ServerStartFailed);
                 return Err("Server process ended unexpectedly".to_string());
             }
             Err(_) => {
@@ -289,20 +394,48 @@ async fn start_server(
     }
 
     // Spawn task to continue reading output
+    let handle = app.clone();
     tokio::spawn(async move {
+        let mut disk_full_reported = false;
         while let Some(event) = rx.recv().await {
             match event {
                 tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
                     println!("Server: {}", String::from_utf8_lossy(&line));
                 }
                 tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
-                    eprintln!("Server error: {}", String::from_utf8_lossy(&line));
+                    let line = String::from_utf8_lossy(&line);
+                    eprintln!("Server error: {}", line);
+                    if !disk_full_reported && (line.contains("No space left on device") || line.contains("[Errno 28]")) {
+                        disk_full_reported = true;
+                        send_notification(&handle, Notification {
+                            category: NotificationCategory::OutOfDisk,
+                            title: "Disk full".to_string(),
+                            body: "Voicebox can't save audio until some space is freed.".to_string(),
+                        });
+                    }
+                }
+                tauri_plugin_shell::process::CommandEvent::Terminated(payload) => {
+                    // stop_server forgets the child before killing it, so a child still
+                    // recorded here ended on its own
+                    let state = handle.state::<ServerState>();
+                    let ours = state.child.lock().unwrap().as_ref().is_some_and(|child| child.pid() == process_pid);
+                    if ours {
+                        state.child.lock().unwrap().take();
+                        state.server_pid.lock().unwrap().take();
+                        announce(&handle, StateChange::ServerCrashed);
+                        send_notification(&handle, Notification {
+                            category: NotificationCategory::BackendCrashed,
+                            title: "Voicebox server stopped".to_string(),
+                            body: format!("The server exited unexpectedly (code {:?}).", payload.code),
+                        });
+                    }
                 }
                 _ => {}
             }
         }
     });
 
+    announce(&app, StateChange::ServerReady);
     Ok(format!("http://127.0.0.1:{}", SERVER_PORT))
 }
 
@@ -361,9 +494,10 @@ fn kill_windows_process_tree(parent_pid: u32) -> Result<(), String> {
 }
 
 #[command]
-async fn stop_server(state: State<'_, ServerState>) -> Result<(), String> {
+async fn stop_server(app: tauri::AppHandle, state: State<'_, ServerState>) -> Result<(), String> {
     let pid = state.server_pid.lock().unwrap().take();
     let _child = state.child.lock().unwrap().take();
+    state.capabilities.lock().unwrap().take();
     
     if let Some(pid) = pid {
         println!("stop_server: Killing server process group with PID: {}", pid);
@@ -445,6 +579,7 @@ async fn stop_server(state: State<'_, ServerState>) -> Result<(), String> {
         {
             println!("stop_server: Process group kill completed");
         }
+        announce(&app, StateChange::ServerStopped);
     }
     
     Ok(())
@@ -455,19 +590,39 @@ fn set_keep_server_running(state: State<'_, ServerState>, keep_running: bool) {
     *state.keep_running_on_close.lock().unwrap() = keep_running;
 }
 
+/// The backend a voicebox-server launcher runs on the app's data directory, as it
+/// published it, and whether it answers
+#[command]
+async fn get_launcher_status(app: tauri::AppHandle) -> Result<BackendStatus, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let state_dir = LauncherPaths::under(data_dir).state_dir;
+    tauri::async_runtime::spawn_blocking(move || status::check(&SystemRunner, &state_dir))
+        .await
+        .map_err(|e| format!("Status check stopped: {}", e))?
+}
+
 #[command]
 async fn start_system_audio_capture(
+    app: tauri::AppHandle,
     state: State<'_, audio_capture::AudioCaptureState>,
     max_duration_secs: u32,
 ) -> Result<(), String> {
-    audio_capture::start_capture(&state, max_duration_secs).await
+    audio_capture::start_capture(&state, max_duration_secs).await?;
+    announce(&app, StateChange::RecordingStarted);
+    Ok(())
 }
 
 #[command]
 async fn stop_system_audio_capture(
+    app: tauri::AppHandle,
     state: State<'_, audio_capture::AudioCaptureState>,
 ) -> Result<String, String> {
-    audio_capture::stop_capture(&state).await
+    let recording = audio_capture::stop_capture(&state).await;
+    announce(&app, StateChange::RecordingStopped);
+    recording
 }
 
 #[command]
@@ -487,8 +642,9 @@ async fn play_audio_to_devices(
     state: State<'_, audio_output::AudioOutputState>,
     audio_data: Vec<u8>,
     device_ids: Vec<String>,
+    duck: Option<f32>,
 ) -> Result<(), String> {
-    state.play_audio_to_devices(audio_data, device_ids).await
+    state.play_audio_to_devices(audio_data, device_ids, duck).await
 }
 
 #[command]
@@ -498,17 +654,1445 @@ fn stop_audio_playback(
     state.stop_all_playback()
 }
 
+fn load_launcher_config(app: &tauri::AppHandle) -> Result<LauncherConfig, LauncherError> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| LauncherError::InvalidConfig(format!("Failed to get app data dir: {}", e)))?;
+    LauncherConfig::load_from_data_dir(&data_dir).map_err(LauncherError::InvalidConfig)
+}
+
+/// The configured presets, with their hotkeys for the UI to register
+#[command]
+fn list_presets(app: tauri::AppHandle) -> Result<Vec<PresetStatus>, LauncherError> {
+    Ok(load_launcher_config(&app)?.preset_status())
+}
+
+#[command]
+fn resolve_preset(app: tauri::AppHandle, name: String) -> Result<ResolvedPreset, LauncherError> {
+    load_launcher_config(&app)?
+        .resolve_preset(&name)
+        .map_err(LauncherError::InvalidConfig)
+}
+
+/// The preset speech uses when none is named, picked from the tray menu or with a
+/// preset's hotkey
+#[derive(Default)]
+struct SelectedPreset(Mutex<Option<String>>);
+
+#[command]
+fn get_selected_preset(selected: State<'_, SelectedPreset>) -> Option<String> {
+    selected.0.lock().unwrap().clone()
+}
+
+/// Make `name` the selected preset, as its tray menu entry and its hotkey do
+#[command]
+fn select_preset(app: tauri::AppHandle, name: String) -> Result<(), LauncherError> {
+    let config = load_launcher_config(&app)?;
+    config.resolve_preset(&name).map_err(LauncherError::InvalidConfig)?;
+    *app.state::<SelectedPreset>().0.lock().unwrap() = Some(name.clone());
+    refresh_tray_menu(&app, &config);
+    let _ = app.emit("preset-selected", &name);
+    Ok(())
+}
+
+/// List the configured presets in the tray menu with the selected one checked. A
+/// selected preset that was removed from the config is deselected.
+fn refresh_tray_menu(app: &tauri::AppHandle, config: &LauncherConfig) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let selected = {
+        let state = app.state::<SelectedPreset>();
+        let mut selected = state.0.lock().unwrap();
+        if selected.as_ref().is_some_and(|name| !config.presets.contains_key(name)) {
+            *selected = None;
+        }
+        selected.clone()
+    };
+    let menu = Menu::new(app).and_then(|menu| {
+        for preset in config.preset_status() {
+            let checked = selected.as_deref() == Some(preset.name.as_str());
+            let id = format!("{}{}", TRAY_PRESET_PREFIX, preset.name);
+            let item = CheckMenuItem::with_id(app, id, &preset.name, preset.error.is_none(), checked, preset.hotkey.as_deref())?;
+            menu.append(&item)?;
+        }
+        Ok(menu)
+    });
+    if let Err(e) = menu.and_then(|menu| tray.set_menu(Some(menu))) {
+        eprintln!("Failed to update the tray menu: {}", e);
+    }
+}
+
+/// Features of the backend at `server_url` (the local server by default). The local
+/// backend's answer is cached until it is stopped or `refresh` is set.
+#[command]
+async fn get_backend_capabilities(
+    state: State<'_, ServerState>,
+    server_url: Option<String>,
+    refresh: Option<bool>,
+) -> Result<Capabilities, String> {
+    let local = server_url.is_none();
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    if local && !refresh.unwrap_or(false) {
+        if let Some(cached) = state.capabilities.lock().unwrap().clone() {
+            return Ok(cached);
+        }
+    }
+
+    let capabilities = voicebox::launcher::capabilities::fetch(&url).await?;
+    if local {
+        *state.capabilities.lock().unwrap() = Some(capabilities.clone());
+    }
+    Ok(capabilities)
+}
+
+/// How much the local backend logs
+#[command]
+async fn get_backend_log_level() -> Result<BackendLevel, String> {
+    backend_log::get_level(&format!("http://127.0.0.1:{}", SERVER_PORT)).await
+}
+
+/// Change how much the local backend logs while it runs, e.g. to `debug` to capture
+/// a bug. It starts with `backend_log_level` from the config again after a restart.
+#[command]
+async fn set_backend_log_level(level: BackendLevel) -> Result<BackendLevel, String> {
+    backend_log::set_level(&format!("http://127.0.0.1:{}", SERVER_PORT), level).await
+}
+
+/// Split long input into chunks the local backend accepts, using its reported text
+/// limit when known
+#[command]
+fn chunk_text(
+    state: State<'_, ServerState>,
+    text: String,
+    language: Option<String>,
+    max_chars: Option<usize>,
+) -> Vec<Chunk> {
+    let capabilities = state.capabilities.lock().unwrap().clone().unwrap_or_default();
+    let mut options = ChunkOptions::for_backend(&capabilities, language.as_deref());
+    if let Some(max_chars) = max_chars {
+        options.max_chars = options.max_chars.min(max_chars);
+    }
+    chunk::split_text(&text, &options)
+}
+
+/// Apply preset-style text normalization without going through a preset
+#[command]
+fn normalize_text(text: String, language: String, rules: Vec<Rule>) -> String {
+    normalize::normalize(&text, &language, &rules)
+}
+
+/// Export a voice profile to a signed archive at `path`
+#[command]
+async fn export_voice_profile(
+    app: tauri::AppHandle,
+    profile_id: String,
+    path: String,
+    server_url: Option<String>,
+) -> Result<ArchiveManifest, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let key = signing::load_or_create_signing_key(&LauncherPaths::under(data_dir).state_dir)?;
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    profile_archive::export_profile(&url, &profile_id, std::path::Path::new(&path), &key).await
+}
+
+#[command]
+async fn get_voice_consent(profile_id: String, server_url: Option<String>) -> Result<Option<ConsentRecord>, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    consent::fetch(&url, &profile_id).await
+}
+
+/// Record the user's acknowledgment of the cloning terms for a profile
+#[command]
+async fn record_voice_consent(
+    profile_id: String,
+    basis: ConsentBasis,
+    speaker: Option<String>,
+    license: Option<String>,
+    source: Option<String>,
+    server_url: Option<String>,
+) -> Result<ConsentRecord, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let record = ConsentRecord::acknowledge(basis, speaker, license, source);
+    consent::store(&url, &profile_id, &record).await?;
+    Ok(record)
+}
+
+/// Verify a profile archive and register it with the backend
+#[command]
+async fn import_voice_profile(path: String, server_url: Option<String>) -> Result<ImportedProfile, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    profile_archive::import_profile(&url, std::path::Path::new(&path)).await
+}
+
+/// GET `path` on the backend at `base_url`
+async fn fetch_backend(base_url: &str, path: &str) -> Result<reqwest::Response, String> {
+    let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+    reqwest::get(&url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))
+}
+
+/// Save a generation's audio to `path` with its generation settings embedded, running
+/// the named preset's post-processing steps and adding a watermark if `watermark` is set.
+/// Each of `lip_sync` writes a timing track next to the audio.
+#[command]
+async fn export_generation_audio(
+    app: tauri::AppHandle,
+    generation_id: String,
+    path: String,
+    preset: Option<String>,
+    watermark: Option<bool>,
+    lip_sync: Option<Vec<LipSyncFormat>>,
+    server_url: Option<String>,
+) -> Result<(), String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let resolved = match &preset {
+        Some(name) => Some(load_launcher_config(&app).map_err(|e| e.to_string())?.resolve_preset(name)?),
+        None => None,
+    };
+    let mut steps = resolved.as_ref().map(|r| r.post_processing.clone()).unwrap_or_default();
+    if watermark.unwrap_or(false) && !steps.iter().any(|s| s == postprocess::WATERMARK) {
+        steps.push(postprocess::WATERMARK.to_string());
+    }
+
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let entry: serde_json::Value = fetch_backend(&url, &format!("/history/{}", generation_id))
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid history entry for generation {}: {}", generation_id, e))?;
+    let wav = fetch_backend(&url, &format!("/audio/{}", generation_id))
+        .await?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download audio of generation {}: {}", generation_id, e))?
+        .to_vec();
+
+    let key = signing::load_or_create_signing_key(&LauncherPaths::under(data_dir).state_dir)?;
+    let context = postprocess::StepContext { signing_key: &key, generation_id: Some(&generation_id) };
+    let wav = postprocess::apply(&steps, wav, &context)?;
+    let settings = GenerationSnapshot {
+        preset,
+        speed: resolved.and_then(|r| r.speed),
+        ..GenerationSnapshot::from_history(&entry)
+    };
+    let wav = snapshot::embed(&wav, &settings)?;
+    std::fs::write(&path, wav).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    let formats = lip_sync.unwrap_or_default();
+    if formats.is_empty() {
+        return Ok(());
+    }
+    let timing: GenerationTiming = fetch_backend(&url, &format!("/history/{}/timing", generation_id))
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid timing of generation {}: {}", generation_id, e))?;
+    let track = lipsync::build(&timing);
+    let audio_path = std::path::Path::new(&path);
+    let sound_file = audio_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    for format in formats {
+        let track_path = audio_path.with_extension(format.extension());
+        std::fs::write(&track_path, format.render(&track, &sound_file))
+            .map_err(|e| format!("Failed to write {}: {}", track_path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Export generations as an audio bank for a game engine: clips in one format with
+/// metadata per clip and a manifest, laid out as the named export profile says
+#[command]
+async fn export_game_bank(
+    app: tauri::AppHandle,
+    generation_ids: Vec<String>,
+    profile: String,
+    bank: String,
+    dir: String,
+    server_url: Option<String>,
+) -> Result<BankManifest, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let profile = load_launcher_config(&app).map_err(|e| e.to_string())?.game_export_profile(&profile)?;
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let writes_lip_sync = !profile.lip_sync.is_empty();
+    let state_dir = LauncherPaths::under(data_dir).state_dir;
+    let label = format!("Export of {}", bank);
+    let reporter = TaskReporter::start(&state_dir, &format!("game-bank-{}", bank), ProgressKind::BatchJob, &label, Some(0.0));
+
+    let export = async {
+        let mut writer = GameBank::create(profile, &bank, std::path::Path::new(&dir))?;
+        for (done, id) in generation_ids.iter().enumerate() {
+            let entry: serde_json::Value = fetch_backend(&url, &format!("/history/{}", id))
+                .await?
+                .json()
+                .await
+                .map_err(|e| format!("Invalid history entry for generation {}: {}", id, e))?;
+            let wav = fetch_backend(&url, &format!("/audio/{}", id))
+                .await?
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to download audio of generation {}: {}", id, e))?;
+            let track = if writes_lip_sync {
+                let timing: GenerationTiming = fetch_backend(&url, &format!("/history/{}/timing", id))
+                    .await?
+                    .json()
+                    .await
+                    .map_err(|e| format!("Invalid timing of generation {}: {}", id, e))?;
+                Some(lipsync::build(&timing))
+            } else {
+                None
+            };
+            writer.add(&entry, &wav, track.as_ref())?;
+            reporter.set(Some((done + 1) as f32 / generation_ids.len() as f32));
+        }
+        writer.finish()
+    };
+    let manifest = export.await;
+    if manifest.is_err() {
+        reporter.fail();
+    }
+    manifest
+}
+
+/// Build a dataset for fine-tuning a voice outside the app from recordings of a
+/// profile and their transcripts: `wavs/`, `metadata.csv` and a `report.json` of what
+/// was left out and why. No `sample_ids` takes all of the profile's recordings.
+#[command]
+async fn export_dataset(
+    app: tauri::AppHandle,
+    profile_id: String,
+    sample_ids: Vec<String>,
+    dir: String,
+    server_url: Option<String>,
+) -> Result<DatasetReport, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let options = load_launcher_config(&app).map_err(|e| e.to_string())?.dataset;
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let state_dir = LauncherPaths::under(data_dir).state_dir;
+    let reporter = TaskReporter::start(&state_dir, &format!("dataset-{}", profile_id), ProgressKind::BatchJob, "Dataset export", Some(0.0));
+
+    let export = async {
+        let samples: Vec<serde_json::Value> = fetch_backend(&url, &format!("/profiles/{}/samples", profile_id))
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid samples of profile {}: {}", profile_id, e))?;
+        let field = |sample: &serde_json::Value, name: &str| sample.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let selected: Vec<_> = samples
+            .iter()
+            .filter(|sample| sample_ids.is_empty() || sample_ids.contains(&field(sample, "id")))
+            .collect();
+        let mut builder = DatasetBuilder::create(options, std::path::Path::new(&dir))?;
+        for (done, sample) in selected.iter().enumerate() {
+            let id = field(sample, "id");
+            let wav = fetch_backend(&url, &format!("/samples/{}", id))
+                .await?
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to download sample {}: {}", id, e))?;
+            builder.add(&id, &wav, &field(sample, "reference_text"))?;
+            reporter.set(Some((done + 1) as f32 / selected.len() as f32));
+        }
+        builder.finish()
+    };
+    let report = export.await;
+    if report.is_err() {
+        reporter.fail();
+    }
+    report
+}
+
+/// Downloads in progress by id, so they can be paused or cancelled
+#[derive(Default)]
+struct Downloads {
+    active: Mutex<HashMap<String, Arc<DownloadControl>>>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct DownloadProgress {
+    id: String,
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// Stream a large file from the backend, such as stems or a dataset, to `destination`
+/// without passing it through the webview. Progress is sent as `download-progress`
+/// events. Starting a paused download again with the same id continues it.
+#[command]
+async fn start_download(
+    app: tauri::AppHandle,
+    downloads: State<'_, Downloads>,
+    id: String,
+    path: String,
+    destination: String,
+    sha256: Option<String>,
+    server_url: Option<String>,
+) -> Result<DownloadOutcome, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let request = DownloadRequest {
+        url: format!("{}{}", url.trim_end_matches('/'), path),
+        destination: std::path::PathBuf::from(&destination),
+        sha256,
+    };
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let state_dir = LauncherPaths::under(data_dir).state_dir;
+    let control = Arc::new(DownloadControl::default());
+    {
+        let mut active = downloads.active.lock().unwrap();
+        if active.contains_key(&id) {
+            return Err(format!("Download {} is already running", id));
+        }
+        active.insert(id.clone(), control.clone());
+    }
+    let label = request.destination.file_name().map_or(destination.clone(), |name| name.to_string_lossy().into_owned());
+    let reporter = TaskReporter::start(&state_dir, &format!("download-{}", id), ProgressKind::FileDownload, &label, None);
+
+    let outcome = download::download(&reqwest::Client::new(), &request, &control, |downloaded, total| {
+        reporter.set(total.filter(|total| *total > 0).map(|total| downloaded as f32 / total as f32));
+        let _ = app.emit("download-progress", DownloadProgress { id: id.clone(), downloaded, total });
+    })
+    .await;
+    downloads.active.lock().unwrap().remove(&id);
+    if outcome.is_err() {
+        reporter.fail();
+    }
+    outcome
+}
+
+/// Stop a running download, keeping what it has so far for `start_download` to continue
+#[command]
+fn pause_download(downloads: State<'_, Downloads>, id: String) -> bool {
+    downloads.active.lock().unwrap().get(&id).map(|control| control.pause()).is_some()
+}
+
+/// Stop a running download and delete what it has so far
+#[command]
+fn cancel_download(downloads: State<'_, Downloads>, id: String) -> bool {
+    downloads.active.lock().unwrap().get(&id).map(|control| control.cancel()).is_some()
+}
+
+/// Fine-tuning runs the app is supervising by id, with the flag that stops each
+#[derive(Default)]
+struct FineTunes {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+fn fine_tune_store(app: &tauri::AppHandle) -> Result<FineTuneStore, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(FineTuneStore::new(&LauncherPaths::under(data_dir).state_dir))
+}
+
+/// Run `job` on a thread of its own, sending it as `fine-tune-updated` events as it
+/// progresses and notifying the user when it ends
+fn spawn_fine_tune(app: &tauri::AppHandle, mut job: FineTuneJob) -> Result<FineTuneJob, String> {
+    let config = load_launcher_config(app).map_err(|e| e.to_string())?.fine_tune;
+    let store = fine_tune_store(app)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let fine_tunes = app.state::<FineTunes>();
+        let mut active = fine_tunes.active.lock().unwrap();
+        if active.contains_key(&job.id) {
+            return Err(format!("Fine-tune {} is already running", job.id));
+        }
+        active.insert(job.id.clone(), stop.clone());
+    }
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let state_dir = LauncherPaths::under(data_dir).state_dir;
+    let started = job.clone();
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        let reporter = TaskReporter::start(&state_dir, &format!("fine-tune-{}", job.id), ProgressKind::FineTune, &job.id, job.fraction);
+        let outcome = fine_tune::run(&SystemRunner, &config, &store, &mut job, || stop.load(Ordering::Relaxed), |job| {
+            reporter.set(job.fraction);
+            let _ = handle.emit("fine-tune-updated", job);
+        });
+        handle.state::<FineTunes>().active.lock().unwrap().remove(&job.id);
+        let (category, title, body) = match (&outcome, &job.status) {
+            (Err(e), _) => (NotificationCategory::JobFinished, "Fine-tuning failed".to_string(), e.clone()),
+            (Ok(()), FineTuneStatus::Finished) => (
+                NotificationCategory::JobFinished,
+                "Fine-tuning finished".to_string(),
+                format!("The trained voice is in {}", job.output.display()),
+            ),
+            (Ok(()), FineTuneStatus::Stopped { reason, low_disk: true }) => (
+                NotificationCategory::OutOfDisk,
+                "Fine-tuning paused".to_string(),
+                format!("{}. Free some space and resume it.", reason),
+            ),
+            (Ok(()), FineTuneStatus::Failed { error }) => {
+                (NotificationCategory::JobFinished, "Fine-tuning failed".to_string(), error.clone())
+            }
+            // Stopped by the user, who knows
+            (Ok(()), _) => return,
+        };
+        if !matches!(job.status, FineTuneStatus::Finished) {
+            reporter.fail();
+        }
+        send_notification(&handle, Notification { category, title, body });
+    });
+    Ok(started)
+}
+
+/// Fine-tune a voice on a dataset made with `export_dataset`, with the trainer set up
+/// under `fine_tune` in the config. The trainer's checkpoints go to `output`, by
+/// default `training` in the dataset.
+#[command]
+fn start_fine_tune(app: tauri::AppHandle, dataset: String, output: Option<String>) -> Result<FineTuneJob, String> {
+    let dataset = std::path::PathBuf::from(dataset);
+    if !dataset.join(METADATA_FILE_NAME).is_file() {
+        return Err(format!("{} is not a dataset: it has no {}", dataset.display(), METADATA_FILE_NAME));
+    }
+    let output = output.map_or_else(|| dataset.join("training"), std::path::PathBuf::from);
+    spawn_fine_tune(&app, FineTuneJob::new(&dataset, &output))
+}
+
+/// Continue a stopped, failed or interrupted run from its newest checkpoint
+#[command]
+fn resume_fine_tune(app: tauri::AppHandle, id: String) -> Result<FineTuneJob, String> {
+    let job = fine_tune_store(&app)?.load(&id)?;
+    if matches!(job.status, FineTuneStatus::Finished) {
+        return Err(format!("Fine-tune {} has already finished", id));
+    }
+    spawn_fine_tune(&app, job)
+}
+
+/// Stop a run the app supervises, letting the trainer save a checkpoint first
+#[command]
+fn stop_fine_tune(fine_tunes: State<'_, FineTunes>, id: String) -> bool {
+    fine_tunes.active.lock().unwrap().get(&id).map(|stop| stop.store(true, Ordering::Relaxed)).is_some()
+}
+
+/// Every fine-tuning run, newest first
+#[command]
+fn list_fine_tunes(app: tauri::AppHandle) -> Result<Vec<FineTuneJob>, String> {
+    Ok(fine_tune_store(&app)?.list())
+}
+
+/// Generation settings stored in an exported file
+#[command]
+fn read_generation_settings(path: String) -> Result<Option<GenerationSnapshot>, String> {
+    let wav = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    snapshot::read(&wav)
+}
+
+/// Generate the audio of an exported file again with the settings stored in it. The
+/// new generation lands in the history like any other.
+#[command]
+async fn rerender_generation(path: String, server_url: Option<String>) -> Result<serde_json::Value, String> {
+    let settings = read_generation_settings(path.clone())?
+        .ok_or_else(|| format!("{} has no generation settings", path))?;
+    let request = settings.rerender_request()?;
+    let url = format!(
+        "{}/generate",
+        server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT)).trim_end_matches('/')
+    );
+    reqwest::Client::new()
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response from {}: {}", url, e))
+}
+
+#[command]
+fn verify_audio_watermark(path: String) -> Result<Verification, String> {
+    let wav = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    watermark::verify(&wav)
+}
+
+/// Bring the library search index up to date with the backend's history and stories
+#[command]
+async fn sync_library(
+    app: tauri::AppHandle,
+    state: State<'_, LibraryState>,
+    server_url: Option<String>,
+) -> Result<SyncReport, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let items = library::fetch_items(&url).await?;
+    let index = library_index(&app, &state)?;
+    let report = tauri::async_runtime::spawn_blocking(move || index.sync(&items))
+        .await
+        .map_err(|e| format!("Library sync failed: {}", e))??;
+    state.synced.store(true, Ordering::Relaxed);
+    Ok(report)
+}
+
+/// Search the library index without going through the backend. The first search after
+/// startup syncs the index unless `sync_library` already ran; if the backend isn't
+/// reachable the index answers from its last sync.
+#[command]
+async fn search_library(
+    app: tauri::AppHandle,
+    state: State<'_, LibraryState>,
+    query: String,
+    limit: Option<usize>,
+    server_url: Option<String>,
+) -> Result<Vec<SearchHit>, String> {
+    if !state.synced.load(Ordering::Relaxed) {
+        if let Err(e) = sync_library(app.clone(), state.clone(), server_url).await {
+            eprintln!("Library sync before search failed: {}", e);
+        }
+    }
+    library_index(&app, &state)?.search(&query, limit.unwrap_or(50))
+}
+
+fn waveform_cache(app: &tauri::AppHandle) -> Result<WaveformCache, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(WaveformCache::new(&LauncherPaths::under(data_dir).cache_dir))
+}
+
+/// Cached waveforms of the given generations, for drawing library cards without
+/// decoding audio. Generations missing from the result need `refresh_waveforms`.
+#[command]
+fn get_waveforms(app: tauri::AppHandle, generation_ids: Vec<String>) -> Result<HashMap<String, Waveform>, String> {
+    Ok(waveform_cache(&app)?.get_many(&generation_ids))
+}
+
+/// Compute waveforms for new or changed generations and drop those of deleted ones
+#[command]
+async fn refresh_waveforms(app: tauri::AppHandle, server_url: Option<String>) -> Result<RefreshReport, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let items = library::fetch_items(&url).await?;
+    waveform_cache(&app)?.refresh(&url, &items).await
+}
+
+/// Queue Opus previews for generations without a current one and drop those of
+/// deleted generations. Each finished preview is announced with a `preview-ready`
+/// event. Returns how many were queued.
+#[command]
+async fn queue_previews(queue: State<'_, PreviewQueue>, server_url: Option<String>) -> Result<usize, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let items = library::fetch_items(&url).await?;
+    let keep = items.iter().map(|item| item.id.as_str()).collect();
+    queue.cache().prune(&keep)?;
+    Ok(items.iter().filter(|item| queue.enqueue(&url, item)).count())
+}
+
+/// Path of the Opus preview of a generation, for streaming in the library. Exports
+/// keep using the full-quality WAV from the backend.
+#[command]
+fn get_preview(queue: State<'_, PreviewQueue>, generation_id: String) -> Option<String> {
+    queue.cache().get(&generation_id).map(|path| path.to_string_lossy().to_string())
+}
+
+fn soundboard(app: &tauri::AppHandle) -> Result<Soundboard, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(Soundboard::new(&LauncherPaths::under(data_dir).cache_dir))
+}
+
+/// The configured soundboard clips, with their hotkeys for the UI to register
+#[command]
+fn list_soundboard_clips(app: tauri::AppHandle) -> Result<Vec<ClipStatus>, String> {
+    let config = load_launcher_config(&app).map_err(|e| e.to_string())?;
+    Ok(soundboard(&app)?.status(&config))
+}
+
+/// Render the clips whose text or preset changed since they were last rendered and
+/// drop those removed from the config. Returns how many were rendered.
+#[command]
+async fn render_soundboard(app: tauri::AppHandle, server_url: Option<String>) -> Result<usize, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let config = load_launcher_config(&app).map_err(|e| e.to_string())?;
+    let board = soundboard(&app)?;
+    let reminder_clips: Vec<String> = config.reminders.iter().map(Reminder::clip_name).collect();
+    let keep = config.soundboard.keys().chain(&reminder_clips).map(String::as_str).collect();
+    board.prune(&keep)?;
+    let stale: Vec<_> = config
+        .soundboard
+        .keys()
+        .filter_map(|name| config.resolve_clip(name).ok())
+        .filter(|clip| board.get(clip).is_none())
+        .collect();
+    if stale.is_empty() {
+        return Ok(0);
+    }
+
+    let state_dir = LauncherPaths::under(app.path().app_data_dir().map_err(|e| e.to_string())?).state_dir;
+    let reporter = TaskReporter::start(&state_dir, "soundboard", ProgressKind::BatchJob, "Rendering soundboard", Some(0.0));
+    for (done, clip) in stale.iter().enumerate() {
+        let rendered = soundboard::render(&url, clip).await;
+        let stored = rendered.and_then(|(generation_id, wav)| board.put(clip, &generation_id, &wav));
+        if let Err(e) = stored {
+            reporter.fail();
+            return Err(format!("Failed to render soundboard clip '{}': {}", clip.name, e));
+        }
+        reporter.set(Some((done + 1) as f32 / stale.len() as f32));
+    }
+    Ok(stale.len())
+}
+
+/// Play a soundboard clip on its preset's output devices, or the default device. A clip
+/// that isn't rendered yet is rendered first.
+#[command]
+async fn play_soundboard_clip(
+    app: tauri::AppHandle,
+    state: State<'_, audio_output::AudioOutputState>,
+    name: String,
+    server_url: Option<String>,
+) -> Result<(), String> {
+    let clip = load_launcher_config(&app).map_err(|e| e.to_string())?.resolve_clip(&name)?;
+    let board = soundboard(&app)?;
+    let path = match board.get(&clip) {
+        Some(path) => path,
+        None => {
+            let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+            let (generation_id, wav) = soundboard::render(&url, &clip).await?;
+            board.put(&clip, &generation_id, &wav)?
+        }
+    };
+    let wav = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let devices = match clip.output_devices.is_empty() {
+        true => state.list_output_devices()?.into_iter().filter(|d| d.is_default).map(|d| d.id).collect(),
+        false => clip.output_devices,
+    };
+    state.play_audio_to_devices(wav, devices, clip.duck).await
+}
+
+/// Library samples that an imported recording duplicates, so the UI can offer to
+/// link the existing sample instead of storing the audio again
+#[command]
+async fn find_duplicate_samples(
+    app: tauri::AppHandle,
+    audio: Vec<u8>,
+    server_url: Option<String>,
+) -> Result<Vec<DuplicateMatch>, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let samples = duplicates::fetch_samples(&url).await?;
+    FingerprintCache::new(&LauncherPaths::under(data_dir).cache_dir)
+        .find_duplicates(&url, &samples, &audio)
+        .await
+}
+
+/// The trash and how many days it keeps items
+fn trash(app: &tauri::AppHandle) -> Result<(Trash, u32), String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let retention = LauncherConfig::load_from_data_dir(&data_dir)?
+        .trash_retention_days
+        .unwrap_or(trash::DEFAULT_RETENTION_DAYS);
+    Ok((Trash::new(&LauncherPaths::under(data_dir).trash_dir), retention))
+}
+
+/// Delete a generation or voice sample so that it can still be restored
+#[command]
+async fn trash_library_item(
+    app: tauri::AppHandle,
+    kind: TrashKind,
+    id: String,
+    server_url: Option<String>,
+) -> Result<TrashEntry, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    trash(&app)?.0.trash_item(&url, kind, &id).await
+}
+
+#[command]
+async fn restore_library_item(
+    app: tauri::AppHandle,
+    kind: TrashKind,
+    id: String,
+    server_url: Option<String>,
+) -> Result<TrashEntry, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    trash(&app)?.0.restore_item(&url, kind, &id).await
+}
+
+/// Items in the trash, after purging those past the retention period
+#[command]
+async fn list_trash(app: tauri::AppHandle, server_url: Option<String>) -> Result<Vec<TrashEntry>, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    let (trash, retention) = trash(&app)?;
+    let report = trash.purge_expired(&url, retention).await?;
+    for (id, error) in &report.failed {
+        eprintln!("Failed to purge {} from the trash: {}", id, error);
+    }
+    trash.entries()
+}
+
+/// Delete everything in the trash for good
+#[command]
+async fn empty_trash(app: tauri::AppHandle, server_url: Option<String>) -> Result<PurgeReport, String> {
+    let url = server_url.unwrap_or_else(|| format!("http://127.0.0.1:{}", SERVER_PORT));
+    trash(&app)?.0.purge_expired(&url, 0).await
+}
+
+/// Model downloads the backend at `url` is running, with percent done when known
+fn active_downloads(client: &reqwest::blocking::Client, url: &str) -> Vec<(String, Option<f32>)> {
+    let tasks: serde_json::Value = match client.get(format!("{}/tasks/active", url)).send().and_then(|r| r.json()) {
+        Ok(tasks) => tasks,
+        Err(_) => return Vec::new(),
+    };
+    tasks["downloads"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|download| {
+            let name = download["model_name"].as_str()?.to_string();
+            Some((name, download["progress"].as_f64().map(|percent| percent as f32 / 100.0)))
+        })
+        .collect()
+}
+
+/// Show long-running work in the taskbar (Windows) or dock (macOS), even while the
+/// window is minimized. Installs and batch jobs are reported to the progress bus by
+/// the processes running them; model downloads are polled from the backend here.
+fn start_progress_indicator(handle: tauri::AppHandle, state_dir: std::path::PathBuf) {
+    use tauri::window::{ProgressBarState, ProgressBarStatus};
+
+    std::thread::spawn(move || {
+        let bus = ProgressBus::new(&state_dir);
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(2))
+            .build()
+            .unwrap_or_default();
+        let url = format!("http://127.0.0.1:{}", SERVER_PORT);
+        let mut downloading: std::collections::HashSet<String> = Default::default();
+        let mut shown: Option<Option<OverallProgress>> = None;
+        let mut running = std::collections::BTreeMap::new();
+        loop {
+            let server_running = handle.state::<ServerState>().server_pid.lock().unwrap().is_some();
+            let downloads = if server_running { active_downloads(&client, &url) } else { Vec::new() };
+            let mut still_downloading = std::collections::HashSet::new();
+            for (model, fraction) in downloads {
+                let id = format!("model-{}", model);
+                let _ = bus.report(&id, ProgressKind::ModelDownload, &model, fraction);
+                still_downloading.insert(id);
+            }
+            for finished in downloading.difference(&still_downloading) {
+                let _ = bus.finish(finished);
+            }
+            downloading = still_downloading;
+
+            let tasks = bus.tasks().unwrap_or_default();
+            for (id, task) in &running {
+                match tasks.get(id) {
+                    None => announce(&handle, StateChange::JobFinished { label: task_label(task) }),
+                    Some(now) if now.failed => announce(&handle, StateChange::JobFailed { label: task_label(now) }),
+                    Some(_) => {}
+                }
+            }
+            running = tasks.into_iter().filter(|(_, task)| !task.failed).collect();
+
+            let overall = bus.overall().unwrap_or(None);
+            if shown.as_ref() != Some(&overall) {
+                let state = match &overall {
+                    None => ProgressBarState { status: Some(ProgressBarStatus::None), progress: None },
+                    Some(overall) if overall.failed => {
+                        ProgressBarState { status: Some(ProgressBarStatus::Error), progress: Some(100) }
+                    }
+                    Some(OverallProgress { fraction: Some(fraction), .. }) => ProgressBarState {
+                        status: Some(ProgressBarStatus::Normal),
+                        progress: Some((fraction * 100.0).round() as u64),
+                    },
+                    Some(_) => ProgressBarState { status: Some(ProgressBarStatus::Indeterminate), progress: None },
+                };
+                if let Some(window) = handle.get_webview_window("main") {
+                    let _ = window.set_progress_bar(state);
+                    #[cfg(target_os = "macos")]
+                    let _ = window.set_badge_count(overall.as_ref().map(|o| o.tasks as i64).filter(|n| *n > 0));
+                }
+                let _ = handle.emit("progress-changed", &overall);
+                shown = Some(overall);
+            }
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+    });
+}
+
+/// How a task is named when announcing it finished or failed
+fn task_label(task: &TaskProgress) -> String {
+    match task.kind {
+        ProgressKind::Install => "Installing packages".to_string(),
+        ProgressKind::ModelDownload => format!("Download of {}", task.label),
+        ProgressKind::BatchJob => task.label.clone(),
+        ProgressKind::FileDownload => format!("Download of {}", task.label),
+        ProgressKind::FineTune => format!("Fine-tuning {}", task.label),
+    }
+}
+
+/// Tell screen readers about `change` through the UI's live regions
+fn announce(app: &tauri::AppHandle, change: StateChange) {
+    if let Some(announcement) = app.state::<Announcer>().announce(change) {
+        let _ = app.emit(announcements::ANNOUNCEMENT_EVENT, announcement);
+    }
+}
+
+/// Announce a change the UI itself noticed, such as microphone recording, in the
+/// same channel as the app's own
+#[command]
+fn announce_state_change(app: tauri::AppHandle, change: StateChange) {
+    announce(&app, change);
+}
+
+/// Run one configured mirror, or only report what it would copy
+fn mirror_now(data_dir: &std::path::Path, config: &MirrorConfig, dry_run: bool) -> Result<MirrorReport, String> {
+    let paths = LauncherPaths::under(data_dir.to_path_buf());
+    let mirror = Mirror::new(config, &paths.data_dir, &paths.state_dir)?;
+    mirror.run(config.destination.open()?.as_ref(), dry_run)
+}
+
+#[command]
+fn list_mirrors(app: tauri::AppHandle) -> Result<Vec<MirrorConfig>, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(LauncherConfig::load_from_data_dir(&data_dir)?.mirrors)
+}
+
+/// Copy library folders to a configured destination now. Uploads block, so they run
+/// off the async runtime.
+#[command]
+async fn run_mirror(app: tauri::AppHandle, name: String, dry_run: bool) -> Result<MirrorReport, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let config = LauncherConfig::load_from_data_dir(&data_dir)?
+        .mirrors
+        .into_iter()
+        .find(|mirror| mirror.name == name)
+        .ok_or_else(|| format!("No mirror named {:?}", name))?;
+    tauri::async_runtime::spawn_blocking(move || mirror_now(&data_dir, &config, dry_run))
+        .await
+        .map_err(|e| format!("Mirror {} stopped: {}", name, e))?
+}
+
+/// Run mirrors that have an interval whenever they are due, telling the UI how each
+/// run went. The config is reread each time so edits apply without a restart.
+fn start_mirror_scheduler(handle: tauri::AppHandle, data_dir: std::path::PathBuf) {
+    std::thread::spawn(move || loop {
+        let mirrors = LauncherConfig::load_from_data_dir(&data_dir).map(|config| config.mirrors).unwrap_or_default();
+        let paths = LauncherPaths::under(data_dir.clone());
+        for config in mirrors.iter().filter(|config| config.interval_minutes.is_some()) {
+            let due = Mirror::new(config, &paths.data_dir, &paths.state_dir).is_ok_and(|mirror| mirror.is_due());
+            if !due {
+                continue;
+            }
+            match mirror_now(&data_dir, config, false) {
+                Ok(report) => {
+                    send_notification(&handle, Notification {
+                        category: NotificationCategory::BackupFinished,
+                        title: format!("Mirror {} finished", report.name),
+                        body: format!("{} copied, {} failed", report.copied.len(), report.failed.len()),
+                    });
+                    let _ = handle.emit("mirror-finished", report);
+                }
+                Err(e) => eprintln!("Mirror {} failed: {}", config.name, e),
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_secs(60));
+    });
+}
+
+/// Queue reminders for the narration thread when they are due; they are rendered into
+/// the soundboard cache the first time they play. The config is reread each time so
+/// edits apply without a restart.
+fn start_reminder_scheduler(handle: tauri::AppHandle, data_dir: std::path::PathBuf) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(30));
+        let Ok(config) = LauncherConfig::load_from_data_dir(&data_dir) else {
+            continue;
+        };
+        let log = ReminderLog::new(&LauncherPaths::under(data_dir.clone()).state_dir);
+        let now = chrono::Local::now();
+        for reminder in log.due(&config.reminders, now) {
+            // Recorded up front, so a reminder that fails isn't retried every 30 seconds
+            if let Err(e) = log.record(&reminder.name, now) {
+                eprintln!("Failed to record reminder {}: {}", reminder.name, e);
+                continue;
+            }
+            let utterance = Utterance {
+                watch: SCHEDULER_SOURCE.to_string(),
+                preset: reminder.preset.clone(),
+                text: reminder.text.clone(),
+                clip: Some(reminder.clip_name()),
+            };
+            if queue_line(&handle, utterance, QueueLimits::DIRECT, config.source_priority(SCHEDULER_SOURCE)) == Queued::Dropped {
+                continue;
+            }
+            let _ = handle.emit("reminder-spoken", &reminder.name);
+        }
+    });
+}
+
+/// Play a soundboard or reminder clip from the render cache, rendering it first if it
+/// isn't there, and return how long it plays
+fn play_cached_clip(handle: &tauri::AppHandle, clip: ResolvedClip) -> Result<std::time::Duration, String> {
+    let board = soundboard(handle)?;
+    let path = match board.get(&clip) {
+        Some(path) => path,
+        None => {
+            let url = format!("http://127.0.0.1:{}", SERVER_PORT);
+            let (generation_id, wav) = tauri::async_runtime::block_on(soundboard::render(&url, &clip))?;
+            board.put(&clip, &generation_id, &wav)?
+        }
+    };
+    let wav = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let duration = narration::wav_duration(&wav).unwrap_or_default();
+    play_clip_audio(handle, clip, wav)?;
+    Ok(duration)
+}
+
+/// Play `wav` on the clip's output devices, or the default device
+fn play_clip_audio(handle: &tauri::AppHandle, clip: ResolvedClip, wav: Vec<u8>) -> Result<(), String> {
+    let state = handle.state::<audio_output::AudioOutputState>();
+    let devices = match clip.output_devices.is_empty() {
+        true => state.list_output_devices()?.into_iter().filter(|d| d.is_default).map(|d| d.id).collect(),
+        false => clip.output_devices,
+    };
+    tauri::async_runtime::block_on(state.play_audio_to_devices(wav, devices, clip.duck))
+}
+
+/// Follow the files in the `narration` config, queueing their new lines, and speak the
+/// queue one line after the other. The config is reread every few seconds so watches
+/// can be added and changed without a restart.
+fn start_narration(handle: tauri::AppHandle, data_dir: std::path::PathBuf) {
+    let watcher = handle.clone();
+    let config_dir = data_dir.clone();
+    std::thread::spawn(move || {
+        let mut tails: HashMap<String, (CompiledWatch, Tail)> = HashMap::new();
+        let mut config = LauncherConfig::default();
+        let mut config_read: Option<std::time::Instant> = None;
+        loop {
+            if config_read.is_none_or(|at| at.elapsed() >= std::time::Duration::from_secs(5)) {
+                config_read = Some(std::time::Instant::now());
+                config = LauncherConfig::load_from_data_dir(&config_dir).unwrap_or_default();
+                let watches = &config.narration;
+                tails.retain(|name, (compiled, _)| watches.iter().any(|watch| watch.name == *name && *watch == compiled.watch));
+                for watch in watches.iter().filter(|watch| watch.enabled) {
+                    if tails.contains_key(&watch.name) {
+                        continue;
+                    }
+                    match CompiledWatch::new(watch) {
+                        Ok(compiled) => {
+                            tails.insert(watch.name.clone(), (compiled, Tail::at_end(&watch.path)));
+                        }
+                        Err(e) => eprintln!("{}", e),
+                    }
+                }
+            }
+            for (compiled, tail) in tails.values_mut() {
+                let priority = config.source_priority(&compiled.watch.name);
+                match tail.read_lines() {
+                    Ok(lines) => lines
+                        .iter()
+                        .filter_map(|line| compiled.route(line))
+                        .for_each(|utterance| {
+                            queue_line(&watcher, utterance, compiled.watch.limits(), priority);
+                        }),
+                    Err(e) => eprintln!("Narration {}: failed to read {}: {}", compiled.watch.name, compiled.watch.path.display(), e),
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(500));
+        }
+    });
+
+    std::thread::spawn(move || loop {
+        let narrator = handle.state::<Narrator>();
+        let Some(utterance) = narrator.next(std::time::Instant::now()) else {
+            std::thread::sleep(std::time::Duration::from_millis(250));
+            continue;
+        };
+        match speak_line(&handle, &data_dir, &utterance) {
+            // Playback runs on its own, so wait for it to end before the next line
+            Ok(duration) => {
+                let until = std::time::Instant::now() + duration;
+                while std::time::Instant::now() < until && !narrator.current_skipped() {
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+            }
+            Err(e) => eprintln!("Narration {} failed: {}", utterance.watch, e),
+        }
+        narrator.finished();
+    });
+}
+
+/// Queue `utterance` for the narration thread, stopping the line playing if it
+/// interrupts it
+fn queue_line(handle: &tauri::AppHandle, utterance: Utterance, limits: QueueLimits, priority: SourcePriority) -> Queued {
+    let queued = handle.state::<Narrator>().push(utterance, limits, priority);
+    if queued == Queued::Interrupting {
+        if let Err(e) = handle.state::<audio_output::AudioOutputState>().stop_all_playback() {
+            eprintln!("Failed to stop playback for an interruption: {}", e);
+        }
+    }
+    queued
+}
+
+/// Generate and play a queued line, or play its rendered clip, returning how long it
+/// plays. Chat messages and file lines come from outside, so they go through the
+/// content filter first.
+fn speak_line(handle: &tauri::AppHandle, data_dir: &std::path::Path, utterance: &Utterance) -> Result<std::time::Duration, String> {
+    let config = LauncherConfig::load_from_data_dir(data_dir)?;
+    if let Some(name) = &utterance.clip {
+        let clip = match config.reminders.iter().find(|reminder| reminder.clip_name() == *name) {
+            Some(reminder) => config.reminder_clip(reminder)?,
+            None => config.resolve_clip(name)?,
+        };
+        return play_cached_clip(handle, clip);
+    }
+    let text = match utterance.watch.as_str() {
+        API_SOURCE => utterance.text.clone(),
+        watch => {
+            let source = if watch == CHAT_SOURCE { filter::source::CHAT } else { filter::source::NARRATION };
+            let resolved = config.presets.get(&utterance.preset).and_then(|preset| preset.resolve(&utterance.preset).ok());
+            let language = resolved.as_ref().map(|resolved| resolved.language());
+            ContentFilter::new(&config.content_filter)?.apply(source, language, &utterance.text)?
+        }
+    };
+    let clip = SoundboardClip { text, preset: utterance.preset.clone(), hotkey: None };
+    let clip = config.clip_for(&format!("narration-{}", utterance.watch), &clip)?;
+    let url = format!("http://127.0.0.1:{}", SERVER_PORT);
+    let (_, wav) = tauri::async_runtime::block_on(soundboard::render(&url, &clip))?;
+    let duration = narration::wav_duration(&wav).unwrap_or_default();
+    play_clip_audio(handle, clip, wav)?;
+    Ok(duration)
+}
+
+/// Read the configured Twitch or YouTube chat and queue the messages the filter lets
+/// through for narration, reconnecting when the connection drops. The config is
+/// checked every few seconds; changing the `chat` section reconnects with it.
+fn start_chat_bridge(handle: tauri::AppHandle, data_dir: std::path::PathBuf) {
+    std::thread::spawn(move || {
+        let load = || LauncherConfig::load_from_data_dir(&data_dir).ok().and_then(|config| config.chat).filter(|chat| chat.enabled);
+        loop {
+            let Some(chat) = load() else {
+                std::thread::sleep(std::time::Duration::from_secs(10));
+                continue;
+            };
+            let bridge = handle.state::<ChatBridge>();
+            let priority = LauncherConfig::load_from_data_dir(&data_dir).unwrap_or_default().source_priority(CHAT_SOURCE);
+            let mut on_message = |message: ChatMessage| {
+                if bridge.admit(&chat.filter, &message, std::time::Instant::now()) {
+                    let utterance = Utterance {
+                        watch: CHAT_SOURCE.to_string(),
+                        preset: chat.preset.clone(),
+                        text: message.spoken(),
+                        clip: None,
+                    };
+                    queue_line(&handle, utterance, chat.limits(), priority);
+                }
+            };
+            let checked = std::cell::Cell::new(std::time::Instant::now());
+            let stop = || {
+                if checked.get().elapsed() < std::time::Duration::from_secs(5) {
+                    return false;
+                }
+                checked.set(std::time::Instant::now());
+                load().as_ref() != Some(&chat)
+            };
+
+            bridge.set_connected(true);
+            let read = match &chat.source {
+                ChatSource::Twitch { channel } => read_twitch(channel, &mut on_message, &stop),
+                ChatSource::Youtube { video_id, api_key } => read_youtube(video_id, api_key, &mut on_message, &stop),
+            };
+            bridge.set_connected(false);
+            if let Err(e) = read {
+                eprintln!("Chat: {}", e);
+                std::thread::sleep(std::time::Duration::from_secs(15));
+            }
+        }
+    });
+}
+
+#[command]
+fn get_chat_status(app: tauri::AppHandle, bridge: State<'_, ChatBridge>) -> ChatStatus {
+    let pause_hotkey = load_launcher_config(&app).ok().and_then(|config| config.chat).and_then(|chat| chat.pause_hotkey);
+    ChatStatus { pause_hotkey, ..bridge.status() }
+}
+
+/// Pause or resume reading chat aloud, e.g. from the moderation hotkey. Pausing drops
+/// the messages waiting to be read as well.
+#[command]
+fn set_chat_paused(bridge: State<'_, ChatBridge>, narrator: State<'_, Narrator>, paused: bool) {
+    bridge.set_paused(paused);
+    if paused {
+        narrator.clear(CHAT_SOURCE);
+    }
+}
+
+/// Play a soundboard clip through the narration queue, as its hotkey does, so it goes
+/// ahead of (or cuts into) lower priority lines instead of playing over them
+#[command]
+fn queue_soundboard_clip(app: tauri::AppHandle, name: String) -> Result<Queued, String> {
+    let config = load_launcher_config(&app).map_err(|e| e.to_string())?;
+    let clip = config.soundboard.get(&name).ok_or_else(|| format!("Unknown soundboard clip '{}'", name))?;
+    let utterance = Utterance {
+        watch: HOTKEY_SOURCE.to_string(),
+        preset: clip.preset.clone(),
+        text: clip.text.clone(),
+        clip: Some(name),
+    };
+    Ok(queue_line(&app, utterance, QueueLimits::DIRECT, config.source_priority(HOTKEY_SOURCE)))
+}
+
+/// Speak `text` with `preset`, or the selected preset, through the narration queue,
+/// under the priority of `source` (`api` unless given)
+#[command]
+fn queue_speech(app: tauri::AppHandle, text: String, preset: Option<String>, source: Option<String>) -> Result<Queued, String> {
+    let config = load_launcher_config(&app).map_err(|e| e.to_string())?;
+    let preset = preset
+        .or_else(|| app.state::<SelectedPreset>().0.lock().unwrap().clone())
+        .ok_or("No preset given and none selected")?;
+    let source = source.unwrap_or_else(|| API_SOURCE.to_string());
+    let priority = config.source_priority(&source);
+    let utterance = Utterance { watch: source, preset, text, clip: None };
+    Ok(queue_line(&app, utterance, QueueLimits::DIRECT, priority))
+}
+
+/// Lines waiting to be narrated and how many were skipped
+#[command]
+fn get_narration_status(narrator: State<'_, Narrator>) -> NarrationStatus {
+    narrator.status()
+}
+
+/// Cut the narrated line short, and with `all` drop the queued ones too. This stops
+/// whatever else is playing as well. Returns how many queued lines were dropped.
+#[command]
+fn skip_narration(
+    narrator: State<'_, Narrator>,
+    state: State<'_, audio_output::AudioOutputState>,
+    all: bool,
+) -> Result<usize, String> {
+    state.stop_all_playback()?;
+    Ok(narrator.skip(all))
+}
+
+/// Show `notification`, hold it until focus time ends, or drop it, as the policy
+/// for its category says
+fn send_notification(app: &tauri::AppHandle, notification: Notification) {
+    let center = app.state::<NotificationCenter>();
+    if center.submit(notification.clone(), notifications::focus_active()) == Delivery::Show {
+        show_notification(app, &notification);
+    }
+}
+
+fn show_notification(app: &tauri::AppHandle, notification: &Notification) {
+    if let Err(e) = app.notification().builder().title(&notification.title).body(&notification.body).show() {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
+
+/// Show notifications held back during focus time once it ends
+fn start_notification_release(handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(30));
+        let released = handle.state::<NotificationCenter>().release(notifications::focus_active());
+        for notification in &released {
+            show_notification(&handle, notification);
+        }
+    });
+}
+
+/// Apply the parts of `config` the app holds itself rather than reading the config
+/// when it needs them
+fn apply_config(app: &tauri::AppHandle, config: &LauncherConfig) {
+    log::set_level(config.log_level);
+    refresh_tray_menu(app, config);
+    let center = app.state::<NotificationCenter>();
+    for (category, policy) in config.notifications.iter().flat_map(|settings| &settings.policies) {
+        if let Err(e) = center.set_policy(*category, *policy) {
+            eprintln!("Failed to apply notification policy from the config: {}", e);
+        }
+    }
+}
+
+/// Watch the config file and apply what changes in it. Sections read when they are
+/// used take effect on their own and a new `backend_log_level` is passed to the running
+/// backend; the UI is told with `config-reloaded` which sections
+/// changed, so it can register hotkeys again, and which still wait for a backend restart.
+fn start_config_watcher(handle: tauri::AppHandle, data_dir: std::path::PathBuf) {
+    std::thread::spawn(move || {
+        let mut watcher = ConfigWatcher::new(&LauncherConfig::path(&data_dir));
+        apply_config(&handle, watcher.config());
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            match watcher.poll() {
+                Some(Ok(change)) => {
+                    apply_config(&handle, watcher.config());
+                    if change.changed.iter().any(|section| section == "backend_log_level") {
+                        let level = watcher.config().backend_log_level.unwrap_or(BackendLevel::Info);
+                        let url = format!("http://127.0.0.1:{}", SERVER_PORT);
+                        if let Err(e) = tauri::async_runtime::block_on(backend_log::set_level(&url, level)) {
+                            eprintln!("Failed to apply the backend log level: {}", e);
+                        }
+                    }
+                    if !change.restart_required.is_empty() {
+                        println!("Config changes waiting for a backend restart: {}", change.restart_required.join(", "));
+                    }
+                    let _ = handle.emit("config-reloaded", &change);
+                }
+                Some(Err(e)) => eprintln!("Keeping the previous config: {}", e),
+                None => {}
+            }
+        }
+    });
+}
+
+/// Notify the user unless their policy for `category` says otherwise, for
+/// notifications raised by the UI such as an available update
+#[command]
+fn notify(app: tauri::AppHandle, category: NotificationCategory, title: String, body: String) {
+    send_notification(&app, Notification { category, title, body });
+}
+
+#[command]
+fn get_notification_settings(center: State<'_, NotificationCenter>) -> Result<NotificationSettings, String> {
+    center.settings()
+}
+
+#[command]
+fn set_notification_policy(
+    center: State<'_, NotificationCenter>,
+    category: NotificationCategory,
+    policy: NotificationPolicy,
+) -> Result<(), String> {
+    center.set_policy(category, policy)
+}
+
+/// Projects of this data dir and the launcher config they are placed by
+fn project_registry(app: &tauri::AppHandle) -> Result<(LauncherConfig, LauncherPaths), String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok((LauncherConfig::load_from_data_dir(&data_dir)?, LauncherPaths::under(data_dir)))
+}
+
+/// Recently opened projects, most recent first
+#[command]
+fn list_recent_projects(app: tauri::AppHandle) -> Result<Vec<ProjectInfo>, String> {
+    let (config, paths) = project_registry(&app)?;
+    Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir).recent()
+}
+
+/// The project the backend runs on; `None` while it runs on the app data dir
+#[command]
+fn get_active_project(app: tauri::AppHandle) -> Result<Option<ProjectInfo>, String> {
+    let (config, paths) = project_registry(&app)?;
+    Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir).active()
+}
+
+/// Start an empty project with its own database and audio in the given data root
+#[command]
+fn create_project(app: tauri::AppHandle, name: String, root: Option<String>) -> Result<ProjectInfo, String> {
+    let (config, paths) = project_registry(&app)?;
+    Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir).create(&name, root.as_deref())
+}
+
+/// Stop the backend, open another project (or none for the app data dir) and start
+/// the backend again on its database. Returns the server URL.
+#[command]
+async fn switch_project(
+    app: tauri::AppHandle,
+    state: State<'_, ServerState>,
+    name: Option<String>,
+    remote: Option<bool>,
+) -> Result<String, String> {
+    let (config, paths) = project_registry(&app)?;
+    let projects = Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir);
+    let previous = projects.active()?.map(|project| project.name);
+    projects.open(name.as_deref())?;
+    stop_server(app.clone(), state.clone()).await?;
+    match start_server(app.clone(), state.clone(), remote).await {
+        Ok(url) => Ok(url),
+        Err(e) => {
+            // Get back to a working backend on the project that was open before
+            projects.open(previous.as_deref())?;
+            let _ = start_server(app, state, remote).await;
+            Err(format!("Failed to start the backend on the project: {}", e))
+        }
+    }
+}
+
+/// Lock a project for review or unlock it. The server is restarted when the project
+/// is open so the change applies right away.
+#[command]
+async fn set_project_locked(
+    app: tauri::AppHandle,
+    state: State<'_, ServerState>,
+    name: String,
+    locked: bool,
+    remote: Option<bool>,
+) -> Result<ProjectInfo, String> {
+    let (config, paths) = project_registry(&app)?;
+    let projects = Projects::new(&config.workspace, &paths.data_dir, &paths.state_dir);
+    let project = projects.set_locked(&name, locked)?;
+    let is_open = projects.active()?.is_some_and(|active| active.name == name);
+    if is_open && state.child.lock().unwrap().is_some() {
+        stop_server(app.clone(), state.clone()).await?;
+        start_server(app, state, remote).await?;
+    }
+    Ok(project)
+}
+
+#[command]
+fn check_data_dir_storage(app: tauri::AppHandle) -> Result<Option<StorageWarning>, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(voicebox::launcher::storage::check_data_dir(&data_dir))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(ServerState {
             child: Mutex::new(None),
             server_pid: Mutex::new(None),
             keep_running_on_close: Mutex::new(false),
+            capabilities: Mutex::new(None),
         })
+        .manage(LibraryState::default())
+        .manage(Downloads::default())
+        .manage(FineTunes::default())
+        .manage(SelectedPreset::default())
+        .manage(Announcer::default())
         .manage(audio_capture::AudioCaptureState::new())
         .manage(audio_output::AudioOutputState::new())
         .setup(|app| {
@@ -547,18 +2131,107 @@ pub fn run() {
                     println!("No window found to open dev tools");
                 }
             }
+
+            // Encode previews in the background and tell the UI as each one is ready
+            let cache_dir = LauncherPaths::under(app.path().app_data_dir()?).cache_dir;
+            let handle = app.handle().clone();
+            app.manage(PreviewQueue::start(PreviewCache::new(&cache_dir), preview::download_audio, move |event| {
+                let _ = handle.emit("preview-ready", event);
+            }));
+
+            app.manage(NotificationCenter::new(&LauncherPaths::under(app.path().app_data_dir()?).state_dir));
+            start_notification_release(app.handle().clone());
+            // Presets can be picked from the tray; the config watcher fills in the menu
+            let mut tray = TrayIconBuilder::with_id(TRAY_ID).tooltip("Voicebox").on_menu_event(|app, event| {
+                if let Some(name) = event.id().as_ref().strip_prefix(TRAY_PRESET_PREFIX) {
+                    if let Err(e) = select_preset(app.clone(), name.to_string()) {
+                        eprintln!("Failed to select preset '{}': {}", name, e);
+                    }
+                }
+            });
+            if let Some(icon) = app.default_window_icon() {
+                tray = tray.icon(icon.clone());
+            }
+            tray.build(app)?;
+            start_config_watcher(app.handle().clone(), app.path().app_data_dir()?);
+            start_mirror_scheduler(app.handle().clone(), app.path().app_data_dir()?);
+            start_reminder_scheduler(app.handle().clone(), app.path().app_data_dir()?);
+            app.manage(Narrator::default());
+            start_narration(app.handle().clone(), app.path().app_data_dir()?);
+            app.manage(ChatBridge::default());
+            start_chat_bridge(app.handle().clone(), app.path().app_data_dir()?);
+            start_progress_indicator(app.handle().clone(), LauncherPaths::under(app.path().app_data_dir()?).state_dir);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             start_server,
             stop_server,
             set_keep_server_running,
+            get_launcher_status,
             start_system_audio_capture,
             stop_system_audio_capture,
             is_system_audio_supported,
             list_audio_output_devices,
             play_audio_to_devices,
-            stop_audio_playback
+            stop_audio_playback,
+            list_soundboard_clips,
+            render_soundboard,
+            play_soundboard_clip,
+            get_narration_status,
+            skip_narration,
+            queue_soundboard_clip,
+            queue_speech,
+            get_chat_status,
+            set_chat_paused,
+            list_presets,
+            get_selected_preset,
+            select_preset,
+            resolve_preset,
+            check_data_dir_storage,
+            get_backend_capabilities,
+            get_backend_log_level,
+            set_backend_log_level,
+            chunk_text,
+            normalize_text,
+            export_voice_profile,
+            import_voice_profile,
+            get_voice_consent,
+            record_voice_consent,
+            export_generation_audio,
+            export_game_bank,
+            export_dataset,
+            start_download,
+            pause_download,
+            cancel_download,
+            start_fine_tune,
+            resume_fine_tune,
+            stop_fine_tune,
+            list_fine_tunes,
+            verify_audio_watermark,
+            read_generation_settings,
+            rerender_generation,
+            sync_library,
+            search_library,
+            get_waveforms,
+            refresh_waveforms,
+            queue_previews,
+            get_preview,
+            trash_library_item,
+            restore_library_item,
+            list_trash,
+            empty_trash,
+            find_duplicate_samples,
+            list_mirrors,
+            run_mirror,
+            list_recent_projects,
+            get_active_project,
+            create_project,
+            switch_project,
+            set_project_locked,
+            notify,
+            get_notification_settings,
+            set_notification_policy,
+            announce_state_change
         ])
         .on_window_event(|window, event| {
             if let WindowEvent::CloseRequested { api, .. } = event {
@@ -0,0 +1,91 @@
+use super::pcm::{analyze, AudioStats, Pcm};
+use serde::Serialize;
+
+/// Silence kept before the first word when aligning, so onsets aren't clipped
+const LEAD_IN_SECS: f64 = 0.05;
+
+/// How rendering B differs from rendering A
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComparisonReport {
+    pub a: AudioStats,
+    pub b: AudioStats,
+    /// Speech duration of B minus A; positive when B is slower
+    pub speech_difference_secs: f64,
+    /// RMS level of B minus A; positive when B is louder
+    pub loudness_difference_db: f64,
+}
+
+/// Compare two renderings of the same text
+pub fn compare(a: &[u8], b: &[u8]) -> Result<ComparisonReport, String> {
+    let (a, b) = (analyze(a)?, analyze(b)?);
+    Ok(ComparisonReport {
+        speech_difference_secs: b.speech_secs() - a.speech_secs(),
+        loudness_difference_db: b.rms_dbfs - a.rms_dbfs,
+        a,
+        b,
+    })
+}
+
+/// Line two renderings up for switching between them: leading silence is cut so
+/// speech starts at the same moment in both, and the shorter one is padded with
+/// silence to the same length
+pub fn align(a: &[u8], b: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let mut decoded = [Pcm::read(a)?, Pcm::read(b)?];
+    for audio in &mut decoded {
+        let lead_in = (f64::from(audio.spec.sample_rate) * LEAD_IN_SECS) as usize;
+        let cut = audio.speech_frames().0.saturating_sub(lead_in) * audio.channels();
+        audio.samples.drain(..cut);
+    }
+    let longest = decoded.iter().map(|d| d.secs(d.frames())).fold(0.0, f64::max);
+    for audio in &mut decoded {
+        let frames = (longest * f64::from(audio.spec.sample_rate)).round() as usize;
+        audio.samples.resize(frames * audio.channels(), 0.0);
+    }
+    let [a, b] = decoded;
+    Ok((a.write()?, b.write()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `silence` seconds of silence, then a tone of `tone` seconds at `amplitude`
+    fn render(silence: f64, tone: f64, amplitude: f32) -> Vec<u8> {
+        let sample_rate = 8000;
+        let samples = (0..((silence + tone) * f64::from(sample_rate)) as usize)
+            .map(|i| match i as f64 / f64::from(sample_rate) < silence {
+                true => 0.0,
+                false => (i as f32 * 0.3).sin() * amplitude,
+            })
+            .collect();
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        Pcm { spec, samples }.write().unwrap()
+    }
+
+    #[test]
+    fn reports_timing_and_level_differences() {
+        let report = compare(&render(0.2, 1.0, 0.5), &render(0.5, 1.5, 0.25)).unwrap();
+        assert!((report.a.speech_start_secs - 0.2).abs() < 0.02, "{:?}", report.a);
+        assert!((report.speech_difference_secs - 0.5).abs() < 0.03, "{:?}", report);
+        // Half the amplitude is about 6 dB quieter
+        assert!((report.loudness_difference_db + 6.0).abs() < 0.2, "{:?}", report);
+
+        let silent = analyze(&render(1.0, 0.0, 0.0)).unwrap();
+        assert_eq!(silent.speech_secs(), 0.0);
+        assert_eq!(silent.rms_dbfs, -120.0);
+    }
+
+    #[test]
+    fn aligns_speech_onsets_and_lengths() {
+        let (a, b) = align(&render(0.2, 1.0, 0.5), &render(0.6, 1.4, 0.5)).unwrap();
+        let (a, b) = (analyze(&a).unwrap(), analyze(&b).unwrap());
+        assert!((a.speech_start_secs - b.speech_start_secs).abs() < 0.02, "{:?} {:?}", a, b);
+        assert!(a.speech_start_secs <= LEAD_IN_SECS + 0.01);
+        assert_eq!(a.duration_secs, b.duration_secs);
+    }
+}
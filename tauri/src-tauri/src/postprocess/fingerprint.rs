@@ -0,0 +1,217 @@
+use super::pcm::{resample, Pcm};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Rate the acoustic fingerprint is computed at, so files at any rate compare
+const ANALYSIS_RATE: u32 = 16_000;
+/// 128 ms analysis frames every 32 ms
+const FRAME_LEN: usize = 2048;
+const HOP_LEN: usize = 512;
+/// The 33 bands (32 bits per frame) span where speech energy is most stable across
+/// encoders and resampling
+const LOW_HZ: f32 = 300.0;
+const HIGH_HZ: f32 = 2000.0;
+const BANDS: usize = 33;
+/// How far apart (in frames, about a second) two takes may start and still be compared
+const MAX_OFFSET_FRAMES: usize = 32;
+/// Shortest overlap, in frames, that says anything about two recordings
+const MIN_OVERLAP_FRAMES: usize = 31;
+/// Share of matching fingerprint bits above which two recordings are the same audio.
+/// Unrelated audio hovers around 0.5.
+pub const MATCH_SIMILARITY: f32 = 0.7;
+
+/// Identifies a recording both byte for byte and by how it sounds, so the same take
+/// is recognized after it has been resampled, re-encoded or had its gain changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// Hex SHA-256 of the file
+    pub sha256: String,
+    /// One 32-bit sub-fingerprint per frame; `None` for files that aren't WAV
+    pub acoustic: Option<Vec<u32>>,
+}
+
+impl Fingerprint {
+    pub fn of(audio: &[u8]) -> Self {
+        Self {
+            sha256: hex::encode(Sha256::digest(audio)),
+            acoustic: Pcm::read(audio).ok().map(|pcm| acoustic(&pcm)),
+        }
+    }
+
+    /// Share of matching bits at the best alignment of the two recordings, or `None`
+    /// when either has no acoustic fingerprint or they overlap too little to tell
+    pub fn similarity(&self, other: &Fingerprint) -> Option<f32> {
+        let (a, b) = (self.acoustic.as_deref()?, other.acoustic.as_deref()?);
+        let mut best: Option<f32> = None;
+        for shift in 0..=MAX_OFFSET_FRAMES {
+            for (x, y) in [(a, b), (b, a)] {
+                let x = x.get(shift..).unwrap_or_default();
+                let overlap = x.len().min(y.len());
+                if overlap < MIN_OVERLAP_FRAMES {
+                    continue;
+                }
+                let errors: u32 = x.iter().zip(y).map(|(x, y)| (x ^ y).count_ones()).sum();
+                let similarity = 1.0 - errors as f32 / (overlap as f32 * 32.0);
+                best = Some(best.map_or(similarity, |best| best.max(similarity)));
+            }
+        }
+        best
+    }
+
+    pub fn matches(&self, other: &Fingerprint) -> bool {
+        self.sha256 == other.sha256 || self.similarity(other).is_some_and(|s| s >= MATCH_SIMILARITY)
+    }
+}
+
+/// In-place radix-2 FFT of `re`/`im`, whose length must be a power of two
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -std::f32::consts::TAU / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_re, w_im) = ((angle * k as f32).cos(), (angle * k as f32).sin());
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Mono speech at the analysis rate, smoothed first so resampling doesn't fold high
+/// frequencies into the analysed bands
+fn prepare(pcm: &Pcm) -> Vec<f32> {
+    let channels = pcm.channels().max(1);
+    let mut mono: Vec<f32> = pcm.samples.chunks(channels).map(|f| f.iter().sum::<f32>() / channels as f32).collect();
+    let width = (pcm.spec.sample_rate / ANALYSIS_RATE).max(1) as usize;
+    if width > 1 {
+        let mut sum = 0.0;
+        let smoothed = (0..mono.len())
+            .map(|i| {
+                sum += mono[i];
+                if i >= width {
+                    sum -= mono[i - width];
+                }
+                sum / width.min(i + 1) as f32
+            })
+            .collect();
+        mono = smoothed;
+    }
+    let mono = resample(&mono, 1, pcm.spec.sample_rate, ANALYSIS_RATE);
+    let spec = hound::WavSpec { channels: 1, sample_rate: ANALYSIS_RATE, ..pcm.spec };
+    let speech = Pcm { spec, samples: mono };
+    let (start, end) = speech.speech_frames();
+    speech.samples[start..end].to_vec()
+}
+
+/// Band-energy differences over time and frequency, after Haitsma and Kalker. Their
+/// signs survive gain changes and most re-encoding.
+fn acoustic(pcm: &Pcm) -> Vec<u32> {
+    let samples = prepare(pcm);
+    let hz_per_bin = ANALYSIS_RATE as f32 / FRAME_LEN as f32;
+    let edges: Vec<usize> = (0..=BANDS)
+        .map(|b| (LOW_HZ * (HIGH_HZ / LOW_HZ).powf(b as f32 / BANDS as f32) / hz_per_bin).round() as usize)
+        .collect();
+    let window: Vec<f32> = (0..FRAME_LEN)
+        .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / FRAME_LEN as f32).cos())
+        .collect();
+
+    let mut previous: Option<Vec<f32>> = None;
+    let mut bits = Vec::new();
+    let frames = samples.len().saturating_sub(FRAME_LEN) / HOP_LEN + usize::from(samples.len() >= FRAME_LEN);
+    for frame in 0..frames {
+        let chunk = &samples[frame * HOP_LEN..frame * HOP_LEN + FRAME_LEN];
+        let mut re: Vec<f32> = chunk.iter().zip(&window).map(|(s, w)| s * w).collect();
+        let mut im = vec![0.0; FRAME_LEN];
+        fft(&mut re, &mut im);
+        let energies: Vec<f32> = edges
+            .windows(2)
+            .map(|edge| (edge[0]..edge[1].max(edge[0] + 1)).map(|k| re[k] * re[k] + im[k] * im[k]).sum())
+            .collect();
+        if let Some(previous) = &previous {
+            let word = (0..BANDS - 1).fold(0u32, |word, m| {
+                let now = energies[m] - energies[m + 1];
+                let before = previous[m] - previous[m + 1];
+                word << 1 | u32::from(now - before > 0.0)
+            });
+            bits.push(word);
+        }
+        previous = Some(energies);
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A few seconds of tones over noise that hop around like syllables, from a fixed seed
+    fn take(seed: u64, sample_rate: u32, channels: u16) -> Pcm {
+        let mut state = seed;
+        let mut next = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as f32 / (1u64 << 31) as f32
+        };
+        let syllable = (sample_rate / 12) as usize;
+        let mut samples = Vec::new();
+        for _ in 0..48 {
+            let tones: Vec<(f32, f32)> = (0..3).map(|_| (250.0 + next() * 1900.0, 0.1 + next() * 0.2)).collect();
+            for _ in 0..syllable {
+                let t = (samples.len() / usize::from(channels)) as f32 / sample_rate as f32;
+                let noise = (next() - 0.5) * 0.1;
+                let s: f32 = noise + tones.iter().map(|(f, a)| (t * f * std::f32::consts::TAU).sin() * a).sum::<f32>();
+                samples.extend(std::iter::repeat_n(s, usize::from(channels)));
+            }
+        }
+        let spec = hound::WavSpec { channels, sample_rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        Pcm { spec, samples }
+    }
+
+    #[test]
+    fn recognizes_the_same_take_after_resampling() {
+        let original = take(1, 44100, 2);
+        let wav = original.write().unwrap();
+        // What the backend stores: mono, 24 kHz, quieter
+        let mono: Vec<f32> = original.samples.chunks(2).map(|f| f[0] * 0.5).collect();
+        let spec = hound::WavSpec { channels: 1, sample_rate: 24000, ..original.spec };
+        let stored = Pcm { spec, samples: resample(&mono, 1, 44100, 24000) }.write().unwrap();
+
+        let (a, b) = (Fingerprint::of(&wav), Fingerprint::of(&stored));
+        assert_ne!(a.sha256, b.sha256);
+        assert!(a.similarity(&b).unwrap() > 0.85, "{:?}", a.similarity(&b));
+        assert!(a.matches(&b));
+
+        let other = Fingerprint::of(&take(2, 24000, 1).write().unwrap());
+        assert!(other.similarity(&b).unwrap() < 0.6, "{:?}", other.similarity(&b));
+        assert!(!other.matches(&b));
+    }
+
+    #[test]
+    fn falls_back_to_the_file_hash() {
+        let a = Fingerprint::of(b"ID3 not a wav");
+        assert_eq!(a.acoustic, None);
+        assert_eq!(a.similarity(&a), None);
+        assert!(a.matches(&Fingerprint::of(b"ID3 not a wav")));
+        assert!(!a.matches(&Fingerprint::of(b"ID3 another")));
+    }
+}
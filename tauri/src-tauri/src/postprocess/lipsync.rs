@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+
+/// When a word is spoken, in seconds from the start of the audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Word timings of a generation as the backend's `/history/{id}/timing` returns them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationTiming {
+    pub generation_id: String,
+    pub text: String,
+    pub duration: f64,
+    pub words: Vec<WordTiming>,
+}
+
+/// Mouth shapes as Rhubarb Lip Sync names them, after Preston Blair's chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MouthShape {
+    /// Closed: M, B, P
+    A,
+    /// Slightly open with clenched teeth: most consonants and EE
+    B,
+    /// Open: EH, AE
+    C,
+    /// Wide open: AA
+    D,
+    /// Slightly rounded: AO, ER
+    E,
+    /// Puckered: UW, OW, W
+    F,
+    /// Upper teeth on the lower lip: F, V
+    G,
+    /// Tongue raised: L
+    H,
+    /// At rest, between words
+    X,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cue<T> {
+    pub start: f64,
+    pub end: f64,
+    pub value: T,
+}
+
+/// Phoneme and mouth shape tracks of one audio file, times rounded to milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LipSyncTrack {
+    pub duration: f64,
+    pub words: Vec<WordTiming>,
+    /// ARPAbet phonemes
+    pub phonemes: Vec<Cue<&'static str>>,
+    /// Covers the whole duration, `X` wherever nothing is said
+    pub visemes: Vec<Cue<MouthShape>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LipSyncFormat {
+    /// Words, phonemes and mouth shapes with millisecond times
+    Json,
+    /// Rhubarb's `--exportFormat json`, read by its Unity, Godot and Spine importers
+    RhubarbJson,
+    /// Rhubarb's default tab-separated output
+    RhubarbTsv,
+}
+
+impl LipSyncFormat {
+    /// Added to the audio file's stem, so `line.wav` gets `line.rhubarb.tsv`
+    pub fn extension(self) -> &'static str {
+        match self {
+            LipSyncFormat::Json => "lipsync.json",
+            LipSyncFormat::RhubarbJson => "rhubarb.json",
+            LipSyncFormat::RhubarbTsv => "rhubarb.tsv",
+        }
+    }
+
+    pub fn render(self, track: &LipSyncTrack, sound_file: &str) -> String {
+        match self {
+            LipSyncFormat::Json => serde_json::to_string_pretty(track).unwrap_or_default(),
+            LipSyncFormat::RhubarbJson => {
+                let cues: Vec<_> = track
+                    .visemes
+                    .iter()
+                    .map(|cue| Cue { start: centis(cue.start), end: centis(cue.end), value: cue.value })
+                    .collect();
+                let rhubarb = serde_json::json!({
+                    "metadata": { "soundFile": sound_file, "duration": centis(track.duration) },
+                    "mouthCues": cues,
+                });
+                serde_json::to_string_pretty(&rhubarb).unwrap_or_default()
+            }
+            LipSyncFormat::RhubarbTsv => {
+                let mut tsv: String = track
+                    .visemes
+                    .iter()
+                    .map(|cue| format!("{:.2}\t{:?}\n", cue.start, cue.value))
+                    .collect();
+                // Rhubarb ends with the rest shape at the end of the audio
+                tsv.push_str(&format!("{:.2}\tX\n", track.duration));
+                tsv
+            }
+        }
+    }
+}
+
+fn millis(secs: f64) -> f64 {
+    (secs * 1000.0).round() / 1000.0
+}
+
+fn centis(secs: f64) -> f64 {
+    (secs * 100.0).round() / 100.0
+}
+
+/// Spread each word's phonemes over the time it is spoken, vowels held twice as long
+/// as consonants, and derive the mouth shapes from them
+pub fn build(timing: &GenerationTiming) -> LipSyncTrack {
+    let duration = millis(timing.duration);
+    let mut phonemes = Vec::new();
+    for word in &timing.words {
+        let sounds = phonemes_of(&word.word);
+        let weights: Vec<f64> = sounds.iter().map(|p| if is_vowel(p) { 2.0 } else { 1.0 }).collect();
+        let per_weight = (word.end - word.start).max(0.0) / weights.iter().sum::<f64>();
+        let mut at = word.start;
+        for (phoneme, weight) in sounds.into_iter().zip(weights) {
+            let end = at + weight * per_weight;
+            phonemes.push(Cue { start: millis(at), end: millis(end), value: phoneme });
+            at = end;
+        }
+    }
+
+    let mut visemes: Vec<Cue<MouthShape>> = Vec::new();
+    let mut push = |start: f64, end: f64, value: MouthShape| {
+        if end <= start {
+            return;
+        }
+        match visemes.last_mut() {
+            Some(last) if last.value == value => last.end = end,
+            _ => visemes.push(Cue { start, end, value }),
+        }
+    };
+    let mut at = 0.0;
+    for cue in &phonemes {
+        push(at, cue.start, MouthShape::X);
+        push(cue.start.max(at), cue.end, shape_of(cue.value));
+        at = at.max(cue.end);
+    }
+    push(at, duration, MouthShape::X);
+
+    LipSyncTrack { duration, words: timing.words.clone(), phonemes, visemes }
+}
+
+/// Letter groups read as one sound, longest first
+const GRAPHEMES: &[(&str, &[&str])] = &[
+    ("tch", &["CH"]),
+    ("igh", &["AY"]),
+    ("sh", &["SH"]),
+    ("ch", &["CH"]),
+    ("th", &["TH"]),
+    ("ph", &["F"]),
+    ("ng", &["NG"]),
+    ("ck", &["K"]),
+    ("qu", &["K", "W"]),
+    ("wh", &["W"]),
+    ("ee", &["IY"]),
+    ("ea", &["IY"]),
+    ("oo", &["UW"]),
+    ("ou", &["AW"]),
+    ("ow", &["OW"]),
+    ("oi", &["OY"]),
+    ("oy", &["OY"]),
+    ("ai", &["EY"]),
+    ("ay", &["EY"]),
+    ("au", &["AO"]),
+    ("aw", &["AO"]),
+    ("er", &["ER"]),
+    ("ir", &["ER"]),
+    ("ur", &["ER"]),
+    ("a", &["AE"]),
+    ("b", &["B"]),
+    ("c", &["K"]),
+    ("d", &["D"]),
+    ("e", &["EH"]),
+    ("f", &["F"]),
+    ("g", &["G"]),
+    ("h", &["HH"]),
+    ("i", &["IH"]),
+    ("j", &["JH"]),
+    ("k", &["K"]),
+    ("l", &["L"]),
+    ("m", &["M"]),
+    ("n", &["N"]),
+    ("o", &["AA"]),
+    ("p", &["P"]),
+    ("q", &["K"]),
+    ("r", &["R"]),
+    ("s", &["S"]),
+    ("t", &["T"]),
+    ("u", &["AH"]),
+    ("v", &["V"]),
+    ("w", &["W"]),
+    ("x", &["K", "S"]),
+    ("y", &["Y"]),
+    ("z", &["Z"]),
+];
+
+/// Rough English spelling-to-sound rules; good enough for mouth shapes, which many
+/// phonemes share, without a pronunciation dictionary
+fn phonemes_of(word: &str) -> Vec<&'static str> {
+    let word: String = word.to_lowercase().chars().filter(|c| c.is_ascii_alphabetic()).collect();
+    // A final silent e, as in "make"
+    let spoken = match word.strip_suffix('e') {
+        Some(stem) if stem.len() > 1 && !stem.ends_with('e') => stem,
+        _ => word.as_str(),
+    };
+    let mut sounds: Vec<&'static str> = Vec::new();
+    let mut rest = spoken;
+    while !rest.is_empty() {
+        let (letters, group) = GRAPHEMES.iter().find(|(letters, _)| rest.starts_with(letters)).unwrap();
+        let group: &[&'static str] = match (*letters, rest[letters.len()..].chars().next()) {
+            ("c", Some('e' | 'i' | 'y')) => &["S"],
+            ("y", None) if !sounds.is_empty() => &["IY"],
+            _ => group,
+        };
+        for &sound in group {
+            // Doubled consonants are said once
+            if sounds.last() != Some(&sound) || is_vowel(sound) {
+                sounds.push(sound);
+            }
+        }
+        rest = &rest[letters.len()..];
+    }
+    if sounds.is_empty() {
+        // Numbers and words in other scripts still move the mouth
+        sounds.push("AH");
+    }
+    sounds
+}
+
+fn is_vowel(phoneme: &str) -> bool {
+    matches!(phoneme, "AA" | "AE" | "AH" | "AO" | "AW" | "AY" | "EH" | "ER" | "EY" | "IH" | "IY" | "OW" | "OY" | "UH" | "UW")
+}
+
+fn shape_of(phoneme: &str) -> MouthShape {
+    match phoneme {
+        "M" | "B" | "P" => MouthShape::A,
+        "AA" | "AH" | "AY" | "AW" => MouthShape::D,
+        "AE" | "EH" | "EY" | "HH" => MouthShape::C,
+        "AO" | "ER" => MouthShape::E,
+        "OW" | "OY" | "UH" | "UW" | "W" => MouthShape::F,
+        "F" | "V" => MouthShape::G,
+        "L" => MouthShape::H,
+        _ => MouthShape::B,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing() -> GenerationTiming {
+        GenerationTiming {
+            generation_id: "g1".to_string(),
+            text: "Move faster".to_string(),
+            duration: 1.5,
+            words: vec![
+                WordTiming { word: "Move".to_string(), start: 0.2, end: 0.6 },
+                WordTiming { word: "faster.".to_string(), start: 0.8, end: 1.3 },
+            ],
+        }
+    }
+
+    #[test]
+    fn mouth_shapes_cover_the_whole_audio() {
+        assert_eq!(phonemes_of("Move"), ["M", "AA", "V"]);
+        assert_eq!(phonemes_of("faster."), ["F", "AE", "S", "T", "ER"]);
+        assert_eq!(phonemes_of("city"), ["S", "IH", "T", "IY"]);
+
+        let track = build(&timing());
+        assert_eq!(track.phonemes.len(), 8);
+        assert_eq!(track.phonemes[0], Cue { start: 0.2, end: 0.3, value: "M" });
+        let shapes: Vec<_> = track.visemes.iter().map(|cue| cue.value).collect();
+        use MouthShape::*;
+        assert_eq!(shapes, [X, A, D, G, X, G, C, B, E, X]);
+        assert_eq!((track.visemes[0].start, track.visemes.last().unwrap().end), (0.0, 1.5));
+        assert!(track.visemes.windows(2).all(|pair| pair[0].end == pair[1].start));
+    }
+
+    #[test]
+    fn writes_rhubarb_formats() {
+        let track = build(&timing());
+        let tsv = LipSyncFormat::RhubarbTsv.render(&track, "line.wav");
+        assert!(tsv.starts_with("0.00\tX\n0.20\tA\n0.30\tD\n"));
+        assert!(tsv.ends_with("1.30\tX\n1.50\tX\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&LipSyncFormat::RhubarbJson.render(&track, "line.wav")).unwrap();
+        assert_eq!(json["metadata"]["soundFile"], "line.wav");
+        assert_eq!(json["mouthCues"][1], serde_json::json!({ "start": 0.2, "end": 0.3, "value": "A" }));
+    }
+}
@@ -0,0 +1,28 @@
+pub mod compare;
+pub mod fingerprint;
+pub mod lipsync;
+pub mod opus;
+pub mod pcm;
+mod riff;
+pub mod snapshot;
+pub mod watermark;
+
+use ed25519_dalek::SigningKey;
+
+/// Preset `post_processing` step that watermarks generated audio
+pub const WATERMARK: &str = "watermark";
+
+/// What post-processing steps may need about the audio they run on
+pub struct StepContext<'a> {
+    pub signing_key: &'a SigningKey,
+    pub generation_id: Option<&'a str>,
+}
+
+/// Run the `post_processing` steps of a preset that are implemented here on a WAV file,
+/// in order. Other steps are left to the app.
+pub fn apply(steps: &[String], wav: Vec<u8>, context: &StepContext) -> Result<Vec<u8>, String> {
+    steps.iter().try_fold(wav, |wav, step| match step.as_str() {
+        WATERMARK => watermark::embed(&wav, context.signing_key, context.generation_id),
+        _ => Ok(wav),
+    })
+}
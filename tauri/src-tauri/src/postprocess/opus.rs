@@ -0,0 +1,153 @@
+use super::pcm::{resample, Pcm};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use opus_rs::{Application, OpusEncoder};
+
+/// Bitrate of library previews; plenty for speech
+pub const PREVIEW_BITRATE_BPS: i32 = 32_000;
+/// Input rates the encoder accepts; anything else is resampled to 48 kHz
+const OPUS_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+/// Ogg Opus granule positions always count 48 kHz samples
+const GRANULE_RATE: u64 = 48_000;
+/// Encoder lookahead at 48 kHz (2.5 ms + 4 ms delay compensation), which players skip
+const PRE_SKIP: u64 = 312;
+const FRAME_MS: u32 = 20;
+/// Previews hold a single logical stream, so any serial number will do
+const STREAM_SERIAL: u32 = 0x766f_6278;
+/// Largest packet the encoder can produce for one frame
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// Mix files with more than two channels down to mono, which Opus mapping family 0
+/// doesn't cover
+fn downmix(pcm: &Pcm) -> (Vec<f32>, usize) {
+    let channels = pcm.channels();
+    if channels <= 2 {
+        return (pcm.samples.clone(), channels);
+    }
+    let mono = pcm.samples.chunks(channels).map(|frame| frame.iter().sum::<f32>() / channels as f32).collect();
+    (mono, 1)
+}
+
+fn header(input_rate: u32, channels: usize) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1);
+    head.push(channels as u8);
+    head.extend_from_slice(&(PRE_SKIP as u16).to_le_bytes());
+    head.extend_from_slice(&input_rate.to_le_bytes());
+    // Output gain, then mapping family 0 (mono or stereo)
+    head.extend_from_slice(&0i16.to_le_bytes());
+    head.push(0);
+    head
+}
+
+fn tags() -> Vec<u8> {
+    let vendor = concat!("Voicebox ", env!("CARGO_PKG_VERSION"));
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
+
+/// Encode a WAV file as Ogg Opus at `bitrate_bps`, for small previews of large masters
+pub fn encode_ogg_opus(wav: &[u8], bitrate_bps: i32) -> Result<Vec<u8>, String> {
+    let pcm = Pcm::read(wav)?;
+    let input_rate = pcm.spec.sample_rate;
+    let (mut samples, channels) = downmix(&pcm);
+    let rate = match OPUS_RATES.contains(&input_rate) {
+        true => input_rate,
+        false => {
+            samples = resample(&samples, channels, input_rate, GRANULE_RATE as u32);
+            GRANULE_RATE as u32
+        }
+    };
+    let failed = |e: &str| format!("Opus encoding failed: {}", e);
+    let mut encoder = OpusEncoder::new(rate as i32, channels, Application::Audio).map_err(failed)?;
+    encoder.bitrate_bps = bitrate_bps;
+
+    let frames = (samples.len() / channels) as u64;
+    let granule_scale = GRANULE_RATE / u64::from(rate);
+    let frame_size = (rate * FRAME_MS / 1000) as usize;
+    // Encode past the end by the lookahead so the last samples come out of the encoder
+    let total = frames + PRE_SKIP / granule_scale;
+    let packets = total.div_ceil(frame_size as u64).max(1);
+    samples.resize(packets as usize * frame_size * channels, 0.0);
+    let end_granule = PRE_SKIP + frames * granule_scale;
+
+    let mut out = Vec::new();
+    let mut writer = PacketWriter::new(&mut out);
+    let write_failed = |e: std::io::Error| format!("Failed to write Ogg data: {}", e);
+    writer
+        .write_packet(header(input_rate, channels), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(write_failed)?;
+    writer.write_packet(tags(), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0).map_err(write_failed)?;
+
+    let mut packet = vec![0u8; MAX_PACKET_BYTES];
+    for (index, frame) in samples.chunks_exact(frame_size * channels).enumerate() {
+        let length = encoder.encode(frame, frame_size, &mut packet).map_err(failed)?;
+        let last = index as u64 + 1 == packets;
+        let granule = ((index as u64 + 1) * frame_size as u64 * granule_scale).min(end_granule);
+        let end = if last { PacketWriteEndInfo::EndStream } else { PacketWriteEndInfo::NormalPacket };
+        writer.write_packet(packet[..length].to_vec(), STREAM_SERIAL, end, granule).map_err(write_failed)?;
+    }
+    drop(writer);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ogg::reading::PacketReader;
+    use std::io::Cursor;
+
+    fn speechlike(sample_rate: u32, channels: u16, secs: f32) -> Vec<u8> {
+        let frames = (sample_rate as f32 * secs) as usize;
+        let samples = (0..frames * usize::from(channels))
+            .map(|i| {
+                let t = (i / usize::from(channels)) as f32 / sample_rate as f32;
+                (t * 220.0 * std::f32::consts::TAU).sin() * 0.4 * (t * 3.0 * std::f32::consts::TAU).sin().abs()
+            })
+            .collect();
+        let spec = hound::WavSpec { channels, sample_rate, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        Pcm { spec, samples }.write().unwrap()
+    }
+
+    fn packets(ogg: &[u8]) -> Vec<ogg::Packet> {
+        let mut reader = PacketReader::new(Cursor::new(ogg));
+        std::iter::from_fn(|| reader.read_packet().unwrap()).collect()
+    }
+
+    #[test]
+    fn writes_a_valid_ogg_opus_stream() {
+        let wav = speechlike(24000, 1, 2.0);
+        let ogg = encode_ogg_opus(&wav, PREVIEW_BITRATE_BPS).unwrap();
+        assert!(ogg.len() * 5 < wav.len(), "{} vs {}", ogg.len(), wav.len());
+
+        let packets = packets(&ogg);
+        assert_eq!(&packets[0].data[..8], b"OpusHead");
+        assert_eq!(u32::from_le_bytes(packets[0].data[12..16].try_into().unwrap()), 24000);
+        assert_eq!(&packets[1].data[..8], b"OpusTags");
+        let last = packets.last().unwrap();
+        assert!(last.last_in_stream());
+        // Two seconds at 48 kHz after the pre-skip
+        assert_eq!(last.absgp_page(), PRE_SKIP + 96_000);
+
+        let mut decoder = opus_rs::OpusDecoder::new(24000, 1).unwrap();
+        let mut decoded = vec![0f32; 480];
+        let mut energy = 0f32;
+        for packet in &packets[2..] {
+            let n = decoder.decode(&packet.data, 480, &mut decoded).unwrap();
+            energy += decoded[..n].iter().map(|s| s * s).sum::<f32>();
+        }
+        assert!(energy > 100.0, "decoded audio is silent: {}", energy);
+    }
+
+    #[test]
+    fn resamples_rates_opus_does_not_support() {
+        let ogg = encode_ogg_opus(&speechlike(44100, 2, 0.5), PREVIEW_BITRATE_BPS).unwrap();
+        let packets = packets(&ogg);
+        assert_eq!(packets[0].data[9], 2);
+        assert_eq!(u32::from_le_bytes(packets[0].data[12..16].try_into().unwrap()), 44100);
+        assert_eq!(packets.last().unwrap().absgp_page(), PRE_SKIP + 24_000);
+        assert!(encode_ogg_opus(b"not a wav", PREVIEW_BITRATE_BPS).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// Windows quieter than this (RMS, dBFS) count as silence when finding where speech
+/// starts and ends
+const SILENCE_DBFS: f64 = -45.0;
+const WINDOW_SECS: f64 = 0.01;
+/// Level and timing of one rendering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioStats {
+    pub duration_secs: f64,
+    /// Where speech starts and ends, ignoring leading and trailing silence
+    pub speech_start_secs: f64,
+    pub speech_end_secs: f64,
+    /// RMS level of the speech, in dBFS
+    pub rms_dbfs: f64,
+    pub peak_dbfs: f64,
+}
+
+impl AudioStats {
+    pub fn speech_secs(&self) -> f64 {
+        self.speech_end_secs - self.speech_start_secs
+    }
+}
+
+/// Decoded PCM audio
+pub struct Pcm {
+    pub spec: hound::WavSpec,
+    /// Interleaved samples scaled to -1.0..=1.0
+    pub samples: Vec<f32>,
+}
+
+impl Pcm {
+    pub fn read(wav: &[u8]) -> Result<Self, String> {
+        let mut reader = hound::WavReader::new(Cursor::new(wav)).map_err(|e| format!("Invalid WAV file: {}", e))?;
+        let spec = reader.spec();
+        let samples: Result<Vec<f32>, _> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().collect(),
+            hound::SampleFormat::Int => {
+                let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                reader.samples::<i32>().map(|s| s.map(|s| s as f32 / scale)).collect()
+            }
+        };
+        let samples = samples.map_err(|e| format!("Invalid WAV file: {}", e))?;
+        Ok(Self { spec, samples })
+    }
+
+    pub fn write(&self) -> Result<Vec<u8>, String> {
+        let mut cursor = Cursor::new(Vec::new());
+        let failed = |e: hound::Error| format!("Failed to write WAV data: {}", e);
+        let mut writer = hound::WavWriter::new(&mut cursor, self.spec).map_err(failed)?;
+        let scale = (1_i64 << (self.spec.bits_per_sample - 1)) as f32;
+        for &sample in &self.samples {
+            match self.spec.sample_format {
+                hound::SampleFormat::Float => writer.write_sample(sample),
+                hound::SampleFormat::Int => {
+                    writer.write_sample((sample * scale).round().clamp(-scale, scale - 1.0) as i32)
+                }
+            }
+            .map_err(failed)?;
+        }
+        writer.finalize().map_err(failed)?;
+        Ok(cursor.into_inner())
+    }
+
+    pub fn channels(&self) -> usize {
+        usize::from(self.spec.channels.max(1))
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels()
+    }
+
+    pub fn secs(&self, frames: usize) -> f64 {
+        frames as f64 / f64::from(self.spec.sample_rate)
+    }
+
+    /// Frame range from the first to the end of the last window above the silence
+    /// threshold; empty at the start if the whole file is silent
+    pub fn speech_frames(&self) -> (usize, usize) {
+        let window = ((f64::from(self.spec.sample_rate) * WINDOW_SECS) as usize).max(1) * self.channels();
+        let threshold = 10f64.powf(SILENCE_DBFS / 20.0);
+        let loud: Vec<bool> = self.samples.chunks(window).map(|w| rms(w) > threshold).collect();
+        let frames_per_window = window / self.channels();
+        match (loud.iter().position(|&l| l), loud.iter().rposition(|&l| l)) {
+            (Some(first), Some(last)) => {
+                (first * frames_per_window, ((last + 1) * frames_per_window).min(self.frames()))
+            }
+            _ => (0, 0),
+        }
+    }
+
+    pub fn stats(&self) -> AudioStats {
+        let (start, end) = self.speech_frames();
+        let speech = &self.samples[start * self.channels()..end * self.channels()];
+        let peak = self.samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+        AudioStats {
+            duration_secs: self.secs(self.frames()),
+            speech_start_secs: self.secs(start),
+            speech_end_secs: self.secs(end),
+            rms_dbfs: dbfs(rms(speech)),
+            peak_dbfs: dbfs(f64::from(peak)),
+        }
+    }
+}
+
+pub fn rms(samples: &[f32]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum::<f64>() / samples.len() as f64).sqrt()
+}
+
+/// Level in dBFS, floored at -120 for digital silence
+pub fn dbfs(level: f64) -> f64 {
+    (20.0 * level.log10()).max(-120.0)
+}
+
+/// Interleaved samples converted from rate `from` to `to` by linear interpolation
+pub fn resample(samples: &[f32], channels: usize, from: u32, to: u32) -> Vec<f32> {
+    let frames = samples.len() / channels;
+    let out_frames = (frames as u64 * u64::from(to) / u64::from(from)) as usize;
+    let mut out = Vec::with_capacity(out_frames * channels);
+    for frame in 0..out_frames {
+        let position = frame as f64 * f64::from(from) / f64::from(to);
+        let (index, fraction) = (position as usize, position.fract() as f32);
+        for channel in 0..channels {
+            let at = |i: usize| samples.get(i.min(frames.saturating_sub(1)) * channels + channel).copied();
+            let (a, b) = (at(index).unwrap_or(0.0), at(index + 1).unwrap_or(0.0));
+            out.push(a + (b - a) * fraction);
+        }
+    }
+    out
+}
+
+/// Level and timing of the speech in a WAV file
+pub fn analyze(wav: &[u8]) -> Result<AudioStats, String> {
+    Ok(Pcm::read(wav)?.stats())
+}
@@ -0,0 +1,71 @@
+/// A RIFF/WAVE file split into its chunks
+pub struct Wav<'a> {
+    pub chunks: Vec<([u8; 4], &'a [u8])>,
+}
+
+impl<'a> Wav<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err("Not a WAV file".to_string());
+        }
+        let mut chunks = Vec::new();
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id: [u8; 4] = bytes[pos..pos + 4].try_into().unwrap_or_default();
+            let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap_or_default()) as usize;
+            let start = pos + 8;
+            let end = start.checked_add(size).filter(|&end| end <= bytes.len());
+            let Some(end) = end else {
+                return Err(format!("Truncated '{}' chunk in WAV file", String::from_utf8_lossy(&id)));
+            };
+            chunks.push((id, &bytes[start..end]));
+            // Chunks are padded to an even length
+            pos = end + (size & 1);
+        }
+        if !chunks.iter().any(|(id, _)| id == b"fmt ") || !chunks.iter().any(|(id, _)| id == b"data") {
+            return Err("WAV file has no fmt or data chunk".to_string());
+        }
+        Ok(Self { chunks })
+    }
+
+    pub fn chunk(&self, id: &[u8; 4]) -> Option<&'a [u8]> {
+        self.chunks.iter().find(|(chunk_id, _)| chunk_id == id).map(|(_, data)| *data)
+    }
+
+    /// The file with chunk `id` replaced by `data`, or `data` appended if there was none
+    pub fn with_chunk(&self, id: [u8; 4], data: &[u8]) -> Vec<u8> {
+        let chunks: Vec<([u8; 4], &[u8])> = self
+            .chunks
+            .iter()
+            .filter(|(chunk_id, _)| *chunk_id != id)
+            .copied()
+            .chain([(id, data)])
+            .collect();
+        write_riff(&chunks)
+    }
+
+    /// 16-bit integer PCM, the only format the sample mark is written into
+    pub fn is_pcm16(&self) -> bool {
+        self.chunk(b"fmt ").is_some_and(|fmt| {
+            fmt.len() >= 16
+                && matches!(u16::from_le_bytes([fmt[0], fmt[1]]), 1 | 0xFFFE)
+                && u16::from_le_bytes([fmt[14], fmt[15]]) == 16
+        })
+    }
+}
+
+pub fn write_riff(chunks: &[([u8; 4], &[u8])]) -> Vec<u8> {
+    let mut body = b"WAVE".to_vec();
+    for (id, data) in chunks {
+        body.extend_from_slice(id);
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            body.push(0);
+        }
+    }
+    let mut out = b"RIFF".to_vec();
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend(body);
+    out
+}
@@ -0,0 +1,165 @@
+use super::riff::Wav;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// RIFF chunk carrying the generation parameters as JSON
+const CHUNK_ID: [u8; 4] = *b"vbgp";
+/// Bumped when fields change meaning, so old files are re-rendered the way they were made
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Fields of a generation that decide what it sounds like, in `/generate` request form
+const REQUEST_FIELDS: &[&str] = &["profile_id", "text", "language", "seed", "model_size", "instruct"];
+
+/// Everything needed to render a generation again with the same settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationSnapshot {
+    pub version: u32,
+    pub generation_id: Option<String>,
+    pub created_at: String,
+    pub app_version: String,
+    /// Version of the backend that rendered the audio, if known
+    pub backend_version: Option<String>,
+    /// Preset the request was resolved from
+    pub preset: Option<String>,
+    /// Full `/generate` request body, text included
+    pub request: Map<String, Value>,
+    /// Playback speed from the preset, applied after generation
+    pub speed: Option<f32>,
+}
+
+impl GenerationSnapshot {
+    /// Snapshot of `request` as the backend answered it. Values the backend filled in
+    /// (model size, seed) are taken from `generation` so a re-render doesn't depend on
+    /// its defaults.
+    pub fn capture(request: &Map<String, Value>, generation: &Value) -> Self {
+        let mut request = request.clone();
+        for field in REQUEST_FIELDS {
+            let answered = generation.get(*field).filter(|v| !v.is_null());
+            if let (None, Some(value)) = (request.get(*field).filter(|v| !v.is_null()), answered) {
+                request.insert(field.to_string(), value.clone());
+            }
+        }
+        Self {
+            version: SNAPSHOT_VERSION,
+            generation_id: generation.get("id").and_then(Value::as_str).map(str::to_string),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            backend_version: None,
+            preset: None,
+            request,
+            speed: None,
+        }
+    }
+
+    /// Snapshot of a backend history entry (`GET /history/{id}`)
+    pub fn from_history(entry: &Value) -> Self {
+        Self::capture(&Map::new(), entry)
+    }
+
+    /// Whether rendering the request again should give the same audio. Without a seed
+    /// the backend samples differently every time.
+    pub fn is_reproducible(&self) -> bool {
+        self.request.get("seed").is_some_and(|seed| !seed.is_null())
+    }
+
+    /// The `/generate` request body that renders this generation again
+    pub fn rerender_request(&self) -> Result<Map<String, Value>, String> {
+        if self.version > SNAPSHOT_VERSION {
+            return Err(format!(
+                "Generation settings were saved by a newer version (format {}, supported: {})",
+                self.version, SNAPSHOT_VERSION
+            ));
+        }
+        for field in ["profile_id", "text"] {
+            if !self.request.get(field).is_some_and(Value::is_string) {
+                return Err(format!("Generation settings have no {}", field));
+            }
+        }
+        Ok(self.request.clone())
+    }
+}
+
+/// Store `snapshot` in a WAV file, replacing any earlier one. The audio is untouched,
+/// so watermark signatures stay valid.
+pub fn embed(wav: &[u8], snapshot: &GenerationSnapshot) -> Result<Vec<u8>, String> {
+    let parsed = Wav::parse(wav)?;
+    let json = serde_json::to_vec(snapshot).map_err(|e| format!("Failed to serialize generation settings: {}", e))?;
+    Ok(parsed.with_chunk(CHUNK_ID, &json))
+}
+
+/// The generation settings stored in a WAV file, if it has any
+pub fn read(wav: &[u8]) -> Result<Option<GenerationSnapshot>, String> {
+    let parsed = Wav::parse(wav)?;
+    parsed
+        .chunk(&CHUNK_ID)
+        .map(|json| {
+            serde_json::from_slice(json)
+                .map_err(|e| format!("Invalid generation settings in WAV file: {}", e))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postprocess::watermark;
+    use ed25519_dalek::SigningKey;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn wav() -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 24000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for i in 0..2000 {
+            writer.write_sample((i % 300) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        cursor.into_inner()
+    }
+
+    fn request() -> Map<String, Value> {
+        json!({"profile_id": "narrator", "text": "Hello.", "seed": null}).as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn fills_in_what_the_backend_chose() {
+        let generation = json!({"id": "gen-1", "profile_id": "narrator", "text": "Hello.", "language": "en",
+                                "seed": 42, "model_size": "1.7B", "instruct": null, "duration": 1.2});
+        let snapshot = GenerationSnapshot::capture(&request(), &generation);
+        assert_eq!(snapshot.generation_id.as_deref(), Some("gen-1"));
+        assert_eq!(snapshot.request["seed"], json!(42));
+        assert_eq!(snapshot.request["model_size"], json!("1.7B"));
+        assert!(!snapshot.request.contains_key("duration"));
+        assert!(snapshot.is_reproducible());
+        assert_eq!(GenerationSnapshot::from_history(&generation).request, snapshot.request);
+    }
+
+    #[test]
+    fn survives_a_round_trip_next_to_a_watermark() {
+        let key = SigningKey::from_bytes(&[5; 32]);
+        let mut snapshot = GenerationSnapshot::capture(&request(), &json!({"id": "gen-2"}));
+        snapshot.preset = Some("audiobook".to_string());
+        let marked = watermark::embed(&wav(), &key, None).unwrap();
+        let file = embed(&marked, &snapshot).unwrap();
+
+        assert_eq!(read(&file).unwrap(), Some(snapshot.clone()));
+        assert!(watermark::verify(&file).unwrap().signature_valid);
+        assert_eq!(snapshot.rerender_request().unwrap(), request());
+        assert_eq!(read(&wav()).unwrap(), None);
+    }
+
+    #[test]
+    fn refuses_snapshots_it_cannot_rerender() {
+        let mut snapshot = GenerationSnapshot::capture(&Map::new(), &json!({}));
+        assert!(snapshot.rerender_request().unwrap_err().contains("profile_id"));
+        snapshot.request = request();
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        assert!(snapshot.rerender_request().is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::launcher::signing::{fingerprint, fingerprint_bytes};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use super::riff::{write_riff, Wav};
+
+/// RIFF chunk carrying the signed provenance record
+const CHUNK_ID: [u8; 4] = *b"vbox";
+const MARK_MAGIC: [u8; 4] = *b"VBX1";
+/// Magic followed by the signer's key fingerprint
+const MARK_BYTES: usize = MARK_MAGIC.len() + 8;
+const MARK_BITS: usize = MARK_BYTES * 8;
+
+/// What the watermark states about a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub generator: String,
+    pub app_version: String,
+    pub synthetic: bool,
+    pub created_at: String,
+    pub generation_id: Option<String>,
+    /// Hex SHA-256 of the sample data, so edits to the audio break the signature
+    pub audio_sha256: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignedChunk {
+    /// `Provenance` as JSON text, signed byte for byte
+    payload: String,
+    public_key: String,
+    signature: String,
+}
+
+/// Result of checking a file for a watermark
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Verification {
+    /// Signed provenance record, if the file still carries one
+    pub provenance: Option<Provenance>,
+    /// The record's signature is valid and matches the audio as it is now
+    pub signature_valid: bool,
+    /// Fingerprint of the key that signed the record
+    pub signer: Option<String>,
+    /// Fingerprint found in the inaudible mark in the samples, which survives metadata
+    /// being stripped and the file being trimmed
+    pub sample_mark: Option<String>,
+}
+
+impl Verification {
+    /// Whether anything identifies the file as generated by Voicebox
+    pub fn is_synthetic(&self) -> bool {
+        self.signature_valid || self.sample_mark.is_some()
+    }
+}
+
+/// Write `payload` into the least significant bit of every sample, repeating it
+/// through the file. That is below the 16-bit noise floor.
+fn embed_mark(samples: &mut [u8], payload: &[u8; MARK_BYTES]) {
+    for (i, sample) in samples.chunks_exact_mut(2).enumerate() {
+        let bit = (payload[(i % MARK_BITS) / 8] >> (7 - i % 8)) & 1;
+        sample[0] = (sample[0] & !1) | bit;
+    }
+}
+
+/// Fingerprint in the sample mark. Windows further into the file are tried when the
+/// start was edited, and every rotation of the payload is tried so trimmed files still
+/// verify.
+fn read_mark(samples: &[u8]) -> Option<String> {
+    let bits: Vec<u8> = samples.chunks_exact(2).take(MARK_BITS * 32).map(|s| s[0] & 1).collect();
+    let windows = bits.chunks_exact(MARK_BITS).collect::<Vec<_>>();
+    windows.windows(2).filter(|pair| pair[0] == pair[1]).find_map(|pair| {
+        let window = pair[0];
+        (0..MARK_BITS).find_map(|rotation| {
+            let bytes: Vec<u8> = (0..MARK_BYTES)
+                .map(|byte| (0..8).fold(0, |acc, bit| acc << 1 | window[(rotation + byte * 8 + bit) % MARK_BITS]))
+                .collect();
+            (bytes[..MARK_MAGIC.len()] == MARK_MAGIC).then(|| hex::encode(&bytes[MARK_MAGIC.len()..]))
+        })
+    })
+}
+
+/// Mark `wav` as synthetic: an inaudible mark in the samples (16-bit PCM only) and a
+/// signed provenance chunk. Any previous watermark is replaced.
+pub fn embed(wav: &[u8], key: &SigningKey, generation_id: Option<&str>) -> Result<Vec<u8>, String> {
+    let parsed = Wav::parse(wav)?;
+    let mut samples = parsed.chunk(b"data").unwrap_or_default().to_vec();
+    if parsed.is_pcm16() {
+        let mut payload = [0; MARK_BYTES];
+        payload[..MARK_MAGIC.len()].copy_from_slice(&MARK_MAGIC);
+        payload[MARK_MAGIC.len()..].copy_from_slice(&fingerprint_bytes(&key.verifying_key()));
+        embed_mark(&mut samples, &payload);
+    }
+
+    let provenance = Provenance {
+        generator: "Voicebox".to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        synthetic: true,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        generation_id: generation_id.map(str::to_string),
+        audio_sha256: hex::encode(Sha256::digest(&samples)),
+    };
+    let payload = serde_json::to_string(&provenance).map_err(|e| format!("Failed to serialize watermark: {}", e))?;
+    let chunk = serde_json::to_vec(&SignedChunk {
+        signature: hex::encode(key.sign(payload.as_bytes()).to_bytes()),
+        public_key: hex::encode(key.verifying_key().as_bytes()),
+        payload,
+    })
+    .map_err(|e| format!("Failed to serialize watermark: {}", e))?;
+
+    let chunks: Vec<([u8; 4], &[u8])> = parsed
+        .chunks
+        .iter()
+        .filter(|(id, _)| *id != CHUNK_ID)
+        .map(|&(id, data)| (id, if &id == b"data" { samples.as_slice() } else { data }))
+        .chain([(CHUNK_ID, chunk.as_slice())])
+        .collect();
+    Ok(write_riff(&chunks))
+}
+
+fn check_signature(chunk: &[u8], samples: &[u8]) -> Option<(Provenance, bool, String)> {
+    let signed: SignedChunk = serde_json::from_slice(chunk).ok()?;
+    let provenance: Provenance = serde_json::from_str(&signed.payload).ok()?;
+    let key_bytes: [u8; 32] = hex::decode(&signed.public_key).ok()?.try_into().ok()?;
+    let key = VerifyingKey::from_bytes(&key_bytes).ok()?;
+    let signature_bytes: [u8; 64] = hex::decode(&signed.signature).ok()?.try_into().ok()?;
+    let valid = key.verify(signed.payload.as_bytes(), &Signature::from_bytes(&signature_bytes)).is_ok()
+        && provenance.audio_sha256 == hex::encode(Sha256::digest(samples));
+    Some((provenance, valid, fingerprint(&key)))
+}
+
+/// Check `wav` for a Voicebox watermark
+pub fn verify(wav: &[u8]) -> Result<Verification, String> {
+    let parsed = Wav::parse(wav)?;
+    let samples = parsed.chunk(b"data").unwrap_or_default();
+    let mut verification = Verification {
+        sample_mark: if parsed.is_pcm16() { read_mark(samples) } else { None },
+        ..Verification::default()
+    };
+    if let Some((provenance, valid, signer)) = parsed.chunk(&CHUNK_ID).and_then(|c| check_signature(c, samples)) {
+        verification.provenance = Some(provenance);
+        verification.signature_valid = valid;
+        verification.signer = Some(signer);
+    }
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn tone(samples: usize) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 24000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for i in 0..samples {
+            writer.write_sample(((i as f32 * 0.05).sin() * 8000.0) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        cursor.into_inner()
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[3; 32])
+    }
+
+    #[test]
+    fn marked_audio_verifies_and_still_decodes() {
+        let marked = embed(&tone(4000), &key(), Some("gen-1")).unwrap();
+        let verification = verify(&marked).unwrap();
+        let signer = fingerprint(&key().verifying_key());
+        assert!(verification.signature_valid);
+        assert_eq!(verification.signer.as_deref(), Some(signer.as_str()));
+        assert_eq!(verification.sample_mark.as_deref(), Some(signer.as_str()));
+        assert_eq!(verification.provenance.unwrap().generation_id.as_deref(), Some("gen-1"));
+
+        let reader = hound::WavReader::new(Cursor::new(marked)).unwrap();
+        assert_eq!(reader.len(), 4000);
+    }
+
+    #[test]
+    fn edited_audio_fails_the_signature_but_keeps_the_mark() {
+        let marked = embed(&tone(4000), &key(), None).unwrap();
+        let mut edited = marked.clone();
+        let len = edited.len();
+        // Flip the high byte of a sample near the end of the data
+        edited[len - 400] ^= 0x40;
+        let verification = verify(&edited).unwrap();
+        assert!(!verification.signature_valid);
+        assert!(verification.is_synthetic());
+    }
+
+    #[test]
+    fn mark_survives_stripped_metadata_and_trimming() {
+        let marked = embed(&tone(4000), &key(), None).unwrap();
+        let parsed = Wav::parse(&marked).unwrap();
+        let samples = parsed.chunk(b"data").unwrap();
+        // Drop 37 samples from the start and the provenance chunk entirely
+        let chunks: Vec<_> = parsed
+            .chunks
+            .iter()
+            .filter(|(id, _)| *id != CHUNK_ID)
+            .map(|&(id, data)| (id, if &id == b"data" { &samples[74..] } else { data }))
+            .collect();
+        let verification = verify(&write_riff(&chunks)).unwrap();
+        assert_eq!(verification.provenance, None);
+        assert_eq!(verification.sample_mark, Some(fingerprint(&key().verifying_key())));
+    }
+
+    #[test]
+    fn plain_audio_has_no_watermark() {
+        let verification = verify(&tone(4000)).unwrap();
+        assert!(!verification.is_synthetic());
+        assert!(verify(b"not audio").is_err());
+    }
+}
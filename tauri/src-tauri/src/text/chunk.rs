@@ -0,0 +1,273 @@
+use crate::launcher::capabilities::Capabilities;
+use serde::{Deserialize, Serialize};
+
+/// Words ending in a period that don't end a sentence, per language (lowercase)
+const ABBREVIATIONS: &[(&str, &[&str])] = &[
+    ("en", &["mr.", "mrs.", "ms.", "dr.", "prof.", "st.", "jr.", "sr.", "vs.", "etc.", "e.g.", "i.e.", "no.", "fig."]),
+    ("de", &["z.b.", "d.h.", "u.a.", "usw.", "bzw.", "ca.", "dr.", "prof.", "nr.", "str.", "vgl."]),
+    ("fr", &["m.", "mme.", "mlle.", "dr.", "p.ex.", "etc.", "cf."]),
+    ("es", &["sr.", "sra.", "srta.", "dr.", "dra.", "p.ej.", "etc.", "ud.", "uds."]),
+    ("it", &["sig.", "sig.ra", "dott.", "prof.", "ecc.", "es."]),
+    ("pt", &["sr.", "sra.", "dr.", "dra.", "p.ex.", "etc."]),
+    ("ru", &["т.е.", "т.к.", "т.д.", "т.п.", "г.", "ул.", "стр."]),
+];
+
+/// How to split text into chunks the backend accepts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkOptions {
+    /// Upper bound on the length of each chunk, in characters
+    pub max_chars: usize,
+    /// Language code used to pick abbreviation rules
+    pub language: Option<String>,
+}
+
+impl ChunkOptions {
+    /// Chunks as long as the backend allows for a single generation
+    pub fn for_backend(capabilities: &Capabilities, language: Option<&str>) -> Self {
+        Self {
+            max_chars: capabilities.max_text_length,
+            language: language.map(str::to_string),
+        }
+    }
+
+    fn abbreviations(&self) -> &'static [&'static str] {
+        let language = self.language.as_deref().unwrap_or("en");
+        ABBREVIATIONS
+            .iter()
+            .find(|(code, _)| *code == language)
+            .map(|(_, words)| *words)
+            .unwrap_or(&[])
+    }
+}
+
+/// A piece of the input small enough for one generation.
+///
+/// `start..end` is the byte range of `text` in the original input, which is what
+/// reassembly and highlighting key on. Chunks never span paragraphs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub index: usize,
+    pub paragraph: usize,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// `start..end` with surrounding whitespace removed, or `None` if nothing is left
+fn trimmed(text: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    let slice = &text[start..end];
+    let leading = slice.len() - slice.trim_start().len();
+    let trailing = slice.len() - slice.trim_end().len();
+    (leading < slice.len()).then(|| (start + leading, end - trailing))
+}
+
+/// Byte ranges of the paragraphs of `text`, separated by blank lines
+fn paragraphs(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(s) = start.take() {
+                ranges.extend(trimmed(text, s, offset));
+            }
+        } else if start.is_none() {
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(s) = start {
+        ranges.extend(trimmed(text, s, offset));
+    }
+    ranges
+}
+
+/// Full-width terminators end a sentence without trailing whitespace
+fn is_cjk_terminator(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '｡')
+}
+
+fn is_terminator(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…') || is_cjk_terminator(c)
+}
+
+/// Quotes and brackets that belong to the sentence they close
+fn is_closer(c: char) -> bool {
+    matches!(c, '"' | '\'' | ')' | ']' | '»' | '”' | '’' | '」' | '』' | '）')
+}
+
+/// Whether the period at `dot` belongs to an abbreviation or initial
+fn is_abbreviation(text: &str, start: usize, dot: usize, abbreviations: &[&str]) -> bool {
+    let word_start = text[start..dot].rfind(char::is_whitespace).map_or(start, |i| start + i + 1);
+    let word = text[word_start..=dot].trim_start_matches(['(', '"', '\'', '«', '“']).to_lowercase();
+    let mut letters = word.chars();
+    let initial = matches!((letters.next(), letters.next(), letters.next()), (Some(c), Some('.'), None) if c.is_alphabetic());
+    initial || abbreviations.contains(&word.as_str())
+}
+
+/// Byte ranges of the sentences in `text[start..end]`
+fn sentences(text: &str, start: usize, end: usize, abbreviations: &[&str]) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = text[start..end].char_indices().map(|(i, c)| (start + i, c)).collect();
+    let mut ranges = Vec::new();
+    let mut sentence_start = start;
+    let mut i = 0;
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        if !is_terminator(c) {
+            i += 1;
+            continue;
+        }
+        let mut j = i + 1;
+        while j < chars.len() && (is_terminator(chars[j].1) || is_closer(chars[j].1)) {
+            j += 1;
+        }
+        let next = chars.get(j).map(|&(_, c)| c);
+        let boundary = if is_cjk_terminator(c) {
+            true
+        } else if !next.is_none_or(char::is_whitespace) {
+            false
+        } else if c == '.' && j == i + 1 {
+            let next_word = chars[j..].iter().map(|&(_, c)| c).find(|c| !c.is_whitespace());
+            !is_abbreviation(text, sentence_start, pos, abbreviations)
+                && !next_word.is_some_and(char::is_lowercase)
+        } else {
+            true
+        };
+        if boundary {
+            let sentence_end = chars.get(j).map_or(end, |&(p, _)| p);
+            ranges.extend(trimmed(text, sentence_start, sentence_end));
+            sentence_start = sentence_end;
+        }
+        i = j;
+    }
+    ranges.extend(trimmed(text, sentence_start, end));
+    ranges
+}
+
+/// Break a range longer than `max_chars` at clause punctuation, then at whitespace,
+/// and only as a last resort in the middle of a word
+fn split_long(text: &str, start: usize, end: usize, max_chars: usize) -> Vec<(usize, usize)> {
+    let mut pieces = Vec::new();
+    let mut rest = Some((start, end));
+    while let Some((start, end)) = rest {
+        let Some((limit, _)) = text[start..end].char_indices().nth(max_chars) else {
+            pieces.push((start, end));
+            break;
+        };
+        let window = &text[start..start + limit];
+        let clause = window
+            .char_indices()
+            .filter(|&(_, c)| matches!(c, ',' | ';' | ':' | '、' | '，' | '；' | '：'))
+            .map(|(i, c)| i + c.len_utf8())
+            .next_back();
+        let space = window.rfind(char::is_whitespace).filter(|&i| i > 0);
+        let cut = start + clause.or(space).unwrap_or(limit);
+        pieces.extend(trimmed(text, start, cut));
+        rest = trimmed(text, cut, end);
+    }
+    pieces
+}
+
+/// Split `text` into chunks of whole sentences, each at most `options.max_chars`
+/// characters long. Sentences that don't fit on their own are split further.
+pub fn split_text(text: &str, options: &ChunkOptions) -> Vec<Chunk> {
+    let max_chars = options.max_chars.max(1);
+    let abbreviations = options.abbreviations();
+    let mut chunks = Vec::new();
+
+    for (paragraph, (p_start, p_end)) in paragraphs(text).into_iter().enumerate() {
+        let pieces = sentences(text, p_start, p_end, abbreviations)
+            .into_iter()
+            .flat_map(|(start, end)| split_long(text, start, end, max_chars));
+
+        let mut current: Option<(usize, usize)> = None;
+        for (start, end) in pieces {
+            current = match current {
+                Some((c_start, _)) if char_len(&text[c_start..end]) <= max_chars => Some((c_start, end)),
+                Some(full) => {
+                    chunks.push(full_chunk(text, chunks.len(), paragraph, full));
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        if let Some(last) = current {
+            chunks.push(full_chunk(text, chunks.len(), paragraph, last));
+        }
+    }
+    chunks
+}
+
+fn full_chunk(text: &str, index: usize, paragraph: usize, (start, end): (usize, usize)) -> Chunk {
+    Chunk { index, paragraph, start, end, text: text[start..end].to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn options(max_chars: usize, language: &str) -> ChunkOptions {
+        ChunkOptions { max_chars, language: Some(language.to_string()) }
+    }
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn packs_sentences_without_crossing_paragraphs() {
+        let text = "One. Two. Three is longer.\n\n  Four.\n";
+        let chunks = split_text(text, &options(16, "en"));
+        assert_eq!(texts(&chunks), vec!["One. Two.", "Three is longer.", "Four."]);
+        assert_eq!(chunks.iter().map(|c| c.paragraph).collect::<Vec<_>>(), vec![0, 0, 1]);
+        assert!(chunks.iter().all(|c| text[c.start..c.end] == c.text));
+    }
+
+    #[test]
+    fn keeps_abbreviations_and_initials_inside_sentences() {
+        let chunks = split_text("Dr. Smith met J. R. Doe. He left.", &options(30, "en"));
+        assert_eq!(texts(&chunks), vec!["Dr. Smith met J. R. Doe.", "He left."]);
+
+        let text = "Vgl. Kapitel drei. Gut.";
+        let count = |language| sentences(text, 0, text.len(), options(1, language).abbreviations()).len();
+        assert_eq!(count("de"), 2);
+        assert_eq!(count("en"), 3);
+    }
+
+    #[test]
+    fn splits_cjk_sentences_without_spaces() {
+        let chunks = split_text("今日は晴れです。明日は雨です。", &options(8, "ja"));
+        assert_eq!(texts(&chunks), vec!["今日は晴れです。", "明日は雨です。"]);
+    }
+
+    #[test]
+    fn breaks_long_sentences_at_clauses_then_words() {
+        let chunks = split_text("alpha beta, gamma delta epsilon", &options(12, "en"));
+        assert_eq!(texts(&chunks), vec!["alpha beta,", "gamma delta", "epsilon"]);
+
+        let chunks = split_text("abcdefghij", &options(4, "en"));
+        assert_eq!(texts(&chunks), vec!["abcd", "efgh", "ij"]);
+    }
+
+    proptest! {
+        #[test]
+        fn chunks_fit_and_lose_no_text(text in "[a-zé。 .,!?\n]{0,300}", max_chars in 1usize..40) {
+            let chunks = split_text(&text, &options(max_chars, "en"));
+            let mut previous_end = 0;
+            for chunk in &chunks {
+                prop_assert!(char_len(&chunk.text) <= max_chars, "{:?}", chunk);
+                prop_assert!(chunk.start >= previous_end);
+                prop_assert_eq!(&text[chunk.start..chunk.end], chunk.text.as_str());
+                previous_end = chunk.end;
+            }
+            let joined: String = chunks.iter().flat_map(|c| c.text.chars()).filter(|c| !c.is_whitespace()).collect();
+            let original: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+            prop_assert_eq!(joined, original);
+        }
+    }
+}
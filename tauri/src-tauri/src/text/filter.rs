@@ -0,0 +1,185 @@
+// Content filter for text from outside the app.
+//
+// Chat messages, remote commands, Home Assistant announcements and narrated files are
+// written by other people or programs, so they pass through this filter before they
+// are synthesized. Word lists per language and regular expressions find what to
+// filter; each source's policy says whether matches are replaced, removed, or the whole
+// text refused.
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Word list key applied whatever the language
+pub const ALL_LANGUAGES: &str = "*";
+/// Policy for sources without one of their own
+pub const DEFAULT_POLICY: &str = "default";
+
+/// What happens to filtered words
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    /// Speak the policy's `replacement` instead
+    #[default]
+    Replace,
+    /// Leave them out
+    Remove,
+    /// Refuse the whole text
+    Reject,
+}
+
+/// How text from one source is filtered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterPolicy {
+    pub enabled: bool,
+    pub action: FilterAction,
+    pub replacement: String,
+}
+
+impl Default for FilterPolicy {
+    fn default() -> Self {
+        Self { enabled: true, action: FilterAction::Replace, replacement: "beep".to_string() }
+    }
+}
+
+/// A regular expression filtered on top of the word lists
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterRule {
+    pub pattern: String,
+    /// Replaces matches whatever the policy's action; may refer to groups as `$1`
+    pub replacement: Option<String>,
+}
+
+/// The `content_filter` section of the launcher config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentFilterConfig {
+    /// Words filtered by language code, e.g. `en`; those under `*` in every language.
+    /// Whole words match, ignoring case.
+    pub wordlists: BTreeMap<String, Vec<String>>,
+    pub rules: Vec<FilterRule>,
+    /// Policies by source: `chat`, `remote`, `home_assistant`, `narration`, or `default`
+    pub policies: BTreeMap<String, FilterPolicy>,
+}
+
+/// Sources of outside text the filter knows policies for
+pub mod source {
+    pub const CHAT: &str = "chat";
+    pub const REMOTE: &str = "remote";
+    pub const HOME_ASSISTANT: &str = "home_assistant";
+    pub const NARRATION: &str = "narration";
+}
+
+/// A config compiled for filtering
+#[derive(Debug, Clone, Default)]
+pub struct ContentFilter {
+    wordlists: Vec<(String, Regex)>,
+    rules: Vec<(Regex, Option<String>)>,
+    policies: BTreeMap<String, FilterPolicy>,
+}
+
+impl ContentFilter {
+    pub fn new(config: &ContentFilterConfig) -> Result<Self, String> {
+        let wordlists = config
+            .wordlists
+            .iter()
+            .filter(|(_, words)| words.iter().any(|word| !word.trim().is_empty()))
+            .map(|(language, words)| {
+                let words: Vec<String> = words.iter().filter(|w| !w.trim().is_empty()).map(|w| regex::escape(w.trim())).collect();
+                let regex = Regex::new(&format!(r"(?i)\b(?:{})\b", words.join("|")))
+                    .map_err(|e| format!("Invalid word list '{}': {}", language, e))?;
+                Ok((language.to_lowercase(), regex))
+            })
+            .collect::<Result<_, String>>()?;
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern).map_err(|e| format!("Invalid filter pattern {:?}: {}", rule.pattern, e))?;
+                Ok((regex, rule.replacement.clone()))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { wordlists, rules, policies: config.policies.clone() })
+    }
+
+    fn policy(&self, source: &str) -> FilterPolicy {
+        self.policies.get(source).or_else(|| self.policies.get(DEFAULT_POLICY)).cloned().unwrap_or_default()
+    }
+
+    /// `text` from `source` as it may be spoken, checked against the word lists of
+    /// `language` (all lists when it isn't known). `Err` when the policy refuses it.
+    pub fn apply(&self, source: &str, language: Option<&str>, text: &str) -> Result<String, String> {
+        let policy = self.policy(source);
+        if !policy.enabled {
+            return Ok(text.to_string());
+        }
+        let language = language.map(|language| language.split(['_', '-']).next().unwrap_or(language).to_lowercase());
+        let lists = self.wordlists.iter().filter(|(list, _)| {
+            list == ALL_LANGUAGES || language.as_ref().is_none_or(|language| list == language)
+        });
+
+        let mut text = text.to_string();
+        for regex in lists.map(|(_, regex)| regex).chain(self.rules.iter().filter(|(_, r)| r.is_none()).map(|(regex, _)| regex)) {
+            if !regex.is_match(&text) {
+                continue;
+            }
+            text = match policy.action {
+                FilterAction::Reject => return Err(format!("Text from {} was refused by the content filter", source)),
+                FilterAction::Replace => regex.replace_all(&text, policy.replacement.as_str()).into_owned(),
+                FilterAction::Remove => regex.replace_all(&text, "").into_owned(),
+            };
+        }
+        for (regex, replacement) in &self.rules {
+            if let Some(replacement) = replacement {
+                text = regex.replace_all(&text, replacement.as_str()).into_owned();
+            }
+        }
+        Ok(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(policies: &[(&str, FilterPolicy)]) -> ContentFilter {
+        let config = ContentFilterConfig {
+            wordlists: BTreeMap::from([
+                ("en".to_string(), vec!["darn".to_string(), "heck".to_string()]),
+                ("de".to_string(), vec!["Mist".to_string()]),
+                (ALL_LANGUAGES.to_string(), vec!["spamlink.example".to_string()]),
+            ]),
+            rules: vec![
+                FilterRule { pattern: r"https?://\S+".to_string(), replacement: Some("a link".to_string()) },
+                FilterRule { pattern: r"(?i)\bf+u+\b".to_string(), replacement: None },
+            ],
+            policies: policies.iter().map(|(source, policy)| (source.to_string(), policy.clone())).collect(),
+        };
+        ContentFilter::new(&config).unwrap()
+    }
+
+    #[test]
+    fn replaces_words_of_the_text_language() {
+        let filter = filter(&[]);
+        assert_eq!(filter.apply(source::CHAT, Some("en-US"), "Darn it, what the HECK").unwrap(), "beep it, what the beep");
+        // `Mist` is only filtered in German; `darning` isn't a whole-word match
+        assert_eq!(filter.apply(source::CHAT, Some("en"), "Mist over the darning").unwrap(), "Mist over the darning");
+        assert_eq!(filter.apply(source::CHAT, Some("de"), "So ein Mist").unwrap(), "So ein beep");
+        assert_eq!(filter.apply(source::CHAT, None, "Mist, darn").unwrap(), "beep, beep");
+        assert_eq!(filter.apply(source::REMOTE, Some("en"), "see https://x.example/a and spamlink.example ffuu").unwrap(), "see a link and beep beep");
+    }
+
+    #[test]
+    fn applies_each_sources_policy() {
+        let remove = FilterPolicy { action: FilterAction::Remove, ..FilterPolicy::default() };
+        let reject = FilterPolicy { action: FilterAction::Reject, ..FilterPolicy::default() };
+        let off = FilterPolicy { enabled: false, ..FilterPolicy::default() };
+        let filter = filter(&[(DEFAULT_POLICY, remove), (source::CHAT, reject), (source::NARRATION, off)]);
+        assert!(filter.apply(source::CHAT, Some("en"), "oh heck").unwrap_err().contains("chat"));
+        assert_eq!(filter.apply(source::CHAT, Some("en"), "oh hi").unwrap(), "oh hi");
+        assert_eq!(filter.apply(source::HOME_ASSISTANT, Some("en"), "oh heck, the door").unwrap(), "oh , the door");
+        assert_eq!(filter.apply(source::NARRATION, Some("en"), "oh heck").unwrap(), "oh heck");
+        assert!(ContentFilter::new(&ContentFilterConfig { rules: vec![FilterRule { pattern: "(".to_string(), replacement: None }], ..Default::default() }).is_err());
+    }
+}
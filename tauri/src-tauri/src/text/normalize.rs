@@ -0,0 +1,784 @@
+use serde::{Deserialize, Serialize};
+
+/// A normalization pass, applied in the order listed here regardless of how a preset
+/// lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// Expand abbreviations such as "e.g." or "z.B."
+    Abbreviations,
+    /// Spell out numeric dates with month names
+    Dates,
+    /// Spell out unit symbols after numbers ("5 km")
+    Units,
+    /// Spell out numbers as words
+    Numbers,
+}
+
+/// Rule tables for one language
+struct Language {
+    code: &'static str,
+    months: [&'static str; 12],
+    /// Whether all-numeric dates are written day first
+    day_first: bool,
+    date_separator: char,
+    digit_grouping: &'static [char],
+    decimal_separator: char,
+    decimal_word: &'static str,
+    cardinal: fn(u64) -> String,
+    /// Date in words; `spell` also spells out day and year
+    date: fn(day: u32, month: &str, year: u64, spell: bool) -> String,
+    /// Symbol, singular, plural
+    units: &'static [(&'static str, &'static str, &'static str)],
+    abbreviations: &'static [(&'static str, &'static str)],
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        months: [
+            "January", "February", "March", "April", "May", "June", "July", "August", "September", "October",
+            "November", "December",
+        ],
+        day_first: false,
+        date_separator: '/',
+        digit_grouping: &[','],
+        decimal_separator: '.',
+        decimal_word: "point",
+        cardinal: en_cardinal,
+        date: |day, month, year, spell| match spell {
+            true => format!("{} {}, {}", month, en_ordinal(day), en_cardinal(year)),
+            false => format!("{} {}, {}", month, day, year),
+        },
+        units: &[
+            ("km/h", "kilometer per hour", "kilometers per hour"),
+            ("mph", "mile per hour", "miles per hour"),
+            ("km", "kilometer", "kilometers"),
+            ("cm", "centimeter", "centimeters"),
+            ("mm", "millimeter", "millimeters"),
+            ("m", "meter", "meters"),
+            ("kg", "kilogram", "kilograms"),
+            ("mg", "milligram", "milligrams"),
+            ("g", "gram", "grams"),
+            ("ml", "milliliter", "milliliters"),
+            ("l", "liter", "liters"),
+            ("ms", "millisecond", "milliseconds"),
+            ("min", "minute", "minutes"),
+            ("h", "hour", "hours"),
+            ("s", "second", "seconds"),
+            ("°C", "degree Celsius", "degrees Celsius"),
+            ("°F", "degree Fahrenheit", "degrees Fahrenheit"),
+            ("%", "percent", "percent"),
+            ("GB", "gigabyte", "gigabytes"),
+            ("MB", "megabyte", "megabytes"),
+            ("kHz", "kilohertz", "kilohertz"),
+            ("Hz", "hertz", "hertz"),
+            ("kW", "kilowatt", "kilowatts"),
+            ("W", "watt", "watts"),
+        ],
+        abbreviations: &[
+            ("e.g.", "for example"),
+            ("i.e.", "that is"),
+            ("etc.", "et cetera"),
+            ("vs.", "versus"),
+            ("approx.", "approximately"),
+            ("Dr.", "Doctor"),
+            ("Prof.", "Professor"),
+            ("Mr.", "Mister"),
+            ("Mrs.", "Missus"),
+        ],
+    },
+    Language {
+        code: "de",
+        months: [
+            "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober",
+            "November", "Dezember",
+        ],
+        day_first: true,
+        date_separator: '.',
+        digit_grouping: &['.'],
+        decimal_separator: ',',
+        decimal_word: "Komma",
+        cardinal: de_cardinal,
+        date: |day, month, year, spell| match spell {
+            true => format!("{}r {} {}", de_ordinal(day), month, de_cardinal(year)),
+            false => format!("{}. {} {}", day, month, year),
+        },
+        units: &[
+            ("km/h", "Kilometer pro Stunde", "Kilometer pro Stunde"),
+            ("km", "Kilometer", "Kilometer"),
+            ("cm", "Zentimeter", "Zentimeter"),
+            ("mm", "Millimeter", "Millimeter"),
+            ("m", "Meter", "Meter"),
+            ("kg", "Kilogramm", "Kilogramm"),
+            ("mg", "Milligramm", "Milligramm"),
+            ("g", "Gramm", "Gramm"),
+            ("ml", "Milliliter", "Milliliter"),
+            ("l", "Liter", "Liter"),
+            ("ms", "Millisekunde", "Millisekunden"),
+            ("min", "Minute", "Minuten"),
+            ("Std.", "Stunde", "Stunden"),
+            ("h", "Stunde", "Stunden"),
+            ("s", "Sekunde", "Sekunden"),
+            ("°C", "Grad Celsius", "Grad Celsius"),
+            ("%", "Prozent", "Prozent"),
+            ("GB", "Gigabyte", "Gigabyte"),
+            ("MB", "Megabyte", "Megabyte"),
+            ("kHz", "Kilohertz", "Kilohertz"),
+            ("Hz", "Hertz", "Hertz"),
+            ("kW", "Kilowatt", "Kilowatt"),
+            ("W", "Watt", "Watt"),
+        ],
+        abbreviations: &[
+            ("z.B.", "zum Beispiel"),
+            ("z. B.", "zum Beispiel"),
+            ("d.h.", "das heißt"),
+            ("u.a.", "unter anderem"),
+            ("usw.", "und so weiter"),
+            ("bzw.", "beziehungsweise"),
+            ("ca.", "circa"),
+            ("vgl.", "vergleiche"),
+            ("Nr.", "Nummer"),
+            ("Str.", "Straße"),
+            ("Dr.", "Doktor"),
+            ("Prof.", "Professor"),
+        ],
+    },
+    Language {
+        code: "fr",
+        months: [
+            "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre",
+            "novembre", "décembre",
+        ],
+        day_first: true,
+        date_separator: '/',
+        digit_grouping: &['\u{a0}', '\u{202f}'],
+        decimal_separator: ',',
+        decimal_word: "virgule",
+        cardinal: fr_cardinal,
+        date: |day, month, year, spell| match (spell, day) {
+            (true, 1) => format!("premier {} {}", month, fr_cardinal(year)),
+            (true, _) => format!("{} {} {}", fr_cardinal(day as u64), month, fr_cardinal(year)),
+            (false, _) => format!("{} {} {}", day, month, year),
+        },
+        units: &[
+            ("km/h", "kilomètre par heure", "kilomètres par heure"),
+            ("km", "kilomètre", "kilomètres"),
+            ("cm", "centimètre", "centimètres"),
+            ("mm", "millimètre", "millimètres"),
+            ("m", "mètre", "mètres"),
+            ("kg", "kilogramme", "kilogrammes"),
+            ("mg", "milligramme", "milligrammes"),
+            ("g", "gramme", "grammes"),
+            ("ml", "millilitre", "millilitres"),
+            ("l", "litre", "litres"),
+            ("ms", "milliseconde", "millisecondes"),
+            ("min", "minute", "minutes"),
+            ("h", "heure", "heures"),
+            ("s", "seconde", "secondes"),
+            ("°C", "degré Celsius", "degrés Celsius"),
+            ("%", "pour cent", "pour cent"),
+            ("Go", "gigaoctet", "gigaoctets"),
+            ("Mo", "mégaoctet", "mégaoctets"),
+        ],
+        abbreviations: &[
+            ("p.ex.", "par exemple"),
+            ("etc.", "et cetera"),
+            ("M.", "Monsieur"),
+            ("Mme", "Madame"),
+            ("Mlle", "Mademoiselle"),
+            ("Dr", "Docteur"),
+        ],
+    },
+    Language {
+        code: "es",
+        months: [
+            "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre",
+            "noviembre", "diciembre",
+        ],
+        day_first: true,
+        date_separator: '/',
+        digit_grouping: &['.'],
+        decimal_separator: ',',
+        decimal_word: "coma",
+        cardinal: es_cardinal,
+        date: |day, month, year, spell| match (spell, day) {
+            (true, 1) => format!("primero de {} de {}", month, es_cardinal(year)),
+            (true, _) => format!("{} de {} de {}", es_cardinal(day as u64), month, es_cardinal(year)),
+            (false, _) => format!("{} de {} de {}", day, month, year),
+        },
+        units: &[
+            ("km/h", "kilómetro por hora", "kilómetros por hora"),
+            ("km", "kilómetro", "kilómetros"),
+            ("cm", "centímetro", "centímetros"),
+            ("mm", "milímetro", "milímetros"),
+            ("m", "metro", "metros"),
+            ("kg", "kilogramo", "kilogramos"),
+            ("mg", "miligramo", "miligramos"),
+            ("g", "gramo", "gramos"),
+            ("ml", "mililitro", "mililitros"),
+            ("l", "litro", "litros"),
+            ("ms", "milisegundo", "milisegundos"),
+            ("min", "minuto", "minutos"),
+            ("h", "hora", "horas"),
+            ("s", "segundo", "segundos"),
+            ("°C", "grado Celsius", "grados Celsius"),
+            ("%", "por ciento", "por ciento"),
+            ("GB", "gigabyte", "gigabytes"),
+            ("MB", "megabyte", "megabytes"),
+        ],
+        abbreviations: &[
+            ("p.ej.", "por ejemplo"),
+            ("etc.", "etcétera"),
+            ("Srta.", "Señorita"),
+            ("Sra.", "Señora"),
+            ("Sr.", "Señor"),
+            ("Dra.", "Doctora"),
+            ("Dr.", "Doctor"),
+            ("Uds.", "ustedes"),
+            ("Ud.", "usted"),
+        ],
+    },
+];
+
+/// Numbers from here on are read digit by digit
+const MAX_SPELLED: u64 = 1_000_000_000_000;
+
+fn language(code: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|l| l.code == code)
+}
+
+/// Whether there are rule tables for `language`
+pub fn supports_language(language: &str) -> bool {
+    self::language(language).is_some()
+}
+
+/// Apply `rules` to `text` using the tables for `language`. Text in languages without
+/// tables is returned unchanged.
+pub fn normalize(text: &str, language: &str, rules: &[Rule]) -> String {
+    let Some(lang) = self::language(language) else {
+        return text.to_string();
+    };
+    let mut text = text.to_string();
+    if rules.contains(&Rule::Abbreviations) {
+        text = expand_abbreviations(&text, lang);
+    }
+    let numbers = rules.contains(&Rule::Numbers);
+    if rules.contains(&Rule::Dates) {
+        text = expand_dates(&text, lang, numbers);
+    }
+    if numbers || rules.contains(&Rule::Units) {
+        text = expand_numbers(&text, lang, numbers, rules.contains(&Rule::Units));
+    }
+    text
+}
+
+fn starts_with_at(chars: &[char], at: usize, pattern: &str) -> bool {
+    (at..).zip(pattern.chars()).all(|(i, p)| chars.get(i) == Some(&p))
+}
+
+fn is_word_char(c: Option<&char>) -> bool {
+    c.is_some_and(|c| c.is_alphanumeric())
+}
+
+fn expand_abbreviations(text: &str, lang: &Language) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    'outer: while i < chars.len() {
+        if i == 0 || (!is_word_char(chars.get(i - 1)) && chars[i - 1] != '.') {
+            for (abbreviation, expansion) in lang.abbreviations {
+                let end = i + abbreviation.chars().count();
+                if starts_with_at(&chars, i, abbreviation) && !is_word_char(chars.get(end)) {
+                    out.push_str(expansion);
+                    // Keep the full stop of an abbreviation that ends the text or a line
+                    if abbreviation.ends_with('.') && chars.get(end).is_none_or(|&c| c == '\n') {
+                        out.push('.');
+                    }
+                    i = end;
+                    continue 'outer;
+                }
+            }
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Digits at `at`, if there are between `min` and `max` of them
+fn digits_at(chars: &[char], at: usize, min: usize, max: usize) -> Option<(u64, usize)> {
+    let len = chars[at.min(chars.len())..].iter().take_while(|c| c.is_ascii_digit()).count();
+    if len < min || len > max {
+        return None;
+    }
+    let value = chars[at..at + len].iter().collect::<String>().parse().ok()?;
+    Some((value, at + len))
+}
+
+/// Parse an ISO (`2024-03-05`) or localized all-numeric date at `at`
+fn date_at(chars: &[char], at: usize, lang: &Language) -> Option<(u32, u32, u64, usize)> {
+    let iso = || {
+        let (year, i) = digits_at(chars, at, 4, 4)?;
+        let (month, i) = (chars.get(i) == Some(&'-')).then(|| digits_at(chars, i + 1, 2, 2))??;
+        let (day, i) = (chars.get(i) == Some(&'-')).then(|| digits_at(chars, i + 1, 2, 2))??;
+        Some((day, month, year, i))
+    };
+    let local = || {
+        let sep = Some(&lang.date_separator);
+        let (first, i) = digits_at(chars, at, 1, 2)?;
+        let (second, i) = (chars.get(i) == sep).then(|| digits_at(chars, i + 1, 1, 2))??;
+        let (year, i) = (chars.get(i) == sep).then(|| digits_at(chars, i + 1, 4, 4))??;
+        let (day, month) = if lang.day_first { (first, second) } else { (second, first) };
+        Some((day, month, year, i))
+    };
+    let (day, month, year, end) = iso().or_else(local)?;
+    let valid = (1..=31).contains(&day) && (1..=12).contains(&month) && !is_word_char(chars.get(end));
+    valid.then_some((day as u32, month as u32, year, end))
+}
+
+fn expand_dates(text: &str, lang: &Language, spell: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_ascii_digit() && (i == 0 || !is_word_char(chars.get(i - 1))) {
+            if let Some((day, month, year, end)) = date_at(&chars, i, lang) {
+                out.push_str(&(lang.date)(day, lang.months[month as usize - 1], year, spell));
+                i = end;
+                continue;
+            }
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Integer digits (grouping removed) and fraction digits of the number at `at`
+fn number_at(chars: &[char], at: usize, lang: &Language) -> (String, Option<String>, usize) {
+    let mut integer: String = chars[at..].iter().take_while(|c| c.is_ascii_digit()).collect();
+    let mut i = at + integer.len();
+    if integer.len() <= 3 {
+        while chars.get(i).is_some_and(|c| lang.digit_grouping.contains(c))
+            && digits_at(chars, i + 1, 3, 3).is_some()
+        {
+            integer.extend(&chars[i + 1..i + 4]);
+            i += 4;
+        }
+    }
+    let mut fraction = None;
+    if chars.get(i) == Some(&lang.decimal_separator) && chars.get(i + 1).is_some_and(char::is_ascii_digit) {
+        let digits: String = chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).collect();
+        i += 1 + digits.len();
+        fraction = Some(digits);
+    }
+    (integer, fraction, i)
+}
+
+/// Unit symbol after a number ending at `at`, allowing one space in between
+fn unit_at(chars: &[char], at: usize, lang: &Language) -> Option<(&'static str, &'static str, usize)> {
+    let start = if chars.get(at).is_some_and(|&c| c == ' ' || c == '\u{a0}') { at + 1 } else { at };
+    lang.units.iter().find_map(|(symbol, singular, plural)| {
+        let end = start + symbol.chars().count();
+        let fits = starts_with_at(chars, start, symbol) && !is_word_char(chars.get(end));
+        fits.then_some((*singular, *plural, end))
+    })
+}
+
+fn spell_digits(digits: &str, lang: &Language) -> String {
+    digits
+        .chars()
+        .filter_map(|d| d.to_digit(10))
+        .map(|d| (lang.cardinal)(d as u64))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn spell_number(integer: &str, fraction: Option<&str>, lang: &Language) -> String {
+    let mut words = match integer.parse::<u64>() {
+        Ok(n) if n < MAX_SPELLED && !(integer.len() > 1 && integer.starts_with('0')) => (lang.cardinal)(n),
+        _ => spell_digits(integer, lang),
+    };
+    if let Some(fraction) = fraction {
+        words.push(' ');
+        words.push_str(lang.decimal_word);
+        words.push(' ');
+        words.push_str(&spell_digits(fraction, lang));
+    }
+    words
+}
+
+fn expand_numbers(text: &str, lang: &Language, numbers: bool, units: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() || (i > 0 && is_word_char(chars.get(i - 1))) {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let (integer, fraction, end) = number_at(&chars, i, lang);
+        let unit = if units { unit_at(&chars, end, lang) } else { None };
+        // Digits that are part of a word ("3D", "4K") are left alone
+        if unit.is_none() && is_word_char(chars.get(end)) {
+            out.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+        if numbers {
+            out.push_str(&spell_number(&integer, fraction.as_deref(), lang));
+        } else {
+            out.extend(&chars[i..end]);
+        }
+        i = end;
+        if let Some((singular, plural, unit_end)) = unit {
+            let one = integer == "1" && fraction.is_none();
+            out.push(' ');
+            out.push_str(if one { singular } else { plural });
+            i = unit_end;
+        }
+    }
+    out
+}
+
+const EN_ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve",
+    "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const EN_TENS: [&str; 10] = ["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+fn en_below_1000(n: u64) -> String {
+    let mut parts = Vec::new();
+    if n >= 100 {
+        parts.push(format!("{} hundred", EN_ONES[(n / 100) as usize]));
+    }
+    let rest = (n % 100) as usize;
+    if rest > 0 || n == 0 {
+        parts.push(match rest {
+            0..=19 => EN_ONES[rest].to_string(),
+            _ if rest.is_multiple_of(10) => EN_TENS[rest / 10].to_string(),
+            _ => format!("{}-{}", EN_TENS[rest / 10], EN_ONES[rest % 10]),
+        });
+    }
+    parts.join(" ")
+}
+
+fn en_cardinal(n: u64) -> String {
+    if n == 0 {
+        return EN_ONES[0].to_string();
+    }
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for (scale, name) in [(1_000_000_000, "billion"), (1_000_000, "million"), (1_000, "thousand")] {
+        if rest >= scale {
+            parts.push(format!("{} {}", en_below_1000(rest / scale), name));
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        parts.push(en_below_1000(rest));
+    }
+    parts.join(" ")
+}
+
+fn en_ordinal(n: u32) -> String {
+    let cardinal = en_cardinal(n as u64);
+    let split = cardinal.rfind(['-', ' ']).map_or(0, |i| i + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        _ if last.ends_with('y') => format!("{}ieth", &last[..last.len() - 1]),
+        _ => format!("{}th", last),
+    };
+    format!("{}{}", head, last)
+}
+
+const DE_ONES: [&str; 20] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun", "zehn", "elf", "zwölf",
+    "dreizehn", "vierzehn", "fünfzehn", "sechzehn", "siebzehn", "achtzehn", "neunzehn",
+];
+const DE_TENS: [&str; 10] =
+    ["", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig"];
+
+/// "ein" instead of "eins" in compounds
+fn de_prefix(n: u64) -> &'static str {
+    if n == 1 {
+        "ein"
+    } else {
+        DE_ONES[n as usize]
+    }
+}
+
+fn de_below_1000(n: u64) -> String {
+    let mut s = String::new();
+    if n >= 100 {
+        s.push_str(de_prefix(n / 100));
+        s.push_str("hundert");
+    }
+    let rest = n % 100;
+    match rest {
+        0 => {}
+        1..=19 => s.push_str(DE_ONES[rest as usize]),
+        _ => {
+            if !rest.is_multiple_of(10) {
+                s.push_str(de_prefix(rest % 10));
+                s.push_str("und");
+            }
+            s.push_str(DE_TENS[(rest / 10) as usize]);
+        }
+    }
+    s
+}
+
+fn de_cardinal(n: u64) -> String {
+    if n == 0 {
+        return DE_ONES[0].to_string();
+    }
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for (scale, singular, plural) in [(1_000_000_000, "eine Milliarde", "Milliarden"), (1_000_000, "eine Million", "Millionen")] {
+        if rest >= scale {
+            let count = rest / scale;
+            parts.push(match count {
+                1 => singular.to_string(),
+                _ => format!("{} {}", de_below_1000(count), plural),
+            });
+            rest %= scale;
+        }
+    }
+    let mut tail = String::new();
+    if rest >= 1000 {
+        let thousands = de_below_1000(rest / 1000);
+        match thousands.strip_suffix("eins") {
+            Some(head) => tail.push_str(&format!("{}ein", head)),
+            None => tail.push_str(&thousands),
+        }
+        tail.push_str("tausend");
+        rest %= 1000;
+    }
+    if rest > 0 {
+        tail.push_str(&de_below_1000(rest));
+    }
+    if !tail.is_empty() {
+        parts.push(tail);
+    }
+    parts.join(" ")
+}
+
+fn de_ordinal(n: u32) -> String {
+    match n {
+        1 => "erste".to_string(),
+        3 => "dritte".to_string(),
+        7 => "siebte".to_string(),
+        8 => "achte".to_string(),
+        2..=19 => format!("{}te", de_cardinal(n as u64)),
+        _ => format!("{}ste", de_cardinal(n as u64)),
+    }
+}
+
+const FR_ONES: [&str; 17] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf", "dix", "onze", "douze", "treize",
+    "quatorze", "quinze", "seize",
+];
+
+fn fr_below_100(n: u64) -> String {
+    match n {
+        0..=16 => FR_ONES[n as usize].to_string(),
+        17..=19 => format!("dix-{}", FR_ONES[(n - 10) as usize]),
+        20..=69 => {
+            let tens = ["vingt", "trente", "quarante", "cinquante", "soixante"][(n / 10 - 2) as usize];
+            match n % 10 {
+                0 => tens.to_string(),
+                1 => format!("{} et un", tens),
+                d => format!("{}-{}", tens, FR_ONES[d as usize]),
+            }
+        }
+        71 => "soixante et onze".to_string(),
+        70..=79 => format!("soixante-{}", fr_below_100(n - 60)),
+        80 => "quatre-vingts".to_string(),
+        _ => format!("quatre-vingt-{}", fr_below_100(n - 80)),
+    }
+}
+
+/// `last` is false when a scale word follows, which drops the plural of "cents"
+fn fr_below_1000(n: u64, last: bool) -> String {
+    let mut parts = Vec::new();
+    let (hundreds, rest) = (n / 100, n % 100);
+    match hundreds {
+        0 => {}
+        1 => parts.push("cent".to_string()),
+        _ => parts.push(format!("{} {}", FR_ONES[hundreds as usize], if rest == 0 && last { "cents" } else { "cent" })),
+    }
+    if rest > 0 || n == 0 {
+        parts.push(fr_below_100(rest));
+    }
+    parts.join(" ")
+}
+
+fn fr_cardinal(n: u64) -> String {
+    if n == 0 {
+        return FR_ONES[0].to_string();
+    }
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for (scale, name) in [(1_000_000_000, "milliard"), (1_000_000, "million")] {
+        if rest >= scale {
+            let count = rest / scale;
+            parts.push(format!("{} {}{}", fr_below_1000(count, false), name, if count > 1 { "s" } else { "" }));
+            rest %= scale;
+        }
+    }
+    if rest >= 1000 {
+        match rest / 1000 {
+            1 => parts.push("mille".to_string()),
+            count => parts.push(format!("{} mille", fr_below_1000(count, false))),
+        }
+        rest %= 1000;
+    }
+    if rest > 0 {
+        parts.push(fr_below_1000(rest, true));
+    }
+    parts.join(" ")
+}
+
+const ES_BELOW_30: [&str; 30] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve", "diez", "once", "doce",
+    "trece", "catorce", "quince", "dieciséis", "diecisiete", "dieciocho", "diecinueve", "veinte", "veintiuno",
+    "veintidós", "veintitrés", "veinticuatro", "veinticinco", "veintiséis", "veintisiete", "veintiocho",
+    "veintinueve",
+];
+const ES_TENS: [&str; 10] =
+    ["", "", "", "treinta", "cuarenta", "cincuenta", "sesenta", "setenta", "ochenta", "noventa"];
+const ES_HUNDREDS: [&str; 10] = [
+    "", "ciento", "doscientos", "trescientos", "cuatrocientos", "quinientos", "seiscientos", "setecientos",
+    "ochocientos", "novecientos",
+];
+
+fn es_below_1000(n: u64) -> String {
+    if n == 100 {
+        return "cien".to_string();
+    }
+    let mut parts = Vec::new();
+    if n >= 100 {
+        parts.push(ES_HUNDREDS[(n / 100) as usize].to_string());
+    }
+    let rest = n % 100;
+    match rest {
+        0 if n > 0 => {}
+        0..=29 => parts.push(ES_BELOW_30[rest as usize].to_string()),
+        _ if rest.is_multiple_of(10) => parts.push(ES_TENS[(rest / 10) as usize].to_string()),
+        _ => parts.push(format!("{} y {}", ES_TENS[(rest / 10) as usize], ES_BELOW_30[(rest % 10) as usize])),
+    }
+    parts.join(" ")
+}
+
+/// "un"/"veintiún" instead of "uno"/"veintiuno" before a noun or scale word
+fn es_apocope(words: String) -> String {
+    if let Some(head) = words.strip_suffix("veintiuno") {
+        format!("{}veintiún", head)
+    } else if let Some(head) = words.strip_suffix("uno") {
+        format!("{}un", head)
+    } else {
+        words
+    }
+}
+
+fn es_below_million(n: u64) -> String {
+    let mut parts = Vec::new();
+    match n / 1000 {
+        0 => {}
+        1 => parts.push("mil".to_string()),
+        count => parts.push(format!("{} mil", es_apocope(es_below_1000(count)))),
+    }
+    if !n.is_multiple_of(1000) || n == 0 {
+        parts.push(es_below_1000(n % 1000));
+    }
+    parts.join(" ")
+}
+
+fn es_cardinal(n: u64) -> String {
+    let mut parts = Vec::new();
+    match n / 1_000_000 {
+        0 => {}
+        1 => parts.push("un millón".to_string()),
+        count => parts.push(format!("{} millones", es_apocope(es_below_million(count)))),
+    }
+    if !n.is_multiple_of(1_000_000) || n == 0 {
+        parts.push(es_below_million(n % 1_000_000));
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: &[Rule] = &[Rule::Abbreviations, Rule::Dates, Rule::Units, Rule::Numbers];
+
+    #[test]
+    fn spells_cardinals_per_language() {
+        type Cardinal = fn(u64) -> String;
+        let cases: &[(Cardinal, u64, &str)] = &[
+            (en_cardinal, 0, "zero"),
+            (en_cardinal, 21, "twenty-one"),
+            (en_cardinal, 105, "one hundred five"),
+            (en_cardinal, 2_000_340, "two million three hundred forty"),
+            (de_cardinal, 21, "einundzwanzig"),
+            (de_cardinal, 101, "einhunderteins"),
+            (de_cardinal, 1_234, "eintausendzweihundertvierunddreißig"),
+            (de_cardinal, 2_000_001, "zwei Millionen eins"),
+            (fr_cardinal, 71, "soixante et onze"),
+            (fr_cardinal, 80, "quatre-vingts"),
+            (fr_cardinal, 97, "quatre-vingt-dix-sept"),
+            (fr_cardinal, 200, "deux cents"),
+            (fr_cardinal, 200_000, "deux cent mille"),
+            (es_cardinal, 100, "cien"),
+            (es_cardinal, 115, "ciento quince"),
+            (es_cardinal, 21_000, "veintiún mil"),
+            (es_cardinal, 1_000_000, "un millón"),
+        ];
+        for (cardinal, n, expected) in cases {
+            assert_eq!(cardinal(*n), *expected);
+        }
+        assert_eq!(en_ordinal(22), "twenty-second");
+        assert_eq!(en_ordinal(30), "thirtieth");
+    }
+
+    #[test]
+    fn normalizes_numbers_units_and_abbreviations() {
+        assert_eq!(
+            normalize("e.g. 1,250 kg, 1 km and 3.5% etc.", "en", ALL),
+            "for example one thousand two hundred fifty kilograms, one kilometer and three point five percent et cetera."
+        );
+        assert_eq!(
+            normalize("z.B. 1.000 m in 2,5 h", "de", ALL),
+            "zum Beispiel eintausend Meter in zwei Komma fünf Stunden"
+        );
+        // Only the listed rules run
+        assert_eq!(normalize("e.g. 5 km", "en", &[Rule::Units]), "e.g. 5 kilometers");
+    }
+
+    #[test]
+    fn spells_dates_in_the_local_order() {
+        assert_eq!(normalize("On 3/5/2024.", "en", &[Rule::Dates]), "On March 5, 2024.");
+        assert_eq!(normalize("2024-03-05", "en", ALL), "March fifth, two thousand twenty-four");
+        assert_eq!(normalize("Am 3.5.2024", "de", &[Rule::Dates]), "Am 3. Mai 2024");
+        assert_eq!(normalize("1/5/2024", "es", ALL), "primero de mayo de dos mil veinticuatro");
+        assert_eq!(normalize("13/13/2024", "fr", &[Rule::Dates]), "13/13/2024");
+    }
+
+    #[test]
+    fn leaves_words_with_digits_and_unknown_languages_alone() {
+        assert_eq!(normalize("mp3 in 4K at 007", "en", ALL), "mp3 in 4K at zero zero seven");
+        assert_eq!(normalize("5 km", "ja", ALL), "5 km");
+        assert!(supports_language("de") && !supports_language("ja"));
+    }
+}